@@ -0,0 +1,3 @@
+-- Add role field for admin access control
+-- Existing users default to the regular 'user' role
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user';
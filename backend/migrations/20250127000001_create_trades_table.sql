@@ -0,0 +1,41 @@
+-- Normalize trade history out of the users.trade_history JSON blob
+CREATE TABLE IF NOT EXISTS trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL,
+    transaction_type TEXT NOT NULL DEFAULT 'Trade',
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL DEFAULT 'USD',
+    side TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    price REAL NOT NULL,
+    timestamp TEXT NOT NULL,
+    base_usd_price REAL,
+    quote_usd_price REAL,
+    executed_by_bot TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_user_id ON trades(user_id, id);
+CREATE INDEX IF NOT EXISTS idx_trades_timestamp ON trades(timestamp);
+
+-- Migrate existing JSON blobs (old trades may use "asset" instead of "base_asset")
+INSERT INTO trades (
+    user_id, transaction_type, base_asset, quote_asset, side, quantity, price,
+    timestamp, base_usd_price, quote_usd_price, executed_by_bot
+)
+SELECT
+    u.user_id,
+    COALESCE(json_extract(t.value, '$.transaction_type'), 'Trade'),
+    COALESCE(json_extract(t.value, '$.base_asset'), json_extract(t.value, '$.asset')),
+    COALESCE(json_extract(t.value, '$.quote_asset'), 'USD'),
+    json_extract(t.value, '$.side'),
+    json_extract(t.value, '$.quantity'),
+    json_extract(t.value, '$.price'),
+    json_extract(t.value, '$.timestamp'),
+    json_extract(t.value, '$.base_usd_price'),
+    json_extract(t.value, '$.quote_usd_price'),
+    json_extract(t.value, '$.executed_by_bot')
+FROM users u, json_each(COALESCE(u.trade_history, '[]')) t
+ORDER BY u.user_id, t.key;
+
+-- The blob is no longer the source of truth
+UPDATE users SET trade_history = '[]';
@@ -0,0 +1,11 @@
+-- Active bot configurations, restored and respawned on startup
+CREATE TABLE IF NOT EXISTS bots (
+    user_id TEXT PRIMARY KEY NOT NULL,   -- One bot per user
+    strategy TEXT NOT NULL,              -- Registry key, e.g. "naive_momentum"
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL,
+    parameters TEXT NOT NULL DEFAULT '{}',
+    stoploss_amount REAL NOT NULL,
+    initial_portfolio_value_usd REAL NOT NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
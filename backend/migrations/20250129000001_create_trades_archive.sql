@@ -0,0 +1,18 @@
+-- Trades moved out of the hot table by the retention job (RETENTION_TRADE_ARCHIVE_MONTHS)
+CREATE TABLE IF NOT EXISTS trades_archive (
+    id INTEGER PRIMARY KEY NOT NULL,     -- Original trades.id
+    user_id TEXT NOT NULL,
+    transaction_type TEXT NOT NULL DEFAULT 'Trade',
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL DEFAULT 'USD',
+    side TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    price REAL NOT NULL,
+    timestamp TEXT NOT NULL,
+    base_usd_price REAL,
+    quote_usd_price REAL,
+    executed_by_bot TEXT,
+    archived_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_archive_user_id ON trades_archive(user_id, id);
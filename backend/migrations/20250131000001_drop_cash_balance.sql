@@ -0,0 +1,7 @@
+-- Move the legacy cash_balance into asset_balances["USD"] for users that never had a USD entry,
+-- then drop the column so balances have a single source of truth
+UPDATE users
+SET asset_balances = json_set(asset_balances, '$.USD', cash_balance)
+WHERE json_extract(asset_balances, '$.USD') IS NULL;
+
+ALTER TABLE users DROP COLUMN cash_balance;
@@ -0,0 +1,12 @@
+-- Server-side login sessions; the client holds the token, only its SHA-256 hash is stored
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY NOT NULL,        -- Public id used to list/revoke a session
+    user_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    user_agent TEXT,
+    ip_address TEXT,
+    created_at BIGINT NOT NULL,          -- Unix seconds
+    expires_at BIGINT NOT NULL           -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON sessions(user_id);
@@ -0,0 +1,29 @@
+-- User-defined price alerts, checked against every incoming price until they fire
+CREATE TABLE IF NOT EXISTS price_alerts (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    condition TEXT NOT NULL,         -- "above", "below" or "change_pct"
+    threshold REAL NOT NULL,         -- USD price, or percent change for change_pct (negative = drop)
+    window_minutes BIGINT,           -- Lookback for change_pct
+    created_at BIGINT NOT NULL,      -- Unix seconds
+    triggered_at BIGINT              -- Unix seconds; NULL while armed
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_alerts_user_id ON price_alerts(user_id);
+CREATE INDEX IF NOT EXISTS idx_price_alerts_asset ON price_alerts(asset, triggered_at);
+
+-- Every time an alert fired, with the price that fired it
+CREATE TABLE IF NOT EXISTS alert_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    alert_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    threshold REAL NOT NULL,
+    price REAL NOT NULL,
+    change_pct REAL,                 -- Observed change, for change_pct alerts
+    triggered_at BIGINT NOT NULL     -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_history_user_id ON alert_history(user_id, id);
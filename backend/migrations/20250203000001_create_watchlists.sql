@@ -0,0 +1,11 @@
+-- Named lists of assets a user follows; every listed asset is polled for prices
+CREATE TABLE IF NOT EXISTS watchlists (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    assets TEXT NOT NULL DEFAULT '[]', -- JSON array of asset symbols
+    created_at BIGINT NOT NULL,        -- Unix seconds
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_watchlists_user_id ON watchlists(user_id);
@@ -0,0 +1,6 @@
+-- Per-user secret authenticating TradingView-style webhook alerts (stored hashed, like session tokens)
+CREATE TABLE IF NOT EXISTS webhook_secrets (
+    user_id TEXT PRIMARY KEY NOT NULL,
+    secret_hash TEXT NOT NULL UNIQUE,
+    created_at BIGINT NOT NULL -- Unix seconds
+);
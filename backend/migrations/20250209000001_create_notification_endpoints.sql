@@ -0,0 +1,26 @@
+-- Where a user's notifications are pushed, besides their event stream
+CREATE TABLE IF NOT EXISTS notification_endpoints (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    channel TEXT NOT NULL,           -- "webhook"
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,            -- Signs webhook payloads (HMAC-SHA256); kept in the clear to sign with
+    created_at BIGINT NOT NULL       -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_endpoints_user_id ON notification_endpoints(user_id);
+
+-- The outcome of pushing one notification to one endpoint, after any retries
+CREATE TABLE IF NOT EXISTS notification_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    endpoint_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    event TEXT NOT NULL,             -- Notification kind, e.g. "price_alert"
+    status TEXT NOT NULL,            -- "delivered" or "failed"
+    attempts BIGINT NOT NULL,
+    response_status BIGINT,          -- HTTP status of the last attempt, if it got a response
+    error TEXT,                      -- Why the last attempt failed
+    created_at BIGINT NOT NULL       -- Unix seconds, when the notification was raised
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_deliveries_user_id ON notification_deliveries(user_id, id);
@@ -0,0 +1,43 @@
+-- Paper-trading competitions: every participant starts with the same balance, apart from their own account
+CREATE TABLE IF NOT EXISTS competitions (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    starting_balance REAL NOT NULL,  -- USD each participant gets
+    starts_at BIGINT NOT NULL,       -- Unix seconds
+    ends_at BIGINT NOT NULL,         -- Unix seconds
+    created_by TEXT NOT NULL,        -- Admin user id
+    created_at BIGINT NOT NULL,      -- Unix seconds
+    finalized_at BIGINT              -- Unix seconds, once final standings were recorded
+);
+
+CREATE INDEX IF NOT EXISTS idx_competitions_ends_at ON competitions(ends_at);
+
+-- A participant's competition portfolio
+CREATE TABLE IF NOT EXISTS competition_entries (
+    competition_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    asset_balances TEXT NOT NULL,    -- JSON object, like users.asset_balances
+    version BIGINT NOT NULL,         -- Bumped on every trade, so concurrent trades can't both spend a balance
+    trade_count BIGINT NOT NULL,
+    joined_at BIGINT NOT NULL,       -- Unix seconds
+    final_value REAL,                -- USD value when the competition was finalized
+    final_rank BIGINT,
+    PRIMARY KEY (competition_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_competition_entries_user_id ON competition_entries(user_id);
+
+CREATE TABLE IF NOT EXISTS competition_trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    competition_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL,
+    side TEXT NOT NULL,              -- "Buy" or "Sell"
+    quantity REAL NOT NULL,
+    price REAL NOT NULL,             -- In quote asset terms
+    timestamp BIGINT NOT NULL        -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_competition_trades_entry ON competition_trades(competition_id, user_id, id);
@@ -0,0 +1,8 @@
+-- Badges users have earned; each is awarded at most once
+CREATE TABLE IF NOT EXISTS user_achievements (
+    user_id TEXT NOT NULL,
+    achievement TEXT NOT NULL,       -- Catalog id, e.g. "first_trade"
+    earned_at BIGINT NOT NULL,       -- Unix seconds
+    details TEXT NOT NULL,           -- What earned it, e.g. "Return reached 12.40%"
+    PRIMARY KEY (user_id, achievement)
+);
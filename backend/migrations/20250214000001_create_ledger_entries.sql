@@ -0,0 +1,15 @@
+-- Append-only ledger: one row per credit (positive) or debit (negative) of one asset
+-- Each user's rows are hash-chained, so editing or removing one breaks every later hash
+CREATE TABLE IF NOT EXISTS ledger_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL,
+    asset TEXT NOT NULL,
+    amount REAL NOT NULL,
+    balance REAL NOT NULL,            -- The asset's balance after this entry
+    kind TEXT NOT NULL,               -- "opening", "trade", "deposit", "withdrawal" or "reset"
+    trade_id BIGINT,                  -- The trades row it belongs to; NULL for openings and resets
+    created_at BIGINT NOT NULL,       -- Unix seconds
+    hash TEXT NOT NULL                -- Hex SHA-256 of the previous entry's hash and this entry
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_user_id ON ledger_entries(user_id, id);
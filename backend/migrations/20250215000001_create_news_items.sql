@@ -0,0 +1,12 @@
+-- Headlines from the news feed, shown alongside the price chart
+CREATE TABLE IF NOT EXISTS news_items (
+    id TEXT PRIMARY KEY,             -- Source-prefixed, e.g. "cryptopanic:123"
+    title TEXT NOT NULL,
+    url TEXT NOT NULL,
+    source TEXT NOT NULL,            -- Publisher, e.g. "CoinDesk"
+    assets TEXT NOT NULL,            -- Comma-delimited with outer commas, e.g. ",BTC,ETH,"
+    published_at BIGINT NOT NULL,    -- Unix seconds
+    fetched_at BIGINT NOT NULL       -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_news_items_published_at ON news_items(published_at);
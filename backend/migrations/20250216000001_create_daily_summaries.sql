@@ -0,0 +1,9 @@
+-- Daily portfolio summaries, compiled at each user's chosen local time
+CREATE TABLE IF NOT EXISTS daily_summaries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL,
+    date TEXT NOT NULL,              -- The user's local date, "YYYY-MM-DD"
+    summary TEXT NOT NULL,           -- JSON: P&L, trades, bot activity and biggest movers
+    created_at BIGINT NOT NULL,      -- Unix seconds
+    UNIQUE (user_id, date)
+);
@@ -0,0 +1,25 @@
+-- Recurring competitions: each season of a league is a competition its members are entered in automatically
+CREATE TABLE IF NOT EXISTS leagues (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    season_length TEXT NOT NULL,     -- "week" or "month" (UTC calendar)
+    starting_balance REAL NOT NULL,  -- USD each member starts every season with
+    created_by TEXT NOT NULL,        -- Admin user id
+    created_at BIGINT NOT NULL       -- Unix seconds
+);
+
+-- Users opted in to a league's seasons
+CREATE TABLE IF NOT EXISTS league_members (
+    league_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    joined_at BIGINT NOT NULL,       -- Unix seconds
+    PRIMARY KEY (league_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_league_members_user_id ON league_members(user_id);
+
+-- The league a competition is a season of
+ALTER TABLE competitions ADD COLUMN league_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_competitions_league_id ON competitions(league_id, starts_at);
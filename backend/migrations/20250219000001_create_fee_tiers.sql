@@ -0,0 +1,7 @@
+-- Each user's fee tier, recalculated daily from their trailing 30-day traded volume
+CREATE TABLE IF NOT EXISTS fee_tiers (
+    user_id TEXT PRIMARY KEY NOT NULL,
+    tier TEXT NOT NULL,                  -- Tier name; its rates come from the schedule in fee_service
+    volume_30d_usd REAL NOT NULL,
+    calculated_at BIGINT NOT NULL        -- Unix seconds
+);
@@ -0,0 +1,26 @@
+-- Key bots by a generated instance id instead of the user, so a user can run several at once
+CREATE TABLE bots_by_instance (
+    id TEXT PRIMARY KEY NOT NULL,        -- bot_instance_id returned by POST /api/bot/start
+    user_id TEXT NOT NULL,
+    strategy TEXT NOT NULL,              -- Registry key, e.g. "naive_momentum"
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL,
+    parameters TEXT NOT NULL DEFAULT '{}',
+    stoploss_amount REAL NOT NULL,
+    initial_portfolio_value_usd REAL NOT NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO bots_by_instance (
+    id, user_id, strategy, base_asset, quote_asset, parameters,
+    stoploss_amount, initial_portfolio_value_usd, started_at
+)
+SELECT
+    lower(hex(randomblob(16))), user_id, strategy, base_asset, quote_asset, parameters,
+    stoploss_amount, initial_portfolio_value_usd, started_at
+FROM bots;
+
+DROP TABLE bots;
+ALTER TABLE bots_by_instance RENAME TO bots;
+
+CREATE INDEX IF NOT EXISTS idx_bots_user_id ON bots(user_id);
@@ -0,0 +1,15 @@
+-- Every decision live bots make, one row per tick, so users can audit why their bot traded
+CREATE TABLE IF NOT EXISTS bot_decisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id TEXT NOT NULL,
+    bot_id TEXT NOT NULL,                -- bots.id; kept after the bot stops
+    bot_name TEXT NOT NULL,
+    tick BIGINT NOT NULL,
+    price REAL NOT NULL,                 -- Pair price the bot decided at
+    decision TEXT NOT NULL,              -- BotDecision as JSON, e.g. {"action":"buy","quote_amount":100}
+    outcome TEXT NOT NULL,               -- "executed", "no_action", "insufficient_funds" or "error"
+    trade_id BIGINT,                     -- trades.id of the resulting fill, if any
+    decided_at BIGINT NOT NULL           -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_bot_decisions_user_id ON bot_decisions(user_id, id);
@@ -0,0 +1,2 @@
+-- Dry-run bots log their decisions without trading
+ALTER TABLE bots ADD COLUMN dry_run BIGINT NOT NULL DEFAULT 0; -- 1 = decisions only, no trades
@@ -0,0 +1,2 @@
+-- Most of the base asset a bot may hold; its buys are cut down or skipped at the cap (NULL = no cap)
+ALTER TABLE bots ADD COLUMN max_base_position REAL;
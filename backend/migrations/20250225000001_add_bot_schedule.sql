@@ -0,0 +1,2 @@
+-- When a bot may trade, as JSON (see bots::schedule::BotSchedule); NULL to trade around the clock
+ALTER TABLE bots ADD COLUMN schedule TEXT;
@@ -0,0 +1,3 @@
+-- Fee charged on a trade in its quote asset, at the user's fee tier rate; 0 for deposits and withdrawals
+ALTER TABLE trades ADD COLUMN fee REAL NOT NULL DEFAULT 0;
+ALTER TABLE trades_archive ADD COLUMN fee REAL NOT NULL DEFAULT 0;
@@ -0,0 +1,69 @@
+-- PostgreSQL schema, equivalent to the SQLite migrations up to 20250128000001
+-- New migrations must be added to both migrations/ and migrations_postgres/
+-- Timestamps default to UTC to match SQLite's CURRENT_TIMESTAMP
+
+CREATE TABLE IF NOT EXISTS users (
+    user_id TEXT PRIMARY KEY NOT NULL,
+    username TEXT NOT NULL,
+    cash_balance DOUBLE PRECISION NOT NULL DEFAULT 10000.0,
+    asset_balances TEXT NOT NULL DEFAULT '{}',
+    password_hash TEXT,
+    trade_history TEXT DEFAULT '[]',     -- Legacy JSON blob, superseded by the trades table
+    role TEXT NOT NULL DEFAULT 'user',
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    updated_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX IF NOT EXISTS idx_users_username ON users(username);
+
+-- External identities (GitHub/Google) linked to local users
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_identities_user_id ON oauth_identities(user_id);
+
+-- Append-only log of security- and money-relevant user actions
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    user_id TEXT,                -- NULL when the actor is unknown (e.g. failed login)
+    action TEXT NOT NULL,
+    details TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_user_id ON audit_log(user_id, id);
+
+CREATE TABLE IF NOT EXISTS trades (
+    id BIGSERIAL PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    transaction_type TEXT NOT NULL DEFAULT 'Trade',
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL DEFAULT 'USD',
+    side TEXT NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    timestamp TEXT NOT NULL,     -- RFC 3339, same representation as SQLite
+    base_usd_price DOUBLE PRECISION,
+    quote_usd_price DOUBLE PRECISION,
+    executed_by_bot TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_user_id ON trades(user_id, id);
+CREATE INDEX IF NOT EXISTS idx_trades_timestamp ON trades(timestamp);
+
+-- Active bot configurations, restored and respawned on startup
+CREATE TABLE IF NOT EXISTS bots (
+    user_id TEXT PRIMARY KEY NOT NULL,   -- One bot per user
+    strategy TEXT NOT NULL,              -- Registry key, e.g. "naive_momentum"
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL,
+    parameters TEXT NOT NULL DEFAULT '{}',
+    stoploss_amount DOUBLE PRECISION NOT NULL,
+    initial_portfolio_value_usd DOUBLE PRECISION NOT NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
@@ -0,0 +1,18 @@
+-- Trades moved out of the hot table by the retention job (RETENTION_TRADE_ARCHIVE_MONTHS)
+CREATE TABLE IF NOT EXISTS trades_archive (
+    id BIGINT PRIMARY KEY NOT NULL,      -- Original trades.id
+    user_id TEXT NOT NULL,
+    transaction_type TEXT NOT NULL DEFAULT 'Trade',
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL DEFAULT 'USD',
+    side TEXT NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    timestamp TEXT NOT NULL,
+    base_usd_price DOUBLE PRECISION,
+    quote_usd_price DOUBLE PRECISION,
+    executed_by_bot TEXT,
+    archived_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_archive_user_id ON trades_archive(user_id, id);
@@ -0,0 +1,18 @@
+-- Shared state for multi-instance deployments (SHARED_STATE=true)
+
+-- Time-limited ownership of a resource ("price_poller", "bot:<user_id>")
+CREATE TABLE IF NOT EXISTS leases (
+    name TEXT PRIMARY KEY NOT NULL,
+    holder TEXT NOT NULL,            -- INSTANCE_ID of the owner
+    expires_at BIGINT NOT NULL       -- Unix seconds
+);
+
+-- Prices published by the instance holding the price_poller lease
+CREATE TABLE IF NOT EXISTS shared_prices (
+    id BIGSERIAL PRIMARY KEY,
+    asset TEXT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    timestamp TEXT NOT NULL          -- RFC 3339
+);
+
+CREATE INDEX IF NOT EXISTS idx_shared_prices_asset ON shared_prices(asset, id);
@@ -0,0 +1,7 @@
+-- Move the legacy cash_balance into asset_balances["USD"] for users that never had a USD entry,
+-- then drop the column so balances have a single source of truth
+UPDATE users
+SET asset_balances = jsonb_set(asset_balances::jsonb, '{USD}', to_jsonb(cash_balance))::text
+WHERE NOT (asset_balances::jsonb ? 'USD');
+
+ALTER TABLE users DROP COLUMN cash_balance;
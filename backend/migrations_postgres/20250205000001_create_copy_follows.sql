@@ -0,0 +1,11 @@
+-- Copy-trading subscriptions: the follower mirrors the leader's trades, scaled to portfolio size
+CREATE TABLE IF NOT EXISTS copy_follows (
+    follower_id TEXT NOT NULL,
+    leader_id TEXT NOT NULL,
+    ratio DOUBLE PRECISION NOT NULL DEFAULT 1.0, -- Multiplier on the portfolio-proportional size
+    max_trade_usd DOUBLE PRECISION,            -- Cap on each mirrored trade (NULL = no cap)
+    created_at BIGINT NOT NULL,                -- Unix seconds
+    PRIMARY KEY (follower_id, leader_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_copy_follows_leader_id ON copy_follows(leader_id);
@@ -0,0 +1,19 @@
+-- Resting limit and stop orders, filled at market once a live price reaches the trigger
+CREATE TABLE IF NOT EXISTS orders (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL,
+    side TEXT NOT NULL,              -- "Buy" or "Sell"
+    order_type TEXT NOT NULL,        -- "limit" or "stop"
+    quantity DOUBLE PRECISION NOT NULL, -- Amount of base asset
+    trigger_price DOUBLE PRECISION NOT NULL, -- Price in quote asset terms
+    status TEXT NOT NULL,            -- "open", "filled", "cancelled" or "failed"
+    status_reason TEXT,              -- Why a fill failed
+    fill_price DOUBLE PRECISION,
+    created_at BIGINT NOT NULL,      -- Unix seconds
+    closed_at BIGINT                 -- Unix seconds; NULL while open
+);
+
+CREATE INDEX IF NOT EXISTS idx_orders_user_id ON orders(user_id, status);
+CREATE INDEX IF NOT EXISTS idx_orders_status ON orders(status);
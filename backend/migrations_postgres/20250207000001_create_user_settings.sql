@@ -0,0 +1,6 @@
+-- Per-user UI and account preferences (chart indicators, theme, ...)
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id TEXT PRIMARY KEY NOT NULL,
+    settings TEXT NOT NULL DEFAULT '{}', -- JSON object, one key per setting
+    updated_at BIGINT NOT NULL           -- Unix seconds
+);
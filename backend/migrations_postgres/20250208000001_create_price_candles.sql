@@ -0,0 +1,13 @@
+-- Imported OHLCV history (CSV files or the exchange candles API) for backtests beyond the in-memory tiers
+CREATE TABLE IF NOT EXISTS price_candles (
+    asset TEXT NOT NULL,             -- Priced in USD, like the live feeds
+    interval_secs BIGINT NOT NULL,   -- Candle length: 60, 300, 900, 3600, 21600 or 86400
+    timestamp BIGINT NOT NULL,       -- Unix seconds at the candle's open
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    volume DOUBLE PRECISION,         -- Base asset volume; NULL when the source has none
+    source TEXT NOT NULL,            -- "csv" or "coinbase"
+    PRIMARY KEY (asset, interval_secs, timestamp)
+);
@@ -0,0 +1,9 @@
+-- Outstanding password reset tokens, mailed to the user's email endpoints
+CREATE TABLE IF NOT EXISTS password_resets (
+    token_hash TEXT PRIMARY KEY NOT NULL, -- SHA-256 of the token; the token itself is only in the email
+    user_id TEXT NOT NULL,
+    expires_at BIGINT NOT NULL,           -- Unix seconds
+    created_at BIGINT NOT NULL            -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_resets_user_id ON password_resets(user_id);
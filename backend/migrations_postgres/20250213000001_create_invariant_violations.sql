@@ -0,0 +1,11 @@
+-- Accounting rules a balance change broke, kept for admins to investigate
+CREATE TABLE IF NOT EXISTS invariant_violations (
+    id BIGSERIAL PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    rule TEXT NOT NULL,              -- e.g. "negative_balance", "trade_legs"
+    details TEXT NOT NULL,           -- What was expected and what happened
+    change TEXT NOT NULL,            -- The change that broke it, e.g. "Buy 0.1 BTC/USD @ 50000"
+    detected_at BIGINT NOT NULL      -- Unix seconds
+);
+
+CREATE INDEX IF NOT EXISTS idx_invariant_violations_user_id ON invariant_violations(user_id, id);
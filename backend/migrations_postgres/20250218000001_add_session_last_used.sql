@@ -0,0 +1,2 @@
+-- When each session's token was last presented (Unix seconds), recorded at most once a minute
+ALTER TABLE sessions ADD COLUMN last_used_at BIGINT;
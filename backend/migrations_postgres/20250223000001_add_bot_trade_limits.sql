@@ -0,0 +1,2 @@
+-- Per-bot trade limits enforced on top of the stoploss
+ALTER TABLE bots ADD COLUMN trade_limits TEXT NOT NULL DEFAULT '{}'; -- JSON, e.g. {"max_trade_value":500}
@@ -0,0 +1,2 @@
+-- Paused by its user (POST /api/bot/pause): the bot keeps ticking but doesn't trade until resumed
+ALTER TABLE bots ADD COLUMN paused BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,19 @@
+-- One row per bot instance, from its start until it stops, kept after the bot is gone
+CREATE TABLE IF NOT EXISTS bot_runs (
+    id BIGSERIAL PRIMARY KEY,
+    bot_id TEXT NOT NULL UNIQUE,         -- bots.id; restarts resume the same run
+    user_id TEXT NOT NULL,
+    bot_name TEXT NOT NULL,
+    strategy TEXT NOT NULL,
+    base_asset TEXT NOT NULL,
+    quote_asset TEXT NOT NULL,
+    dry_run BIGINT NOT NULL DEFAULT 0,
+    status TEXT NOT NULL,                -- "running", "stopped", "stoploss" or "failed"
+    stop_reason TEXT,
+    errors BIGINT NOT NULL DEFAULT 0,    -- Errors the bot hit while running, fatal or not
+    last_error TEXT,
+    started_at BIGINT NOT NULL,          -- Unix seconds
+    stopped_at BIGINT                    -- Unix seconds; NULL while running
+);
+
+CREATE INDEX IF NOT EXISTS idx_bot_runs_user_id ON bot_runs(user_id, id);
@@ -0,0 +1,4 @@
+-- Instance id of the bot that placed the trade (GET /api/trades?source=bot&bot_instance_id=), NULL otherwise
+ALTER TABLE trades ADD COLUMN bot_id TEXT;
+ALTER TABLE trades_archive ADD COLUMN bot_id TEXT;
+CREATE INDEX IF NOT EXISTS idx_trades_bot_id ON trades(bot_id);
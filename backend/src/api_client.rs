@@ -1,3 +1,4 @@
+use crate::config::ExchangeConfig;
 use crate::models::{PricePoint, Candle};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -12,19 +13,10 @@ struct CoinbaseData {
     amount: String,
 }
 
-#[derive(Deserialize)]
-struct CoinbaseCandle {
-    // [timestamp, low, high, open, close, volume]
-    // We'll use close price
-    #[serde(rename = "0")]
-    timestamp: i64,
-    #[serde(rename = "4")]
-    close: String,
-}
-
 pub struct ApiClient {
     client: reqwest::Client,
-    base_url: String,
+    base_url: String,    // Spot prices
+    candles_url: String, // Historical candles
 }
 
 #[derive(Debug)]
@@ -45,21 +37,14 @@ impl std::fmt::Display for ApiError {
 impl std::error::Error for ApiError {}
 
 impl ApiClient {
-    pub fn new() -> Self {
+    pub fn new(config: &ExchangeConfig) -> Self {
         Self {
             client: reqwest::Client::new(),
-            base_url: "https://api.coinbase.com/v2".to_string(),
+            base_url: config.spot_url.clone(),
+            candles_url: config.candles_url.clone(),
         }
     }
 
-    pub async fn fetch_btc_price(&self) -> Result<PricePoint, ApiError> {
-        self.fetch_price("BTC", "USD").await
-    }
-
-    pub async fn fetch_eth_price(&self) -> Result<PricePoint, ApiError> {
-        self.fetch_price("ETH", "USD").await
-    }
-
     pub async fn fetch_price(&self, base: &str, quote: &str) -> Result<PricePoint, ApiError> {
         let url = format!("{}/prices/{}-{}/spot", self.base_url, base, quote);
 
@@ -79,6 +64,7 @@ impl ApiClient {
             timestamp: Utc::now(),
             asset: base.to_string(),
             price,
+            volume: None,
         })
     }
 
@@ -94,7 +80,8 @@ impl ApiClient {
     ) -> Result<Vec<(DateTime<Utc>, f64)>, ApiError> {
         // Use Coinbase Advanced Trade API for historical data
         let url = format!(
-            "https://api.exchange.coinbase.com/products/{}-USD/candles?start={}&end={}&granularity={}",
+            "{}/products/{}-USD/candles?start={}&end={}&granularity={}",
+            self.candles_url,
             asset,
             start.to_rfc3339(),
             end.to_rfc3339(),
@@ -140,19 +127,22 @@ impl ApiClient {
         Ok(result)
     }
 
-    /// Interpolate between candles to create smooth 5-second data points
+    /// Interpolate between candle closes to create smooth data points at the polling interval
+    /// Each candle's volume is spread evenly over the points up to the next candle; the final
+    /// point, whose candle is still open, has none
     pub fn interpolate_candles(
         asset: &str,
-        candles: Vec<(DateTime<Utc>, f64)>,
+        candles: &[Candle],
         target_interval_secs: i64,
     ) -> Vec<PricePoint> {
         if candles.len() < 2 {
             return candles
-                .into_iter()
-                .map(|(timestamp, price)| PricePoint {
-                    timestamp,
+                .iter()
+                .map(|candle| PricePoint {
+                    timestamp: candle.timestamp,
                     asset: asset.to_string(),
-                    price,
+                    price: candle.close,
+                    volume: None,
                 })
                 .collect();
         }
@@ -160,38 +150,67 @@ impl ApiClient {
         let mut result = Vec::new();
 
         for window in candles.windows(2) {
-            let (start_time, start_price) = window[0];
-            let (end_time, end_price) = window[1];
+            let (start, end) = (&window[0], &window[1]);
 
-            let duration = (end_time - start_time).num_seconds();
+            let duration = (end.timestamp - start.timestamp).num_seconds();
             let num_points = (duration / target_interval_secs).max(1);
+            let volume = start.volume.map(|volume| volume / num_points as f64);
 
             // Add interpolated points
             for i in 0..num_points {
                 let t = i as f64 / num_points as f64;
-                let interpolated_price = start_price + (end_price - start_price) * t;
-                let interpolated_time = start_time + chrono::Duration::seconds(i * target_interval_secs);
+                let interpolated_price = start.close + (end.close - start.close) * t;
+                let interpolated_time = start.timestamp + chrono::Duration::seconds(i * target_interval_secs);
 
                 result.push(PricePoint {
                     timestamp: interpolated_time,
                     asset: asset.to_string(),
                     price: interpolated_price,
+                    volume,
                 });
             }
         }
 
         // Add the final point
-        if let Some((last_time, last_price)) = candles.last() {
+        if let Some(last) = candles.last() {
             result.push(PricePoint {
-                timestamp: *last_time,
+                timestamp: last.timestamp,
                 asset: asset.to_string(),
-                price: *last_price,
+                price: last.close,
+                volume: None,
             });
         }
 
         result
     }
 
+    /// Fetch OHLC candles for a range longer than one request allows
+    /// Coinbase returns at most 300 candles per request, so the range is fetched in pages
+    pub async fn fetch_ohlc_candles_paged(
+        &self,
+        asset: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        granularity: i64,
+    ) -> Result<Vec<Candle>, ApiError> {
+        const MAX_CANDLES_PER_REQUEST: i64 = 300;
+        let page = chrono::Duration::seconds(granularity * MAX_CANDLES_PER_REQUEST);
+
+        let mut result = Vec::new();
+        let mut page_start = start;
+        while page_start < end {
+            let page_end = (page_start + page).min(end);
+            result.extend(self.fetch_ohlc_candles(asset, page_start, page_end, granularity).await?);
+            page_start = page_end;
+        }
+
+        // Page boundaries are inclusive on both ends
+        result.sort_by_key(|c| c.timestamp);
+        result.dedup_by_key(|c| c.timestamp);
+
+        Ok(result)
+    }
+
     /// Fetch full OHLC candles (not just close price)
     pub async fn fetch_ohlc_candles(
         &self,
@@ -201,7 +220,8 @@ impl ApiClient {
         granularity: i64,
     ) -> Result<Vec<Candle>, ApiError> {
         let url = format!(
-            "https://api.exchange.coinbase.com/products/{}-USD/candles?start={}&end={}&granularity={}",
+            "{}/products/{}-USD/candles?start={}&end={}&granularity={}",
+            self.candles_url,
             asset,
             start.to_rfc3339(),
             end.to_rfc3339(),
@@ -241,6 +261,8 @@ impl ApiClient {
                 let close = candle[4].as_f64()
                     .or_else(|| candle[4].as_str().and_then(|s| s.parse::<f64>().ok()))
                     .ok_or_else(|| ApiError::ParseError("Invalid close price".to_string()))?;
+                let volume = candle.get(5)
+                    .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok())));
 
                 let dt = DateTime::from_timestamp(timestamp, 0)
                     .ok_or_else(|| ApiError::ParseError("Invalid timestamp conversion".to_string()))?;
@@ -252,6 +274,7 @@ impl ApiClient {
                     high,
                     low,
                     close,
+                    volume,
                 });
             }
         }
@@ -0,0 +1,211 @@
+//! The HTTP app and the background tasks reacting to its events, shared by the server and the tests
+
+use crate::middleware::{self, rate_limit::{self, RateLimiter, UserRateLimiter}};
+use crate::state::AppState;
+use crate::{routes, services};
+use axum::{extract::DefaultBodyLimit, middleware::from_fn_with_state, routing::{delete, get, post, put}, Router};
+use tower_http::{
+    compression::CompressionLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    services::ServeDir,
+    trace::TraceLayer,
+};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+/// Every route, with rate limits, CORS and request tracing
+/// Serve it with `into_make_service_with_connect_info::<SocketAddr>()`: auth rate limits go by client address
+pub fn router(state: AppState) -> Router {
+    // Rate limiters (per IP for auth, per user and IP for money-moving endpoints)
+    let auth_limiter = RateLimiter::from_env("RATE_LIMIT_AUTH", 10, 60);
+    let trade_limiter = UserRateLimiter {
+        per_user: RateLimiter::from_env("RATE_LIMIT_TRADE", 60, 60),
+        per_ip: RateLimiter::from_env("RATE_LIMIT_TRADE_IP", 300, 60),
+    };
+    let webhook_limiter = RateLimiter::from_env("RATE_LIMIT_WEBHOOK", 30, 60);
+
+    let auth_routes = Router::new()
+        .route("/signup", post(routes::auth::signup))
+        .route("/login", post(routes::auth::login))
+        .route("/password-reset", post(routes::auth::request_password_reset))
+        .route("/password-reset/confirm", post(routes::auth::confirm_password_reset))
+        .route("/auth/oauth/:provider/authorize", get(routes::auth::oauth_authorize))
+        .route("/auth/oauth/:provider/callback", get(routes::auth::oauth_callback))
+        .route_layer(from_fn_with_state(auth_limiter, rate_limit::limit_by_ip));
+
+    let trade_routes = Router::new()
+        .route("/trade", post(routes::trade::post_trade))
+        .route("/deposit", post(routes::trade::post_deposit))
+        .route("/withdrawal", post(routes::trade::post_withdrawal))
+        .route("/orders", post(routes::orders::create_order))
+        .route("/competitions/:competition_id/trade", post(routes::competitions::post_trade))
+        .route_layer(from_fn_with_state(trade_limiter, rate_limit::limit_by_user));
+
+    // Called by TradingView, not the frontend; the secret in the body identifies the user
+    let webhook_routes = Router::new()
+        .route("/webhooks/tradingview", post(routes::webhooks::tradingview_webhook))
+        .route_layer(from_fn_with_state(webhook_limiter, rate_limit::limit_by_ip));
+
+    let api_routes = Router::new()
+        .route("/price", get(routes::price::get_price))
+        .route("/assets", get(routes::price::get_assets))
+        .route("/price/stats", get(routes::price::get_ticker_stats))
+        .route("/price/history", get(routes::price::get_price_history))
+        .route("/price/candles", get(routes::price::get_candle_history))
+        .route("/indicators", get(routes::indicators::get_indicators))
+        .route("/news", get(routes::news::get_news))
+        .route("/stats", get(routes::stats::public_stats))
+        .route("/ws/prices", get(routes::stream::price_stream))
+        .route("/events", get(routes::stream::portfolio_events))
+        .route("/portfolio", get(routes::portfolio::get_portfolio))
+        .route("/portfolio/history", get(routes::portfolio::get_portfolio_history))
+        .route("/portfolio/summaries", get(routes::portfolio::get_daily_summaries))
+        .route("/trades", get(routes::trade::get_trades))
+        .route("/funds", get(routes::trade::get_funds))
+        .route("/ledger", get(routes::trade::get_ledger))
+        .route("/ledger/statements", get(routes::trade::get_ledger_statements))
+        .route("/trade/preview", post(routes::orders::preview_trade))
+        .route("/fees", get(routes::orders::get_fees))
+        .route("/orders", get(routes::orders::list_orders))
+        .route("/orders/:order_id", delete(routes::orders::cancel_order))
+        .merge(auth_routes)
+        .merge(trade_routes)
+        .merge(webhook_routes)
+        .route("/bots", get(routes::bot::list_bots))
+        .route("/bots/available", get(routes::bot::available_bots))
+        .route("/bot/start", post(routes::bot::start_bot))
+        .route("/bot/stop", post(routes::bot::stop_bot))
+        .route("/bot/pause", post(routes::bot::pause_bot))
+        .route("/bot/resume", post(routes::bot::resume_bot))
+        .route("/bot/status", get(routes::bot::bot_status))
+        .route("/bot/risk", get(routes::bot::bot_risk))
+        .route("/bot/decisions", get(routes::bot::bot_decisions))
+        .route("/bot/runs", get(routes::bot::bot_runs))
+        .route("/bot/compare", get(routes::bot::get_comparison).post(routes::bot::start_comparison))
+        .route(
+            "/bot/strategy",
+            get(routes::bot::export_strategy).post(routes::bot::import_strategy),
+        )
+        .route("/bot/strategy/validate", post(routes::bot::validate_strategy))
+        .route("/ws/bot", get(routes::stream::bot_activity_stream))
+        .route("/backtest", get(routes::backtest::list_backtests).post(routes::backtest::start_backtest))
+        .route("/backtest/optimize", post(routes::backtest::optimize_backtest))
+        .route("/backtest/walk-forward", post(routes::backtest::walk_forward))
+        .route("/optimize", post(routes::backtest::genetic_optimize))
+        .route("/backtest/replay", post(routes::backtest::start_replay))
+        .route("/backtest/strategy", post(routes::backtest::backtest_strategy))
+        .route(
+            "/backtest/replay/:replay_id",
+            get(routes::backtest::get_replay).delete(routes::backtest::cancel_replay),
+        )
+        .route("/backtest/:backtest_id", get(routes::backtest::get_backtest))
+        .route("/backtest/:backtest_id/risk", get(routes::backtest::get_backtest_risk))
+        .route("/backtest/:backtest_id/strategy", get(routes::backtest::export_strategy))
+        .route("/account", delete(routes::account::delete_account))
+        .route("/account/password", post(routes::account::change_password))
+        .route("/account/export", get(routes::account::export_account))
+        .route("/session", get(routes::session::current_session))
+        .route("/logout", post(routes::session::logout))
+        .route(
+            "/sessions",
+            get(routes::session::list_sessions).delete(routes::session::revoke_all_sessions),
+        )
+        .route("/sessions/:session_id", delete(routes::session::revoke_session))
+        .route("/audit", get(routes::audit::get_audit_log))
+        .route("/settings", get(routes::settings::get_settings).patch(routes::settings::update_settings))
+        .route("/alerts", get(routes::alerts::list_alerts).post(routes::alerts::create_alert))
+        .route("/watchlists", get(routes::watchlists::list_watchlists).post(routes::watchlists::create_watchlist))
+        .route("/watchlists/:watchlist_id", delete(routes::watchlists::delete_watchlist))
+        .route("/alerts/history", get(routes::alerts::alert_history))
+        .route("/follows", get(routes::follows::list_following).post(routes::follows::follow))
+        .route("/follows/:leader_id", delete(routes::follows::unfollow))
+        .route("/followers", get(routes::follows::list_followers))
+        .route(
+            "/webhooks/tradingview/secret",
+            get(routes::webhooks::webhook_status)
+                .post(routes::webhooks::rotate_secret)
+                .delete(routes::webhooks::disable_webhook),
+        )
+        .route(
+            "/notifications/endpoints",
+            get(routes::notifications::list_endpoints).post(routes::notifications::create_endpoint),
+        )
+        .route("/notifications/endpoints/:endpoint_id", delete(routes::notifications::delete_endpoint))
+        .route("/notifications/endpoints/:endpoint_id/test", post(routes::notifications::test_endpoint))
+        .route("/notifications/deliveries", get(routes::notifications::list_deliveries))
+        .route(
+            "/competitions",
+            get(routes::competitions::list_competitions).post(routes::competitions::create_competition),
+        )
+        .route("/competitions/:competition_id", get(routes::competitions::get_competition))
+        .route("/competitions/:competition_id/join", post(routes::competitions::join_competition))
+        .route("/competitions/:competition_id/portfolio", get(routes::competitions::get_portfolio))
+        .route("/competitions/:competition_id/trades", get(routes::competitions::list_trades))
+        .route("/competitions/:competition_id/leaderboard", get(routes::competitions::leaderboard))
+        .route("/leagues", get(routes::leagues::list_leagues).post(routes::leagues::create_league))
+        .route("/leagues/:league_id", get(routes::leagues::get_league))
+        .route(
+            "/leagues/:league_id/join",
+            post(routes::leagues::join_league).delete(routes::leagues::leave_league),
+        )
+        .route("/leagues/:league_id/seasons", get(routes::leagues::list_seasons))
+        .route("/leagues/:league_id/leaderboard", get(routes::leagues::leaderboard))
+        .route("/achievements", get(routes::achievements::list_achievements))
+        .route(
+            "/alerts/:alert_id",
+            put(routes::alerts::update_alert).delete(routes::alerts::delete_alert),
+        )
+        .route("/admin/users", get(routes::admin::list_users))
+        .route("/admin/stats", get(routes::admin::stats))
+        .route("/admin/users/:target_id/reset", post(routes::admin::reset_user))
+        .route("/admin/users/:target_id/stop-bot", post(routes::admin::stop_user_bot))
+        .route("/admin/backup", get(routes::admin::backup))
+        .route("/admin/invariants", get(routes::admin::invariant_violations))
+        .route("/admin/bot-runs", get(routes::admin::bot_runs))
+        .route("/admin/ledger", get(routes::admin::reconcile_ledger))
+        .route("/admin/history", get(routes::admin::history_coverage))
+        .route(
+            "/admin/history/import",
+            post(routes::admin::import_history)
+                .layer(DefaultBodyLimit::max(services::history_service::MAX_IMPORT_BYTES)),
+        )
+        .route("/admin/history/fetch", post(routes::admin::fetch_history))
+        .layer(from_fn_with_state(state.clone(), middleware::session_activity::record_session_use));
+
+    let cors_config = middleware::cors::CorsConfig::from_env();
+    tracing::info!("CORS: {:?}", cors_config);
+
+    Router::new()
+        .nest("/api", api_routes)
+        .merge(SwaggerUi::new("/api/docs").url("/api/docs/openapi.json", routes::docs::ApiDoc::openapi()))
+        .nest_service("/", ServeDir::new("static"))
+        // gzip/br per Accept-Encoding; SSE streams and small bodies are left alone
+        .layer(CompressionLayer::new())
+        .layer(cors_config.layer())
+        // Outermost last: assign the id, open the request span, then echo the id on the response
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(TraceLayer::new_for_http().make_span_with(middleware::request_id::make_request_span))
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .with_state(state)
+}
+
+/// Tasks acting on live prices, trades and bot activity as they happen
+pub fn spawn_event_handlers(state: &AppState) {
+    // Fire price alerts as live prices arrive
+    tokio::spawn(services::alert_service::run_alerts(state.clone()));
+
+    // Fill limit and stop orders as live prices reach them
+    tokio::spawn(services::order_service::run_orders(state.clone()));
+
+    // Push notifications to users' webhook endpoints
+    tokio::spawn(services::notification_service::run_dispatcher(state.clone()));
+
+    // Record final competition standings as competitions end
+    tokio::spawn(services::competition_service::run_finalizer(state.clone()));
+
+    // Start each league's next season as the last one ends
+    tokio::spawn(services::league_service::run_scheduler(state.clone()));
+
+    // Award achievements as users trade and run bots
+    tokio::spawn(services::achievement_service::run_achievements(state.clone()));
+}
@@ -0,0 +1,109 @@
+//! Display metadata for assets: how many decimals amounts are shown and rounded to, their symbol and icon
+//! Served at `/api/assets` so the frontend formats amounts the same way as the backend's reports
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Decimals for assets the registry doesn't list
+const DEFAULT_DECIMALS: u32 = 6;
+
+/// Colored SVG icons by lowercase ticker (the spothq/cryptocurrency-icons set)
+const ICON_BASE_URL: &str = "https://cdn.jsdelivr.net/gh/spothq/cryptocurrency-icons@master/svg/color";
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AssetInfo {
+    pub asset: String,
+    pub name: String,
+    pub symbol: String, // e.g. "$" or "₿"; the ticker when there is no sign
+    pub decimals: u32,  // Amounts of the asset, and prices quoted in it, are shown to this many places
+    pub icon: String,   // Icon URL
+    pub stablecoin: bool, // Pegged to the US dollar
+}
+
+/// (ticker, name, symbol, decimals)
+const KNOWN_ASSETS: &[(&str, &str, &str, u32)] = &[
+    ("USD", "US Dollar", "$", 2),
+    ("BTC", "Bitcoin", "₿", 8),
+    ("ETH", "Ethereum", "Ξ", 6),
+    ("SOL", "Solana", "◎", 4),
+    ("DOGE", "Dogecoin", "Ð", 2),
+    ("LTC", "Litecoin", "Ł", 6),
+    ("ADA", "Cardano", "₳", 4),
+    ("XRP", "XRP", "XRP", 4),
+    ("USDC", "USD Coin", "USDC", 2),
+    ("USDT", "Tether", "₮", 2),
+];
+
+/// Dollar-pegged assets; their feeds fall back to a simulated peg when Coinbase has no price
+pub const STABLECOINS: &[&str] = &["USDC", "USDT"];
+
+pub fn is_stablecoin(asset: &str) -> bool {
+    STABLECOINS.contains(&asset)
+}
+
+/// Metadata of any asset; unlisted ones get their ticker as name and symbol and the default decimals
+pub fn info(asset: &str) -> AssetInfo {
+    let (name, symbol, decimals) = KNOWN_ASSETS
+        .iter()
+        .find(|(ticker, ..)| *ticker == asset)
+        .map(|(_, name, symbol, decimals)| (*name, *symbol, *decimals))
+        .unwrap_or((asset, asset, DEFAULT_DECIMALS));
+    AssetInfo {
+        asset: asset.to_string(),
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+        decimals,
+        icon: format!("{}/{}.svg", ICON_BASE_URL, asset.to_lowercase()),
+        stablecoin: is_stablecoin(asset),
+    }
+}
+
+/// The listed assets, then `others` not among them
+pub fn registry(others: &[String]) -> Vec<AssetInfo> {
+    KNOWN_ASSETS
+        .iter()
+        .map(|(ticker, ..)| *ticker)
+        .chain(others.iter().map(String::as_str).filter(|a| !KNOWN_ASSETS.iter().any(|(ticker, ..)| ticker == a)))
+        .map(info)
+        .collect()
+}
+
+pub fn decimals(asset: &str) -> u32 {
+    KNOWN_ASSETS.iter().find(|(ticker, ..)| *ticker == asset).map_or(DEFAULT_DECIMALS, |(.., decimals)| *decimals)
+}
+
+/// `amount` rounded to the asset's decimals
+pub fn round(amount: f64, asset: &str) -> f64 {
+    let scale = 10f64.powi(decimals(asset) as i32);
+    (amount * scale).round() / scale
+}
+
+/// `amount` to the asset's decimals, followed by its ticker, e.g. "0.10000000 BTC"
+pub fn format(amount: f64, asset: &str) -> String {
+    format!("{:.*} {}", decimals(asset) as usize, amount, asset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precision_by_asset() {
+        assert_eq!(format(1234.5, "USD"), "1234.50 USD");
+        assert_eq!(format(0.1, "BTC"), "0.10000000 BTC");
+        assert_eq!(format(2.0, "SOL"), "2.0000 SOL");
+        assert_eq!(round(10.005_1, "USD"), 10.01);
+        assert_eq!(round(1.234_567_891, "BTC"), 1.234_567_89);
+
+        // Unlisted assets fall back to the defaults
+        let pepe = info("PEPE");
+        assert_eq!((pepe.name.as_str(), pepe.symbol.as_str(), pepe.decimals), ("PEPE", "PEPE", DEFAULT_DECIMALS));
+        assert!(pepe.icon.ends_with("/pepe.svg"));
+        assert!(info("USDT").stablecoin && !pepe.stablecoin);
+
+        let listed: Vec<String> = registry(&["BTC".to_string(), "PEPE".to_string()]).into_iter().map(|a| a.asset).collect();
+        assert_eq!(listed.first().map(String::as_str), Some("USD"));
+        assert_eq!(listed.iter().filter(|a| *a == "BTC").count(), 1);
+        assert_eq!(listed.last().map(String::as_str), Some("PEPE"));
+    }
+}
@@ -0,0 +1,417 @@
+//! Operator tasks against the server's database and configuration, without going through the API
+//! Reads the same DATABASE_URL, CONFIG_PATH and env overrides as the server
+
+use backend::api_client::ApiClient;
+use backend::config::Config;
+use backend::db::{queries, Database};
+use backend::models::UserData;
+use backend::services::audit_service::AuditAction;
+use backend::services::backtest_service::{self, BacktestParams};
+use backend::services::history_service;
+use backend::services::maintenance_service::{self, RetentionConfig};
+use backend::services::seed_service::{self, SeedOptions};
+use backend::state::AppState;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::process::ExitCode;
+
+const DEFAULT_DATABASE_URL: &str = "sqlite:/app/data/trading_sim.db";
+const DEFAULT_INITIAL_BALANCE: f64 = 10_000.0;
+
+const USAGE: &str = "\
+Usage: admin <command> [arguments]
+
+Commands:
+  create-user <username> <password> [--admin]
+      Create an account with the configured starting balance
+  promote <username>
+      Grant the admin role
+  list-users
+      Usernames, roles and USD balances
+  reset-user <username>
+      Reset a portfolio to the starting balance and delete its trades and saved bot
+  import-history <asset> <interval_secs> <file.csv>
+      Store OHLCV candles from a CSV file (same format as POST /api/admin/history/import)
+  fetch-history <asset> <interval_secs> <start> [end]
+      Store OHLCV candles from the exchange; dates are RFC 3339 or YYYY-MM-DD
+  backtest <strategy> <base[/quote]> <start> [end] --stoploss <amount> [--balance <amount>] [--params <json>] [--json]
+      Replay a strategy over stored prices and print its metrics (or the whole result with --json)
+  seed [--users <n>] [--days <n>] [--seed <n>]
+      Generate demo data: hourly BTC/ETH/SOL history and users named like alice_demo
+      (password demo-password) with trade histories; the first two get a running bot
+  prune
+      Archive old trades, prune the audit log and expired sessions, and vacuum (RETENTION_* settings)
+
+Stop the server (or the user's bot) before resetting a portfolio: a running server keeps
+its own copy of users unless SHARED_STATE is enabled.";
+
+/// Positional arguments and `--flag [value]` options
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// Options listed in `with_value` take the next argument as their value
+    fn parse(mut args: impl Iterator<Item = String>, with_value: &[&str]) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if with_value.contains(&name) => {
+                    let value = args.next().ok_or_else(|| format!("--{} needs a value", name))?;
+                    options.push((name.to_string(), Some(value)));
+                }
+                Some(name) => options.push((name.to_string(), None)),
+                None => positional.push(arg),
+            }
+        }
+        Ok(Self { positional, options })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| n == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options.iter().find(|(n, _)| n == name).and_then(|(_, v)| v.as_deref())
+    }
+
+    /// The positional arguments, if there are between `min` and `max` of them
+    fn expect(&self, min: usize, max: usize) -> Result<&[String], String> {
+        if !(min..=max).contains(&self.positional.len()) {
+            return Err(format!("Expected {} to {} arguments, got {}", min, max, self.positional.len()));
+        }
+        Ok(&self.positional)
+    }
+
+    /// Refuse options the command doesn't know
+    fn allow(&self, known: &[&str]) -> Result<(), String> {
+        match self.options.iter().find(|(n, _)| !known.contains(&n.as_str())) {
+            Some((name, _)) => Err(format!("Unknown option --{}", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()))
+        .map_err(|_| format!("Invalid date {:?} (use RFC 3339 or YYYY-MM-DD)", value))
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid {}: {:?}", name, value))
+}
+
+fn normalize_asset(asset: &str) -> Result<String, String> {
+    let asset = asset.trim().to_uppercase();
+    if !(2..=10).contains(&asset.len()) || !asset.chars().all(|c| c.is_ascii_alphanumeric()) || asset == "USD" {
+        return Err(format!("Invalid asset: {}", asset));
+    }
+    Ok(asset)
+}
+
+async fn user_id_of(db: &Database, username: &str) -> Result<String, String> {
+    queries::find_user_id_by_username(db.pool(), username)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No user named {:?}", username))
+}
+
+/// Audit entries are written inline here; the server's fire-and-forget writes could outlive the process
+async fn audit(db: &Database, user_id: Option<&str>, action: AuditAction, details: String) {
+    if let Err(e) = queries::insert_audit_entry(db.pool(), user_id, action.as_str(), &details).await {
+        eprintln!("Warning: failed to write audit entry: {}", e);
+    }
+}
+
+async fn create_user(db: &Database, config: &Config, args: &Args) -> Result<(), String> {
+    args.allow(&["admin"])?;
+    let [username, password] = args.expect(2, 2)? else { unreachable!() };
+
+    let user_id = backend::services::auth_service::generate_user_id();
+    queries::create_user(db.pool(), &user_id, username, password, config.accounts.starting_balance)
+        .await
+        .map_err(|e| e.to_string())?;
+    audit(db, Some(&user_id), AuditAction::Signup, "by admin CLI".to_string()).await;
+    if args.flag("admin") {
+        queries::promote_admins(db.pool(), std::slice::from_ref(username)).await.map_err(|e| e.to_string())?;
+    }
+
+    println!("Created {} ({})", username, user_id);
+    Ok(())
+}
+
+async fn promote(db: &Database, args: &Args) -> Result<(), String> {
+    args.allow(&[])?;
+    let [username] = args.expect(1, 1)? else { unreachable!() };
+
+    match queries::promote_admins(db.pool(), std::slice::from_ref(username)).await.map_err(|e| e.to_string())? {
+        0 => Err(format!("No user named {:?}", username)),
+        _ => {
+            println!("{} is now an admin", username);
+            Ok(())
+        }
+    }
+}
+
+async fn list_users(db: &Database, args: &Args) -> Result<(), String> {
+    args.allow(&[])?;
+    args.expect(0, 0)?;
+
+    let pool = db.pool();
+    let roles = queries::load_user_roles(pool).await.map_err(|e| e.to_string())?;
+    let mut users: Vec<_> = queries::load_all_users(pool).await.map_err(|e| e.to_string())?.into_iter().collect();
+    users.sort_by(|a, b| a.1.username.cmp(&b.1.username));
+
+    println!("{:<36}  {:<24}  {:<6}  {:>14}", "USER ID", "USERNAME", "ROLE", "USD");
+    for (user_id, user) in users {
+        let role = roles.get(&user_id).map_or("guest", String::as_str);
+        println!("{:<36}  {:<24}  {:<6}  {:>14.2}", user_id, user.username, role, user.get_balance("USD"));
+    }
+    Ok(())
+}
+
+async fn reset_user(db: &Database, config: &Config, args: &Args) -> Result<(), String> {
+    args.allow(&[])?;
+    let [username] = args.expect(1, 1)? else { unreachable!() };
+    let user_id = user_id_of(db, username).await?;
+
+    let pool = db.pool();
+    // Without their saved configs the bots aren't restarted
+    queries::delete_user_bot_configs(pool, &user_id).await.map_err(|e| e.to_string())?;
+    let previous = queries::get_user(pool, &user_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No user named {:?}", username))?;
+    let fresh = UserData::new(username.clone(), config.accounts.starting_balance);
+    queries::reset_portfolio(pool, &user_id, &previous, &fresh, Utc::now()).await.map_err(|e| e.to_string())?;
+    audit(db, Some(&user_id), AuditAction::PortfolioReset, "by admin CLI".to_string()).await;
+
+    println!("Reset {} to {:.2} USD", username, config.accounts.starting_balance);
+    Ok(())
+}
+
+fn print_import(source: &str, summary: &history_service::ImportSummary) {
+    println!(
+        "{} {}s candles from {}: {} read, {} new, {} duplicate, {} rejected",
+        summary.asset, summary.interval_secs, source, summary.received, summary.inserted, summary.duplicates, summary.rejected
+    );
+    for error in &summary.errors {
+        println!("  {}", error);
+    }
+}
+
+async fn import_history(db: &Database, args: &Args) -> Result<(), String> {
+    args.allow(&[])?;
+    let [asset, interval, path] = args.expect(3, 3)? else { unreachable!() };
+    let asset = normalize_asset(asset)?;
+    let interval_secs: i64 = parse_number("interval", interval)?;
+    history_service::validate_interval(interval_secs)?;
+
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let batch = history_service::parse_csv(&text, interval_secs)?;
+    let summary = history_service::store(db.pool(), &asset, interval_secs, batch, "csv")
+        .await
+        .map_err(|e| e.to_string())?;
+    audit(
+        db,
+        None,
+        AuditAction::HistoryImported,
+        format!("csv {} {}s inserted={} by admin CLI", summary.asset, summary.interval_secs, summary.inserted),
+    )
+    .await;
+
+    print_import("csv", &summary);
+    Ok(())
+}
+
+async fn fetch_history(db: &Database, config: &Config, args: &Args) -> Result<(), String> {
+    args.allow(&[])?;
+    let positional = args.expect(3, 4)?;
+    let asset = normalize_asset(&positional[0])?;
+    let interval_secs: i64 = parse_number("interval", &positional[1])?;
+    let start = parse_time(&positional[2])?;
+    let end = positional.get(3).map(|end| parse_time(end)).transpose()?.unwrap_or_else(Utc::now);
+
+    let end = history_service::validate_fetch_range(interval_secs, start, end, Utc::now())?;
+    let batch = history_service::fetch_exchange(&ApiClient::new(&config.exchange), &asset, interval_secs, start, end).await?;
+    let summary = history_service::store(db.pool(), &asset, interval_secs, batch, "coinbase")
+        .await
+        .map_err(|e| e.to_string())?;
+    audit(
+        db,
+        None,
+        AuditAction::HistoryImported,
+        format!("coinbase {} {}s inserted={} by admin CLI", summary.asset, summary.interval_secs, summary.inserted),
+    )
+    .await;
+
+    print_import("coinbase", &summary);
+    Ok(())
+}
+
+async fn backtest(db: Database, config: Config, args: &Args) -> Result<(), String> {
+    args.allow(&["stoploss", "balance", "params", "json"])?;
+    let positional = args.expect(3, 4)?;
+    let (base, quote) = positional[1].split_once('/').unwrap_or((&positional[1], "USD"));
+    let params = BacktestParams {
+        strategy: positional[0].clone(),
+        base_asset: base.trim().to_uppercase(),
+        quote_asset: quote.trim().to_uppercase(),
+        start: parse_time(&positional[2])?,
+        end: positional.get(3).map(|end| parse_time(end)).transpose()?.unwrap_or_else(Utc::now),
+        initial_balance: args.value("balance").map(|b| parse_number("balance", b)).transpose()?.unwrap_or(DEFAULT_INITIAL_BALANCE),
+        stoploss_amount: parse_number("stoploss", args.value("stoploss").ok_or("--stoploss is required")?)?,
+        parameters: match args.value("params") {
+            Some(json) => serde_json::from_str(json).map_err(|e| format!("Invalid --params: {}", e))?,
+            None => serde_json::json!({}),
+        },
+    };
+
+    let history_start = queries::earliest_price_candle(db.pool(), &params.base_asset).await.map_err(|e| e.to_string())?;
+    backtest_service::validate(&params, Utc::now(), history_start)?;
+    let mut bot = backend::bots::create_bot(&params.strategy, params.stoploss_amount, &params.parameters)
+        .map_err(|e| e.to_string())?;
+
+    // Only imported history is available: the in-memory prices belong to the running server
+    let state = AppState::new(db, config).await;
+    let series = backtest_service::pair_series(&state, &params).await;
+    if series.is_empty() {
+        return Err(format!(
+            "No {}/{} prices between {} and {}; import history first",
+            params.base_asset, params.quote_asset, params.start, params.end
+        ));
+    }
+    let result = backtest_service::simulate(bot.as_mut(), &series, &params);
+
+    if args.flag("json") {
+        println!("{}", serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    let metrics = &result.metrics;
+    println!("{} on {}/{}: {} ticks", params.strategy, params.base_asset, params.quote_asset, result.ticks);
+    println!("  Final value       {:.2} {}", metrics.final_value, params.quote_asset);
+    println!("  Return            {:.2}%", metrics.performance.total_return_pct);
+    println!("  Buy and hold      {:.2}%", metrics.buy_and_hold_return_pct);
+    println!("  Max drawdown      {:.2}%", metrics.performance.max_drawdown_pct);
+    println!("  Trades            {} ({} closed)", metrics.trade_count, metrics.performance.closed_trades);
+    if let Some(reason) = &result.stopped_reason {
+        println!("  Stopped early     {}", reason);
+    }
+    Ok(())
+}
+
+async fn prune(db: Database, config: Config, args: &Args) -> Result<(), String> {
+    args.allow(&[])?;
+    args.expect(0, 0)?;
+
+    let retention = RetentionConfig::from_env();
+    println!("Running database maintenance: {:?}", retention);
+    let state = AppState::new(db, config).await;
+    maintenance_service::run_db_maintenance(&state, &retention).await;
+    Ok(())
+}
+
+async fn seed(db: &Database, config: &Config, args: &Args) -> Result<(), String> {
+    args.allow(&["users", "days", "seed"])?;
+    args.expect(0, 0)?;
+    let defaults = SeedOptions::default();
+    let options = SeedOptions {
+        users: args.value("users").map(|n| parse_number("users", n)).transpose()?.unwrap_or(defaults.users),
+        days: args.value("days").map(|n| parse_number("days", n)).transpose()?.unwrap_or(defaults.days),
+        seed: args.value("seed").map(|n| parse_number("seed", n)).transpose()?.unwrap_or(defaults.seed),
+    };
+
+    let summary = seed_service::seed(db.pool(), &options, config.accounts.starting_balance).await?;
+    audit(
+        db,
+        None,
+        AuditAction::HistoryImported,
+        format!("seed {} days inserted={} by admin CLI", options.days, summary.candles),
+    )
+    .await;
+
+    println!("Stored {} candles", summary.candles);
+    println!("Created {} users with {} transactions and {} bots", summary.users.len(), summary.trades, summary.bots);
+    if !summary.users.is_empty() {
+        println!("  {} (password {:?})", summary.users.join(", "), seed_service::SEED_PASSWORD);
+    }
+    if !summary.skipped.is_empty() {
+        println!("Skipped existing users: {}", summary.skipped.join(", "));
+    }
+    if summary.bots > 0 {
+        println!("Bots start with the server");
+    }
+    Ok(())
+}
+
+async fn run(command: &str, args: Args) -> Result<(), String> {
+    let config = Config::load()?;
+    let database_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| DEFAULT_DATABASE_URL.to_string());
+    let db = Database::new(&database_url).await.map_err(|e| format!("Failed to connect to database: {}", e))?;
+    db.run_migrations().await.map_err(|e| format!("Failed to run migrations: {}", e))?;
+
+    match command {
+        "create-user" => create_user(&db, &config, &args).await,
+        "promote" => promote(&db, &args).await,
+        "list-users" => list_users(&db, &args).await,
+        "reset-user" => reset_user(&db, &config, &args).await,
+        "import-history" => import_history(&db, &args).await,
+        "fetch-history" => fetch_history(&db, &config, &args).await,
+        "backtest" => backtest(db, config, &args).await,
+        "seed" => seed(&db, &config, &args).await,
+        "prune" => prune(db, config, &args).await,
+        _ => unreachable!("commands are checked before connecting"),
+    }
+}
+
+const COMMANDS: &[&str] = &[
+    "create-user",
+    "promote",
+    "list-users",
+    "reset-user",
+    "import-history",
+    "fetch-history",
+    "backtest",
+    "seed",
+    "prune",
+];
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Output is for the operator; logs are warnings and errors only, on stderr
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::WARN)
+        .init();
+
+    let mut argv = std::env::args().skip(1);
+    let command = match argv.next() {
+        Some(command) if COMMANDS.contains(&command.as_str()) => command,
+        Some(command) if command == "help" || command == "--help" || command == "-h" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let args = match Args::parse(argv, &["stoploss", "balance", "params", "users", "days", "seed"]) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&command, args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
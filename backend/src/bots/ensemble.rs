@@ -0,0 +1,286 @@
+use super::{BotConfigError, BotContext, BotDecision, BotRegistry, DecisionOutcome, StrategyParams, TradingBot};
+use crate::models::TradeSide;
+use serde::{Deserialize, Serialize};
+
+/// Registry key of the ensemble, which can't be one of its own members
+const STRATEGY: &str = "ensemble";
+
+const MAX_MEMBERS: usize = 5;
+
+/// A strategy voting in the ensemble
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnsembleMember {
+    pub strategy: String, // Registry key, e.g. "naive_momentum"
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+}
+
+impl EnsembleMember {
+    fn new(strategy: &str, parameters: serde_json::Value) -> Self {
+        Self { strategy: strategy.to_string(), parameters }
+    }
+}
+
+/// Tunable settings; fields left out of a parameters object keep these defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnsembleParams {
+    pub members: Vec<EnsembleMember>,
+    pub quorum: usize, // Members that must agree on the direction before the ensemble trades
+}
+
+impl Default for EnsembleParams {
+    fn default() -> Self {
+        Self {
+            members: vec![
+                EnsembleMember::new("naive_momentum", serde_json::json!({})),
+                EnsembleMember::new("naive_momentum", serde_json::json!({"trend_ticks": 5})),
+                EnsembleMember::new("signal_follower", serde_json::json!({})),
+            ],
+            quorum: 2,
+        }
+    }
+}
+
+impl StrategyParams for EnsembleParams {
+    fn validate(&self) -> Result<(), String> {
+        if !(2..=MAX_MEMBERS).contains(&self.members.len()) {
+            return Err(format!("members must list 2 to {} strategies", MAX_MEMBERS));
+        }
+        if self.quorum == 0 || self.quorum > self.members.len() {
+            return Err("quorum must be between 1 and the number of members".to_string());
+        }
+        if self.members.iter().any(|member| member.strategy == STRATEGY) {
+            return Err("an ensemble can't contain another ensemble".to_string());
+        }
+        Ok(())
+    }
+
+    fn resolve_members(mut self, registry: &BotRegistry) -> Result<Self, BotConfigError> {
+        for (i, member) in self.members.iter_mut().enumerate() {
+            member.parameters = registry.resolve_parameters(&member.strategy, &member.parameters).map_err(|e| {
+                BotConfigError::InvalidParameters(format!("member {} ({}): {}", i + 1, member.strategy, e))
+            })?;
+        }
+        Ok(self)
+    }
+}
+
+/// Ensemble bot: ticks every member on the same context and trades only when at least `quorum`
+/// of them want to trade the same way, for the average of their quantities
+/// Limit orders don't vote; with a quorum of half or less, a split vote holds
+pub struct EnsembleBot {
+    members: Vec<Box<dyn TradingBot>>,
+    quorum: usize,
+    backers: Vec<(usize, f64)>, // Members whose order the last tick's decision carries, with the quantity each asked for
+}
+
+impl EnsembleBot {
+    pub fn new(members: Vec<Box<dyn TradingBot>>, quorum: usize) -> Self {
+        Self { members, quorum, backers: Vec::new() }
+    }
+
+    /// Create the members through `registry`, each with the ensemble's stoploss
+    pub fn with_params(registry: &BotRegistry, stoploss_amount: f64, params: EnsembleParams) -> Result<Self, BotConfigError> {
+        let members = params
+            .members
+            .iter()
+            .map(|member| registry.create(&member.strategy, stoploss_amount, &member.parameters))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(members, params.quorum))
+    }
+}
+
+/// Mean quantity of a side's votes
+fn average_of(votes: &[(usize, f64)]) -> f64 {
+    votes.iter().map(|(_, quantity)| quantity).sum::<f64>() / votes.len() as f64
+}
+
+impl TradingBot for EnsembleBot {
+    fn tick(&mut self, ctx: &BotContext) -> BotDecision {
+        // Every member ticks, so each keeps its own state current whatever the vote
+        let mut orders: Vec<(usize, TradeSide, f64)> = Vec::new();
+        let mut outvoted = Vec::new();
+        for (index, member) in self.members.iter_mut().enumerate() {
+            let decision = member.tick(ctx);
+            match decision.market_order(ctx.current_price, ctx.base_balance) {
+                Some((side, quantity)) if quantity.is_finite() && quantity > 0.0 => orders.push((index, side, quantity)),
+                _ if decision != BotDecision::DoNothing => outvoted.push(index),
+                _ => {}
+            }
+        }
+        let votes = |side: TradeSide| -> Vec<(usize, f64)> {
+            orders.iter().filter(|(_, s, _)| *s == side).map(|(index, _, quantity)| (*index, *quantity)).collect()
+        };
+        let (buys, sells) = (votes(TradeSide::Buy), votes(TradeSide::Sell));
+
+        let decision = match (buys.len() >= self.quorum, sells.len() >= self.quorum) {
+            (true, false) => {
+                self.backers = buys;
+                BotDecision::BuyBase { quantity: average_of(&self.backers) }
+            }
+            (false, true) => {
+                self.backers = sells;
+                BotDecision::SellBase { quantity: average_of(&self.backers) }
+            }
+            _ => {
+                self.backers = Vec::new();
+                BotDecision::DoNothing
+            }
+        };
+
+        // Members whose order isn't going out hear now that nothing traded
+        outvoted.extend(orders.iter().map(|(index, ..)| *index).filter(|index| !self.backers.iter().any(|(backer, _)| backer == index)));
+        for index in outvoted {
+            self.members[index].on_outcome(DecisionOutcome::Skipped);
+        }
+        decision
+    }
+
+    fn name(&self) -> &str {
+        "Ensemble"
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        let states = self.members.iter().map(|member| member.save_state().unwrap_or_default()).collect();
+        Some(serde_json::Value::Array(states))
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) {
+        let serde_json::Value::Array(states) = state else {
+            return; // Unreadable state: start fresh
+        };
+
+        for (member, state) in self.members.iter_mut().zip(states) {
+            if !state.is_null() {
+                member.restore_state(state);
+            }
+        }
+    }
+
+    fn warm_up(&mut self, prices: &[f64]) {
+        for member in &mut self.members {
+            member.warm_up(prices);
+        }
+    }
+
+    /// The first member's that has one; only one secondary asset's prices are fetched
+    fn secondary_asset(&self) -> Option<&str> {
+        self.members.iter().find_map(|member| member.secondary_asset())
+    }
+
+    /// Backers traded the averaged quantity, not their own
+    fn on_outcome(&mut self, outcome: DecisionOutcome) {
+        let traded = match outcome {
+            DecisionOutcome::Executed => Some(average_of(&self.backers)),
+            DecisionOutcome::Resized(quantity) => Some(quantity),
+            DecisionOutcome::Skipped => None,
+        };
+        for (index, asked) in std::mem::take(&mut self.backers) {
+            self.members[index].on_outcome(match traded {
+                Some(quantity) if quantity == asked => DecisionOutcome::Executed,
+                Some(quantity) => DecisionOutcome::Resized(quantity),
+                None => DecisionOutcome::Skipped,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::registry;
+
+    /// Plays back a fixed list of decisions, one per tick
+    struct Scripted(Vec<BotDecision>, usize);
+
+    impl TradingBot for Scripted {
+        fn tick(&mut self, _ctx: &BotContext) -> BotDecision {
+            self.1 += 1;
+            self.0.get(self.1 - 1).cloned().unwrap_or(BotDecision::DoNothing)
+        }
+
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn save_state(&self) -> Option<serde_json::Value> {
+            Some(serde_json::json!(self.1))
+        }
+
+        fn restore_state(&mut self, state: serde_json::Value) {
+            self.1 = state.as_u64().unwrap_or(0) as usize;
+        }
+    }
+
+    fn context() -> BotContext {
+        BotContext {
+            price_window: Vec::new(),
+            base_balance: 2.0,
+            quote_balance: 10_000.0,
+            current_price: 100.0,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            tick_count: 0,
+            signals: Vec::new(),
+            headlines: Vec::new(),
+            secondary: None,
+        }
+    }
+
+    fn ensemble(members: Vec<Vec<BotDecision>>, quorum: usize) -> EnsembleBot {
+        let members = members.into_iter().map(|decisions| Box::new(Scripted(decisions, 0)) as Box<dyn TradingBot>).collect();
+        EnsembleBot::new(members, quorum)
+    }
+
+    #[test]
+    fn test_trades_only_when_a_quorum_agrees() {
+        use BotDecision::*;
+        let mut bot = ensemble(
+            vec![
+                vec![Buy { quote_amount: 100.0 }, Buy { quote_amount: 100.0 }, ClosePosition, Buy { quote_amount: 100.0 }],
+                vec![DoNothing, BuyBase { quantity: 3.0 }, SellBase { quantity: 1.0 }, Sell { quote_amount: 100.0 }],
+                vec![DoNothing, DoNothing, DoNothing, PlaceLimit { side: TradeSide::Buy, price: 90.0, quantity: 1.0 }],
+            ],
+            2,
+        );
+        let decisions: Vec<BotDecision> = (0..4).map(|_| bot.tick(&context())).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                DoNothing,                      // One vote short
+                BuyBase { quantity: 2.0 },      // Average of 1 and 3
+                SellBase { quantity: 1.5 },     // Closing 2, selling 1
+                DoNothing,                      // Split, and limits don't vote
+            ]
+        );
+
+        // A low quorum holds on a split vote
+        let mut split = ensemble(vec![vec![Buy { quote_amount: 100.0 }], vec![Sell { quote_amount: 100.0 }]], 1);
+        assert_eq!(split.tick(&context()), DoNothing);
+
+        // Members resume where they left off
+        let mut restarted = ensemble(vec![vec![DoNothing, Buy { quote_amount: 100.0 }]; 2], 2);
+        restarted.restore_state(serde_json::json!([1, 1]));
+        assert_eq!(restarted.tick(&context()), BuyBase { quantity: 1.0 });
+    }
+
+    #[test]
+    fn test_members_resolve_through_the_registry() {
+        let resolved = registry().resolve_parameters("ensemble", &serde_json::Value::Null).unwrap();
+        assert_eq!(resolved["quorum"], 2);
+        assert_eq!(resolved["members"][1]["parameters"], serde_json::json!({"trend_ticks": 5, "step_pct": 1.0, "cooldown_ticks": 3}));
+        assert!(registry().create("ensemble", 1000.0, &serde_json::Value::Null).is_ok());
+
+        let invalid = |parameters: serde_json::Value| {
+            matches!(registry().create("ensemble", 1000.0, &parameters), Err(BotConfigError::InvalidParameters(_)))
+        };
+        let momentum = serde_json::json!({"strategy": "naive_momentum"});
+        assert!(invalid(serde_json::json!({"members": [momentum]})));
+        assert!(invalid(serde_json::json!({"members": [momentum, momentum], "quorum": 3})));
+        assert!(invalid(serde_json::json!({"members": [momentum, {"strategy": "martingale"}]})));
+        assert!(invalid(serde_json::json!({"members": [momentum, {"strategy": "naive_momentum", "parameters": {"trend_ticks": 50}}]})));
+        assert!(invalid(serde_json::json!({"members": [momentum, {"strategy": "ensemble"}]})));
+    }
+}
@@ -1,6 +1,250 @@
-use crate::models::PricePoint;
+use crate::models::{NewsItem, PricePoint, TradeSide};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
 
+pub mod ensemble;
 pub mod naive_momentum;
+pub mod risk_managed;
+pub mod schedule;
+pub mod script_bot;
+pub mod signal_follower;
+pub mod spread;
+
+use ensemble::{EnsembleBot, EnsembleParams};
+use naive_momentum::{MomentumParams, NaiveMomentumBot, VolumeMomentumParams};
+use script_bot::{ScriptBot, ScriptParams};
+use signal_follower::SignalFollowerBot;
+use spread::{SpreadBot, SpreadParams};
+
+/// Why a bot could not be constructed
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotConfigError {
+    UnknownStrategy(String),
+    InvalidParameters(String),
+}
+
+impl std::fmt::Display for BotConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotConfigError::UnknownStrategy(strategy) => write!(f, "Unknown strategy: {}", strategy),
+            BotConfigError::InvalidParameters(msg) => write!(f, "Invalid strategy parameters: {}", msg),
+        }
+    }
+}
+
+/// A strategy's settings as they arrive in a parameters object
+/// Fields left out keep their defaults (derive `Default` and use `#[serde(default)]`)
+pub trait StrategyParams: Serialize + DeserializeOwned {
+    /// Why the settings can't be traded with, if they can't
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Check and fill in the settings of the strategies these build on, for composite strategies
+    fn resolve_members(self, _registry: &BotRegistry) -> Result<Self, BotConfigError> {
+        Ok(self)
+    }
+}
+
+/// Parameters of a strategy without any
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NoParameters {}
+
+impl StrategyParams for NoParameters {}
+
+/// Read a strategy's parameters object; null counts as {} (all defaults)
+fn parse_parameters<T: StrategyParams>(registry: &BotRegistry, parameters: &serde_json::Value) -> Result<T, BotConfigError> {
+    let parameters = if parameters.is_null() { serde_json::json!({}) } else { parameters.clone() };
+    let parameters: T =
+        serde_json::from_value(parameters).map_err(|e| BotConfigError::InvalidParameters(e.to_string()))?;
+    parameters.validate().map_err(BotConfigError::InvalidParameters)?;
+    parameters.resolve_members(registry)
+}
+
+type BotFactory =
+    Box<dyn Fn(&BotRegistry, f64, &serde_json::Value) -> Result<Box<dyn TradingBot>, BotConfigError> + Send + Sync>;
+type ParameterResolver =
+    Box<dyn Fn(&BotRegistry, &serde_json::Value) -> Result<serde_json::Value, BotConfigError> + Send + Sync>;
+
+/// A registered strategy
+struct BotEntry {
+    info: BotInfo,
+    create: BotFactory,
+    resolve: ParameterResolver,
+}
+
+/// What a registered strategy is, for listing
+#[derive(Debug, Clone, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct BotInfo {
+    pub strategy: &'static str,     // Key used in requests and the bots table, e.g. "naive_momentum"
+    pub name: &'static str,         // Display name
+    pub description: &'static str,
+    #[schema(value_type = Object)]
+    pub default_parameters: serde_json::Value, // Every setting the strategy takes, at its default
+}
+
+/// The strategies bots can be started with, by key
+/// Add one with `register` in `BotRegistry::builtin`; routes, backtests and the optimizer pick it up
+pub struct BotRegistry {
+    entries: Vec<BotEntry>,
+}
+
+impl BotRegistry {
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Every strategy this build ships
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(
+            "naive_momentum",
+            "Naive Momentum",
+            "Buys after consecutive rising ticks and sells after consecutive falling ones, a step of the stoploss at a time",
+            |stoploss_amount, params: MomentumParams| Box::new(NaiveMomentumBot::with_params(stoploss_amount, params)),
+        );
+        registry.register(
+            "volume_momentum",
+            "Volume Momentum",
+            "Naive momentum that only trades a trend backed by above-average volume, skipping thinly traded moves",
+            |stoploss_amount, params: VolumeMomentumParams| {
+                Box::new(NaiveMomentumBot::with_volume_params(stoploss_amount, params))
+            },
+        );
+        registry.register(
+            "signal_follower",
+            "Signal Follower",
+            "Trades only on external signals such as TradingView webhooks, netting the signals of each tick",
+            |_, _: NoParameters| Box::new(SignalFollowerBot::new()),
+        );
+        registry.register(
+            "script",
+            "Script",
+            "Runs your own Rhai script: define on_tick(ctx) and return buy(amount), sell(amount) or hold()",
+            |stoploss_amount, params: ScriptParams| Box::new(ScriptBot::with_params(stoploss_amount, params)),
+        );
+        registry.register(
+            "spread",
+            "Spread",
+            "Trades the base against a correlated asset: buys when their price ratio falls well below its rolling mean, sells when it rises well above",
+            |stoploss_amount, params: SpreadParams| Box::new(SpreadBot::with_params(stoploss_amount, params)),
+        );
+        registry.register_composite(
+            "ensemble",
+            "Ensemble",
+            "Runs several strategies side by side and trades only when a quorum of them agree on the direction",
+            |registry, stoploss_amount, params: EnsembleParams| {
+                Ok(Box::new(EnsembleBot::with_params(registry, stoploss_amount, params)?))
+            },
+        );
+        registry
+    }
+
+    /// Add a strategy under `strategy`; `factory` gets the stoploss and checked parameters
+    /// A key registered twice keeps the first registration
+    pub fn register<P, F>(&mut self, strategy: &'static str, name: &'static str, description: &'static str, factory: F)
+    where
+        P: StrategyParams + 'static,
+        F: Fn(f64, P) -> Box<dyn TradingBot> + Send + Sync + 'static,
+    {
+        self.register_composite(strategy, name, description, move |_, stoploss_amount, params: P| {
+            Ok(factory(stoploss_amount, params))
+        });
+    }
+
+    /// Add a strategy built out of other strategies: `factory` also gets this registry to create them with,
+    /// and the parameters' `resolve_members` checks them
+    pub fn register_composite<P, F>(&mut self, strategy: &'static str, name: &'static str, description: &'static str, factory: F)
+    where
+        P: StrategyParams + 'static,
+        F: Fn(&BotRegistry, f64, P) -> Result<Box<dyn TradingBot>, BotConfigError> + Send + Sync + 'static,
+    {
+        if self.entries.iter().any(|entry| entry.info.strategy == strategy) {
+            return;
+        }
+        let resolve = |registry: &BotRegistry, parameters: &serde_json::Value| {
+            serde_json::to_value(parse_parameters::<P>(registry, parameters)?)
+                .map_err(|e| BotConfigError::InvalidParameters(e.to_string()))
+        };
+        let default_parameters = resolve(self, &serde_json::Value::Null).unwrap_or_default();
+        self.entries.push(BotEntry {
+            info: BotInfo { strategy, name, description, default_parameters },
+            create: Box::new(move |registry, stoploss_amount, parameters| {
+                factory(registry, stoploss_amount, parse_parameters(registry, parameters)?)
+            }),
+            resolve: Box::new(resolve),
+        });
+    }
+
+    fn entry(&self, strategy: &str) -> Result<&BotEntry, BotConfigError> {
+        self.entries
+            .iter()
+            .find(|entry| entry.info.strategy == strategy)
+            .ok_or_else(|| BotConfigError::UnknownStrategy(strategy.to_string()))
+    }
+
+    pub fn create(
+        &self,
+        strategy: &str,
+        stoploss_amount: f64,
+        parameters: &serde_json::Value,
+    ) -> Result<Box<dyn TradingBot>, BotConfigError> {
+        (self.entry(strategy)?.create)(self, stoploss_amount, parameters)
+    }
+
+    pub fn resolve_parameters(&self, strategy: &str, parameters: &serde_json::Value) -> Result<serde_json::Value, BotConfigError> {
+        (self.entry(strategy)?.resolve)(self, parameters)
+    }
+
+    /// The registered strategies, in registration order
+    pub fn list(&self) -> Vec<BotInfo> {
+        self.entries.iter().map(|entry| entry.info.clone()).collect()
+    }
+}
+
+impl Default for BotRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The built-in strategies, registered on first use
+pub fn registry() -> &'static BotRegistry {
+    static REGISTRY: OnceLock<BotRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(BotRegistry::builtin)
+}
+
+/// Construct a bot from its strategy key (as used in API requests and the bots table)
+/// `parameters` tunes the strategy; fields left out keep their defaults
+pub fn create_bot(
+    strategy: &str,
+    stoploss_amount: f64,
+    parameters: &serde_json::Value,
+) -> Result<Box<dyn TradingBot>, BotConfigError> {
+    registry().create(strategy, stoploss_amount, parameters)
+}
+
+/// Check a bot built for a pair can trade it: a secondary asset must be another asset than the base,
+/// or the ratio the bot trades on is always 1
+pub fn check_pair(bot: &dyn TradingBot, base_asset: &str) -> Result<(), BotConfigError> {
+    match bot.secondary_asset() {
+        Some(secondary) if secondary.eq_ignore_ascii_case(base_asset) => Err(BotConfigError::InvalidParameters(format!(
+            "secondary_asset must be another asset than the base asset {}",
+            base_asset
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// A strategy's parameters with every setting filled in, defaults included
+/// Fails like `create_bot` on unknown strategies or invalid parameters
+pub fn resolve_parameters(strategy: &str, parameters: &serde_json::Value) -> Result<serde_json::Value, BotConfigError> {
+    registry().resolve_parameters(strategy, parameters)
+}
 
 /// Core trait that all trading bots must implement
 pub trait TradingBot: Send {
@@ -10,13 +254,49 @@ pub trait TradingBot: Send {
 
     /// Bot display name for UI
     fn name(&self) -> &str;
+
+    /// Internal state to carry over a crash or restart (None = start fresh)
+    fn save_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Reload state produced by `save_state`
+    fn restore_state(&mut self, _state: serde_json::Value) {}
+
+    /// Take in minutely prices from before a fresh start, oldest first, so indicators are valid
+    /// from the first tick instead of after a warm-up; bots without price history ignore them
+    fn warm_up(&mut self, _prices: &[f64]) {}
+
+    /// An asset besides the pair's base whose prices the bot trades on; they arrive, in the
+    /// pair's quote asset, as `BotContext::secondary`
+    fn secondary_asset(&self) -> Option<&str> {
+        None
+    }
+
+    /// What came of the decision the last tick returned, told after every tick that decided
+    /// anything but DoNothing; bots that track a position commit it here, not when deciding
+    fn on_outcome(&mut self, _outcome: DecisionOutcome) {}
+}
+
+/// What came of a bot's decision, see `TradingBot::on_outcome`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecisionOutcome {
+    /// Carried out as decided: traded, rested as a limit order, or taken as traded by a dry run
+    /// or an A/B comparison's shadow account
+    Executed,
+    /// Traded, but this base quantity instead of the one decided (cut down by a limit, or averaged)
+    Resized(f64),
+    /// Rejected or held back: nothing traded
+    Skipped,
 }
 
 /// Immutable context passed to bot each tick
+/// Not every strategy reads every field, so some are unused by the bundled bots
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct BotContext {
     /// Raw 5s price data from polling window
-    /// Most recent prices (e.g., last 720 points = 1 hour)
+    /// Most recent prices, the whole configured window (default 720 points = 1 hour)
     pub price_window: Vec<PricePoint>,
 
     /// Current balances
@@ -32,10 +312,39 @@ pub struct BotContext {
 
     /// How many ticks since bot started (0-indexed)
     pub tick_count: u64,
+
+    /// External signals (e.g. TradingView webhooks) received since the last tick, oldest first
+    pub signals: Vec<ExternalSignal>,
+
+    /// News about the base asset from the last hour, newest first (empty in backtests and replays)
+    /// For experimental strategies; none of the bundled bots trade on headlines
+    pub headlines: Vec<NewsItem>,
+
+    /// Prices of the bot's `secondary_asset`, if it has one (None in backtests)
+    pub secondary: Option<SecondaryPrices>,
+}
+
+/// A second asset's prices in the pair's quote asset, for strategies comparing two assets
+#[derive(Debug, Clone)]
+pub struct SecondaryPrices {
+    pub asset: String,
+    pub price_window: Vec<PricePoint>, // Same span and spacing as the context's own price_window
+    pub current_price: f64,
+}
+
+/// A trade request from outside the simulator, queued for the user's running bot
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ExternalSignal {
+    pub source: String, // Where it came from, e.g. "tradingview"
+    pub side: TradeSide,
+    pub quantity: f64, // Base asset units
+    pub received_at: DateTime<Utc>,
 }
 
 /// Decision returned by bot after each tick
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
 pub enum BotDecision {
     /// Take no action this tick
     DoNothing,
@@ -47,41 +356,78 @@ pub enum BotDecision {
     /// Sell worth X in quote asset (e.g., "sell $100 worth of BTC")
     /// Framework converts to base quantity using current price
     Sell { quote_amount: f64 },
+
+    /// Buy an exact amount of the base asset at market (e.g., "buy 0.01 BTC")
+    BuyBase { quantity: f64 },
+
+    /// Sell an exact amount of the base asset at market
+    SellBase { quantity: f64 },
+
+    /// Sell the whole base balance at market
+    ClosePosition,
+
+    /// Rest a limit order on the book: buy at or below `price`, or sell at or above it
+    /// It fills like any user order, after the tick that placed it
+    PlaceLimit { side: TradeSide, price: f64, quantity: f64 },
+}
+
+impl BotDecision {
+    /// The market trade this decision asks for at `price`, as a side and base quantity
+    /// None for holding, limit orders, or a position to close that is empty
+    pub fn market_order(&self, price: f64, base_balance: f64) -> Option<(TradeSide, f64)> {
+        match *self {
+            BotDecision::Buy { quote_amount } => Some((TradeSide::Buy, quote_amount / price)),
+            BotDecision::Sell { quote_amount } => Some((TradeSide::Sell, quote_amount / price)),
+            BotDecision::BuyBase { quantity } => Some((TradeSide::Buy, quantity)),
+            BotDecision::SellBase { quantity } => Some((TradeSide::Sell, quantity)),
+            BotDecision::ClosePosition => (base_balance > 0.0).then_some((TradeSide::Sell, base_balance)),
+            BotDecision::DoNothing | BotDecision::PlaceLimit { .. } => None,
+        }
+    }
 }
 
-/// Bot template helper: maintains recent price history
-/// Useful for bots that need to track price movements
+/// Bot template helper: maintains recent price history, with the statistics strategies are built from
+/// Statistics cover every tracked price; size the history to the window a strategy needs
 #[derive(Debug, Clone)]
 pub struct PriceHistory {
-    prices: Vec<f64>,
+    prices: VecDeque<f64>, // Oldest first
     max_size: usize,
 }
 
 impl PriceHistory {
     pub fn new(max_size: usize) -> Self {
         Self {
-            prices: Vec::new(),
+            prices: VecDeque::with_capacity(max_size + 1),
             max_size,
         }
     }
 
     /// Add a new price (automatically maintains max_size)
     pub fn push(&mut self, price: f64) {
-        self.prices.push(price);
+        self.prices.push_back(price);
         if self.prices.len() > self.max_size {
-            self.prices.remove(0);
+            self.prices.pop_front();
         }
     }
 
-    /// Get all tracked prices
-    pub fn prices(&self) -> &[f64] {
-        &self.prices
+    /// Iterate over the tracked prices, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = f64> + ExactSizeIterator + '_ {
+        self.prices.iter().copied()
+    }
+
+    /// Get all tracked prices, oldest first
+    pub fn prices(&self) -> Vec<f64> {
+        self.iter().collect()
     }
 
     /// Get the most recent N prices (or fewer if not enough data)
-    pub fn last_n(&self, n: usize) -> &[f64] {
-        let start = self.prices.len().saturating_sub(n);
-        &self.prices[start..]
+    pub fn last_n(&self, n: usize) -> Vec<f64> {
+        self.iter().skip(self.len().saturating_sub(n)).collect()
+    }
+
+    /// The latest price
+    pub fn latest(&self) -> Option<f64> {
+        self.prices.back().copied()
     }
 
     /// Check if we have at least N prices
@@ -93,4 +439,154 @@ impl PriceHistory {
     pub fn len(&self) -> usize {
         self.prices.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    /// Average price
+    pub fn mean(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.iter().sum::<f64>() / self.len() as f64)
+    }
+
+    /// Population standard deviation of the prices (as Bollinger bands use)
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let variance = self.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / self.len() as f64;
+        Some(variance.sqrt())
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.iter().reduce(f64::min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.iter().reduce(f64::max)
+    }
+
+    /// Percent change from the price `n` ticks ago to the latest, e.g. 2.5 for +2.5%
+    /// None until more than `n` prices are tracked, or if that price was zero
+    pub fn pct_change(&self, n: usize) -> Option<f64> {
+        let latest = self.latest()?;
+        let then = *self.prices.get(self.len().checked_sub(n + 1)?)?;
+        if then == 0.0 {
+            return None;
+        }
+        Some((latest - then) / then * 100.0)
+    }
+
+    /// Tick-to-tick returns as fractions (0.01 for +1%), oldest first; one fewer than the prices
+    /// A return from a zero price is skipped
+    pub fn returns(&self) -> Vec<f64> {
+        self.iter()
+            .zip(self.iter().skip(1))
+            .filter(|(previous, _)| *previous != 0.0)
+            .map(|(previous, price)| price / previous - 1.0)
+            .collect()
+    }
+
+    /// Least-squares slope of the prices, in price per tick: positive in an uptrend
+    /// None with fewer than two prices
+    pub fn slope(&self) -> Option<f64> {
+        if self.len() < 2 {
+            return None;
+        }
+        let n = self.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = self.mean()?;
+        let (covariance, variance) = self.iter().enumerate().fold((0.0, 0.0), |(cov, var), (x, y)| {
+            let dx = x as f64 - mean_x;
+            (cov + dx * (y - mean_y), var + dx * dx)
+        });
+        Some(covariance / variance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history_of(prices: &[f64]) -> PriceHistory {
+        let mut history = PriceHistory::new(5);
+        for &price in prices {
+            history.push(price);
+        }
+        history
+    }
+
+    #[test]
+    fn test_registry_builds_registered_strategies() {
+        let strategies: Vec<&str> = registry().list().iter().map(|info| info.strategy).collect();
+        assert_eq!(strategies, vec!["naive_momentum", "volume_momentum", "signal_follower", "script", "spread", "ensemble"]);
+        assert_eq!(
+            registry().list()[0].default_parameters,
+            serde_json::json!({"trend_ticks": 3, "step_pct": 1.0, "cooldown_ticks": 3})
+        );
+
+        // New strategies plug in without touching the callers
+        let mut custom = BotRegistry::new();
+        custom.register("idle", "Idle", "Never trades", |_, _: NoParameters| Box::new(SignalFollowerBot::new()));
+        custom.register("idle", "Duplicate", "Ignored", |_, _: NoParameters| Box::new(SignalFollowerBot::new()));
+        assert_eq!(custom.list().len(), 1);
+        assert!(custom.create("idle", 100.0, &serde_json::Value::Null).is_ok());
+        assert!(matches!(
+            custom.create("idle", 100.0, &serde_json::json!({"speed": 1})),
+            Err(BotConfigError::InvalidParameters(_))
+        ));
+        assert!(matches!(custom.create("naive_momentum", 100.0, &serde_json::Value::Null), Err(BotConfigError::UnknownStrategy(_))));
+    }
+
+    #[test]
+    fn test_decisions_resolve_to_market_orders() {
+        let order = |decision: BotDecision| decision.market_order(50.0, 3.0);
+        assert_eq!(order(BotDecision::Buy { quote_amount: 100.0 }), Some((TradeSide::Buy, 2.0)));
+        assert_eq!(order(BotDecision::Sell { quote_amount: 25.0 }), Some((TradeSide::Sell, 0.5)));
+        assert_eq!(order(BotDecision::BuyBase { quantity: 0.1 }), Some((TradeSide::Buy, 0.1)));
+        assert_eq!(order(BotDecision::SellBase { quantity: 1.5 }), Some((TradeSide::Sell, 1.5)));
+        assert_eq!(order(BotDecision::ClosePosition), Some((TradeSide::Sell, 3.0)));
+        assert_eq!(BotDecision::ClosePosition.market_order(50.0, 0.0), None);
+        assert_eq!(order(BotDecision::PlaceLimit { side: TradeSide::Buy, price: 45.0, quantity: 1.0 }), None);
+        assert_eq!(order(BotDecision::DoNothing), None);
+
+        // Logged and streamed as tagged JSON
+        assert_eq!(
+            serde_json::to_value(BotDecision::PlaceLimit { side: TradeSide::Sell, price: 60.0, quantity: 0.5 }).unwrap(),
+            serde_json::json!({"action": "place_limit", "side": "Sell", "price": 60.0, "quantity": 0.5})
+        );
+        assert_eq!(serde_json::to_value(BotDecision::ClosePosition).unwrap(), serde_json::json!({"action": "close_position"}));
+    }
+
+    #[test]
+    fn test_price_history_keeps_the_latest_window() {
+        let history = history_of(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(history.prices(), vec![3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(history.last_n(2), vec![6.0, 7.0]);
+        assert_eq!(history.last_n(10).len(), 5);
+        assert_eq!(history.latest(), Some(7.0));
+    }
+
+    #[test]
+    fn test_price_history_statistics() {
+        let history = history_of(&[2.0, 4.0, 4.0, 5.0, 5.0]);
+        assert_eq!(history.mean(), Some(4.0));
+        assert!((history.std_dev().unwrap() - 1.095_445).abs() < 1e-6);
+        assert_eq!((history.min(), history.max()), (Some(2.0), Some(5.0)));
+        assert_eq!(history.pct_change(1), Some(0.0));
+        assert_eq!(history.pct_change(4), Some(150.0));
+        assert_eq!(history.pct_change(5), None);
+        assert_eq!(history.returns(), vec![1.0, 0.0, 0.25, 0.0]);
+        assert!((history.slope().unwrap() - 0.7).abs() < 1e-9);
+
+        // A straight line's slope is its step, and a flat series has no spread
+        assert_eq!(history_of(&[10.0, 8.0, 6.0]).slope(), Some(-2.0));
+        assert_eq!(history_of(&[3.0, 3.0]).std_dev(), Some(0.0));
+
+        let empty = PriceHistory::new(5);
+        assert_eq!((empty.mean(), empty.std_dev(), empty.min(), empty.slope()), (None, None, None, None));
+        assert!(empty.returns().is_empty());
+        assert_eq!(history_of(&[1.0]).slope(), None);
+    }
 }
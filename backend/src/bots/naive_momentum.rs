@@ -1,10 +1,18 @@
-use super::{BotContext, BotDecision, PriceHistory, TradingBot};
+use super::{BotContext, BotDecision, PriceHistory, StrategyParams, TradingBot};
+use crate::models::PricePoint;
+use serde::{Deserialize, Serialize};
 
 /// Naive momentum bot: Buys on 3 consecutive price increases, sells on 3 consecutive decreases
 /// Uses 1% of stoploss as step size, enforces 3-tick cooldown after each trade
+/// (all three are tunable through `MomentumParams`)
+/// The volume momentum variant also needs the trend to come on above-average volume; where the
+/// prices carry no volume (live spot polls) it trades the plain trend
 pub struct NaiveMomentumBot {
     // Configuration (set at initialization)
-    stepsize_quote: f64, // 1% of stoploss amount
+    stepsize_quote: f64, // step_pct of stoploss amount
+    trend_ticks: usize,
+    cooldown_ticks: u32,
+    volume_factor: Option<f64>, // Recent volume needed, as a multiple of the window's average
 
     // Internal state (tracked across ticks)
     price_history: PriceHistory,  // Template helper for tracking prices
@@ -16,13 +24,109 @@ pub struct NaiveMomentumBot {
     last_action: String,
 }
 
+/// Tunable settings; fields left out of a parameters object keep these defaults
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MomentumParams {
+    pub trend_ticks: usize,  // Consecutive rises (falls) that trigger a buy (sell)
+    pub step_pct: f64,       // Trade size, as a percent of the stoploss
+    pub cooldown_ticks: u32, // Ticks skipped after a trade
+}
+
+impl Default for MomentumParams {
+    fn default() -> Self {
+        Self {
+            trend_ticks: 3,
+            step_pct: 1.0,
+            cooldown_ticks: 3,
+        }
+    }
+}
+
+impl StrategyParams for MomentumParams {
+    fn validate(&self) -> Result<(), String> {
+        if !(2..=20).contains(&self.trend_ticks) {
+            return Err("trend_ticks must be between 2 and 20".to_string());
+        }
+        if !self.step_pct.is_finite() || self.step_pct <= 0.0 || self.step_pct > 100.0 {
+            return Err("step_pct must be above 0 and at most 100".to_string());
+        }
+        if self.cooldown_ticks > 100 {
+            return Err("cooldown_ticks must be at most 100".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Tunable settings of the volume momentum variant; fields left out keep these defaults
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VolumeMomentumParams {
+    pub trend_ticks: usize,
+    pub step_pct: f64,
+    pub cooldown_ticks: u32,
+    pub volume_factor: f64, // Average volume over the trend, as a multiple of the window's, needed to trade it
+}
+
+impl Default for VolumeMomentumParams {
+    fn default() -> Self {
+        let MomentumParams { trend_ticks, step_pct, cooldown_ticks } = MomentumParams::default();
+        Self { trend_ticks, step_pct, cooldown_ticks, volume_factor: 1.5 }
+    }
+}
+
+impl From<VolumeMomentumParams> for MomentumParams {
+    fn from(params: VolumeMomentumParams) -> Self {
+        Self { trend_ticks: params.trend_ticks, step_pct: params.step_pct, cooldown_ticks: params.cooldown_ticks }
+    }
+}
+
+impl StrategyParams for VolumeMomentumParams {
+    fn validate(&self) -> Result<(), String> {
+        MomentumParams::from(*self).validate()?;
+        if !self.volume_factor.is_finite() || self.volume_factor <= 0.0 || self.volume_factor > 10.0 {
+            return Err("volume_factor must be above 0 and at most 10".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Average volume per point over the window's last `recent` points, relative to the whole window's
+/// Only points that carry a volume count; None when the recent ones carry none
+fn volume_ratio(window: &[PricePoint], recent: usize) -> Option<f64> {
+    let mean = |volumes: Vec<f64>| (!volumes.is_empty()).then(|| volumes.iter().sum::<f64>() / volumes.len() as f64);
+
+    let overall = mean(window.iter().filter_map(|p| p.volume).collect())?;
+    let latest = mean(window[window.len().saturating_sub(recent)..].iter().filter_map(|p| p.volume).collect())?;
+    (overall > 0.0).then(|| latest / overall)
+}
+
+/// Internal state kept across restarts (configuration is rebuilt from the stoploss)
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    prices: Vec<f64>,
+    cooldown_remaining: u32,
+    total_buys: u32,
+    total_sells: u32,
+    last_action: String,
+}
+
 impl NaiveMomentumBot {
     /// Create new bot with given stoploss amount
     /// Stepsize is automatically set to 1% of stoploss
+    #[allow(dead_code)] // Bots are built through `create_bot`, which always has parameters
     pub fn new(stoploss_amount: f64) -> Self {
+        Self::with_params(stoploss_amount, MomentumParams::default())
+    }
+
+    /// Create a bot with tuned settings (checked with `MomentumParams::validate`)
+    pub fn with_params(stoploss_amount: f64, params: MomentumParams) -> Self {
         Self {
-            stepsize_quote: stoploss_amount * 0.01, // 1% of stoploss
-            price_history: PriceHistory::new(10),    // Track last 10 prices (more than we need)
+            stepsize_quote: stoploss_amount * params.step_pct / 100.0,
+            trend_ticks: params.trend_ticks,
+            cooldown_ticks: params.cooldown_ticks,
+            volume_factor: None,
+            price_history: PriceHistory::new(params.trend_ticks.max(10)), // Never fewer than the trend needs
             cooldown_remaining: 0,
             total_buys: 0,
             total_sells: 0,
@@ -30,24 +134,32 @@ impl NaiveMomentumBot {
         }
     }
 
-    /// Check if last 3 prices show consecutive increases
+    /// Create the volume momentum variant (checked with `VolumeMomentumParams::validate`)
+    pub fn with_volume_params(stoploss_amount: f64, params: VolumeMomentumParams) -> Self {
+        Self {
+            volume_factor: Some(params.volume_factor),
+            ..Self::with_params(stoploss_amount, params.into())
+        }
+    }
+
+    /// Check if the last `trend_ticks` prices show consecutive increases
     fn is_uptrend(&self) -> bool {
-        if !self.price_history.has_at_least(3) {
+        if !self.price_history.has_at_least(self.trend_ticks) {
             return false;
         }
 
-        let recent = self.price_history.last_n(3);
-        recent[1] > recent[0] && recent[2] > recent[1]
+        let recent = self.price_history.last_n(self.trend_ticks);
+        recent.windows(2).all(|pair| pair[1] > pair[0])
     }
 
-    /// Check if last 3 prices show consecutive decreases
+    /// Check if the last `trend_ticks` prices show consecutive decreases
     fn is_downtrend(&self) -> bool {
-        if !self.price_history.has_at_least(3) {
+        if !self.price_history.has_at_least(self.trend_ticks) {
             return false;
         }
 
-        let recent = self.price_history.last_n(3);
-        recent[1] < recent[0] && recent[2] < recent[1]
+        let recent = self.price_history.last_n(self.trend_ticks);
+        recent.windows(2).all(|pair| pair[1] < pair[0])
     }
 }
 
@@ -63,17 +175,31 @@ impl TradingBot for NaiveMomentumBot {
             return BotDecision::DoNothing;
         }
 
-        // Need at least 3 prices to detect trend
-        if !self.price_history.has_at_least(3) {
+        // Need enough prices to detect a trend
+        if !self.price_history.has_at_least(self.trend_ticks) {
             self.last_action = "warming up".to_string();
             return BotDecision::DoNothing;
         }
 
+        // The volume variant only trades a trend that comes on above-average volume, when there is
+        // volume to check: live spot polls carry none, so there it trades the plain trend
+        let mut unconfirmed = "";
+        if let Some(factor) = self.volume_factor.filter(|_| self.is_uptrend() || self.is_downtrend()) {
+            match volume_ratio(&ctx.price_window, self.trend_ticks) {
+                None => unconfirmed = " (no volume data)",
+                Some(ratio) if ratio < factor => {
+                    self.last_action = format!("trend on low volume ({:.2}x)", ratio);
+                    return BotDecision::DoNothing;
+                }
+                Some(_) => {}
+            }
+        }
+
         // Check for uptrend -> Buy
         if self.is_uptrend() {
-            self.cooldown_remaining = 3;
+            self.cooldown_remaining = self.cooldown_ticks;
             self.total_buys += 1;
-            self.last_action = format!("buy ${:.2}", self.stepsize_quote);
+            self.last_action = format!("buy ${:.2}{}", self.stepsize_quote, unconfirmed);
             return BotDecision::Buy {
                 quote_amount: self.stepsize_quote,
             };
@@ -81,9 +207,9 @@ impl TradingBot for NaiveMomentumBot {
 
         // Check for downtrend -> Sell
         if self.is_downtrend() {
-            self.cooldown_remaining = 3;
+            self.cooldown_remaining = self.cooldown_ticks;
             self.total_sells += 1;
-            self.last_action = format!("sell ${:.2}", self.stepsize_quote);
+            self.last_action = format!("sell ${:.2}{}", self.stepsize_quote, unconfirmed);
             return BotDecision::Sell {
                 quote_amount: self.stepsize_quote,
             };
@@ -95,15 +221,49 @@ impl TradingBot for NaiveMomentumBot {
     }
 
     fn name(&self) -> &str {
-        "Naive Momentum"
+        if self.volume_factor.is_some() {
+            "Volume Momentum"
+        } else {
+            "Naive Momentum"
+        }
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(SavedState {
+            prices: self.price_history.prices(),
+            cooldown_remaining: self.cooldown_remaining,
+            total_buys: self.total_buys,
+            total_sells: self.total_sells,
+            last_action: self.last_action.clone(),
+        })
+        .ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) {
+        let Ok(saved) = serde_json::from_value::<SavedState>(state) else {
+            return; // Unreadable state: start fresh
+        };
+
+        for price in saved.prices {
+            self.price_history.push(price);
+        }
+        self.cooldown_remaining = saved.cooldown_remaining;
+        self.total_buys = saved.total_buys;
+        self.total_sells = saved.total_sells;
+        self.last_action = saved.last_action;
+    }
+
+    fn warm_up(&mut self, prices: &[f64]) {
+        for &price in prices {
+            self.price_history.push(price);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::PricePoint;
-    use chrono::Utc;
+    use chrono::{Duration as ChronoDuration, TimeZone, Utc};
 
     fn create_test_context(prices: Vec<f64>, current_price: f64) -> BotContext {
         let price_window = prices
@@ -112,6 +272,7 @@ mod tests {
                 timestamp: Utc::now(),
                 asset: "BTC".to_string(),
                 price: p,
+                volume: None,
             })
             .collect();
 
@@ -123,6 +284,9 @@ mod tests {
             base_asset: "BTC".to_string(),
             quote_asset: "USD".to_string(),
             tick_count: 0,
+            signals: Vec::new(),
+            headlines: Vec::new(),
+            secondary: None,
         }
     }
 
@@ -144,6 +308,15 @@ mod tests {
         assert_eq!(bot.cooldown_remaining, 3);
     }
 
+    #[test]
+    fn test_warm_up_prices_count_from_the_first_tick() {
+        let mut bot = NaiveMomentumBot::new(10000.0);
+        bot.warm_up(&[95.0, 100.0, 105.0]);
+
+        let decision = bot.tick(&create_test_context(vec![], 110.0));
+        assert_eq!(decision, BotDecision::Buy { quote_amount: 100.0 });
+    }
+
     #[test]
     fn test_downtrend_detection() {
         let mut bot = NaiveMomentumBot::new(10000.0);
@@ -186,4 +359,86 @@ mod tests {
 
         assert_eq!(decision, BotDecision::DoNothing);
     }
+
+    #[test]
+    fn test_state_survives_restore() {
+        let mut bot = NaiveMomentumBot::new(10000.0);
+        bot.tick(&create_test_context(vec![], 100.0));
+        bot.tick(&create_test_context(vec![], 105.0));
+
+        // A restored bot continues the trend the original was tracking
+        let mut restored = NaiveMomentumBot::new(10000.0);
+        restored.restore_state(bot.save_state().unwrap());
+        assert_eq!(
+            restored.tick(&create_test_context(vec![], 110.0)),
+            BotDecision::Buy { quote_amount: 100.0 }
+        );
+        assert_eq!(restored.total_buys, 1);
+    }
+
+    #[test]
+    fn test_parameters_tune_trend_size_and_cooldown() {
+        let params = MomentumParams { trend_ticks: 4, step_pct: 5.0, cooldown_ticks: 1 };
+        let mut bot = NaiveMomentumBot::with_params(10000.0, params);
+
+        // Three rises are no longer a trend; four are, buying 5% of the stoploss
+        for price in [100.0, 101.0, 102.0] {
+            assert_eq!(bot.tick(&create_test_context(vec![], price)), BotDecision::DoNothing);
+        }
+        assert_eq!(bot.tick(&create_test_context(vec![], 103.0)), BotDecision::Buy { quote_amount: 500.0 });
+        assert_eq!(bot.tick(&create_test_context(vec![], 104.0)), BotDecision::DoNothing); // Cooldown
+        assert_eq!(bot.tick(&create_test_context(vec![], 105.0)), BotDecision::Buy { quote_amount: 500.0 });
+
+        // Parameters objects: defaults fill gaps, unknown fields and bad values are rejected
+        let create = |parameters: serde_json::Value| crate::bots::create_bot("naive_momentum", 1000.0, &parameters);
+        assert!(create(serde_json::Value::Null).is_ok());
+        assert!(create(serde_json::json!({"trend_ticks": 5})).is_ok());
+        assert!(create(serde_json::json!({"trend_tick": 5})).is_err());
+        assert!(create(serde_json::json!({"step_pct": 0})).is_err());
+        assert!(matches!(
+            crate::bots::create_bot("nope", 1000.0, &serde_json::Value::Null),
+            Err(crate::bots::BotConfigError::UnknownStrategy(_))
+        ));
+    }
+
+    #[test]
+    fn test_volume_variant_needs_volume_behind_the_trend() {
+        // Sixty hourly candles trading 1.0 each; the last `volumes` are the recent ones
+        let context = |volumes: &[Option<f64>], current_price: f64| {
+            let start = Utc.with_ymd_and_hms(2025, 3, 3, 12, 0, 0).unwrap();
+            let mut window: Vec<Option<f64>> = vec![Some(1.0); 60 - volumes.len()];
+            window.extend_from_slice(volumes);
+            let price_window = window
+                .into_iter()
+                .enumerate()
+                .map(|(i, volume)| PricePoint {
+                    timestamp: start + ChronoDuration::hours(i as i64),
+                    asset: "BTC".to_string(),
+                    price: current_price,
+                    volume,
+                })
+                .collect();
+            BotContext { price_window, ..create_test_context(vec![], current_price) }
+        };
+        let params = VolumeMomentumParams { cooldown_ticks: 0, ..Default::default() };
+        assert!(params.validate().is_ok());
+        let mut bot = NaiveMomentumBot::with_volume_params(10000.0, params);
+        assert_eq!(bot.name(), "Volume Momentum");
+        bot.warm_up(&[100.0, 101.0]);
+
+        // A breakout on ordinary volume is a whipsaw risk: hold
+        assert_eq!(bot.tick(&context(&[Some(1.2); 3], 102.0)), BotDecision::DoNothing);
+        assert_eq!(bot.last_action, "trend on low volume (1.19x)");
+
+        // Three times the usual volume over the trend confirms it
+        assert_eq!(bot.tick(&context(&[Some(3.0); 3], 103.0)), BotDecision::Buy { quote_amount: 100.0 });
+        assert_eq!(bot.last_action, "buy $100.00");
+
+        // Live spot polls carry no volume, so the plain trend is traded
+        assert_eq!(bot.tick(&context(&[None; 60], 104.0)), BotDecision::Buy { quote_amount: 100.0 });
+        assert_eq!(bot.last_action, "buy $100.00 (no volume data)");
+
+        assert!(VolumeMomentumParams { volume_factor: 0.0, ..Default::default() }.validate().is_err());
+        assert!(VolumeMomentumParams { trend_ticks: 50, ..Default::default() }.validate().is_err());
+    }
 }
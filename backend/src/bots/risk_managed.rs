@@ -0,0 +1,270 @@
+use super::{BotContext, BotDecision, DecisionOutcome, TradingBot};
+use crate::models::TradeSide;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Ticks are a minute apart, so an hour is this many
+const TICKS_PER_HOUR: u64 = 60;
+
+/// Limits on what any bot may trade, on top of its stoploss; unset ones don't apply
+/// Values are in the quote asset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct TradeLimits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_position_value: Option<f64>, // Most the base holding may be worth after a buy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_trades_per_hour: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_trade_value: Option<f64>, // Largest single trade
+}
+
+impl TradeLimits {
+    /// No limit set
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [("max_position_value", self.max_position_value), ("max_trade_value", self.max_trade_value)] {
+            if value.is_some_and(|value| !value.is_finite() || value <= 0.0) {
+                return Err(format!("{} must be positive", name));
+            }
+        }
+        if self.max_trades_per_hour == Some(0) {
+            return Err("max_trades_per_hour must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Wraps any bot and holds its decisions to `TradeLimits`: trades over the hourly count are vetoed,
+/// and trades too large for the single-trade or position limit are cut down to fit
+pub struct RiskManagedBot {
+    inner: Box<dyn TradingBot>,
+    limits: TradeLimits,
+    trade_ticks: VecDeque<u64>, // Ticks of the trades let through in the last hour, oldest first
+    cut_to: Option<f64>,        // Base quantity the last tick's decision was cut down to
+}
+
+/// Internal state kept across restarts: the wrapped bot's, and the recent trades
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    inner: Option<serde_json::Value>,
+    trade_ticks: Vec<u64>,
+}
+
+impl RiskManagedBot {
+    pub fn new(inner: Box<dyn TradingBot>, limits: TradeLimits) -> Self {
+        Self { inner, limits, trade_ticks: VecDeque::new(), cut_to: None }
+    }
+
+    /// The decision as the limits allow it, or why they veto it
+    fn enforce(&self, decision: BotDecision, ctx: &BotContext) -> Result<BotDecision, String> {
+        if decision == BotDecision::DoNothing {
+            return Ok(decision);
+        }
+        if let Some(max) = self.limits.max_trades_per_hour {
+            if self.trade_ticks.len() >= max as usize {
+                return Err(format!("already {} trades in the last hour", self.trade_ticks.len()));
+            }
+        }
+
+        // Side, base quantity, and the price the trade is valued at
+        let (side, quantity, price) = match &decision {
+            BotDecision::PlaceLimit { side, price, quantity } => (side.clone(), *quantity, *price),
+            _ => match decision.market_order(ctx.current_price, ctx.base_balance) {
+                Some((side, quantity)) => (side, quantity, ctx.current_price),
+                None => return Ok(decision),
+            },
+        };
+        if !price.is_finite() || price <= 0.0 {
+            return Ok(decision); // Nothing to value it by; execution rejects it
+        }
+
+        let mut max_quantity = f64::INFINITY;
+        if let Some(max_value) = self.limits.max_trade_value {
+            max_quantity = max_quantity.min(max_value / price);
+        }
+        if side == TradeSide::Buy {
+            if let Some(max_position) = self.limits.max_position_value {
+                let room = max_position / price - ctx.base_balance.max(0.0);
+                if room <= 0.0 {
+                    return Err(format!("position already at its {:.2} limit", max_position));
+                }
+                max_quantity = max_quantity.min(room);
+            }
+        }
+        if quantity <= max_quantity {
+            return Ok(decision);
+        }
+
+        // Cut down, keeping the kind of order
+        Ok(match decision {
+            BotDecision::Buy { .. } => BotDecision::Buy { quote_amount: max_quantity * price },
+            BotDecision::Sell { .. } => BotDecision::Sell { quote_amount: max_quantity * price },
+            BotDecision::BuyBase { .. } => BotDecision::BuyBase { quantity: max_quantity },
+            BotDecision::SellBase { .. } | BotDecision::ClosePosition => BotDecision::SellBase { quantity: max_quantity },
+            BotDecision::PlaceLimit { side, price, .. } => BotDecision::PlaceLimit { side, price, quantity: max_quantity },
+            BotDecision::DoNothing => BotDecision::DoNothing,
+        })
+    }
+}
+
+impl TradingBot for RiskManagedBot {
+    fn tick(&mut self, ctx: &BotContext) -> BotDecision {
+        while self.trade_ticks.front().is_some_and(|tick| tick + TICKS_PER_HOUR <= ctx.tick_count) {
+            self.trade_ticks.pop_front();
+        }
+
+        self.cut_to = None;
+        let decision = self.inner.tick(ctx);
+        match self.enforce(decision.clone(), ctx) {
+            Ok(allowed) => {
+                if allowed != decision {
+                    tracing::info!("Trade limits cut {:?} to {:?} for bot '{}'", decision, allowed, self.inner.name());
+                    self.cut_to = match &allowed {
+                        BotDecision::PlaceLimit { quantity, .. } => Some(*quantity),
+                        _ => allowed.market_order(ctx.current_price, ctx.base_balance).map(|(_, quantity)| quantity),
+                    };
+                }
+                if allowed != BotDecision::DoNothing {
+                    self.trade_ticks.push_back(ctx.tick_count);
+                }
+                allowed
+            }
+            Err(reason) => {
+                tracing::info!("Trade limits vetoed {:?} for bot '{}': {}", decision, self.inner.name(), reason);
+                self.inner.on_outcome(DecisionOutcome::Skipped);
+                BotDecision::DoNothing
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(SavedState {
+            inner: self.inner.save_state(),
+            trade_ticks: self.trade_ticks.iter().copied().collect(),
+        })
+        .ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) {
+        let Ok(saved) = serde_json::from_value::<SavedState>(state) else {
+            return; // Unreadable state: start fresh
+        };
+
+        if let Some(inner) = saved.inner {
+            self.inner.restore_state(inner);
+        }
+        self.trade_ticks = saved.trade_ticks.into();
+    }
+
+    fn warm_up(&mut self, prices: &[f64]) {
+        self.inner.warm_up(prices);
+    }
+
+    fn secondary_asset(&self) -> Option<&str> {
+        self.inner.secondary_asset()
+    }
+
+    fn on_outcome(&mut self, outcome: DecisionOutcome) {
+        let outcome = match (outcome, self.cut_to.take()) {
+            (DecisionOutcome::Executed, Some(quantity)) => DecisionOutcome::Resized(quantity),
+            (outcome, _) => outcome,
+        };
+        self.inner.on_outcome(outcome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Makes the same decision every tick
+    struct Always(BotDecision);
+
+    impl TradingBot for Always {
+        fn tick(&mut self, _ctx: &BotContext) -> BotDecision {
+            self.0.clone()
+        }
+
+        fn name(&self) -> &str {
+            "Always"
+        }
+    }
+
+    fn context(base_balance: f64, tick_count: u64) -> BotContext {
+        BotContext {
+            price_window: Vec::new(),
+            base_balance,
+            quote_balance: 10_000.0,
+            current_price: 100.0,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            tick_count,
+            signals: Vec::new(),
+            headlines: Vec::new(),
+            secondary: None,
+        }
+    }
+
+    fn managed(decision: BotDecision, limits: TradeLimits) -> RiskManagedBot {
+        RiskManagedBot::new(Box::new(Always(decision)), limits)
+    }
+
+    #[test]
+    fn test_trades_are_cut_to_the_value_and_position_limits() {
+        let limits = TradeLimits { max_trade_value: Some(500.0), max_position_value: Some(1_200.0), ..Default::default() };
+
+        let mut buyer = managed(BotDecision::Buy { quote_amount: 800.0 }, limits);
+        assert_eq!(buyer.tick(&context(0.0, 0)), BotDecision::Buy { quote_amount: 500.0 });
+        // Holding $1,000 leaves room for $200 more
+        assert_eq!(buyer.tick(&context(10.0, 1)), BotDecision::Buy { quote_amount: 200.0 });
+        assert_eq!(buyer.tick(&context(12.0, 2)), BotDecision::DoNothing);
+
+        // Sells are never held back by the position limit, only by the trade size
+        let mut closer = managed(BotDecision::ClosePosition, limits);
+        assert_eq!(closer.tick(&context(12.0, 0)), BotDecision::SellBase { quantity: 5.0 });
+        assert_eq!(closer.tick(&context(3.0, 1)), BotDecision::ClosePosition);
+
+        // Limit orders are valued at their own price
+        let limit = BotDecision::PlaceLimit { side: TradeSide::Buy, price: 50.0, quantity: 20.0 };
+        assert_eq!(
+            managed(limit, limits).tick(&context(0.0, 0)),
+            BotDecision::PlaceLimit { side: TradeSide::Buy, price: 50.0, quantity: 10.0 }
+        );
+    }
+
+    #[test]
+    fn test_hourly_trade_count_vetoes_until_the_hour_passes() {
+        let limits = TradeLimits { max_trades_per_hour: Some(2), ..Default::default() };
+        let mut bot = managed(BotDecision::Sell { quote_amount: 10.0 }, limits);
+        let decisions: Vec<BotDecision> = [0, 1, 2, 59, 60, 61].iter().map(|&tick| bot.tick(&context(1.0, tick))).collect();
+        let sell = BotDecision::Sell { quote_amount: 10.0 };
+        assert_eq!(
+            decisions,
+            vec![sell.clone(), sell.clone(), BotDecision::DoNothing, BotDecision::DoNothing, sell.clone(), sell]
+        );
+
+        // The count survives a restart
+        let mut restarted = managed(BotDecision::Sell { quote_amount: 10.0 }, limits);
+        restarted.restore_state(bot.save_state().unwrap());
+        assert_eq!(restarted.tick(&context(1.0, 62)), BotDecision::DoNothing);
+    }
+
+    #[test]
+    fn test_limits_validate() {
+        assert!(TradeLimits::default().is_unlimited());
+        assert!(TradeLimits { max_trade_value: Some(0.0), ..Default::default() }.validate().is_err());
+        assert!(TradeLimits { max_position_value: Some(f64::NAN), ..Default::default() }.validate().is_err());
+        assert!(TradeLimits { max_trades_per_hour: Some(0), ..Default::default() }.validate().is_err());
+        assert!(TradeLimits { max_trades_per_hour: Some(3), max_trade_value: Some(100.0), ..Default::default() }
+            .validate()
+            .is_ok());
+    }
+}
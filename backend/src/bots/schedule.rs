@@ -0,0 +1,115 @@
+use crate::services::timezone_service;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Why a bot outside its schedule isn't trading, as reported in its status
+pub const PAUSED_BY_SCHEDULE: &str = "paused by schedule";
+
+/// When a bot may trade: a daily window, on some days of the week, in a fixed timezone
+/// Outside it the bot still ticks (so its indicators keep up) but its decisions aren't traded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BotSchedule {
+    pub start: String, // "HH:MM", when trading starts each day
+    pub end: String,   // "HH:MM", when it stops; earlier than start for a window across midnight, equal for all day
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<String>, // "mon" to "sun", the days a window starts on; empty for every day
+    #[serde(default)]
+    pub utc_offset_minutes: i32, // Timezone of the times and days; 0 for UTC
+}
+
+fn parse_day(day: &str) -> Result<Weekday, String> {
+    match day.to_ascii_lowercase().as_str() {
+        "mon" => Ok(Weekday::Mon),
+        "tue" => Ok(Weekday::Tue),
+        "wed" => Ok(Weekday::Wed),
+        "thu" => Ok(Weekday::Thu),
+        "fri" => Ok(Weekday::Fri),
+        "sat" => Ok(Weekday::Sat),
+        "sun" => Ok(Weekday::Sun),
+        _ => Err(format!("Unknown day {:?} (use mon, tue, wed, thu, fri, sat or sun)", day)),
+    }
+}
+
+impl BotSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        timezone_service::parse_time(&self.start)?;
+        timezone_service::parse_time(&self.end)?;
+        timezone_service::offset(self.utc_offset_minutes)?;
+        for day in &self.days {
+            parse_day(day)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `at` falls in the window; an invalid schedule is never active
+    pub fn is_active(&self, at: DateTime<Utc>) -> bool {
+        let (Ok(start), Ok(end), Ok(offset)) = (
+            timezone_service::parse_time(&self.start),
+            timezone_service::parse_time(&self.end),
+            timezone_service::offset(self.utc_offset_minutes),
+        ) else {
+            return false;
+        };
+        let local = at.with_timezone(&offset);
+        let time = local.time();
+
+        // The day the window containing `at` started on, if one does
+        let window_day = if start < end {
+            (start <= time && time < end).then(|| local.weekday())
+        } else if time >= start || start == end {
+            Some(local.weekday())
+        } else if time < end {
+            Some((local - ChronoDuration::days(1)).weekday())
+        } else {
+            None
+        };
+        window_day.is_some_and(|day| self.days.is_empty() || self.days.iter().any(|d| parse_day(d) == Ok(day)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn schedule(start: &str, end: &str, days: &[&str], utc_offset_minutes: i32) -> BotSchedule {
+        BotSchedule {
+            start: start.to_string(),
+            end: end.to_string(),
+            days: days.iter().map(|d| d.to_string()).collect(),
+            utc_offset_minutes,
+        }
+    }
+
+    #[test]
+    fn test_schedule_windows() {
+        // 2025-03-03 is a Monday
+        let at = |day: u32, hour: u32, minute: u32| Utc.with_ymd_and_hms(2025, 3, day, hour, minute, 0).unwrap();
+
+        let weekdays = schedule("13:00", "21:00", &["mon", "tue", "wed", "thu", "fri"], 0);
+        assert!(weekdays.validate().is_ok());
+        assert!(weekdays.is_active(at(3, 13, 0)));
+        assert!(weekdays.is_active(at(7, 20, 59)));
+        assert!(!weekdays.is_active(at(3, 21, 0))); // The end is exclusive
+        assert!(!weekdays.is_active(at(3, 12, 59)));
+        assert!(!weekdays.is_active(at(8, 15, 0))); // Saturday
+
+        // Across midnight the window belongs to the day it starts on
+        let nights = schedule("22:00", "02:00", &["fri"], 0);
+        assert!(nights.is_active(at(7, 23, 0)));
+        assert!(nights.is_active(at(8, 1, 0))); // Saturday morning, Friday's window
+        assert!(!nights.is_active(at(7, 1, 0))); // Friday morning, Thursday's window
+
+        // Times are local to the offset: 09:00-17:00 at UTC-5 is 14:00-22:00 UTC
+        let new_york = schedule("09:00", "17:00", &[], -300);
+        assert!(new_york.is_active(at(3, 21, 30)));
+        assert!(!new_york.is_active(at(3, 9, 30)));
+
+        assert!(schedule("00:00", "00:00", &[], 0).is_active(at(4, 5, 0)));
+
+        assert!(schedule("25:00", "21:00", &[], 0).validate().is_err());
+        assert!(schedule("13:00", "21:00", &["someday"], 0).validate().is_err());
+        assert!(schedule("13:00", "21:00", &[], 15 * 60).validate().is_err());
+    }
+}
@@ -0,0 +1,476 @@
+use super::{BotContext, BotDecision, PriceHistory, StrategyParams, TradingBot};
+use crate::models::TradeSide;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+/// The entry point every script defines, called once a tick with the context map
+const ENTRY_POINT: &str = "on_tick";
+
+/// Longest script accepted, in bytes
+const MAX_SCRIPT_LEN: usize = 20_000;
+
+/// Work a script may do in one tick before it is stopped, in Rhai operations
+const MAX_OPERATIONS_PER_TICK: u64 = 200_000;
+
+/// Buys 1% of the stoploss when the price crosses above its 10-tick average, sells when it
+/// crosses back below; shown as the default so a new script has something to start from
+const EXAMPLE_SCRIPT: &str = r#"fn on_tick(ctx) {
+    let average = sma(ctx.prices, 10);
+    if average == () { return hold(); }
+    let above = ctx.price > average;
+    let crossed = this.above != () && this.above != above;
+    this.above = above;
+    if !crossed { return hold(); }
+    let amount = ctx.stoploss * 0.01;
+    if above { buy(amount) } else { sell(amount) }
+}"#;
+
+/// Script bot: runs a user's Rhai script every tick, so strategies can be written without Rust
+///
+/// The script defines `on_tick(ctx)` and returns `buy(amount)` or `sell(amount)` (in the quote
+/// asset), `buy_base(quantity)`, `sell_base(quantity)`, `close_position()`, `limit_buy(price, quantity)`,
+/// `limit_sell(price, quantity)` or `hold()`. `ctx` carries `price`, `prices` (one per tick, oldest first),
+/// `window` (the 5s prices), `base_balance`, `quote_balance`, `base_asset`, `quote_asset`,
+/// `stoploss`, `tick` and `signals`. `this` is a map the script keeps between ticks and restarts.
+/// Indicators over an array of prices: `sma(prices, n)`, `mean`, `std_dev`, `lowest`, `highest`,
+/// `pct_change(prices, n)`, `slope` and `returns`; each gives `()` when there are too few prices.
+/// A script that fails or runs too long holds for the tick.
+pub struct ScriptBot {
+    engine: Engine,
+    ast: AST,
+    stoploss_amount: f64,
+    price_history: PriceHistory,
+    memory: Dynamic, // `this` in the script
+
+    // Statistics (for debugging/visibility)
+    total_buys: u32,
+    total_sells: u32,
+    last_action: String,
+}
+
+/// Tunable settings; fields left out of a parameters object keep these defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScriptParams {
+    pub script: String,       // Rhai source defining on_tick(ctx)
+    pub history_ticks: usize, // Per-tick prices kept for ctx.prices
+}
+
+impl Default for ScriptParams {
+    fn default() -> Self {
+        Self {
+            script: EXAMPLE_SCRIPT.to_string(),
+            history_ticks: 60,
+        }
+    }
+}
+
+impl StrategyParams for ScriptParams {
+    fn validate(&self) -> Result<(), String> {
+        if self.script.len() > MAX_SCRIPT_LEN {
+            return Err(format!("script must be at most {} bytes", MAX_SCRIPT_LEN));
+        }
+        if !(1..=1440).contains(&self.history_ticks) {
+            return Err("history_ticks must be between 1 and 1440".to_string());
+        }
+        compile(&engine(), &self.script).map(|_| ())
+    }
+}
+
+/// Internal state kept across restarts (the script is recompiled from the parameters)
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    prices: Vec<f64>,
+    memory: serde_json::Value,
+    total_buys: u32,
+    total_sells: u32,
+    last_action: String,
+}
+
+/// A sandboxed engine with the trading bindings: no file or module access, and bounded
+/// work, nesting and sizes so a script can't stall or exhaust the server
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .set_max_operations(MAX_OPERATIONS_PER_TICK)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(10_000)
+        .set_max_array_size(10_000)
+        .set_max_map_size(1_000)
+        .on_print(|text| tracing::debug!("Script bot: {}", text))
+        .on_debug(|text, _, _| tracing::debug!("Script bot: {}", text));
+
+    // Decisions are maps shaped like BotDecision's JSON, e.g. #{action: "buy", quote_amount: 100.0}
+    engine
+        .register_fn("buy", |amount: Dynamic| decision("buy", [("quote_amount", amount)]))
+        .register_fn("sell", |amount: Dynamic| decision("sell", [("quote_amount", amount)]))
+        .register_fn("buy_base", |quantity: Dynamic| decision("buy_base", [("quantity", quantity)]))
+        .register_fn("sell_base", |quantity: Dynamic| decision("sell_base", [("quantity", quantity)]))
+        .register_fn("close_position", || decision("close_position", []))
+        .register_fn("limit_buy", |price: Dynamic, quantity: Dynamic| {
+            decision("place_limit", [("side", "Buy".into()), ("price", price), ("quantity", quantity)])
+        })
+        .register_fn("limit_sell", |price: Dynamic, quantity: Dynamic| {
+            decision("place_limit", [("side", "Sell".into()), ("price", price), ("quantity", quantity)])
+        })
+        .register_fn("hold", || decision("do_nothing", []));
+
+    engine
+        .register_fn("sma", |prices: Array, n: i64| {
+            let n = usize::try_from(n).unwrap_or(0);
+            let history = history_of(&prices);
+            if n == 0 || !history.has_at_least(n) {
+                return Dynamic::UNIT;
+            }
+            float_or_unit(history_of_last(&history, n).mean())
+        })
+        .register_fn("mean", |prices: Array| float_or_unit(history_of(&prices).mean()))
+        .register_fn("std_dev", |prices: Array| float_or_unit(history_of(&prices).std_dev()))
+        .register_fn("lowest", |prices: Array| float_or_unit(history_of(&prices).min()))
+        .register_fn("highest", |prices: Array| float_or_unit(history_of(&prices).max()))
+        .register_fn("slope", |prices: Array| float_or_unit(history_of(&prices).slope()))
+        .register_fn("pct_change", |prices: Array, n: i64| {
+            let n = usize::try_from(n).unwrap_or(usize::MAX);
+            float_or_unit(history_of(&prices).pct_change(n))
+        })
+        .register_fn("returns", |prices: Array| -> Array {
+            history_of(&prices).returns().into_iter().map(Dynamic::from_float).collect()
+        });
+
+    engine
+}
+
+/// Compile a script and check it defines the entry point
+fn compile(engine: &Engine, script: &str) -> Result<AST, String> {
+    let ast = engine.compile(script).map_err(|e| format!("script does not compile: {}", e))?;
+    if !ast.iter_functions().any(|f| f.name == ENTRY_POINT && f.params.len() == 1) {
+        return Err(format!("script must define {}(ctx)", ENTRY_POINT));
+    }
+    Ok(ast)
+}
+
+fn decision<const N: usize>(action: &str, fields: [(&str, Dynamic); N]) -> Map {
+    let mut map = Map::new();
+    map.insert("action".into(), action.into());
+    for (name, value) in fields {
+        map.insert(name.into(), value);
+    }
+    map
+}
+
+/// The numbers in a script array, as a history holding all of them
+fn history_of(prices: &Array) -> PriceHistory {
+    let mut history = PriceHistory::new(prices.len());
+    for price in prices {
+        if let Some(price) = number(price) {
+            history.push(price);
+        }
+    }
+    history
+}
+
+fn history_of_last(history: &PriceHistory, n: usize) -> PriceHistory {
+    let mut last = PriceHistory::new(n);
+    for price in history.last_n(n) {
+        last.push(price);
+    }
+    last
+}
+
+fn number(value: &Dynamic) -> Option<f64> {
+    value.as_float().ok().or_else(|| value.as_int().ok().map(|n| n as f64))
+}
+
+fn float_or_unit(value: Option<f64>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Dynamic::from_float)
+}
+
+fn prices_array(prices: impl Iterator<Item = f64>) -> Dynamic {
+    Dynamic::from_array(prices.map(Dynamic::from_float).collect())
+}
+
+/// Read what `on_tick` returned: a decision map, or `()` to hold
+fn parse_decision(result: Dynamic) -> Result<BotDecision, String> {
+    if result.is_unit() {
+        return Ok(BotDecision::DoNothing);
+    }
+    if !result.is_map() {
+        return Err(format!("on_tick returned {}, not a decision such as buy(amount) or hold()", result.type_name()));
+    }
+    let json = serde_json::to_value(&result).map_err(|e| e.to_string())?;
+    let decision: BotDecision = serde_json::from_value(json).map_err(|e| format!("invalid decision: {}", e))?;
+
+    let positive = |name: &str, value: f64| {
+        if value.is_finite() && value > 0.0 {
+            Ok(())
+        } else {
+            Err(format!("{} must be positive, got {}", name, value))
+        }
+    };
+    match &decision {
+        BotDecision::Buy { quote_amount } | BotDecision::Sell { quote_amount } => positive("amount", *quote_amount)?,
+        BotDecision::BuyBase { quantity } | BotDecision::SellBase { quantity } => positive("quantity", *quantity)?,
+        BotDecision::PlaceLimit { price, quantity, .. } => {
+            positive("price", *price)?;
+            positive("quantity", *quantity)?;
+        }
+        BotDecision::DoNothing | BotDecision::ClosePosition => {}
+    }
+    Ok(decision)
+}
+
+impl ScriptBot {
+    /// Build a bot from checked parameters (see `ScriptParams::validate`)
+    pub fn with_params(stoploss_amount: f64, params: ScriptParams) -> Self {
+        let engine = engine();
+        // Parameters are validated before bots are built, so this only falls back for a script
+        // that stopped compiling, e.g. one saved by an older version; that bot then just holds
+        let ast = compile(&engine, &params.script).unwrap_or_default();
+        Self {
+            engine,
+            ast,
+            stoploss_amount,
+            price_history: PriceHistory::new(params.history_ticks),
+            memory: Dynamic::from_map(Map::new()),
+            total_buys: 0,
+            total_sells: 0,
+            last_action: "initialized".to_string(),
+        }
+    }
+
+    /// The context as the script sees it
+    fn context_map(&self, ctx: &BotContext) -> Map {
+        let signals: Array = ctx
+            .signals
+            .iter()
+            .map(|signal| {
+                let mut map = Map::new();
+                let side = match signal.side {
+                    TradeSide::Buy => "buy",
+                    TradeSide::Sell => "sell",
+                };
+                map.insert("side".into(), side.into());
+                map.insert("quantity".into(), Dynamic::from_float(signal.quantity));
+                map.insert("source".into(), signal.source.clone().into());
+                Dynamic::from_map(map)
+            })
+            .collect();
+
+        let mut map = Map::new();
+        map.insert("price".into(), Dynamic::from_float(ctx.current_price));
+        map.insert("prices".into(), prices_array(self.price_history.iter()));
+        map.insert("window".into(), prices_array(ctx.price_window.iter().map(|p| p.price)));
+        map.insert("base_balance".into(), Dynamic::from_float(ctx.base_balance));
+        map.insert("quote_balance".into(), Dynamic::from_float(ctx.quote_balance));
+        map.insert("base_asset".into(), ctx.base_asset.clone().into());
+        map.insert("quote_asset".into(), ctx.quote_asset.clone().into());
+        map.insert("stoploss".into(), Dynamic::from_float(self.stoploss_amount));
+        map.insert("tick".into(), Dynamic::from_int(ctx.tick_count as i64));
+        map.insert("signals".into(), Dynamic::from_array(signals));
+        map
+    }
+
+    fn run(&mut self, ctx: &BotContext) -> Result<BotDecision, String> {
+        let context = self.context_map(ctx);
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.memory);
+        let result = self
+            .engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, ENTRY_POINT, (context,))
+            .map_err(|e| e.to_string())?;
+        parse_decision(result)
+    }
+}
+
+impl TradingBot for ScriptBot {
+    fn tick(&mut self, ctx: &BotContext) -> BotDecision {
+        self.price_history.push(ctx.current_price);
+
+        match self.run(ctx) {
+            Ok(decision) => {
+                match decision.market_order(ctx.current_price, ctx.base_balance) {
+                    Some((TradeSide::Buy, _)) => self.total_buys += 1,
+                    Some((TradeSide::Sell, _)) => self.total_sells += 1,
+                    None => {}
+                }
+                self.last_action = match &decision {
+                    BotDecision::DoNothing => "hold".to_string(),
+                    BotDecision::Buy { quote_amount } => format!("buy ${:.2}", quote_amount),
+                    BotDecision::Sell { quote_amount } => format!("sell ${:.2}", quote_amount),
+                    BotDecision::BuyBase { quantity } => format!("buy {} {}", quantity, ctx.base_asset),
+                    BotDecision::SellBase { quantity } => format!("sell {} {}", quantity, ctx.base_asset),
+                    BotDecision::ClosePosition => "close position".to_string(),
+                    BotDecision::PlaceLimit { side, price, quantity } => {
+                        format!("limit {:?} {} {} at {}", side, quantity, ctx.base_asset, price)
+                    }
+                };
+                decision
+            }
+            Err(e) => {
+                tracing::warn!("Script bot error on tick {}: {}", ctx.tick_count, e);
+                self.last_action = format!("script error: {}", e);
+                BotDecision::DoNothing
+            }
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Script"
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(SavedState {
+            prices: self.price_history.prices(),
+            memory: serde_json::to_value(&self.memory).ok()?,
+            total_buys: self.total_buys,
+            total_sells: self.total_sells,
+            last_action: self.last_action.clone(),
+        })
+        .ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) {
+        let Ok(saved) = serde_json::from_value::<SavedState>(state) else {
+            return; // Unreadable state: start fresh
+        };
+
+        for price in saved.prices {
+            self.price_history.push(price);
+        }
+        if let Ok(memory) = serde_json::from_value::<Dynamic>(saved.memory) {
+            self.memory = memory;
+        }
+        self.total_buys = saved.total_buys;
+        self.total_sells = saved.total_sells;
+        self.last_action = saved.last_action;
+    }
+
+    fn warm_up(&mut self, prices: &[f64]) {
+        for &price in prices {
+            self.price_history.push(price);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(price: f64, tick_count: u64) -> BotContext {
+        BotContext {
+            price_window: Vec::new(),
+            base_balance: 1.0,
+            quote_balance: 10000.0,
+            current_price: price,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            tick_count,
+            signals: Vec::new(),
+            headlines: Vec::new(),
+            secondary: None,
+        }
+    }
+
+    fn bot(script: &str) -> ScriptBot {
+        let params = ScriptParams { script: script.to_string(), ..ScriptParams::default() };
+        params.validate().unwrap();
+        ScriptBot::with_params(1000.0, params)
+    }
+
+    #[test]
+    fn test_script_decides_with_context_and_indicators() {
+        let mut bot = bot(r#"
+            fn on_tick(ctx) {
+                let average = sma(ctx.prices, 3);
+                if average == () { return hold(); }
+                if ctx.price > average { buy(ctx.stoploss / 10) } else if ctx.price < average { sell(25) } else { () }
+            }
+        "#);
+        assert_eq!(bot.tick(&context(100.0, 0)), BotDecision::DoNothing); // Warming up
+        assert_eq!(bot.tick(&context(100.0, 1)), BotDecision::DoNothing);
+        assert_eq!(bot.tick(&context(103.0, 2)), BotDecision::Buy { quote_amount: 100.0 });
+        assert_eq!(bot.tick(&context(90.0, 3)), BotDecision::Sell { quote_amount: 25.0 });
+        assert_eq!(bot.tick(&context(90.0, 4)), BotDecision::Sell { quote_amount: 25.0 });
+        assert_eq!(bot.tick(&context(90.0, 5)), BotDecision::DoNothing);
+    }
+
+    #[test]
+    fn test_script_can_size_in_base_close_and_place_limits() {
+        let mut bot = bot(r#"
+            fn on_tick(ctx) {
+                switch ctx.tick {
+                    0 => buy_base(0.5),
+                    1 => sell_base(0.25),
+                    2 => limit_buy(ctx.price * 0.9, 2),
+                    3 => limit_sell(110, 0.1),
+                    _ => close_position(),
+                }
+            }
+        "#);
+        assert_eq!(bot.tick(&context(100.0, 0)), BotDecision::BuyBase { quantity: 0.5 });
+        assert_eq!(bot.tick(&context(100.0, 1)), BotDecision::SellBase { quantity: 0.25 });
+        assert_eq!(
+            bot.tick(&context(100.0, 2)),
+            BotDecision::PlaceLimit { side: TradeSide::Buy, price: 90.0, quantity: 2.0 }
+        );
+        assert_eq!(
+            bot.tick(&context(100.0, 3)),
+            BotDecision::PlaceLimit { side: TradeSide::Sell, price: 110.0, quantity: 0.1 }
+        );
+        assert_eq!(bot.tick(&context(100.0, 4)), BotDecision::ClosePosition);
+        assert_eq!((bot.total_buys, bot.total_sells), (1, 2));
+    }
+
+    #[test]
+    fn test_memory_survives_ticks_and_restarts() {
+        let script = "fn on_tick(ctx) { this.seen = (this.seen ?? 0) + 1; if this.seen == 3 { buy(5) } else { hold() } }";
+        let mut first = bot(script);
+        first.tick(&context(1.0, 0));
+        first.tick(&context(1.0, 1));
+
+        let mut restarted = bot(script);
+        restarted.restore_state(first.save_state().unwrap());
+        assert_eq!(restarted.price_history.len(), 2);
+        assert_eq!(restarted.tick(&context(1.0, 2)), BotDecision::Buy { quote_amount: 5.0 });
+    }
+
+    #[test]
+    fn test_bad_scripts_are_rejected_or_hold() {
+        let invalid = |script: &str| ScriptParams { script: script.to_string(), ..ScriptParams::default() }.validate();
+        assert!(invalid("fn on_tick(ctx) {").unwrap_err().contains("does not compile"));
+        assert!(invalid("fn tick(ctx) { hold() }").unwrap_err().contains("on_tick(ctx)"));
+        assert!(invalid(&"x".repeat(MAX_SCRIPT_LEN + 1)).is_err());
+        assert!(ScriptParams::default().validate().is_ok());
+
+        // Runtime failures hold for the tick instead of trading
+        let mut looping = bot("fn on_tick(ctx) { loop {} }");
+        assert_eq!(looping.tick(&context(1.0, 0)), BotDecision::DoNothing);
+        assert!(looping.last_action.starts_with("script error"));
+        let mut negative = bot("fn on_tick(ctx) { sell(-1) }");
+        assert_eq!(negative.tick(&context(1.0, 0)), BotDecision::DoNothing);
+        let mut wrong_type = bot(r#"fn on_tick(ctx) { "buy" }"#);
+        assert_eq!(wrong_type.tick(&context(1.0, 0)), BotDecision::DoNothing);
+        let mut unknown = bot(r#"fn on_tick(ctx) { #{action: "short", quantity: 1} }"#);
+        assert_eq!(unknown.tick(&context(1.0, 0)), BotDecision::DoNothing);
+        assert!(unknown.last_action.contains("invalid decision"));
+        let mut no_files = bot(r#"fn on_tick(ctx) { import "secrets" as s; hold() }"#);
+        assert_eq!(no_files.tick(&context(1.0, 0)), BotDecision::DoNothing);
+    }
+
+    #[test]
+    fn test_example_script_trades_on_average_crossings() {
+        let mut bot = ScriptBot::with_params(1000.0, ScriptParams::default());
+        let decisions: Vec<BotDecision> = [100.0; 10]
+            .into_iter()
+            .chain([110.0, 111.0, 90.0])
+            .enumerate()
+            .map(|(tick, price)| bot.tick(&context(price, tick as u64)))
+            .collect();
+        assert!(decisions[..10].iter().all(|d| *d == BotDecision::DoNothing)); // Warming up, then the first side
+        assert_eq!(decisions[10], BotDecision::Buy { quote_amount: 10.0 });
+        assert_eq!(decisions[11], BotDecision::DoNothing); // Still above
+        assert_eq!(decisions[12], BotDecision::Sell { quote_amount: 10.0 });
+    }
+}
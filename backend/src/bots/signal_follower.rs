@@ -0,0 +1,152 @@
+use super::{BotContext, BotDecision, TradingBot};
+use crate::models::TradeSide;
+use serde::{Deserialize, Serialize};
+
+/// Signal follower bot: trades only on external signals (e.g. TradingView webhooks)
+/// Signals received between ticks are netted into a single buy or sell at the current price
+pub struct SignalFollowerBot {
+    // Statistics (for debugging/visibility)
+    signals_received: u32,
+    total_buys: u32,
+    total_sells: u32,
+    last_action: String,
+}
+
+/// Internal state kept across restarts
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    signals_received: u32,
+    total_buys: u32,
+    total_sells: u32,
+    last_action: String,
+}
+
+impl SignalFollowerBot {
+    pub fn new() -> Self {
+        Self {
+            signals_received: 0,
+            total_buys: 0,
+            total_sells: 0,
+            last_action: "initialized".to_string(),
+        }
+    }
+}
+
+impl Default for SignalFollowerBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Base quantity the signals add up to: positive to buy, negative to sell
+fn net_quantity(ctx: &BotContext) -> f64 {
+    ctx.signals
+        .iter()
+        .map(|signal| match signal.side {
+            TradeSide::Buy => signal.quantity,
+            TradeSide::Sell => -signal.quantity,
+        })
+        .sum()
+}
+
+impl TradingBot for SignalFollowerBot {
+    fn tick(&mut self, ctx: &BotContext) -> BotDecision {
+        if ctx.signals.is_empty() {
+            self.last_action = "waiting for signals".to_string();
+            return BotDecision::DoNothing;
+        }
+        self.signals_received += ctx.signals.len() as u32;
+
+        let net = net_quantity(ctx);
+        let quote_amount = net.abs() * ctx.current_price;
+
+        if net > 0.0 {
+            self.total_buys += 1;
+            self.last_action = format!("buy ${:.2}", quote_amount);
+            BotDecision::Buy { quote_amount }
+        } else if net < 0.0 {
+            self.total_sells += 1;
+            self.last_action = format!("sell ${:.2}", quote_amount);
+            BotDecision::Sell { quote_amount }
+        } else {
+            // Opposite signals cancelled out
+            self.last_action = "signals offset".to_string();
+            BotDecision::DoNothing
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Signal Follower"
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(SavedState {
+            signals_received: self.signals_received,
+            total_buys: self.total_buys,
+            total_sells: self.total_sells,
+            last_action: self.last_action.clone(),
+        })
+        .ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) {
+        let Ok(saved) = serde_json::from_value::<SavedState>(state) else {
+            return; // Unreadable state: start fresh
+        };
+
+        self.signals_received = saved.signals_received;
+        self.total_buys = saved.total_buys;
+        self.total_sells = saved.total_sells;
+        self.last_action = saved.last_action;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::ExternalSignal;
+    use chrono::Utc;
+
+    fn context(signals: Vec<(TradeSide, f64)>) -> BotContext {
+        BotContext {
+            price_window: Vec::new(),
+            base_balance: 1.0,
+            quote_balance: 10000.0,
+            current_price: 50000.0,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            tick_count: 0,
+            signals: signals
+                .into_iter()
+                .map(|(side, quantity)| ExternalSignal {
+                    source: "tradingview".to_string(),
+                    side,
+                    quantity,
+                    received_at: Utc::now(),
+                })
+                .collect(),
+            headlines: Vec::new(),
+            secondary: None,
+        }
+    }
+
+    #[test]
+    fn test_signals_are_netted_at_current_price() {
+        let mut bot = SignalFollowerBot::new();
+
+        assert_eq!(bot.tick(&context(vec![])), BotDecision::DoNothing);
+        assert_eq!(
+            bot.tick(&context(vec![(TradeSide::Buy, 0.5)])),
+            BotDecision::Buy { quote_amount: 25000.0 }
+        );
+        assert_eq!(
+            bot.tick(&context(vec![(TradeSide::Buy, 0.25), (TradeSide::Sell, 0.75)])),
+            BotDecision::Sell { quote_amount: 25000.0 }
+        );
+        assert_eq!(
+            bot.tick(&context(vec![(TradeSide::Buy, 0.2), (TradeSide::Sell, 0.2)])),
+            BotDecision::DoNothing
+        );
+        assert_eq!(bot.signals_received, 5);
+    }
+}
@@ -0,0 +1,273 @@
+use super::{BotContext, BotDecision, DecisionOutcome, PriceHistory, StrategyParams, TradingBot};
+use serde::{Deserialize, Serialize};
+
+/// Tunable settings; fields left out of a parameters object keep these defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpreadParams {
+    pub secondary_asset: String, // Asset the base is measured against, e.g. "ETH" for a BTC bot
+    pub window_ticks: usize,     // Ticks of the ratio the rolling mean and deviation cover
+    pub entry_std_devs: f64,     // How far from the mean the ratio must stray to open a position
+    pub exit_std_devs: f64,      // How close it must come back to close it
+    pub step_pct: f64,           // Position size, as a percent of the stoploss
+}
+
+impl Default for SpreadParams {
+    fn default() -> Self {
+        Self {
+            secondary_asset: "ETH".to_string(),
+            window_ticks: 60,
+            entry_std_devs: 2.0,
+            exit_std_devs: 0.5,
+            step_pct: 5.0,
+        }
+    }
+}
+
+impl StrategyParams for SpreadParams {
+    fn validate(&self) -> Result<(), String> {
+        let asset = &self.secondary_asset;
+        if asset.is_empty() || asset.len() > 10 || !asset.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+            return Err("secondary_asset must be an asset symbol such as ETH".to_string());
+        }
+        if !(10..=1440).contains(&self.window_ticks) {
+            return Err("window_ticks must be between 10 and 1440".to_string());
+        }
+        if !self.entry_std_devs.is_finite() || self.entry_std_devs <= 0.0 || self.entry_std_devs > 10.0 {
+            return Err("entry_std_devs must be above 0 and at most 10".to_string());
+        }
+        if !self.exit_std_devs.is_finite() || self.exit_std_devs < 0.0 || self.exit_std_devs >= self.entry_std_devs {
+            return Err("exit_std_devs must be at least 0 and below entry_std_devs".to_string());
+        }
+        if !self.step_pct.is_finite() || self.step_pct <= 0.0 || self.step_pct > 100.0 {
+            return Err("step_pct must be above 0 and at most 100".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The position the bot has open on the spread, in base units
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Position {
+    Flat,
+    Long(f64),  // Bought while the base was cheap against the secondary asset
+    Short(f64), // Sold out of holdings while it was rich
+}
+
+/// Spread bot: watches the ratio of the base price to a correlated secondary asset's (BTC/ETH, say)
+/// and bets on it reverting to its rolling mean: it buys the base when the ratio falls
+/// `entry_std_devs` below the mean, sells when it rises as far above, and unwinds the position
+/// once the ratio is back within `exit_std_devs`
+/// A position only changes once its trade goes through, so a rejected entry leaves the bot flat
+/// Holds without the secondary asset's prices, as in backtests
+pub struct SpreadBot {
+    stepsize_quote: f64,
+    secondary_asset: String,
+    entry_std_devs: f64,
+    exit_std_devs: f64,
+    window_ticks: usize,
+    ratios: PriceHistory, // Base price over secondary price, one a tick
+    position: Position,
+    pending: Option<Position>, // Where the last tick's trade leaves the position, once it goes through
+}
+
+/// Internal state kept across restarts
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    ratios: Vec<f64>,
+    position: Position,
+}
+
+impl SpreadBot {
+    pub fn with_params(stoploss_amount: f64, params: SpreadParams) -> Self {
+        Self {
+            stepsize_quote: stoploss_amount * params.step_pct / 100.0,
+            secondary_asset: params.secondary_asset,
+            entry_std_devs: params.entry_std_devs,
+            exit_std_devs: params.exit_std_devs,
+            window_ticks: params.window_ticks,
+            ratios: PriceHistory::new(params.window_ticks),
+            position: Position::Flat,
+            pending: None,
+        }
+    }
+
+    /// How many deviations the latest ratio is from the window's mean, once the window is full
+    fn z_score(&self) -> Option<f64> {
+        if !self.ratios.has_at_least(self.window_ticks) {
+            return None;
+        }
+        let (latest, mean, std_dev) = (self.ratios.latest()?, self.ratios.mean()?, self.ratios.std_dev()?);
+        (std_dev > 0.0).then(|| (latest - mean) / std_dev)
+    }
+}
+
+impl TradingBot for SpreadBot {
+    fn tick(&mut self, ctx: &BotContext) -> BotDecision {
+        self.pending = None;
+        let Some(secondary) = ctx.secondary.as_ref().filter(|s| s.asset == self.secondary_asset) else {
+            return BotDecision::DoNothing;
+        };
+        if ctx.current_price <= 0.0 || secondary.current_price <= 0.0 {
+            return BotDecision::DoNothing;
+        }
+        self.ratios.push(ctx.current_price / secondary.current_price);
+        let Some(z) = self.z_score() else {
+            return BotDecision::DoNothing; // Warming up, or a flat ratio
+        };
+
+        let (next, decision) = match self.position {
+            Position::Flat if z <= -self.entry_std_devs => {
+                let quantity = self.stepsize_quote / ctx.current_price;
+                (Position::Long(quantity), BotDecision::BuyBase { quantity })
+            }
+            Position::Flat if z >= self.entry_std_devs && ctx.base_balance > 0.0 => {
+                let quantity = (self.stepsize_quote / ctx.current_price).min(ctx.base_balance);
+                (Position::Short(quantity), BotDecision::SellBase { quantity })
+            }
+            Position::Long(quantity) if z >= -self.exit_std_devs => {
+                (Position::Flat, BotDecision::SellBase { quantity: quantity.min(ctx.base_balance) })
+            }
+            Position::Short(quantity) if z <= self.exit_std_devs => (Position::Flat, BotDecision::BuyBase { quantity }),
+            _ => return BotDecision::DoNothing,
+        };
+        self.pending = Some(next);
+        decision
+    }
+
+    fn name(&self) -> &str {
+        "Spread"
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(SavedState { ratios: self.ratios.prices(), position: self.position }).ok()
+    }
+
+    fn restore_state(&mut self, state: serde_json::Value) {
+        let Ok(saved) = serde_json::from_value::<SavedState>(state) else {
+            return; // Unreadable state: start fresh
+        };
+
+        for ratio in saved.ratios {
+            self.ratios.push(ratio);
+        }
+        self.position = saved.position;
+    }
+
+    fn secondary_asset(&self) -> Option<&str> {
+        Some(&self.secondary_asset)
+    }
+
+    fn on_outcome(&mut self, outcome: DecisionOutcome) {
+        let Some(next) = self.pending.take() else {
+            return;
+        };
+        self.position = match (outcome, next, self.position) {
+            (DecisionOutcome::Skipped, ..) => self.position,
+            (DecisionOutcome::Executed, next, _) => next,
+            (DecisionOutcome::Resized(quantity), Position::Long(_), _) => Position::Long(quantity),
+            (DecisionOutcome::Resized(quantity), Position::Short(_), _) => Position::Short(quantity),
+            // Part of an unwind leaves the rest open
+            (DecisionOutcome::Resized(quantity), Position::Flat, Position::Long(open)) if quantity < open => {
+                Position::Long(open - quantity)
+            }
+            (DecisionOutcome::Resized(quantity), Position::Flat, Position::Short(open)) if quantity < open => {
+                Position::Short(open - quantity)
+            }
+            (DecisionOutcome::Resized(_), Position::Flat, _) => Position::Flat,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::{check_pair, SecondaryPrices};
+
+    fn context(price: f64, secondary_price: f64, base_balance: f64) -> BotContext {
+        BotContext {
+            price_window: Vec::new(),
+            base_balance,
+            quote_balance: 10_000.0,
+            current_price: price,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            tick_count: 0,
+            signals: Vec::new(),
+            headlines: Vec::new(),
+            secondary: Some(SecondaryPrices {
+                asset: "ETH".to_string(),
+                price_window: Vec::new(),
+                current_price: secondary_price,
+            }),
+        }
+    }
+
+    fn spread_bot() -> SpreadBot {
+        let params = SpreadParams { window_ticks: 10, step_pct: 10.0, ..Default::default() };
+        assert!(params.validate().is_ok());
+        SpreadBot::with_params(1_000.0, params)
+    }
+
+    /// A bot whose ratio wobbled around 20 while its window filled
+    fn warmed_up_bot() -> SpreadBot {
+        let mut bot = spread_bot();
+        for i in 0..9 {
+            let price = if i % 2 == 0 { 2_010.0 } else { 1_990.0 };
+            assert_eq!(bot.tick(&context(price, 100.0, 0.0)), BotDecision::DoNothing);
+        }
+        bot
+    }
+
+    #[test]
+    fn test_trades_the_ratio_back_to_its_mean() {
+        let mut bot = warmed_up_bot();
+
+        // BTC cheapens against ETH: buy $100 of it
+        assert_eq!(bot.tick(&context(1_900.0, 100.0, 0.0)), BotDecision::BuyBase { quantity: 100.0 / 1_900.0 });
+        bot.on_outcome(DecisionOutcome::Executed);
+        assert_eq!(bot.tick(&context(1_910.0, 100.0, 0.1)), BotDecision::DoNothing);
+
+        // Back near the mean: unwind what was bought
+        assert_eq!(bot.tick(&context(2_000.0, 100.0, 0.1)), BotDecision::SellBase { quantity: 100.0 / 1_900.0 });
+        bot.on_outcome(DecisionOutcome::Executed);
+
+        // ETH's own moves count too: ETH falling makes BTC rich, so it sells out of holdings
+        let mut rich = spread_bot();
+        rich.restore_state(bot.save_state().unwrap());
+        assert_eq!(rich.position, Position::Flat);
+        assert_eq!(rich.tick(&context(2_000.0, 90.0, 1.0)), BotDecision::SellBase { quantity: 0.05 });
+
+        // Without the secondary asset's prices it holds
+        let mut blind = SpreadBot::with_params(1_000.0, SpreadParams::default());
+        assert_eq!(blind.tick(&BotContext { secondary: None, ..context(1_900.0, 100.0, 0.0) }), BotDecision::DoNothing);
+        assert_eq!(blind.secondary_asset(), Some("ETH"));
+
+        // ETH against itself is a ratio of 1
+        assert!(check_pair(&blind, "BTC").is_ok());
+        assert!(check_pair(&blind, "ETH").is_err());
+
+        assert!(SpreadParams { secondary_asset: "eth".to_string(), ..Default::default() }.validate().is_err());
+        assert!(SpreadParams { exit_std_devs: 3.0, ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_a_rejected_entry_leaves_the_bot_flat() {
+        let mut bot = warmed_up_bot();
+
+        // The buy fails (say, for want of quote), so there is nothing to unwind
+        assert_eq!(bot.tick(&context(1_900.0, 100.0, 0.0)), BotDecision::BuyBase { quantity: 100.0 / 1_900.0 });
+        bot.on_outcome(DecisionOutcome::Skipped);
+        assert_eq!(bot.position, Position::Flat);
+        // Back near the mean with unrelated BTC held: it is left alone
+        assert_eq!(bot.tick(&context(2_000.0, 100.0, 1.0)), BotDecision::DoNothing);
+
+        // A buy cut down by a limit is tracked at what was bought, and part of an unwind leaves the rest
+        assert_eq!(bot.tick(&context(1_880.0, 100.0, 0.0)), BotDecision::BuyBase { quantity: 100.0 / 1_880.0 });
+        bot.on_outcome(DecisionOutcome::Resized(0.02));
+        assert_eq!(bot.position, Position::Long(0.02));
+        assert_eq!(bot.tick(&context(2_000.0, 100.0, 1.0)), BotDecision::SellBase { quantity: 0.02 });
+        bot.on_outcome(DecisionOutcome::Resized(0.015));
+        assert!(matches!(bot.position, Position::Long(open) if (open - 0.005).abs() < 1e-12));
+    }
+}
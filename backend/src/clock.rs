@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Where the simulation gets the time, so replays and tests can run it on their own time
+/// Used for market time: prices, bot ticks, fills, price compaction and the schedulers driving them.
+/// Sessions, leases, backups and rate limits stay on the wall clock.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Resolves once `now()` reaches `deadline`
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep;
+}
+
+/// `at` plus `period`, saturating
+fn after(at: DateTime<Utc>, period: Duration) -> DateTime<Utc> {
+    chrono::Duration::from_std(period)
+        .ok()
+        .and_then(|period| at.checked_add_signed(period))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// Wait `period` of `clock`'s time
+pub fn sleep(clock: &Arc<dyn Clock>, period: Duration) -> Sleep {
+    clock.sleep_until(after(clock.now(), period))
+}
+
+/// Like `tokio::time::interval` on `clock`: the first tick is immediate, then one per `period`
+/// Ticks missed while the clock jumped ahead are skipped rather than fired in a burst
+/// Panics if `period` is zero, as tokio's does
+pub fn interval(clock: &Arc<dyn Clock>, period: Duration) -> Ticker {
+    assert!(!period.is_zero(), "interval period must be non-zero");
+    Ticker { clock: clock.clone(), period, next: None }
+}
+
+pub struct Ticker {
+    clock: Arc<dyn Clock>,
+    period: Duration,
+    next: Option<DateTime<Utc>>,
+}
+
+impl Ticker {
+    pub async fn tick(&mut self) {
+        if let Some(next) = self.next {
+            self.clock.sleep_until(next).await;
+        }
+        let now = self.clock.now();
+        let mut next = after(self.next.unwrap_or(now), self.period);
+        while next <= now && next < DateTime::<Utc>::MAX_UTC {
+            next = after(next, self.period);
+        }
+        self.next = Some(next);
+    }
+}
+
+/// The real time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        let wait = (deadline - Utc::now()).to_std().unwrap_or_default();
+        Box::pin(tokio::time::sleep(wait))
+    }
+}
+
+/// Time that only moves when told to; sleepers wake once it passes their deadline
+pub struct SimulatedClock {
+    now: Mutex<DateTime<Utc>>,
+    changed: watch::Sender<DateTime<Utc>>,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(start), changed: watch::channel(start).0 }
+    }
+
+    /// Move to `to`; never backwards
+    pub fn set(&self, to: DateTime<Utc>) {
+        let mut now = self.now.lock().unwrap_or_else(|e| e.into_inner());
+        if to > *now {
+            *now = to;
+            self.changed.send_replace(to);
+        }
+    }
+
+    pub fn advance(&self, by: chrono::Duration) {
+        self.set(self.now() + by);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn sleep_until(&self, deadline: DateTime<Utc>) -> Sleep {
+        let mut changed = self.changed.subscribe();
+        Box::pin(async move {
+            // The sender lives as long as the clock; if it's gone, nothing will ever wake us
+            if changed.wait_for(|now| *now >= deadline).await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_simulated_ticker_follows_the_clock_and_skips_jumps() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let sim = Arc::new(SimulatedClock::new(start));
+        let clock: Arc<dyn Clock> = sim.clone();
+        let mut ticker = interval(&clock, Duration::from_secs(60));
+
+        // First tick is immediate
+        ticker.tick().await;
+        let waiting = tokio::spawn(async move {
+            ticker.tick().await;
+            ticker
+        });
+        tokio::task::yield_now().await;
+        sim.advance(chrono::Duration::seconds(59));
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        sim.advance(chrono::Duration::seconds(1));
+        let mut ticker = waiting.await.unwrap();
+
+        // Ten minutes at once is one tick, not ten
+        sim.advance(chrono::Duration::minutes(10));
+        ticker.tick().await;
+        assert_eq!(ticker.next, Some(start + chrono::Duration::minutes(12)));
+        // Never backwards
+        sim.set(start);
+        assert_eq!(clock.now(), start + chrono::Duration::minutes(11));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::services::price_service::MAX_POLLED_ASSETS;
+use serde::Deserialize;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// File read when `CONFIG_PATH` is not set; it may be absent
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// Settings fixed at startup: defaults, then the TOML file, then environment variables
+/// Subsystems configured only by env vars (snapshots, backups, SMTP, rate limits...) keep reading their own
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub prices: PriceConfig,
+    pub accounts: AccountConfig,
+    pub exchange: ExchangeConfig,
+    pub news: NewsConfig,
+    pub bots: BotsConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: String, // BIND_ADDRESS
+    pub port: u16,            // PORT
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriceConfig {
+    pub poll_interval_secs: u64,     // PRICE_POLL_SECS; also the spacing of the high-frequency window
+    pub tracked_assets: Vec<String>, // TRACKED_ASSETS (comma-separated); always polled, watched or not
+    pub window_minutes: usize,       // PRICE_WINDOW_MINUTES; capped by available memory
+    pub synthetic_stablecoins: bool, // SYNTHETIC_STABLECOINS; simulate stablecoin pegs (with depegs) instead of fetching
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountConfig {
+    pub starting_balance: f64, // STARTING_BALANCE; USD every new or reset account gets
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExchangeConfig {
+    pub spot_url: String,    // COINBASE_API_URL; spot prices
+    pub candles_url: String, // COINBASE_EXCHANGE_URL; historical candles
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NewsConfig {
+    pub api_url: String,           // CRYPTOPANIC_API_URL
+    pub api_token: Option<String>, // CRYPTOPANIC_TOKEN; the feed is off without one
+    pub poll_interval_secs: u64,   // NEWS_POLL_SECS
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotsConfig {
+    pub max_context_failures: u32, // BOT_MAX_CONTEXT_FAILURES; ticks in a row a bot may fail to gather its inputs before it stops
+    pub retry_backoff_secs: u64,   // BOT_RETRY_BACKOFF_SECS; wait before the first retry, doubling with each failure after
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self { bind_address: "0.0.0.0".to_string(), port: 3000 }
+    }
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 5,
+            tracked_assets: ["BTC", "ETH", "USDC", "USDT"].map(str::to_string).to_vec(),
+            window_minutes: 60,
+            synthetic_stablecoins: false,
+        }
+    }
+}
+
+impl Default for AccountConfig {
+    fn default() -> Self {
+        Self { starting_balance: 10_000.0 }
+    }
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        Self {
+            spot_url: "https://api.coinbase.com/v2".to_string(),
+            candles_url: "https://api.exchange.coinbase.com".to_string(),
+        }
+    }
+}
+
+impl Default for NewsConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://cryptopanic.com/api/v1".to_string(),
+            api_token: None,
+            poll_interval_secs: 300,
+        }
+    }
+}
+
+impl Default for BotsConfig {
+    fn default() -> Self {
+        Self { max_context_failures: 5, retry_backoff_secs: 5 }
+    }
+}
+
+impl BotsConfig {
+    /// Longest wait between retries
+    const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+    /// How long to wait before retrying after `failures` failures in a row
+    pub fn retry_backoff(&self, failures: u32) -> Duration {
+        let factor = 2u64.saturating_pow(failures.saturating_sub(1));
+        Duration::from_secs(self.retry_backoff_secs.saturating_mul(factor)).min(Self::MAX_BACKOFF)
+    }
+}
+
+/// Parse an env var if it is set and not empty
+fn env<T: FromStr>(name: &str) -> Result<Option<T>, String>
+where
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(name) {
+        Ok(value) if !value.trim().is_empty() => {
+            value.trim().parse().map(Some).map_err(|e| format!("{}={:?}: {}", name, value, e))
+        }
+        _ => Ok(None),
+    }
+}
+
+impl Config {
+    /// Load the file named by `CONFIG_PATH` (default `config.toml`, skipped if missing) and apply env overrides
+    pub fn load() -> Result<Self, String> {
+        let (path, required) = match std::env::var("CONFIG_PATH") {
+            Ok(path) => (path, true),
+            Err(_) => (DEFAULT_CONFIG_PATH.to_string(), false),
+        };
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                tracing::info!("Loading configuration from {}", path);
+                Self::parse(&text).map_err(|e| format!("{}: {}", path, e))?
+            }
+            Err(e) if required || e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Cannot read {}: {}", path, e));
+            }
+            Err(_) => Self::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    fn apply_env(&mut self) -> Result<(), String> {
+        if let Some(address) = env("BIND_ADDRESS")? {
+            self.server.bind_address = address;
+        }
+        if let Some(port) = env("PORT")? {
+            self.server.port = port;
+        }
+        if let Some(secs) = env("PRICE_POLL_SECS")? {
+            self.prices.poll_interval_secs = secs;
+        }
+        if let Some(assets) = env::<String>("TRACKED_ASSETS")? {
+            self.prices.tracked_assets = assets.split(',').map(|a| a.trim().to_string()).collect();
+        }
+        if let Some(minutes) = env("PRICE_WINDOW_MINUTES")? {
+            self.prices.window_minutes = minutes;
+        }
+        if let Some(synthetic) = env("SYNTHETIC_STABLECOINS")? {
+            self.prices.synthetic_stablecoins = synthetic;
+        }
+        if let Some(balance) = env("STARTING_BALANCE")? {
+            self.accounts.starting_balance = balance;
+        }
+        if let Some(url) = env("COINBASE_API_URL")? {
+            self.exchange.spot_url = url;
+        }
+        if let Some(url) = env("COINBASE_EXCHANGE_URL")? {
+            self.exchange.candles_url = url;
+        }
+        if let Some(url) = env("CRYPTOPANIC_API_URL")? {
+            self.news.api_url = url;
+        }
+        if let Some(token) = env("CRYPTOPANIC_TOKEN")? {
+            self.news.api_token = Some(token);
+        }
+        if let Some(secs) = env("NEWS_POLL_SECS")? {
+            self.news.poll_interval_secs = secs;
+        }
+        if let Some(failures) = env("BOT_MAX_CONTEXT_FAILURES")? {
+            self.bots.max_context_failures = failures;
+        }
+        if let Some(secs) = env("BOT_RETRY_BACKOFF_SECS")? {
+            self.bots.retry_backoff_secs = secs;
+        }
+        Ok(())
+    }
+
+    /// Upper-cases tracked assets and drops trailing slashes from URLs
+    fn validate(&mut self) -> Result<(), String> {
+        if self.server.bind_address.parse::<std::net::IpAddr>().is_err() {
+            return Err(format!("bind_address {:?} is not an IP address", self.server.bind_address));
+        }
+        if !(1..=60).contains(&self.prices.poll_interval_secs) {
+            return Err("poll_interval_secs must be between 1 and 60".to_string());
+        }
+
+        let mut assets: Vec<String> = Vec::new();
+        for asset in &self.prices.tracked_assets {
+            let asset = asset.trim().to_uppercase();
+            if asset.is_empty() || asset.len() > 10 || !asset.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!("Invalid tracked asset {:?}", asset));
+            }
+            if !assets.contains(&asset) {
+                assets.push(asset);
+            }
+        }
+        if assets.is_empty() || assets.len() > MAX_POLLED_ASSETS {
+            return Err(format!("tracked_assets must list 1 to {} assets", MAX_POLLED_ASSETS));
+        }
+        self.prices.tracked_assets = assets;
+
+        if !self.accounts.starting_balance.is_finite() || self.accounts.starting_balance < 0.0 {
+            return Err("starting_balance must be a non-negative number".to_string());
+        }
+        for url in [&mut self.exchange.spot_url, &mut self.exchange.candles_url] {
+            reqwest::Url::parse(url).map_err(|e| format!("Invalid exchange URL {:?}: {}", url, e))?;
+            *url = url.trim_end_matches('/').to_string();
+        }
+        // The free CryptoPanic plan allows a few requests a minute
+        if self.news.poll_interval_secs < 60 {
+            return Err("news poll_interval_secs must be at least 60".to_string());
+        }
+        reqwest::Url::parse(&self.news.api_url)
+            .map_err(|e| format!("Invalid news URL {:?}: {}", self.news.api_url, e))?;
+        self.news.api_url = self.news.api_url.trim_end_matches('/').to_string();
+        if self.bots.max_context_failures == 0 {
+            return Err("max_context_failures must be at least 1".to_string());
+        }
+        if !(1..=300).contains(&self.bots.retry_backoff_secs) {
+            return Err("retry_backoff_secs must be between 1 and 300".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_overrides_defaults_and_is_validated() {
+        let mut config = Config::parse(
+            r#"
+            [server]
+            port = 8080
+
+            [prices]
+            tracked_assets = ["btc", "SOL", "BTC"]
+
+            [exchange]
+            spot_url = "http://localhost:9000/v2/"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.bind_address, "0.0.0.0");
+        assert_eq!(config.prices.tracked_assets, vec!["BTC", "SOL"]);
+        assert_eq!(config.prices.poll_interval_secs, 5);
+        assert_eq!(config.accounts.starting_balance, 10_000.0);
+        assert_eq!(config.exchange.spot_url, "http://localhost:9000/v2");
+        assert_eq!(config.news.api_token, None);
+        assert_eq!(config.bots.max_context_failures, 5);
+
+        // Backoff doubles per failure, up to its cap
+        let backoffs: Vec<u64> = [1, 2, 3, 4, 40].iter().map(|&n| config.bots.retry_backoff(n).as_secs()).collect();
+        assert_eq!(backoffs, vec![5, 10, 20, 40, 600]);
+
+        assert!(Config::parse("[server]\nprot = 1").is_err());
+        let mut config = Config::parse("[prices]\npoll_interval_secs = 0").unwrap();
+        assert!(config.validate().is_err());
+        let mut config = Config::parse("[bots]\nmax_context_failures = 0").unwrap();
+        assert!(config.validate().is_err());
+    }
+}
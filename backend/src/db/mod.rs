@@ -1,41 +1,84 @@
-use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use sqlx::{any::AnyPoolOptions, AnyPool};
 use std::time::Duration;
 
 pub mod queries;
 
+/// Connection pool shared by all queries
+/// Backed by SQLite or PostgreSQL depending on the DATABASE_URL scheme
+pub type DbPool = AnyPool;
+
+/// Database engine selected from the connection URL
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DbBackend {
+    Sqlite,
+    Postgres,
+}
+
+impl DbBackend {
+    pub fn from_url(database_url: &str) -> Option<Self> {
+        if database_url.starts_with("sqlite:") {
+            Some(DbBackend::Sqlite)
+        } else if database_url.starts_with("postgres:") || database_url.starts_with("postgresql:") {
+            Some(DbBackend::Postgres)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Database {
-    pool: SqlitePool,
+    pool: DbPool,
+    backend: DbBackend,
 }
 
 impl Database {
     pub async fn new(database_url: &str) -> Result<Self, sqlx::Error> {
+        sqlx::any::install_default_drivers();
+
+        let backend = DbBackend::from_url(database_url).ok_or_else(|| {
+            sqlx::Error::Configuration(
+                format!("Unsupported DATABASE_URL scheme: {}", database_url).into(),
+            )
+        })?;
+
         // Add SQLite connection options for proper file handling in containers
-        let connection_url = if database_url.starts_with("sqlite:") {
+        let connection_url = if backend == DbBackend::Sqlite && !database_url.contains('?') {
             format!("{}?mode=rwc", database_url)
         } else {
             database_url.to_string()
         };
 
-        tracing::info!("Connecting with URL: {}", connection_url);
+        tracing::info!("Connecting to {:?} database", backend);
+
+        let max_connections = match backend {
+            DbBackend::Sqlite => 5,
+            DbBackend::Postgres => 20,
+        };
 
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
+        let pool = AnyPoolOptions::new()
+            .max_connections(max_connections)
             .acquire_timeout(Duration::from_secs(3))
             .connect(&connection_url)
             .await?;
 
-        Ok(Self { pool })
+        Ok(Self { pool, backend })
     }
 
-    pub fn pool(&self) -> &SqlitePool {
+    pub fn pool(&self) -> &DbPool {
         &self.pool
     }
 
+    pub fn backend(&self) -> DbBackend {
+        self.backend
+    }
+
     pub async fn run_migrations(&self) -> Result<(), sqlx::Error> {
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
-            .await?;
+        // Each backend keeps its own migration set (SQL dialects differ)
+        match self.backend {
+            DbBackend::Sqlite => sqlx::migrate!("./migrations").run(&self.pool).await?,
+            DbBackend::Postgres => sqlx::migrate!("./migrations_postgres").run(&self.pool).await?,
+        }
         Ok(())
     }
 }
@@ -1,14 +1,42 @@
-use crate::models::{UserData, UserId};
+use crate::models::{
+    AlertCondition, Asset, BotId, NewsItem, NotificationChannel, OrderStatus, OrderType, PricePoint, Trade, TradeSide, TradeSource,
+    TradeSourceKind, TransactionType, UserData, UserId, COPY_EXECUTOR_PREFIX,
+};
 use crate::services::auth_service::{self, AuthError};
-use sqlx::{SqlitePool, Row};
+use crate::services::ledger_service::{self, EntryKind, Posting};
+use crate::services::summary_service::DailySummary;
+use crate::bots::risk_managed::TradeLimits;
+use crate::bots::schedule::BotSchedule;
+use crate::db::{DbBackend, DbPool};
+use crate::pagination::SortOrder;
+use sqlx::any::AnyRow;
+use sqlx::AnyConnection;
+use sqlx::{Column, Row, TypeInfo, ValueRef};
 use std::collections::HashMap;
 
-pub async fn get_user(pool: &SqlitePool, user_id: &UserId) -> Result<Option<UserData>, sqlx::Error> {
+// Queries that bind an Option use `.persistent(false)`: the Any driver sends NULL as an
+// int4 on Postgres, and a cached statement would keep that parameter type for later
+// non-NULL binds. Nullable parameters are also wrapped in CAST for the same reason.
+
+/// Decode a nullable column
+/// The Any driver does not report NULL to `Option<T>`, so check the value kind first
+fn get_optional<'r, T>(row: &'r AnyRow, column: &str) -> Option<T>
+where
+    T: sqlx::Decode<'r, sqlx::Any> + sqlx::Type<sqlx::Any>,
+{
+    let raw = row.try_get_raw(column).ok()?;
+    if raw.type_info().name() == "NULL" {
+        return None;
+    }
+    row.try_get(column).ok()
+}
+
+pub async fn get_user(pool: &DbPool, user_id: &UserId) -> Result<Option<UserData>, sqlx::Error> {
     let row = sqlx::query(
         r#"
-        SELECT user_id, username, cash_balance, asset_balances, trade_history
+        SELECT user_id, username, asset_balances
         FROM users
-        WHERE user_id = ?
+        WHERE user_id = $1
         "#
     )
     .bind(user_id)
@@ -18,23 +46,14 @@ pub async fn get_user(pool: &SqlitePool, user_id: &UserId) -> Result<Option<User
     match row {
         Some(r) => {
             let username: String = r.get("username");
-            let cash_balance: f64 = r.get("cash_balance");
             let asset_balances_str: String = r.get("asset_balances");
-            let trade_history_str: String = r.get("trade_history");
 
-            let mut asset_balances: HashMap<String, f64> = serde_json::from_str(&asset_balances_str)
+            let asset_balances: HashMap<String, f64> = serde_json::from_str(&asset_balances_str)
                 .unwrap_or_default();
-            let trade_history = serde_json::from_str(&trade_history_str)
-                .unwrap_or_default();
-
-            // Migration: Move cash_balance to USD asset if not already there
-            if !asset_balances.contains_key("USD") && cash_balance > 0.0 {
-                asset_balances.insert("USD".to_string(), cash_balance);
-            }
+            let trade_history = load_trades_for_user(pool, user_id).await?;
 
             Ok(Some(UserData {
                 username,
-                cash_balance,  // Keep for backward compat
                 asset_balances,
                 trade_history,
             }))
@@ -43,67 +62,60 @@ pub async fn get_user(pool: &SqlitePool, user_id: &UserId) -> Result<Option<User
     }
 }
 
-pub async fn save_user(pool: &SqlitePool, user_id: &UserId, user: &UserData) -> Result<(), sqlx::Error> {
+pub async fn save_user(pool: &DbPool, user_id: &UserId, user: &UserData) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    upsert_user(&mut conn, user_id, user).await
+}
+
+async fn upsert_user(conn: &mut AnyConnection, user_id: &UserId, user: &UserData) -> Result<(), sqlx::Error> {
     let asset_balances_json = serde_json::to_string(&user.asset_balances)
         .unwrap_or_else(|_| "{}".to_string());
-    let trade_history_json = serde_json::to_string(&user.trade_history)
-        .unwrap_or_else(|_| "[]".to_string());
 
+    // Trade history lives in the trades table (see record_transaction)
     sqlx::query(
         r#"
-        INSERT INTO users (user_id, username, cash_balance, asset_balances, trade_history)
-        VALUES (?, ?, ?, ?, ?)
+        INSERT INTO users (user_id, username, asset_balances)
+        VALUES ($1, $2, $3)
         ON CONFLICT(user_id) DO UPDATE SET
             username = excluded.username,
-            cash_balance = excluded.cash_balance,
-            asset_balances = excluded.asset_balances,
-            trade_history = excluded.trade_history
+            asset_balances = excluded.asset_balances
         "#
     )
     .bind(user_id)
     .bind(&user.username)
-    .bind(user.cash_balance)
     .bind(asset_balances_json)
-    .bind(trade_history_json)
-    .execute(pool)
+    .execute(&mut *conn)
     .await?;
 
     Ok(())
 }
 
-pub async fn load_all_users(pool: &SqlitePool) -> Result<HashMap<UserId, UserData>, sqlx::Error> {
+pub async fn load_all_users(pool: &DbPool) -> Result<HashMap<UserId, UserData>, sqlx::Error> {
     let rows = sqlx::query(
         r#"
-        SELECT user_id, username, cash_balance, asset_balances, trade_history
+        SELECT user_id, username, asset_balances
         FROM users
         "#
     )
     .fetch_all(pool)
     .await?;
 
+    let mut trades_by_user = load_all_trades(pool).await?;
+
     let mut users = HashMap::new();
     for row in rows {
         let user_id: String = row.get("user_id");
         let username: String = row.get("username");
-        let cash_balance: f64 = row.get("cash_balance");
         let asset_balances_str: String = row.get("asset_balances");
-        let trade_history_str: String = row.get("trade_history");
 
-        let mut asset_balances: HashMap<String, f64> = serde_json::from_str(&asset_balances_str)
-            .unwrap_or_default();
-        let trade_history = serde_json::from_str(&trade_history_str)
+        let asset_balances: HashMap<String, f64> = serde_json::from_str(&asset_balances_str)
             .unwrap_or_default();
-
-        // Migration: Move cash_balance to USD asset if not already there
-        if !asset_balances.contains_key("USD") && cash_balance > 0.0 {
-            asset_balances.insert("USD".to_string(), cash_balance);
-        }
+        let trade_history = trades_by_user.remove(&user_id).unwrap_or_default();
 
         users.insert(
             user_id,
             UserData {
                 username,
-                cash_balance,  // Keep for backward compat
                 asset_balances,
                 trade_history,
             },
@@ -113,10 +125,10 @@ pub async fn load_all_users(pool: &SqlitePool) -> Result<HashMap<UserId, UserDat
     Ok(users)
 }
 
-pub async fn delete_user(pool: &SqlitePool, user_id: &UserId) -> Result<(), sqlx::Error> {
+pub async fn delete_user(pool: &DbPool, user_id: &UserId) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        DELETE FROM users WHERE user_id = ?
+        DELETE FROM users WHERE user_id = $1
         "#
     )
     .bind(user_id)
@@ -127,15 +139,16 @@ pub async fn delete_user(pool: &SqlitePool, user_id: &UserId) -> Result<(), sqlx
 }
 
 pub async fn create_user(
-    pool: &SqlitePool,
+    pool: &DbPool,
     user_id: &UserId,
     username: &str,
     password: &str,
+    starting_balance: f64,
 ) -> Result<(), AuthError> {
     // Check if username already exists
     let existing = sqlx::query(
         r#"
-        SELECT user_id FROM users WHERE username = ?
+        SELECT user_id FROM users WHERE username = $1
         "#
     )
     .bind(username)
@@ -151,24 +164,20 @@ pub async fn create_user(
     let password_hash = auth_service::hash_password(password)?;
 
     // Create user data
-    let user_data = UserData::new(username.to_string());
+    let user_data = UserData::new(username.to_string(), starting_balance);
     let asset_balances_json = serde_json::to_string(&user_data.asset_balances)
         .unwrap_or_else(|_| "{}".to_string());
-    let trade_history_json = serde_json::to_string(&user_data.trade_history)
-        .unwrap_or_else(|_| "[]".to_string());
 
     // Insert user with password
     sqlx::query(
         r#"
-        INSERT INTO users (user_id, username, cash_balance, asset_balances, trade_history, password_hash)
-        VALUES (?, ?, ?, ?, ?, ?)
+        INSERT INTO users (user_id, username, asset_balances, password_hash)
+        VALUES ($1, $2, $3, $4)
         "#
     )
     .bind(user_id)
     .bind(username)
-    .bind(user_data.cash_balance)
     .bind(asset_balances_json)
-    .bind(trade_history_json)
     .bind(password_hash)
     .execute(pool)
     .await
@@ -178,12 +187,12 @@ pub async fn create_user(
 }
 
 pub async fn get_user_by_username(
-    pool: &SqlitePool,
+    pool: &DbPool,
     username: &str,
 ) -> Result<Option<(UserId, String)>, AuthError> {
     let row = sqlx::query(
         r#"
-        SELECT user_id, password_hash FROM users WHERE username = ?
+        SELECT user_id, password_hash FROM users WHERE username = $1
         "#
     )
     .bind(username)
@@ -194,7 +203,7 @@ pub async fn get_user_by_username(
     match row {
         Some(r) => {
             let user_id: String = r.get("user_id");
-            let password_hash: Option<String> = r.get("password_hash");
+            let password_hash: Option<String> = get_optional(&r, "password_hash");
 
             match password_hash {
                 Some(hash) => Ok(Some((user_id, hash))),
@@ -206,7 +215,7 @@ pub async fn get_user_by_username(
 }
 
 pub async fn verify_user_credentials(
-    pool: &SqlitePool,
+    pool: &DbPool,
     username: &str,
     password: &str,
 ) -> Result<UserId, AuthError> {
@@ -221,3 +230,3778 @@ pub async fn verify_user_credentials(
         None => Err(AuthError::InvalidCredentials),
     }
 }
+
+/// Whether a user has a password to sign in with; OAuth-only accounts have none
+pub async fn has_password(pool: &DbPool, user_id: &UserId) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT password_hash FROM users WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.is_some_and(|r| get_optional::<String>(&r, "password_hash").is_some()))
+}
+
+/// Replace a user's password hash; false if the user does not exist
+pub async fn update_password_hash(pool: &DbPool, user_id: &UserId, password_hash: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE users SET password_hash = $1 WHERE user_id = $2
+        "#
+    )
+    .bind(password_hash)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_user_role(pool: &DbPool, user_id: &UserId) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT role FROM users WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.get("role")))
+}
+
+/// Get roles for all persisted users (user_id -> role)
+pub async fn load_user_roles(pool: &DbPool) -> Result<HashMap<UserId, String>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT user_id, role FROM users
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.get("user_id"), r.get("role")))
+        .collect())
+}
+
+/// Grant the admin role to the given usernames (returns number of rows updated)
+pub async fn promote_admins(pool: &DbPool, usernames: &[String]) -> Result<u64, sqlx::Error> {
+    let mut updated = 0;
+    for username in usernames {
+        let result = sqlx::query(
+            r#"
+            UPDATE users SET role = 'admin' WHERE username = $1
+            "#
+        )
+        .bind(username)
+        .execute(pool)
+        .await?;
+        updated += result.rows_affected();
+    }
+
+    Ok(updated)
+}
+
+/// Remove every row belonging to a user in a single transaction
+pub async fn delete_account(pool: &DbPool, user_id: &UserId) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM oauth_identities WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM audit_log WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM trades WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM trades_archive WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM bots WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM sessions WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM price_alerts WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM alert_history WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM watchlists WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM webhook_secrets WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM copy_follows WHERE follower_id = $1 OR leader_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM orders WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM user_settings WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM notification_endpoints WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM notification_deliveries WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM password_resets WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM competition_entries WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM competition_trades WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM league_members WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM user_achievements WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM fee_tiers WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM bot_decisions WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM bot_runs WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM invariant_violations WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM daily_summaries WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM ledger_entries WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM users WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Look up the local user linked to an external identity
+pub async fn find_oauth_user(
+    pool: &DbPool,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<(UserId, String)>, AuthError> {
+    let row = sqlx::query(
+        r#"
+        SELECT u.user_id, u.username
+        FROM oauth_identities o
+        JOIN users u ON u.user_id = o.user_id
+        WHERE o.provider = $1 AND o.subject = $2
+        "#
+    )
+    .bind(provider)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+    Ok(row.map(|r| (r.get("user_id"), r.get("username"))))
+}
+
+/// Create a password-less user linked to an external identity
+/// Returns the username actually assigned (suffixed if the preferred one is taken)
+pub async fn create_oauth_user(
+    pool: &DbPool,
+    user_id: &UserId,
+    preferred_username: &str,
+    provider: &str,
+    subject: &str,
+    starting_balance: f64,
+) -> Result<String, AuthError> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+    // Find a free username: "alice", then "alice-github", "alice-github-2", ...
+    let mut username = preferred_username.to_string();
+    let mut attempt = 1;
+    loop {
+        let existing = sqlx::query(
+            r#"
+            SELECT user_id FROM users WHERE username = $1
+            "#
+        )
+        .bind(&username)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        if existing.is_none() {
+            break;
+        }
+
+        username = if attempt == 1 {
+            format!("{}-{}", preferred_username, provider)
+        } else {
+            format!("{}-{}-{}", preferred_username, provider, attempt)
+        };
+        attempt += 1;
+    }
+
+    let user_data = UserData::new(username.clone(), starting_balance);
+    let asset_balances_json = serde_json::to_string(&user_data.asset_balances)
+        .unwrap_or_else(|_| "{}".to_string());
+
+    sqlx::query(
+        r#"
+        INSERT INTO users (user_id, username, asset_balances)
+        VALUES ($1, $2, $3)
+        "#
+    )
+    .bind(user_id)
+    .bind(&username)
+    .bind(asset_balances_json)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO oauth_identities (provider, subject, user_id)
+        VALUES ($1, $2, $3)
+        "#
+    )
+    .bind(provider)
+    .bind(subject)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+    Ok(username)
+}
+
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub user_id: Option<UserId>,
+    pub action: String,
+    pub details: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Size of the database in bytes
+pub async fn database_size_bytes(pool: &DbPool, backend: DbBackend) -> Result<i64, sqlx::Error> {
+    let sql = match backend {
+        DbBackend::Sqlite => "SELECT page_count * page_size AS size FROM pragma_page_count(), pragma_page_size()",
+        DbBackend::Postgres => "SELECT pg_database_size(current_database()) AS size",
+    };
+    let row = sqlx::query(sql).fetch_one(pool).await?;
+    Ok(row.get("size"))
+}
+
+/// Comparison and ORDER BY direction for keyset pagination on `id`
+fn keyset(sort: SortOrder) -> (&'static str, &'static str) {
+    match sort {
+        SortOrder::Asc => (">", "ASC"),
+        SortOrder::Desc => ("<", "DESC"),
+    }
+}
+
+/// Entries for a user whose account is gone are dropped: a bot stopped by the account's deletion
+/// audits its stop in the background, and may get there after the account's rows were deleted
+pub async fn insert_audit_entry(
+    pool: &DbPool,
+    user_id: Option<&str>,
+    action: &str,
+    details: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (user_id, action, details)
+        SELECT CAST($1 AS TEXT), $2, $3
+        WHERE CAST($1 AS TEXT) IS NULL OR EXISTS (SELECT 1 FROM users WHERE user_id = CAST($1 AS TEXT))
+        "#
+    )
+    .bind(user_id)
+    .bind(action)
+    .bind(details)
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A page of audit entries (all users when user_id is None)
+/// `cursor` is the id of the last entry from the previous page (exclusive)
+pub async fn get_audit_entries(
+    pool: &DbPool,
+    user_id: Option<&str>,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT id, user_id, action, details, CAST(created_at AS TEXT) AS created_at
+        FROM audit_log
+        WHERE (CAST($1 AS TEXT) IS NULL OR user_id = CAST($1 AS TEXT))
+          AND (CAST($2 AS BIGINT) IS NULL OR id {after} CAST($2 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $3
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| AuditEntry {
+            id: r.get("id"),
+            user_id: get_optional(&r, "user_id"),
+            action: r.get("action"),
+            details: r.get("details"),
+            created_at: parse_db_timestamp(&r.get::<String, _>("created_at")),
+        })
+        .collect())
+}
+
+fn transaction_type_str(transaction_type: &TransactionType) -> &'static str {
+    match transaction_type {
+        TransactionType::Trade => "Trade",
+        TransactionType::Deposit => "Deposit",
+        TransactionType::Withdrawal => "Withdrawal",
+    }
+}
+
+fn side_str(side: &TradeSide) -> &'static str {
+    match side {
+        TradeSide::Buy => "Buy",
+        TradeSide::Sell => "Sell",
+    }
+}
+
+/// Parse a `CAST(... AS TEXT)` timestamp column
+/// SQLite yields "YYYY-MM-DD HH:MM:SS", PostgreSQL may append fractional seconds
+fn parse_db_timestamp(value: &str) -> chrono::NaiveDateTime {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").unwrap_or_default()
+}
+
+fn row_to_trade(row: &AnyRow) -> Trade {
+    let transaction_type: String = row.get("transaction_type");
+    let side: String = row.get("side");
+    let timestamp: String = row.get("timestamp");
+    let executed_by_bot: Option<String> = get_optional(row, "executed_by_bot");
+
+    Trade {
+        user_id: row.get("user_id"),
+        transaction_type: match transaction_type.as_str() {
+            "Deposit" => TransactionType::Deposit,
+            "Withdrawal" => TransactionType::Withdrawal,
+            _ => TransactionType::Trade,
+        },
+        base_asset: row.get("base_asset"),
+        quote_asset: row.get("quote_asset"),
+        side: if side == "Sell" { TradeSide::Sell } else { TradeSide::Buy },
+        quantity: row.get("quantity"),
+        price: row.get("price"),
+        timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp)
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .unwrap_or_default(),
+        base_usd_price: get_optional(row, "base_usd_price"),
+        quote_usd_price: get_optional(row, "quote_usd_price"),
+        source: TradeSource::from_executor(executed_by_bot.clone(), get_optional(row, "bot_id")),
+        executed_by_bot,
+        fee: row.get("fee"),
+    }
+}
+
+/// Write a user's new balances and the trade that produced them in one transaction
+/// Returns the id of the new trades row
+pub async fn record_transaction(
+    pool: &DbPool,
+    user_id: &UserId,
+    user: &UserData,
+    trade: &Trade,
+) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    upsert_user(&mut tx, user_id, user).await?;
+    let trade_id = insert_trade(&mut tx, trade).await?;
+    let postings = ledger_service::postings(trade);
+    let before = ledger_service::unapply(&user.asset_balances, &postings);
+    append_ledger(&mut tx, user_id, &before, &postings, Some(trade_id), trade.timestamp).await?;
+
+    tx.commit().await?;
+
+    Ok(trade_id)
+}
+
+/// Save a user's balances together with a run of past transactions, in one transaction
+pub async fn record_transactions(
+    pool: &DbPool,
+    user_id: &UserId,
+    user: &UserData,
+    trades: &[Trade],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    upsert_user(&mut tx, user_id, user).await?;
+    let postings: Vec<_> = trades.iter().map(ledger_service::postings).collect();
+    let mut balances = ledger_service::unapply(&user.asset_balances, &postings.concat());
+    for (trade, postings) in trades.iter().zip(&postings) {
+        let trade_id = insert_trade(&mut tx, trade).await?;
+        balances = append_ledger(&mut tx, user_id, &balances, postings, Some(trade_id), trade.timestamp).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Append a single fill (or deposit/withdrawal) to the trades table
+async fn insert_trade(conn: &mut AnyConnection, trade: &Trade) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO trades (
+            user_id, transaction_type, base_asset, quote_asset, side, quantity, price,
+            timestamp, base_usd_price, quote_usd_price, executed_by_bot, bot_id, fee
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, CAST($11 AS TEXT), CAST($12 AS TEXT), $13)
+        RETURNING id
+        "#
+    )
+    .bind(&trade.user_id)
+    .bind(transaction_type_str(&trade.transaction_type))
+    .bind(&trade.base_asset)
+    .bind(&trade.quote_asset)
+    .bind(side_str(&trade.side))
+    .bind(trade.quantity)
+    .bind(trade.price)
+    .bind(trade.timestamp.to_rfc3339())
+    .bind(trade.base_usd_price)
+    .bind(trade.quote_usd_price)
+    .bind(&trade.executed_by_bot)
+    .bind(trade.source.bot_instance())
+    .bind(trade.fee)
+    .persistent(false)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(row.get("id"))
+}
+
+/// Full trade history for a user, oldest first
+pub async fn load_trades_for_user(pool: &DbPool, user_id: &UserId) -> Result<Vec<Trade>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM trades WHERE user_id = $1 ORDER BY id ASC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_trade).collect())
+}
+
+/// Trade history for every user, grouped by user_id (oldest first)
+async fn load_all_trades(pool: &DbPool) -> Result<HashMap<UserId, Vec<Trade>>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM trades ORDER BY id ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut trades: HashMap<UserId, Vec<Trade>> = HashMap::new();
+    for row in &rows {
+        let trade = row_to_trade(row);
+        trades.entry(trade.user_id.clone()).or_default().push(trade);
+    }
+
+    Ok(trades)
+}
+
+/// A page of trade history
+/// `cursor` is the id of the last row from the previous page (exclusive)
+/// `source` keeps one kind of trade and `bot_id` one bot instance's; deposits and withdrawals count as manual
+pub async fn get_trades_page(
+    pool: &DbPool,
+    user_id: &UserId,
+    source: Option<TradeSourceKind>,
+    bot_id: Option<&BotId>,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<(i64, Trade)>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let source = match source {
+        None => "1 = 1".to_string(),
+        Some(TradeSourceKind::Manual) => "executed_by_bot IS NULL".to_string(),
+        Some(TradeSourceKind::Bot) => format!(
+            "executed_by_bot IS NOT NULL AND (bot_id IS NOT NULL OR executed_by_bot NOT LIKE '{COPY_EXECUTOR_PREFIX}%')"
+        ),
+        Some(TradeSourceKind::Copy) => format!("bot_id IS NULL AND executed_by_bot LIKE '{COPY_EXECUTOR_PREFIX}%'"),
+    };
+    let sql = format!(
+        r#"
+        SELECT * FROM trades
+        WHERE user_id = $1 AND (CAST($2 AS BIGINT) IS NULL OR id {after} CAST($2 AS BIGINT))
+            AND ({source}) AND (CAST($4 AS TEXT) IS NULL OR bot_id = CAST($4 AS TEXT))
+        ORDER BY id {order}
+        LIMIT $3
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .bind(bot_id)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| (r.get::<i64, _>("id"), row_to_trade(r)))
+        .collect())
+}
+
+/// Replace a user's portfolio with `user` and clear their trades; the ledger keeps the
+/// trades' entries and records the reset as the difference from `previous`
+pub async fn reset_portfolio(
+    pool: &DbPool,
+    user_id: &UserId,
+    previous: &UserData,
+    user: &UserData,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM trades WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    upsert_user(&mut tx, user_id, user).await?;
+    let postings = ledger_service::difference(&previous.asset_balances, &user.asset_balances, EntryKind::Reset);
+    append_ledger(&mut tx, user_id, &previous.asset_balances, &postings, None, at).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Persisted configuration of a running bot
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BotConfigRow {
+    pub bot_id: BotId,
+    pub user_id: UserId,
+    pub strategy: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub parameters: serde_json::Value,
+    pub stoploss_amount: f64,
+    pub initial_portfolio_value_usd: f64,
+    pub dry_run: bool, // Decisions are logged but never traded
+    pub trade_limits: TradeLimits,
+    pub max_base_position: Option<f64>, // Hard cap on the base asset held, checked as trades execute
+    pub schedule: Option<BotSchedule>, // When the bot may trade; None for always
+    pub paused: bool, // Paused by its user: ticking, but not trading
+}
+
+pub async fn save_bot_config(pool: &DbPool, config: &BotConfigRow) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO bots (
+            id, user_id, strategy, base_asset, quote_asset, parameters,
+            stoploss_amount, initial_portfolio_value_usd, dry_run, trade_limits, max_base_position, schedule, paused
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT(id) DO UPDATE SET
+            strategy = excluded.strategy,
+            base_asset = excluded.base_asset,
+            quote_asset = excluded.quote_asset,
+            parameters = excluded.parameters,
+            stoploss_amount = excluded.stoploss_amount,
+            initial_portfolio_value_usd = excluded.initial_portfolio_value_usd,
+            dry_run = excluded.dry_run,
+            trade_limits = excluded.trade_limits,
+            max_base_position = excluded.max_base_position,
+            schedule = excluded.schedule,
+            paused = excluded.paused,
+            started_at = excluded.started_at
+        "#
+    )
+    .bind(&config.bot_id)
+    .bind(&config.user_id)
+    .bind(&config.strategy)
+    .bind(&config.base_asset)
+    .bind(&config.quote_asset)
+    .bind(config.parameters.to_string())
+    .bind(config.stoploss_amount)
+    .bind(config.initial_portfolio_value_usd)
+    .bind(config.dry_run as i64)
+    .bind(serde_json::to_string(&config.trade_limits).unwrap_or_else(|_| "{}".to_string()))
+    .bind(config.max_base_position)
+    .bind(config.schedule.as_ref().and_then(|schedule| serde_json::to_string(schedule).ok()))
+    .bind(config.paused as i64)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_bot_paused(pool: &DbPool, bot_id: &BotId, paused: bool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE bots SET paused = $1 WHERE id = $2
+        "#
+    )
+    .bind(paused as i64)
+    .bind(bot_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_bot_config(pool: &DbPool, bot_id: &BotId) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        DELETE FROM bots WHERE id = $1
+        "#
+    )
+    .bind(bot_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Remove every bot of a user, so none is restarted
+pub async fn delete_user_bot_configs(pool: &DbPool, user_id: &UserId) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        DELETE FROM bots WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn load_bot_configs(pool: &DbPool) -> Result<Vec<BotConfigRow>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, user_id, strategy, base_asset, quote_asset, parameters,
+               stoploss_amount, initial_portfolio_value_usd, dry_run, trade_limits, max_base_position, schedule, paused
+        FROM bots
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_bot_config).collect())
+}
+
+pub async fn get_bot_config(pool: &DbPool, bot_id: &BotId) -> Result<Option<BotConfigRow>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, user_id, strategy, base_asset, quote_asset, parameters,
+               stoploss_amount, initial_portfolio_value_usd, dry_run, trade_limits, max_base_position, schedule, paused
+        FROM bots
+        WHERE id = $1
+        "#
+    )
+    .bind(bot_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_bot_config))
+}
+
+/// A user's bots, oldest first
+pub async fn list_user_bot_configs(pool: &DbPool, user_id: &UserId) -> Result<Vec<BotConfigRow>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, user_id, strategy, base_asset, quote_asset, parameters,
+               stoploss_amount, initial_portfolio_value_usd, dry_run, trade_limits, max_base_position, schedule, paused
+        FROM bots
+        WHERE user_id = $1
+        ORDER BY started_at, id
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_bot_config).collect())
+}
+
+/// When a running bot was started; restarts after a crash keep the original time
+pub async fn bot_started_at(
+    pool: &DbPool,
+    bot_id: &BotId,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT CAST(started_at AS TEXT) AS started_at FROM bots WHERE id = $1
+        "#
+    )
+    .bind(bot_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| parse_db_timestamp(&r.get::<String, _>("started_at")).and_utc()))
+}
+
+fn row_to_bot_config(row: &AnyRow) -> BotConfigRow {
+    let parameters: String = row.get("parameters");
+    let trade_limits: String = row.get("trade_limits");
+    BotConfigRow {
+        bot_id: row.get("id"),
+        user_id: row.get("user_id"),
+        strategy: row.get("strategy"),
+        base_asset: row.get("base_asset"),
+        quote_asset: row.get("quote_asset"),
+        parameters: serde_json::from_str(&parameters).unwrap_or_default(),
+        stoploss_amount: row.get("stoploss_amount"),
+        initial_portfolio_value_usd: row.get("initial_portfolio_value_usd"),
+        dry_run: row.get::<i64, _>("dry_run") != 0,
+        trade_limits: serde_json::from_str(&trade_limits).unwrap_or_default(),
+        max_base_position: get_optional(row, "max_base_position"),
+        schedule: get_optional::<String>(row, "schedule").and_then(|schedule| serde_json::from_str(&schedule).ok()),
+        paused: row.get::<i64, _>("paused") != 0,
+    }
+}
+
+/// One tick of a live bot: what it decided, at what price, and what came of it
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct BotDecisionEntry {
+    pub id: i64,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub bot_instance_id: BotId,
+    pub bot_name: String,
+    pub tick: i64,
+    pub price: f64,
+    #[schema(value_type = Object)]
+    pub decision: serde_json::Value, // e.g. {"action":"buy","quote_amount":100.0}
+    pub outcome: String, // "executed", "order_placed", "no_action", "insufficient_funds", "error", "dry_run" for a trade a dry run skipped, "shadow_trade" for one in a comparison's shadow account, "position_capped" for a buy cut down or skipped by max_base_position, "outside_schedule" for a trade held back by the schedule, or "paused" for one held back while the bot is paused
+    pub trade_id: Option<i64>, // The resulting fill, as in the ledger
+    pub decided_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn insert_bot_decision(pool: &DbPool, entry: &BotDecisionEntry) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO bot_decisions (user_id, bot_id, bot_name, tick, price, decision, outcome, trade_id, decided_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, CAST($8 AS BIGINT), $9)
+        "#
+    )
+    .bind(&entry.user_id)
+    .bind(&entry.bot_instance_id)
+    .bind(&entry.bot_name)
+    .bind(entry.tick)
+    .bind(entry.price)
+    .bind(entry.decision.to_string())
+    .bind(&entry.outcome)
+    .bind(entry.trade_id)
+    .bind(entry.decided_at.timestamp())
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A page of a user's bot decisions, optionally of one bot
+pub async fn get_bot_decisions(
+    pool: &DbPool,
+    user_id: &UserId,
+    bot_id: Option<&BotId>,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<BotDecisionEntry>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT id, user_id, bot_id, bot_name, tick, price, decision, outcome, trade_id, decided_at
+        FROM bot_decisions
+        WHERE user_id = $1
+          AND (CAST($2 AS TEXT) IS NULL OR bot_id = CAST($2 AS TEXT))
+          AND (CAST($3 AS BIGINT) IS NULL OR id {after} CAST($3 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $4
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(bot_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            let decision: String = r.get("decision");
+            BotDecisionEntry {
+                id: r.get("id"),
+                user_id: r.get("user_id"),
+                bot_instance_id: r.get("bot_id"),
+                bot_name: r.get("bot_name"),
+                tick: r.get("tick"),
+                price: r.get("price"),
+                decision: serde_json::from_str(&decision).unwrap_or_default(),
+                outcome: r.get("outcome"),
+                trade_id: get_optional(r, "trade_id"),
+                decided_at: from_unix(r.get("decided_at")),
+            }
+        })
+        .collect())
+}
+
+/// Delete bot decisions made before `cutoff` (unix seconds)
+pub async fn prune_bot_decisions_before(pool: &DbPool, cutoff: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM bot_decisions WHERE decided_at < $1
+        "#
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// A bot's run from its start until it stopped (or until now, while it runs)
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct BotRun {
+    pub id: i64,
+    pub bot_instance_id: BotId,
+    pub user_id: UserId,
+    pub bot_name: String,
+    pub strategy: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub dry_run: bool,
+    pub status: String, // "running", "stopped" (by the user or replaced), "stoploss", or "failed" (errors, insufficient funds)
+    pub stop_reason: Option<String>,
+    pub errors: i64, // Errors the bot hit while running, retried ones included
+    pub last_error: Option<String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub stopped_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Open a run for a bot; a bot relaunched after a restart keeps the run it has
+pub async fn start_bot_run(
+    pool: &DbPool,
+    config: &BotConfigRow,
+    bot_name: &str,
+    started_at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO bot_runs (bot_id, user_id, bot_name, strategy, base_asset, quote_asset, dry_run, status, started_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'running', $8)
+        ON CONFLICT(bot_id) DO NOTHING
+        "#
+    )
+    .bind(&config.bot_id)
+    .bind(&config.user_id)
+    .bind(bot_name)
+    .bind(&config.strategy)
+    .bind(&config.base_asset)
+    .bind(&config.quote_asset)
+    .bind(config.dry_run as i64)
+    .bind(started_at.timestamp())
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Count an error against a bot's run
+pub async fn record_bot_run_error(pool: &DbPool, bot_id: &BotId, message: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE bot_runs SET errors = errors + 1, last_error = $2 WHERE bot_id = $1
+        "#
+    )
+    .bind(bot_id)
+    .bind(message)
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Close a bot's run; a run already closed keeps how it ended
+pub async fn finish_bot_run(
+    pool: &DbPool,
+    bot_id: &BotId,
+    status: &str,
+    reason: &str,
+    stopped_at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE bot_runs SET status = $2, stop_reason = $3, stopped_at = $4
+        WHERE bot_id = $1 AND stopped_at IS NULL
+        "#
+    )
+    .bind(bot_id)
+    .bind(status)
+    .bind(reason)
+    .bind(stopped_at.timestamp())
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A page of bot runs, in start order, of one user or (for admins) everyone, optionally of one bot
+pub async fn get_bot_runs(
+    pool: &DbPool,
+    user_id: Option<&str>,
+    bot_id: Option<&BotId>,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<BotRun>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT id, bot_id, user_id, bot_name, strategy, base_asset, quote_asset, dry_run, status,
+               stop_reason, errors, last_error, started_at, stopped_at
+        FROM bot_runs
+        WHERE (CAST($1 AS TEXT) IS NULL OR user_id = CAST($1 AS TEXT))
+          AND (CAST($2 AS TEXT) IS NULL OR bot_id = CAST($2 AS TEXT))
+          AND (CAST($3 AS BIGINT) IS NULL OR id {after} CAST($3 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $4
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(bot_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| BotRun {
+            id: r.get("id"),
+            bot_instance_id: r.get("bot_id"),
+            user_id: r.get("user_id"),
+            bot_name: r.get("bot_name"),
+            strategy: r.get("strategy"),
+            base_asset: r.get("base_asset"),
+            quote_asset: r.get("quote_asset"),
+            dry_run: r.get::<i64, _>("dry_run") != 0,
+            status: r.get("status"),
+            stop_reason: get_optional(r, "stop_reason"),
+            errors: r.get("errors"),
+            last_error: get_optional(r, "last_error"),
+            started_at: from_unix(r.get("started_at")),
+            stopped_at: get_optional::<i64>(r, "stopped_at").map(from_unix),
+        })
+        .collect())
+}
+
+/// Move trades older than `cutoff` (RFC 3339 prefix) into trades_archive
+/// Deposits and withdrawals stay, since funding totals are summed from them
+/// Returns the number of rows moved
+pub async fn archive_trades_before(pool: &DbPool, cutoff: &str) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO trades_archive (
+            id, user_id, transaction_type, base_asset, quote_asset, side, quantity, price,
+            timestamp, base_usd_price, quote_usd_price, executed_by_bot, bot_id, fee
+        )
+        SELECT
+            id, user_id, transaction_type, base_asset, quote_asset, side, quantity, price,
+            timestamp, base_usd_price, quote_usd_price, executed_by_bot, bot_id, fee
+        FROM trades
+        WHERE timestamp < $1 AND transaction_type = 'Trade'
+        "#
+    )
+    .bind(cutoff)
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query(
+        r#"
+        DELETE FROM trades WHERE timestamp < $1 AND transaction_type = 'Trade'
+        "#
+    )
+    .bind(cutoff)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Delete audit entries created before `cutoff` ("YYYY-MM-DD HH:MM:SS", UTC)
+pub async fn prune_audit_log_before(pool: &DbPool, cutoff: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM audit_log WHERE CAST(created_at AS TEXT) < $1
+        "#
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Reclaim free pages in the SQLite file (PostgreSQL relies on autovacuum)
+pub async fn vacuum(pool: &DbPool) -> Result<(), sqlx::Error> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    Ok(())
+}
+
+/// Tables included in a JSON export, with timestamp columns cast to TEXT
+/// (the Any driver cannot decode native timestamp types)
+const EXPORT_TABLES: &[(&str, &str)] = &[
+    (
+        "users",
+        "user_id, username, asset_balances, password_hash, role, \
+         CAST(created_at AS TEXT) AS created_at, CAST(updated_at AS TEXT) AS updated_at",
+    ),
+    (
+        "oauth_identities",
+        "provider, subject, user_id, CAST(created_at AS TEXT) AS created_at",
+    ),
+    (
+        "audit_log",
+        "id, user_id, action, details, CAST(created_at AS TEXT) AS created_at",
+    ),
+    ("trades", "*"),
+    (
+        "trades_archive",
+        "id, user_id, transaction_type, base_asset, quote_asset, side, quantity, price, \
+         timestamp, base_usd_price, quote_usd_price, executed_by_bot, bot_id, \
+         CAST(archived_at AS TEXT) AS archived_at",
+    ),
+    (
+        "bots",
+        "id, user_id, strategy, base_asset, quote_asset, parameters, stoploss_amount, \
+         initial_portfolio_value_usd, dry_run, trade_limits, max_base_position, schedule, CAST(started_at AS TEXT) AS started_at",
+    ),
+    ("price_alerts", "*"),
+    ("alert_history", "*"),
+    ("watchlists", "*"),
+    ("copy_follows", "*"),
+    ("orders", "*"),
+    ("user_settings", "*"),
+    ("price_candles", "*"),
+    // Signing secrets stay out of backups, like webhook_secrets
+    ("notification_endpoints", "id, user_id, channel, url, created_at"),
+    ("notification_deliveries", "*"),
+    ("competitions", "*"),
+    ("competition_entries", "*"),
+    ("competition_trades", "*"),
+    ("leagues", "*"),
+    ("league_members", "*"),
+    ("user_achievements", "*"),
+    ("invariant_violations", "*"),
+    ("ledger_entries", "*"),
+    ("news_items", "*"),
+    ("daily_summaries", "*"),
+    ("fee_tiers", "*"),
+    ("bot_decisions", "*"),
+    ("bot_runs", "*"),
+];
+
+fn column_to_json(row: &AnyRow, index: usize) -> serde_json::Value {
+    let type_name = match row.try_get_raw(index) {
+        Ok(raw) => raw.type_info().name().to_string(),
+        Err(_) => return serde_json::Value::Null,
+    };
+
+    match type_name.as_str() {
+        "SMALLINT" | "INTEGER" | "BIGINT" => row
+            .try_get::<i64, _>(index)
+            .map(serde_json::Value::from)
+            .unwrap_or_default(),
+        "REAL" | "DOUBLE" => row
+            .try_get::<f64, _>(index)
+            .map(serde_json::Value::from)
+            .unwrap_or_default(),
+        "TEXT" => row
+            .try_get::<String, _>(index)
+            .map(serde_json::Value::from)
+            .unwrap_or_default(),
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// Dump every table as `{ table: [ {column: value, ...}, ... ] }`
+/// All tables are read in one transaction so the export is a consistent snapshot
+pub async fn export_tables(
+    pool: &DbPool,
+    backend: DbBackend,
+) -> Result<serde_json::Map<String, serde_json::Value>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Postgres' default READ COMMITTED takes a new snapshot per statement
+    if backend == DbBackend::Postgres {
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let mut tables = serde_json::Map::new();
+    for (table, columns) in EXPORT_TABLES {
+        let rows = sqlx::query(&format!("SELECT {} FROM {}", columns, table))
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let rows: Vec<serde_json::Value> = rows
+            .iter()
+            .map(|row| {
+                let object: serde_json::Map<String, serde_json::Value> = row
+                    .columns()
+                    .iter()
+                    .map(|column| (column.name().to_string(), column_to_json(row, column.ordinal())))
+                    .collect();
+                serde_json::Value::Object(object)
+            })
+            .collect();
+
+        tables.insert(table.to_string(), serde_json::Value::Array(rows));
+    }
+
+    tx.commit().await?;
+
+    Ok(tables)
+}
+
+/// Write a consistent copy of the SQLite database to `path` (which must not exist)
+pub async fn snapshot_sqlite(pool: &DbPool, path: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("VACUUM INTO $1")
+        .bind(path)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Take or renew the lease `name` for `holder` until `expires_at` (Unix seconds)
+/// Succeeds if the lease is free, expired, or already held by `holder`
+pub async fn try_acquire_lease(
+    pool: &DbPool,
+    name: &str,
+    holder: &str,
+    now: i64,
+    expires_at: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO leases (name, holder, expires_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT(name) DO UPDATE SET
+            holder = excluded.holder,
+            expires_at = excluded.expires_at
+        WHERE leases.holder = excluded.holder OR leases.expires_at < $4
+        "#
+    )
+    .bind(name)
+    .bind(holder)
+    .bind(expires_at)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn release_lease(pool: &DbPool, name: &str, holder: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        DELETE FROM leases WHERE name = $1 AND holder = $2
+        "#
+    )
+    .bind(name)
+    .bind(holder)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn insert_shared_price(pool: &DbPool, point: &PricePoint) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO shared_prices (asset, price, timestamp)
+        VALUES ($1, $2, $3)
+        "#
+    )
+    .bind(&point.asset)
+    .bind(point.price)
+    .bind(point.timestamp.to_rfc3339())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Most recent published price for an asset
+pub async fn latest_shared_price(pool: &DbPool, asset: &str) -> Result<Option<PricePoint>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT asset, price, timestamp FROM shared_prices
+        WHERE asset = $1
+        ORDER BY id DESC
+        LIMIT 1
+        "#
+    )
+    .bind(asset)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.and_then(|r| {
+        let timestamp: String = r.get("timestamp");
+        Some(PricePoint {
+            timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp)
+                .ok()?
+                .with_timezone(&chrono::Utc),
+            asset: r.get("asset"),
+            price: r.get("price"),
+            volume: None,
+        })
+    }))
+}
+
+/// Delete published prices older than `cutoff` (RFC 3339 prefix)
+pub async fn prune_shared_prices(pool: &DbPool, cutoff: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM shared_prices WHERE timestamp < $1
+        "#
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+
+/// A login session as shown to its owner (the token itself is never stored)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>, // None for sessions from before it was tracked
+}
+
+fn from_unix(secs: i64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(secs, 0).unwrap_or_default()
+}
+
+pub async fn create_session(
+    pool: &DbPool,
+    session: &SessionInfo,
+    user_id: &UserId,
+    token_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO sessions (id, user_id, token_hash, user_agent, ip_address, created_at, expires_at, last_used_at)
+        VALUES ($1, $2, $3, CAST($4 AS TEXT), CAST($5 AS TEXT), $6, $7, $6)
+        "#
+    )
+    .bind(&session.id)
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(session.user_agent.as_deref())
+    .bind(session.ip_address.as_deref())
+    .bind(session.created_at.timestamp())
+    .bind(session.expires_at.timestamp())
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Owner of an unexpired session token
+pub async fn find_session_user(
+    pool: &DbPool,
+    token_hash: &str,
+    now: i64,
+) -> Result<Option<UserId>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT user_id FROM sessions WHERE token_hash = $1 AND expires_at > $2
+        "#
+    )
+    .bind(token_hash)
+    .bind(now)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.get("user_id")))
+}
+
+/// When an unexpired session was signed in, as unix seconds
+pub async fn find_session_created_at(pool: &DbPool, token_hash: &str, now: i64) -> Result<Option<i64>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT created_at FROM sessions WHERE token_hash = $1 AND expires_at > $2
+        "#
+    )
+    .bind(token_hash)
+    .bind(now)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.get("created_at")))
+}
+
+/// A user's unexpired sessions, newest first
+pub async fn list_sessions(
+    pool: &DbPool,
+    user_id: &UserId,
+    now: i64,
+) -> Result<Vec<(SessionInfo, String)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, token_hash, user_agent, ip_address, created_at, expires_at, last_used_at
+        FROM sessions
+        WHERE user_id = $1 AND expires_at > $2
+        ORDER BY created_at DESC
+        "#
+    )
+    .bind(user_id)
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let session = SessionInfo {
+                id: r.get("id"),
+                user_agent: get_optional(&r, "user_agent"),
+                ip_address: get_optional(&r, "ip_address"),
+                created_at: from_unix(r.get("created_at")),
+                expires_at: from_unix(r.get("expires_at")),
+                last_used_at: get_optional::<i64>(&r, "last_used_at").map(from_unix),
+            };
+            (session, r.get("token_hash"))
+        })
+        .collect())
+}
+
+/// Record that a session's token was used at `now`, unless that was already recorded since `since`
+pub async fn touch_session(pool: &DbPool, token_hash: &str, now: i64, since: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE sessions SET last_used_at = $1
+        WHERE token_hash = $2 AND expires_at > $1 AND (last_used_at IS NULL OR last_used_at < $3)
+        "#
+    )
+    .bind(now)
+    .bind(token_hash)
+    .bind(since)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Revoke one of a user's sessions; false if it doesn't exist or belongs to someone else
+pub async fn delete_session(pool: &DbPool, user_id: &UserId, session_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM sessions WHERE id = $1 AND user_id = $2
+        "#
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_session_by_token(pool: &DbPool, token_hash: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM sessions WHERE token_hash = $1
+        "#
+    )
+    .bind(token_hash)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke every session of a user ("log out everywhere")
+pub async fn delete_sessions_for_user(pool: &DbPool, user_id: &UserId) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM sessions WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Revoke every session of a user except the one holding `keep_token_hash`
+pub async fn delete_other_sessions(pool: &DbPool, user_id: &UserId, keep_token_hash: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM sessions WHERE user_id = $1 AND token_hash <> $2
+        "#
+    )
+    .bind(user_id)
+    .bind(keep_token_hash)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn prune_expired_sessions(pool: &DbPool, now: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM sessions WHERE expires_at <= $1
+        "#
+    )
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Store a password reset token's hash, replacing any the user already had outstanding
+pub async fn create_password_reset(
+    pool: &DbPool,
+    user_id: &UserId,
+    token_hash: &str,
+    created_at: i64,
+    expires_at: i64,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM password_resets WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO password_resets (token_hash, user_id, expires_at, created_at)
+        VALUES ($1, $2, $3, $4)
+        "#
+    )
+    .bind(token_hash)
+    .bind(user_id)
+    .bind(expires_at)
+    .bind(created_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Use up a password reset token: its owner, if it exists and has not expired
+pub async fn take_password_reset(pool: &DbPool, token_hash: &str, now: i64) -> Result<Option<UserId>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        DELETE FROM password_resets WHERE token_hash = $1 RETURNING user_id, expires_at
+        "#
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row
+        .filter(|r| r.get::<i64, _>("expires_at") > now)
+        .map(|r| r.get("user_id")))
+}
+
+pub async fn prune_expired_password_resets(pool: &DbPool, now: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM password_resets WHERE expires_at <= $1
+        "#
+    )
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// A user's price alert
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct PriceAlert {
+    pub id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub asset: String,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub window_minutes: Option<i64>, // Lookback for change_pct
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub triggered_at: Option<chrono::DateTime<chrono::Utc>>, // None while armed
+}
+
+/// One firing of a price alert
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct AlertTrigger {
+    pub id: i64,
+    pub alert_id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub asset: String,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub price: f64,
+    pub change_pct: Option<f64>, // Observed change, for change_pct alerts
+    pub triggered_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn row_to_alert(row: &AnyRow) -> PriceAlert {
+    let condition: String = row.get("condition");
+    PriceAlert {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        asset: row.get("asset"),
+        condition: AlertCondition::parse(&condition).unwrap_or(AlertCondition::Above),
+        threshold: row.get("threshold"),
+        window_minutes: get_optional(row, "window_minutes"),
+        created_at: from_unix(row.get("created_at")),
+        triggered_at: get_optional(row, "triggered_at").map(from_unix),
+    }
+}
+
+pub async fn create_alert(pool: &DbPool, alert: &PriceAlert) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO price_alerts (id, user_id, asset, condition, threshold, window_minutes, created_at)
+        VALUES ($1, $2, $3, $4, $5, CAST($6 AS BIGINT), $7)
+        "#
+    )
+    .bind(&alert.id)
+    .bind(&alert.user_id)
+    .bind(&alert.asset)
+    .bind(alert.condition.as_str())
+    .bind(alert.threshold)
+    .bind(alert.window_minutes)
+    .bind(alert.created_at.timestamp())
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A user's alerts, newest first
+pub async fn list_alerts(pool: &DbPool, user_id: &UserId) -> Result<Vec<PriceAlert>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM price_alerts WHERE user_id = $1 ORDER BY created_at DESC, id
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_alert).collect())
+}
+
+pub async fn get_alert(pool: &DbPool, user_id: &UserId, alert_id: &str) -> Result<Option<PriceAlert>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT * FROM price_alerts WHERE id = $1 AND user_id = $2
+        "#
+    )
+    .bind(alert_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_alert))
+}
+
+pub async fn count_alerts(pool: &DbPool, user_id: &UserId) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS count FROM price_alerts WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.get("count"))
+}
+
+/// Change an alert's condition and re-arm it; false if it doesn't exist or belongs to someone else
+pub async fn update_alert(pool: &DbPool, alert: &PriceAlert) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE price_alerts
+        SET asset = $1, condition = $2, threshold = $3, window_minutes = CAST($4 AS BIGINT), triggered_at = NULL
+        WHERE id = $5 AND user_id = $6
+        "#
+    )
+    .bind(&alert.asset)
+    .bind(alert.condition.as_str())
+    .bind(alert.threshold)
+    .bind(alert.window_minutes)
+    .bind(&alert.id)
+    .bind(&alert.user_id)
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete one of a user's alerts; false if it doesn't exist or belongs to someone else
+pub async fn delete_alert(pool: &DbPool, user_id: &UserId, alert_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM price_alerts WHERE id = $1 AND user_id = $2
+        "#
+    )
+    .bind(alert_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Alerts on `asset` that have not fired yet
+pub async fn armed_alerts_for_asset(pool: &DbPool, asset: &str) -> Result<Vec<PriceAlert>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM price_alerts WHERE asset = $1 AND triggered_at IS NULL
+        "#
+    )
+    .bind(asset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_alert).collect())
+}
+
+/// Disarm an alert and record the firing, in one transaction
+/// Returns false if the alert was already fired (e.g. by another instance) or deleted
+pub async fn trigger_alert(pool: &DbPool, trigger: &AlertTrigger) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE price_alerts SET triggered_at = $1 WHERE id = $2 AND triggered_at IS NULL
+        "#
+    )
+    .bind(trigger.triggered_at.timestamp())
+    .bind(&trigger.alert_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO alert_history (alert_id, user_id, asset, condition, threshold, price, change_pct, triggered_at)
+        VALUES ($1, $2, $3, $4, $5, $6, CAST($7 AS DOUBLE PRECISION), $8)
+        "#
+    )
+    .bind(&trigger.alert_id)
+    .bind(&trigger.user_id)
+    .bind(&trigger.asset)
+    .bind(trigger.condition.as_str())
+    .bind(trigger.threshold)
+    .bind(trigger.price)
+    .bind(trigger.change_pct)
+    .bind(trigger.triggered_at.timestamp())
+    .persistent(false)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// A page of a user's fired alerts
+/// `cursor` is the id of the last entry from the previous page (exclusive)
+pub async fn get_alert_history(
+    pool: &DbPool,
+    user_id: &UserId,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<AlertTrigger>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT * FROM alert_history
+        WHERE user_id = $1 AND (CAST($2 AS BIGINT) IS NULL OR id {after} CAST($2 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $3
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            let condition: String = r.get("condition");
+            AlertTrigger {
+                id: r.get("id"),
+                alert_id: r.get("alert_id"),
+                user_id: r.get("user_id"),
+                asset: r.get("asset"),
+                condition: AlertCondition::parse(&condition).unwrap_or(AlertCondition::Above),
+                threshold: r.get("threshold"),
+                price: r.get("price"),
+                change_pct: get_optional(r, "change_pct"),
+                triggered_at: from_unix(r.get("triggered_at")),
+            }
+        })
+        .collect())
+}
+
+/// A user's named list of assets
+#[derive(Debug, Clone)]
+pub struct WatchlistRow {
+    pub id: String,
+    pub user_id: UserId,
+    pub name: String,
+    pub assets: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn row_to_watchlist(row: &AnyRow) -> WatchlistRow {
+    let assets: String = row.get("assets");
+    WatchlistRow {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        name: row.get("name"),
+        assets: serde_json::from_str(&assets).unwrap_or_default(),
+        created_at: from_unix(row.get("created_at")),
+    }
+}
+
+/// Insert a watchlist; false if the user already has one with this name
+pub async fn create_watchlist(pool: &DbPool, watchlist: &WatchlistRow) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO watchlists (id, user_id, name, assets, created_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (user_id, name) DO NOTHING
+        "#
+    )
+    .bind(&watchlist.id)
+    .bind(&watchlist.user_id)
+    .bind(&watchlist.name)
+    .bind(serde_json::to_string(&watchlist.assets).unwrap_or_else(|_| "[]".to_string()))
+    .bind(watchlist.created_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// A user's watchlists, oldest first
+pub async fn list_watchlists(pool: &DbPool, user_id: &UserId) -> Result<Vec<WatchlistRow>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM watchlists WHERE user_id = $1 ORDER BY created_at, name
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_watchlist).collect())
+}
+
+/// Delete one of a user's watchlists; false if it doesn't exist or belongs to someone else
+pub async fn delete_watchlist(pool: &DbPool, user_id: &UserId, watchlist_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM watchlists WHERE id = $1 AND user_id = $2
+        "#
+    )
+    .bind(watchlist_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Every asset on any watchlist, once per list it appears on
+pub async fn watched_assets(pool: &DbPool) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT assets FROM watchlists
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .flat_map(|r| serde_json::from_str::<Vec<String>>(&r.get::<String, _>("assets")).unwrap_or_default())
+        .collect())
+}
+
+/// Set (or replace) a user's webhook secret, invalidating the previous one
+pub async fn set_webhook_secret(
+    pool: &DbPool,
+    user_id: &UserId,
+    secret_hash: &str,
+    created_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_secrets (user_id, secret_hash, created_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id) DO UPDATE SET secret_hash = excluded.secret_hash, created_at = excluded.created_at
+        "#
+    )
+    .bind(user_id)
+    .bind(secret_hash)
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Owner of a webhook secret
+pub async fn find_webhook_user(pool: &DbPool, secret_hash: &str) -> Result<Option<UserId>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT user_id FROM webhook_secrets WHERE secret_hash = $1
+        "#
+    )
+    .bind(secret_hash)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.get("user_id")))
+}
+
+/// When the user's webhook secret was created, if they have one
+pub async fn webhook_secret_created_at(
+    pool: &DbPool,
+    user_id: &UserId,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT created_at FROM webhook_secrets WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| from_unix(r.get("created_at"))))
+}
+
+/// Disable a user's webhook; false if they had no secret
+pub async fn delete_webhook_secret(pool: &DbPool, user_id: &UserId) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM webhook_secrets WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// A copy-trading subscription
+#[derive(Debug, Clone)]
+pub struct CopyFollow {
+    pub follower_id: UserId,
+    pub leader_id: UserId,
+    pub ratio: f64,
+    pub max_trade_usd: Option<f64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn row_to_follow(row: &AnyRow) -> CopyFollow {
+    CopyFollow {
+        follower_id: row.get("follower_id"),
+        leader_id: row.get("leader_id"),
+        ratio: row.get("ratio"),
+        max_trade_usd: get_optional(row, "max_trade_usd"),
+        created_at: from_unix(row.get("created_at")),
+    }
+}
+
+/// Id of the user with this username
+pub async fn find_user_id_by_username(pool: &DbPool, username: &str) -> Result<Option<UserId>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT user_id FROM users WHERE username = $1
+        "#
+    )
+    .bind(username)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.get("user_id")))
+}
+
+/// Follow a leader, or change the settings of an existing follow (keeping its creation time)
+pub async fn upsert_follow(pool: &DbPool, follow: &CopyFollow) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO copy_follows (follower_id, leader_id, ratio, max_trade_usd, created_at)
+        VALUES ($1, $2, $3, CAST($4 AS DOUBLE PRECISION), $5)
+        ON CONFLICT (follower_id, leader_id)
+        DO UPDATE SET ratio = excluded.ratio, max_trade_usd = excluded.max_trade_usd
+        "#
+    )
+    .bind(&follow.follower_id)
+    .bind(&follow.leader_id)
+    .bind(follow.ratio)
+    .bind(follow.max_trade_usd)
+    .bind(follow.created_at.timestamp())
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Leaders a user follows, oldest first
+pub async fn list_following(pool: &DbPool, follower_id: &UserId) -> Result<Vec<CopyFollow>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM copy_follows WHERE follower_id = $1 ORDER BY created_at, leader_id
+        "#
+    )
+    .bind(follower_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_follow).collect())
+}
+
+/// Users following a leader, oldest first
+pub async fn list_followers(pool: &DbPool, leader_id: &UserId) -> Result<Vec<CopyFollow>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM copy_follows WHERE leader_id = $1 ORDER BY created_at, follower_id
+        "#
+    )
+    .bind(leader_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_follow).collect())
+}
+
+/// Stop following a leader; false if the user wasn't following them
+pub async fn delete_follow(pool: &DbPool, follower_id: &UserId, leader_id: &UserId) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM copy_follows WHERE follower_id = $1 AND leader_id = $2
+        "#
+    )
+    .bind(follower_id)
+    .bind(leader_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// A resting limit or stop order
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct Order {
+    pub id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub side: TradeSide,
+    pub order_type: OrderType,
+    pub quantity: f64,
+    pub trigger_price: f64, // In quote asset terms
+    pub status: OrderStatus,
+    pub status_reason: Option<String>, // Why a fill failed
+    pub fill_price: Option<f64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub closed_at: Option<chrono::DateTime<chrono::Utc>>, // None while open
+}
+
+fn row_to_order(row: &AnyRow) -> Order {
+    let side: String = row.get("side");
+    let order_type: String = row.get("order_type");
+    let status: String = row.get("status");
+    Order {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        base_asset: row.get("base_asset"),
+        quote_asset: row.get("quote_asset"),
+        side: if side == "Sell" { TradeSide::Sell } else { TradeSide::Buy },
+        order_type: OrderType::parse(&order_type).unwrap_or(OrderType::Limit),
+        quantity: row.get("quantity"),
+        trigger_price: row.get("trigger_price"),
+        status: OrderStatus::parse(&status).unwrap_or(OrderStatus::Failed),
+        status_reason: get_optional(row, "status_reason"),
+        fill_price: get_optional(row, "fill_price"),
+        created_at: from_unix(row.get("created_at")),
+        closed_at: get_optional(row, "closed_at").map(from_unix),
+    }
+}
+
+pub async fn create_order(pool: &DbPool, order: &Order) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO orders (id, user_id, base_asset, quote_asset, side, order_type, quantity, trigger_price, status, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "#
+    )
+    .bind(&order.id)
+    .bind(&order.user_id)
+    .bind(&order.base_asset)
+    .bind(&order.quote_asset)
+    .bind(side_str(&order.side))
+    .bind(order.order_type.as_str())
+    .bind(order.quantity)
+    .bind(order.trigger_price)
+    .bind(order.status.as_str())
+    .bind(order.created_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A user's orders, newest first, optionally only those with `status`
+pub async fn list_orders(
+    pool: &DbPool,
+    user_id: &UserId,
+    status: Option<OrderStatus>,
+) -> Result<Vec<Order>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM orders
+        WHERE user_id = $1 AND (CAST($2 AS TEXT) IS NULL OR status = CAST($2 AS TEXT))
+        ORDER BY created_at DESC, id
+        "#
+    )
+    .bind(user_id)
+    .bind(status.map(|s| s.as_str()))
+    .persistent(false)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_order).collect())
+}
+
+pub async fn count_open_orders(pool: &DbPool, user_id: &UserId) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS count FROM orders WHERE user_id = $1 AND status = 'open'
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.get("count"))
+}
+
+/// Cancel one of a user's open orders; false if it doesn't exist, belongs to someone else or already closed
+pub async fn cancel_order(pool: &DbPool, user_id: &UserId, order_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE orders SET status = 'cancelled', closed_at = $1
+        WHERE id = $2 AND user_id = $3 AND status = 'open'
+        "#
+    )
+    .bind(chrono::Utc::now().timestamp())
+    .bind(order_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Open orders priced in or against `asset`
+pub async fn open_orders_for_asset(pool: &DbPool, asset: &str) -> Result<Vec<Order>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM orders WHERE status = 'open' AND (base_asset = $1 OR quote_asset = $1)
+        "#
+    )
+    .bind(asset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_order).collect())
+}
+
+/// Mark an open order filled at `price`
+/// Returns false if it was already closed (e.g. filled by another instance, or cancelled)
+pub async fn fill_order(
+    pool: &DbPool,
+    order_id: &str,
+    price: f64,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE orders SET status = 'filled', fill_price = $1, closed_at = $2
+        WHERE id = $3 AND status = 'open'
+        "#
+    )
+    .bind(price)
+    .bind(at.timestamp())
+    .bind(order_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record that a claimed order's trade did not go through
+pub async fn fail_order(pool: &DbPool, order_id: &str, reason: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE orders SET status = 'failed', status_reason = $1, fill_price = NULL WHERE id = $2
+        "#
+    )
+    .bind(reason)
+    .bind(order_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A user's settings object and when it last changed; None if they never saved any
+pub async fn get_user_settings(
+    pool: &DbPool,
+    user_id: &UserId,
+) -> Result<Option<(serde_json::Map<String, serde_json::Value>, chrono::DateTime<chrono::Utc>)>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT settings, updated_at FROM user_settings WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| {
+        let settings: String = r.get("settings");
+        (serde_json::from_str(&settings).unwrap_or_default(), from_unix(r.get("updated_at")))
+    }))
+}
+
+/// Replace a user's settings object
+pub async fn save_user_settings(
+    pool: &DbPool,
+    user_id: &UserId,
+    settings: &serde_json::Map<String, serde_json::Value>,
+    updated_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_settings (user_id, settings, updated_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id) DO UPDATE SET settings = excluded.settings, updated_at = excluded.updated_at
+        "#
+    )
+    .bind(user_id)
+    .bind(serde_json::Value::Object(settings.clone()).to_string())
+    .bind(updated_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// One imported OHLCV candle, priced in USD
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceCandle {
+    pub timestamp: chrono::DateTime<chrono::Utc>, // Candle open
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<f64>, // None when the source has no volume
+}
+
+/// Imported history of one asset at one candle length
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct CandleCoverage {
+    pub asset: String,
+    pub interval_secs: i64,
+    pub candles: i64,
+    pub first: chrono::DateTime<chrono::Utc>,
+    pub last: chrono::DateTime<chrono::Utc>,
+}
+
+/// Store candles in one transaction, skipping any already stored for the same open time
+/// Returns how many were new
+pub async fn insert_price_candles(
+    pool: &DbPool,
+    asset: &str,
+    interval_secs: i64,
+    candles: &[PriceCandle],
+    source: &str,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+
+    for candle in candles {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO price_candles (asset, interval_secs, timestamp, open, high, low, close, volume, source)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, CAST($8 AS DOUBLE PRECISION), $9)
+            ON CONFLICT (asset, interval_secs, timestamp) DO NOTHING
+            "#
+        )
+        .bind(asset)
+        .bind(interval_secs)
+        .bind(candle.timestamp.timestamp())
+        .bind(candle.open)
+        .bind(candle.high)
+        .bind(candle.low)
+        .bind(candle.close)
+        .bind(candle.volume)
+        .bind(source)
+        .persistent(false)
+        .execute(&mut *tx)
+        .await?;
+        inserted += result.rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Candles of one length with an open time in `[start, end]`, oldest first
+pub async fn get_price_candles(
+    pool: &DbPool,
+    asset: &str,
+    interval_secs: i64,
+    start: i64,
+    end: i64,
+) -> Result<Vec<PriceCandle>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT timestamp, open, high, low, close, volume
+        FROM price_candles
+        WHERE asset = $1 AND interval_secs = $2 AND timestamp >= $3 AND timestamp <= $4
+        ORDER BY timestamp
+        "#
+    )
+    .bind(asset)
+    .bind(interval_secs)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| PriceCandle {
+            timestamp: from_unix(r.get("timestamp")),
+            open: r.get("open"),
+            high: r.get("high"),
+            low: r.get("low"),
+            close: r.get("close"),
+            volume: get_optional(r, "volume"),
+        })
+        .collect())
+}
+
+/// What has been imported, per asset and candle length
+pub async fn price_candle_coverage(pool: &DbPool) -> Result<Vec<CandleCoverage>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT asset, interval_secs, COUNT(*) AS candles, MIN(timestamp) AS first, MAX(timestamp) AS last
+        FROM price_candles
+        GROUP BY asset, interval_secs
+        ORDER BY asset, interval_secs
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| CandleCoverage {
+            asset: r.get("asset"),
+            interval_secs: r.get("interval_secs"),
+            candles: r.get("candles"),
+            first: from_unix(r.get("first")),
+            last: from_unix(r.get("last")),
+        })
+        .collect())
+}
+
+/// Open time of an asset's oldest imported candle, of any length
+pub async fn earliest_price_candle(
+    pool: &DbPool,
+    asset: &str,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT MIN(timestamp) AS first FROM price_candles WHERE asset = $1
+        "#
+    )
+    .bind(asset)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(get_optional::<i64>(&row, "first").map(from_unix))
+}
+
+/// Somewhere a user's notifications are pushed
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct NotificationEndpoint {
+    pub id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub channel: NotificationChannel,
+    pub url: String,
+    #[serde(skip)]
+    pub secret: String, // Shown once, when the endpoint is created
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The outcome of pushing one notification to one endpoint
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct NotificationDelivery {
+    pub id: i64,
+    pub endpoint_id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub event: String,
+    pub status: String, // "delivered", "failed", or "suppressed" by quiet hours (never attempted)
+    pub attempts: i64,
+    pub response_status: Option<i64>, // HTTP status of the last attempt, if it got a response
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn row_to_notification_endpoint(row: &AnyRow) -> NotificationEndpoint {
+    let channel: String = row.get("channel");
+    NotificationEndpoint {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        channel: NotificationChannel::parse(&channel).unwrap_or(NotificationChannel::Webhook),
+        url: row.get("url"),
+        secret: row.get("secret"),
+        created_at: from_unix(row.get("created_at")),
+    }
+}
+
+pub async fn create_notification_endpoint(pool: &DbPool, endpoint: &NotificationEndpoint) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO notification_endpoints (id, user_id, channel, url, secret, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#
+    )
+    .bind(&endpoint.id)
+    .bind(&endpoint.user_id)
+    .bind(endpoint.channel.as_str())
+    .bind(&endpoint.url)
+    .bind(&endpoint.secret)
+    .bind(endpoint.created_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A user's notification endpoints, oldest first
+pub async fn list_notification_endpoints(
+    pool: &DbPool,
+    user_id: &UserId,
+) -> Result<Vec<NotificationEndpoint>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM notification_endpoints WHERE user_id = $1 ORDER BY created_at, id
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_notification_endpoint).collect())
+}
+
+pub async fn get_notification_endpoint(
+    pool: &DbPool,
+    user_id: &UserId,
+    endpoint_id: &str,
+) -> Result<Option<NotificationEndpoint>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT * FROM notification_endpoints WHERE id = $1 AND user_id = $2
+        "#
+    )
+    .bind(endpoint_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_notification_endpoint))
+}
+
+pub async fn count_notification_endpoints(pool: &DbPool, user_id: &UserId) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS count FROM notification_endpoints WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.get("count"))
+}
+
+/// Delete one of a user's endpoints; its delivery log is kept
+pub async fn delete_notification_endpoint(
+    pool: &DbPool,
+    user_id: &UserId,
+    endpoint_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM notification_endpoints WHERE id = $1 AND user_id = $2
+        "#
+    )
+    .bind(endpoint_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Log a delivery; returns its id
+pub async fn insert_notification_delivery(
+    pool: &DbPool,
+    delivery: &NotificationDelivery,
+) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO notification_deliveries
+            (endpoint_id, user_id, event, status, attempts, response_status, error, created_at)
+        VALUES ($1, $2, $3, $4, $5, CAST($6 AS BIGINT), CAST($7 AS TEXT), $8)
+        RETURNING id
+        "#
+    )
+    .bind(&delivery.endpoint_id)
+    .bind(&delivery.user_id)
+    .bind(&delivery.event)
+    .bind(&delivery.status)
+    .bind(delivery.attempts)
+    .bind(delivery.response_status)
+    .bind(&delivery.error)
+    .bind(delivery.created_at.timestamp())
+    .persistent(false)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.get("id"))
+}
+
+/// A page of a user's notification deliveries
+/// `cursor` is the id of the last entry from the previous page (exclusive)
+pub async fn get_notification_deliveries(
+    pool: &DbPool,
+    user_id: &UserId,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<NotificationDelivery>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT * FROM notification_deliveries
+        WHERE user_id = $1 AND (CAST($2 AS BIGINT) IS NULL OR id {after} CAST($2 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $3
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| NotificationDelivery {
+            id: r.get("id"),
+            endpoint_id: r.get("endpoint_id"),
+            user_id: r.get("user_id"),
+            event: r.get("event"),
+            status: r.get("status"),
+            attempts: r.get("attempts"),
+            response_status: get_optional(r, "response_status"),
+            error: get_optional(r, "error"),
+            created_at: from_unix(r.get("created_at")),
+        })
+        .collect())
+}
+
+/// A paper-trading competition
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct Competition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub starting_balance: f64, // USD each participant gets
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip)]
+    pub created_by: UserId,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub finalized_at: Option<chrono::DateTime<chrono::Utc>>, // Set once final standings are recorded
+    pub league_id: Option<String>,                           // Set on a league's seasons
+}
+
+/// A participant's competition portfolio, kept apart from their account
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct CompetitionEntry {
+    pub competition_id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub asset_balances: HashMap<String, f64>,
+    #[serde(skip)]
+    pub version: i64, // Bumped on every trade
+    pub trade_count: i64,
+    pub joined_at: chrono::DateTime<chrono::Utc>,
+    pub final_value: Option<f64>, // USD, once the competition is finalized
+    pub final_rank: Option<i64>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct CompetitionTrade {
+    pub id: i64,
+    #[serde(skip)]
+    pub competition_id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64, // In quote asset terms
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+fn row_to_competition(row: &AnyRow) -> Competition {
+    Competition {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        starting_balance: row.get("starting_balance"),
+        starts_at: from_unix(row.get("starts_at")),
+        ends_at: from_unix(row.get("ends_at")),
+        created_by: row.get("created_by"),
+        created_at: from_unix(row.get("created_at")),
+        finalized_at: get_optional::<i64>(row, "finalized_at").map(from_unix),
+        league_id: get_optional(row, "league_id"),
+    }
+}
+
+fn row_to_competition_entry(row: &AnyRow) -> CompetitionEntry {
+    let balances: String = row.get("asset_balances");
+    CompetitionEntry {
+        competition_id: row.get("competition_id"),
+        user_id: row.get("user_id"),
+        asset_balances: serde_json::from_str(&balances).unwrap_or_default(),
+        version: row.get("version"),
+        trade_count: row.get("trade_count"),
+        joined_at: from_unix(row.get("joined_at")),
+        final_value: get_optional(row, "final_value"),
+        final_rank: get_optional(row, "final_rank"),
+    }
+}
+
+/// Store a new competition; false if one with its id exists (a league season another instance created)
+pub async fn create_competition(pool: &DbPool, competition: &Competition) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO competitions (id, name, description, starting_balance, starts_at, ends_at, created_by, created_at, league_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, CAST($9 AS TEXT))
+        ON CONFLICT(id) DO NOTHING
+        "#
+    )
+    .bind(&competition.id)
+    .bind(&competition.name)
+    .bind(&competition.description)
+    .bind(competition.starting_balance)
+    .bind(competition.starts_at.timestamp())
+    .bind(competition.ends_at.timestamp())
+    .bind(&competition.created_by)
+    .bind(competition.created_at.timestamp())
+    .bind(competition.league_id.as_deref())
+    .persistent(false)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Every competition with its participant count, latest start first
+pub async fn list_competitions(pool: &DbPool) -> Result<Vec<(Competition, i64)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT c.*, (SELECT COUNT(*) FROM competition_entries e WHERE e.competition_id = c.id) AS participants
+        FROM competitions c
+        ORDER BY c.starts_at DESC, c.id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|r| (row_to_competition(r), r.get("participants"))).collect())
+}
+
+pub async fn get_competition(pool: &DbPool, competition_id: &str) -> Result<Option<Competition>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT * FROM competitions WHERE id = $1
+        "#
+    )
+    .bind(competition_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_competition))
+}
+
+/// Competitions past their end that have no final standings yet
+pub async fn competitions_to_finalize(
+    pool: &DbPool,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<Competition>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM competitions WHERE finalized_at IS NULL AND ends_at <= $1
+        "#
+    )
+    .bind(now.timestamp())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_competition).collect())
+}
+
+/// Record final values and ranks, once: false if another instance finalized it first
+pub async fn finalize_competition(
+    pool: &DbPool,
+    competition_id: &str,
+    finalized_at: chrono::DateTime<chrono::Utc>,
+    standings: &[(UserId, f64, i64)], // (user, USD value, rank)
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let claimed = sqlx::query(
+        r#"
+        UPDATE competitions SET finalized_at = $1 WHERE id = $2 AND finalized_at IS NULL
+        "#
+    )
+    .bind(finalized_at.timestamp())
+    .bind(competition_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if !claimed {
+        return Ok(false);
+    }
+
+    for (user_id, value, rank) in standings {
+        sqlx::query(
+            r#"
+            UPDATE competition_entries SET final_value = $1, final_rank = $2
+            WHERE competition_id = $3 AND user_id = $4
+            "#
+        )
+        .bind(value)
+        .bind(rank)
+        .bind(competition_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Add a participant; false if they had already joined
+pub async fn join_competition(pool: &DbPool, entry: &CompetitionEntry) -> Result<bool, sqlx::Error> {
+    let balances = serde_json::to_string(&entry.asset_balances).unwrap_or_else(|_| "{}".to_string());
+    let result = sqlx::query(
+        r#"
+        INSERT INTO competition_entries (competition_id, user_id, asset_balances, version, trade_count, joined_at)
+        VALUES ($1, $2, $3, 0, 0, $4)
+        ON CONFLICT(competition_id, user_id) DO NOTHING
+        "#
+    )
+    .bind(&entry.competition_id)
+    .bind(&entry.user_id)
+    .bind(balances)
+    .bind(entry.joined_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_competition_entry(
+    pool: &DbPool,
+    competition_id: &str,
+    user_id: &UserId,
+) -> Result<Option<CompetitionEntry>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT * FROM competition_entries WHERE competition_id = $1 AND user_id = $2
+        "#
+    )
+    .bind(competition_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.as_ref().map(row_to_competition_entry))
+}
+
+/// Every participant of a competition with their username, earliest joiner first
+pub async fn list_competition_entries(
+    pool: &DbPool,
+    competition_id: &str,
+) -> Result<Vec<(CompetitionEntry, String)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT e.*, u.username FROM competition_entries e
+        JOIN users u ON u.user_id = e.user_id
+        WHERE e.competition_id = $1
+        ORDER BY e.joined_at, e.user_id
+        "#
+    )
+    .bind(competition_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|r| (row_to_competition_entry(r), r.get("username"))).collect())
+}
+
+/// Save an entry's balances after a trade and log the trade, unless the entry changed since
+/// it was read at `entry.version` (None: re-read and try again)
+/// Returns the trade's id
+pub async fn record_competition_trade(
+    pool: &DbPool,
+    entry: &CompetitionEntry,
+    trade: &CompetitionTrade,
+) -> Result<Option<i64>, sqlx::Error> {
+    let balances = serde_json::to_string(&entry.asset_balances).unwrap_or_else(|_| "{}".to_string());
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE competition_entries
+        SET asset_balances = $1, version = version + 1, trade_count = trade_count + 1
+        WHERE competition_id = $2 AND user_id = $3 AND version = $4
+        "#
+    )
+    .bind(balances)
+    .bind(&entry.competition_id)
+    .bind(&entry.user_id)
+    .bind(entry.version)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if !updated {
+        return Ok(None);
+    }
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO competition_trades (competition_id, user_id, base_asset, quote_asset, side, quantity, price, timestamp)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id
+        "#
+    )
+    .bind(&trade.competition_id)
+    .bind(&trade.user_id)
+    .bind(&trade.base_asset)
+    .bind(&trade.quote_asset)
+    .bind(side_str(&trade.side))
+    .bind(trade.quantity)
+    .bind(trade.price)
+    .bind(trade.timestamp.timestamp())
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(row.get("id")))
+}
+
+/// A page of a participant's competition trades
+/// `cursor` is the id of the last entry from the previous page (exclusive)
+pub async fn get_competition_trades(
+    pool: &DbPool,
+    competition_id: &str,
+    user_id: &UserId,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<CompetitionTrade>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT * FROM competition_trades
+        WHERE competition_id = $1 AND user_id = $2 AND (CAST($3 AS BIGINT) IS NULL OR id {after} CAST($3 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $4
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(competition_id)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            let side: String = r.get("side");
+            CompetitionTrade {
+                id: r.get("id"),
+                competition_id: r.get("competition_id"),
+                user_id: r.get("user_id"),
+                base_asset: r.get("base_asset"),
+                quote_asset: r.get("quote_asset"),
+                side: if side == "Sell" { TradeSide::Sell } else { TradeSide::Buy },
+                quantity: r.get("quantity"),
+                price: r.get("price"),
+                timestamp: from_unix(r.get("timestamp")),
+            }
+        })
+        .collect())
+}
+
+/// A recurring competition, run as one competition per season
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct League {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub season_length: String, // "week" or "month"
+    pub starting_balance: f64, // USD each member starts every season with
+    #[serde(skip)]
+    pub created_by: UserId,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn row_to_league(row: &AnyRow) -> League {
+    League {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        season_length: row.get("season_length"),
+        starting_balance: row.get("starting_balance"),
+        created_by: row.get("created_by"),
+        created_at: from_unix(row.get("created_at")),
+    }
+}
+
+pub async fn create_league(pool: &DbPool, league: &League) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO leagues (id, name, description, season_length, starting_balance, created_by, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#
+    )
+    .bind(&league.id)
+    .bind(&league.name)
+    .bind(&league.description)
+    .bind(&league.season_length)
+    .bind(league.starting_balance)
+    .bind(&league.created_by)
+    .bind(league.created_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Every league with its member count, oldest first
+pub async fn list_leagues(pool: &DbPool) -> Result<Vec<(League, i64)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT l.*, (SELECT COUNT(*) FROM league_members m WHERE m.league_id = l.id) AS members
+        FROM leagues l
+        ORDER BY l.created_at, l.id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|r| (row_to_league(r), r.get("members"))).collect())
+}
+
+/// A league with its member count
+pub async fn get_league(pool: &DbPool, league_id: &str) -> Result<Option<(League, i64)>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT l.*, (SELECT COUNT(*) FROM league_members m WHERE m.league_id = l.id) AS members
+        FROM leagues l
+        WHERE l.id = $1
+        "#
+    )
+    .bind(league_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| (row_to_league(&r), r.get("members"))))
+}
+
+/// Opt a user in to a league; false if they already were
+pub async fn add_league_member(
+    pool: &DbPool,
+    league_id: &str,
+    user_id: &UserId,
+    joined_at: chrono::DateTime<chrono::Utc>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO league_members (league_id, user_id, joined_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT(league_id, user_id) DO NOTHING
+        "#
+    )
+    .bind(league_id)
+    .bind(user_id)
+    .bind(joined_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Opt a user out of a league's future seasons; false if they were not a member
+pub async fn remove_league_member(pool: &DbPool, league_id: &str, user_id: &UserId) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM league_members WHERE league_id = $1 AND user_id = $2
+        "#
+    )
+    .bind(league_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Give every league member not yet in the season a fresh portfolio of `balances` in it
+/// Returns how many were entered
+pub async fn enter_league_members(
+    pool: &DbPool,
+    season: &Competition,
+    balances: &HashMap<String, f64>,
+    joined_at: chrono::DateTime<chrono::Utc>,
+) -> Result<u64, sqlx::Error> {
+    let balances = serde_json::to_string(balances).unwrap_or_else(|_| "{}".to_string());
+    let result = sqlx::query(
+        r#"
+        INSERT INTO competition_entries (competition_id, user_id, asset_balances, version, trade_count, joined_at)
+        SELECT CAST($1 AS TEXT), user_id, CAST($2 AS TEXT), 0, 0, CAST($3 AS BIGINT) FROM league_members WHERE league_id = $4
+        ON CONFLICT(competition_id, user_id) DO NOTHING
+        "#
+    )
+    .bind(&season.id)
+    .bind(balances)
+    .bind(joined_at.timestamp())
+    .bind(season.league_id.as_deref().unwrap_or_default())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// A league's seasons with their participant counts, latest first
+pub async fn list_league_seasons(pool: &DbPool, league_id: &str) -> Result<Vec<(Competition, i64)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT c.*, (SELECT COUNT(*) FROM competition_entries e WHERE e.competition_id = c.id) AS participants
+        FROM competitions c
+        WHERE c.league_id = $1
+        ORDER BY c.starts_at DESC, c.id
+        "#
+    )
+    .bind(league_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(|r| (row_to_competition(r), r.get("participants"))).collect())
+}
+
+/// A badge a user has earned
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct UserAchievement {
+    pub achievement: String, // Catalog id, e.g. "first_trade"
+    pub earned_at: chrono::DateTime<chrono::Utc>,
+    pub details: String,
+}
+
+/// A user's badges, earliest first
+pub async fn list_user_achievements(pool: &DbPool, user_id: &UserId) -> Result<Vec<UserAchievement>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT achievement, earned_at, details FROM user_achievements
+        WHERE user_id = $1
+        ORDER BY earned_at, achievement
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| UserAchievement {
+            achievement: r.get("achievement"),
+            earned_at: from_unix(r.get("earned_at")),
+            details: r.get("details"),
+        })
+        .collect())
+}
+
+/// Award a badge; false if the user already had it
+pub async fn award_achievement(
+    pool: &DbPool,
+    user_id: &UserId,
+    achievement: &UserAchievement,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO user_achievements (user_id, achievement, earned_at, details)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT(user_id, achievement) DO NOTHING
+        "#
+    )
+    .bind(user_id)
+    .bind(&achievement.achievement)
+    .bind(achievement.earned_at.timestamp())
+    .bind(&achievement.details)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// A broken accounting rule, as recorded by invariant_service
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct InvariantViolation {
+    pub id: i64,
+    pub user_id: UserId,
+    pub rule: String,
+    pub details: String,
+    pub change: String, // The transaction that broke it
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn insert_invariant_violation(pool: &DbPool, violation: &InvariantViolation) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO invariant_violations (user_id, rule, details, change, detected_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#
+    )
+    .bind(&violation.user_id)
+    .bind(&violation.rule)
+    .bind(&violation.details)
+    .bind(&violation.change)
+    .bind(violation.detected_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// A page of violations (all users when user_id is None)
+/// `cursor` is the id of the last violation from the previous page (exclusive)
+pub async fn get_invariant_violations(
+    pool: &DbPool,
+    user_id: Option<&str>,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<InvariantViolation>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT id, user_id, rule, details, change, detected_at
+        FROM invariant_violations
+        WHERE (CAST($1 AS TEXT) IS NULL OR user_id = CAST($1 AS TEXT))
+          AND (CAST($2 AS BIGINT) IS NULL OR id {after} CAST($2 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $3
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| InvariantViolation {
+            id: r.get("id"),
+            user_id: r.get("user_id"),
+            rule: r.get("rule"),
+            details: r.get("details"),
+            change: r.get("change"),
+            detected_at: from_unix(r.get("detected_at")),
+        })
+        .collect())
+}
+
+pub async fn count_invariant_violations(pool: &DbPool) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query("SELECT COUNT(*) AS count FROM invariant_violations").fetch_one(pool).await?;
+    Ok(row.get("count"))
+}
+
+/// One credit or debit in a user's ledger
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct LedgerEntry {
+    pub id: i64,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub asset: Asset,
+    pub amount: f64,  // Positive for credits, negative for debits
+    pub balance: f64, // The asset's balance after this entry
+    pub kind: String, // "opening", "trade", "fee", "deposit", "withdrawal" or "reset"
+    pub trade_id: Option<i64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub hash: String,
+}
+
+/// Append postings made from the balances in `before` to a user's ledger, chained to their
+/// last entry, and return the balances they lead to. A user's first entries open with `before`
+async fn append_ledger(
+    conn: &mut AnyConnection,
+    user_id: &UserId,
+    before: &HashMap<Asset, f64>,
+    postings: &[Posting],
+    trade_id: Option<i64>,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<HashMap<Asset, f64>, sqlx::Error> {
+    let last = sqlx::query(
+        r#"
+        SELECT hash FROM ledger_entries WHERE user_id = $1 ORDER BY id DESC LIMIT 1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let (mut previous, opening) = match last {
+        Some(row) => (row.get::<String, _>("hash"), Vec::new()),
+        None => (String::new(), ledger_service::difference(&HashMap::new(), before, EntryKind::Opening)),
+    };
+
+    let mut balances = before.clone();
+    for posting in opening.iter().chain(postings) {
+        // Openings state what `before` already holds
+        if posting.kind != EntryKind::Opening {
+            *balances.entry(posting.asset.clone()).or_insert(0.0) += posting.amount;
+        }
+        let mut entry = LedgerEntry {
+            id: 0, // Assigned by the database
+            user_id: user_id.clone(),
+            asset: posting.asset.clone(),
+            amount: posting.amount,
+            balance: balances.get(&posting.asset).copied().unwrap_or(0.0),
+            kind: posting.kind.as_str().to_string(),
+            trade_id: if posting.kind == EntryKind::Opening { None } else { trade_id },
+            created_at: at,
+            hash: String::new(),
+        };
+        entry.hash = ledger_service::entry_hash(&previous, &entry);
+
+        sqlx::query(
+            r#"
+            INSERT INTO ledger_entries (user_id, asset, amount, balance, kind, trade_id, created_at, hash)
+            VALUES ($1, $2, $3, $4, $5, CAST($6 AS BIGINT), $7, $8)
+            "#
+        )
+        .bind(&entry.user_id)
+        .bind(&entry.asset)
+        .bind(entry.amount)
+        .bind(entry.balance)
+        .bind(&entry.kind)
+        .bind(entry.trade_id)
+        .bind(entry.created_at.timestamp())
+        .bind(&entry.hash)
+        .persistent(false)
+        .execute(&mut *conn)
+        .await?;
+        previous = entry.hash;
+    }
+
+    Ok(balances)
+}
+
+/// A page of a user's ledger
+/// `cursor` is the id of the last entry from the previous page (exclusive)
+pub async fn get_ledger_entries(
+    pool: &DbPool,
+    user_id: &UserId,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<LedgerEntry>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT id, user_id, asset, amount, balance, kind, trade_id, created_at, hash
+        FROM ledger_entries
+        WHERE user_id = $1
+          AND (CAST($2 AS BIGINT) IS NULL OR id {after} CAST($2 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $3
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(row_to_ledger_entry).collect())
+}
+
+/// A user's whole ledger, oldest first
+pub async fn load_ledger(pool: &DbPool, user_id: &UserId) -> Result<Vec<LedgerEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, user_id, asset, amount, balance, kind, trade_id, created_at, hash
+        FROM ledger_entries
+        WHERE user_id = $1
+        ORDER BY id ASC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.iter().map(row_to_ledger_entry).collect())
+}
+
+fn row_to_ledger_entry(r: &AnyRow) -> LedgerEntry {
+    LedgerEntry {
+        id: r.get("id"),
+        user_id: r.get("user_id"),
+        asset: r.get("asset"),
+        amount: r.get("amount"),
+        balance: r.get("balance"),
+        kind: r.get("kind"),
+        trade_id: get_optional(r, "trade_id"),
+        created_at: from_unix(r.get("created_at")),
+        hash: r.get("hash"),
+    }
+}
+
+/// Store headlines, skipping any already stored; returns how many were new
+pub async fn insert_news_items(pool: &DbPool, items: &[NewsItem], fetched_at: i64) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut inserted = 0;
+
+    for item in items {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO news_items (id, title, url, source, assets, published_at, fetched_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO NOTHING
+            "#
+        )
+        .bind(&item.id)
+        .bind(&item.title)
+        .bind(&item.url)
+        .bind(&item.source)
+        // Outer commas let `LIKE '%,BTC,%'` match whole asset names
+        .bind(format!(",{},", item.assets.join(",")))
+        .bind(item.published_at.timestamp())
+        .bind(fetched_at)
+        .execute(&mut *tx)
+        .await?;
+        inserted += result.rows_affected();
+    }
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Headlines published in `[start, end]` (mentioning `asset`, if given), newest first
+pub async fn get_news_items(
+    pool: &DbPool,
+    asset: Option<&str>,
+    start: i64,
+    end: i64,
+    limit: i64,
+) -> Result<Vec<NewsItem>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, title, url, source, assets, published_at
+        FROM news_items
+        WHERE (CAST($1 AS TEXT) IS NULL OR assets LIKE CAST($1 AS TEXT))
+          AND published_at >= $2 AND published_at <= $3
+        ORDER BY published_at DESC, id
+        LIMIT $4
+        "#
+    )
+    .bind(asset.map(|a| format!("%,{},%", a)))
+    .bind(start)
+    .bind(end)
+    .bind(limit)
+    .persistent(false)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| NewsItem {
+            id: r.get("id"),
+            title: r.get("title"),
+            url: r.get("url"),
+            source: r.get("source"),
+            assets: r
+                .get::<String, _>("assets")
+                .split(',')
+                .filter(|a| !a.is_empty())
+                .map(str::to_string)
+                .collect(),
+            published_at: from_unix(r.get("published_at")),
+        })
+        .collect())
+}
+
+/// Settings holding `key`, by user
+pub async fn users_with_setting(pool: &DbPool, key: &str) -> Result<Vec<(UserId, serde_json::Value)>, sqlx::Error> {
+    // LIKE narrows the rows; parsing checks the key is a top-level one
+    let rows = sqlx::query(
+        r#"
+        SELECT user_id, settings FROM user_settings WHERE settings LIKE $1
+        "#
+    )
+    .bind(format!("%\"{}\"%", key))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|r| {
+            let settings: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&r.get::<String, _>("settings")).ok()?;
+            Some((r.get("user_id"), settings.get(key)?.clone()))
+        })
+        .collect())
+}
+
+/// A stored daily summary
+#[derive(Debug, Clone, serde::Serialize, utoipa::ToSchema)]
+pub struct DailySummaryRecord {
+    pub id: i64,
+    pub date: String, // The user's local date
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub summary: DailySummary,
+}
+
+/// Store a user's summary for a date; false if one was already stored (by this or another instance)
+pub async fn insert_daily_summary(
+    pool: &DbPool,
+    user_id: &UserId,
+    summary: &DailySummary,
+    created_at: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO daily_summaries (user_id, date, summary, created_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id, date) DO NOTHING
+        "#
+    )
+    .bind(user_id)
+    .bind(&summary.date)
+    .bind(serde_json::to_string(summary).unwrap_or_default())
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn has_daily_summary(pool: &DbPool, user_id: &UserId, date: &str) -> Result<bool, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT COUNT(*) AS count FROM daily_summaries WHERE user_id = $1 AND date = $2
+        "#
+    )
+    .bind(user_id)
+    .bind(date)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.get::<i64, _>("count") > 0)
+}
+
+/// A page of a user's summaries; summaries that no longer parse are skipped
+/// `cursor` is the id of the last summary from the previous page (exclusive)
+pub async fn get_daily_summaries(
+    pool: &DbPool,
+    user_id: &UserId,
+    cursor: Option<i64>,
+    sort: SortOrder,
+    limit: i64,
+) -> Result<Vec<DailySummaryRecord>, sqlx::Error> {
+    let (after, order) = keyset(sort);
+    let sql = format!(
+        r#"
+        SELECT id, date, summary, created_at
+        FROM daily_summaries
+        WHERE user_id = $1
+          AND (CAST($2 AS BIGINT) IS NULL OR id {after} CAST($2 AS BIGINT))
+        ORDER BY id {order}
+        LIMIT $3
+        "#
+    );
+    let rows = sqlx::query(&sql)
+        .bind(user_id)
+        .bind(cursor)
+        .bind(limit)
+        .persistent(false)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|r| {
+            Some(DailySummaryRecord {
+                id: r.get("id"),
+                date: r.get("date"),
+                created_at: from_unix(r.get("created_at")),
+                summary: serde_json::from_str(&r.get::<String, _>("summary")).ok()?,
+            })
+        })
+        .collect())
+}
+
+/// A user's fee tier as last recalculated
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeTierRecord {
+    pub tier: String,
+    pub volume_30d_usd: f64,
+    pub calculated_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn save_fee_tier(pool: &DbPool, user_id: &UserId, record: &FeeTierRecord) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO fee_tiers (user_id, tier, volume_30d_usd, calculated_at)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT(user_id) DO UPDATE SET
+            tier = excluded.tier,
+            volume_30d_usd = excluded.volume_30d_usd,
+            calculated_at = excluded.calculated_at
+        "#
+    )
+    .bind(user_id)
+    .bind(&record.tier)
+    .bind(record.volume_30d_usd)
+    .bind(record.calculated_at.timestamp())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_fee_tier(pool: &DbPool, user_id: &UserId) -> Result<Option<FeeTierRecord>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT tier, volume_30d_usd, calculated_at FROM fee_tiers WHERE user_id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| FeeTierRecord {
+        tier: r.get("tier"),
+        volume_30d_usd: r.get("volume_30d_usd"),
+        calculated_at: from_unix(r.get("calculated_at")),
+    }))
+}
@@ -0,0 +1,147 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::services::auth_service::AuthError;
+use crate::services::trading_service::TradeError;
+
+/// Error returned by every API route
+/// Serialized as `{"error": "<message>", "code": "<code>", "details": ...}`; `error` is meant
+/// for people, `code` is stable and meant for clients to match on
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[schema(as = ErrorResponse)]
+pub struct ErrorBody {
+    pub error: String,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+
+    /// Replace the generic code with a more specific one
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: self.message,
+            code: self.code.to_string(),
+            details: self.details,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        tracing::error!("Database error: {}", e);
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "database_error", format!("Database error: {}", e))
+    }
+}
+
+impl From<AuthError> for ApiError {
+    fn from(e: AuthError) -> Self {
+        let message = e.to_string();
+        match e {
+            AuthError::InvalidCredentials => Self::unauthorized(message).with_code("invalid_credentials"),
+            AuthError::UserAlreadyExists => Self::conflict(message).with_code("username_taken"),
+            AuthError::OAuthFailed(_) => Self::bad_request(message).with_code("oauth_failed"),
+            AuthError::HashError(_) => Self::internal(message),
+            AuthError::DatabaseError(_) => Self::internal(message).with_code("database_error"),
+        }
+    }
+}
+
+/// Status and code for a failed trade, deposit or withdrawal; routes supply the message
+/// since it depends on the pair or operation
+pub fn trade_error(err: &TradeError, message: impl Into<String>) -> ApiError {
+    let (status, code) = match err {
+        TradeError::InsufficientFunds => (StatusCode::BAD_REQUEST, "insufficient_funds"),
+        TradeError::InsufficientAssets => (StatusCode::BAD_REQUEST, "insufficient_assets"),
+        TradeError::InvalidQuantity => (StatusCode::BAD_REQUEST, "invalid_quantity"),
+        TradeError::UserNotFound => (StatusCode::NOT_FOUND, "user_not_found"),
+        TradeError::PriceUnavailable(_) => (StatusCode::BAD_REQUEST, "price_unavailable"),
+        TradeError::DepositTooSmall => (StatusCode::BAD_REQUEST, "deposit_too_small"),
+        TradeError::DepositTooLarge => (StatusCode::BAD_REQUEST, "deposit_too_large"),
+        TradeError::WithdrawalExceedsBalance => (StatusCode::BAD_REQUEST, "insufficient_funds"),
+        // A failed DB write is ours, not the client's
+        TradeError::PersistenceFailed => (StatusCode::INTERNAL_SERVER_ERROR, "persistence_failed"),
+    };
+    ApiError::new(status, code, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_body_keeps_message_under_error() {
+        let err = ApiError::not_found("User not found").with_details(serde_json::json!({ "user_id": "u1" }));
+        let body = ErrorBody {
+            error: err.message,
+            code: err.code.to_string(),
+            details: err.details,
+        };
+
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            serde_json::json!({ "error": "User not found", "code": "not_found", "details": { "user_id": "u1" } })
+        );
+    }
+}
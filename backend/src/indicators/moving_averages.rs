@@ -1,5 +1,6 @@
 /// Simple Moving Average (SMA)
 /// Calculates the arithmetic mean of the last N prices
+#[allow(clippy::upper_case_acronyms)]
 pub struct SMA {
     period: usize,
 }
@@ -33,6 +34,7 @@ impl SMA {
 
 /// Exponential Moving Average (EMA)
 /// Gives more weight to recent prices using exponential smoothing
+#[allow(clippy::upper_case_acronyms)]
 pub struct EMA {
     period: usize,
 }
@@ -79,6 +81,7 @@ impl EMA {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_range_loop)]
 mod tests {
     use super::*;
 
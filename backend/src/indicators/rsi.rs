@@ -3,6 +3,7 @@
 /// Returns values between 0-100:
 /// - Below 30: Oversold (potentially undervalued)
 /// - Above 70: Overbought (potentially overvalued)
+#[allow(clippy::upper_case_acronyms)]
 pub struct RSI {
     period: usize,
 }
@@ -72,6 +73,7 @@ impl RSI {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_range_loop)]
 mod tests {
     use super::*;
 
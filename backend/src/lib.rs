@@ -0,0 +1,17 @@
+//! The simulator's server internals, shared by the `backend` server, the `admin` CLI and the integration tests
+
+pub mod api_client;
+pub mod app;
+pub mod assets;
+pub mod bots;
+pub mod clock;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod indicators;
+pub mod middleware;
+pub mod models;
+pub mod pagination;
+pub mod routes;
+pub mod services;
+pub mod state;
@@ -1,27 +1,17 @@
-mod api_client;
-mod bots;
-mod db;
-mod indicators;
-mod models;
-mod routes;
-mod services;
-mod state;
-
-use axum::{routing::{get, post}, Router};
-use state::AppState;
-use tower_http::{cors::CorsLayer, services::ServeDir};
-use tracing_subscriber;
+use backend::state::AppState;
+use backend::{app, config, db, services};
 
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
 
+    let config = config::Config::load().expect("Invalid configuration");
+
     // Initialize database
     let db_path = "/app/data/trading_sim.db";
     let database_url = std::env::var("DATABASE_URL")
         .unwrap_or_else(|_| format!("sqlite:{}", db_path));
 
-    tracing::info!("Connecting to database: {}", database_url);
     tracing::info!("Database file path: {}", db_path);
 
     // Ensure data directory exists and check permissions
@@ -63,8 +53,29 @@ async fn main() {
 
     tracing::info!("Database initialized successfully");
 
+    // Grant admin role to configured usernames (comma-separated)
+    if let Ok(admins) = std::env::var("ADMIN_USERNAMES") {
+        let usernames: Vec<String> = admins
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        match db::queries::promote_admins(db.pool(), &usernames).await {
+            Ok(count) => tracing::info!("Granted admin role to {} user(s)", count),
+            Err(e) => tracing::error!("Failed to grant admin roles: {}", e),
+        }
+    }
+
     // Initialize application state
-    let state = AppState::new(db).await;
+    let addr = std::net::SocketAddr::new(
+        config.server.bind_address.parse().expect("bind_address is checked when loading"),
+        config.server.port,
+    );
+    let state = AppState::new(db, config).await;
+
+    // Recover state a crash didn't get to flush (must precede price polling and bot restore)
+    let snapshot_config = services::snapshot_service::SnapshotConfig::from_env();
+    let recovered_bots = services::snapshot_service::recover(&state, &snapshot_config).await;
 
     // Spawn price polling task
     let polling_state = state.clone();
@@ -72,32 +83,52 @@ async fn main() {
         services::price_service::start_price_polling(polling_state).await;
     });
 
-    let api_routes = Router::new()
-        .route("/price", get(routes::price::get_price))
-        .route("/price/history", get(routes::price::get_price_history))
-        .route("/price/candles", get(routes::price::get_candle_history))
-        .route("/indicators", get(routes::indicators::get_indicators))
-        .route("/portfolio", get(routes::portfolio::get_portfolio))
-        .route("/trade", post(routes::trade::post_trade))
-        .route("/deposit", post(routes::trade::post_deposit))
-        .route("/withdrawal", post(routes::trade::post_withdrawal))
-        .route("/signup", post(routes::auth::signup))
-        .route("/login", post(routes::auth::login))
-        .route("/bot/start", post(routes::bot::start_bot))
-        .route("/bot/stop", post(routes::bot::stop_bot))
-        .route("/bot/status", get(routes::bot::bot_status));
-
-    let app = Router::new()
-        .nest("/api", api_routes)
-        .nest_service("/", ServeDir::new("static"))
-        .layer(CorsLayer::permissive())
-        .with_state(state);
-
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 3000));
+    // Headlines for the chart and bots (disabled unless CRYPTOPANIC_TOKEN is set)
+    tokio::spawn(services::news_service::run_news_polling(state.clone()));
+
+    // Daily summaries at each user's chosen local time
+    tokio::spawn(services::summary_service::run_daily_summaries(state.clone()));
+
+    // Fee tiers from each user's 30-day volume, recalculated daily
+    tokio::spawn(services::fee_service::run_recalculation(state.clone()));
+
+    // Spawn retention/pruning jobs
+    let maintenance_state = state.clone();
+    tokio::spawn(async move {
+        let config = services::maintenance_service::RetentionConfig::from_env();
+        services::maintenance_service::run_maintenance(maintenance_state, config).await;
+    });
+
+    // Spawn scheduled backups (disabled unless BACKUP_INTERVAL_HOURS is set)
+    let backup_db = state.db.clone();
+    tokio::spawn(async move {
+        let config = services::backup_service::BackupConfig::from_env();
+        services::backup_service::run_backup_scheduler(backup_db, config).await;
+    });
+
+    // Periodic crash-recovery snapshots (SNAPSHOT_INTERVAL_SECS=0 disables)
+    tokio::spawn(services::snapshot_service::run_snapshots(state.clone(), snapshot_config));
+
+    // Alerts, order fills, notifications, competition results and achievements
+    app::spawn_event_handlers(&state);
+
+    // Coordinate with other instances when SHARED_STATE is enabled
+    tokio::spawn(services::cluster_service::run_cluster(state.clone()));
+
+    // Respawn bots that were running before the restart
+    services::bot_service::restore_bots(state.clone(), recovered_bots).await;
+
+    let app = app::router(state);
+
     tracing::info!("Server listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
 
 // use axum::{
@@ -0,0 +1,110 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// Chart data changes only when a new price arrives, so clients revalidate on every request
+/// and get a 304 until then
+const CACHE_CONTROL: &str = "public, no-cache";
+
+/// Validators for a response derived from the price feed
+/// The ETag covers the request query (asset, timeframe, paging...) and the latest price time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validators {
+    pub etag: String,
+    pub last_modified: DateTime<Utc>,
+}
+
+impl Validators {
+    pub fn new(query: &str, latest: DateTime<Utc>) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(query.as_bytes());
+        hasher.update(latest.timestamp_micros().to_be_bytes());
+        let digest = hasher.finalize();
+
+        Self {
+            // Weak: the body for the same data may be re-serialized or compressed differently
+            etag: format!("W/\"{}\"", hex::encode(&digest[..16])),
+            last_modified: latest,
+        }
+    }
+
+    /// True when the client's cached copy is still current
+    /// `If-None-Match` wins over `If-Modified-Since` when both are sent
+    pub fn is_fresh(&self, headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+            return if_none_match
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || weak_eq(tag, &self.etag));
+        }
+
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+            .is_some_and(|since| self.last_modified.timestamp() <= since.timestamp())
+    }
+
+    /// Attach the validators and caching policy to a response (the 304 or the full body)
+    pub fn apply(&self, response: impl IntoResponse) -> Response {
+        let mut response = response.into_response();
+        let headers = response.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Ok(last_modified) = HeaderValue::from_str(&http_date(self.last_modified)) {
+            headers.insert(header::LAST_MODIFIED, last_modified);
+        }
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(CACHE_CONTROL));
+        response
+    }
+
+    pub fn not_modified(&self) -> Response {
+        self.apply(StatusCode::NOT_MODIFIED)
+    }
+}
+
+fn weak_eq(a: &str, b: &str) -> bool {
+    a.trim_start_matches("W/") == b.trim_start_matches("W/")
+}
+
+fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_etag_changes_with_query_and_latest_price() {
+        let at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let base = Validators::new("asset=BTC&timeframe=1h", at);
+
+        assert_eq!(base, Validators::new("asset=BTC&timeframe=1h", at));
+        assert_ne!(base.etag, Validators::new("asset=ETH&timeframe=1h", at).etag);
+        assert_ne!(base.etag, Validators::new("asset=BTC&timeframe=1h", at + chrono::Duration::seconds(5)).etag);
+    }
+
+    #[test]
+    fn test_freshness_checks_etag_before_date() {
+        let at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let validators = Validators::new("asset=BTC", at);
+
+        let mut headers = HeaderMap::new();
+        assert!(!validators.is_fresh(&headers));
+
+        headers.insert(header::IF_MODIFIED_SINCE, HeaderValue::from_str(&http_date(at)).unwrap());
+        assert!(validators.is_fresh(&headers));
+
+        // A stale ETag overrides a matching date
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("W/\"stale\""));
+        assert!(!validators.is_fresh(&headers));
+
+        let current = validators.etag.trim_start_matches("W/").to_string();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&format!("\"other\", {}", current)).unwrap());
+        assert!(validators.is_fresh(&headers));
+    }
+}
@@ -0,0 +1,120 @@
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+
+/// Cross-origin policy, read from the environment
+/// `CORS_ALLOWED_ORIGINS` and `CORS_ALLOWED_METHODS` are comma-separated lists, `*` (the default)
+/// allows any; `CORS_ALLOW_CREDENTIALS=true` lets browsers send cookies/auth headers cross-origin
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsConfig {
+    pub origins: Option<Vec<HeaderValue>>, // None = any origin
+    pub methods: Option<Vec<Method>>,      // None = any method
+    pub allow_credentials: bool,
+}
+
+impl CorsConfig {
+    pub fn from_env() -> Self {
+        Self::parse(
+            std::env::var("CORS_ALLOWED_ORIGINS").ok().as_deref(),
+            std::env::var("CORS_ALLOWED_METHODS").ok().as_deref(),
+            std::env::var("CORS_ALLOW_CREDENTIALS").ok().as_deref(),
+        )
+    }
+
+    fn parse(origins: Option<&str>, methods: Option<&str>, credentials: Option<&str>) -> Self {
+        let origins = parse_list(origins, |origin| {
+            HeaderValue::from_str(origin.trim_end_matches('/')).ok()
+        });
+        let methods = parse_list(methods, |method| Method::from_bytes(method.to_uppercase().as_bytes()).ok());
+        let mut allow_credentials = credentials.is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1");
+
+        // Browsers reject credentialed responses for a wildcard origin
+        if allow_credentials && origins.is_none() {
+            tracing::warn!("CORS_ALLOW_CREDENTIALS ignored: it requires an explicit CORS_ALLOWED_ORIGINS list");
+            allow_credentials = false;
+        }
+
+        Self {
+            origins,
+            methods,
+            allow_credentials,
+        }
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        let origins = match &self.origins {
+            Some(list) => AllowOrigin::list(list.clone()),
+            None => AllowOrigin::any(),
+        };
+        // Wildcards can't be combined with credentials, so mirror the request or name what we use
+        let methods = match &self.methods {
+            Some(list) => AllowMethods::list(list.clone()),
+            None if self.allow_credentials => AllowMethods::mirror_request(),
+            None => AllowMethods::any(),
+        };
+        let headers = if self.allow_credentials {
+            AllowHeaders::list([header::AUTHORIZATION, header::CONTENT_TYPE, REQUEST_ID_HEADER])
+        } else {
+            AllowHeaders::any()
+        };
+        let exposed: [HeaderName; 2] = [REQUEST_ID_HEADER, header::RETRY_AFTER];
+
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .allow_credentials(self.allow_credentials)
+            .expose_headers(exposed)
+    }
+}
+
+/// None for an unset, empty or `*` list; unparseable entries are logged and skipped
+fn parse_list<T>(value: Option<&str>, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    let value = value.map(str::trim).filter(|v| !v.is_empty() && *v != "*")?;
+
+    let items = value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| {
+            let parsed = parse(item);
+            if parsed.is_none() {
+                tracing::warn!("Ignoring invalid CORS entry: {}", item);
+            }
+            parsed
+        })
+        .collect();
+    Some(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_allow_any_origin_without_credentials() {
+        let config = CorsConfig::parse(None, Some("*"), Some("true"));
+        assert_eq!(config.origins, None);
+        assert_eq!(config.methods, None);
+        assert!(!config.allow_credentials);
+    }
+
+    #[test]
+    fn test_explicit_lists_are_parsed() {
+        let config = CorsConfig::parse(
+            Some("https://trade.example.com/, http://localhost:8080"),
+            Some("get, post"),
+            Some("true"),
+        );
+        assert_eq!(
+            config.origins,
+            Some(vec![
+                HeaderValue::from_static("https://trade.example.com"),
+                HeaderValue::from_static("http://localhost:8080"),
+            ])
+        );
+        assert_eq!(config.methods, Some(vec![Method::GET, Method::POST]));
+        assert!(config.allow_credentials);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod rate_limit;
+pub mod request_id;
+pub mod cors;
+pub mod conditional;
+pub mod session_activity;
@@ -0,0 +1,217 @@
+use axum::{
+    extract::{ConnectInfo, Query, Request, State},
+    http::{header, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::ApiError;
+
+/// Stale entries are swept once the map grows past this many keys
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Fixed-window request counter for a single key (IP or user)
+struct Window {
+    started: Instant,
+    count: u32,
+}
+
+/// Fixed-window rate limiter keyed by an arbitrary string
+/// Cheap to clone - all clones share the same counters
+#[derive(Clone)]
+pub struct RateLimiter {
+    max_requests: u32,
+    window: Duration,
+    windows: Arc<Mutex<HashMap<String, Window>>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: u32, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window,
+            windows: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Build a limiter from `<PREFIX>_MAX_REQUESTS` / `<PREFIX>_WINDOW_SECS` env vars
+    pub fn from_env(prefix: &str, default_max: u32, default_window_secs: u64) -> Self {
+        let max_requests = std::env::var(format!("{}_MAX_REQUESTS", prefix))
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_max);
+        let window_secs = std::env::var(format!("{}_WINDOW_SECS", prefix))
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_window_secs);
+
+        tracing::info!(
+            "Rate limit {}: {} requests per {}s",
+            prefix,
+            max_requests,
+            window_secs
+        );
+
+        Self::new(max_requests, Duration::from_secs(window_secs))
+    }
+
+    /// Record a request for `key`
+    /// Returns Err(retry_after) if the key has exhausted its budget for the current window
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap();
+
+        if windows.len() > SWEEP_THRESHOLD {
+            let window = self.window;
+            windows.retain(|_, w| now.duration_since(w.started) < window);
+        }
+
+        let entry = windows.entry(key.to_string()).or_insert(Window {
+            started: now,
+            count: 0,
+        });
+
+        // Start a fresh window once the old one has elapsed
+        if now.duration_since(entry.started) >= self.window {
+            entry.started = now;
+            entry.count = 0;
+        }
+
+        if entry.count >= self.max_requests {
+            let retry_after = self.window - now.duration_since(entry.started);
+            return Err(retry_after);
+        }
+
+        entry.count += 1;
+        Ok(())
+    }
+}
+
+/// Limits for money-moving routes: a budget per user, and a larger one per client IP that every
+/// `user_id` sent from it shares, so cycling ids can't get past it while users behind one address
+/// (an office, a NAT) don't use up each other's budgets
+#[derive(Clone)]
+pub struct UserRateLimiter {
+    pub per_user: RateLimiter,
+    pub per_ip: RateLimiter,
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    // Round up so clients never retry before the window actually resets
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    let error = ApiError::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limited",
+        format!("Too many requests. Retry after {} seconds", secs),
+    )
+    .with_details(serde_json::json!({ "retry_after_secs": secs }));
+
+    ([(header::RETRY_AFTER, secs.to_string())], error).into_response()
+}
+
+/// Limit requests per client IP (used for login/signup)
+pub async fn limit_by_ip(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = addr.ip().to_string();
+
+    match limiter.check(&key) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            tracing::warn!("Rate limit exceeded for IP {}", key);
+            too_many_requests(retry_after)
+        }
+    }
+}
+
+/// Limit requests per user (used for trade/deposit/withdrawal)
+/// Counts against the client IP's budget and, if the request names one, the `user_id`'s
+pub async fn limit_by_user(
+    State(limiter): State<UserRateLimiter>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip_key = format!("ip:{}", addr.ip());
+    let user_key = user_id_param(request.uri()).map(|user_id| format!("user:{}", user_id));
+
+    let checks = std::iter::once((&limiter.per_ip, ip_key)).chain(user_key.map(|key| (&limiter.per_user, key)));
+    for (limiter, key) in checks {
+        if let Err(retry_after) = limiter.check(&key) {
+            tracing::warn!("Rate limit exceeded for {}", key);
+            return too_many_requests(retry_after);
+        }
+    }
+    next.run(request).await
+}
+
+#[derive(Deserialize)]
+struct UserIdParam {
+    user_id: Option<String>,
+}
+
+/// The request's `user_id` query parameter, decoded as the route handlers decode it
+fn user_id_param(uri: &Uri) -> Option<String> {
+    Query::<UserIdParam>::try_from_uri(uri).ok()?.0.user_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_up_to_limit() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(limiter.check_at("a", now).is_ok());
+        assert!(limiter.check_at("a", now).is_ok());
+        assert!(limiter.check_at("a", now).is_ok());
+        assert!(limiter.check_at("a", now).is_err());
+    }
+
+    #[test]
+    fn test_keys_are_independent() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(limiter.check_at("a", now).is_ok());
+        assert!(limiter.check_at("b", now).is_ok());
+        assert!(limiter.check_at("a", now).is_err());
+    }
+
+    #[test]
+    fn test_window_resets() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(limiter.check_at("a", now).is_ok());
+
+        let retry_after = limiter
+            .check_at("a", now + Duration::from_secs(20))
+            .unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(40));
+
+        assert!(limiter.check_at("a", now + Duration::from_secs(60)).is_ok());
+    }
+
+    #[test]
+    fn test_user_id_is_decoded() {
+        let uri = |s: &str| s.parse::<Uri>().unwrap();
+        assert_eq!(user_id_param(&uri("/api/trade?user_id=alice&x=1")), Some("alice".to_string()));
+        assert_eq!(user_id_param(&uri("/api/trade?x=1&user_id=al%69ce")), Some("alice".to_string()));
+        assert_eq!(user_id_param(&uri("/api/trade")), None);
+    }
+}
@@ -0,0 +1,22 @@
+use axum::{extract::Request, http::HeaderName};
+use tracing::Span;
+
+/// Assigned to every request (or taken from the client) and echoed back on the response
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Span wrapping a request, so every log line it triggers - down to the DB write - carries its id
+/// Only the path is recorded: query strings can hold session tokens
+pub fn make_request_span(request: &Request) -> Span {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .unwrap_or("-");
+
+    tracing::info_span!(
+        "request",
+        id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    )
+}
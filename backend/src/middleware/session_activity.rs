@@ -0,0 +1,17 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::session_service;
+use crate::state::AppState;
+
+/// Note when each session was last used, for the session list: any API request carrying its
+/// bearer token counts, whether or not the route checks it
+pub async fn record_session_use(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    if let Some(token) = session_service::bearer_token(req.headers()) {
+        session_service::touch(state.db.pool(), token);
+    }
+    next.run(req).await
+}
@@ -1,8 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use utoipa::ToSchema;
 
 pub type UserId = String;
+pub type BotId = String; // Generated per running bot, returned by POST /api/bot/start
 pub type Asset = String;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +12,8 @@ pub struct PricePoint {
     pub timestamp: DateTime<Utc>,
     pub asset: String,
     pub price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f64>, // Base units traded since the previous point; None where the source doesn't say (spot polls)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,9 +24,30 @@ pub struct Candle {
     pub high: f64,
     pub low: f64,
     pub close: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f64>, // Base units traded in the candle, if known
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Total of two volumes, either of which may be unknown
+pub fn add_volume(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// A headline from the news feed, tagged with the assets it mentions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsItem {
+    pub id: String, // Source-prefixed, e.g. "cryptopanic:123"
+    pub title: String,
+    pub url: String,
+    pub source: String, // Publisher, e.g. "CoinDesk"
+    pub assets: Vec<Asset>,
+    pub published_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum TransactionType {
     Trade,
     Deposit,
@@ -33,15 +58,14 @@ fn default_transaction_type() -> TransactionType {
     TransactionType::Trade
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UserData {
     pub username: String,
-    pub cash_balance: f64,
     pub asset_balances: HashMap<Asset, f64>,
     pub trade_history: Vec<Trade>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Trade {
     pub user_id: UserId,
 
@@ -66,12 +90,78 @@ pub struct Trade {
     // Bot execution tracking (None if manual trade)
     #[serde(default)]
     pub executed_by_bot: Option<String>,  // Bot name if trade was executed by a bot
+
+    #[serde(default)]
+    pub source: TradeSource,  // Who placed the trade; `executed_by_bot` is its flattened label
+
+    #[serde(default)]
+    pub fee: f64,  // In the quote asset, on top of a buy's cost or out of a sell's proceeds; 0 for deposits and withdrawals
+}
+
+/// `executed_by_bot` label prefix of trades mirrored from a followed user: `"copy:<leader username>"`
+pub const COPY_EXECUTOR_PREFIX: &str = "copy:";
+
+/// Who placed a trade
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TradeSource {
+    /// The user: the UI, the API, a TradingView webhook or one of their orders filling
+    #[default]
+    Manual,
+    /// A running bot; `instance_id` is None for trades recorded before bot instances were kept
+    Bot { instance_id: Option<BotId>, name: String },
+    /// Mirrored from a followed user by copy trading
+    Copy { leader: String },
+}
+
+impl TradeSource {
+    pub fn bot(instance_id: &BotId, name: &str) -> Self {
+        TradeSource::Bot { instance_id: Some(instance_id.clone()), name: name.to_string() }
+    }
+
+    /// The bot instance that placed the trade, if one did and it was recorded
+    pub fn bot_instance(&self) -> Option<&BotId> {
+        match self {
+            TradeSource::Bot { instance_id, .. } => instance_id.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The trade's `executed_by_bot` label
+    pub fn executor(&self) -> Option<String> {
+        match self {
+            TradeSource::Manual => None,
+            TradeSource::Bot { name, .. } => Some(name.clone()),
+            TradeSource::Copy { leader } => Some(format!("{}{}", COPY_EXECUTOR_PREFIX, leader)),
+        }
+    }
+
+    /// Rebuild the source from a stored `executed_by_bot` label and bot instance id
+    pub fn from_executor(executed_by_bot: Option<String>, bot_id: Option<BotId>) -> Self {
+        match executed_by_bot {
+            None => TradeSource::Manual,
+            Some(label) => match label.strip_prefix(COPY_EXECUTOR_PREFIX) {
+                Some(leader) if bot_id.is_none() => TradeSource::Copy { leader: leader.to_string() },
+                _ => TradeSource::Bot { instance_id: bot_id, name: label },
+            },
+        }
+    }
+}
+
+/// Trade history filter on `TradeSource`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSourceKind {
+    Manual,
+    Bot,
+    Copy,
 }
 
 fn default_quote_asset() -> String {
     "USD".to_string()
 }
 
+#[allow(dead_code)]
 impl Trade {
     /// Calculate total cost in quote asset
     pub fn quote_cost(&self) -> f64 {
@@ -89,36 +179,141 @@ impl Trade {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub enum TradeSide {
     Buy,
     Sell,
 }
 
+/// When a price alert fires
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertCondition {
+    Above,     // Price at or above the threshold
+    Below,     // Price at or below the threshold
+    ChangePct, // Change over the window reaches the threshold percent (negative for drops)
+}
+
+impl AlertCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertCondition::Above => "above",
+            AlertCondition::Below => "below",
+            AlertCondition::ChangePct => "change_pct",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "above" => Some(AlertCondition::Above),
+            "below" => Some(AlertCondition::Below),
+            "change_pct" => Some(AlertCondition::ChangePct),
+            _ => None,
+        }
+    }
+}
+
+/// Where a notification endpoint delivers
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Webhook, // Signed JSON POST to the URL
+    Discord, // Embed posted to a Discord channel webhook
+    Slack,   // Blocks posted to a Slack incoming webhook
+    Email,   // Plain-text mail over SMTP, for the rarer notifications only
+}
+
+impl NotificationChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationChannel::Webhook => "webhook",
+            NotificationChannel::Discord => "discord",
+            NotificationChannel::Slack => "slack",
+            NotificationChannel::Email => "email",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "webhook" => Some(NotificationChannel::Webhook),
+            "discord" => Some(NotificationChannel::Discord),
+            "slack" => Some(NotificationChannel::Slack),
+            "email" => Some(NotificationChannel::Email),
+            _ => None,
+        }
+    }
+}
+
+/// How a resting order triggers; either way it fills at market once triggered
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderType {
+    Limit, // Buy at or below the price, sell at or above it
+    Stop,  // Buy at or above the price, sell at or below it
+}
+
+impl OrderType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderType::Limit => "limit",
+            OrderType::Stop => "stop",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "limit" => Some(OrderType::Limit),
+            "stop" => Some(OrderType::Stop),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderStatus {
+    Open,
+    Filled,
+    Cancelled,
+    Failed, // Triggered, but the trade could not execute (e.g. insufficient balance)
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Open => "open",
+            OrderStatus::Filled => "filled",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "open" => Some(OrderStatus::Open),
+            "filled" => Some(OrderStatus::Filled),
+            "cancelled" => Some(OrderStatus::Cancelled),
+            "failed" => Some(OrderStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
 impl UserData {
-    pub fn new(username: String) -> Self {
+    /// A fresh account holding `starting_balance` USD (see `AccountConfig`)
+    pub fn new(username: String, starting_balance: f64) -> Self {
         let mut balances = HashMap::new();
-        balances.insert("USD".to_string(), 10000.0);
+        balances.insert("USD".to_string(), starting_balance);
 
         Self {
             username,
-            cash_balance: 10000.0,  // Kept for backward compatibility during migration
             asset_balances: balances,
             trade_history: Vec::new(),
         }
     }
 
-    /// Get USD balance (helper for convenience)
-    pub fn usd_balance(&self) -> f64 {
-        self.asset_balances.get("USD").copied().unwrap_or(self.cash_balance)
-    }
-
-    /// Get balance for any asset
+    /// Get balance for any asset (USD included)
     pub fn get_balance(&self, asset: &str) -> f64 {
-        if asset == "USD" && !self.asset_balances.contains_key("USD") {
-            // Backward compatibility: use cash_balance if USD not in map
-            return self.cash_balance;
-        }
         self.asset_balances.get(asset).copied().unwrap_or(0.0)
     }
 
@@ -140,9 +335,9 @@ impl UserData {
             .sum()
     }
 
-    /// Calculate lifetime funding (seed + deposits)
-    pub fn lifetime_funding(&self) -> f64 {
-        10000.0 + self.lifetime_deposits()
+    /// Calculate lifetime funding (seed + deposits), given the configured starting balance
+    pub fn lifetime_funding(&self, starting_balance: f64) -> f64 {
+        starting_balance + self.lifetime_deposits()
     }
 
     /// Calculate total trade volume in USD (estimated for non-USD pairs)
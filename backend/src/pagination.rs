@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Paging parameters shared by every list endpoint
+/// Cursors are the sort key of the last item on the previous page: a row id for DB-backed lists,
+/// a unix timestamp for price series
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    #[serde(alias = "before_id")]
+    pub cursor: Option<i64>, // `next_cursor` from the previous response (exclusive)
+    pub sort: Option<SortOrder>, // "asc" or "desc"; each endpoint documents its default
+}
+
+/// A `PageQuery` with the endpoint's defaults and limits applied
+#[derive(Debug, Clone, Copy)]
+pub struct PageRequest {
+    pub limit: i64,
+    pub cursor: Option<i64>,
+    pub sort: SortOrder,
+}
+
+/// One page of a list, with the cursor to pass back for the next one
+#[derive(Serialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<i64>, // None when there are no more pages
+}
+
+impl PageQuery {
+    pub fn resolve(&self, default_limit: i64, max_limit: i64, default_sort: SortOrder) -> PageRequest {
+        PageRequest {
+            limit: self.limit.unwrap_or(default_limit).clamp(1, max_limit),
+            cursor: self.cursor,
+            sort: self.sort.unwrap_or(default_sort),
+        }
+    }
+}
+
+impl PageRequest {
+    /// Rows to fetch: one past the limit, to tell whether another page follows
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    /// Trim an over-fetched page and work out the cursor for the next one
+    pub fn finish<T>(&self, mut items: Vec<T>, key: impl Fn(&T) -> i64) -> Page<T> {
+        let more = items.len() as i64 > self.limit;
+        items.truncate(self.limit as usize);
+        let next_cursor = if more { items.last().map(&key) } else { None };
+        Page { items, next_cursor }
+    }
+
+    /// Page through an in-memory series that is ordered oldest first
+    pub fn slice<T>(&self, items: Vec<T>, key: impl Fn(&T) -> i64) -> Page<T> {
+        let after_cursor = |item: &T| match (self.cursor, self.sort) {
+            (None, _) => true,
+            (Some(cursor), SortOrder::Asc) => key(item) > cursor,
+            (Some(cursor), SortOrder::Desc) => key(item) < cursor,
+        };
+        let mut items: Vec<T> = items.into_iter().filter(after_cursor).collect();
+        if self.sort == SortOrder::Desc {
+            items.reverse();
+        }
+        self.finish(items, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(limit: i64, cursor: Option<i64>, sort: SortOrder) -> PageRequest {
+        PageQuery { limit: Some(limit), cursor, sort: Some(sort) }.resolve(50, 500, SortOrder::Desc)
+    }
+
+    #[test]
+    fn test_finish_sets_cursor_only_when_more_rows_follow() {
+        let page = request(2, None, SortOrder::Desc).finish(vec![9, 8, 7], |id| *id);
+        assert_eq!(page.items, vec![9, 8]);
+        assert_eq!(page.next_cursor, Some(8));
+
+        let page = request(3, None, SortOrder::Desc).finish(vec![9, 8, 7], |id| *id);
+        assert_eq!(page.items, vec![9, 8, 7]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_slice_pages_both_directions_from_cursor() {
+        let series: Vec<i64> = (1..=5).collect();
+
+        let page = request(2, Some(2), SortOrder::Asc).slice(series.clone(), |t| *t);
+        assert_eq!(page.items, vec![3, 4]);
+        assert_eq!(page.next_cursor, Some(4));
+
+        let page = request(2, Some(4), SortOrder::Desc).slice(series.clone(), |t| *t);
+        assert_eq!(page.items, vec![3, 2]);
+        assert_eq!(page.next_cursor, Some(2));
+
+        let page = request(10, None, SortOrder::Desc).slice(series, |t| *t);
+        assert_eq!(page.items, vec![5, 4, 3, 2, 1]);
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -0,0 +1,225 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::UserId;
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::auth_service::{self, AuthError};
+use crate::services::{bot_service, export_service, session_service};
+use crate::routes::session::{require_fresh_session, require_session_token};
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AccountQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    pub user_id: UserId,
+    pub token: Option<String>, // Session token, for a plain download link that can't set headers
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    #[serde(default)]
+    pub password: Option<String>, // Re-confirm identity before destroying data; not needed by accounts without one
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ChangePasswordResponse {
+    pub success: bool,
+    pub sessions_revoked: u64, // Other sessions signed out by the change
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeleteAccountResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+/// Confirm a password belongs to the account, mapping every mismatch to 401
+async fn verify_password(state: &AppState, user_id: &UserId, username: &str, password: &str) -> ApiResult<()> {
+    match queries::verify_user_credentials(state.db.pool(), username, password).await {
+        Ok(verified_id) if &verified_id == user_id => Ok(()),
+        Ok(_) | Err(AuthError::InvalidCredentials) => Err(ApiError::unauthorized("Invalid password")),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Change the account password and sign out every other session
+/// OAuth-only accounts have no password to confirm, so they are rejected as unauthorized
+#[utoipa::path(post, path = "/api/account/password", tag = "account", params(AccountQuery),
+    request_body = ChangePasswordRequest,
+    security((), ("session_token" = [])),
+    responses(
+        (status = 200, description = "Password changed", body = ChangePasswordResponse),
+        (status = 400, description = "Demo account, or an empty or unchanged new password", body = ErrorBody),
+        (status = 401, description = "Wrong current password", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn change_password(
+    State(state): State<AppState>,
+    Query(query): Query<AccountQuery>,
+    headers: HeaderMap,
+    Json(req): Json<ChangePasswordRequest>,
+) -> ApiResult<Json<ChangePasswordResponse>> {
+    let user_id = query.user_id;
+
+    if user_id == "demo_user" {
+        return Err(ApiError::bad_request("The demo account has no password"));
+    }
+    if req.new_password.is_empty() {
+        return Err(ApiError::bad_request("New password cannot be empty").with_code("invalid_password"));
+    }
+    if req.new_password == req.current_password {
+        return Err(ApiError::bad_request("New password must differ from the current one").with_code("invalid_password"));
+    }
+
+    let user = state
+        .get_user(&user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    verify_password(&state, &user_id, &user.username, &req.current_password).await?;
+
+    let password_hash = auth_service::hash_password(&req.new_password)?;
+    if !queries::update_password_hash(state.db.pool(), &user_id, &password_hash).await? {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    // Whoever knew the old password may still hold a session; keep only the caller's
+    let sessions_revoked =
+        session_service::revoke_others(state.db.pool(), &user_id, session_service::bearer_token(&headers)).await?;
+
+    audit_service::record(
+        state.db.pool(),
+        Some(&user_id),
+        AuditAction::PasswordChanged,
+        format!("sessions_revoked={}", sessions_revoked),
+    );
+
+    Ok(Json(ChangePasswordResponse {
+        success: true,
+        sessions_revoked,
+    }))
+}
+
+/// Download everything stored about the account as a zip archive: `account.json` with the profile,
+/// balances, trades, orders, bot history, daily summaries and settings, and CSV files of its tables
+/// Needs one of the user's session tokens, as the bearer or `?token=`
+#[utoipa::path(get, path = "/api/account/export", tag = "account", params(ExportQuery),
+    security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Zip archive of the account's data", content_type = "application/zip"),
+        (status = 401, description = "Missing, expired or revoked token", body = ErrorBody),
+        (status = 403, description = "The session is another user's", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn export_account(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ExportQuery>,
+) -> ApiResult<Response> {
+    let token = session_service::bearer_token(&headers).or(query.token.as_deref());
+    require_session_token(&state, token, &query.user_id).await?;
+
+    let user = state
+        .get_user(&query.user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    let export = export_service::collect(&state, &query.user_id, user).await?;
+    let bytes = export_service::archive(&export).map_err(ApiError::internal)?;
+    let file_name = export_service::file_name(&export);
+
+    audit_service::record(
+        state.db.pool(),
+        Some(&query.user_id),
+        AuditAction::AccountExported,
+        format!("trades={} orders={} bytes={}", export.trades.len(), export.orders.len(), bytes.len()),
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// Permanently delete an account: stop bots, purge memory, remove all DB rows
+/// Confirmed with the password, or for OAuth-only accounts, which have none, by a session signed in
+/// within the last `REAUTH_WINDOW_MINUTES` as the bearer
+#[utoipa::path(delete, path = "/api/account", tag = "account", params(AccountQuery), request_body = DeleteAccountRequest,
+    security((), ("session_token" = [])),
+    responses(
+        (status = 200, description = "Account and all its data deleted", body = DeleteAccountResponse),
+        (status = 400, description = "The demo account cannot be deleted", body = ErrorBody),
+        (status = 401, description = "Wrong password, or for an account without one, a missing or stale session", body = ErrorBody),
+        (status = 403, description = "The session is another user's", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn delete_account(
+    State(state): State<AppState>,
+    Query(query): Query<AccountQuery>,
+    headers: HeaderMap,
+    Json(req): Json<DeleteAccountRequest>,
+) -> ApiResult<Json<DeleteAccountResponse>> {
+    let user_id = query.user_id;
+
+    if user_id == "demo_user" {
+        return Err(ApiError::bad_request("The demo account cannot be deleted"));
+    }
+
+    let user = state
+        .get_user(&user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    // Verify password belongs to this account; with no password to ask for, a fresh sign-in stands in
+    if queries::has_password(state.db.pool(), &user_id).await? {
+        let password = req.password.as_deref().unwrap_or_default();
+        verify_password(&state, &user_id, &user.username, password).await?;
+    } else {
+        require_fresh_session(&state, &headers, &user_id).await?;
+    }
+
+    // Stop bots first so nothing mutates the account while it is being removed
+    bot_service::stop_user_bots(&state, &user_id, "account deleted").await;
+
+    // The database first: if its delete fails, the account is still whole in memory and can be retried
+    queries::delete_account(state.db.pool(), &user_id).await?;
+    state.users.write().await.remove(&user_id);
+
+    tracing::info!("Deleted account {} ({})", user_id, user.username);
+    // Not under the user's id: the audit entries under it were just deleted with the account
+    audit_service::record(
+        state.db.pool(),
+        None,
+        AuditAction::AccountDeleted,
+        format!("{} ({})", user_id, user.username),
+    );
+
+    Ok(Json(DeleteAccountResponse {
+        success: true,
+        message: format!("Account '{}' deleted", user.username),
+    }))
+}
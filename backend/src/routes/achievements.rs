@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries;
+use crate::error::ApiResult;
+use crate::models::UserId;
+use crate::services::achievement_service::{Achievement, ACHIEVEMENTS};
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AchievementQuery {
+    pub user_id: UserId,
+}
+
+/// A badge from the catalog, and whether the user has it
+#[derive(Serialize, ToSchema)]
+pub struct Badge {
+    #[serde(flatten)]
+    pub achievement: Achievement,
+    pub earned_at: Option<DateTime<Utc>>, // Null until earned
+    pub details: Option<String>,          // What earned it
+}
+
+/// Every badge in catalog order, with the user's earned ones filled in
+/// Badges are awarded as the user trades and runs bots; guests earn none
+#[utoipa::path(get, path = "/api/achievements", tag = "achievements", params(AchievementQuery),
+    responses((status = 200, description = "The badge catalog with earned badges marked", body = Vec<Badge>)))]
+pub async fn list_achievements(
+    State(state): State<AppState>,
+    Query(query): Query<AchievementQuery>,
+) -> ApiResult<Json<Vec<Badge>>> {
+    let earned = queries::list_user_achievements(state.db.pool(), &query.user_id).await?;
+
+    Ok(Json(
+        ACHIEVEMENTS
+            .iter()
+            .map(|achievement| {
+                let held = earned.iter().find(|e| e.achievement == achievement.id);
+                Badge {
+                    achievement: *achievement,
+                    earned_at: held.map(|e| e.earned_at),
+                    details: held.map(|e| e.details.clone()),
+                }
+            })
+            .collect(),
+    ))
+}
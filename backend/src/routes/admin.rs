@@ -0,0 +1,516 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::api_client::ApiClient;
+use crate::db::queries;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{Asset, BotId, TransactionType, UserId};
+use crate::pagination::{Page, PageQuery, SortOrder};
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::backup_service::{self, BackupFormat};
+use crate::services::bot_service;
+use crate::services::history_service::{self, ImportSummary};
+use crate::services::ledger_service::{self, Reconciliation};
+use crate::services::stats_service::{self, PlatformStats};
+use crate::routes::session::require_session;
+use crate::state::{AppState, NotificationKind, TransactionError};
+
+const DEFAULT_VIOLATION_LIMIT: i64 = 100;
+const MAX_VIOLATION_LIMIT: i64 = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AdminQuery {
+    pub user_id: UserId, // The admin making the request
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BackupQuery {
+    pub user_id: UserId,
+    pub format: Option<String>, // "sqlite" or "json" (defaults to sqlite on SQLite, json otherwise)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryImportQuery {
+    pub user_id: UserId,
+    pub asset: String,      // Priced in USD, e.g. "BTC"
+    pub interval_secs: i64, // Candle length of every row: 60, 300, 900, 3600, 21600 or 86400
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InvariantQuery {
+    pub user_id: UserId,
+    /// Only this user's violations
+    pub target_user_id: Option<UserId>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BotRunsQuery {
+    pub user_id: UserId,
+    /// Only this user's runs
+    pub target_user_id: Option<UserId>,
+    /// Only this bot's run
+    pub bot_instance_id: Option<BotId>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LedgerQuery {
+    pub user_id: UserId,
+    pub target_user_id: UserId, // Account to reconcile
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LedgerReconciliation {
+    pub user_id: UserId,
+    pub stored: HashMap<Asset, f64>, // Balances the account trades on
+    #[serde(flatten)]
+    pub reconciliation: Reconciliation,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct HistoryFetchRequest {
+    pub asset: String,
+    pub interval_secs: i64,
+    pub start: DateTime<Utc>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>, // Defaults to now
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdminUserSummary {
+    pub user_id: UserId,
+    pub username: String,
+    pub role: String,
+    pub usd_balance: f64,
+    pub trade_count: usize,
+    pub bot_active: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdminStatsResponse {
+    #[serde(flatten)]
+    pub platform: PlatformStats,
+    pub total_deposits: usize,
+    pub total_withdrawals: usize,
+    pub db_size_bytes: Option<i64>, // None if the database can't report it
+    pub invariant_violations: i64,  // Recorded so far; see /api/admin/invariants
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdminActionResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+/// Verify the caller has the admin role: the bearer session token must be `user_id`'s, and they an admin
+pub async fn require_admin(state: &AppState, headers: &HeaderMap, user_id: &UserId) -> ApiResult<()> {
+    require_session(state, headers, user_id).await?;
+
+    let role = queries::get_user_role(state.db.pool(), user_id)
+        .await?;
+
+    match role.as_deref() {
+        Some("admin") => Ok(()),
+        Some(_) => Err(ApiError::forbidden("Admin access required")),
+        None => Err(ApiError::unauthorized("Unknown user")),
+    }
+}
+
+/// List all users with a short portfolio summary
+#[utoipa::path(get, path = "/api/admin/users", tag = "admin", params(AdminQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Every user", body = Vec<AdminUserSummary>),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn list_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AdminQuery>,
+) -> ApiResult<Json<Vec<AdminUserSummary>>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let roles = queries::load_user_roles(state.db.pool())
+        .await?;
+
+    let active_bots: HashSet<UserId> = state.bots.read().await.values().map(|bot| bot.user_id.clone()).collect();
+
+    let mut users: Vec<AdminUserSummary> = state
+        .users
+        .read()
+        .await
+        .iter()
+        .map(|(user_id, user)| AdminUserSummary {
+            user_id: user_id.clone(),
+            username: user.username.clone(),
+            role: roles.get(user_id).cloned().unwrap_or_else(|| "guest".to_string()),
+            usd_balance: user.get_balance("USD"),
+            trade_count: user
+                .trade_history
+                .iter()
+                .filter(|t| t.transaction_type == TransactionType::Trade)
+                .count(),
+            bot_active: active_bots.contains(user_id),
+        })
+        .collect();
+
+    users.sort_by(|a, b| a.username.cmp(&b.username));
+
+    Ok(Json(users))
+}
+
+/// Aggregate platform statistics: the public stats plus funding totals and DB size
+#[utoipa::path(get, path = "/api/admin/stats", tag = "admin", params(AdminQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Platform totals, activity, feed health and DB size", body = AdminStatsResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AdminQuery>,
+) -> ApiResult<Json<AdminStatsResponse>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let platform = stats_service::platform_stats(&state).await;
+    let db_size_bytes = stats_service::database_size_bytes(&state.db).await;
+    let invariant_violations = queries::count_invariant_violations(state.db.pool()).await?;
+
+    let users = state.users.read().await;
+    let count_of = |kind: TransactionType| -> usize {
+        users
+            .values()
+            .flat_map(|u| u.trade_history.iter())
+            .filter(|t| t.transaction_type == kind)
+            .count()
+    };
+
+    Ok(Json(AdminStatsResponse {
+        platform,
+        total_deposits: count_of(TransactionType::Deposit),
+        total_withdrawals: count_of(TransactionType::Withdrawal),
+        db_size_bytes,
+        invariant_violations,
+    }))
+}
+
+/// Reset a user's portfolio to the starting balance (stops their bot first)
+#[utoipa::path(post, path = "/api/admin/users/{target_id}/reset", tag = "admin", params(("target_id" = String, Path, description = "User to act on"), AdminQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Portfolio reset", body = AdminActionResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn reset_user(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(target_id): Path<UserId>,
+    Query(query): Query<AdminQuery>,
+) -> ApiResult<Json<AdminActionResponse>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let stopped = bot_service::stop_user_bots(&state, &target_id, "portfolio reset by admin").await;
+    notify_stopped(&state, &target_id, stopped, "portfolio reset by admin");
+
+    state
+        .reset_portfolio(&target_id, state.config.accounts.starting_balance)
+        .await
+        .map_err(|e| match e {
+            TransactionError::UserNotFound => ApiError::not_found("User not found"),
+            TransactionError::Persistence | TransactionError::InsufficientBalance(_) => {
+                ApiError::internal("Failed to reset portfolio")
+            }
+        })?;
+
+    tracing::info!("Admin {} reset portfolio for user {}", query.user_id, target_id);
+    audit_service::record(
+        state.db.pool(),
+        Some(&target_id),
+        AuditAction::PortfolioReset,
+        format!("by admin {}", query.user_id),
+    );
+
+    Ok(Json(AdminActionResponse {
+        success: true,
+        message: format!("Portfolio reset for user {}", target_id),
+    }))
+}
+
+/// Stop every bot a user runs
+#[utoipa::path(post, path = "/api/admin/users/{target_id}/stop-bot", tag = "admin", params(("target_id" = String, Path, description = "User to act on"), AdminQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Bots stopped", body = AdminActionResponse),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+        (status = 404, description = "No active bot for this user", body = ErrorBody),
+    ))]
+pub async fn stop_user_bot(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(target_id): Path<UserId>,
+    Query(query): Query<AdminQuery>,
+) -> ApiResult<Json<AdminActionResponse>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let stopped = bot_service::stop_user_bots(&state, &target_id, "stopped by admin").await;
+    if stopped.is_empty() {
+        return Err(ApiError::not_found("No active bot for this user"));
+    }
+    let count = stopped.len();
+    notify_stopped(&state, &target_id, stopped, "stopped by admin");
+
+    Ok(Json(AdminActionResponse {
+        success: true,
+        message: format!("Stopped {} bot(s) for user {}", count, target_id),
+    }))
+}
+
+/// Tell the user about bots an admin stopped, as a stop from the bot page or a stoploss would
+fn notify_stopped(state: &AppState, user_id: &UserId, bot_names: Vec<String>, reason: &str) {
+    for bot_name in bot_names {
+        state.notify(user_id, NotificationKind::BotStopped { bot_name, reason: reason.to_string(), by_user: false });
+    }
+}
+
+/// Download a consistent backup of the database
+#[utoipa::path(get, path = "/api/admin/backup", tag = "admin", params(BackupQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Database file (sqlite) or JSON export", content_type = "application/octet-stream"),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<BackupQuery>,
+) -> ApiResult<Response> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let format = match query.format.as_deref() {
+        Some(name) => BackupFormat::from_name(name)
+            .ok_or_else(|| ApiError::bad_request(format!("Unknown backup format: {}", name)))?,
+        None => BackupFormat::default_for(state.db.backend()),
+    };
+
+    if format == BackupFormat::Sqlite && state.db.backend() != crate::db::DbBackend::Sqlite {
+        return Err(ApiError::bad_request("SQLite snapshots are only available with a SQLite database"));
+    }
+
+    let bytes = backup_service::create_backup(&state.db, format)
+        .await
+        .map_err(ApiError::internal)?;
+
+    let file_name = backup_service::backup_file_name(format);
+    tracing::info!("Admin {} downloaded backup {} ({} bytes)", query.user_id, file_name, bytes.len());
+    audit_service::record(
+        state.db.pool(),
+        Some(&query.user_id),
+        AuditAction::BackupExported,
+        file_name.clone(),
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file_name)),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// Asset symbols are 2 to 10 letters or digits; USD is the quote every candle is priced in
+fn normalize_asset(asset: &str) -> ApiResult<String> {
+    let asset = asset.trim().to_uppercase();
+    if !(2..=10).contains(&asset.len()) || !asset.chars().all(|c| c.is_ascii_alphanumeric()) || asset == "USD" {
+        return Err(ApiError::bad_request(format!("Invalid asset: {}", asset)).with_code("invalid_asset"));
+    }
+    Ok(asset)
+}
+
+fn record_import(state: &AppState, user_id: &UserId, source: &str, summary: &ImportSummary) {
+    tracing::info!(
+        "Admin {} imported {} {}s candles from {}: {} new, {} duplicate, {} rejected",
+        user_id, summary.asset, summary.interval_secs, source, summary.inserted, summary.duplicates, summary.rejected
+    );
+    audit_service::record(
+        state.db.pool(),
+        Some(user_id),
+        AuditAction::HistoryImported,
+        format!("{} {} {}s inserted={}", source, summary.asset, summary.interval_secs, summary.inserted),
+    );
+}
+
+/// Accounting invariants broken by balance changes (see invariant_service), newest first unless `sort=asc`
+/// Anything listed here is a bug: the change went through, but didn't add up
+#[utoipa::path(get, path = "/api/admin/invariants", tag = "admin", params(InvariantQuery, PageQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "A page of violations; `next_cursor` is a violation id", body = Page<queries::InvariantViolation>),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn invariant_violations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<InvariantQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<queries::InvariantViolation>>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let page = page.resolve(DEFAULT_VIOLATION_LIMIT, MAX_VIOLATION_LIMIT, SortOrder::Desc);
+    let violations = queries::get_invariant_violations(
+        state.db.pool(),
+        query.target_user_id.as_deref(),
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(page.finish(violations, |violation| violation.id)))
+}
+
+/// Bot runs across all users (or one), with their errors and how they ended; newest first unless `sort=asc`
+#[utoipa::path(get, path = "/api/admin/bot-runs", tag = "admin", params(BotRunsQuery, PageQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "A page of runs; `next_cursor` is a run id", body = Page<queries::BotRun>),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn bot_runs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<BotRunsQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<queries::BotRun>>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let page = page.resolve(DEFAULT_VIOLATION_LIMIT, MAX_VIOLATION_LIMIT, SortOrder::Desc);
+    let runs = queries::get_bot_runs(
+        state.db.pool(),
+        query.target_user_id.as_deref(),
+        query.bot_instance_id.as_ref(),
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(page.finish(runs, |run| run.id)))
+}
+
+/// Rebuild an account's balances from its ledger, check the ledger's hash chain, and list
+/// the assets whose stored balance disagrees; accounts without entries have nothing to check yet
+#[utoipa::path(get, path = "/api/admin/ledger", tag = "admin", params(LedgerQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Rebuilt balances, chain check and mismatches", body = LedgerReconciliation),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn reconcile_ledger(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LedgerQuery>,
+) -> ApiResult<Json<LedgerReconciliation>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let user = state.get_user(&query.target_user_id).await.ok_or_else(|| ApiError::not_found("User not found"))?;
+    let entries = queries::load_ledger(state.db.pool(), &query.target_user_id).await?;
+    let reconciliation = ledger_service::reconcile(&entries, &user.asset_balances);
+    if !reconciliation.chain_intact || !reconciliation.mismatches.is_empty() {
+        tracing::warn!(
+            "Ledger for user {} does not reconcile: first broken entry {:?}, {} mismatched assets",
+            query.target_user_id,
+            reconciliation.first_broken_entry,
+            reconciliation.mismatches.len()
+        );
+    }
+
+    Ok(Json(LedgerReconciliation { user_id: query.target_user_id, stored: user.asset_balances, reconciliation }))
+}
+
+/// Imported price history per asset and candle length
+#[utoipa::path(get, path = "/api/admin/history", tag = "admin", params(AdminQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Candle counts and time ranges", body = Vec<queries::CandleCoverage>),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn history_coverage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AdminQuery>,
+) -> ApiResult<Json<Vec<queries::CandleCoverage>>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    Ok(Json(queries::price_candle_coverage(state.db.pool()).await?))
+}
+
+/// Import OHLCV history from a CSV body, for backtests older than the in-memory prices
+/// Columns are found by header name (timestamp/date, open, high, low, close, optional volume), or
+/// taken in that order without a header. Invalid rows are skipped and reported; candles already
+/// stored for the same open time are left as they are
+#[utoipa::path(post, path = "/api/admin/history/import", tag = "admin", params(HistoryImportQuery), security(("session_token" = [])),
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = 200, description = "What was stored, skipped and rejected", body = ImportSummary),
+        (status = 400, description = "Invalid asset or interval, or an unreadable file", body = ErrorBody),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn import_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<HistoryImportQuery>,
+    body: String,
+) -> ApiResult<Json<ImportSummary>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let asset = normalize_asset(&query.asset)?;
+    history_service::validate_interval(query.interval_secs)
+        .map_err(|e| ApiError::bad_request(e).with_code("invalid_interval"))?;
+    let batch = history_service::parse_csv(&body, query.interval_secs)
+        .map_err(|e| ApiError::bad_request(e).with_code("invalid_csv"))?;
+
+    let summary = history_service::store(state.db.pool(), &asset, query.interval_secs, batch, "csv").await?;
+    record_import(&state, &query.user_id, "csv", &summary);
+
+    Ok(Json(summary))
+}
+
+/// Import OHLCV history from the exchange candles API (Coinbase, in USD)
+/// The request waits for every page; ranges are limited to 100,000 candles
+#[utoipa::path(post, path = "/api/admin/history/fetch", tag = "admin", params(AdminQuery), security(("session_token" = [])),
+    request_body = HistoryFetchRequest,
+    responses(
+        (status = 200, description = "What was stored and skipped", body = ImportSummary),
+        (status = 400, description = "Invalid asset, interval or range", body = ErrorBody),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+        (status = 502, description = "The exchange request failed", body = ErrorBody),
+    ))]
+pub async fn fetch_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AdminQuery>,
+    Json(req): Json<HistoryFetchRequest>,
+) -> ApiResult<Json<ImportSummary>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let asset = normalize_asset(&req.asset)?;
+    let end = history_service::validate_fetch_range(req.interval_secs, req.start, req.end.unwrap_or_else(Utc::now), Utc::now())
+        .map_err(|e| ApiError::bad_request(e).with_code("invalid_history_range"))?;
+    let batch = history_service::fetch_exchange(&ApiClient::new(&state.config.exchange), &asset, req.interval_secs, req.start, end)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::BAD_GATEWAY, "history_fetch_failed", e))?;
+
+    let summary = history_service::store(state.db.pool(), &asset, req.interval_secs, batch, "coinbase").await?;
+    record_import(&state, &query.user_id, "coinbase", &summary);
+
+    Ok(Json(summary))
+}
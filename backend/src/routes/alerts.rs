@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries::{self, AlertTrigger, PriceAlert};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{AlertCondition, UserId};
+use crate::pagination::{Page, PageQuery, SortOrder};
+use crate::services::alert_service::{self, MAX_ALERTS_PER_USER};
+use crate::state::AppState;
+
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 500;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AlertQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AlertRequest {
+    pub asset: String,
+    pub condition: AlertCondition,
+    pub threshold: f64, // USD price, or percent for change_pct (e.g. -5 for a 5% drop)
+    pub window_minutes: Option<i64>, // Required for change_pct
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeleteAlertResponse {
+    pub success: bool,
+}
+
+/// Normalized asset, after checking the definition makes sense
+/// Only polled assets get prices to check against (add others to a watchlist first)
+async fn validate_request(state: &AppState, req: &AlertRequest) -> ApiResult<String> {
+    let asset = req.asset.trim().to_uppercase();
+    let polled = state.polled_assets().await;
+    if !polled.contains(&asset) {
+        return Err(ApiError::bad_request(format!(
+            "Unsupported asset: {}. Alerts can watch {}",
+            asset,
+            polled.join(", ")
+        ))
+        .with_code("unsupported_asset"));
+    }
+
+    alert_service::validate(req.condition, req.threshold, req.window_minutes)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_alert"))?;
+    Ok(asset)
+}
+
+/// List the user's alerts, newest first; fired alerts have `triggered_at` set
+#[utoipa::path(get, path = "/api/alerts", tag = "alerts", params(AlertQuery),
+    responses((status = 200, description = "The user's alerts", body = Vec<PriceAlert>)))]
+pub async fn list_alerts(
+    State(state): State<AppState>,
+    Query(query): Query<AlertQuery>,
+) -> ApiResult<Json<Vec<PriceAlert>>> {
+    Ok(Json(queries::list_alerts(state.db.pool(), &query.user_id).await?))
+}
+
+/// Create a price alert; it fires once, the first time a live price meets the condition
+#[utoipa::path(post, path = "/api/alerts", tag = "alerts", params(AlertQuery), request_body = AlertRequest,
+    responses(
+        (status = 200, description = "The armed alert", body = PriceAlert),
+        (status = 400, description = "Invalid alert or unsupported asset", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "Alert limit reached", body = ErrorBody),
+    ))]
+pub async fn create_alert(
+    State(state): State<AppState>,
+    Query(query): Query<AlertQuery>,
+    Json(req): Json<AlertRequest>,
+) -> ApiResult<Json<PriceAlert>> {
+    let asset = validate_request(&state, &req).await?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let count = queries::count_alerts(state.db.pool(), &query.user_id).await?;
+    if count >= MAX_ALERTS_PER_USER {
+        return Err(ApiError::conflict(format!(
+            "You can have at most {} alerts; delete some first",
+            MAX_ALERTS_PER_USER
+        ))
+        .with_code("alert_limit_reached"));
+    }
+
+    let alert = PriceAlert {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: query.user_id,
+        asset,
+        condition: req.condition,
+        threshold: req.threshold,
+        window_minutes: req.window_minutes,
+        created_at: Utc::now(),
+        triggered_at: None,
+    };
+    queries::create_alert(state.db.pool(), &alert).await?;
+
+    Ok(Json(alert))
+}
+
+/// Change an alert; this also re-arms it if it already fired
+#[utoipa::path(put, path = "/api/alerts/{alert_id}", tag = "alerts",
+    params(("alert_id" = String, Path, description = "Alert to change"), AlertQuery), request_body = AlertRequest,
+    responses(
+        (status = 200, description = "The updated, armed alert", body = PriceAlert),
+        (status = 400, description = "Invalid alert or unsupported asset", body = ErrorBody),
+        (status = 404, description = "No such alert", body = ErrorBody),
+    ))]
+pub async fn update_alert(
+    State(state): State<AppState>,
+    Path(alert_id): Path<String>,
+    Query(query): Query<AlertQuery>,
+    Json(req): Json<AlertRequest>,
+) -> ApiResult<Json<PriceAlert>> {
+    let asset = validate_request(&state, &req).await?;
+
+    let mut alert = queries::get_alert(state.db.pool(), &query.user_id, &alert_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Alert not found"))?;
+
+    alert.asset = asset;
+    alert.condition = req.condition;
+    alert.threshold = req.threshold;
+    alert.window_minutes = req.window_minutes;
+    alert.triggered_at = None;
+
+    if !queries::update_alert(state.db.pool(), &alert).await? {
+        return Err(ApiError::not_found("Alert not found"));
+    }
+
+    Ok(Json(alert))
+}
+
+#[utoipa::path(delete, path = "/api/alerts/{alert_id}", tag = "alerts",
+    params(("alert_id" = String, Path, description = "Alert to delete"), AlertQuery),
+    responses(
+        (status = 200, description = "Alert deleted; its history is kept", body = DeleteAlertResponse),
+        (status = 404, description = "No such alert", body = ErrorBody),
+    ))]
+pub async fn delete_alert(
+    State(state): State<AppState>,
+    Path(alert_id): Path<String>,
+    Query(query): Query<AlertQuery>,
+) -> ApiResult<Json<DeleteAlertResponse>> {
+    if !queries::delete_alert(state.db.pool(), &query.user_id, &alert_id).await? {
+        return Err(ApiError::not_found("Alert not found"));
+    }
+
+    Ok(Json(DeleteAlertResponse { success: true }))
+}
+
+/// Alerts that fired, with the price that fired them, newest first unless `sort=asc`
+#[utoipa::path(get, path = "/api/alerts/history", tag = "alerts", params(AlertQuery, PageQuery),
+    responses((status = 200, description = "A page of fired alerts; `next_cursor` is an entry id", body = Page<AlertTrigger>)))]
+pub async fn alert_history(
+    State(state): State<AppState>,
+    Query(query): Query<AlertQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<AlertTrigger>>> {
+    let page = page.resolve(DEFAULT_HISTORY_LIMIT, MAX_HISTORY_LIMIT, SortOrder::Desc);
+
+    let entries = queries::get_alert_history(
+        state.db.pool(),
+        &query.user_id,
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(page.finish(entries, |entry| entry.id)))
+}
@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::db::queries::{self, AuditEntry};
+use crate::error::{ApiResult, ErrorBody};
+use crate::models::UserId;
+use crate::pagination::{Page, PageQuery, SortOrder};
+use crate::routes::admin::require_admin;
+use crate::routes::session::require_session;
+use crate::state::AppState;
+
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    pub user_id: UserId,
+    /// Admin only: view another user's log
+    pub target_user_id: Option<UserId>,
+    /// Admin only: view the log for all users (including failed logins)
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// Get audit log entries (own account, or any account for admins), newest first unless `sort=asc`
+/// Needs the caller's own session either way
+#[utoipa::path(get, path = "/api/audit", tag = "account", params(AuditQuery, PageQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "A page of audit entries; `next_cursor` is an entry id", body = Page<AuditEntry>),
+        (status = 401, description = "Missing, expired or another user's session", body = ErrorBody),
+        (status = 403, description = "Another user's log requested without the admin role", body = ErrorBody),
+    ))]
+pub async fn get_audit_log(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<AuditEntry>>> {
+    let page = page.resolve(DEFAULT_AUDIT_LIMIT, MAX_AUDIT_LIMIT, SortOrder::Desc);
+
+    let scope = if query.all {
+        require_admin(&state, &headers, &query.user_id).await?;
+        None
+    } else {
+        match query.target_user_id {
+            Some(target) if target != query.user_id => {
+                require_admin(&state, &headers, &query.user_id).await?;
+                Some(target)
+            }
+            _ => {
+                require_session(&state, &headers, &query.user_id).await?;
+                Some(query.user_id)
+            }
+        }
+    };
+
+    let entries = queries::get_audit_entries(
+        state.db.pool(),
+        scope.as_deref(),
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(page.finish(entries, |entry| entry.id)))
+}
@@ -1,41 +1,69 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{ConnectInfo, Path, Query, State},
+    http::HeaderMap,
+    response::Redirect,
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::state::AppState;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use utoipa::{IntoParams, ToSchema};
+use crate::state::{AppState, PendingOAuth};
+use crate::routes::account::ChangePasswordResponse;
+use crate::services::audit_service::{self, AuditAction};
 use crate::services::auth_service::{self, AuthError};
+use crate::services::email_service;
+use crate::services::oauth_service::{self, OAuthConfig, OAuthProvider};
+use crate::services::session_service::{self, DeviceInfo, IssuedSession};
 use crate::db::queries;
-use crate::models::{UserId, UserData};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{NotificationChannel, UserId, UserData};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct SignupRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct AuthResponse {
     pub user_id: UserId,
     pub username: String,
+    pub session_token: String, // Send as `Authorization: Bearer <token>`
+    pub expires_at: DateTime<Utc>,
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
+/// Issue a session for a user who just authenticated
+async fn start_session(
+    state: &AppState,
+    user_id: &UserId,
+    headers: &HeaderMap,
+    addr: SocketAddr,
+) -> ApiResult<IssuedSession> {
+    session_service::create_session(state.db.pool(), user_id, DeviceInfo::from_request(headers, addr))
+        .await
+        .map_err(|e| ApiError::internal(format!("Failed to create session: {}", e)).with_code("database_error"))
 }
 
+#[utoipa::path(post, path = "/api/signup", tag = "auth", request_body = SignupRequest,
+    responses(
+        (status = 200, description = "Account created and signed in", body = AuthResponse),
+        (status = 409, description = "Username already taken", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
 pub async fn signup(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<SignupRequest>,
-) -> Result<Json<AuthResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> ApiResult<Json<AuthResponse>> {
     // Generate new user ID
     let user_id = auth_service::generate_user_id();
 
@@ -45,84 +73,352 @@ pub async fn signup(
         &user_id,
         &payload.username,
         &payload.password,
+        state.config.accounts.starting_balance,
     )
     .await
     {
         Ok(_) => {
             // Also add user to in-memory state
-            let user_data = UserData::new(payload.username.clone());
-            let mut inner_state = state.inner.write().await;
-            inner_state.users.insert(user_id.clone(), user_data);
-            drop(inner_state);
+            let user_data = UserData::new(payload.username.clone(), state.config.accounts.starting_balance);
+            state.users.write().await.insert(user_id.clone(), user_data);
 
+            audit_service::record(state.db.pool(), Some(&user_id), AuditAction::Signup, String::new());
+
+            let session = start_session(&state, &user_id, &headers, addr).await?;
             Ok(Json(AuthResponse {
                 user_id,
                 username: payload.username,
+                session_token: session.token,
+                expires_at: session.expires_at,
             }))
         }
-        Err(AuthError::UserAlreadyExists) => Err((
-            StatusCode::CONFLICT,
-            Json(ErrorResponse {
-                error: "Username already exists".to_string(),
-            }),
-        )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Failed to create user: {}", e),
-            }),
-        )),
+        Err(e @ AuthError::UserAlreadyExists) => Err(e.into()),
+        Err(e) => Err(ApiError::internal(format!("Failed to create user: {}", e))),
     }
 }
 
+#[utoipa::path(post, path = "/api/login", tag = "auth", request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Signed in", body = AuthResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
 pub async fn login(
     State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> ApiResult<Json<AuthResponse>> {
     match queries::verify_user_credentials(state.db.pool(), &payload.username, &payload.password)
         .await
     {
-        Ok(user_id) => Ok(Json(AuthResponse {
-            user_id,
-            username: payload.username,
-        })),
-        Err(AuthError::InvalidCredentials) => Err((
-            StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "Invalid username or password".to_string(),
-            }),
-        )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: format!("Login failed: {}", e),
-            }),
-        )),
+        Ok(user_id) => {
+            audit_service::record(state.db.pool(), Some(&user_id), AuditAction::Login, String::new());
+
+            let session = start_session(&state, &user_id, &headers, addr).await?;
+            Ok(Json(AuthResponse {
+                user_id,
+                username: payload.username,
+                session_token: session.token,
+                expires_at: session.expires_at,
+            }))
+        }
+        Err(AuthError::InvalidCredentials) => {
+            audit_service::record(
+                state.db.pool(),
+                None,
+                AuditAction::LoginFailed,
+                format!("username={}", payload.username),
+            );
+            Err(AuthError::InvalidCredentials.into())
+        }
+        Err(e) => Err(ApiError::internal(format!("Login failed: {}", e))),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordResetRequest {
+    pub username: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PasswordResetResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ConfirmPasswordResetRequest {
+    pub token: String, // From the reset email
+    pub new_password: String,
+}
+
+/// Mail a reset token to each of the account's email notification endpoints
+async fn send_password_reset(state: &AppState, username: &str) -> Result<(), String> {
+    let (Some(mailer), Ok(Some((user_id, _)))) =
+        (&state.mailer, queries::get_user_by_username(state.db.pool(), username).await)
+    else {
+        return Ok(()); // No such user, or no password to reset
+    };
+    let addresses: Vec<String> = queries::list_notification_endpoints(state.db.pool(), &user_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|endpoint| endpoint.channel == NotificationChannel::Email)
+        .map(|endpoint| email_service::address(&endpoint.url).to_string())
+        .collect();
+    if addresses.is_empty() {
+        return Ok(());
+    }
+
+    let token = session_service::issue_password_reset(state.db.pool(), &user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let body = format!(
+        "A password reset was requested for {}.\n\nReset token: {}\n\n\
+         Send it to POST /api/password-reset/confirm with your new password within {} minutes. \
+         If you did not ask for this, ignore this email; your password is unchanged.\n",
+        username,
+        token,
+        session_service::PASSWORD_RESET_TTL_MINUTES
+    );
+    for address in addresses {
+        mailer
+            .send(&address, "[Trading Simulator] Password reset", body.clone())
+            .await
+            .map_err(|e| e.message)?;
+    }
+    Ok(())
+}
+
+/// Email a password reset token to the account's email notification endpoints, if it has any
+/// The answer is the same whether or not the account exists or has one
+#[utoipa::path(post, path = "/api/password-reset", tag = "auth", request_body = PasswordResetRequest,
+    responses(
+        (status = 200, description = "Accepted; a token is mailed if the account has an email endpoint", body = PasswordResetResponse),
+        (status = 400, description = "Email is not configured on this server", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(payload): Json<PasswordResetRequest>,
+) -> ApiResult<Json<PasswordResetResponse>> {
+    if state.mailer.is_none() {
+        return Err(ApiError::bad_request("Email is not configured on this server").with_code("email_unavailable"));
+    }
+
+    // Sent in the background so the response time doesn't tell whether the account exists
+    let username = payload.username.trim().to_string();
+    tokio::spawn(async move {
+        if let Err(e) = send_password_reset(&state, &username).await {
+            tracing::error!("Failed to send password reset for {}: {}", username, e);
+        }
+    });
+
+    Ok(Json(PasswordResetResponse {
+        success: true,
+        message: "If the account has an email endpoint, a reset token is on its way".to_string(),
+    }))
+}
+
+/// Set a new password with a mailed reset token, signing out every session
+#[utoipa::path(post, path = "/api/password-reset/confirm", tag = "auth", request_body = ConfirmPasswordResetRequest,
+    responses(
+        (status = 200, description = "Password changed", body = ChangePasswordResponse),
+        (status = 400, description = "Invalid or expired token, or an empty password", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
+pub async fn confirm_password_reset(
+    State(state): State<AppState>,
+    Json(payload): Json<ConfirmPasswordResetRequest>,
+) -> ApiResult<Json<ChangePasswordResponse>> {
+    if payload.new_password.is_empty() {
+        return Err(ApiError::bad_request("New password cannot be empty").with_code("invalid_password"));
+    }
+
+    let user_id = session_service::redeem_password_reset(state.db.pool(), &payload.token)
+        .await?
+        .ok_or_else(|| {
+            ApiError::bad_request("The reset token is invalid or has expired").with_code("invalid_reset_token")
+        })?;
+
+    let password_hash = auth_service::hash_password(&payload.new_password)?;
+    if !queries::update_password_hash(state.db.pool(), &user_id, &password_hash).await? {
+        return Err(ApiError::bad_request("The reset token is invalid or has expired").with_code("invalid_reset_token"));
+    }
+
+    // Whoever the old password leaked to may hold a session
+    let sessions_revoked = session_service::revoke_others(state.db.pool(), &user_id, None).await?;
+
+    audit_service::record(
+        state.db.pool(),
+        Some(&user_id),
+        AuditAction::PasswordChanged,
+        format!("reset by email, sessions_revoked={}", sessions_revoked),
+    );
+
+    Ok(Json(ChangePasswordResponse {
+        success: true,
+        sessions_revoked,
+    }))
+}
+
+/// How long an OAuth authorization may take before its CSRF state expires
+const OAUTH_STATE_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OAuthCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+fn oauth_config(provider: &str) -> ApiResult<OAuthConfig> {
+    OAuthProvider::from_name(provider)
+        .and_then(OAuthConfig::from_env)
+        .ok_or_else(|| {
+            ApiError::not_found(format!("OAuth provider '{}' is not configured", provider))
+                .with_code("oauth_provider_not_configured")
+        })
+}
+
+/// Start an OAuth login: redirect the browser to the provider's consent page
+#[utoipa::path(get, path = "/api/auth/oauth/{provider}/authorize", tag = "auth",
+    params(("provider" = String, Path, description = "`google` or `github`")),
+    responses(
+        (status = 303, description = "Redirect to the provider's consent page"),
+        (status = 404, description = "Provider not configured", body = ErrorBody),
+    ))]
+pub async fn oauth_authorize(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> ApiResult<Redirect> {
+    let config = oauth_config(&provider)?;
+    let csrf_state = auth_service::generate_user_id();
+
+    {
+        let mut pending_oauth = state.pending_oauth.lock().await;
+        pending_oauth.retain(|_, pending| pending.created_at.elapsed() < OAUTH_STATE_TTL);
+        pending_oauth.insert(
+            csrf_state.clone(),
+            PendingOAuth {
+                provider: config.provider.name().to_string(),
+                created_at: Instant::now(),
+            },
+        );
     }
+
+    Ok(Redirect::to(&config.authorize_url(&csrf_state)))
+}
+
+/// Finish an OAuth login: link (or create) the local user, then hand the
+/// same user_id/username/session token a password login returns back to the frontend
+#[utoipa::path(get, path = "/api/auth/oauth/{provider}/callback", tag = "auth",
+    params(("provider" = String, Path, description = "`google` or `github`"), OAuthCallbackQuery),
+    responses(
+        (status = 303, description = "Redirect to the frontend with the session token in the URL fragment"),
+        (status = 400, description = "Provider error or unknown state", body = ErrorBody),
+    ))]
+pub async fn oauth_callback(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthCallbackQuery>,
+) -> ApiResult<Redirect> {
+    let config = oauth_config(&provider)?;
+    let bad_request = |msg: String| ApiError::bad_request(msg).with_code("oauth_failed");
+
+    if let Some(error) = query.error {
+        return Err(bad_request(format!("Provider denied login: {}", error)));
+    }
+    let code = query.code.ok_or_else(|| bad_request("Missing code parameter".to_string()))?;
+    let csrf_state = query.state.ok_or_else(|| bad_request("Missing state parameter".to_string()))?;
+
+    // Validate and consume the CSRF state
+    let pending = state.pending_oauth.lock().await.remove(&csrf_state);
+    match pending {
+        Some(p) if p.provider == config.provider.name() && p.created_at.elapsed() < OAUTH_STATE_TTL => {}
+        _ => return Err(bad_request("Invalid or expired OAuth state".to_string())),
+    }
+
+    let internal_error = |e: AuthError| ApiError::internal(format!("OAuth login failed: {}", e));
+
+    let identity = oauth_service::exchange_code(&config, &code)
+        .await
+        .map_err(internal_error)?;
+
+    let provider_name = config.provider.name();
+    let (user_id, username) =
+        match queries::find_oauth_user(state.db.pool(), provider_name, &identity.subject)
+            .await
+            .map_err(internal_error)?
+        {
+            Some(existing) => existing,
+            None => {
+                let user_id = auth_service::generate_user_id();
+                let username = queries::create_oauth_user(
+                    state.db.pool(),
+                    &user_id,
+                    &identity.preferred_username,
+                    provider_name,
+                    &identity.subject,
+                    state.config.accounts.starting_balance,
+                )
+                .await
+                .map_err(internal_error)?;
+
+                state
+                    .users
+                    .write()
+                    .await
+                    .insert(user_id.clone(), UserData::new(username.clone(), state.config.accounts.starting_balance));
+
+                tracing::info!("Created user {} via {} OAuth", username, provider_name);
+                (user_id, username)
+            }
+        };
+
+    audit_service::record(
+        state.db.pool(),
+        Some(&user_id),
+        AuditAction::OAuthLogin,
+        format!("provider={}", provider_name),
+    );
+
+    let success_redirect =
+        std::env::var("OAUTH_SUCCESS_REDIRECT").unwrap_or_else(|_| "/".to_string());
+    let session = start_session(&state, &user_id, &headers, addr).await?;
+    // The fragment never reaches a server, so the token stays out of access logs and Referer headers
+    let params = oauth_service::encode_query(&[
+        ("user_id", &user_id),
+        ("username", &username),
+        ("session_token", &session.token),
+    ]);
+
+    Ok(Redirect::to(&format!("{}#{}", success_redirect, params)))
 }
 
 #[derive(Serialize)]
+#[allow(dead_code)]
 pub struct UserInfoResponse {
     pub user_id: UserId,
     pub username: String,
-    pub cash_balance: f64,
+    pub usd_balance: f64,
 }
 
+#[allow(dead_code)]
 pub async fn get_me(
     State(state): State<AppState>,
     user_id: String,
-) -> Result<Json<UserInfoResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> ApiResult<Json<UserInfoResponse>> {
     match state.get_user(&user_id).await {
         Some(user) => Ok(Json(UserInfoResponse {
             user_id,
+            usd_balance: user.get_balance("USD"),
             username: user.username,
-            cash_balance: user.cash_balance,
         })),
-        None => Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "User not found".to_string(),
-            }),
-        )),
+        None => Err(ApiError::not_found("User not found")),
     }
 }
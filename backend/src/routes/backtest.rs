@@ -0,0 +1,454 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::UserId;
+use crate::bots::BotConfigError;
+use crate::services::backtest_service::{self, BacktestParams, BacktestRun, BacktestRunSummary};
+use crate::services::genetic_service::{self, Evolution, Gene, GeneticResult};
+use crate::services::optimizer_service::{self, OptimizationResult, RankBy};
+use crate::services::replay_service::{self, ReplayRun};
+use crate::routes::bot::{strategy_config_error, StrategyQuery};
+use crate::services::risk_service::{self, RiskScore};
+use crate::services::strategy_service::StrategyConfig;
+use crate::services::walk_forward_service::{self, WalkForwardResult, MAX_WALK_FORWARD_BACKTESTS};
+use crate::state::AppState;
+
+const DEFAULT_INITIAL_BALANCE: f64 = 10_000.0;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BacktestQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BacktestRequest {
+    pub strategy: String, // Bot strategy key, e.g. "naive_momentum"
+    pub asset: String,    // Base asset
+    #[serde(default)]
+    pub quote_asset: Option<String>, // Defaults to "USD"
+    pub start: DateTime<Utc>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>, // Defaults to now
+    #[serde(default)]
+    pub initial_balance: Option<f64>, // In quote asset terms; defaults to 10,000
+    pub stoploss_amount: f64,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Value, // Strategy settings, e.g. {"trend_ticks":4}; omitted ones keep their defaults
+}
+
+impl BacktestRequest {
+    fn into_params(self) -> BacktestParams {
+        BacktestParams {
+            strategy: self.strategy,
+            base_asset: self.asset.trim().to_uppercase(),
+            quote_asset: self.quote_asset.map(|q| q.trim().to_uppercase()).unwrap_or_else(|| "USD".to_string()),
+            start: self.start,
+            end: self.end.unwrap_or_else(Utc::now),
+            initial_balance: self.initial_balance.unwrap_or(DEFAULT_INITIAL_BALANCE),
+            stoploss_amount: self.stoploss_amount,
+            parameters: if self.parameters.is_null() { serde_json::json!({}) } else { self.parameters },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StrategyBacktestRequest {
+    pub config: StrategyConfig, // Strategy, pair, parameters and stoploss
+    pub start: DateTime<Utc>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>, // Defaults to now
+    #[serde(default)]
+    pub initial_balance: Option<f64>, // In quote asset terms; defaults to 10,000
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OptimizeRequest {
+    #[serde(flatten)]
+    pub backtest: BacktestRequest, // `parameters` holds settings shared by every combination
+    #[schema(value_type = Object)]
+    pub grid: BTreeMap<String, Vec<serde_json::Value>>, // Values to try per parameter, e.g. {"trend_ticks":[2,3,4]}
+    #[serde(default)]
+    pub rank_by: RankBy, // Defaults to total_return
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GeneticOptimizeRequest {
+    #[serde(flatten)]
+    pub backtest: BacktestRequest, // `parameters` holds settings the genes leave alone
+    #[schema(value_type = Object)]
+    pub genes: BTreeMap<String, Gene>, // Range per parameter, e.g. {"trend_ticks":{"min":2,"max":10,"integer":true}}
+    #[serde(default)]
+    pub evolution: Evolution,
+    #[serde(default)]
+    pub rank_by: RankBy, // The fitness; defaults to total_return
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WalkForwardRequest {
+    #[serde(flatten)]
+    pub optimize: OptimizeRequest,
+    pub in_sample_days: u32,     // Length of each window parameters are optimized over
+    pub out_of_sample_days: u32, // Length of the window after it the winner is tested on, and of each step
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReplayRequest {
+    #[serde(flatten)]
+    pub backtest: BacktestRequest,
+    pub speed: u32, // Multiple of real time, 10 to 1000
+}
+
+pub fn bot_config_error(e: BotConfigError) -> ApiError {
+    let code = match e {
+        BotConfigError::UnknownStrategy(_) => "unknown_strategy",
+        BotConfigError::InvalidParameters(_) => "invalid_parameters",
+    };
+    ApiError::bad_request(e.to_string()).with_code(code)
+}
+
+/// Check the range reaches no further back than stored prices, and the balance and stoploss
+async fn validate_params(state: &AppState, params: &BacktestParams) -> ApiResult<()> {
+    let history_start = queries::earliest_price_candle(state.db.pool(), &params.base_asset).await?;
+    backtest_service::validate(params, Utc::now(), history_start)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_backtest"))
+}
+
+/// Start a backtest: replay a bot strategy over stored prices in the background
+/// Ranges older than the in-memory prices (30 days) use history imported by an admin
+/// Poll `GET /api/backtest/{id}` until `status` is no longer `running`
+#[utoipa::path(post, path = "/api/backtest", tag = "bots", params(BacktestQuery), request_body = BacktestRequest,
+    responses(
+        (status = 200, description = "The started run", body = BacktestRun),
+        (status = 400, description = "Unknown strategy, or invalid parameters, range, balance or stoploss", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn start_backtest(
+    State(state): State<AppState>,
+    Query(query): Query<BacktestQuery>,
+    Json(req): Json<BacktestRequest>,
+) -> ApiResult<Json<BacktestRun>> {
+    let params = req.into_params();
+    validate_params(&state, &params).await?;
+
+    let bot = crate::bots::create_bot(&params.strategy, params.stoploss_amount, &params.parameters)
+        .map_err(bot_config_error)?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    Ok(Json(backtest_service::start(&state, &query.user_id, params, bot).await))
+}
+
+/// Grid search: backtest every combination of the grid's parameter values over the same prices
+/// Runs in parallel and answers once all are done, ranked best first, with a heatmap per parameter pair
+#[utoipa::path(post, path = "/api/backtest/optimize", tag = "bots", params(BacktestQuery), request_body = OptimizeRequest,
+    responses(
+        (status = 200, description = "Every combination's metrics, best first", body = OptimizationResult),
+        (status = 400, description = "Invalid grid (or over 200 combinations), strategy, parameters or range, or no prices in the range", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn optimize_backtest(
+    State(state): State<AppState>,
+    Query(query): Query<BacktestQuery>,
+    Json(req): Json<OptimizeRequest>,
+) -> ApiResult<Json<OptimizationResult>> {
+    let params = req.backtest.into_params();
+    validate_params(&state, &params).await?;
+
+    let combinations = optimizer_service::combinations(&params.parameters, &req.grid)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_grid"))?;
+    let candidates = optimizer_service::build_bots(&params.strategy, params.stoploss_amount, combinations)
+        .map_err(bot_config_error)?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let series = backtest_service::pair_series(&state, &params).await;
+    if series.is_empty() {
+        return Err(ApiError::bad_request(format!(
+            "No {}/{} prices between {} and {}",
+            params.base_asset, params.quote_asset, params.start, params.end
+        ))
+        .with_code("no_prices"));
+    }
+
+    optimizer_service::optimize(params, series, &req.grid, candidates, req.rank_by)
+        .await
+        .map(Json)
+        .map_err(ApiError::internal)
+}
+
+/// Genetic optimizer: evolve parameters within the genes' ranges, backtesting each generation over the same prices
+/// The fittest parameter sets by `rank_by` survive and breed the next generation; answers once the last is scored
+#[utoipa::path(post, path = "/api/optimize", tag = "bots", params(BacktestQuery), request_body = GeneticOptimizeRequest,
+    responses(
+        (status = 200, description = "The fittest parameter set, the best found, and each generation's scores", body = GeneticResult),
+        (status = 400, description = "Invalid genes or evolution settings (or over 500 backtests), strategy, parameters or range, or no prices in the range", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn genetic_optimize(
+    State(state): State<AppState>,
+    Query(query): Query<BacktestQuery>,
+    Json(req): Json<GeneticOptimizeRequest>,
+) -> ApiResult<Json<GeneticResult>> {
+    let params = req.backtest.into_params();
+    validate_params(&state, &params).await?;
+
+    genetic_service::validate(&req.genes, &req.evolution)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_genes"))?;
+    // Each end of every range must make a bot, so unknown parameters and out-of-range values fail here
+    optimizer_service::build_bots(&params.strategy, params.stoploss_amount, genetic_service::bounds(&params.parameters, &req.genes))
+        .map_err(bot_config_error)?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let series = backtest_service::pair_series(&state, &params).await;
+    if series.is_empty() {
+        return Err(ApiError::bad_request(format!(
+            "No {}/{} prices between {} and {}",
+            params.base_asset, params.quote_asset, params.start, params.end
+        ))
+        .with_code("no_prices"));
+    }
+
+    genetic_service::evolve(params, series, &req.genes, req.evolution, req.rank_by)
+        .await
+        .map(Json)
+        .map_err(ApiError::internal)
+}
+
+/// Walk-forward analysis: optimize over rolling in-sample windows, and test each winner on the window after it
+/// The out-of-sample results, chained together, are what to expect from re-optimizing the strategy as you go
+#[utoipa::path(post, path = "/api/backtest/walk-forward", tag = "bots", params(BacktestQuery), request_body = WalkForwardRequest,
+    responses(
+        (status = 200, description = "Each window's chosen parameters and metrics, and the out-of-sample aggregate", body = WalkForwardResult),
+        (status = 400, description = "Invalid windows (or over 20 of them, or over 2000 backtests), grid, strategy, parameters or range, or no prices in the range", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn walk_forward(
+    State(state): State<AppState>,
+    Query(query): Query<BacktestQuery>,
+    Json(req): Json<WalkForwardRequest>,
+) -> ApiResult<Json<WalkForwardResult>> {
+    let invalid = |message: String| ApiError::bad_request(message).with_code("invalid_walk_forward");
+    let grid = req.optimize.grid;
+    let rank_by = req.optimize.rank_by;
+    let params = req.optimize.backtest.into_params();
+    validate_params(&state, &params).await?;
+
+    let bounds = walk_forward_service::windows(
+        params.start,
+        params.end,
+        chrono::Duration::days(i64::from(req.in_sample_days)),
+        chrono::Duration::days(i64::from(req.out_of_sample_days)),
+    )
+    .map_err(invalid)?;
+    let combinations = optimizer_service::combinations(&params.parameters, &grid)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_grid"))?;
+    if combinations.len() * bounds.len() > MAX_WALK_FORWARD_BACKTESTS {
+        return Err(invalid(format!(
+            "{} combinations over {} windows is {} backtests; the limit is {}",
+            combinations.len(),
+            bounds.len(),
+            combinations.len() * bounds.len(),
+            MAX_WALK_FORWARD_BACKTESTS
+        )));
+    }
+    optimizer_service::build_bots(&params.strategy, params.stoploss_amount, combinations).map_err(bot_config_error)?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let series = backtest_service::pair_series(&state, &params).await;
+    if series.is_empty() {
+        return Err(ApiError::bad_request(format!(
+            "No {}/{} prices between {} and {}",
+            params.base_asset, params.quote_asset, params.start, params.end
+        ))
+        .with_code("no_prices"));
+    }
+
+    walk_forward_service::walk_forward(params, series, &grid, bounds, rank_by)
+        .await
+        .map(Json)
+        .map_err(ApiError::internal)
+}
+
+/// The user's recent runs, newest first, without their equity curves and trades
+#[utoipa::path(get, path = "/api/backtest", tag = "bots", params(BacktestQuery),
+    responses((status = 200, description = "Runs kept in memory on this instance", body = Vec<BacktestRunSummary>)))]
+pub async fn list_backtests(
+    State(state): State<AppState>,
+    Query(query): Query<BacktestQuery>,
+) -> ApiResult<Json<Vec<BacktestRunSummary>>> {
+    Ok(Json(backtest_service::list_runs(&state, &query.user_id).await))
+}
+
+/// A backtest run; `result` holds the equity curve, metrics and trades once completed
+/// Runs are kept in memory on the instance that ran them, for an hour after they finish
+#[utoipa::path(get, path = "/api/backtest/{backtest_id}", tag = "bots",
+    params(("backtest_id" = String, Path, description = "Run to fetch"), BacktestQuery),
+    responses(
+        (status = 200, description = "The run", body = BacktestRun),
+        (status = 404, description = "No such run", body = ErrorBody),
+    ))]
+pub async fn get_backtest(
+    State(state): State<AppState>,
+    Path(backtest_id): Path<String>,
+    Query(query): Query<BacktestQuery>,
+) -> ApiResult<Json<BacktestRun>> {
+    backtest_service::get_run(&state, &query.user_id, &backtest_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("Backtest not found"))
+}
+
+/// Risk grade of a completed backtest: turnover, position concentration, drawdown and leverage, each graded A to F
+#[utoipa::path(get, path = "/api/backtest/{backtest_id}/risk", tag = "bots",
+    params(("backtest_id" = String, Path, description = "Run to score"), BacktestQuery),
+    responses(
+        (status = 200, description = "The run's risk grade with its components", body = RiskScore),
+        (status = 404, description = "No such run", body = ErrorBody),
+        (status = 409, description = "The run has not completed", body = ErrorBody),
+    ))]
+pub async fn get_backtest_risk(
+    State(state): State<AppState>,
+    Path(backtest_id): Path<String>,
+    Query(query): Query<BacktestQuery>,
+) -> ApiResult<Json<RiskScore>> {
+    let run = backtest_service::get_run(&state, &query.user_id, &backtest_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("Backtest not found"))?;
+    let result = run
+        .result
+        .ok_or_else(|| ApiError::conflict("Backtest has not completed").with_code("backtest_not_completed"))?;
+    Ok(Json(risk_service::score_backtest(&result, run.params.stoploss_amount)))
+}
+
+/// Export the configuration a backtest ran as a portable strategy configuration, to start a bot with it or share it
+#[utoipa::path(get, path = "/api/backtest/{backtest_id}/strategy", tag = "bots",
+    params(("backtest_id" = String, Path, description = "Run to export"), BacktestQuery),
+    responses(
+        (status = 200, description = "The run's configuration", body = StrategyConfig),
+        (status = 404, description = "No such run", body = ErrorBody),
+    ))]
+pub async fn export_strategy(
+    State(state): State<AppState>,
+    Path(backtest_id): Path<String>,
+    Query(query): Query<BacktestQuery>,
+) -> ApiResult<Json<StrategyConfig>> {
+    let run = backtest_service::get_run(&state, &query.user_id, &backtest_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("Backtest not found"))?;
+    StrategyConfig::from_backtest(&run.params).map(Json).map_err(strategy_config_error)
+}
+
+/// Backtest a strategy configuration over a range, as `POST /api/backtest` would with its settings
+#[utoipa::path(post, path = "/api/backtest/strategy", tag = "bots", params(StrategyQuery), request_body = StrategyBacktestRequest,
+    responses(
+        (status = 200, description = "The started run", body = BacktestRun),
+        (status = 400, description = "Invalid configuration, range or balance", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn backtest_strategy(
+    State(state): State<AppState>,
+    Query(query): Query<StrategyQuery>,
+    Json(req): Json<StrategyBacktestRequest>,
+) -> ApiResult<Json<BacktestRun>> {
+    let config = req.config.validate().map_err(strategy_config_error)?;
+    let backtest = BacktestRequest {
+        strategy: config.strategy,
+        asset: config.base_asset,
+        quote_asset: Some(config.quote_asset),
+        start: req.start,
+        end: req.end,
+        initial_balance: req.initial_balance,
+        stoploss_amount: config.risk.stoploss_amount,
+        parameters: config.parameters,
+    };
+    start_backtest(State(state), Query(BacktestQuery { user_id: query.user_id }), Json(backtest)).await
+}
+
+/// Live replay: run a bot through the live bot pipeline (ticks, validation, execution, stoploss)
+/// against stored prices played back at 10 to 1000 times real time, in a sandbox apart from real balances
+/// Poll `GET /api/backtest/replay/{replay_id}` for progress; results are filled in once it finishes
+#[utoipa::path(post, path = "/api/backtest/replay", tag = "bots", params(BacktestQuery), request_body = ReplayRequest,
+    responses(
+        (status = 200, description = "The started replay", body = ReplayRun),
+        (status = 400, description = "Unknown strategy, or invalid parameters, range, balance, stoploss or speed", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "A replay is already running for this user, or too many are running", body = ErrorBody),
+    ))]
+pub async fn start_replay(
+    State(state): State<AppState>,
+    Query(query): Query<BacktestQuery>,
+    Json(req): Json<ReplayRequest>,
+) -> ApiResult<Json<ReplayRun>> {
+    let params = req.backtest.into_params();
+    validate_params(&state, &params).await?;
+    replay_service::validate_speed(&params, req.speed)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_replay"))?;
+
+    let bot = crate::bots::create_bot(&params.strategy, params.stoploss_amount, &params.parameters)
+        .map_err(bot_config_error)?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    replay_service::start(&state, &query.user_id, params, req.speed, bot)
+        .await
+        .map(Json)
+        .map_err(|message| ApiError::conflict(message).with_code("replay_limit"))
+}
+
+/// A live replay's progress, and its metrics and trades once finished
+#[utoipa::path(get, path = "/api/backtest/replay/{replay_id}", tag = "bots",
+    params(("replay_id" = String, Path, description = "Replay to fetch"), BacktestQuery),
+    responses(
+        (status = 200, description = "The replay", body = ReplayRun),
+        (status = 404, description = "No such replay", body = ErrorBody),
+    ))]
+pub async fn get_replay(
+    State(state): State<AppState>,
+    Path(replay_id): Path<String>,
+    Query(query): Query<BacktestQuery>,
+) -> ApiResult<Json<ReplayRun>> {
+    replay_service::get_run(&state, &query.user_id, &replay_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("Replay not found"))
+}
+
+/// Cancel a running live replay; its bot is stopped and the results so far are reported
+#[utoipa::path(delete, path = "/api/backtest/replay/{replay_id}", tag = "bots",
+    params(("replay_id" = String, Path, description = "Replay to cancel"), BacktestQuery),
+    responses(
+        (status = 200, description = "The replay, now cancelled unless it had already finished", body = ReplayRun),
+        (status = 404, description = "No such replay", body = ErrorBody),
+    ))]
+pub async fn cancel_replay(
+    State(state): State<AppState>,
+    Path(replay_id): Path<String>,
+    Query(query): Query<BacktestQuery>,
+) -> ApiResult<Json<ReplayRun>> {
+    replay_service::cancel(&state, &query.user_id, &replay_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("Replay not found"))
+}
@@ -1,179 +1,700 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
     Json,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-use crate::bots::naive_momentum::NaiveMomentumBot;
-use crate::models::UserId;
-use crate::services::bot_service::{calculate_portfolio_value_usd, spawn_bot_task};
-use crate::state::{AppState, BotInstance};
+use crate::bots::risk_managed::TradeLimits;
+use crate::bots::schedule::BotSchedule;
+use crate::bots::BotInfo;
+use crate::db::queries::{self, BotConfigRow, BotDecisionEntry, BotRun};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{BotId, UserId};
+use crate::pagination::{Page, PageQuery, SortOrder};
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::bot_service::{self, calculate_portfolio_value_usd, RunEnd, RunningBot, MAX_BOTS_PER_USER};
+use crate::services::cluster_service;
+use crate::services::comparison_service::{self, Comparison, EquityPoint, ShadowAccount};
+use crate::services::portfolio_service::{self, HistoryRange};
+use crate::services::risk_service::{self, RiskScore};
+use crate::services::strategy_service::{ConfigError, StrategyConfig};
+use crate::state::{AppState, NotificationKind};
+use std::collections::VecDeque;
 
-#[derive(Debug, Deserialize)]
+const DEFAULT_DECISION_LIMIT: i64 = 100;
+const MAX_DECISION_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct StartBotRequest {
     pub user_id: UserId,
     pub bot_name: String,
     pub base_asset: String,
     pub quote_asset: String,
     pub stoploss_amount: f64,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Value, // Strategy settings, e.g. {"trend_ticks":4}; omitted ones keep their defaults
+    #[serde(default)]
+    pub dry_run: bool, // Run on live prices but only log decisions (GET /api/bot/decisions), never trading
+    #[serde(default)]
+    pub trade_limits: TradeLimits, // Caps on position, trade count and trade size; none by default
+    #[serde(default)]
+    pub max_base_position: Option<f64>, // Most of the base asset the bot may hold; buys past it are cut down or skipped
+    #[serde(default)]
+    pub schedule: Option<BotSchedule>, // Trade only in this window, e.g. weekdays 13:00-21:00 UTC; always if omitted
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StartBotResponse {
     pub success: bool,
     pub message: String,
+    pub bot_instance_id: BotId, // The bot started (or stopped); pass it to the other bot endpoints
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BotStatusResponse {
     pub is_active: bool,
+    pub bot_instance_id: Option<BotId>,
     pub bot_name: Option<String>,
+    pub strategy: Option<String>,
     pub trading_pair: Option<String>,
     pub stoploss_amount: Option<f64>,
     pub initial_portfolio_value: Option<f64>,
+    pub dry_run: bool, // Logging decisions without trading
+    pub max_base_position: Option<f64>,
+    pub schedule: Option<BotSchedule>,
+    pub paused: Option<String>, // Why the bot isn't trading right now, e.g. "paused by schedule"
+    pub context_failures: u32, // Ticks in a row it couldn't gather prices and balances for; it stops at the configured maximum
+    pub comparison_id: Option<String>, // Set for bots of an A/B comparison (GET /api/bot/compare)
 }
 
-/// Start a bot for a user
+impl BotStatusResponse {
+    fn of(bot: Option<RunningBot>) -> Self {
+        match bot {
+            Some(bot) => Self {
+                is_active: true,
+                bot_instance_id: Some(bot.bot_id),
+                bot_name: Some(bot.bot_name),
+                strategy: Some(bot.strategy),
+                trading_pair: Some(format!("{}/{}", bot.trading_pair.0, bot.trading_pair.1)),
+                stoploss_amount: Some(bot.stoploss_amount),
+                initial_portfolio_value: Some(bot.initial_portfolio_value_usd),
+                dry_run: bot.dry_run,
+                max_base_position: bot.max_base_position,
+                schedule: bot.schedule,
+                paused: bot.paused,
+                context_failures: bot.context_failures,
+                comparison_id: bot.comparison_id,
+            },
+            None => Self {
+                is_active: false,
+                bot_instance_id: None,
+                bot_name: None,
+                strategy: None,
+                trading_pair: None,
+                stoploss_amount: None,
+                initial_portfolio_value: None,
+                dry_run: false,
+                max_base_position: None,
+                schedule: None,
+                paused: None,
+                context_failures: 0,
+                comparison_id: None,
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BotQuery {
+    pub user_id: UserId,
+    pub bot_instance_id: Option<BotId>, // May be omitted while the user runs a single bot
+}
+
+/// The bot a request is about: the one `bot_instance_id` names, or the user's only bot when it's omitted
+async fn select_bot(state: &AppState, user_id: &UserId, bot_instance_id: Option<&BotId>) -> ApiResult<Option<RunningBot>> {
+    let mut running = bot_service::running_bots(state, user_id).await?;
+    match bot_instance_id {
+        Some(bot_id) => Ok(running.into_iter().find(|bot| &bot.bot_id == bot_id)),
+        None if running.len() > 1 => Err(ApiError::bad_request(
+            "Several bots are running; choose one with bot_instance_id",
+        )
+        .with_code("bot_instance_id_required")),
+        None => Ok(running.pop()),
+    }
+}
+
+/// Strategies a bot can be started with, and the parameters each takes
+#[utoipa::path(get, path = "/api/bots/available", tag = "bots",
+    responses((status = 200, description = "Registered strategies with their default parameters", body = Vec<BotInfo>)))]
+pub async fn available_bots() -> Json<Vec<BotInfo>> {
+    Json(crate::bots::registry().list())
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserBotsQuery {
+    pub user_id: UserId,
+}
+
+/// Every bot the user is running, by trading pair
+#[utoipa::path(get, path = "/api/bots", tag = "bots", params(UserBotsQuery),
+    responses((status = 200, description = "The user's running bots", body = Vec<BotStatusResponse>)))]
+pub async fn list_bots(
+    State(state): State<AppState>,
+    Query(query): Query<UserBotsQuery>,
+) -> ApiResult<Json<Vec<BotStatusResponse>>> {
+    let running = bot_service::running_bots(&state, &query.user_id).await?;
+    Ok(Json(running.into_iter().map(|bot| BotStatusResponse::of(Some(bot))).collect()))
+}
+
+/// Start a bot for a user, alongside any they already run
+#[utoipa::path(post, path = "/api/bot/start", tag = "bots", request_body = StartBotRequest,
+    responses(
+        (status = 200, description = "Bot started", body = StartBotResponse),
+        (status = 400, description = "Invalid configuration", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "The user already runs as many bots as allowed", body = ErrorBody),
+    ))]
 pub async fn start_bot(
     State(state): State<AppState>,
     Json(req): Json<StartBotRequest>,
-) -> Result<Json<StartBotResponse>, (StatusCode, String)> {
+) -> ApiResult<Json<StartBotResponse>> {
     // Validate stoploss amount
     if req.stoploss_amount <= 0.0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            "Stoploss amount must be positive".to_string(),
-        ));
-    }
-
-    // Check if user already has an active bot
-    {
-        let state_lock = state.inner.read().await;
-        if state_lock.active_bots.contains_key(&req.user_id) {
-            return Err((
-                StatusCode::CONFLICT,
-                "User already has an active bot running".to_string(),
-            ));
-        }
+        return Err(ApiError::bad_request("Stoploss amount must be positive"));
+    }
+    req.trade_limits
+        .validate()
+        .map_err(|e| ApiError::bad_request(e).with_code("invalid_trade_limits"))?;
+    check_max_base_position(req.max_base_position)?;
+    if let Some(schedule) = &req.schedule {
+        schedule.validate().map_err(|e| ApiError::bad_request(e).with_code("invalid_schedule"))?;
+    }
+
+    // Check the user's bot limit
+    let too_many_bots = || {
+        ApiError::conflict(format!("A user can run at most {} bots at once", MAX_BOTS_PER_USER))
+            .with_code("too_many_bots")
+    };
+    if bot_service::running_bots(&state, &req.user_id).await?.len() >= MAX_BOTS_PER_USER {
+        return Err(too_many_bots());
     }
 
     // Verify user exists
     if state.get_user(&req.user_id).await.is_none() {
-        return Err((StatusCode::NOT_FOUND, "User not found".to_string()));
+        return Err(ApiError::not_found("User not found"));
     }
 
     // Calculate initial portfolio value for stoploss tracking
     let initial_portfolio_value = calculate_portfolio_value_usd(&state, &req.user_id)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
-
-    // Create bot instance based on bot_name
-    let bot: Box<dyn crate::bots::TradingBot> = match req.bot_name.as_str() {
-        "naive_momentum" => Box::new(NaiveMomentumBot::new(req.stoploss_amount)),
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                format!("Unknown bot: {}", req.bot_name),
-            ))
-        }
+        .map_err(ApiError::internal)?;
+
+    // Build the bot through the strategy registry
+    let parameters = if req.parameters.is_null() { serde_json::json!({}) } else { req.parameters.clone() };
+    let bot = crate::bots::create_bot(&req.bot_name, req.stoploss_amount, &parameters)
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+    crate::bots::check_pair(bot.as_ref(), &req.base_asset).map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    let config = BotConfigRow {
+        bot_id: uuid::Uuid::new_v4().to_string(),
+        user_id: req.user_id.clone(),
+        strategy: req.bot_name.clone(),
+        base_asset: req.base_asset.clone(),
+        quote_asset: req.quote_asset.clone(),
+        parameters,
+        stoploss_amount: req.stoploss_amount,
+        initial_portfolio_value_usd: initial_portfolio_value,
+        dry_run: req.dry_run,
+        trade_limits: req.trade_limits,
+        max_base_position: req.max_base_position,
+        schedule: req.schedule.clone(),
+        paused: false,
     };
 
-    let bot_display_name = bot.name().to_string();
-
-    // Spawn bot task
-    let task_handle = spawn_bot_task(
-        state.clone(),
-        req.user_id.clone(),
-        bot,
-        req.base_asset.clone(),
-        req.quote_asset.clone(),
-        req.stoploss_amount,
-        initial_portfolio_value,
-    );
+    // With shared state, claim the bot so no other instance adopts it
+    if let Some(cluster) = &state.cluster {
+        if req.user_id != "demo_user"
+            && !cluster.try_acquire(&state, &cluster_service::bot_lease(&config.bot_id)).await
+        {
+            return Err(ApiError::internal("Could not claim the new bot's lease"));
+        }
+    }
 
-    // Store bot instance in state
-    {
-        let mut state_lock = state.inner.write().await;
-        state_lock.active_bots.insert(
-            req.user_id.clone(),
-            BotInstance {
-                bot_name: bot_display_name.clone(),
-                trading_pair: (req.base_asset.clone(), req.quote_asset.clone()),
-                stoploss_amount: req.stoploss_amount,
-                initial_portfolio_value_usd: initial_portfolio_value,
-                task_handle,
-            },
-        );
+    // Spawn bot task and store bot instance in state
+    let bot_display_name = bot_service::launch_bot(&state, &config, bot, Default::default())
+        .await
+        .ok_or_else(too_many_bots)?;
+    bot_service::notify_started(&state, &config, &bot_display_name);
+
+    // Persist so the bot is respawned after a restart (demo user is memory-only)
+    if req.user_id != "demo_user" {
+        if let Err(e) = queries::save_bot_config(state.db.pool(), &config).await {
+            tracing::error!("Failed to persist bot config for user {}: {}", req.user_id, e);
+        }
     }
 
+    let mode = if req.dry_run { " as a dry run" } else { "" };
+    audit_service::record(
+        state.db.pool(),
+        Some(&req.user_id),
+        AuditAction::BotStarted,
+        format!(
+            "{} on {}/{} (stoploss {:.2}){}",
+            bot_display_name, req.base_asset, req.quote_asset, req.stoploss_amount, mode
+        ),
+    );
+
     Ok(Json(StartBotResponse {
         success: true,
         message: format!(
-            "Bot '{}' started on {}/{} with ${:.2} stoploss",
-            bot_display_name, req.base_asset, req.quote_asset, req.stoploss_amount
+            "Bot '{}' started on {}/{} with ${:.2} stoploss{}",
+            bot_display_name, req.base_asset, req.quote_asset, req.stoploss_amount, mode
         ),
+        bot_instance_id: config.bot_id,
     }))
 }
 
-/// Stop a bot for a user
+/// 400 unless a position cap is unset or positive
+fn check_max_base_position(max_base_position: Option<f64>) -> ApiResult<()> {
+    if max_base_position.is_some_and(|cap| !cap.is_finite() || cap <= 0.0) {
+        return Err(ApiError::bad_request("max_base_position must be positive").with_code("invalid_max_base_position"));
+    }
+    Ok(())
+}
+
+/// Stop one of a user's bots
+#[utoipa::path(post, path = "/api/bot/stop", tag = "bots", params(BotQuery),
+    responses(
+        (status = 200, description = "Bot stopped", body = StartBotResponse),
+        (status = 400, description = "Several bots are running and none was chosen", body = ErrorBody),
+        (status = 404, description = "No such bot running for this user", body = ErrorBody),
+    ))]
 pub async fn stop_bot(
     State(state): State<AppState>,
-    Query(params): Query<std::collections::HashMap<String, String>>,
-) -> Result<Json<StartBotResponse>, (StatusCode, String)> {
-    let user_id = params
-        .get("user_id")
-        .ok_or((StatusCode::BAD_REQUEST, "Missing user_id parameter".to_string()))?;
-
-    // Remove bot from active_bots (this signals the task to stop)
-    let bot_instance = {
-        let mut state_lock = state.inner.write().await;
-        state_lock.active_bots.remove(user_id)
+    Query(query): Query<BotQuery>,
+) -> ApiResult<Json<StartBotResponse>> {
+    let bot = select_bot(&state, &query.user_id, query.bot_instance_id.as_ref())
+        .await?
+        .ok_or_else(|| ApiError::not_found("No such bot running for this user"))?;
+
+    if state.bots.read().await.contains_key(&bot.bot_id) {
+        bot_service::stop_bot(&state, &bot.bot_id, "stopped by user").await;
+    } else {
+        // With shared state the bot may run on another instance; removing its
+        // config makes the owner stop it on its next lease renewal
+        bot_service::forget_bot_config(&state, &bot.bot_id);
+        bot_service::finish_run(&state, &query.user_id, &bot.bot_id, RunEnd::Stopped, "stopped by user");
+        audit_service::record(
+            state.db.pool(),
+            Some(&query.user_id),
+            AuditAction::BotStopped,
+            format!("{}: stopped by user", bot.bot_name),
+        );
+    }
+    state.notify(
+        &query.user_id,
+        NotificationKind::BotStopped { bot_name: bot.bot_name.clone(), reason: "stopped by user".to_string(), by_user: true },
+    );
+
+    Ok(Json(StartBotResponse {
+        success: true,
+        message: format!("Bot '{}' stopped", bot.bot_name),
+        bot_instance_id: bot.bot_id,
+    }))
+}
+
+/// Pause one of a user's bots: it keeps ticking, so its momentum history and cooldowns stay
+/// current, but its decisions aren't traded until it is resumed
+#[utoipa::path(post, path = "/api/bot/pause", tag = "bots", params(BotQuery),
+    responses(
+        (status = 200, description = "Bot paused, or already was", body = StartBotResponse),
+        (status = 400, description = "Several bots are running and none was chosen", body = ErrorBody),
+        (status = 404, description = "No such bot running for this user", body = ErrorBody),
+        (status = 409, description = "The bot runs on another server instance", body = ErrorBody),
+    ))]
+pub async fn pause_bot(
+    State(state): State<AppState>,
+    Query(query): Query<BotQuery>,
+) -> ApiResult<Json<StartBotResponse>> {
+    set_paused(&state, query, true).await
+}
+
+/// Resume a paused bot where it left off
+#[utoipa::path(post, path = "/api/bot/resume", tag = "bots", params(BotQuery),
+    responses(
+        (status = 200, description = "Bot resumed, or wasn't paused", body = StartBotResponse),
+        (status = 400, description = "Several bots are running and none was chosen", body = ErrorBody),
+        (status = 404, description = "No such bot running for this user", body = ErrorBody),
+        (status = 409, description = "The bot runs on another server instance", body = ErrorBody),
+    ))]
+pub async fn resume_bot(
+    State(state): State<AppState>,
+    Query(query): Query<BotQuery>,
+) -> ApiResult<Json<StartBotResponse>> {
+    set_paused(&state, query, false).await
+}
+
+async fn set_paused(state: &AppState, query: BotQuery, paused: bool) -> ApiResult<Json<StartBotResponse>> {
+    let bot = select_bot(state, &query.user_id, query.bot_instance_id.as_ref())
+        .await?
+        .ok_or_else(|| ApiError::not_found("No such bot running for this user"))?;
+
+    // Only the instance running a bot can pause it; with shared state that may be another one
+    let bot_name = bot_service::set_paused(state, &bot.bot_id, paused).await.ok_or_else(|| {
+        ApiError::conflict("This bot runs on another server instance; try again there").with_code("bot_not_local")
+    })?;
+
+    let action = if paused { "paused" } else { "resumed" };
+    Ok(Json(StartBotResponse {
+        success: true,
+        message: format!("Bot '{}' {}", bot_name, action),
+        bot_instance_id: bot.bot_id,
+    }))
+}
+
+/// Get the status of one of a user's bots
+#[utoipa::path(get, path = "/api/bot/status", tag = "bots", params(BotQuery),
+    responses(
+        (status = 200, description = "The bot, or is_active false if it isn't running", body = BotStatusResponse),
+        (status = 400, description = "Several bots are running and none was chosen", body = ErrorBody),
+    ))]
+pub async fn bot_status(
+    State(state): State<AppState>,
+    Query(query): Query<BotQuery>,
+) -> ApiResult<Json<BotStatusResponse>> {
+    let bot = select_bot(&state, &query.user_id, query.bot_instance_id.as_ref()).await?;
+    Ok(Json(BotStatusResponse::of(bot)))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BotRiskQuery {
+    pub user_id: UserId,
+    pub bot_instance_id: Option<BotId>, // May be omitted while the user runs a single bot
+    pub range: Option<String>, // "24h", "7d" (default), "30d", or "all", as for portfolio history
+}
+
+/// Risk grade of one of a user's running bots over a range of their portfolio history:
+/// turnover, position concentration, drawdown and leverage, each graded A to F
+#[utoipa::path(get, path = "/api/bot/risk", tag = "bots", params(BotRiskQuery),
+    responses(
+        (status = 200, description = "The bot's risk grade with its components", body = RiskScore),
+        (status = 400, description = "Unknown range, or several bots are running and none was chosen", body = ErrorBody),
+        (status = 404, description = "No such bot running for this user", body = ErrorBody),
+    ))]
+pub async fn bot_risk(
+    State(state): State<AppState>,
+    Query(query): Query<BotRiskQuery>,
+) -> ApiResult<Json<RiskScore>> {
+    let range = query.range.as_deref().unwrap_or("7d");
+    let range = HistoryRange::parse(range).ok_or_else(|| {
+        ApiError::bad_request(format!("Unknown range '{}' (use 24h, 7d, 30d, or all)", range))
+            .with_code("invalid_range")
+    })?;
+
+    let bot = select_bot(&state, &query.user_id, query.bot_instance_id.as_ref())
+        .await?
+        .ok_or_else(|| ApiError::not_found("No such bot running for this user"))?;
+    let user = state
+        .get_user(&query.user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    let (_, samples) = portfolio_service::equity(&state, &user, range).await;
+    Ok(Json(risk_service::score_bot(&samples, &user.trade_history, &bot.bot_id, bot.stoploss_amount)))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BotDecisionsQuery {
+    pub user_id: UserId,
+    pub bot_instance_id: Option<BotId>, // Omit for every bot the user has run, stopped ones included
+}
+
+/// What the user's bots decided each tick and what came of it, newest first unless `sort=asc`
+/// Kept for 30 days by default (`RETENTION_BOT_DECISION_DAYS`); guests' bots aren't logged
+#[utoipa::path(get, path = "/api/bot/decisions", tag = "bots", params(BotDecisionsQuery, PageQuery),
+    responses((status = 200, description = "A page of decisions; `next_cursor` is a decision id", body = Page<BotDecisionEntry>)))]
+pub async fn bot_decisions(
+    State(state): State<AppState>,
+    Query(query): Query<BotDecisionsQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<BotDecisionEntry>>> {
+    let page = page.resolve(DEFAULT_DECISION_LIMIT, MAX_DECISION_LIMIT, SortOrder::Desc);
+    let decisions = queries::get_bot_decisions(
+        state.db.pool(),
+        &query.user_id,
+        query.bot_instance_id.as_ref(),
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+    Ok(Json(page.finish(decisions, |decision| decision.id)))
+}
+
+/// Every run of the user's bots, from start to stop, with errors and how it ended; newest first unless `sort=asc`
+/// Runs survive restarts and are kept after the bot stops; guests' bots aren't recorded
+#[utoipa::path(get, path = "/api/bot/runs", tag = "bots", params(BotDecisionsQuery, PageQuery),
+    responses((status = 200, description = "A page of runs; `next_cursor` is a run id", body = Page<BotRun>)))]
+pub async fn bot_runs(
+    State(state): State<AppState>,
+    Query(query): Query<BotDecisionsQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<BotRun>>> {
+    let page = page.resolve(DEFAULT_DECISION_LIMIT, MAX_DECISION_LIMIT, SortOrder::Desc);
+    let runs = queries::get_bot_runs(
+        state.db.pool(),
+        Some(&query.user_id),
+        query.bot_instance_id.as_ref(),
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+    Ok(Json(page.finish(runs, |run| run.id)))
+}
+
+/// One side of an A/B comparison
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ComparedStrategy {
+    pub bot_name: String, // Strategy key, as for POST /api/bot/start
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Value,
+    #[serde(default)]
+    pub trade_limits: TradeLimits,
+    #[serde(default)]
+    pub max_base_position: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CompareBotsRequest {
+    pub user_id: UserId,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub stoploss_amount: f64, // Each bot's, against its own shadow account
+    pub a: ComparedStrategy,
+    pub b: ComparedStrategy,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CompareBotsResponse {
+    pub comparison_id: String,
+    pub message: String,
+    pub bot_instance_ids: Vec<BotId>, // A's, then B's; stop them with POST /api/bot/stop
+}
+
+/// Start two bots side by side on the same live prices, each trading a shadow copy of the user's
+/// balances in the pair instead of the real ones; replaces the user's previous comparison
+#[utoipa::path(post, path = "/api/bot/compare", tag = "bots", request_body = CompareBotsRequest,
+    responses(
+        (status = 200, description = "Both bots started", body = CompareBotsResponse),
+        (status = 400, description = "Invalid configuration", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "The user has no room for two more bots", body = ErrorBody),
+    ))]
+pub async fn start_comparison(
+    State(state): State<AppState>,
+    Json(req): Json<CompareBotsRequest>,
+) -> ApiResult<Json<CompareBotsResponse>> {
+    if req.stoploss_amount <= 0.0 {
+        return Err(ApiError::bad_request("Stoploss amount must be positive"));
+    }
+    let mut bots = Vec::new();
+    for side in [&req.a, &req.b] {
+        side.trade_limits
+            .validate()
+            .map_err(|e| ApiError::bad_request(e).with_code("invalid_trade_limits"))?;
+        check_max_base_position(side.max_base_position)?;
+        let parameters = if side.parameters.is_null() { serde_json::json!({}) } else { side.parameters.clone() };
+        let bot = crate::bots::create_bot(&side.bot_name, req.stoploss_amount, &parameters)
+            .map_err(|e| ApiError::bad_request(e.to_string()))?;
+        crate::bots::check_pair(bot.as_ref(), &req.base_asset).map_err(|e| ApiError::bad_request(e.to_string()))?;
+        bots.push((side, parameters, bot));
+    }
+    let user = state.get_user(&req.user_id).await.ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    comparison_service::end_user_comparison(&state, &req.user_id).await;
+    let too_many_bots = || {
+        ApiError::conflict(format!(
+            "A comparison runs two bots, and a user can run at most {} at once",
+            MAX_BOTS_PER_USER
+        ))
+        .with_code("too_many_bots")
     };
+    if bot_service::running_bots(&state, &req.user_id).await?.len() + 2 > MAX_BOTS_PER_USER {
+        return Err(too_many_bots());
+    }
 
-    match bot_instance {
-        Some(instance) => {
-            instance.task_handle.abort(); // Force abort the task
-            Ok(Json(StartBotResponse {
-                success: true,
-                message: format!("Bot '{}' stopped", instance.bot_name),
-            }))
+    // Both accounts start from the same balances, and so the same value
+    let base_balance = user.get_balance(&req.base_asset);
+    let quote_balance = user.get_balance(&req.quote_asset);
+    let starting_value =
+        comparison_service::value_usd(&state, [(&req.base_asset, base_balance), (&req.quote_asset, quote_balance)]).await;
+    let comparison_id = uuid::Uuid::new_v4().to_string();
+    let started_at = state.clock.now();
+
+    let mut bot_ids = Vec::new();
+    let mut names = Vec::new();
+    for (side, parameters, bot) in bots {
+        let config = BotConfigRow {
+            bot_id: uuid::Uuid::new_v4().to_string(),
+            user_id: req.user_id.clone(),
+            strategy: side.bot_name.clone(),
+            base_asset: req.base_asset.clone(),
+            quote_asset: req.quote_asset.clone(),
+            parameters,
+            stoploss_amount: req.stoploss_amount,
+            initial_portfolio_value_usd: starting_value,
+            dry_run: false,
+            trade_limits: side.trade_limits,
+            max_base_position: side.max_base_position,
+            schedule: None,
+            paused: false,
+        };
+        state.shadow_accounts.write().await.insert(
+            config.bot_id.clone(),
+            ShadowAccount {
+                user_id: req.user_id.clone(),
+                comparison_id: comparison_id.clone(),
+                bot_name: bot.name().to_string(),
+                strategy: side.bot_name.clone(),
+                base_asset: req.base_asset.clone(),
+                quote_asset: req.quote_asset.clone(),
+                base_balance,
+                quote_balance,
+                starting_value_usd: starting_value,
+                trades: 0,
+                equity: VecDeque::from([EquityPoint { timestamp: started_at, value_usd: starting_value }]),
+                started_at,
+                stopped_reason: None,
+            },
+        );
+        // Not persisted: shadow accounts live in memory, so the comparison ends with a restart
+        match bot_service::launch_bot(&state, &config, bot, Default::default()).await {
+            Some(name) => {
+                bot_service::notify_started(&state, &config, &name);
+                names.push(name);
+            }
+            None => {
+                comparison_service::end_user_comparison(&state, &req.user_id).await;
+                return Err(too_many_bots());
+            }
         }
-        None => Err((
-            StatusCode::NOT_FOUND,
-            "No active bot for this user".to_string(),
-        )),
+        bot_ids.push(config.bot_id);
     }
+
+    let message = format!("Comparing '{}' with '{}' on {}/{}", names[0], names[1], req.base_asset, req.quote_asset);
+    audit_service::record(
+        state.db.pool(),
+        Some(&req.user_id),
+        AuditAction::BotStarted,
+        format!("{} (stoploss {:.2} each, shadow balances)", message, req.stoploss_amount),
+    );
+
+    Ok(Json(CompareBotsResponse { comparison_id, message, bot_instance_ids: bot_ids }))
 }
 
-/// Get bot status for a user
-pub async fn bot_status(
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ComparisonQuery {
+    pub user_id: UserId,
+}
+
+/// The user's A/B comparison: both bots' shadow balances, returns and equity curves, sampled every tick
+/// Stopped bots stay in it until the next comparison starts
+#[utoipa::path(get, path = "/api/bot/compare", tag = "bots", params(ComparisonQuery),
+    responses(
+        (status = 200, description = "The comparison", body = Comparison),
+        (status = 404, description = "The user has no comparison", body = ErrorBody),
+    ))]
+pub async fn get_comparison(
     State(state): State<AppState>,
-    Query(params): Query<std::collections::HashMap<String, String>>,
-) -> Result<Json<BotStatusResponse>, (StatusCode, String)> {
-    let user_id = params
-        .get("user_id")
-        .ok_or((StatusCode::BAD_REQUEST, "Missing user_id parameter".to_string()))?;
-
-    let state_lock = state.inner.read().await;
-
-    match state_lock.active_bots.get(user_id) {
-        Some(instance) => Ok(Json(BotStatusResponse {
-            is_active: true,
-            bot_name: Some(instance.bot_name.clone()),
-            trading_pair: Some(format!(
-                "{}/{}",
-                instance.trading_pair.0, instance.trading_pair.1
-            )),
-            stoploss_amount: Some(instance.stoploss_amount),
-            initial_portfolio_value: Some(instance.initial_portfolio_value_usd),
-        })),
-        None => Ok(Json(BotStatusResponse {
-            is_active: false,
-            bot_name: None,
-            trading_pair: None,
-            stoploss_amount: None,
-            initial_portfolio_value: None,
-        })),
+    Query(query): Query<ComparisonQuery>,
+) -> ApiResult<Json<Comparison>> {
+    comparison_service::user_comparison(&state, &query.user_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("No comparison for this user"))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StrategyQuery {
+    pub user_id: UserId,
+}
+
+/// 400 for a strategy configuration that can't be loaded
+pub fn strategy_config_error(e: ConfigError) -> ApiError {
+    match e {
+        ConfigError::Format(message) => ApiError::bad_request(message).with_code("invalid_strategy_config"),
+        ConfigError::Bot(e) => crate::routes::backtest::bot_config_error(e),
+    }
+}
+
+/// Export a running bot's strategy, parameters and stoploss as a portable strategy configuration
+/// Load it with `POST /api/bot/strategy` or `POST /api/backtest/strategy`, on this account or another
+#[utoipa::path(get, path = "/api/bot/strategy", tag = "bots", params(BotQuery),
+    responses(
+        (status = 200, description = "The bot's configuration", body = StrategyConfig),
+        (status = 400, description = "Several bots are running and none was chosen", body = ErrorBody),
+        (status = 404, description = "No such bot running for this user", body = ErrorBody),
+    ))]
+pub async fn export_strategy(
+    State(state): State<AppState>,
+    Query(query): Query<BotQuery>,
+) -> ApiResult<Json<StrategyConfig>> {
+    let bot = select_bot(&state, &query.user_id, query.bot_instance_id.as_ref()).await?;
+    // Parameters are only kept in the persisted config, so the memory-only demo bot can't be exported
+    let config = match bot {
+        Some(bot) => queries::get_bot_config(state.db.pool(), &bot.bot_id).await?,
+        None => None,
     }
+    .ok_or_else(|| ApiError::not_found("No such bot running for this user"))?;
+    StrategyConfig::from_bot(&config).map(Json).map_err(strategy_config_error)
+}
+
+/// Check a strategy configuration without loading it
+/// Answers with it normalized: assets uppercased and every parameter filled in with its default
+#[utoipa::path(post, path = "/api/bot/strategy/validate", tag = "bots", request_body = StrategyConfig,
+    responses(
+        (status = 200, description = "The configuration can be loaded", body = StrategyConfig),
+        (status = 400, description = "Unknown format, version or strategy, or invalid parameters, assets or stoploss", body = ErrorBody),
+    ))]
+pub async fn validate_strategy(Json(config): Json<StrategyConfig>) -> ApiResult<Json<StrategyConfig>> {
+    config.validate().map(Json).map_err(strategy_config_error)
+}
+
+/// Start a bot from a strategy configuration, as `POST /api/bot/start` would with its settings
+#[utoipa::path(post, path = "/api/bot/strategy", tag = "bots", params(StrategyQuery), request_body = StrategyConfig,
+    responses(
+        (status = 200, description = "Bot started", body = StartBotResponse),
+        (status = 400, description = "Invalid configuration", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "The user already runs as many bots as allowed", body = ErrorBody),
+    ))]
+pub async fn import_strategy(
+    State(state): State<AppState>,
+    Query(query): Query<StrategyQuery>,
+    Json(config): Json<StrategyConfig>,
+) -> ApiResult<Json<StartBotResponse>> {
+    let config = config.validate().map_err(strategy_config_error)?;
+    let req = StartBotRequest {
+        user_id: query.user_id,
+        bot_name: config.strategy,
+        base_asset: config.base_asset,
+        quote_asset: config.quote_asset,
+        stoploss_amount: config.risk.stoploss_amount,
+        parameters: config.parameters,
+        dry_run: false,
+        trade_limits: config.risk.trade_limits,
+        max_base_position: config.risk.max_base_position,
+        schedule: config.schedule,
+    };
+    start_bot(State(state), Json(req)).await
 }
@@ -0,0 +1,315 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries::{self, Competition, CompetitionEntry, CompetitionTrade};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::UserId;
+use crate::pagination::{Page, PageQuery, SortOrder};
+use crate::routes::admin::require_admin;
+use crate::routes::trade::{trade_failure, TradeRequest};
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::competition_service::{self, CompetitionStatus, Standing};
+use crate::state::{self, AppState};
+
+const DEFAULT_TRADE_LIMIT: i64 = 50;
+const MAX_TRADE_LIMIT: i64 = 500;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CompetitionQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCompetitionRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub starting_balance: f64, // USD each participant starts with
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CompetitionSummary {
+    #[serde(flatten)]
+    pub competition: Competition,
+    pub status: CompetitionStatus,
+    pub participants: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CompetitionPortfolio {
+    #[serde(flatten)]
+    pub entry: CompetitionEntry,
+    pub value_usd: f64, // At the latest prices, or the final value once finalized
+    pub status: CompetitionStatus,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Leaderboard {
+    pub competition_id: String,
+    pub status: CompetitionStatus,
+    pub is_final: bool, // Values were recorded when the competition ended; otherwise they are live
+    pub standings: Vec<Standing>,
+}
+
+async fn find(state: &AppState, competition_id: &str) -> ApiResult<Competition> {
+    queries::get_competition(state.db.pool(), competition_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Competition not found"))
+}
+
+async fn find_entry(state: &AppState, competition_id: &str, user_id: &UserId) -> ApiResult<CompetitionEntry> {
+    queries::get_competition_entry(state.db.pool(), competition_id, user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("You have not joined this competition").with_code("not_joined"))
+}
+
+/// Every competition, latest start first
+#[utoipa::path(get, path = "/api/competitions", tag = "competitions",
+    responses((status = 200, description = "Competitions with their status and participant counts", body = Vec<CompetitionSummary>)))]
+pub async fn list_competitions(State(state): State<AppState>) -> ApiResult<Json<Vec<CompetitionSummary>>> {
+    let now = state.clock.now();
+    let competitions = queries::list_competitions(state.db.pool()).await?;
+
+    Ok(Json(
+        competitions
+            .into_iter()
+            .map(|(competition, participants)| CompetitionSummary {
+                status: competition_service::status(&competition, now),
+                competition,
+                participants,
+            })
+            .collect(),
+    ))
+}
+
+/// Create a competition (admins only)
+#[utoipa::path(post, path = "/api/competitions", tag = "competitions", params(CompetitionQuery),
+    security(("session_token" = [])),
+    request_body = CreateCompetitionRequest,
+    responses(
+        (status = 200, description = "The new competition", body = CompetitionSummary),
+        (status = 400, description = "Invalid name, balance or dates", body = ErrorBody),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn create_competition(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CompetitionQuery>,
+    Json(req): Json<CreateCompetitionRequest>,
+) -> ApiResult<Json<CompetitionSummary>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let now = state.clock.now();
+    competition_service::validate(&req.name, &req.description, req.starting_balance, req.starts_at, req.ends_at, now)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_competition"))?;
+
+    let competition = Competition {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: req.name.trim().to_string(),
+        description: req.description.trim().to_string(),
+        starting_balance: req.starting_balance,
+        starts_at: req.starts_at,
+        ends_at: req.ends_at,
+        created_by: query.user_id.clone(),
+        created_at: now,
+        finalized_at: None,
+        league_id: None,
+    };
+    queries::create_competition(state.db.pool(), &competition).await?;
+    audit_service::record(
+        state.db.pool(),
+        Some(&query.user_id),
+        AuditAction::CompetitionCreated,
+        format!("{} ({})", competition.name, competition.id),
+    );
+
+    Ok(Json(CompetitionSummary {
+        status: competition_service::status(&competition, now),
+        competition,
+        participants: 0,
+    }))
+}
+
+#[utoipa::path(get, path = "/api/competitions/{competition_id}", tag = "competitions",
+    params(("competition_id" = String, Path, description = "Competition id")),
+    responses(
+        (status = 200, description = "The competition", body = CompetitionSummary),
+        (status = 404, description = "No such competition", body = ErrorBody),
+    ))]
+pub async fn get_competition(
+    State(state): State<AppState>,
+    Path(competition_id): Path<String>,
+) -> ApiResult<Json<CompetitionSummary>> {
+    let competition = find(&state, &competition_id).await?;
+    let participants = queries::list_competition_entries(state.db.pool(), &competition_id).await?.len() as i64;
+
+    Ok(Json(CompetitionSummary {
+        status: competition_service::status(&competition, state.clock.now()),
+        competition,
+        participants,
+    }))
+}
+
+/// Join before the competition ends; the entry starts with only the starting balance in USD
+#[utoipa::path(post, path = "/api/competitions/{competition_id}/join", tag = "competitions",
+    params(("competition_id" = String, Path, description = "Competition to join"), CompetitionQuery),
+    responses(
+        (status = 200, description = "The new competition portfolio", body = CompetitionPortfolio),
+        (status = 400, description = "Guests can't join", body = ErrorBody),
+        (status = 404, description = "No such competition or user", body = ErrorBody),
+        (status = 409, description = "Already joined, or the competition has ended", body = ErrorBody),
+    ))]
+pub async fn join_competition(
+    State(state): State<AppState>,
+    Path(competition_id): Path<String>,
+    Query(query): Query<CompetitionQuery>,
+) -> ApiResult<Json<CompetitionPortfolio>> {
+    let competition = find(&state, &competition_id).await?;
+    let status = competition_service::status(&competition, state.clock.now());
+    if status == CompetitionStatus::Ended {
+        return Err(ApiError::conflict("This competition has ended").with_code("competition_ended"));
+    }
+    if state::is_memory_only(&query.user_id) {
+        return Err(ApiError::bad_request("Sign up to join competitions").with_code("account_required"));
+    }
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let entry = CompetitionEntry {
+        competition_id,
+        user_id: query.user_id,
+        asset_balances: HashMap::from([("USD".to_string(), competition.starting_balance)]),
+        version: 0,
+        trade_count: 0,
+        joined_at: state.clock.now(),
+        final_value: None,
+        final_rank: None,
+    };
+    if !queries::join_competition(state.db.pool(), &entry).await? {
+        return Err(ApiError::conflict("You have already joined this competition").with_code("already_joined"));
+    }
+
+    Ok(Json(CompetitionPortfolio { value_usd: competition.starting_balance, entry, status }))
+}
+
+/// The caller's competition balances and their value
+#[utoipa::path(get, path = "/api/competitions/{competition_id}/portfolio", tag = "competitions",
+    params(("competition_id" = String, Path, description = "Competition id"), CompetitionQuery),
+    responses(
+        (status = 200, description = "The competition portfolio", body = CompetitionPortfolio),
+        (status = 404, description = "No such competition, or not joined", body = ErrorBody),
+    ))]
+pub async fn get_portfolio(
+    State(state): State<AppState>,
+    Path(competition_id): Path<String>,
+    Query(query): Query<CompetitionQuery>,
+) -> ApiResult<Json<CompetitionPortfolio>> {
+    let competition = find(&state, &competition_id).await?;
+    let entry = find_entry(&state, &competition_id, &query.user_id).await?;
+    let value_usd = match entry.final_value {
+        Some(value) => value,
+        None => competition_service::value_usd(&state, &entry.asset_balances).await,
+    };
+
+    Ok(Json(CompetitionPortfolio {
+        value_usd,
+        entry,
+        status: competition_service::status(&competition, state.clock.now()),
+    }))
+}
+
+/// Trade at market in the competition portfolio, while the competition runs
+/// Same body as `POST /api/trade`; the user's own account is not touched
+#[utoipa::path(post, path = "/api/competitions/{competition_id}/trade", tag = "competitions",
+    params(("competition_id" = String, Path, description = "Competition id"), CompetitionQuery),
+    request_body = TradeRequest,
+    responses(
+        (status = 200, description = "The executed trade", body = CompetitionTrade),
+        (status = 400, description = "Invalid trade (balance, price, amount)", body = ErrorBody),
+        (status = 404, description = "No such competition, or not joined", body = ErrorBody),
+        (status = 409, description = "The competition is not running", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
+pub async fn post_trade(
+    State(state): State<AppState>,
+    Path(competition_id): Path<String>,
+    Query(query): Query<CompetitionQuery>,
+    Json(req): Json<TradeRequest>,
+) -> ApiResult<Json<CompetitionTrade>> {
+    let competition = find(&state, &competition_id).await?;
+    match competition_service::status(&competition, state.clock.now()) {
+        CompetitionStatus::Active => {}
+        CompetitionStatus::Upcoming => {
+            return Err(ApiError::conflict("This competition has not started").with_code("competition_not_started"))
+        }
+        CompetitionStatus::Ended => {
+            return Err(ApiError::conflict("This competition has ended").with_code("competition_ended"))
+        }
+    }
+    find_entry(&state, &competition_id, &query.user_id).await?;
+
+    let base_asset = req.asset.trim().to_uppercase();
+    let quote_asset = req.quote_asset.as_deref().unwrap_or("USD").trim().to_uppercase();
+    competition_service::trade(&state, &competition_id, &query.user_id, &base_asset, &quote_asset, req.side, req.quantity)
+        .await
+        .map(Json)
+        .map_err(|err| trade_failure(&err, &base_asset, &quote_asset))
+}
+
+/// The caller's competition trades, newest first unless `sort=asc`
+#[utoipa::path(get, path = "/api/competitions/{competition_id}/trades", tag = "competitions",
+    params(("competition_id" = String, Path, description = "Competition id"), CompetitionQuery, PageQuery),
+    responses((status = 200, description = "A page of trades; `next_cursor` is a trade id", body = Page<CompetitionTrade>)))]
+pub async fn list_trades(
+    State(state): State<AppState>,
+    Path(competition_id): Path<String>,
+    Query(query): Query<CompetitionQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<CompetitionTrade>>> {
+    let page = page.resolve(DEFAULT_TRADE_LIMIT, MAX_TRADE_LIMIT, SortOrder::Desc);
+
+    let entries = queries::get_competition_trades(
+        state.db.pool(),
+        &competition_id,
+        &query.user_id,
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(page.finish(entries, |entry| entry.id)))
+}
+
+/// Participants ranked by portfolio value: live while it runs, final once it has ended
+#[utoipa::path(get, path = "/api/competitions/{competition_id}/leaderboard", tag = "competitions",
+    params(("competition_id" = String, Path, description = "Competition id")),
+    responses(
+        (status = 200, description = "The ranking", body = Leaderboard),
+        (status = 404, description = "No such competition", body = ErrorBody),
+    ))]
+pub async fn leaderboard(
+    State(state): State<AppState>,
+    Path(competition_id): Path<String>,
+) -> ApiResult<Json<Leaderboard>> {
+    let competition = find(&state, &competition_id).await?;
+    let standings = competition_service::standings(&state, &competition).await?;
+
+    Ok(Json(Leaderboard {
+        status: competition_service::status(&competition, state.clock.now()),
+        is_final: competition.finalized_at.is_some(),
+        competition_id,
+        standings,
+    }))
+}
@@ -0,0 +1,170 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::routes::{account, achievements, admin, alerts, audit, auth, backtest, bot, competitions, follows, leagues, indicators, news, notifications, orders, portfolio, price, session, settings, stats, stream, trade, watchlists, webhooks};
+
+/// OpenAPI document for every `/api` route, served with Swagger UI at `/api/docs`
+/// Schemas are collected from the `#[utoipa::path]` annotations on each handler
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Rust Trading Simulator API",
+        description = "Simulated crypto trading: prices, portfolios, trades, bots and accounts. \
+                       Most routes identify the caller with `?user_id=`; session routes and \
+                       streams use the session token from login."
+    ),
+    paths(
+        price::get_price,
+        price::get_assets,
+        price::get_ticker_stats,
+        price::get_price_history,
+        price::get_candle_history,
+        indicators::get_indicators,
+        news::get_news,
+        portfolio::get_portfolio,
+        portfolio::get_portfolio_history,
+        portfolio::get_daily_summaries,
+        trade::post_trade,
+        trade::post_deposit,
+        trade::post_withdrawal,
+        trade::get_trades,
+        trade::get_funds,
+        trade::get_ledger,
+        trade::get_ledger_statements,
+        orders::preview_trade,
+        orders::get_fees,
+        orders::list_orders,
+        orders::create_order,
+        orders::cancel_order,
+        auth::signup,
+        auth::login,
+        auth::request_password_reset,
+        auth::confirm_password_reset,
+        auth::oauth_authorize,
+        auth::oauth_callback,
+        bot::list_bots,
+        bot::available_bots,
+        bot::start_bot,
+        bot::stop_bot,
+        bot::pause_bot,
+        bot::resume_bot,
+        bot::bot_status,
+        bot::bot_risk,
+        bot::bot_decisions,
+        bot::bot_runs,
+        bot::start_comparison,
+        bot::get_comparison,
+        bot::export_strategy,
+        bot::validate_strategy,
+        bot::import_strategy,
+        backtest::start_backtest,
+        backtest::list_backtests,
+        backtest::optimize_backtest,
+        backtest::walk_forward,
+        backtest::genetic_optimize,
+        backtest::start_replay,
+        backtest::get_replay,
+        backtest::cancel_replay,
+        backtest::get_backtest,
+        backtest::get_backtest_risk,
+        backtest::export_strategy,
+        backtest::backtest_strategy,
+        account::change_password,
+        account::export_account,
+        account::delete_account,
+        session::current_session,
+        session::logout,
+        session::list_sessions,
+        session::revoke_session,
+        session::revoke_all_sessions,
+        audit::get_audit_log,
+        settings::get_settings,
+        settings::update_settings,
+        alerts::list_alerts,
+        alerts::create_alert,
+        alerts::update_alert,
+        alerts::delete_alert,
+        alerts::alert_history,
+        notifications::list_endpoints,
+        notifications::create_endpoint,
+        notifications::delete_endpoint,
+        notifications::test_endpoint,
+        notifications::list_deliveries,
+        watchlists::list_watchlists,
+        watchlists::create_watchlist,
+        watchlists::delete_watchlist,
+        follows::list_following,
+        follows::list_followers,
+        follows::follow,
+        follows::unfollow,
+        webhooks::webhook_status,
+        webhooks::rotate_secret,
+        webhooks::disable_webhook,
+        webhooks::tradingview_webhook,
+        competitions::list_competitions,
+        competitions::create_competition,
+        competitions::get_competition,
+        competitions::join_competition,
+        competitions::get_portfolio,
+        competitions::post_trade,
+        competitions::list_trades,
+        competitions::leaderboard,
+        leagues::list_leagues,
+        leagues::create_league,
+        leagues::get_league,
+        leagues::join_league,
+        leagues::leave_league,
+        leagues::list_seasons,
+        leagues::leaderboard,
+        achievements::list_achievements,
+        admin::list_users,
+        admin::stats,
+        admin::reset_user,
+        admin::stop_user_bot,
+        admin::backup,
+        admin::invariant_violations,
+        admin::bot_runs,
+        admin::reconcile_ledger,
+        admin::history_coverage,
+        admin::import_history,
+        admin::fetch_history,
+        stats::public_stats,
+        stream::price_stream,
+        stream::portfolio_events,
+        stream::bot_activity_stream,
+    ),
+    modifiers(&SessionTokenAuth),
+    tags(
+        (name = "prices", description = "Live and historical prices, candles, indicators and asset display metadata"),
+        (name = "news", description = "Crypto news headlines, aligned with chart timeframes"),
+        (name = "trading", description = "Portfolio, trades, deposits, withdrawals, daily summaries and the balance ledger"),
+        (name = "orders", description = "Limit and stop orders, trade cost previews and fee tiers"),
+        (name = "auth", description = "Signup, login, password resets and OAuth"),
+        (name = "bots", description = "Automated trading bots and backtests, with risk grades and portable strategy configurations"),
+        (name = "account", description = "Sessions, audit log, settings, password changes, data export and account deletion"),
+        (name = "alerts", description = "Price alerts and their firing history"),
+        (name = "notifications", description = "Webhook, Discord, Slack and email endpoints notifications are pushed to, and their delivery log"),
+        (name = "watchlists", description = "Named asset lists with live quotes"),
+        (name = "follows", description = "Copy-trading: following users and mirroring their trades"),
+        (name = "webhooks", description = "TradingView alerts driving trades and bots"),
+        (name = "competitions", description = "Paper-trading competitions and seasonal leagues with their own portfolios and leaderboards"),
+        (name = "achievements", description = "Badges earned for trading milestones"),
+        (name = "admin", description = "Admin-only user management, statistics, backups, accounting checks and price history imports"),
+        (name = "stats", description = "Public platform statistics"),
+        (name = "streams", description = "WebSocket and server-sent event feeds"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the `Authorization: Bearer <session_token>` scheme referenced by session routes
+struct SessionTokenAuth;
+
+impl Modify for SessionTokenAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "session_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries::{self, CopyFollow};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::UserId;
+use crate::services::copy_service::{MAX_FOLLOWING, MAX_RATIO};
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FollowQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowRequest {
+    pub leader: String, // Username to copy
+    #[serde(default = "default_ratio")]
+    pub ratio: f64, // 1.0 = same share of portfolio as the leader, 0.5 = half that
+    pub max_trade_usd: Option<f64>, // Cap on each mirrored trade
+}
+
+fn default_ratio() -> f64 {
+    1.0
+}
+
+/// One side of a follow: the leader in `/api/follows`, the follower in `/api/followers`
+#[derive(Serialize, ToSchema)]
+pub struct FollowEntry {
+    pub user_id: UserId,
+    pub username: String,
+    pub ratio: f64,
+    pub max_trade_usd: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UnfollowResponse {
+    pub success: bool,
+}
+
+async fn entry(state: &AppState, follow: CopyFollow, user_id: UserId) -> FollowEntry {
+    let username = state
+        .get_user(&user_id)
+        .await
+        .map(|u| u.username)
+        .unwrap_or_else(|| "unknown".to_string());
+
+    FollowEntry {
+        user_id,
+        username,
+        ratio: follow.ratio,
+        max_trade_usd: follow.max_trade_usd,
+        created_at: follow.created_at,
+    }
+}
+
+fn validate_request(req: &FollowRequest) -> ApiResult<()> {
+    if !(req.ratio.is_finite() && req.ratio > 0.0 && req.ratio <= MAX_RATIO) {
+        return Err(ApiError::bad_request(format!("ratio must be above 0 and at most {}", MAX_RATIO))
+            .with_code("invalid_follow"));
+    }
+    if req.max_trade_usd.is_some_and(|cap| !(cap.is_finite() && cap > 0.0)) {
+        return Err(ApiError::bad_request("max_trade_usd must be positive").with_code("invalid_follow"));
+    }
+    Ok(())
+}
+
+/// Users this user copies, oldest first
+#[utoipa::path(get, path = "/api/follows", tag = "follows", params(FollowQuery),
+    responses((status = 200, description = "Followed leaders and the copy settings for each", body = Vec<FollowEntry>)))]
+pub async fn list_following(
+    State(state): State<AppState>,
+    Query(query): Query<FollowQuery>,
+) -> ApiResult<Json<Vec<FollowEntry>>> {
+    let mut entries = Vec::new();
+    for follow in queries::list_following(state.db.pool(), &query.user_id).await? {
+        let leader_id = follow.leader_id.clone();
+        entries.push(entry(&state, follow, leader_id).await);
+    }
+    Ok(Json(entries))
+}
+
+/// Users copying this user, oldest first
+#[utoipa::path(get, path = "/api/followers", tag = "follows", params(FollowQuery),
+    responses((status = 200, description = "Followers and their copy settings", body = Vec<FollowEntry>)))]
+pub async fn list_followers(
+    State(state): State<AppState>,
+    Query(query): Query<FollowQuery>,
+) -> ApiResult<Json<Vec<FollowEntry>>> {
+    let mut entries = Vec::new();
+    for follow in queries::list_followers(state.db.pool(), &query.user_id).await? {
+        let follower_id = follow.follower_id.clone();
+        entries.push(entry(&state, follow, follower_id).await);
+    }
+    Ok(Json(entries))
+}
+
+/// Start copying a user's trades, or change the settings of an existing follow
+/// Each trade the leader makes is mirrored at the same price, scaled by the two portfolios'
+/// USD values times `ratio`, capped at `max_trade_usd` and at what the follower can afford
+#[utoipa::path(post, path = "/api/follows", tag = "follows", params(FollowQuery), request_body = FollowRequest,
+    responses(
+        (status = 200, description = "The follow", body = FollowEntry),
+        (status = 400, description = "Invalid settings, or following yourself", body = ErrorBody),
+        (status = 404, description = "No such user", body = ErrorBody),
+        (status = 409, description = "Follow limit reached", body = ErrorBody),
+    ))]
+pub async fn follow(
+    State(state): State<AppState>,
+    Query(query): Query<FollowQuery>,
+    Json(req): Json<FollowRequest>,
+) -> ApiResult<Json<FollowEntry>> {
+    validate_request(&req)?;
+
+    if query.user_id == "demo_user" {
+        return Err(ApiError::bad_request("The demo account can't copy-trade; sign up first")
+            .with_code("invalid_follow"));
+    }
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let leader_id = queries::find_user_id_by_username(state.db.pool(), req.leader.trim())
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("No user named {}", req.leader.trim())))?;
+    if leader_id == query.user_id {
+        return Err(ApiError::bad_request("You can't follow yourself").with_code("invalid_follow"));
+    }
+
+    let following = queries::list_following(state.db.pool(), &query.user_id).await?;
+    let existing = following.iter().find(|f| f.leader_id == leader_id);
+    if existing.is_none() && following.len() >= MAX_FOLLOWING {
+        return Err(ApiError::conflict(format!("You can follow at most {} users", MAX_FOLLOWING))
+            .with_code("follow_limit_reached"));
+    }
+
+    let follow = CopyFollow {
+        follower_id: query.user_id,
+        leader_id: leader_id.clone(),
+        ratio: req.ratio,
+        max_trade_usd: req.max_trade_usd,
+        created_at: existing.map(|f| f.created_at).unwrap_or_else(Utc::now),
+    };
+    queries::upsert_follow(state.db.pool(), &follow).await?;
+
+    Ok(Json(entry(&state, follow, leader_id).await))
+}
+
+#[utoipa::path(delete, path = "/api/follows/{leader_id}", tag = "follows",
+    params(("leader_id" = String, Path, description = "User to stop copying"), FollowQuery),
+    responses(
+        (status = 200, description = "No longer copying; past mirrored trades are kept", body = UnfollowResponse),
+        (status = 404, description = "Not following that user", body = ErrorBody),
+    ))]
+pub async fn unfollow(
+    State(state): State<AppState>,
+    Path(leader_id): Path<UserId>,
+    Query(query): Query<FollowQuery>,
+) -> ApiResult<Json<UnfollowResponse>> {
+    if !queries::delete_follow(state.db.pool(), &query.user_id, &leader_id).await? {
+        return Err(ApiError::not_found("Not following that user"));
+    }
+
+    Ok(Json(UnfollowResponse { success: true }))
+}
@@ -1,16 +1,23 @@
-use axum::{extract::{Query, State}, http::StatusCode, Json};
+use axum::{
+    extract::{Query, RawQuery, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use std::collections::HashMap;
-use crate::{indicators::{SMA, EMA, RSI}, state::AppState};
+use crate::{error::{ApiError, ApiResult, ErrorBody}, indicators::{SMA, EMA, RSI}, middleware::conditional::Validators, state::AppState};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct IndicatorQuery {
     pub asset: String,
-    pub timeframe: String,      // "1h", "8h", or "24h"
+    pub timeframe: String,      // "1h", "8h", "24h", "7d", or "30d"
     pub indicators: String,      // comma-separated: "sma_20,sma_50,ema_12"
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct IndicatorResponse {
     pub asset: String,
     pub timeframe: String,
@@ -19,45 +26,49 @@ pub struct IndicatorResponse {
     pub indicators: HashMap<String, Vec<Option<f64>>>,
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    pub error: String,
-}
-
+#[utoipa::path(get, path = "/api/indicators", tag = "prices", params(IndicatorQuery),
+    responses(
+        (status = 200, description = "Indicator series aligned with the price series", body = IndicatorResponse),
+        (status = 304, description = "Unchanged since the `ETag` / `Last-Modified` the client sent"),
+        (status = 400, description = "Unsupported timeframe or not enough data", body = ErrorBody),
+        (status = 404, description = "No price data for the asset", body = ErrorBody),
+    ))]
 pub async fn get_indicators(
     State(state): State<AppState>,
     Query(query): Query<IndicatorQuery>,
-) -> Result<Json<IndicatorResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Validate timeframe - only 1h is supported for now
-    if query.timeframe != "1h" {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!(
-                    "Indicators are only supported for 1h timeframe. Requested: {}",
-                    query.timeframe
-                ),
-            }),
-        ));
+    RawQuery(raw_query): RawQuery,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let range = match query.timeframe.as_str() {
+        "1h" => chrono::Duration::hours(1),
+        "8h" => chrono::Duration::hours(8),
+        "24h" => chrono::Duration::hours(24),
+        "7d" => chrono::Duration::days(7),
+        "30d" => chrono::Duration::days(30),
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "Unsupported timeframe: {}. Use 1h, 8h, 24h, 7d or 30d",
+                other
+            ))
+            .with_code("unsupported_timeframe"));
+        }
+    };
+
+    // Indicators only move when a new price arrives, so skip the work if the client is current
+    let validators = state
+        .get_latest_price_time(&query.asset)
+        .await
+        .map(|latest| Validators::new(raw_query.as_deref().unwrap_or_default(), latest));
+    if let Some(validators) = validators.as_ref().filter(|v| v.is_fresh(&headers)) {
+        return Ok(validators.not_modified());
     }
 
-    // Get price data from state (1h = 5-second price_window data)
-    let state_lock = state.inner.read().await;
-    let price_window = &state_lock.price_window;
-
-    // Filter prices for the requested asset
-    let asset_prices: Vec<_> = price_window
-        .iter()
-        .filter(|p| p.asset == query.asset)
-        .collect();
+    // Closes from the finest resolution tier that covers the range
+    let asset_prices = state.get_close_series(&query.asset, range).await;
 
     if asset_prices.is_empty() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("No price data found for asset: {}", query.asset),
-            }),
-        ));
+        return Err(ApiError::not_found(format!("No price data found for asset: {}", query.asset))
+            .with_code("no_price_data"));
     }
 
     // Extract prices and timestamps
@@ -69,15 +80,12 @@ pub async fn get_indicators(
 
     // Check if we have enough data for indicators
     if prices.len() < 20 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!(
-                    "Insufficient data for indicators. Need at least 20 points, have {}",
-                    prices.len()
-                ),
-            }),
-        ));
+        return Err(ApiError::bad_request(format!(
+            "Insufficient data for indicators. Need at least 20 points, have {}",
+            prices.len()
+        ))
+        .with_code("insufficient_data")
+        .with_details(serde_json::json!({ "required": 20, "available": prices.len() })));
     }
 
     // Parse requested indicators
@@ -98,7 +106,7 @@ pub async fn get_indicators(
         };
 
         // Validate period
-        if period < 2 || period > 200 {
+        if !(2..=200).contains(&period) {
             continue; // Skip invalid periods
         }
 
@@ -128,11 +136,15 @@ pub async fn get_indicators(
         indicators.insert(indicator_str.to_string(), values_option);
     }
 
-    Ok(Json(IndicatorResponse {
+    let body = Json(IndicatorResponse {
         asset: query.asset,
         timeframe: query.timeframe,
         timestamps,
         prices,
         indicators,
-    }))
+    });
+    Ok(match validators {
+        Some(validators) => validators.apply(body),
+        None => body.into_response(),
+    })
 }
@@ -0,0 +1,214 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db::queries::{self, League};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::routes::admin::require_admin;
+use crate::routes::competitions::{CompetitionQuery, CompetitionSummary, Leaderboard};
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::competition_service;
+use crate::services::league_service;
+use crate::state::{self, AppState};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateLeagueRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub season_length: String, // "week" or "month"; seasons follow the UTC calendar
+    pub starting_balance: f64, // USD each member starts every season with
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LeagueSummary {
+    #[serde(flatten)]
+    pub league: League,
+    pub members: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LeagueMembership {
+    pub league_id: String,
+    pub member: bool,
+    pub season: Option<CompetitionSummary>, // The season the member was entered in; None after opting out
+}
+
+async fn find(state: &AppState, league_id: &str) -> ApiResult<(League, i64)> {
+    queries::get_league(state.db.pool(), league_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("League not found"))
+}
+
+/// A season with its status and participant count
+async fn season_summary(state: &AppState, league: &League) -> ApiResult<CompetitionSummary> {
+    let season = league_service::current_season(state, league).await?;
+    let participants = queries::list_competition_entries(state.db.pool(), &season.id).await?.len() as i64;
+    Ok(CompetitionSummary {
+        status: competition_service::status(&season, state.clock.now()),
+        competition: season,
+        participants,
+    })
+}
+
+/// Every league, oldest first
+#[utoipa::path(get, path = "/api/leagues", tag = "competitions",
+    responses((status = 200, description = "Leagues with their member counts", body = Vec<LeagueSummary>)))]
+pub async fn list_leagues(State(state): State<AppState>) -> ApiResult<Json<Vec<LeagueSummary>>> {
+    let leagues = queries::list_leagues(state.db.pool()).await?;
+    Ok(Json(leagues.into_iter().map(|(league, members)| LeagueSummary { league, members }).collect()))
+}
+
+/// Create a league (admins only); its first season starts right away
+#[utoipa::path(post, path = "/api/leagues", tag = "competitions", params(CompetitionQuery),
+    security(("session_token" = [])),
+    request_body = CreateLeagueRequest,
+    responses(
+        (status = 200, description = "The new league", body = LeagueSummary),
+        (status = 400, description = "Invalid name, balance or season length", body = ErrorBody),
+        (status = 403, description = "Caller is not an admin", body = ErrorBody),
+    ))]
+pub async fn create_league(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CompetitionQuery>,
+    Json(req): Json<CreateLeagueRequest>,
+) -> ApiResult<Json<LeagueSummary>> {
+    require_admin(&state, &headers, &query.user_id).await?;
+
+    let period = league_service::validate(&req.name, &req.description, &req.season_length, req.starting_balance)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_league"))?;
+
+    let league = League {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: req.name.trim().to_string(),
+        description: req.description.trim().to_string(),
+        season_length: period.as_str().to_string(),
+        starting_balance: req.starting_balance,
+        created_by: query.user_id.clone(),
+        created_at: state.clock.now(),
+    };
+    queries::create_league(state.db.pool(), &league).await?;
+    league_service::current_season(&state, &league).await?;
+    audit_service::record(
+        state.db.pool(),
+        Some(&query.user_id),
+        AuditAction::LeagueCreated,
+        format!("{} ({})", league.name, league.id),
+    );
+
+    Ok(Json(LeagueSummary { league, members: 0 }))
+}
+
+#[utoipa::path(get, path = "/api/leagues/{league_id}", tag = "competitions",
+    params(("league_id" = String, Path, description = "League id")),
+    responses(
+        (status = 200, description = "The league", body = LeagueSummary),
+        (status = 404, description = "No such league", body = ErrorBody),
+    ))]
+pub async fn get_league(State(state): State<AppState>, Path(league_id): Path<String>) -> ApiResult<Json<LeagueSummary>> {
+    let (league, members) = find(&state, &league_id).await?;
+    Ok(Json(LeagueSummary { league, members }))
+}
+
+/// Opt in to the league: a fresh portfolio in the current season now, and in every season after
+#[utoipa::path(post, path = "/api/leagues/{league_id}/join", tag = "competitions",
+    params(("league_id" = String, Path, description = "League to join"), CompetitionQuery),
+    responses(
+        (status = 200, description = "Membership and the season entered", body = LeagueMembership),
+        (status = 400, description = "Guests can't join", body = ErrorBody),
+        (status = 404, description = "No such league or user", body = ErrorBody),
+        (status = 409, description = "Already a member", body = ErrorBody),
+    ))]
+pub async fn join_league(
+    State(state): State<AppState>,
+    Path(league_id): Path<String>,
+    Query(query): Query<CompetitionQuery>,
+) -> ApiResult<Json<LeagueMembership>> {
+    let (league, _) = find(&state, &league_id).await?;
+    if state::is_memory_only(&query.user_id) {
+        return Err(ApiError::bad_request("Sign up to join leagues").with_code("account_required"));
+    }
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    if !queries::add_league_member(state.db.pool(), &league_id, &query.user_id, state.clock.now()).await? {
+        return Err(ApiError::conflict("You are already a member of this league").with_code("already_member"));
+    }
+    let season = season_summary(&state, &league).await?;
+
+    Ok(Json(LeagueMembership { league_id, member: true, season: Some(season) }))
+}
+
+/// Opt out of the league's future seasons; the current season's portfolio stays on its leaderboard
+#[utoipa::path(delete, path = "/api/leagues/{league_id}/join", tag = "competitions",
+    params(("league_id" = String, Path, description = "League to leave"), CompetitionQuery),
+    responses(
+        (status = 200, description = "No longer a member", body = LeagueMembership),
+        (status = 404, description = "No such league, or not a member", body = ErrorBody),
+    ))]
+pub async fn leave_league(
+    State(state): State<AppState>,
+    Path(league_id): Path<String>,
+    Query(query): Query<CompetitionQuery>,
+) -> ApiResult<Json<LeagueMembership>> {
+    find(&state, &league_id).await?;
+    if !queries::remove_league_member(state.db.pool(), &league_id, &query.user_id).await? {
+        return Err(ApiError::not_found("You are not a member of this league").with_code("not_member"));
+    }
+
+    Ok(Json(LeagueMembership { league_id, member: false, season: None }))
+}
+
+/// The league's seasons, latest first: the current one and the archive of ended ones
+/// Trade in a season and see its standings through the competition endpoints, by the season's id
+#[utoipa::path(get, path = "/api/leagues/{league_id}/seasons", tag = "competitions",
+    params(("league_id" = String, Path, description = "League id")),
+    responses(
+        (status = 200, description = "Seasons with their status and participant counts", body = Vec<CompetitionSummary>),
+        (status = 404, description = "No such league", body = ErrorBody),
+    ))]
+pub async fn list_seasons(
+    State(state): State<AppState>,
+    Path(league_id): Path<String>,
+) -> ApiResult<Json<Vec<CompetitionSummary>>> {
+    find(&state, &league_id).await?;
+    let now = state.clock.now();
+    let seasons = queries::list_league_seasons(state.db.pool(), &league_id).await?;
+
+    Ok(Json(
+        seasons
+            .into_iter()
+            .map(|(competition, participants)| CompetitionSummary {
+                status: competition_service::status(&competition, now),
+                competition,
+                participants,
+            })
+            .collect(),
+    ))
+}
+
+/// The current season's leaderboard, live at the latest prices
+#[utoipa::path(get, path = "/api/leagues/{league_id}/leaderboard", tag = "competitions",
+    params(("league_id" = String, Path, description = "League id")),
+    responses(
+        (status = 200, description = "The current season's ranking", body = Leaderboard),
+        (status = 404, description = "No such league", body = ErrorBody),
+    ))]
+pub async fn leaderboard(State(state): State<AppState>, Path(league_id): Path<String>) -> ApiResult<Json<Leaderboard>> {
+    let (league, _) = find(&state, &league_id).await?;
+    let season = league_service::current_season(&state, &league).await?;
+    let standings = competition_service::standings(&state, &season).await?;
+
+    Ok(Json(Leaderboard {
+        status: competition_service::status(&season, state.clock.now()),
+        is_final: season.finalized_at.is_some(),
+        competition_id: season.id,
+        standings,
+    }))
+}
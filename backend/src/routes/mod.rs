@@ -4,3 +4,22 @@ pub mod trade;
 pub mod auth;
 pub mod bot;
 pub mod indicators;
+pub mod admin;
+pub mod account;
+pub mod audit;
+pub mod alerts;
+pub mod watchlists;
+pub mod follows;
+pub mod session;
+pub mod stream;
+pub mod stats;
+pub mod docs;
+pub mod webhooks;
+pub mod orders;
+pub mod backtest;
+pub mod settings;
+pub mod notifications;
+pub mod competitions;
+pub mod achievements;
+pub mod news;
+pub mod leagues;
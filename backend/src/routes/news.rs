@@ -0,0 +1,88 @@
+use crate::db::queries;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::state::AppState;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+const DEFAULT_NEWS_LIMIT: i64 = 50;
+const MAX_NEWS_LIMIT: i64 = 200;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NewsQuery {
+    pub asset: Option<String>,     // Only headlines mentioning this asset; all when omitted
+    pub timeframe: Option<String>, // "1h", "8h", "24h" (default), "7d", or "30d", as on the chart
+    pub limit: Option<i64>,        // Default 50, at most 200
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Headline {
+    pub timestamp: i64, // Publication time in unix seconds, like the chart's points
+    pub title: String,
+    pub url: String,
+    pub source: String,
+    pub assets: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct NewsResponse {
+    pub asset: Option<String>,
+    pub headlines: Vec<Headline>, // Newest first
+}
+
+/// Headlines published within a chart timeframe, to set against the price moves around them
+/// Empty unless the server has a CryptoPanic token configured
+#[utoipa::path(get, path = "/api/news", tag = "news", params(NewsQuery),
+    responses(
+        (status = 200, description = "Headlines in the timeframe, newest first", body = NewsResponse),
+        (status = 400, description = "Unsupported timeframe", body = ErrorBody),
+    ))]
+pub async fn get_news(
+    State(state): State<AppState>,
+    Query(query): Query<NewsQuery>,
+) -> ApiResult<Json<NewsResponse>> {
+    let range = match query.timeframe.as_deref().unwrap_or("24h") {
+        "1h" => chrono::Duration::hours(1),
+        "8h" => chrono::Duration::hours(8),
+        "24h" => chrono::Duration::hours(24),
+        "7d" => chrono::Duration::days(7),
+        "30d" => chrono::Duration::days(30),
+        other => {
+            return Err(ApiError::bad_request(format!(
+                "Unsupported timeframe: {}. Use 1h, 8h, 24h, 7d or 30d",
+                other
+            ))
+            .with_code("unsupported_timeframe"));
+        }
+    };
+    let asset = query.asset.map(|a| a.trim().to_uppercase()).filter(|a| !a.is_empty());
+    let limit = query.limit.unwrap_or(DEFAULT_NEWS_LIMIT).clamp(1, MAX_NEWS_LIMIT);
+
+    let now = state.clock.now();
+    let items = queries::get_news_items(
+        state.db.pool(),
+        asset.as_deref(),
+        (now - range).timestamp(),
+        now.timestamp(),
+        limit,
+    )
+    .await?;
+
+    Ok(Json(NewsResponse {
+        asset,
+        headlines: items
+            .into_iter()
+            .map(|item| Headline {
+                timestamp: item.published_at.timestamp(),
+                title: item.title,
+                url: item.url,
+                source: item.source,
+                assets: item.assets,
+            })
+            .collect(),
+    }))
+}
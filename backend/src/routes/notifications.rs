@@ -0,0 +1,174 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries::{self, NotificationDelivery, NotificationEndpoint};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{NotificationChannel, UserId};
+use crate::pagination::{Page, PageQuery, SortOrder};
+use crate::services::notification_service::{self, MAX_ENDPOINTS_PER_USER, SIGNATURE_HEADER};
+use crate::state::{AppState, Notification, NotificationKind};
+
+const DEFAULT_DELIVERY_LIMIT: i64 = 50;
+const MAX_DELIVERY_LIMIT: i64 = 500;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NotificationQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct EndpointRequest {
+    pub channel: NotificationChannel,
+    pub url: String, // http(s) for webhooks; the channel's webhook URL for Discord and Slack; an address for email
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateEndpointResponse {
+    pub endpoint: NotificationEndpoint,
+    pub secret: String, // Shown only once; verify webhook deliveries with it (other channels are unsigned)
+    pub signature_header: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeleteEndpointResponse {
+    pub success: bool,
+}
+
+/// The user's notification endpoints, oldest first
+#[utoipa::path(get, path = "/api/notifications/endpoints", tag = "notifications", params(NotificationQuery),
+    responses((status = 200, description = "The user's endpoints", body = Vec<NotificationEndpoint>)))]
+pub async fn list_endpoints(
+    State(state): State<AppState>,
+    Query(query): Query<NotificationQuery>,
+) -> ApiResult<Json<Vec<NotificationEndpoint>>> {
+    Ok(Json(queries::list_notification_endpoints(state.db.pool(), &query.user_id).await?))
+}
+
+/// Register an endpoint; every notification the user gets is also pushed to it
+/// Webhook bodies are the notification as JSON, signed with the returned secret:
+/// the signature header holds `sha256=` and the hex HMAC-SHA256 of the raw body.
+/// Discord endpoints get a message with one embed per notification, Slack ones a few blocks.
+/// Email endpoints (`mailto:` address) get only stoploss hits and bots stopping on their own, and
+/// password reset tokens; registering one is the opt-in
+#[utoipa::path(post, path = "/api/notifications/endpoints", tag = "notifications", params(NotificationQuery),
+    request_body = EndpointRequest,
+    responses(
+        (status = 200, description = "The endpoint and its signing secret", body = CreateEndpointResponse),
+        (status = 400, description = "Invalid URL for the channel, or email not configured", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "Endpoint limit reached", body = ErrorBody),
+    ))]
+pub async fn create_endpoint(
+    State(state): State<AppState>,
+    Query(query): Query<NotificationQuery>,
+    Json(req): Json<EndpointRequest>,
+) -> ApiResult<Json<CreateEndpointResponse>> {
+    let url = notification_service::validate_url(req.channel, &req.url)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_endpoint"))?;
+    if req.channel == NotificationChannel::Email && state.mailer.is_none() {
+        return Err(ApiError::bad_request("Email is not configured on this server").with_code("email_unavailable"));
+    }
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let count = queries::count_notification_endpoints(state.db.pool(), &query.user_id).await?;
+    if count >= MAX_ENDPOINTS_PER_USER {
+        return Err(ApiError::conflict(format!(
+            "You can have at most {} notification endpoints; delete some first",
+            MAX_ENDPOINTS_PER_USER
+        ))
+        .with_code("endpoint_limit_reached"));
+    }
+
+    let endpoint = NotificationEndpoint {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: query.user_id,
+        channel: req.channel,
+        url,
+        secret: notification_service::issue_secret(),
+        created_at: Utc::now(),
+    };
+    queries::create_notification_endpoint(state.db.pool(), &endpoint).await?;
+
+    Ok(Json(CreateEndpointResponse {
+        secret: endpoint.secret.clone(),
+        endpoint,
+        signature_header: SIGNATURE_HEADER.to_string(),
+    }))
+}
+
+#[utoipa::path(delete, path = "/api/notifications/endpoints/{endpoint_id}", tag = "notifications",
+    params(("endpoint_id" = String, Path, description = "Endpoint to delete"), NotificationQuery),
+    responses(
+        (status = 200, description = "Endpoint deleted; its deliveries stay logged", body = DeleteEndpointResponse),
+        (status = 404, description = "No such endpoint", body = ErrorBody),
+    ))]
+pub async fn delete_endpoint(
+    State(state): State<AppState>,
+    Path(endpoint_id): Path<String>,
+    Query(query): Query<NotificationQuery>,
+) -> ApiResult<Json<DeleteEndpointResponse>> {
+    if !queries::delete_notification_endpoint(state.db.pool(), &query.user_id, &endpoint_id).await? {
+        return Err(ApiError::not_found("Endpoint not found"));
+    }
+
+    Ok(Json(DeleteEndpointResponse { success: true }))
+}
+
+/// Send a `test` notification to one endpoint, once, and report how it went
+#[utoipa::path(post, path = "/api/notifications/endpoints/{endpoint_id}/test", tag = "notifications",
+    params(("endpoint_id" = String, Path, description = "Endpoint to test"), NotificationQuery),
+    responses(
+        (status = 200, description = "The logged delivery; check `status`", body = NotificationDelivery),
+        (status = 404, description = "No such endpoint", body = ErrorBody),
+    ))]
+pub async fn test_endpoint(
+    State(state): State<AppState>,
+    Path(endpoint_id): Path<String>,
+    Query(query): Query<NotificationQuery>,
+) -> ApiResult<Json<NotificationDelivery>> {
+    let endpoint = queries::get_notification_endpoint(state.db.pool(), &query.user_id, &endpoint_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Endpoint not found"))?;
+
+    let notification = Notification {
+        user_id: query.user_id,
+        timestamp: Utc::now(),
+        kind: NotificationKind::Test { message: "Test notification from the trading simulator".to_string() },
+    };
+    let delivery =
+        notification_service::deliver(&state, &reqwest::Client::new(), &endpoint, &notification, 1).await;
+
+    Ok(Json(delivery))
+}
+
+/// Log of pushes to the user's endpoints, newest first unless `sort=asc`
+#[utoipa::path(get, path = "/api/notifications/deliveries", tag = "notifications",
+    params(NotificationQuery, PageQuery),
+    responses((status = 200, description = "A page of deliveries; `next_cursor` is a delivery id", body = Page<NotificationDelivery>)))]
+pub async fn list_deliveries(
+    State(state): State<AppState>,
+    Query(query): Query<NotificationQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<NotificationDelivery>>> {
+    let page = page.resolve(DEFAULT_DELIVERY_LIMIT, MAX_DELIVERY_LIMIT, SortOrder::Desc);
+
+    let entries = queries::get_notification_deliveries(
+        state.db.pool(),
+        &query.user_id,
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(page.finish(entries, |entry| entry.id)))
+}
@@ -0,0 +1,235 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries::{self, Order};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{OrderStatus, OrderType, TradeSide, UserId};
+use crate::routes::trade::trade_failure;
+use crate::services::fee_service::{self, FeeTier, Liquidity};
+use crate::services::order_service::{self, TradePreview, MAX_OPEN_ORDERS};
+use crate::services::trading_service::TradeError;
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OrderQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OrderListQuery {
+    pub user_id: UserId,
+    pub status: Option<OrderStatus>, // All orders when omitted
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OrderRequest {
+    pub asset: String, // Base asset
+    #[serde(default)]
+    pub quote_asset: Option<String>, // Defaults to "USD"
+    pub side: TradeSide,
+    pub order_type: OrderType,
+    pub quantity: f64,
+    pub price: f64, // Trigger price in quote asset terms
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PreviewRequest {
+    pub asset: String, // Base asset
+    #[serde(default)]
+    pub quote_asset: Option<String>, // Defaults to "USD"
+    pub side: TradeSide,
+    pub quantity: f64,
+    #[serde(default)]
+    pub order_type: Option<OrderType>, // Market when omitted
+    #[serde(default)]
+    pub price: Option<f64>, // Required for limit and stop orders
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CancelOrderResponse {
+    pub success: bool,
+}
+
+/// Normalized (base, quote) pair and its current price
+async fn resolve_pair(state: &AppState, asset: &str, quote_asset: Option<&str>) -> ApiResult<(String, String, f64)> {
+    let base = asset.trim().to_uppercase();
+    let quote = quote_asset.map(|q| q.trim().to_uppercase()).unwrap_or_else(|| "USD".to_string());
+    let price = state
+        .get_pair_price(&base, &quote)
+        .await
+        .map_err(|e| trade_failure(&TradeError::PriceUnavailable(e), &base, &quote))?;
+    Ok((base, quote, price))
+}
+
+/// List the user's orders, newest first; `status=open` for the ones still waiting
+#[utoipa::path(get, path = "/api/orders", tag = "orders", params(OrderListQuery),
+    responses((status = 200, description = "The user's orders", body = Vec<Order>)))]
+pub async fn list_orders(
+    State(state): State<AppState>,
+    Query(query): Query<OrderListQuery>,
+) -> ApiResult<Json<Vec<Order>>> {
+    Ok(Json(queries::list_orders(state.db.pool(), &query.user_id, query.status).await?))
+}
+
+/// Place a limit or stop order; it fills once, at market, the first time a live price reaches it
+/// Nothing is reserved: the balance is checked when the order fills
+#[utoipa::path(post, path = "/api/orders", tag = "orders", params(OrderQuery), request_body = OrderRequest,
+    responses(
+        (status = 200, description = "The open order", body = Order),
+        (status = 400, description = "Invalid order or no price for the pair", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "Open order limit reached", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
+pub async fn create_order(
+    State(state): State<AppState>,
+    Query(query): Query<OrderQuery>,
+    Json(req): Json<OrderRequest>,
+) -> ApiResult<Json<Order>> {
+    order_service::validate(req.quantity, req.price)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_order"))?;
+    let (base_asset, quote_asset, _) = resolve_pair(&state, &req.asset, req.quote_asset.as_deref()).await?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let count = queries::count_open_orders(state.db.pool(), &query.user_id).await?;
+    if count >= MAX_OPEN_ORDERS {
+        return Err(ApiError::conflict(format!(
+            "You can have at most {} open orders; cancel some first",
+            MAX_OPEN_ORDERS
+        ))
+        .with_code("order_limit_reached"));
+    }
+
+    let order = Order {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: query.user_id,
+        base_asset,
+        quote_asset,
+        side: req.side,
+        order_type: req.order_type,
+        quantity: req.quantity,
+        trigger_price: req.price,
+        status: OrderStatus::Open,
+        status_reason: None,
+        fill_price: None,
+        created_at: Utc::now(),
+        closed_at: None,
+    };
+    queries::create_order(state.db.pool(), &order).await?;
+
+    Ok(Json(order))
+}
+
+#[utoipa::path(delete, path = "/api/orders/{order_id}", tag = "orders",
+    params(("order_id" = String, Path, description = "Order to cancel"), OrderQuery),
+    responses(
+        (status = 200, description = "Order cancelled", body = CancelOrderResponse),
+        (status = 404, description = "No such open order", body = ErrorBody),
+    ))]
+pub async fn cancel_order(
+    State(state): State<AppState>,
+    Path(order_id): Path<String>,
+    Query(query): Query<OrderQuery>,
+) -> ApiResult<Json<CancelOrderResponse>> {
+    if !queries::cancel_order(state.db.pool(), &query.user_id, &order_id).await? {
+        return Err(ApiError::not_found("Open order not found"));
+    }
+
+    Ok(Json(CancelOrderResponse { success: true }))
+}
+
+/// Estimated cost, fee and resulting balances of a market trade or order, without placing it
+/// The fee is the user's tier rate: taker for market trades and stops, maker for limits
+#[utoipa::path(post, path = "/api/trade/preview", tag = "orders", params(OrderQuery), request_body = PreviewRequest,
+    responses(
+        (status = 200, description = "The estimate", body = TradePreview),
+        (status = 400, description = "Invalid quantity or price, or no price for the pair", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn preview_trade(
+    State(state): State<AppState>,
+    Query(query): Query<OrderQuery>,
+    Json(req): Json<PreviewRequest>,
+) -> ApiResult<Json<TradePreview>> {
+    let (base_asset, quote_asset, market_price) =
+        resolve_pair(&state, &req.asset, req.quote_asset.as_deref()).await?;
+
+    let price = match req.order_type {
+        None => market_price,
+        Some(_) => req
+            .price
+            .ok_or_else(|| ApiError::bad_request("Limit and stop orders need a price").with_code("invalid_order"))?,
+    };
+    order_service::validate(req.quantity, price)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_order"))?;
+
+    let user = state
+        .get_user(&query.user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+    let quote_usd_price = if quote_asset == "USD" {
+        Some(1.0)
+    } else {
+        state.get_latest_price(&quote_asset).await
+    };
+    let liquidity = req.order_type.map_or(Liquidity::Taker, order_service::liquidity);
+    let fee_rate = fee_service::rate_for(&state, &query.user_id, &user, liquidity).await;
+
+    Ok(Json(order_service::preview(
+        &user,
+        &base_asset,
+        &quote_asset,
+        req.side,
+        req.quantity,
+        price,
+        market_price,
+        quote_usd_price,
+        fee_rate,
+    )))
+}
+
+/// A user's fee tier, what it takes to reach the next one, and the whole schedule
+#[derive(Serialize, ToSchema)]
+pub struct FeeStatus {
+    pub tier: FeeTier,
+    pub volume_30d_usd: f64,
+    pub calculated_at: chrono::DateTime<Utc>, // Tiers are recalculated once a day
+    pub next_tier: Option<FeeTier>,           // None at the top tier
+    pub volume_to_next_tier_usd: Option<f64>,
+    pub schedule: Vec<FeeTier>,
+}
+
+/// The user's maker and taker rates, set by their traded volume over the last 30 days
+#[utoipa::path(get, path = "/api/fees", tag = "orders", params(OrderQuery),
+    responses(
+        (status = 200, description = "The user's fee tier and the schedule", body = FeeStatus),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn get_fees(State(state): State<AppState>, Query(query): Query<OrderQuery>) -> ApiResult<Json<FeeStatus>> {
+    let user = state
+        .get_user(&query.user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+    let record = fee_service::current(&state, &query.user_id, &user).await?;
+    let tier = fee_service::tier_named(&record.tier);
+    let next_tier = fee_service::next_tier(&tier);
+
+    Ok(Json(FeeStatus {
+        volume_to_next_tier_usd: next_tier.map(|next| (next.min_volume_usd - record.volume_30d_usd).max(0.0)),
+        tier,
+        volume_30d_usd: record.volume_30d_usd,
+        calculated_at: record.calculated_at,
+        next_tier,
+        schedule: fee_service::TIERS.to_vec(),
+    }))
+}
@@ -1,12 +1,23 @@
-use crate::{models::UserData, state::AppState};
+use crate::{
+    db::queries::{self, DailySummaryRecord},
+    error::{ApiError, ApiResult, ErrorBody},
+    models::UserData,
+    pagination::{Page, PageQuery, SortOrder},
+    services::portfolio_service::{self, HistoryRange, PortfolioHistory},
+    state::AppState,
+};
 use axum::{extract::{State, Query}, Json};
 use serde::Deserialize;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PortfolioQuery {
     pub user_id: String,
 }
 
+#[utoipa::path(get, path = "/api/portfolio", tag = "trading", params(PortfolioQuery),
+    responses((status = 200, description = "Balances and transaction history", body = UserData)))]
 pub async fn get_portfolio(
     State(state): State<AppState>,
     Query(query): Query<PortfolioQuery>,
@@ -14,6 +25,59 @@ pub async fn get_portfolio(
     let user = state
         .get_user(&query.user_id)
         .await
-        .unwrap_or_else(|| UserData::new("Unknown".to_string()));
+        .unwrap_or_else(|| UserData::new("Unknown".to_string(), state.config.accounts.starting_balance));
     Json(user)
 }
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PortfolioHistoryQuery {
+    pub user_id: String,
+    pub range: Option<String>, // "24h" (default), "7d", "30d", or "all"
+}
+
+/// Portfolio value over time next to a buy-and-hold benchmark, for the equity chart
+#[utoipa::path(get, path = "/api/portfolio/history", tag = "trading", params(PortfolioHistoryQuery),
+    responses(
+        (status = 200, description = "Equity curve, oldest first (empty until prices arrive)", body = PortfolioHistory),
+        (status = 400, description = "Unknown range", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn get_portfolio_history(
+    State(state): State<AppState>,
+    Query(query): Query<PortfolioHistoryQuery>,
+) -> ApiResult<Json<PortfolioHistory>> {
+    let range = query.range.as_deref().unwrap_or("24h");
+    let range = HistoryRange::parse(range).ok_or_else(|| {
+        ApiError::bad_request(format!("Unknown range '{}' (use 24h, 7d, 30d, or all)", range))
+            .with_code("invalid_range")
+    })?;
+
+    let user = state
+        .get_user(&query.user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    Ok(Json(portfolio_service::portfolio_history(&state, &user, range).await))
+}
+
+const DEFAULT_SUMMARY_LIMIT: i64 = 30;
+const MAX_SUMMARY_LIMIT: i64 = 365;
+
+/// Past daily summaries, newest first unless `sort=asc`
+/// Compiled at the local time set in the `daily_summary` setting, e.g. `{"time": "18:00"}`, in the user's timezone
+/// unless the setting carries its own `utc_offset_minutes`
+#[utoipa::path(get, path = "/api/portfolio/summaries", tag = "trading", params(PortfolioQuery, PageQuery),
+    responses((status = 200, description = "A page of summaries; `next_cursor` is a summary id", body = Page<DailySummaryRecord>)))]
+pub async fn get_daily_summaries(
+    State(state): State<AppState>,
+    Query(query): Query<PortfolioQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<DailySummaryRecord>>> {
+    let page = page.resolve(DEFAULT_SUMMARY_LIMIT, MAX_SUMMARY_LIMIT, SortOrder::Desc);
+
+    let summaries =
+        queries::get_daily_summaries(state.db.pool(), &query.user_id, page.cursor, page.sort, page.fetch_limit()).await?;
+
+    Ok(Json(page.finish(summaries, |summary| summary.id)))
+}
@@ -1,26 +1,39 @@
+use crate::assets::{self, AssetInfo};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::middleware::conditional::Validators;
+use crate::pagination::{PageQuery, SortOrder};
+use crate::services::price_service::{self, TickerStats};
 use crate::state::AppState;
-use axum::{extract::{State, Query}, Json};
+use axum::{
+    extract::{Query, RawQuery, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PriceResponse {
     pub asset: String,
     pub price: f64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[schema(as = PriceHistoryPoint)]
 pub struct PricePoint {
     pub timestamp: i64,
     pub price: f64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PriceHistoryResponse {
     pub asset: String,
     pub prices: Vec<PricePoint>,
+    pub next_cursor: Option<i64>, // Timestamp to pass as `cursor` for the next page
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CandleResponse {
     pub timestamp: i64,
     pub open: f64,
@@ -29,18 +42,31 @@ pub struct CandleResponse {
     pub close: f64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CandleHistoryResponse {
     pub asset: String,
     pub candles: Vec<CandleResponse>,
+    pub next_cursor: Option<i64>, // Timestamp to pass as `cursor` for the next page
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AssetQuery {
     pub asset: Option<String>,
-    pub timeframe: Option<String>, // "1h", "8h", or "24h"
+    pub timeframe: Option<String>, // "1h", "8h", "24h", "7d", or "30d"
 }
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TickerQuery {
+    pub asset: Option<String>, // Defaults to BTC
+}
+
+// Series are at most 720 points, so by default a timeframe comes back whole
+const MAX_SERIES_LIMIT: i64 = 1440;
+
+#[utoipa::path(get, path = "/api/price", tag = "prices", params(AssetQuery),
+    responses((status = 200, description = "Latest USD price (0 if none yet)", body = PriceResponse)))]
 pub async fn get_price(
     State(state): State<AppState>,
     Query(query): Query<AssetQuery>,
@@ -53,24 +79,82 @@ pub async fn get_price(
     })
 }
 
+/// Display metadata for USD, the listed assets and any other polled asset, for formatting amounts
+#[utoipa::path(get, path = "/api/assets", tag = "prices",
+    responses((status = 200, description = "Name, symbol, decimals and icon of each asset", body = Vec<AssetInfo>)))]
+pub async fn get_assets(State(state): State<AppState>) -> Json<Vec<AssetInfo>> {
+    Json(assets::registry(&state.polled_assets().await))
+}
+
+/// 24-hour ticker stats: change against the price a day ago, with the high and low in between
+/// Computed from the 1-minute candles and latest ticks, so right after a restart they cover less than a day (see `since`)
+#[utoipa::path(get, path = "/api/price/stats", tag = "prices", params(TickerQuery),
+    responses(
+        (status = 200, description = "Last price, open, high, low and change over the last 24 hours", body = TickerStats),
+        (status = 304, description = "Unchanged since the `ETag` / `Last-Modified` the client sent"),
+        (status = 404, description = "No price data for the asset", body = ErrorBody),
+    ))]
+pub async fn get_ticker_stats(
+    State(state): State<AppState>,
+    Query(query): Query<TickerQuery>,
+    RawQuery(raw_query): RawQuery,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let asset = query.asset.unwrap_or_else(|| "BTC".to_string());
+
+    let validators = state
+        .get_latest_price_time(&asset)
+        .await
+        .map(|latest| Validators::new(raw_query.as_deref().unwrap_or_default(), latest));
+    if let Some(validators) = validators.as_ref().filter(|v| v.is_fresh(&headers)) {
+        return Ok(validators.not_modified());
+    }
+
+    let stats = price_service::current_ticker(&state, &asset).await.ok_or_else(|| {
+        ApiError::not_found(format!("No price data found for asset: {}", asset)).with_code("no_price_data")
+    })?;
+
+    let body = Json(stats);
+    Ok(match validators {
+        Some(validators) => validators.apply(body),
+        None => body.into_response(),
+    })
+}
+
+#[utoipa::path(get, path = "/api/price/history", tag = "prices", params(AssetQuery, PageQuery),
+    responses((status = 200, description = "Close prices for the timeframe, oldest first unless `sort=desc`", body = PriceHistoryResponse),
+        (status = 304, description = "Unchanged since the `ETag` / `Last-Modified` the client sent")))]
 pub async fn get_price_history(
     State(state): State<AppState>,
     Query(query): Query<AssetQuery>,
-) -> Json<PriceHistoryResponse> {
+    Query(page): Query<PageQuery>,
+    RawQuery(raw_query): RawQuery,
+    headers: HeaderMap,
+) -> Response {
     let asset = query.asset.unwrap_or_else(|| "BTC".to_string());
     let timeframe = query.timeframe.as_deref().unwrap_or("1h");
 
+    let validators = state
+        .get_latest_price_time(&asset)
+        .await
+        .map(|latest| Validators::new(raw_query.as_deref().unwrap_or_default(), latest));
+    if let Some(validators) = validators.as_ref().filter(|v| v.is_fresh(&headers)) {
+        return validators.not_modified();
+    }
+
     tracing::info!(
         "Price history request: asset={}, timeframe={}",
         asset,
         timeframe
     );
 
-    // For 1h: use high-frequency 5-second data (720 points)
+    // For 1h: use high-frequency 5-second data (720 points, fewer if the window is shorter)
     // For 8h/24h: use low-frequency 5-minute candles (96 or 288 points)
+    // For 7d/30d: use closes of 15-minute / 1-hour candles (672 or 720 points)
     let prices: Vec<PricePoint> = match timeframe {
         "1h" => {
-            let price_window = state.get_price_window(&asset, 720).await;
+            let points = state.price_window_config.points_for(chrono::Duration::hours(1));
+            let price_window = state.get_price_window(&asset, points).await;
             price_window
                 .iter()
                 .map(|p| PricePoint {
@@ -101,9 +185,32 @@ pub async fn get_price_history(
                 })
                 .collect()
         }
+        "7d" => {
+            // 7 days of 15-minute candles = 672 candles
+            let ohlc_candles = state.get_ohlc_candles_15m(&asset, 672).await;
+            ohlc_candles
+                .iter()
+                .map(|c| PricePoint {
+                    timestamp: c.timestamp.timestamp(),
+                    price: c.close,
+                })
+                .collect()
+        }
+        "30d" => {
+            // 30 days of 1-hour candles = 720 candles
+            let ohlc_candles = state.get_ohlc_candles_1h(&asset, 720).await;
+            ohlc_candles
+                .iter()
+                .map(|c| PricePoint {
+                    timestamp: c.timestamp.timestamp(),
+                    price: c.close,
+                })
+                .collect()
+        }
         _ => {
             // Default to 1h
-            let price_window = state.get_price_window(&asset, 720).await;
+            let points = state.price_window_config.points_for(chrono::Duration::hours(1));
+            let price_window = state.get_price_window(&asset, points).await;
             price_window
                 .iter()
                 .map(|p| PricePoint {
@@ -121,19 +228,42 @@ pub async fn get_price_history(
         timeframe
     );
 
-    Json(PriceHistoryResponse {
+    let page = page
+        .resolve(MAX_SERIES_LIMIT, MAX_SERIES_LIMIT, SortOrder::Asc)
+        .slice(prices, |p| p.timestamp);
+
+    let body = Json(PriceHistoryResponse {
         asset: asset.clone(),
-        prices,
-    })
+        prices: page.items,
+        next_cursor: page.next_cursor,
+    });
+    match validators {
+        Some(validators) => validators.apply(body),
+        None => body.into_response(),
+    }
 }
 
+#[utoipa::path(get, path = "/api/price/candles", tag = "prices", params(AssetQuery, PageQuery),
+    responses((status = 200, description = "OHLC candles for the timeframe, oldest first unless `sort=desc`", body = CandleHistoryResponse),
+        (status = 304, description = "Unchanged since the `ETag` / `Last-Modified` the client sent")))]
 pub async fn get_candle_history(
     State(state): State<AppState>,
     Query(query): Query<AssetQuery>,
-) -> Json<CandleHistoryResponse> {
+    Query(page): Query<PageQuery>,
+    RawQuery(raw_query): RawQuery,
+    headers: HeaderMap,
+) -> Response {
     let asset = query.asset.unwrap_or_else(|| "BTC".to_string());
     let timeframe = query.timeframe.as_deref().unwrap_or("1h");
 
+    let validators = state
+        .get_latest_price_time(&asset)
+        .await
+        .map(|latest| Validators::new(raw_query.as_deref().unwrap_or_default(), latest));
+    if let Some(validators) = validators.as_ref().filter(|v| v.is_fresh(&headers)) {
+        return validators.not_modified();
+    }
+
     tracing::info!(
         "Candle history request: asset={}, timeframe={}",
         asset,
@@ -142,6 +272,7 @@ pub async fn get_candle_history(
 
     // For 1h: use 1-minute OHLC candles (60 candles)
     // For 8h/24h: use 5-minute OHLC candles (96 or 288 candles)
+    // For 7d/30d: use 15-minute / 1-hour OHLC candles (672 or 720 candles)
     let candles: Vec<CandleResponse> = match timeframe {
         "1h" => {
             let ohlc_candles = state.get_ohlc_candles_1m(&asset, 60).await;
@@ -184,6 +315,34 @@ pub async fn get_candle_history(
                 })
                 .collect()
         }
+        "7d" => {
+            // 7 days of 15-minute candles = 672 candles
+            let ohlc_candles = state.get_ohlc_candles_15m(&asset, 672).await;
+            ohlc_candles
+                .iter()
+                .map(|c| CandleResponse {
+                    timestamp: c.timestamp.timestamp(),
+                    open: c.open,
+                    high: c.high,
+                    low: c.low,
+                    close: c.close,
+                })
+                .collect()
+        }
+        "30d" => {
+            // 30 days of 1-hour candles = 720 candles
+            let ohlc_candles = state.get_ohlc_candles_1h(&asset, 720).await;
+            ohlc_candles
+                .iter()
+                .map(|c| CandleResponse {
+                    timestamp: c.timestamp.timestamp(),
+                    open: c.open,
+                    high: c.high,
+                    low: c.low,
+                    close: c.close,
+                })
+                .collect()
+        }
         _ => {
             // Default to 1h
             let ohlc_candles = state.get_ohlc_candles_1m(&asset, 60).await;
@@ -207,8 +366,17 @@ pub async fn get_candle_history(
         timeframe
     );
 
-    Json(CandleHistoryResponse {
+    let page = page
+        .resolve(MAX_SERIES_LIMIT, MAX_SERIES_LIMIT, SortOrder::Asc)
+        .slice(candles, |c| c.timestamp);
+
+    let body = Json(CandleHistoryResponse {
         asset: asset.clone(),
-        candles,
-    })
+        candles: page.items,
+        next_cursor: page.next_cursor,
+    });
+    match validators {
+        Some(validators) => validators.apply(body),
+        None => body.into_response(),
+    }
 }
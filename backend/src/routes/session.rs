@@ -0,0 +1,238 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::UserId;
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::session_service;
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SessionQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SessionEntry {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>, // To the minute; None if not used since this was tracked
+    pub current: bool, // True for the session that made this request
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SessionUserResponse {
+    pub user_id: UserId,
+    pub username: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RevokeResponse {
+    pub success: bool,
+    pub revoked: u64,
+}
+
+fn missing_token() -> ApiError {
+    ApiError::unauthorized("Missing bearer token")
+}
+
+/// Verify the bearer session token belongs to `user_id`
+/// User ids show up in follows and leaderboards, so routes that expose or change an account
+/// can't take the id alone as proof of who is asking
+pub async fn require_session(state: &AppState, headers: &HeaderMap, user_id: &UserId) -> ApiResult<()> {
+    require_session_token(state, session_service::bearer_token(headers), user_id).await
+}
+
+/// `require_session` for a token that may come from elsewhere, e.g. `?token=` on a plain download link
+pub async fn require_session_token(state: &AppState, token: Option<&str>, user_id: &UserId) -> ApiResult<()> {
+    let token = token.ok_or_else(missing_token)?;
+    let session_user = session_service::resolve(state.db.pool(), token)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Session expired or revoked"))?;
+    if &session_user != user_id {
+        return Err(ApiError::forbidden("This session belongs to another user").with_code("session_mismatch"));
+    }
+    Ok(())
+}
+
+/// `require_session` for a session signed in within the last `REAUTH_WINDOW_MINUTES`,
+/// confirming who is asking where an account has no password to re-enter
+pub async fn require_fresh_session(state: &AppState, headers: &HeaderMap, user_id: &UserId) -> ApiResult<()> {
+    require_session(state, headers, user_id).await?;
+    let token = session_service::bearer_token(headers).ok_or_else(missing_token)?;
+    let signed_in_at = session_service::signed_in_at(state.db.pool(), token)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Session expired or revoked"))?;
+    if Utc::now() - signed_in_at > Duration::minutes(session_service::REAUTH_WINDOW_MINUTES) {
+        return Err(ApiError::unauthorized("Sign in again to confirm").with_code("reauthentication_required"));
+    }
+    Ok(())
+}
+
+/// Resolve the bearer token to its user, so a stored token survives reloads and restarts
+#[utoipa::path(get, path = "/api/session", tag = "account", security(("session_token" = [])),
+    responses(
+        (status = 200, description = "The session's user", body = SessionUserResponse),
+        (status = 401, description = "Missing, expired or revoked token", body = ErrorBody),
+    ))]
+pub async fn current_session(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<SessionUserResponse>> {
+    let token = session_service::bearer_token(&headers).ok_or_else(missing_token)?;
+
+    let user_id = session_service::resolve(state.db.pool(), token)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Session expired or revoked"))?;
+
+    let user = state
+        .get_user(&user_id)
+        .await
+        .ok_or_else(|| ApiError::unauthorized("Session expired or revoked"))?;
+
+    Ok(Json(SessionUserResponse {
+        user_id,
+        username: user.username,
+    }))
+}
+
+/// End the session making the request
+#[utoipa::path(post, path = "/api/logout", tag = "account", security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Session revoked", body = RevokeResponse),
+        (status = 401, description = "Missing token", body = ErrorBody),
+    ))]
+pub async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<RevokeResponse>> {
+    let token = session_service::bearer_token(&headers).ok_or_else(missing_token)?;
+
+    let user_id = session_service::resolve(state.db.pool(), token).await?;
+    let revoked = session_service::revoke_token(state.db.pool(), token)
+        .await?;
+
+    if revoked {
+        audit_service::record(state.db.pool(), user_id.as_deref(), AuditAction::Logout, String::new());
+    }
+
+    Ok(Json(RevokeResponse {
+        success: true,
+        revoked: revoked as u64,
+    }))
+}
+
+/// List the user's active sessions (the API tokens issued at login) with when each was created and last used
+#[utoipa::path(get, path = "/api/sessions", tag = "account", params(SessionQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "Active sessions, newest first", body = Vec<SessionEntry>),
+        (status = 401, description = "Missing, expired or revoked token", body = ErrorBody),
+        (status = 403, description = "The session is another user's", body = ErrorBody),
+    ))]
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    Query(query): Query<SessionQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Json<Vec<SessionEntry>>> {
+    require_session(&state, &headers, &query.user_id).await?;
+
+    let sessions = session_service::list(
+        state.db.pool(),
+        &query.user_id,
+        session_service::bearer_token(&headers),
+    )
+    .await?;
+
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|(session, current)| SessionEntry {
+                id: session.id,
+                user_agent: session.user_agent,
+                ip_address: session.ip_address,
+                created_at: session.created_at,
+                expires_at: session.expires_at,
+                last_used_at: session.last_used_at,
+                current,
+            })
+            .collect(),
+    ))
+}
+
+/// Revoke a single session (e.g. a lost device)
+#[utoipa::path(delete, path = "/api/sessions/{session_id}", tag = "account", security(("session_token" = [])),
+    params(("session_id" = String, Path, description = "Session to revoke"), SessionQuery),
+    responses(
+        (status = 200, description = "Session revoked", body = RevokeResponse),
+        (status = 401, description = "Missing, expired or revoked token", body = ErrorBody),
+        (status = 403, description = "The session is another user's", body = ErrorBody),
+        (status = 404, description = "No such session", body = ErrorBody),
+    ))]
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    Query(query): Query<SessionQuery>,
+) -> ApiResult<Json<RevokeResponse>> {
+    require_session(&state, &headers, &query.user_id).await?;
+
+    let revoked = queries::delete_session(state.db.pool(), &query.user_id, &session_id)
+        .await?;
+
+    if !revoked {
+        return Err(ApiError::not_found("Session not found"));
+    }
+
+    audit_service::record(
+        state.db.pool(),
+        Some(&query.user_id),
+        AuditAction::SessionsRevoked,
+        format!("session={}", session_id),
+    );
+
+    Ok(Json(RevokeResponse {
+        success: true,
+        revoked: 1,
+    }))
+}
+
+/// Log out everywhere: revoke all of the user's sessions
+#[utoipa::path(delete, path = "/api/sessions", tag = "account", params(SessionQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "All of the user's sessions revoked", body = RevokeResponse),
+        (status = 401, description = "Missing, expired or revoked token", body = ErrorBody),
+        (status = 403, description = "The session is another user's", body = ErrorBody),
+    ))]
+pub async fn revoke_all_sessions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<SessionQuery>,
+) -> ApiResult<Json<RevokeResponse>> {
+    require_session(&state, &headers, &query.user_id).await?;
+
+    let revoked = queries::delete_sessions_for_user(state.db.pool(), &query.user_id)
+        .await?;
+
+    audit_service::record(
+        state.db.pool(),
+        Some(&query.user_id),
+        AuditAction::SessionsRevoked,
+        format!("all ({})", revoked),
+    );
+
+    Ok(Json(RevokeResponse {
+        success: true,
+        revoked,
+    }))
+}
@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::UserId;
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::settings_service;
+use crate::state::AppState;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SettingsQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserSettings {
+    #[schema(value_type = Object)]
+    pub settings: Map<String, Value>, // e.g. {"chart_indicators": ["sma_20", "ema_12"], "theme": "dark"}
+    pub updated_at: Option<DateTime<Utc>>, // None until the first change
+}
+
+/// The user's settings; an empty object until they save any
+#[utoipa::path(get, path = "/api/settings", tag = "account", params(SettingsQuery),
+    responses((status = 200, description = "The user's settings", body = UserSettings)))]
+pub async fn get_settings(
+    State(state): State<AppState>,
+    Query(query): Query<SettingsQuery>,
+) -> ApiResult<Json<UserSettings>> {
+    let stored = queries::get_user_settings(state.db.pool(), &query.user_id).await?;
+    let (settings, updated_at) = match stored {
+        Some((settings, updated_at)) => (settings, Some(updated_at)),
+        None => (Map::new(), None),
+    };
+
+    Ok(Json(UserSettings { settings, updated_at }))
+}
+
+/// Change some settings: each key in the body replaces the stored value, `null` removes it,
+/// and keys left out are kept. Clients should only send the keys they own
+#[utoipa::path(patch, path = "/api/settings", tag = "account", params(SettingsQuery),
+    request_body(content = Object, description = "Settings to change, by name"),
+    responses(
+        (status = 200, description = "All of the user's settings after the change", body = UserSettings),
+        (status = 400, description = "Invalid setting name, or too many or too large settings", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn update_settings(
+    State(state): State<AppState>,
+    Query(query): Query<SettingsQuery>,
+    Json(patch): Json<Map<String, Value>>,
+) -> ApiResult<Json<UserSettings>> {
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let pool = state.db.pool();
+    let stored = queries::get_user_settings(pool, &query.user_id).await?;
+    let (mut settings, updated_at) = match stored {
+        Some((settings, updated_at)) => (settings, Some(updated_at)),
+        None => (Map::new(), None),
+    };
+
+    let changed = settings_service::apply_patch(&mut settings, patch)
+        .map_err(|message| ApiError::bad_request(message).with_code("invalid_settings"))?;
+    if changed.is_empty() {
+        return Ok(Json(UserSettings { settings, updated_at }));
+    }
+
+    let now = Utc::now();
+    queries::save_user_settings(pool, &query.user_id, &settings, now.timestamp()).await?;
+    audit_service::record(pool, Some(&query.user_id), AuditAction::SettingsChanged, changed.join(", "));
+
+    Ok(Json(UserSettings {
+        settings,
+        updated_at: Some(now),
+    }))
+}
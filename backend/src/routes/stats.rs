@@ -0,0 +1,11 @@
+use axum::{extract::State, Json};
+
+use crate::services::stats_service::{self, PlatformStats};
+use crate::state::AppState;
+
+/// Public platform statistics for the stats page (no per-user or infrastructure details)
+#[utoipa::path(get, path = "/api/stats", tag = "stats",
+    responses((status = 200, description = "Users, bots, trading activity and price feed health", body = PlatformStats)))]
+pub async fn public_stats(State(state): State<AppState>) -> Json<PlatformStats> {
+    Json(stats_service::platform_stats(&state).await)
+}
@@ -0,0 +1,310 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::IntoParams;
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+
+use crate::models::PricePoint;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::services::session_service;
+use crate::models::{BotId, UserId};
+use crate::state::{AppState, BalanceChange, BalanceEvent, BotEvent, Notification};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PriceStreamQuery {
+    pub assets: Option<String>, // comma-separated, e.g. "BTC,ETH" (default: every polled asset)
+}
+
+/// Sent by the client to change its subscription after connecting
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum PriceStreamRequest {
+    Subscribe { assets: Vec<String> },
+    Unsubscribe { assets: Vec<String> },
+}
+
+#[derive(Serialize)]
+struct PriceUpdate<'a> {
+    asset: &'a str,
+    price: f64,
+    timestamp: i64,
+}
+
+fn parse_assets(list: &str) -> HashSet<String> {
+    list.split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Live price updates over a WebSocket, replacing polling of `/api/price`
+/// Each subscribed asset's latest price is sent on connect, then every new price as it arrives.
+/// Clients can send `{"action":"subscribe","assets":["BTC"]}` (or `unsubscribe`) at any time
+#[utoipa::path(get, path = "/api/ws/prices", tag = "streams", params(PriceStreamQuery),
+    responses((status = 101, description = "WebSocket upgrade; sends `{asset, price, timestamp}` messages")))]
+pub async fn price_stream(
+    State(state): State<AppState>,
+    Query(query): Query<PriceStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let assets = match query.assets.as_deref() {
+        Some(list) => parse_assets(list),
+        None => state.polled_assets().await.into_iter().collect(),
+    };
+
+    ws.on_upgrade(move |socket| stream_prices(socket, state, assets))
+}
+
+/// Returns false once the client is gone
+async fn send_json<T: Serialize>(socket: &mut WebSocket, message: &T) -> bool {
+    match serde_json::to_string(message) {
+        Ok(json) => socket.send(Message::Text(json)).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+async fn send_price(socket: &mut WebSocket, point: &PricePoint) -> bool {
+    let update = PriceUpdate {
+        asset: &point.asset,
+        price: point.price,
+        timestamp: point.timestamp.timestamp(),
+    };
+    send_json(socket, &update).await
+}
+
+async fn stream_prices(mut socket: WebSocket, state: AppState, mut assets: HashSet<String>) {
+    // Subscribe before sending the latest prices so nothing arrives in between unseen
+    let mut feed = state.price_feed.subscribe();
+
+    for asset in &assets {
+        if let Some(point) = state.get_price_window(asset, 1).await.pop() {
+            if !send_price(&mut socket, &point).await {
+                return;
+            }
+        }
+    }
+
+    loop {
+        tokio::select! {
+            update = feed.recv() => match update {
+                Ok(point) => {
+                    if assets.contains(&point.asset) && !send_price(&mut socket, &point).await {
+                        break;
+                    }
+                }
+                // A slow client missed some updates; the next one carries the current price
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Price stream client lagged, skipped {} updates", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(PriceStreamRequest::Subscribe { assets: added }) => {
+                        for asset in added {
+                            let asset = asset.to_uppercase();
+                            if let Some(point) = state.get_price_window(&asset, 1).await.pop() {
+                                if !send_price(&mut socket, &point).await {
+                                    return;
+                                }
+                            }
+                            assets.insert(asset);
+                        }
+                    }
+                    Ok(PriceStreamRequest::Unsubscribe { assets: removed }) => {
+                        for asset in removed {
+                            assets.remove(&asset.to_uppercase());
+                        }
+                    }
+                    Err(e) => {
+                        let error = serde_json::json!({ "error": format!("Invalid request: {}", e) });
+                        if socket.send(Message::Text(error.to_string())).await.is_err() {
+                            break;
+                        }
+                    }
+                },
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => {} // Pings are answered automatically
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventStreamQuery {
+    pub token: Option<String>, // Session token, for clients (EventSource, WebSocket) that can't set headers
+}
+
+/// User owning the session token from the `Authorization` header or `?token=`
+async fn authenticate(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: &EventStreamQuery,
+) -> ApiResult<UserId> {
+    let token = session_service::bearer_token(headers)
+        .or(query.token.as_deref())
+        .ok_or_else(|| ApiError::unauthorized("Missing session token"))?;
+
+    let user_id = session_service::resolve(state.db.pool(), token)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Session expired or revoked"))?;
+    // Header tokens are recorded by the session activity middleware
+    if session_service::bearer_token(headers).is_none() {
+        session_service::touch(state.db.pool(), token);
+    }
+    Ok(user_id)
+}
+
+fn balance_event(event: &BalanceEvent) -> Event {
+    Event::default()
+        .event("balance")
+        .json_data(event)
+        .unwrap_or_else(|_| Event::default().event("balance"))
+}
+
+fn notification_event(notification: &Notification) -> Event {
+    Event::default()
+        .event("notification")
+        .json_data(notification)
+        .unwrap_or_else(|_| Event::default().event("notification"))
+}
+
+/// Server-sent events for the authenticated user's balances and notifications
+/// Sends the current balances on connect, then a `balance` event after every trade, bot fill,
+/// deposit, withdrawal or reset. A `resync` event means updates were missed and the client
+/// should refetch its portfolio. `notification` events carry fired price alerts
+#[utoipa::path(get, path = "/api/events", tag = "streams", params(EventStreamQuery), security(("session_token" = [])),
+    responses(
+        (status = 200, description = "`text/event-stream` of `balance`, `resync` and `notification` events", content_type = "text/event-stream"),
+        (status = 401, description = "Missing, expired or revoked token", body = ErrorBody),
+    ))]
+pub async fn portfolio_events(
+    State(state): State<AppState>,
+    Query(query): Query<EventStreamQuery>,
+    headers: HeaderMap,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let user_id = authenticate(&state, &headers, &query).await?;
+
+    // Subscribe before reading the balances so no change falls in between
+    let updates = BroadcastStream::new(state.balance_events.subscribe());
+    let notifications = BroadcastStream::new(state.notifications.subscribe());
+
+    let user = state
+        .get_user(&user_id)
+        .await
+        .ok_or_else(|| ApiError::unauthorized("Session expired or revoked"))?;
+    let initial = BalanceEvent {
+        user_id: user_id.clone(),
+        reason: BalanceChange::Initial,
+        asset_balances: user.asset_balances,
+        trade: None,
+    };
+
+    let balance_user = user_id.clone();
+    let updates = updates.filter_map(move |update| match update {
+        Ok(event) if event.user_id == balance_user => Some(Ok(balance_event(&event))),
+        Ok(_) => None,
+        Err(BroadcastStreamRecvError::Lagged(_)) => Some(Ok(Event::default().event("resync").data("{}"))),
+    });
+    // Missed notifications stay available from their history endpoints
+    let notifications = notifications.filter_map(move |notification| match notification {
+        Ok(notification) if notification.user_id == user_id => Some(Ok(notification_event(&notification))),
+        _ => None,
+    });
+
+    let stream = tokio_stream::once(Ok(balance_event(&initial))).chain(updates.merge(notifications));
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[derive(Serialize)]
+struct BotStatusMessage {
+    event: &'static str, // Always "status"
+    running: bool,
+    bots: Vec<RunningBotMessage>,
+}
+
+#[derive(Serialize)]
+struct RunningBotMessage {
+    bot_instance_id: BotId,
+    bot_name: String,
+    tick_count: u64,
+}
+
+/// Live activity feed of the authenticated user's bots over a WebSocket, each event tagged with its `bot_instance_id`
+/// Sends a `status` message listing the running bots on connect, then every start, tick, executed trade,
+/// stoploss hit, error and stop as it happens
+#[utoipa::path(get, path = "/api/ws/bot", tag = "streams", params(EventStreamQuery), security(("session_token" = [])),
+    responses(
+        (status = 101, description = "WebSocket upgrade; sends a `status` message, then bot events"),
+        (status = 401, description = "Missing, expired or revoked token", body = ErrorBody),
+    ))]
+pub async fn bot_activity_stream(
+    State(state): State<AppState>,
+    Query(query): Query<EventStreamQuery>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> ApiResult<Response> {
+    let user_id = authenticate(&state, &headers, &query).await?;
+    Ok(ws.on_upgrade(move |socket| stream_bot_activity(socket, state, user_id)))
+}
+
+async fn stream_bot_activity(mut socket: WebSocket, state: AppState, user_id: UserId) {
+    // Subscribe before reading the status so no event falls in between
+    let mut events = state.bot_events.subscribe();
+
+    let status = {
+        let bots: Vec<RunningBotMessage> = state
+            .bots
+            .read()
+            .await
+            .iter()
+            .filter(|(_, bot)| bot.user_id == user_id)
+            .map(|(bot_id, bot)| RunningBotMessage {
+                bot_instance_id: bot_id.clone(),
+                bot_name: bot.bot_name.clone(),
+                tick_count: bot.runtime.tick_count,
+            })
+            .collect();
+        BotStatusMessage { event: "status", running: !bots.is_empty(), bots }
+    };
+    if !send_json(&mut socket, &status).await {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if event.user_id == user_id && !send_json::<BotEvent>(&mut socket, &event).await {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Bot activity client lagged, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => {} // The feed is one-way; pings are answered automatically
+            },
+        }
+    }
+}
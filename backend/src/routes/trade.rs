@@ -1,8 +1,11 @@
-use crate::{models::*, services::trading_service::{self, TradeError}, state::AppState};
-use axum::{extract::{State, Query}, http::StatusCode, Json};
+use crate::{db::queries, error::{self, ApiError, ApiResult, ErrorBody}, models::*, pagination::{Page, PageQuery, SortOrder}, services::trading_service::{self, TradeError}, state::AppState};
+use crate::services::ledger_service::{self, PeriodStatement};
+use crate::services::timezone_service::{self, Period};
+use axum::{extract::{State, Query}, Json};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct TradeRequest {
     pub asset: String,           // base_asset for backward compatibility
     #[serde(default)]
@@ -11,31 +14,64 @@ pub struct TradeRequest {
     pub quantity: f64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct DepositRequest {
     pub amount: f64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct WithdrawalRequest {
     pub amount: f64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TradeQuery {
     pub user_id: String,
 }
 
-#[derive(Serialize)]
-pub struct TradeErrorResponse {
-    pub error: String,
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TradeHistoryQuery {
+    pub user_id: String,
+    pub source: Option<TradeSourceKind>, // "manual", "bot" or "copy"; every trade if missing
+    pub bot_instance_id: Option<BotId>,  // Only this bot instance's trades
+}
+
+const DEFAULT_TRADES_LIMIT: i64 = 50;
+const MAX_TRADES_LIMIT: i64 = 500;
+const RECENT_TRANSFERS: usize = 50;
+
+/// Cash moved in and out of the account
+#[derive(Serialize, ToSchema)]
+pub struct FundsSummary {
+    pub usd_balance: f64,
+    pub lifetime_funding: f64,     // Starting balance plus every deposit
+    pub lifetime_deposits: f64,
+    pub lifetime_withdrawals: f64,
+    pub net_funding: f64,          // Funding minus withdrawals
+    pub transfers: Vec<Trade>,     // Latest deposits and withdrawals, newest first
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TradeHistoryEntry {
+    pub id: i64,
+    #[serde(flatten)]
+    pub trade: Trade,
 }
 
+#[utoipa::path(post, path = "/api/trade", tag = "trading", params(TradeQuery), request_body = TradeRequest,
+    responses(
+        (status = 200, description = "The executed trade", body = Trade),
+        (status = 400, description = "Invalid trade (balance, price, amount)", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+        (status = 500, description = "The trade could not be saved", body = ErrorBody),
+    ))]
 pub async fn post_trade(
     State(state): State<AppState>,
     Query(query): Query<TradeQuery>,
     Json(req): Json<TradeRequest>,
-) -> Result<Json<Trade>, (StatusCode, Json<TradeErrorResponse>)> {
+) -> ApiResult<Json<Trade>> {
     let base_asset = &req.asset;
     let quote_asset = req.quote_asset.as_deref().unwrap_or("USD");
 
@@ -50,65 +86,192 @@ pub async fn post_trade(
     .await
     {
         Ok(trade) => Ok(Json(trade)),
-        Err(err) => {
-            let error_msg = match err {
-                TradeError::InsufficientFunds => format!("Insufficient {} to complete this purchase", quote_asset),
-                TradeError::InsufficientAssets => format!("Insufficient {} to complete this sale", base_asset),
-                TradeError::InvalidQuantity => "Invalid quantity specified".to_string(),
-                TradeError::UserNotFound => "User not found".to_string(),
-                TradeError::PriceUnavailable => "Price unavailable for this trading pair".to_string(),
-                TradeError::DepositTooSmall => "Deposit must be at least $10".to_string(),
-                TradeError::DepositTooLarge => "Deposit cannot exceed $100,000".to_string(),
-                TradeError::WithdrawalExceedsBalance => "Insufficient balance for withdrawal".to_string(),
-            };
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(TradeErrorResponse { error: error_msg }),
-            ))
-        }
+        Err(err) => Err(trade_failure(&err, base_asset, quote_asset)),
     }
 }
 
+/// API error for a failed trade on a pair
+pub fn trade_failure(err: &TradeError, base_asset: &str, quote_asset: &str) -> error::ApiError {
+    let error_msg = match err {
+        TradeError::InsufficientFunds => format!("Insufficient {} to complete this purchase", quote_asset),
+        TradeError::InsufficientAssets => format!("Insufficient {} to complete this sale", base_asset),
+        TradeError::InvalidQuantity => "Invalid quantity specified".to_string(),
+        TradeError::UserNotFound => "User not found".to_string(),
+        TradeError::PriceUnavailable(e) => format!("Price unavailable for {}/{}: {}", base_asset, quote_asset, e),
+        TradeError::DepositTooSmall => "Deposit must be at least $10".to_string(),
+        TradeError::DepositTooLarge => "Deposit cannot exceed $100,000".to_string(),
+        TradeError::WithdrawalExceedsBalance => "Insufficient balance for withdrawal".to_string(),
+        TradeError::PersistenceFailed => "Trade could not be saved, please try again".to_string(),
+    };
+    error::trade_error(err, error_msg)
+}
+
+#[utoipa::path(post, path = "/api/deposit", tag = "trading", params(TradeQuery), request_body = DepositRequest,
+    responses(
+        (status = 200, description = "The recorded deposit", body = Trade),
+        (status = 400, description = "Amount out of range", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
 pub async fn post_deposit(
     State(state): State<AppState>,
     Query(query): Query<TradeQuery>,
     Json(req): Json<DepositRequest>,
-) -> Result<Json<Trade>, (StatusCode, Json<TradeErrorResponse>)> {
+) -> ApiResult<Json<Trade>> {
     match trading_service::deposit(&state, &query.user_id, req.amount).await {
         Ok(transaction) => Ok(Json(transaction)),
         Err(err) => {
-            let error_msg = match err {
+            let error_msg = match &err {
                 TradeError::DepositTooSmall => "Deposit must be at least $10".to_string(),
                 TradeError::DepositTooLarge => "Deposit cannot exceed $100,000".to_string(),
                 TradeError::UserNotFound => "User not found".to_string(),
+                TradeError::PersistenceFailed => "Deposit could not be saved, please try again".to_string(),
                 _ => "Deposit failed".to_string(),
             };
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(TradeErrorResponse { error: error_msg }),
-            ))
+            Err(error::trade_error(&err, error_msg))
         }
     }
 }
 
+#[utoipa::path(post, path = "/api/withdrawal", tag = "trading", params(TradeQuery), request_body = WithdrawalRequest,
+    responses(
+        (status = 200, description = "The recorded withdrawal", body = Trade),
+        (status = 400, description = "Amount out of range or insufficient balance", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
 pub async fn post_withdrawal(
     State(state): State<AppState>,
     Query(query): Query<TradeQuery>,
     Json(req): Json<WithdrawalRequest>,
-) -> Result<Json<Trade>, (StatusCode, Json<TradeErrorResponse>)> {
+) -> ApiResult<Json<Trade>> {
     match trading_service::withdraw(&state, &query.user_id, req.amount).await {
         Ok(transaction) => Ok(Json(transaction)),
         Err(err) => {
-            let error_msg = match err {
+            let error_msg = match &err {
                 TradeError::WithdrawalExceedsBalance => "Insufficient balance for withdrawal".to_string(),
                 TradeError::InvalidQuantity => "Invalid withdrawal amount".to_string(),
                 TradeError::UserNotFound => "User not found".to_string(),
+                TradeError::PersistenceFailed => "Withdrawal could not be saved, please try again".to_string(),
                 _ => "Withdrawal failed".to_string(),
             };
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(TradeErrorResponse { error: error_msg }),
-            ))
+            Err(error::trade_error(&err, error_msg))
         }
     }
 }
+
+/// Lifetime funding and withdrawal totals, with the latest deposits and withdrawals
+/// Totals come from the in-memory history, so trades archived by retention are not counted
+#[utoipa::path(get, path = "/api/funds", tag = "trading", params(TradeQuery),
+    responses(
+        (status = 200, description = "The user's funding summary", body = FundsSummary),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn get_funds(
+    State(state): State<AppState>,
+    Query(query): Query<TradeQuery>,
+) -> ApiResult<Json<FundsSummary>> {
+    let user = state
+        .get_user(&query.user_id)
+        .await
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    let transfers: Vec<Trade> = user
+        .trade_history
+        .iter()
+        .rev()
+        .filter(|t| t.transaction_type != TransactionType::Trade)
+        .take(RECENT_TRANSFERS)
+        .cloned()
+        .collect();
+
+    let lifetime_funding = user.lifetime_funding(state.config.accounts.starting_balance);
+    Ok(Json(FundsSummary {
+        usd_balance: user.get_balance("USD"),
+        lifetime_funding,
+        lifetime_deposits: user.lifetime_deposits(),
+        lifetime_withdrawals: user.lifetime_withdrawals(),
+        net_funding: lifetime_funding - user.lifetime_withdrawals(),
+        transfers,
+    }))
+}
+
+/// Paginated trade history from the trades table, newest first unless `sort=asc`, optionally
+/// only manual, bot or copied trades, or one bot instance's
+#[utoipa::path(get, path = "/api/trades", tag = "trading", params(TradeHistoryQuery, PageQuery),
+    responses((status = 200, description = "A page of transactions; `next_cursor` is a trade id", body = Page<TradeHistoryEntry>)))]
+pub async fn get_trades(
+    State(state): State<AppState>,
+    Query(query): Query<TradeHistoryQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<TradeHistoryEntry>>> {
+    let page = page.resolve(DEFAULT_TRADES_LIMIT, MAX_TRADES_LIMIT, SortOrder::Desc);
+
+    let rows = queries::get_trades_page(
+        state.db.pool(),
+        &query.user_id,
+        query.source,
+        query.bot_instance_id.as_ref(),
+        page.cursor,
+        page.sort,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    let entries = rows
+        .into_iter()
+        .map(|(id, trade)| TradeHistoryEntry { id, trade })
+        .collect();
+
+    Ok(Json(page.finish(entries, |entry| entry.id)))
+}
+
+/// Paginated ledger statement: every credit and debit to the account's balances, newest first unless `sort=asc`
+#[utoipa::path(get, path = "/api/ledger", tag = "trading", params(TradeQuery, PageQuery),
+    responses((status = 200, description = "A page of ledger entries; `next_cursor` is an entry id", body = Page<queries::LedgerEntry>)))]
+pub async fn get_ledger(
+    State(state): State<AppState>,
+    Query(query): Query<TradeQuery>,
+    Query(page): Query<PageQuery>,
+) -> ApiResult<Json<Page<queries::LedgerEntry>>> {
+    let page = page.resolve(DEFAULT_TRADES_LIMIT, MAX_TRADES_LIMIT, SortOrder::Desc);
+
+    let entries = queries::get_ledger_entries(state.db.pool(), &query.user_id, page.cursor, page.sort, page.fetch_limit())
+        .await?;
+
+    Ok(Json(page.finish(entries, |entry| entry.id)))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatementQuery {
+    pub user_id: String,
+    pub period: Option<String>, // "day", "week" or "month" (default)
+    pub limit: Option<usize>,   // Default 12, at most 366
+}
+
+const DEFAULT_STATEMENTS: usize = 12;
+const MAX_STATEMENTS: usize = 366;
+
+/// Ledger statements per day, week or month of the user's local calendar (the `utc_offset_minutes` setting, UTC
+/// without it), newest first: each asset's opening and closing balance, credits and debits
+/// Periods without entries are left out
+#[utoipa::path(get, path = "/api/ledger/statements", tag = "trading", params(StatementQuery),
+    responses(
+        (status = 200, description = "Statements, newest first", body = Vec<PeriodStatement>),
+        (status = 400, description = "Unknown period", body = ErrorBody),
+    ))]
+pub async fn get_ledger_statements(
+    State(state): State<AppState>,
+    Query(query): Query<StatementQuery>,
+) -> ApiResult<Json<Vec<PeriodStatement>>> {
+    let period = query.period.as_deref().unwrap_or("month");
+    let period = Period::parse(period).ok_or_else(|| {
+        ApiError::bad_request(format!("Unknown period '{}' (use day, week or month)", period)).with_code("invalid_period")
+    })?;
+    let limit = query.limit.unwrap_or(DEFAULT_STATEMENTS).clamp(1, MAX_STATEMENTS);
+
+    let offset = timezone_service::user_offset(&state, &query.user_id).await;
+    let entries = queries::load_ledger(state.db.pool(), &query.user_id).await?;
+    let mut statements = ledger_service::statements(&entries, period, offset);
+    statements.truncate(limit);
+    Ok(Json(statements))
+}
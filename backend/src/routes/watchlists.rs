@@ -0,0 +1,210 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::db::queries::{self, WatchlistRow};
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{Asset, UserId};
+use crate::services::price_service::{self, MAX_POLLED_ASSETS};
+use crate::state::AppState;
+
+const MAX_WATCHLISTS_PER_USER: usize = 20;
+const MAX_ASSETS_PER_WATCHLIST: usize = 20;
+const MAX_NAME_LEN: usize = 50;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WatchlistQuery {
+    pub user_id: UserId,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WatchlistRequest {
+    pub name: String,
+    pub assets: Vec<String>, // Symbols quoted in USD on Coinbase, e.g. ["BTC", "SOL"]
+}
+
+/// A watched asset with its latest price
+#[derive(Serialize, ToSchema)]
+pub struct WatchlistQuote {
+    pub asset: Asset,
+    pub price: Option<f64>,          // None until the first price arrives
+    pub change_24h_pct: Option<f64>, // From the oldest price within the last 24 hours
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Watchlist {
+    pub id: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub assets: Vec<WatchlistQuote>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeleteWatchlistResponse {
+    pub success: bool,
+}
+
+/// Uppercased, de-duplicated symbols, in the order given
+fn normalize_assets(assets: &[String]) -> ApiResult<Vec<Asset>> {
+    let mut normalized: Vec<Asset> = Vec::new();
+    for asset in assets {
+        let asset = asset.trim().to_uppercase();
+        let valid = (2..=10).contains(&asset.len()) && asset.chars().all(|c| c.is_ascii_alphanumeric());
+        if !valid || asset == "USD" {
+            return Err(ApiError::bad_request(format!("Invalid asset symbol: {}", asset)).with_code("invalid_asset"));
+        }
+        if !normalized.contains(&asset) {
+            normalized.push(asset);
+        }
+    }
+
+    if normalized.is_empty() || normalized.len() > MAX_ASSETS_PER_WATCHLIST {
+        return Err(ApiError::bad_request(format!(
+            "A watchlist needs between 1 and {} assets",
+            MAX_ASSETS_PER_WATCHLIST
+        ))
+        .with_code("invalid_watchlist"));
+    }
+    Ok(normalized)
+}
+
+async fn quote(state: &AppState, asset: &str) -> WatchlistQuote {
+    let day = state.get_close_series(asset, chrono::Duration::hours(24)).await;
+    let price = state.get_latest_price(asset).await;
+    let change_24h_pct = match (day.first(), price) {
+        (Some(start), Some(price)) if start.price > 0.0 => Some((price - start.price) / start.price * 100.0),
+        _ => None,
+    };
+
+    WatchlistQuote {
+        asset: asset.to_string(),
+        price,
+        change_24h_pct,
+    }
+}
+
+async fn with_quotes(state: &AppState, row: WatchlistRow) -> Watchlist {
+    let mut assets = Vec::with_capacity(row.assets.len());
+    for asset in &row.assets {
+        assets.push(quote(state, asset).await);
+    }
+
+    Watchlist {
+        id: row.id,
+        name: row.name,
+        created_at: row.created_at,
+        assets,
+    }
+}
+
+/// The user's watchlists with the latest price and 24h change of every asset
+#[utoipa::path(get, path = "/api/watchlists", tag = "watchlists", params(WatchlistQuery),
+    responses((status = 200, description = "The user's watchlists, oldest first", body = Vec<Watchlist>)))]
+pub async fn list_watchlists(
+    State(state): State<AppState>,
+    Query(query): Query<WatchlistQuery>,
+) -> ApiResult<Json<Vec<Watchlist>>> {
+    let rows = queries::list_watchlists(state.db.pool(), &query.user_id).await?;
+
+    let mut watchlists = Vec::with_capacity(rows.len());
+    for row in rows {
+        watchlists.push(with_quotes(&state, row).await);
+    }
+    Ok(Json(watchlists))
+}
+
+/// Create a named watchlist; assets that aren't polled yet start polling right away
+#[utoipa::path(post, path = "/api/watchlists", tag = "watchlists", params(WatchlistQuery), request_body = WatchlistRequest,
+    responses(
+        (status = 200, description = "The new watchlist", body = Watchlist),
+        (status = 400, description = "Invalid name or asset, or an asset Coinbase doesn't quote", body = ErrorBody),
+        (status = 404, description = "User not found", body = ErrorBody),
+        (status = 409, description = "Duplicate name, or a watchlist or polling limit reached", body = ErrorBody),
+    ))]
+pub async fn create_watchlist(
+    State(state): State<AppState>,
+    Query(query): Query<WatchlistQuery>,
+    Json(req): Json<WatchlistRequest>,
+) -> ApiResult<Json<Watchlist>> {
+    let name = req.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(ApiError::bad_request(format!("Name must be 1 to {} characters", MAX_NAME_LEN))
+            .with_code("invalid_watchlist"));
+    }
+    let assets = normalize_assets(&req.assets)?;
+
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let existing = queries::list_watchlists(state.db.pool(), &query.user_id).await?;
+    if existing.len() >= MAX_WATCHLISTS_PER_USER {
+        return Err(ApiError::conflict(format!(
+            "You can have at most {} watchlists",
+            MAX_WATCHLISTS_PER_USER
+        ))
+        .with_code("watchlist_limit_reached"));
+    }
+
+    // Every listed asset must get a feed, and new ones must exist on Coinbase
+    let polled = state.polled_assets().await;
+    let mut watched = queries::watched_assets(state.db.pool()).await?;
+    watched.extend(assets.iter().cloned());
+    let wanted = price_service::wanted_assets(&state.config.prices.tracked_assets, &watched);
+
+    for asset in assets.iter().filter(|a| !polled.contains(a)) {
+        if !wanted.contains(asset) {
+            return Err(ApiError::conflict(format!(
+                "Can't start polling {}: at most {} assets are polled at once",
+                asset, MAX_POLLED_ASSETS
+            ))
+            .with_code("asset_limit_reached"));
+        }
+        if !price_service::quotes_asset(&state, asset).await {
+            return Err(ApiError::bad_request(format!("Coinbase has no USD price for {}", asset))
+                .with_code("unknown_asset"));
+        }
+    }
+
+    let row = WatchlistRow {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: query.user_id,
+        name,
+        assets,
+        created_at: Utc::now(),
+    };
+    if !queries::create_watchlist(state.db.pool(), &row).await? {
+        return Err(ApiError::conflict(format!("You already have a watchlist named {}", row.name))
+            .with_code("duplicate_watchlist"));
+    }
+
+    price_service::sync_price_feeds(&state).await;
+
+    Ok(Json(with_quotes(&state, row).await))
+}
+
+/// Delete a watchlist; assets no one else watches stop being polled
+#[utoipa::path(delete, path = "/api/watchlists/{watchlist_id}", tag = "watchlists",
+    params(("watchlist_id" = String, Path, description = "Watchlist to delete"), WatchlistQuery),
+    responses(
+        (status = 200, description = "Watchlist deleted", body = DeleteWatchlistResponse),
+        (status = 404, description = "No such watchlist", body = ErrorBody),
+    ))]
+pub async fn delete_watchlist(
+    State(state): State<AppState>,
+    Path(watchlist_id): Path<String>,
+    Query(query): Query<WatchlistQuery>,
+) -> ApiResult<Json<DeleteWatchlistResponse>> {
+    if !queries::delete_watchlist(state.db.pool(), &query.user_id, &watchlist_id).await? {
+        return Err(ApiError::not_found("Watchlist not found"));
+    }
+
+    price_service::sync_price_feeds(&state).await;
+
+    Ok(Json(DeleteWatchlistResponse { success: true }))
+}
@@ -0,0 +1,223 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::assets;
+use crate::bots::ExternalSignal;
+use crate::db::queries;
+use crate::error::{ApiError, ApiResult, ErrorBody};
+use crate::models::{Trade, UserId};
+use crate::routes::trade::trade_failure;
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::bot_service::{self, SignalError, SIGNAL_STRATEGY};
+use crate::services::{session_service, trading_service, webhook_service};
+use crate::state::AppState;
+
+const WEBHOOK_PATH: &str = "/api/webhooks/tradingview";
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WebhookQuery {
+    pub user_id: UserId,
+}
+
+/// What a webhook alert does
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookMode {
+    #[default]
+    Trade,  // Execute a simulated market trade immediately
+    Signal, // Queue the order for the user's running signal_follower bot
+}
+
+/// Body of a TradingView alert message, e.g.
+/// `{"secret": "...", "symbol": "{{ticker}}", "action": "{{strategy.order.action}}", "size": "{{strategy.order.contracts}}"}`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TradingViewAlert {
+    #[serde(alias = "passphrase")]
+    pub secret: String,
+    #[serde(alias = "ticker")]
+    pub symbol: String, // "BTCUSD", "COINBASE:BTCUSD", "BTC-USD", "ETHBTC"...
+    pub action: String, // "buy" or "sell"
+    #[serde(alias = "contracts")]
+    #[schema(value_type = f64)]
+    pub size: serde_json::Value, // Base asset quantity, as a number or numeric string
+    #[serde(default)]
+    pub mode: WebhookMode,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WebhookOutcome {
+    /// The trade was executed
+    Executed { trade: Trade },
+    /// The signal waits for the bot's next tick (within 60 seconds)
+    Queued { base_asset: String, quote_asset: String },
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WebhookStatus {
+    pub enabled: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub webhook_path: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WebhookSecretResponse {
+    pub secret: String, // Shown only once; put it in the alert message
+    pub webhook_path: String,
+    pub message_template: String, // Alert message to paste into TradingView
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DisableWebhookResponse {
+    pub success: bool,
+}
+
+fn message_template(secret: &str) -> String {
+    format!(
+        r#"{{"secret": "{}", "symbol": "{{{{ticker}}}}", "action": "{{{{strategy.order.action}}}}", "size": "{{{{strategy.order.contracts}}}}"}}"#,
+        secret
+    )
+}
+
+/// Whether the user's webhook is enabled
+#[utoipa::path(get, path = "/api/webhooks/tradingview/secret", tag = "webhooks", params(WebhookQuery),
+    responses((status = 200, description = "Webhook status", body = WebhookStatus)))]
+pub async fn webhook_status(
+    State(state): State<AppState>,
+    Query(query): Query<WebhookQuery>,
+) -> ApiResult<Json<WebhookStatus>> {
+    let created_at = queries::webhook_secret_created_at(state.db.pool(), &query.user_id).await?;
+
+    Ok(Json(WebhookStatus {
+        enabled: created_at.is_some(),
+        created_at,
+        webhook_path: WEBHOOK_PATH.to_string(),
+    }))
+}
+
+/// Enable the webhook, or rotate its secret; any previous secret stops working
+#[utoipa::path(post, path = "/api/webhooks/tradingview/secret", tag = "webhooks", params(WebhookQuery),
+    responses(
+        (status = 200, description = "The new secret, shown only this once", body = WebhookSecretResponse),
+        (status = 404, description = "User not found", body = ErrorBody),
+    ))]
+pub async fn rotate_secret(
+    State(state): State<AppState>,
+    Query(query): Query<WebhookQuery>,
+) -> ApiResult<Json<WebhookSecretResponse>> {
+    if state.get_user(&query.user_id).await.is_none() {
+        return Err(ApiError::not_found("User not found"));
+    }
+
+    let (secret, secret_hash) = webhook_service::issue_secret();
+    queries::set_webhook_secret(state.db.pool(), &query.user_id, &secret_hash, Utc::now().timestamp()).await?;
+    audit_service::record(state.db.pool(), Some(&query.user_id), AuditAction::WebhookEnabled, String::new());
+
+    Ok(Json(WebhookSecretResponse {
+        message_template: message_template(&secret),
+        secret,
+        webhook_path: WEBHOOK_PATH.to_string(),
+    }))
+}
+
+#[utoipa::path(delete, path = "/api/webhooks/tradingview/secret", tag = "webhooks", params(WebhookQuery),
+    responses(
+        (status = 200, description = "Webhook disabled", body = DisableWebhookResponse),
+        (status = 404, description = "Webhook was not enabled", body = ErrorBody),
+    ))]
+pub async fn disable_webhook(
+    State(state): State<AppState>,
+    Query(query): Query<WebhookQuery>,
+) -> ApiResult<Json<DisableWebhookResponse>> {
+    if !queries::delete_webhook_secret(state.db.pool(), &query.user_id).await? {
+        return Err(ApiError::not_found("Webhook is not enabled"));
+    }
+    audit_service::record(state.db.pool(), Some(&query.user_id), AuditAction::WebhookDisabled, String::new());
+
+    Ok(Json(DisableWebhookResponse { success: true }))
+}
+
+/// Receive a TradingView alert and trade on it for the secret's owner
+/// The body is parsed here rather than by the extractor because TradingView may send JSON as text/plain
+#[utoipa::path(post, path = "/api/webhooks/tradingview", tag = "webhooks", request_body = TradingViewAlert,
+    responses(
+        (status = 200, description = "Trade executed or signal queued", body = WebhookOutcome),
+        (status = 400, description = "Malformed alert, or the trade was refused", body = ErrorBody),
+        (status = 401, description = "Unknown secret", body = ErrorBody),
+        (status = 409, description = "No signal_follower bot is running on the symbol", body = ErrorBody),
+        (status = 429, description = "Rate limited; retry after the `Retry-After` header", body = ErrorBody),
+    ))]
+pub async fn tradingview_webhook(
+    State(state): State<AppState>,
+    body: String,
+) -> ApiResult<Json<WebhookOutcome>> {
+    let alert: TradingViewAlert = serde_json::from_str(&body).map_err(|e| {
+        ApiError::bad_request(format!("Alert message is not a valid webhook payload: {}", e))
+            .with_code("invalid_payload")
+    })?;
+
+    let user_id = queries::find_webhook_user(state.db.pool(), &session_service::hash_token(alert.secret.trim()))
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("Unknown webhook secret").with_code("invalid_secret"))?;
+
+    let registry = assets::registry(&state.polled_assets().await);
+    let (base_asset, quote_asset) = webhook_service::parse_symbol(&alert.symbol, &registry).ok_or_else(|| {
+        ApiError::bad_request(format!("Unrecognized symbol: {}", alert.symbol)).with_code("invalid_symbol")
+    })?;
+    let side = webhook_service::parse_action(&alert.action).ok_or_else(|| {
+        ApiError::bad_request(format!("Action must be buy or sell, got: {}", alert.action)).with_code("invalid_action")
+    })?;
+    let quantity = webhook_service::parse_size(&alert.size)
+        .ok_or_else(|| ApiError::bad_request("Size must be a positive number").with_code("invalid_quantity"))?;
+
+    tracing::info!(
+        "TradingView alert for user {}: {:?} {} {}/{} ({:?})",
+        user_id,
+        side,
+        quantity,
+        base_asset,
+        quote_asset,
+        alert.mode
+    );
+
+    match alert.mode {
+        WebhookMode::Trade => {
+            let trade = trading_service::execute_trade(&state, &user_id, &base_asset, &quote_asset, side, quantity)
+                .await
+                .map_err(|err| trade_failure(&err, &base_asset, &quote_asset))?;
+            Ok(Json(WebhookOutcome::Executed { trade }))
+        }
+        WebhookMode::Signal => {
+            let signal = ExternalSignal {
+                source: "tradingview".to_string(),
+                side,
+                quantity,
+                received_at: state.clock.now(),
+            };
+            bot_service::queue_signal(&state, &user_id, &base_asset, &quote_asset, signal)
+                .await
+                .map_err(|err| {
+                    let message = match err {
+                        SignalError::NoBot => format!("No bot is running; start a {} bot first", SIGNAL_STRATEGY),
+                        SignalError::IgnoresSignals => format!(
+                            "None of the running bots follows signals; start a {} bot",
+                            SIGNAL_STRATEGY
+                        ),
+                        SignalError::PairMismatch(base, quote) => format!(
+                            "The running {} bot trades {}/{}, not {}/{}",
+                            SIGNAL_STRATEGY, base, quote, base_asset, quote_asset
+                        ),
+                        SignalError::QueueFull => "Too many signals waiting for the bot's next tick".to_string(),
+                    };
+                    ApiError::conflict(message).with_code("signal_rejected")
+                })?;
+            Ok(Json(WebhookOutcome::Queued { base_asset, quote_asset }))
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::db::queries::{self, UserAchievement};
+use crate::models::{BotId, TransactionType, UserId};
+use crate::services::portfolio_service::{self, HistoryRange};
+use crate::services::{bot_service, performance_service};
+use crate::state::{self, AppState, BotActivity, NotificationKind};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::ToSchema;
+
+pub const ACTIVE_TRADER_TRADES: usize = 10;
+pub const RETURN_TARGET_PCT: f64 = 10.0;
+pub const DRAWDOWN_SURVIVED_PCT: f64 = 20.0;
+pub const BOT_WEEK_DAYS: i64 = 7;
+
+/// Bot ticks come every few seconds; re-check a running bot at most this often
+const BOT_CHECK_INTERVAL_MINUTES: i64 = 10;
+
+/// A badge users can earn
+#[derive(Debug, Clone, Copy, Serialize, ToSchema)]
+pub struct Achievement {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+}
+
+/// Every badge, in the order they are usually earned
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement { id: "first_trade", title: "First trade", description: "Make your first trade" },
+    Achievement { id: "active_trader", title: "Active trader", description: "Make 10 trades" },
+    Achievement { id: "first_bot", title: "Automated", description: "Start a trading bot" },
+    Achievement {
+        id: "profitable_bot_week",
+        title: "Profitable bot week",
+        description: "Keep a bot running for a week with the portfolio worth more than when it started",
+    },
+    Achievement {
+        id: "return_10pct",
+        title: "Double digits",
+        description: "Reach a 10% return over the last 30 days, not counting deposits",
+    },
+    Achievement {
+        id: "survived_drawdown_20pct",
+        title: "Diamond hands",
+        description: "Fall 20% from a peak and climb back to it",
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static Achievement> {
+    ACHIEVEMENTS.iter().find(|a| a.id == id)
+}
+
+/// What a user has done so far, as far as badges care
+#[derive(Debug, Default)]
+pub struct Progress {
+    pub trade_count: usize,
+    pub bot_running: bool,
+    pub bot_week_gain_pct: Option<f64>, // Portfolio change since a bot started, once it has run a week
+    pub growth: Vec<f64>,               // Growth index of the portfolio (see performance_service::growth_index)
+}
+
+/// Whether `growth` fell `drawdown_pct` below a peak and later got back to that peak
+pub fn recovered_from_drawdown(growth: &[f64], drawdown_pct: f64) -> bool {
+    let mut peak = f64::MIN;
+    let mut fell_from: Option<f64> = None;
+    for &value in growth {
+        if fell_from.is_some_and(|from| value >= from) {
+            return true;
+        }
+        peak = peak.max(value);
+        if peak > 0.0 && (peak - value) / peak * 100.0 >= drawdown_pct {
+            fell_from = Some(fell_from.map_or(peak, |from| from.min(peak)));
+        }
+    }
+    false
+}
+
+/// Badges `progress` qualifies for, with what earned each
+pub fn qualifies(progress: &Progress) -> Vec<(&'static str, String)> {
+    let mut earned = Vec::new();
+    if progress.trade_count >= 1 {
+        earned.push(("first_trade", "Made a first trade".to_string()));
+    }
+    if progress.trade_count >= ACTIVE_TRADER_TRADES {
+        earned.push(("active_trader", format!("Made {} trades", progress.trade_count)));
+    }
+    if progress.bot_running {
+        earned.push(("first_bot", "Started a bot".to_string()));
+    }
+    if let Some(gain) = progress.bot_week_gain_pct.filter(|gain| *gain > 0.0) {
+        earned.push(("profitable_bot_week", format!("Up {:.2}% after a week of bot trading", gain)));
+    }
+    if let Some(growth) = progress.growth.last() {
+        let return_pct = (growth - 1.0) * 100.0;
+        if return_pct >= RETURN_TARGET_PCT {
+            earned.push(("return_10pct", format!("Return reached {:.2}%", return_pct)));
+        }
+    }
+    if recovered_from_drawdown(&progress.growth, DRAWDOWN_SURVIVED_PCT) {
+        earned.push((
+            "survived_drawdown_20pct",
+            format!("Recovered from a drawdown of at least {}%", DRAWDOWN_SURVIVED_PCT),
+        ));
+    }
+    earned
+}
+
+/// Gather a user's progress; `None` for unknown users
+async fn progress(state: &AppState, user_id: &UserId) -> Option<Progress> {
+    let user = state.get_user(user_id).await?;
+    let trade_count = user
+        .trade_history
+        .iter()
+        .filter(|t| t.transaction_type == TransactionType::Trade)
+        .count();
+    let growth = if trade_count > 0 {
+        let (_, samples) = portfolio_service::equity(state, &user, HistoryRange::All).await;
+        performance_service::growth_index(&samples)
+    } else {
+        Vec::new()
+    };
+
+    let bots: Vec<(BotId, f64)> = state
+        .bots
+        .read()
+        .await
+        .iter()
+        .filter(|(_, bot)| &bot.user_id == user_id)
+        .map(|(bot_id, bot)| (bot_id.clone(), bot.initial_portfolio_value_usd))
+        .collect();
+    // The best gain of any bot running for a week
+    let mut bot_week_gain_pct: Option<f64> = None;
+    for (bot_id, initial_value) in bots.iter().filter(|(_, v)| *v > 0.0) {
+        let started_at = queries::bot_started_at(state.db.pool(), bot_id).await.ok().flatten();
+        if started_at.is_some_and(|at| state.clock.now() - at >= Duration::days(BOT_WEEK_DAYS)) {
+            if let Ok(value) = bot_service::calculate_portfolio_value_usd(state, user_id).await {
+                let gain_pct = (value - initial_value) / initial_value * 100.0;
+                bot_week_gain_pct = Some(bot_week_gain_pct.map_or(gain_pct, |best| best.max(gain_pct)));
+            }
+        }
+    }
+
+    Some(Progress { trade_count, bot_running: !bots.is_empty(), bot_week_gain_pct, growth })
+}
+
+/// Award whatever the user newly qualifies for and tell them about it
+async fn evaluate(state: &AppState, user_id: &UserId) {
+    let Some(progress) = progress(state, user_id).await else {
+        return;
+    };
+    let earned = qualifies(&progress);
+    if earned.is_empty() {
+        return;
+    }
+    let held = match queries::list_user_achievements(state.db.pool(), user_id).await {
+        Ok(held) => held,
+        Err(e) => {
+            tracing::error!("Failed to load achievements for {}: {}", user_id, e);
+            return;
+        }
+    };
+
+    for (id, details) in earned.into_iter().filter(|(id, _)| !held.iter().any(|h| h.achievement == *id)) {
+        let achievement = UserAchievement { achievement: id.to_string(), earned_at: state.clock.now(), details };
+        match queries::award_achievement(state.db.pool(), user_id, &achievement).await {
+            Ok(true) => {
+                tracing::info!("User {} earned achievement {}", user_id, id);
+                state.notify(
+                    user_id,
+                    NotificationKind::AchievementEarned {
+                        achievement: achievement.achievement,
+                        title: find(id).map_or(id, |a| a.title).to_string(),
+                        details: achievement.details,
+                    },
+                );
+            }
+            Ok(false) => {} // Awarded meanwhile, e.g. by another instance
+            Err(e) => tracing::error!("Failed to award achievement {} to {}: {}", id, user_id, e),
+        }
+    }
+}
+
+/// Check users for new badges as they trade and run bots
+/// Every instance evaluates the events raised on it; awards are idempotent
+pub async fn run_achievements(state: AppState) {
+    let mut balances = state.balance_events.subscribe();
+    let mut bots = state.bot_events.subscribe();
+    let mut bot_checked: HashMap<UserId, DateTime<Utc>> = HashMap::new();
+
+    loop {
+        let user_id = tokio::select! {
+            event = balances.recv() => match event {
+                Ok(event) => event.user_id,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Achievements lagged, skipped {} balance events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            event = bots.recv() => match event {
+                Ok(event) => {
+                    let now = state.clock.now();
+                    let due = match event.activity {
+                        BotActivity::Started { .. } => true,
+                        // Trades also arrive as balance events
+                        BotActivity::Tick { .. } => bot_checked
+                            .get(&event.user_id)
+                            .is_none_or(|at| now - *at >= Duration::minutes(BOT_CHECK_INTERVAL_MINUTES)),
+                        _ => false,
+                    };
+                    if !due {
+                        continue;
+                    }
+                    bot_checked.insert(event.user_id.clone(), now);
+                    event.user_id
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Achievements lagged, skipped {} bot events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+        if state::is_memory_only(&user_id) {
+            continue;
+        }
+        evaluate(&state, &user_id).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drawdown_counts_only_once_recovered() {
+        assert!(!recovered_from_drawdown(&[1.0, 1.1, 0.85, 1.05], 20.0));
+        assert!(recovered_from_drawdown(&[1.0, 1.1, 0.85, 1.05, 1.1], 20.0));
+        // A shallower dip doesn't count, however fully it recovers
+        assert!(!recovered_from_drawdown(&[1.0, 0.9, 1.2], 20.0));
+        // Climbing back to the lowest peak it fell from is enough
+        assert!(recovered_from_drawdown(&[1.0, 0.75, 1.0, 1.5, 1.1], 20.0));
+    }
+
+    #[test]
+    fn test_qualifies_for_each_threshold() {
+        let progress = Progress {
+            trade_count: 10,
+            bot_running: true,
+            bot_week_gain_pct: Some(-1.0),
+            growth: vec![1.0, 1.05, 1.12],
+        };
+        let earned: Vec<&str> = qualifies(&progress).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(earned, vec!["first_trade", "active_trader", "first_bot", "return_10pct"]);
+
+        assert!(qualifies(&Progress::default()).is_empty());
+        assert!(ACHIEVEMENTS.iter().all(|a| find(a.id).is_some()));
+    }
+}
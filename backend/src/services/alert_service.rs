@@ -0,0 +1,195 @@
+use crate::db::queries::{self, AlertTrigger, PriceAlert};
+use crate::models::{AlertCondition, PricePoint};
+use crate::state::{AppState, NotificationKind};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::Instrument;
+
+/// Alerts a user may keep, fired or not
+pub const MAX_ALERTS_PER_USER: i64 = 50;
+
+/// Longest change_pct lookback: the coarsest price tier keeps 30 days
+pub const MAX_WINDOW_MINUTES: i64 = 30 * 24 * 60;
+
+/// Why an alert definition was rejected
+pub fn validate(condition: AlertCondition, threshold: f64, window_minutes: Option<i64>) -> Result<(), String> {
+    if !threshold.is_finite() {
+        return Err("Threshold must be a number".to_string());
+    }
+
+    match condition {
+        AlertCondition::Above | AlertCondition::Below => {
+            if threshold <= 0.0 {
+                return Err("Price threshold must be positive".to_string());
+            }
+            if window_minutes.is_some() {
+                return Err("window_minutes only applies to change_pct alerts".to_string());
+            }
+        }
+        AlertCondition::ChangePct => {
+            // A drop can't exceed 100%
+            if threshold == 0.0 || threshold <= -100.0 {
+                return Err("Percent threshold must be non-zero and above -100".to_string());
+            }
+            match window_minutes {
+                Some(minutes) if (1..=MAX_WINDOW_MINUTES).contains(&minutes) => {}
+                _ => {
+                    return Err(format!(
+                        "change_pct alerts need window_minutes between 1 and {}",
+                        MAX_WINDOW_MINUTES
+                    ))
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Percent move from `from` to `to`
+fn percent_change(from: f64, to: f64) -> Option<f64> {
+    (from > 0.0).then(|| (to - from) / from * 100.0)
+}
+
+/// Whether an alert fires at `price`; `change_pct` is the move over its window
+/// A negative change threshold fires on drops of at least that much, a positive one on rises
+fn condition_met(alert: &PriceAlert, price: f64, change_pct: Option<f64>) -> bool {
+    match alert.condition {
+        AlertCondition::Above => price >= alert.threshold,
+        AlertCondition::Below => price <= alert.threshold,
+        AlertCondition::ChangePct => change_pct.is_some_and(|change| {
+            if alert.threshold < 0.0 {
+                change <= alert.threshold
+            } else {
+                change >= alert.threshold
+            }
+        }),
+    }
+}
+
+/// Fire every armed alert on the point's asset whose condition now holds
+async fn check_alerts(state: &AppState, point: &PricePoint) {
+    let alerts = match queries::armed_alerts_for_asset(state.db.pool(), &point.asset).await {
+        Ok(alerts) => alerts,
+        Err(e) => {
+            tracing::error!("Failed to load alerts for {}: {}", point.asset, e);
+            return;
+        }
+    };
+
+    for alert in alerts {
+        let change_pct = match alert.window_minutes.filter(|_| alert.condition == AlertCondition::ChangePct) {
+            Some(minutes) => {
+                let series = state
+                    .get_close_series(&point.asset, chrono::Duration::minutes(minutes))
+                    .await;
+                series.first().and_then(|start| percent_change(start.price, point.price))
+            }
+            None => None,
+        };
+
+        if !condition_met(&alert, point.price, change_pct) {
+            continue;
+        }
+
+        let trigger = AlertTrigger {
+            id: 0, // Assigned by the database
+            alert_id: alert.id.clone(),
+            user_id: alert.user_id.clone(),
+            asset: alert.asset.clone(),
+            condition: alert.condition,
+            threshold: alert.threshold,
+            price: point.price,
+            change_pct,
+            triggered_at: point.timestamp,
+        };
+
+        // Only the instance that disarms the alert notifies, so each firing is delivered once
+        match queries::trigger_alert(state.db.pool(), &trigger).await {
+            Ok(true) => {
+                tracing::info!(
+                    "Alert {} fired for user {}: {} {} {} at {}",
+                    alert.id,
+                    alert.user_id,
+                    alert.asset,
+                    alert.condition.as_str(),
+                    alert.threshold,
+                    point.price
+                );
+                state.notify(
+                    &alert.user_id,
+                    NotificationKind::PriceAlert {
+                        alert_id: alert.id,
+                        asset: alert.asset,
+                        condition: alert.condition,
+                        threshold: alert.threshold,
+                        price: point.price,
+                        change_pct,
+                    },
+                );
+            }
+            Ok(false) => {}
+            Err(e) => tracing::error!("Failed to record alert {}: {}", alert.id, e),
+        }
+    }
+}
+
+/// Check alerts against every live price as it arrives
+pub async fn run_alerts(state: AppState) {
+    let mut feed = state.price_feed.subscribe();
+
+    loop {
+        match feed.recv().await {
+            Ok(point) => {
+                check_alerts(&state, &point)
+                    .instrument(tracing::info_span!("alerts", asset = %point.asset))
+                    .await
+            }
+            // Missed prices are superseded by the next one; alerts are level-based
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Alert checker lagged, skipped {} prices", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(condition: AlertCondition, threshold: f64) -> PriceAlert {
+        PriceAlert {
+            id: "a1".to_string(),
+            user_id: "u1".to_string(),
+            asset: "BTC".to_string(),
+            condition,
+            threshold,
+            window_minutes: (condition == AlertCondition::ChangePct).then_some(60),
+            created_at: chrono::Utc::now(),
+            triggered_at: None,
+        }
+    }
+
+    #[test]
+    fn test_conditions_fire_at_threshold() {
+        assert!(condition_met(&alert(AlertCondition::Above, 100_000.0), 100_000.0, None));
+        assert!(!condition_met(&alert(AlertCondition::Above, 100_000.0), 99_999.0, None));
+        assert!(condition_met(&alert(AlertCondition::Below, 50_000.0), 49_000.0, None));
+
+        // "Drops 5% in an hour"
+        let drop = alert(AlertCondition::ChangePct, -5.0);
+        assert!(condition_met(&drop, 95.0, percent_change(100.0, 95.0)));
+        assert!(!condition_met(&drop, 96.0, percent_change(100.0, 96.0)));
+        assert!(!condition_met(&drop, 95.0, None));
+        assert!(condition_met(&alert(AlertCondition::ChangePct, 5.0), 106.0, percent_change(100.0, 106.0)));
+    }
+
+    #[test]
+    fn test_validate_rejects_mismatched_windows() {
+        assert!(validate(AlertCondition::Above, 100_000.0, None).is_ok());
+        assert!(validate(AlertCondition::Above, 100_000.0, Some(60)).is_err());
+        assert!(validate(AlertCondition::ChangePct, -5.0, Some(60)).is_ok());
+        assert!(validate(AlertCondition::ChangePct, -5.0, None).is_err());
+        assert!(validate(AlertCondition::ChangePct, -100.0, Some(60)).is_err());
+        assert!(validate(AlertCondition::Below, f64::NAN, None).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::db::DbPool;
+use tracing::Instrument;
+
+/// Actions recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    Signup,
+    Login,
+    LoginFailed,
+    OAuthLogin,
+    Trade,
+    Deposit,
+    Withdrawal,
+    BotStarted,
+    BotStopped,
+    BotPaused,
+    BotResumed,
+    PortfolioReset,
+    BackupExported,
+    Logout,
+    SessionsRevoked,
+    WebhookEnabled,
+    WebhookDisabled,
+    SettingsChanged,
+    PasswordChanged,
+    HistoryImported,
+    CompetitionCreated,
+    LeagueCreated,
+    AccountExported,
+    AccountDeleted,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Signup => "signup",
+            AuditAction::Login => "login",
+            AuditAction::LoginFailed => "login_failed",
+            AuditAction::OAuthLogin => "oauth_login",
+            AuditAction::Trade => "trade",
+            AuditAction::Deposit => "deposit",
+            AuditAction::Withdrawal => "withdrawal",
+            AuditAction::BotStarted => "bot_started",
+            AuditAction::BotStopped => "bot_stopped",
+            AuditAction::BotPaused => "bot_paused",
+            AuditAction::BotResumed => "bot_resumed",
+            AuditAction::PortfolioReset => "portfolio_reset",
+            AuditAction::BackupExported => "backup_exported",
+            AuditAction::Logout => "logout",
+            AuditAction::SessionsRevoked => "sessions_revoked",
+            AuditAction::WebhookEnabled => "webhook_enabled",
+            AuditAction::WebhookDisabled => "webhook_disabled",
+            AuditAction::SettingsChanged => "settings_changed",
+            AuditAction::PasswordChanged => "password_changed",
+            AuditAction::HistoryImported => "history_imported",
+            AuditAction::CompetitionCreated => "competition_created",
+            AuditAction::LeagueCreated => "league_created",
+            AuditAction::AccountExported => "account_exported",
+            AuditAction::AccountDeleted => "account_deleted",
+        }
+    }
+}
+
+/// Record an audit entry without blocking the caller
+/// Memory-only users (the demo user, bot replays) are not recorded
+pub fn record(pool: &DbPool, user_id: Option<&str>, action: AuditAction, details: String) {
+    if user_id.is_some_and(crate::state::is_memory_only) {
+        return;
+    }
+
+    let pool = pool.clone();
+    let user_id = user_id.map(|id| id.to_string());
+
+    tokio::spawn(
+        async move {
+            if let Err(e) =
+                crate::db::queries::insert_audit_entry(&pool, user_id.as_deref(), action.as_str(), &details).await
+            {
+                tracing::error!("Failed to write audit entry {}: {}", action.as_str(), e);
+            }
+        }
+        .in_current_span(),
+    );
+}
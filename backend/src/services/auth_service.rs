@@ -7,6 +7,7 @@ pub enum AuthError {
     UserAlreadyExists,
     HashError(String),
     DatabaseError(String),
+    OAuthFailed(String),
 }
 
 impl std::fmt::Display for AuthError {
@@ -16,6 +17,7 @@ impl std::fmt::Display for AuthError {
             AuthError::UserAlreadyExists => write!(f, "Username already exists"),
             AuthError::HashError(msg) => write!(f, "Password hashing error: {}", msg),
             AuthError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
+            AuthError::OAuthFailed(msg) => write!(f, "OAuth error: {}", msg),
         }
     }
 }
@@ -0,0 +1,549 @@
+use crate::bots::{BotContext, BotDecision, DecisionOutcome, TradingBot};
+use crate::models::{OrderType, PricePoint, TradeSide, UserId};
+use crate::services::history_service;
+use crate::services::order_service;
+use crate::services::performance_service::{self, ClosedTrade, EquitySample, Fill, PerformanceMetrics};
+use crate::state::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use tracing::Instrument;
+use utoipa::ToSchema;
+
+/// The in-memory price tiers reach back this far; older ranges need imported history
+pub const MAX_BACKTEST_DAYS: i64 = 30;
+
+/// Finished runs are forgotten after this long
+const RUN_TTL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Runs a user may keep (oldest are dropped first)
+const MAX_RUNS_PER_USER: usize = 10;
+
+/// Prices handed to the bot each tick, like the live price window
+const CONTEXT_WINDOW_POINTS: usize = 720;
+
+/// What to backtest
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BacktestParams {
+    pub strategy: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub initial_balance: f64, // In quote asset terms
+    pub stoploss_amount: f64, // Also sizes naive_momentum's trades, as for live bots
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Value, // Strategy settings, as for live bots
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BacktestTrade {
+    pub timestamp: DateTime<Utc>,
+    pub side: TradeSide,
+    pub quantity: f64, // Base asset
+    pub price: f64,    // In quote asset terms
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BacktestEquityPoint {
+    pub timestamp: i64, // Unix seconds
+    pub value: f64,     // Portfolio value in quote asset terms
+    pub hold_value: f64, // Value had the initial balance bought the base asset at the first price
+    pub position_value: f64, // Base asset held, in quote asset terms
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BacktestMetrics {
+    pub final_value: f64,
+    pub buy_and_hold_return_pct: f64, // Holding the base asset over the same range
+    pub trade_count: usize,           // Fills; closed_trades counts the sells among them
+    #[serde(flatten)]
+    pub performance: PerformanceMetrics, // Computed as for live portfolios
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BacktestResult {
+    pub ticks: usize,
+    pub metrics: BacktestMetrics,
+    pub equity: Vec<BacktestEquityPoint>, // Oldest first
+    pub trades: Vec<BacktestTrade>,       // Oldest first
+    pub stopped_reason: Option<String>,   // Set if the bot stopped before the end of the range
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BacktestStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// One backtest, kept in memory while it runs and for a while after
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BacktestRun {
+    pub id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub status: BacktestStatus,
+    pub params: BacktestParams,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub created: std::time::Instant,
+    pub result: Option<BacktestResult>, // Set once completed
+    pub error: Option<String>,          // Set if failed
+}
+
+/// A run without its equity curve and trades, for listing
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BacktestRunSummary {
+    pub id: String,
+    pub status: BacktestStatus,
+    pub params: BacktestParams,
+    pub created_at: DateTime<Utc>,
+    pub metrics: Option<BacktestMetrics>, // Set once completed
+    pub error: Option<String>,
+}
+
+/// Replay `series` (pair prices, oldest first) through `bot`, one tick per point
+/// Decisions are validated as for live bots: a buy the balance can't cover stops the bot,
+/// an oversized sell is skipped, and the stoploss is checked after every tick
+pub fn simulate(
+    bot: &mut dyn TradingBot,
+    series: &[PricePoint],
+    params: &BacktestParams,
+) -> BacktestResult {
+    simulate_detailed(bot, series, params).0
+}
+
+/// Trade `quantity` of the base asset at `price` if the balances cover it
+fn fill(base_balance: &mut f64, quote_balance: &mut f64, side: &TradeSide, quantity: f64, price: f64) -> bool {
+    let cost = quantity * price;
+    if !quantity.is_finite() || quantity <= 0.0 {
+        return false;
+    }
+    match side {
+        TradeSide::Buy if *quote_balance >= cost => {
+            *quote_balance -= cost;
+            *base_balance += quantity;
+        }
+        TradeSide::Sell if *base_balance >= quantity => {
+            *quote_balance += cost;
+            *base_balance -= quantity;
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// `simulate`, also returning the equity samples and closed trades its metrics were computed from
+pub fn simulate_detailed(
+    bot: &mut dyn TradingBot,
+    series: &[PricePoint],
+    params: &BacktestParams,
+) -> (BacktestResult, Vec<EquitySample>, Vec<ClosedTrade>) {
+    let mut base_balance = 0.0;
+    let mut quote_balance = params.initial_balance;
+    let mut trades = Vec::new();
+    let mut equity = Vec::with_capacity(series.len());
+    let mut samples = Vec::with_capacity(series.len());
+    let mut stopped_reason = None;
+    let hold_units = series.first().filter(|p| p.price > 0.0).map_or(0.0, |p| params.initial_balance / p.price);
+    let mut resting: Vec<(TradeSide, f64, f64)> = Vec::new(); // Limit orders: side, limit price, quantity
+
+    for (tick, point) in series.iter().enumerate() {
+        // Resting limits fill at the price once it reaches them, as the live order matcher does;
+        // one the balances no longer cover is dropped, like a failed order
+        let (triggered, still_resting) = resting
+            .into_iter()
+            .partition(|(side, limit, _)| order_service::triggered(OrderType::Limit, side, *limit, point.price));
+        resting = still_resting;
+        for (side, _, quantity) in triggered {
+            if fill(&mut base_balance, &mut quote_balance, &side, quantity, point.price) {
+                trades.push(BacktestTrade { timestamp: point.timestamp, side, quantity, price: point.price });
+            }
+        }
+
+        let window_start = (tick + 1).saturating_sub(CONTEXT_WINDOW_POINTS);
+        let ctx = BotContext {
+            price_window: series[window_start..=tick].to_vec(),
+            base_balance,
+            quote_balance,
+            current_price: point.price,
+            base_asset: params.base_asset.clone(),
+            quote_asset: params.quote_asset.clone(),
+            tick_count: tick as u64,
+            signals: Vec::new(),
+            headlines: Vec::new(),
+            secondary: None,
+        };
+
+        let decision = bot.tick(&ctx);
+        let mut outcome = DecisionOutcome::Skipped;
+        let trade = match &decision {
+            BotDecision::PlaceLimit { side, price, quantity } => {
+                if resting.len() < order_service::MAX_OPEN_ORDERS as usize {
+                    resting.push((side.clone(), *price, *quantity));
+                    outcome = DecisionOutcome::Executed;
+                }
+                None
+            }
+            _ => match decision.market_order(point.price, base_balance) {
+                Some((TradeSide::Buy, quantity)) if quote_balance < quantity * point.price => {
+                    stopped_reason = Some(format!(
+                        "Insufficient funds: needed {:.2} {} but had {:.2}",
+                        quantity * point.price,
+                        params.quote_asset,
+                        quote_balance
+                    ));
+                    None
+                }
+                trade => trade,
+            },
+        };
+
+        if let Some((side, quantity)) = trade {
+            if fill(&mut base_balance, &mut quote_balance, &side, quantity, point.price) {
+                trades.push(BacktestTrade { timestamp: point.timestamp, side, quantity, price: point.price });
+                outcome = DecisionOutcome::Executed;
+            }
+        }
+        if decision != BotDecision::DoNothing {
+            bot.on_outcome(outcome);
+        }
+
+        let position = base_balance * point.price;
+        let value = quote_balance + position;
+        samples.push(EquitySample {
+            timestamp: point.timestamp,
+            value,
+            flow: 0.0,
+            exposed: performance_service::is_exposed(position, value),
+            position,
+        });
+        equity.push(BacktestEquityPoint {
+            timestamp: point.timestamp.timestamp(),
+            value,
+            hold_value: hold_units * point.price,
+            position_value: position,
+        });
+
+        let loss = params.initial_balance - value;
+        if stopped_reason.is_none() && loss >= params.stoploss_amount {
+            stopped_reason = Some(format!(
+                "Stoploss breached: lost {:.2} (limit: {:.2})",
+                loss, params.stoploss_amount
+            ));
+        }
+        if stopped_reason.is_some() {
+            break;
+        }
+    }
+
+    let final_value = equity.last().map(|p| p.value).unwrap_or(params.initial_balance);
+    let buy_and_hold_return_pct = match (series.first(), series.last()) {
+        (Some(first), Some(last)) if first.price > 0.0 => (last.price - first.price) / first.price * 100.0,
+        _ => 0.0,
+    };
+
+    let fills: Vec<Fill> = trades
+        .iter()
+        .map(|t| Fill { timestamp: t.timestamp, side: t.side.clone(), quantity: t.quantity, price: t.price })
+        .collect();
+
+    let closed = performance_service::closed_trades(&fills);
+    let result = BacktestResult {
+        ticks: equity.len(),
+        metrics: BacktestMetrics {
+            final_value,
+            buy_and_hold_return_pct,
+            trade_count: trades.len(),
+            performance: performance_service::compute(&samples, &closed),
+        },
+        equity,
+        trades,
+        stopped_reason,
+    };
+    (result, samples, closed)
+}
+
+/// USD close prices of `asset` between `start` and `end`, oldest first
+/// Imported history fills in wherever the in-memory tiers don't reach back to `start`
+pub async fn usd_series(state: &AppState, asset: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<PricePoint> {
+    let live: Vec<PricePoint> = state
+        .get_close_series(asset, Utc::now() - start)
+        .await
+        .into_iter()
+        .filter(|p| p.timestamp >= start && p.timestamp <= end)
+        .collect();
+
+    let live_start = live.first().map_or(end, |p| p.timestamp);
+    if start >= live_start {
+        return live;
+    }
+    let mut series = match history_service::close_series(state.db.pool(), asset, start, live_start).await {
+        Ok(points) => points,
+        Err(e) => {
+            tracing::warn!("Failed to load imported {} history: {}", asset, e);
+            Vec::new()
+        }
+    };
+    series.retain(|p| p.timestamp < live_start);
+    series.extend(live);
+    series
+}
+
+/// Close prices of base in quote terms between `start` and `end`, oldest first
+/// Non-USD quotes are converted via each asset's USD series
+pub async fn pair_series(state: &AppState, params: &BacktestParams) -> Vec<PricePoint> {
+    let base = usd_series(state, &params.base_asset, params.start, params.end).await;
+    if params.quote_asset == "USD" {
+        return base;
+    }
+
+    let quote = usd_series(state, &params.quote_asset, params.start, params.end).await;
+    base.into_iter()
+        .filter_map(|point| {
+            // Latest quote price at or before the base point
+            let idx = quote.partition_point(|q| q.timestamp <= point.timestamp);
+            let quote_price = quote.get(idx.checked_sub(1)?)?.price;
+            (quote_price > 0.0).then(|| PricePoint { price: point.price / quote_price, ..point })
+        })
+        .collect()
+}
+
+/// Why a backtest can't run as requested
+/// `history_start` is the base asset's oldest imported candle, if any
+pub fn validate(params: &BacktestParams, now: DateTime<Utc>, history_start: Option<DateTime<Utc>>) -> Result<(), String> {
+    if params.base_asset == params.quote_asset {
+        return Err("Base and quote asset must differ".to_string());
+    }
+    if params.start >= params.end {
+        return Err("start must be before end".to_string());
+    }
+    let in_memory_start = now - ChronoDuration::days(MAX_BACKTEST_DAYS);
+    if params.start < history_start.map_or(in_memory_start, |h| h.min(in_memory_start)) {
+        return Err(match history_start {
+            Some(h) => format!("Backtests can reach back to {} (imported history) or {} days", h, MAX_BACKTEST_DAYS),
+            None => format!("Backtests can reach back at most {} days without imported history", MAX_BACKTEST_DAYS),
+        });
+    }
+    if !params.initial_balance.is_finite() || params.initial_balance <= 0.0 {
+        return Err("initial_balance must be positive".to_string());
+    }
+    if !params.stoploss_amount.is_finite() || params.stoploss_amount <= 0.0 {
+        return Err("stoploss_amount must be positive".to_string());
+    }
+    Ok(())
+}
+
+/// Record a run and start it in the background; the caller polls `get_run` for the result
+pub async fn start(state: &AppState, user_id: &UserId, params: BacktestParams, bot: Box<dyn TradingBot>) -> BacktestRun {
+    let run = BacktestRun {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user_id.clone(),
+        status: BacktestStatus::Running,
+        params,
+        created_at: Utc::now(),
+        created: std::time::Instant::now(),
+        result: None,
+        error: None,
+    };
+
+    {
+        let mut runs = state.backtests.lock().await;
+        runs.retain(|_, r| r.status == BacktestStatus::Running || r.created.elapsed() < RUN_TTL);
+
+        let mut own: Vec<(std::time::Instant, String)> = runs
+            .values()
+            .filter(|r| r.user_id == *user_id)
+            .map(|r| (r.created, r.id.clone()))
+            .collect();
+        own.sort();
+        for (_, id) in own.iter().take((own.len() + 1).saturating_sub(MAX_RUNS_PER_USER)) {
+            runs.remove(id);
+        }
+        runs.insert(run.id.clone(), run.clone());
+    }
+
+    let task_state = state.clone();
+    let id = run.id.clone();
+    let params = run.params.clone();
+    tokio::spawn(
+        async move {
+            let series = pair_series(&task_state, &params).await;
+            let outcome = if series.is_empty() {
+                Err(format!(
+                    "No {}/{} prices between {} and {}",
+                    params.base_asset, params.quote_asset, params.start, params.end
+                ))
+            } else {
+                // Replaying is CPU-bound; keep it off the async workers
+                tokio::task::spawn_blocking(move || {
+                    let mut bot = bot;
+                    simulate(bot.as_mut(), &series, &params)
+                })
+                .await
+                .map_err(|e| format!("Backtest crashed: {}", e))
+            };
+
+            if let Some(run) = task_state.backtests.lock().await.get_mut(&id) {
+                match outcome {
+                    Ok(result) => {
+                        run.status = BacktestStatus::Completed;
+                        run.result = Some(result);
+                    }
+                    Err(e) => {
+                        tracing::warn!("Backtest {} failed: {}", id, e);
+                        run.status = BacktestStatus::Failed;
+                        run.error = Some(e);
+                    }
+                }
+            }
+        }
+        .in_current_span(),
+    );
+
+    run
+}
+
+/// A user's runs still in memory, newest first
+pub async fn list_runs(state: &AppState, user_id: &UserId) -> Vec<BacktestRunSummary> {
+    let runs = state.backtests.lock().await;
+    let mut own: Vec<BacktestRunSummary> = runs
+        .values()
+        .filter(|r| r.user_id == *user_id)
+        .filter(|r| r.status == BacktestStatus::Running || r.created.elapsed() < RUN_TTL)
+        .map(|r| BacktestRunSummary {
+            id: r.id.clone(),
+            status: r.status,
+            params: r.params.clone(),
+            created_at: r.created_at,
+            metrics: r.result.as_ref().map(|result| result.metrics.clone()),
+            error: r.error.clone(),
+        })
+        .collect();
+    own.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+    own
+}
+
+/// One of a user's runs
+pub async fn get_run(state: &AppState, user_id: &UserId, id: &str) -> Option<BacktestRun> {
+    let runs = state.backtests.lock().await;
+    runs.get(id).filter(|r| r.user_id == *user_id).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::naive_momentum::NaiveMomentumBot;
+    use crate::services::performance_service::max_drawdown_pct;
+    use chrono::TimeZone;
+
+    fn params() -> BacktestParams {
+        BacktestParams {
+            strategy: "naive_momentum".to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            start: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            end: Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap(),
+            initial_balance: 10_000.0,
+            stoploss_amount: 1_000.0,
+            parameters: serde_json::json!({}),
+        }
+    }
+
+    fn series(prices: &[f64]) -> Vec<PricePoint> {
+        let start = params().start;
+        prices
+            .iter()
+            .enumerate()
+            .map(|(i, &price)| PricePoint {
+                timestamp: start + ChronoDuration::minutes(i as i64),
+                asset: "BTC".to_string(),
+                price,
+                volume: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_simulate_replays_trades_into_equity() {
+        // Three rises: buys $10 (1% of the stoploss) at 103
+        let mut bot = NaiveMomentumBot::new(1_000.0);
+        let result = simulate(&mut bot, &series(&[100.0, 101.0, 103.0, 103.0, 206.0]), &params());
+
+        assert_eq!(result.ticks, 5);
+        assert_eq!(result.trades.len(), 1);
+        assert!(matches!(result.trades[0].side, TradeSide::Buy));
+        assert_eq!(result.equity[0].value, 10_000.0);
+        // 10 USD of BTC doubled
+        assert!((result.metrics.final_value - 10_010.0).abs() < 1e-9);
+        assert!((result.metrics.performance.total_return_pct - 0.1).abs() < 1e-9);
+        // Holding from the buy at 103 to the end
+        assert!((result.metrics.performance.exposure_pct - 50.0).abs() < 1e-9);
+        assert!((result.metrics.buy_and_hold_return_pct - 106.0).abs() < 1e-9);
+        assert!((result.equity[4].hold_value - 20_600.0).abs() < 1e-9);
+        assert_eq!(result.stopped_reason, None);
+    }
+
+    /// Plays back a fixed list of decisions, one a tick
+    struct Scripted(std::vec::IntoIter<BotDecision>);
+
+    impl TradingBot for Scripted {
+        fn tick(&mut self, _ctx: &BotContext) -> BotDecision {
+            self.0.next().unwrap_or(BotDecision::DoNothing)
+        }
+
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+    }
+
+    #[test]
+    fn test_simulate_fills_base_sized_trades_and_resting_limits() {
+        let mut bot = Scripted(
+            vec![
+                BotDecision::BuyBase { quantity: 10.0 },
+                BotDecision::PlaceLimit { side: TradeSide::Buy, price: 90.0, quantity: 5.0 },
+                BotDecision::PlaceLimit { side: TradeSide::Sell, price: 120.0, quantity: 4.0 },
+                BotDecision::DoNothing,
+                BotDecision::ClosePosition,
+            ]
+            .into_iter(),
+        );
+        let result = simulate(&mut bot, &series(&[100.0, 100.0, 100.0, 88.0, 125.0, 125.0]), &params());
+
+        let trades: Vec<(bool, f64, f64)> = result
+            .trades
+            .iter()
+            .map(|t| (matches!(t.side, TradeSide::Buy), t.quantity, t.price))
+            .collect();
+        assert_eq!(
+            trades,
+            vec![
+                (true, 10.0, 100.0), // Bought by quantity
+                (true, 5.0, 88.0),   // The buy limit, once the price fell through 90
+                (false, 4.0, 125.0), // The sell limit, filled on the same tick as the close...
+                (false, 11.0, 125.0), // ...which sold what was left
+            ]
+        );
+        assert!((result.metrics.final_value - (10_000.0 - 1_000.0 - 440.0 + 15.0 * 125.0)).abs() < 1e-9);
+        assert_eq!(result.stopped_reason, None);
+    }
+
+    #[test]
+    fn test_max_drawdown_and_validation() {
+        assert_eq!(max_drawdown_pct([100.0, 120.0, 90.0, 130.0]), 25.0);
+        assert_eq!(max_drawdown_pct([100.0, 110.0]), 0.0);
+
+        let now = params().end;
+        assert!(validate(&params(), now, None).is_ok());
+        let mut backwards = params();
+        backwards.end = backwards.start;
+        assert!(validate(&backwards, now, None).is_err());
+        let later = now + ChronoDuration::days(MAX_BACKTEST_DAYS + 1);
+        assert!(validate(&params(), later, None).is_err());
+        // Imported history reaching back to the start makes older ranges valid
+        assert!(validate(&params(), later, Some(params().start)).is_ok());
+        assert!(validate(&params(), later, Some(params().start + ChronoDuration::hours(1))).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::db::{queries, Database, DbBackend};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Prefix of every backup file name (scheduled and downloaded)
+const BACKUP_FILE_PREFIX: &str = "trading_sim-";
+
+/// Export formats
+/// `Sqlite` is a byte-for-byte database copy (SQLite only), `Json` a per-table dump (any backend)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackupFormat {
+    Sqlite,
+    Json,
+}
+
+impl BackupFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sqlite" => Some(BackupFormat::Sqlite),
+            "json" => Some(BackupFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Native snapshot where available, JSON otherwise
+    pub fn default_for(backend: DbBackend) -> Self {
+        match backend {
+            DbBackend::Sqlite => BackupFormat::Sqlite,
+            DbBackend::Postgres => BackupFormat::Json,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BackupFormat::Sqlite => "db",
+            BackupFormat::Json => "json",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            BackupFormat::Sqlite => "application/vnd.sqlite3",
+            BackupFormat::Json => "application/json",
+        }
+    }
+}
+
+/// File name for a backup taken now, e.g. `trading_sim-20250130-120000.db`
+pub fn backup_file_name(format: BackupFormat) -> String {
+    format!(
+        "{}{}.{}",
+        BACKUP_FILE_PREFIX,
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    )
+}
+
+/// Write a consistent backup to `path` without stopping the server
+pub async fn write_backup(db: &Database, format: BackupFormat, path: &Path) -> Result<(), String> {
+    match format {
+        BackupFormat::Sqlite => {
+            if db.backend() != DbBackend::Sqlite {
+                return Err("SQLite snapshots are only available with a SQLite database".to_string());
+            }
+            let path = path.to_str().ok_or("Backup path is not valid UTF-8")?;
+            queries::snapshot_sqlite(db.pool(), path)
+                .await
+                .map_err(|e| format!("Snapshot failed: {}", e))
+        }
+        BackupFormat::Json => {
+            let tables = queries::export_tables(db.pool(), db.backend())
+                .await
+                .map_err(|e| format!("Export failed: {}", e))?;
+            let export = serde_json::json!({
+                "exported_at": Utc::now().to_rfc3339(),
+                "tables": tables,
+            });
+            let bytes = serde_json::to_vec_pretty(&export).map_err(|e| e.to_string())?;
+            tokio::fs::write(path, bytes)
+                .await
+                .map_err(|e| format!("Failed to write backup: {}", e))
+        }
+    }
+}
+
+/// Produce a backup in memory for download (via a temporary file)
+pub async fn create_backup(db: &Database, format: BackupFormat) -> Result<Vec<u8>, String> {
+    let path = std::env::temp_dir().join(format!(
+        "{}{}.{}",
+        BACKUP_FILE_PREFIX,
+        uuid::Uuid::new_v4(),
+        format.extension()
+    ));
+
+    let result = match write_backup(db, format, &path).await {
+        Ok(()) => tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("Failed to read backup: {}", e)),
+        Err(e) => Err(e),
+    };
+
+    let _ = tokio::fs::remove_file(&path).await;
+    result
+}
+
+/// Scheduled backups, read from the environment
+/// `BACKUP_INTERVAL_HOURS` (unset or 0 = disabled), `BACKUP_DIR`, `BACKUP_KEEP`
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub interval: Option<Duration>,
+    pub dir: PathBuf,
+    pub keep: usize, // Older backups beyond this count are deleted
+}
+
+impl BackupConfig {
+    pub fn from_env() -> Self {
+        let interval_hours: u64 = std::env::var("BACKUP_INTERVAL_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let dir = std::env::var("BACKUP_DIR").unwrap_or_else(|_| "/app/data/backups".to_string());
+        let keep = std::env::var("BACKUP_KEEP")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(7);
+
+        Self {
+            interval: (interval_hours > 0).then(|| Duration::from_secs(interval_hours * 3600)),
+            dir: PathBuf::from(dir),
+            keep,
+        }
+    }
+}
+
+/// Write a backup to `config.dir` every `config.interval` and rotate old files
+pub async fn run_backup_scheduler(db: Database, config: BackupConfig) {
+    let Some(period) = config.interval else {
+        info!("Scheduled backups disabled (set BACKUP_INTERVAL_HOURS to enable)");
+        return;
+    };
+
+    info!("Scheduled backups every {:?} to {}", period, config.dir.display());
+
+    if let Err(e) = tokio::fs::create_dir_all(&config.dir).await {
+        error!("Cannot create backup directory {}: {}", config.dir.display(), e);
+        return;
+    }
+
+    let format = BackupFormat::default_for(db.backend());
+    let mut interval = tokio::time::interval(period);
+    // The first tick fires immediately; skip it so startup isn't slowed by a backup
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let path = config.dir.join(backup_file_name(format));
+        match write_backup(&db, format, &path).await {
+            Ok(()) => info!("Wrote backup {}", path.display()),
+            Err(e) => {
+                error!("Scheduled backup failed: {}", e);
+                continue;
+            }
+        }
+
+        if let Err(e) = rotate_backups(&config.dir, config.keep).await {
+            error!("Failed to rotate backups: {}", e);
+        }
+    }
+}
+
+/// Delete the oldest backups so at most `keep` remain (names sort chronologically)
+async fn rotate_backups(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut backups = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(BACKUP_FILE_PREFIX) {
+            backups.push(entry.path());
+        }
+    }
+
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep);
+    for path in &backups[..excess] {
+        tokio::fs::remove_file(path).await?;
+        info!("Removed old backup {}", path.display());
+    }
+
+    Ok(())
+}
@@ -1,24 +1,390 @@
-use crate::bots::{BotContext, BotDecision, TradingBot};
+use crate::bots::risk_managed::RiskManagedBot;
+use crate::bots::schedule::{BotSchedule, PAUSED_BY_SCHEDULE};
+use crate::bots::{BotContext, BotDecision, DecisionOutcome, ExternalSignal, SecondaryPrices, TradingBot};
+use crate::clock;
+use crate::db::queries::{self, BotConfigRow, BotDecisionEntry, Order};
 use crate::models::*;
-use crate::state::AppState;
-use std::sync::Arc;
-use tokio::time::{interval, Duration};
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::cluster_service;
+use crate::services::comparison_service;
+use crate::services::fee_service::{self, Liquidity};
+use crate::services::ledger_service;
+use crate::services::news_service;
+use crate::services::order_service;
+use crate::services::snapshot_service::BotSnapshot;
+use crate::state::{AppState, BotActivity, BotInstance, BotRuntime, NotificationKind};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::ControlFlow;
+use std::time::Duration;
+use tracing::Instrument;
+
+/// Live bot decision cadence, in market time (replays speed their clock up)
+pub const BOT_TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How far back the headlines in a bot's context go
+const HEADLINE_HOURS: i64 = 1;
+
+/// Status of a bot its user paused
+pub const PAUSED_BY_USER: &str = "paused by user";
+
+/// Most bots one user may run at once
+pub const MAX_BOTS_PER_USER: usize = 5;
+
+/// Most minutely prices a fresh bot is warmed up with: the 24 hours of 1-minute candles
+const WARM_UP_MINUTES: usize = 24 * 60;
+
+/// Spawn a bot task and register it in state.bots under its instance id, returning the bot's display name
+/// Returns None (and spawns nothing) if the bot is already running or its user runs MAX_BOTS_PER_USER bots
+/// The write lock is held across the spawn so the task's first tick always finds its entry
+/// `runtime` resumes a bot recovered from a snapshot; pass the default for a fresh start,
+/// which warms the bot up with the pair's recent minutely prices
+pub async fn launch_bot(
+    state: &AppState,
+    config: &BotConfigRow,
+    mut bot: Box<dyn TradingBot>,
+    runtime: BotRuntime,
+) -> Option<String> {
+    let bot_name = bot.name().to_string();
+    if !config.trade_limits.is_unlimited() {
+        bot = Box::new(RiskManagedBot::new(bot, config.trade_limits));
+    }
+
+    if runtime.strategy_state.is_none() && runtime.tick_count == 0 {
+        let prices = warm_up_prices(state, &config.base_asset, &config.quote_asset).await;
+        tracing::debug!("Warming up bot {} with {} prices", config.bot_id, prices.len());
+        bot.warm_up(&prices);
+    }
+
+    let mut bots = state.bots.write().await;
+    if bots.contains_key(&config.bot_id) || user_bot_count(&bots, &config.user_id) >= MAX_BOTS_PER_USER {
+        return None;
+    }
+
+    if let Some(saved) = runtime.strategy_state.clone() {
+        bot.restore_state(saved);
+    }
+
+    state.emit_bot_event(
+        &config.user_id,
+        &config.bot_id,
+        &bot_name,
+        BotActivity::Started {
+            base_asset: config.base_asset.clone(),
+            quote_asset: config.quote_asset.clone(),
+            stoploss_amount: config.stoploss_amount,
+            tick_count: runtime.tick_count,
+        },
+    );
+    record_run_start(state, config, &bot_name).await;
+
+    let task_handle = spawn_bot_task(
+        state.clone(),
+        config.bot_id.clone(),
+        config.user_id.clone(),
+        bot,
+        config.base_asset.clone(),
+        config.quote_asset.clone(),
+        config.stoploss_amount,
+        config.dry_run,
+        config.max_base_position,
+        config.schedule.clone(),
+        runtime.tick_count,
+    );
+    bots.insert(
+        config.bot_id.clone(),
+        BotInstance {
+            user_id: config.user_id.clone(),
+            bot_name: bot_name.clone(),
+            strategy: config.strategy.clone(),
+            trading_pair: (config.base_asset.clone(), config.quote_asset.clone()),
+            stoploss_amount: config.stoploss_amount,
+            initial_portfolio_value_usd: config.initial_portfolio_value_usd,
+            dry_run: config.dry_run,
+            max_base_position: config.max_base_position,
+            schedule: config.schedule.clone(),
+            paused: config.paused,
+            runtime,
+            pending_signals: Vec::new(),
+            context_failures: 0,
+            task_handle,
+        },
+    );
+
+    Some(bot_name)
+}
+
+/// Minutely prices of a pair before now, oldest first: the 1-minute candle closes, or while those
+/// haven't filled in yet (just after a restart) the 5s window sampled once a tick
+async fn warm_up_prices(state: &AppState, base_asset: &str, quote_asset: &str) -> Vec<f64> {
+    // Closes in USD by minute; None for USD itself
+    let usd_closes = |asset: &str| {
+        let asset = asset.to_string();
+        async move {
+            if asset == "USD" {
+                return None;
+            }
+            let candles = state.get_ohlc_candles_1m(&asset, WARM_UP_MINUTES).await;
+            Some(candles.into_iter().map(|c| (c.timestamp, c.close)).collect::<BTreeMap<_, _>>())
+        }
+    };
+
+    let prices: Vec<f64> = match (usd_closes(base_asset).await, usd_closes(quote_asset).await) {
+        (Some(base), None) => base.into_values().collect(),
+        (Some(base), Some(quote)) => base
+            .iter()
+            .filter_map(|(minute, price)| Some(price / quote.get(minute).filter(|q| **q > 0.0)?))
+            .collect(),
+        (None, Some(quote)) => quote.into_values().filter(|q| *q > 0.0).map(|q| 1.0 / q).collect(),
+        (None, None) => Vec::new(),
+    };
+    if !prices.is_empty() {
+        return prices;
+    }
+
+    let step = (BOT_TICK_INTERVAL.as_secs() as i64 / state.price_window_config.tick_secs).max(1) as usize;
+    let window = state
+        .get_pair_price_window(base_asset, quote_asset, state.price_window_config.points)
+        .await
+        .unwrap_or_default();
+    // The latest point is left to the first tick
+    let mut sampled: Vec<f64> = window.iter().rev().skip(step).step_by(step).map(|p| p.price).collect();
+    sampled.reverse();
+    sampled
+}
+
+/// How many bots a user runs on this instance
+pub fn user_bot_count(bots: &HashMap<BotId, BotInstance>, user_id: &UserId) -> usize {
+    bots.values().filter(|instance| &instance.user_id == user_id).count()
+}
+
+/// A running bot as its owner sees it, wherever it runs
+pub struct RunningBot {
+    pub bot_id: BotId,
+    pub bot_name: String,
+    pub strategy: String,
+    pub trading_pair: (String, String), // (base_asset, quote_asset)
+    pub stoploss_amount: f64,
+    pub initial_portfolio_value_usd: f64,
+    pub dry_run: bool,
+    pub max_base_position: Option<f64>,
+    pub schedule: Option<BotSchedule>,
+    pub paused: Option<String>, // Why it isn't trading right now, if it isn't
+    pub context_failures: u32,
+    pub comparison_id: Option<String>, // Set for A/B compared bots, which trade a shadow account
+}
+
+/// Why a bot isn't trading at `now`, if it isn't
+fn paused_reason(paused: bool, schedule: Option<&BotSchedule>, now: chrono::DateTime<chrono::Utc>) -> Option<String> {
+    if paused {
+        return Some(PAUSED_BY_USER.to_string());
+    }
+    schedule.filter(|schedule| !schedule.is_active(now)).map(|_| PAUSED_BY_SCHEDULE.to_string())
+}
+
+/// A user's running bots, by trading pair
+/// With shared state this includes the bots other instances run, known from their persisted configs
+pub async fn running_bots(state: &AppState, user_id: &UserId) -> Result<Vec<RunningBot>, sqlx::Error> {
+    let comparisons: HashMap<BotId, String> = state
+        .shadow_accounts
+        .read()
+        .await
+        .iter()
+        .map(|(bot_id, account)| (bot_id.clone(), account.comparison_id.clone()))
+        .collect();
+    let now = state.clock.now();
+    let mut running: Vec<RunningBot> = state
+        .bots
+        .read()
+        .await
+        .iter()
+        .filter(|(_, instance)| &instance.user_id == user_id)
+        .map(|(bot_id, instance)| RunningBot {
+            bot_id: bot_id.clone(),
+            bot_name: instance.bot_name.clone(),
+            strategy: instance.strategy.clone(),
+            trading_pair: instance.trading_pair.clone(),
+            stoploss_amount: instance.stoploss_amount,
+            initial_portfolio_value_usd: instance.initial_portfolio_value_usd,
+            dry_run: instance.dry_run,
+            max_base_position: instance.max_base_position,
+            schedule: instance.schedule.clone(),
+            paused: paused_reason(instance.paused, instance.schedule.as_ref(), now),
+            context_failures: instance.context_failures,
+            comparison_id: comparisons.get(bot_id).cloned(),
+        })
+        .collect();
+
+    if state.cluster.is_some() {
+        for config in queries::list_user_bot_configs(state.db.pool(), user_id).await? {
+            if running.iter().any(|bot| bot.bot_id == config.bot_id) {
+                continue;
+            }
+            let bot_name = crate::bots::create_bot(&config.strategy, config.stoploss_amount, &config.parameters)
+                .map(|bot| bot.name().to_string())
+                .unwrap_or_else(|_| config.strategy.clone());
+            running.push(RunningBot {
+                bot_id: config.bot_id,
+                bot_name,
+                strategy: config.strategy,
+                trading_pair: (config.base_asset, config.quote_asset),
+                stoploss_amount: config.stoploss_amount,
+                initial_portfolio_value_usd: config.initial_portfolio_value_usd,
+                dry_run: config.dry_run,
+                max_base_position: config.max_base_position,
+                paused: paused_reason(config.paused, config.schedule.as_ref(), now),
+                schedule: config.schedule,
+                context_failures: 0, // Only the instance running it knows
+                comparison_id: None, // Compared bots aren't persisted, so never run elsewhere
+            });
+        }
+    }
+
+    running.sort_by(|a, b| (&a.trading_pair, &a.strategy, &a.bot_id).cmp(&(&b.trading_pair, &b.strategy, &b.bot_id)));
+    Ok(running)
+}
+
+/// Respawn bots persisted in the bots table (called once at startup)
+/// Bots found in the recovered snapshot pick up where they left off
+pub async fn restore_bots(state: AppState, mut recovered: HashMap<BotId, BotSnapshot>) {
+    let configs = match queries::load_bot_configs(state.db.pool()).await {
+        Ok(configs) => configs,
+        Err(e) => {
+            tracing::error!("Failed to load persisted bots: {}", e);
+            return;
+        }
+    };
+
+    tracing::info!("Restoring {} persisted bot(s)", configs.len());
+
+    for config in configs {
+        // A snapshot of a different strategy belongs to a bot that has since been replaced
+        let runtime = recovered
+            .remove(&config.bot_id)
+            .filter(|snapshot| snapshot.strategy == config.strategy)
+            .map(|snapshot| snapshot.runtime)
+            .unwrap_or_default();
+        adopt_bot(&state, config, runtime).await;
+    }
+}
+
+/// Launch a persisted bot on this instance
+/// With shared state the bot's lease must be acquired first, as another instance may own it
+/// The bot waits for its pair to have price data so its first tick doesn't fail
+pub async fn adopt_bot(state: &AppState, config: BotConfigRow, runtime: BotRuntime) {
+    if state.get_user(&config.user_id).await.is_none() {
+        tracing::warn!("Dropping persisted bot for unknown user {}", config.user_id);
+        let _ = queries::delete_bot_config(state.db.pool(), &config.bot_id).await;
+        return;
+    }
+
+    let bot = match crate::bots::create_bot(&config.strategy, config.stoploss_amount, &config.parameters) {
+        Ok(bot) => bot,
+        Err(e) => {
+            tracing::warn!("Dropping persisted bot for user {}: {}", config.user_id, e);
+            let _ = queries::delete_bot_config(state.db.pool(), &config.bot_id).await;
+            return;
+        }
+    };
+
+    if let Some(cluster) = &state.cluster {
+        if !cluster.try_acquire(state, &cluster_service::bot_lease(&config.bot_id)).await {
+            return; // Running on another instance
+        }
+    }
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        // Wait up to 2 minutes for the price feed to produce data for this pair
+        for _ in 0..24 {
+            if state.get_pair_price(&config.base_asset, &config.quote_asset).await.is_ok() {
+                break;
+            }
+            clock::sleep(&state.clock, Duration::from_secs(5)).await;
+        }
+
+        // None if the bot was adopted twice, or the user started bots up to the limit while we were waiting
+        if let Some(bot_name) = launch_bot(&state, &config, bot, runtime).await {
+            tracing::info!(
+                "Restored bot '{}' for user {} on {}/{}",
+                bot_name,
+                config.user_id,
+                config.base_asset,
+                config.quote_asset
+            );
+        }
+    });
+}
+
+/// Strategy that acts on queued external signals
+pub const SIGNAL_STRATEGY: &str = "signal_follower";
+
+/// Signals a bot may have waiting; more arriving before its next tick are refused
+const MAX_PENDING_SIGNALS: usize = 100;
+
+/// Why an external signal couldn't be queued
+#[derive(Debug)]
+pub enum SignalError {
+    NoBot,
+    IgnoresSignals,                // None of the running bots follows signals
+    PairMismatch(String, String),  // Pair a running signal_follower bot trades instead
+    QueueFull,
+}
+
+/// Queue a signal for a signal_follower bot the user runs on the pair; it is acted on at the next tick
+pub async fn queue_signal(
+    state: &AppState,
+    user_id: &UserId,
+    base_asset: &str,
+    quote_asset: &str,
+    signal: ExternalSignal,
+) -> Result<(), SignalError> {
+    let mut bots = state.bots.write().await;
+    let user_bots: Vec<&mut BotInstance> = bots.values_mut().filter(|instance| &instance.user_id == user_id).collect();
+    if user_bots.is_empty() {
+        return Err(SignalError::NoBot);
+    }
+
+    let followers: Vec<&mut BotInstance> =
+        user_bots.into_iter().filter(|instance| instance.strategy == SIGNAL_STRATEGY).collect();
+    let (bot_base, bot_quote) = followers.first().ok_or(SignalError::IgnoresSignals)?.trading_pair.clone();
+    let instance = followers
+        .into_iter()
+        .find(|instance| instance.trading_pair.0 == base_asset && instance.trading_pair.1 == quote_asset)
+        .ok_or(SignalError::PairMismatch(bot_base, bot_quote))?;
+    if instance.pending_signals.len() >= MAX_PENDING_SIGNALS {
+        return Err(SignalError::QueueFull);
+    }
+
+    instance.pending_signals.push(signal);
+    Ok(())
+}
 
 /// Spawn a bot execution task for a user
 /// Returns JoinHandle for the spawned task
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_bot_task(
     state: AppState,
+    bot_id: BotId,
     user_id: UserId,
     bot: Box<dyn TradingBot>,
     base_asset: String,
     quote_asset: String,
     stoploss_amount: f64,
-    initial_portfolio_value: f64,
+    dry_run: bool,
+    max_base_position: Option<f64>,
+    schedule: Option<BotSchedule>,
+    start_tick: u64,
 ) -> tokio::task::JoinHandle<()> {
+    // Detached from whichever request started the bot; ticks open their own spans
+    let span = tracing::info_span!(parent: None, "bot", user_id = %user_id, bot_id = %bot_id);
+
     tokio::spawn(async move {
         let mut bot = bot;
-        let mut tick_count = 0u64;
-        let mut interval = interval(Duration::from_secs(60)); // 60-second cadence
+        let mut tick_count = start_tick;
+        let mut interval = clock::interval(&state.clock, BOT_TICK_INTERVAL);
+        let secondary_asset = bot.secondary_asset().map(str::to_string);
+        let mut context_failures = 0u32;
+        let mut retry_in: Option<Duration> = None; // Set after a failed tick to retry sooner than the next
 
         tracing::info!(
             "Bot '{}' started for user {} on {}/{} (stoploss: ${:.2})",
@@ -30,102 +396,337 @@ pub fn spawn_bot_task(
         );
 
         loop {
-            interval.tick().await;
+            match retry_in.take() {
+                Some(backoff) => clock::sleep(&state.clock, backoff).await,
+                None => interval.tick().await,
+            }
 
-            // Check if bot was stopped by user
-            let bot_exists = {
-                let state_lock = state.inner.read().await;
-                state_lock.active_bots.contains_key(&user_id)
+            // Check if bot was stopped by user, and collect signals queued since the last tick
+            let instance = {
+                let mut bots = state.bots.write().await;
+                bots.get_mut(&bot_id)
+                    .map(|instance| (std::mem::take(&mut instance.pending_signals), instance.paused))
             };
 
-            if !bot_exists {
+            let Some((signals, paused)) = instance else {
                 tracing::info!("Bot stopped by user for {}", user_id);
                 break;
-            }
+            };
+
+            // Each tick gets its own trace id, so price -> decision -> trade -> DB write can be followed
+            let tick_span = tracing::info_span!("tick", trace_id = %uuid::Uuid::new_v4(), n = tick_count);
+            let outcome = async {
+                // An A/B compared bot trades its shadow account instead of the user's balances
+                let shadow = state
+                    .shadow_accounts
+                    .read()
+                    .await
+                    .get(&bot_id)
+                    .map(|account| (account.base_balance, account.quote_balance));
 
-            // Assemble bot context
-            let ctx = match assemble_bot_context(
-                &state,
-                &user_id,
-                &base_asset,
-                &quote_asset,
-                tick_count,
-            )
-            .await
-            {
-                Ok(ctx) => ctx,
-                Err(e) => {
-                    tracing::error!("Failed to assemble bot context: {}", e);
-                    stop_bot(&state, &user_id, "context assembly failed").await;
-                    break;
+                // Assemble bot context
+                let mut ctx = match assemble_bot_context(
+                    &state,
+                    &user_id,
+                    &base_asset,
+                    &quote_asset,
+                    tick_count,
+                    signals,
+                    secondary_asset.as_deref(),
+                )
+                .await
+                {
+                    Ok(ctx) => ctx,
+                    Err(e) => {
+                        // Usually a brief gap in the price feed: retry with backoff, and only stop
+                        // once it has failed too many ticks in a row
+                        context_failures += 1;
+                        set_context_failures(&state, &bot_id, context_failures).await;
+                        let max_failures = state.config.bots.max_context_failures;
+                        if context_failures < max_failures {
+                            let backoff = state.config.bots.retry_backoff(context_failures);
+                            tracing::warn!(
+                                "Failed to assemble bot context ({} of {}), retrying in {:?}: {}",
+                                context_failures,
+                                max_failures,
+                                backoff,
+                                e
+                            );
+                            record_run_error(&state, &user_id, &bot_id, &e);
+                            state.emit_bot_event(&user_id, &bot_id, bot.name(), BotActivity::Error {
+                                message: format!("{} (failure {} of {}, retrying in {}s)", e, context_failures, max_failures, backoff.as_secs()),
+                            });
+                            retry_in = Some(backoff);
+                            return ControlFlow::Continue(());
+                        }
+
+                        tracing::error!("Failed to assemble bot context {} times in a row: {}", context_failures, e);
+                        record_run_error(&state, &user_id, &bot_id, &e);
+                        state.emit_bot_event(&user_id, &bot_id, bot.name(), BotActivity::Error { message: e });
+                        let reason = format!("context assembly failed {} times in a row", context_failures);
+                        state.notify(
+                            &user_id,
+                            NotificationKind::BotStopped { bot_name: bot.name().to_string(), reason: reason.clone(), by_user: false },
+                        );
+                        end_bot(&state, &bot_id, RunEnd::Failed, &reason).await;
+                        return ControlFlow::Break(());
+                    }
+                };
+                if context_failures > 0 {
+                    tracing::info!("Bot '{}' recovered after {} failed context assemblies", bot.name(), context_failures);
+                    context_failures = 0;
+                    set_context_failures(&state, &bot_id, 0).await;
+                }
+                if let Some((base_balance, quote_balance)) = shadow {
+                    ctx.base_balance = base_balance;
+                    ctx.quote_balance = quote_balance;
                 }
-            };
 
-            // Call bot's tick method
-            let decision = bot.tick(&ctx);
+                // Call bot's tick method
+                let decision = bot.tick(&ctx);
 
-            // Log every tick decision at INFO level for visibility
-            tracing::info!(
-                "Bot '{}' tick {} @ ${:.2}: {:?}",
-                bot.name(),
-                tick_count,
-                ctx.current_price,
-                decision
-            );
+                // Log every tick decision at INFO level for visibility
+                tracing::info!(
+                    "Bot '{}' tick {} @ ${:.2}: {:?}",
+                    bot.name(),
+                    tick_count,
+                    ctx.current_price,
+                    decision
+                );
+                state.emit_bot_event(
+                    &user_id,
+                    &bot_id,
+                    bot.name(),
+                    BotActivity::Tick {
+                        tick: tick_count,
+                        price: ctx.current_price,
+                        decision: decision.clone(),
+                    },
+                );
 
-            // Validate and execute decision
-            match execute_bot_decision(
-                &state,
-                &user_id,
-                &decision,
-                &base_asset,
-                &quote_asset,
-                ctx.current_price,
-                bot.name(),
-            )
-            .await
-            {
-                Ok(ExecutionResult::TradeExecuted) => {
-                    tracing::info!(
-                        "Bot '{}' executed trade: {:?}",
-                        bot.name(),
-                        decision
-                    );
+                // Validate and execute decision; a dry run only logs it, and while paused or outside its
+                // schedule the bot's decisions count as DoNothing
+                let outside_schedule = schedule.as_ref().is_some_and(|schedule| !schedule.is_active(state.clock.now()));
+                let execution = match &decision {
+                    BotDecision::DoNothing if dry_run || paused || outside_schedule => Ok(ExecutionResult::NoAction),
+                    _ if paused => Ok(ExecutionResult::Paused),
+                    _ if outside_schedule => Ok(ExecutionResult::OutsideSchedule),
+                    _ if dry_run => Ok(ExecutionResult::DryRun),
+                    _ if shadow.is_some() => {
+                        execute_shadow_decision(&state, &bot_id, &decision, ctx.current_price, max_base_position).await
+                    }
+                    _ => {
+                        execute_bot_decision(
+                            &state,
+                            &user_id,
+                            &decision,
+                            &base_asset,
+                            &quote_asset,
+                            ctx.current_price,
+                            TradeSource::bot(&bot_id, bot.name()),
+                            max_base_position,
+                        )
+                        .await
+                    }
+                };
+
+                // Keep the decision for the user's audit trail
+                let (outcome, trade_id) = decision_outcome(&execution);
+                // Positions the bot tracks follow what actually traded
+                if decision != BotDecision::DoNothing {
+                    bot.on_outcome(match &execution {
+                        Ok(
+                            ExecutionResult::TradeExecuted(_)
+                            | ExecutionResult::OrderPlaced(_)
+                            | ExecutionResult::DryRun
+                            | ExecutionResult::ShadowTrade,
+                        ) => DecisionOutcome::Executed,
+                        Ok(ExecutionResult::PositionCapped { allowed, .. }) if *allowed > 0.0 => {
+                            DecisionOutcome::Resized(*allowed)
+                        }
+                        _ => DecisionOutcome::Skipped,
+                    });
                 }
-                Ok(ExecutionResult::NoAction) => {
-                    // DoNothing decision, continue
+                record_decision(
+                    &state,
+                    BotDecisionEntry {
+                        id: 0, // Assigned by the database
+                        user_id: user_id.clone(),
+                        bot_instance_id: bot_id.clone(),
+                        bot_name: bot.name().to_string(),
+                        tick: tick_count as i64,
+                        price: ctx.current_price,
+                        decision: serde_json::to_value(&decision).unwrap_or_default(),
+                        outcome: outcome.to_string(),
+                        trade_id,
+                        decided_at: state.clock.now(),
+                    },
+                );
+
+                match execution {
+                    Ok(ExecutionResult::TradeExecuted(_)) => {
+                        tracing::info!(
+                            "Bot '{}' executed trade: {:?}",
+                            bot.name(),
+                            decision
+                        );
+                        state.emit_bot_event(
+                            &user_id,
+                            &bot_id,
+                            bot.name(),
+                            BotActivity::TradeExecuted {
+                                tick: tick_count,
+                                price: ctx.current_price,
+                                decision: decision.clone(),
+                            },
+                        );
+                        state.notify(
+                            &user_id,
+                            NotificationKind::BotTrade {
+                                bot_name: bot.name().to_string(),
+                                base_asset: base_asset.clone(),
+                                quote_asset: quote_asset.clone(),
+                                price: ctx.current_price,
+                                decision: decision.clone(),
+                            },
+                        );
+                    }
+                    Ok(ExecutionResult::NoAction) => {
+                        // DoNothing decision, continue
+                    }
+                    Ok(ExecutionResult::OrderPlaced(order_id)) => {
+                        tracing::info!("Bot '{}' placed limit order {}: {:?}", bot.name(), order_id, decision);
+                    }
+                    Ok(ExecutionResult::DryRun) => {
+                        tracing::info!("Bot '{}' would have traded (dry run): {:?}", bot.name(), decision);
+                    }
+                    Ok(ExecutionResult::Paused) => {
+                        tracing::debug!("Bot '{}' is paused, not trading: {:?}", bot.name(), decision);
+                    }
+                    Ok(ExecutionResult::OutsideSchedule) => {
+                        tracing::debug!("Bot '{}' is outside its schedule, not trading: {:?}", bot.name(), decision);
+                    }
+                    Ok(ExecutionResult::ShadowTrade) => {
+                        tracing::info!("Bot '{}' traded its shadow account: {:?}", bot.name(), decision);
+                    }
+                    Ok(ExecutionResult::PositionCapped { requested, allowed, .. }) => {
+                        let cap = max_base_position.unwrap_or_default();
+                        tracing::info!(
+                            "Bot '{}' buy of {:.8} {} capped to {:.8} by its {:.8} position limit",
+                            bot.name(),
+                            requested,
+                            base_asset,
+                            allowed,
+                            cap
+                        );
+                        state.emit_bot_event(
+                            &user_id,
+                            &bot_id,
+                            bot.name(),
+                            BotActivity::PositionCapped { tick: tick_count, requested, allowed, max_base_position: cap },
+                        );
+                        // What was bought, if anything; a compared bot's buy was only in its shadow account
+                        if allowed > 0.0 && shadow.is_none() {
+                            let bought = BotDecision::BuyBase { quantity: allowed };
+                            state.emit_bot_event(
+                                &user_id,
+                                &bot_id,
+                                bot.name(),
+                                BotActivity::TradeExecuted { tick: tick_count, price: ctx.current_price, decision: bought.clone() },
+                            );
+                            state.notify(
+                                &user_id,
+                                NotificationKind::BotTrade {
+                                    bot_name: bot.name().to_string(),
+                                    base_asset: base_asset.clone(),
+                                    quote_asset: quote_asset.clone(),
+                                    price: ctx.current_price,
+                                    decision: bought,
+                                },
+                            );
+                        }
+                    }
+                    Ok(ExecutionResult::InsufficientFunds(msg)) => {
+                        tracing::warn!("Bot stopped due to insufficient funds: {}", msg);
+                        state.notify(
+                            &user_id,
+                            NotificationKind::BotStopped {
+                                bot_name: bot.name().to_string(),
+                                reason: format!("insufficient funds: {}", msg),
+                                by_user: false,
+                            },
+                        );
+                        end_bot(&state, &bot_id, RunEnd::Failed, "insufficient funds").await;
+                        return ControlFlow::Break(());
+                    }
+                    Err(e) => {
+                        tracing::error!("Bot execution error: {}", e);
+                        record_run_error(&state, &user_id, &bot_id, &e);
+                        state.emit_bot_event(&user_id, &bot_id, bot.name(), BotActivity::Error { message: e.clone() });
+                        state.notify(
+                            &user_id,
+                            NotificationKind::BotStopped {
+                                bot_name: bot.name().to_string(),
+                                reason: format!("execution error: {}", e),
+                                by_user: false,
+                            },
+                        );
+                        end_bot(&state, &bot_id, RunEnd::Failed, &format!("execution error: {}", e)).await;
+                        return ControlFlow::Break(());
+                    }
                 }
-                Ok(ExecutionResult::InsufficientFunds(msg)) => {
-                    tracing::warn!("Bot stopped due to insufficient funds: {}", msg);
-                    stop_bot(&state, &user_id, "insufficient funds").await;
-                    break;
+
+                // Check stoploss after trade execution; a dry run risks nothing, so it never stops on it,
+                // and a compared bot can only lose its shadow account
+                let stoploss = if dry_run {
+                    Ok(())
+                } else if shadow.is_some() {
+                    match comparison_service::record_equity(&state, &bot_id).await {
+                        Some((starting_value, value)) => stoploss_status(starting_value - value, stoploss_amount),
+                        None => Ok(()),
+                    }
+                } else {
+                    check_stoploss(&state, &user_id, &bot_id, stoploss_amount).await
+                };
+                if let Err(reason) = stoploss {
+                    tracing::warn!("Bot stopped: {}", reason);
+                    state.emit_bot_event(&user_id, &bot_id, bot.name(), BotActivity::StoplossHit { reason: reason.clone() });
+                    state.notify(
+                        &user_id,
+                        NotificationKind::StoplossHit { bot_name: bot.name().to_string(), reason: reason.clone() },
+                    );
+                    end_bot(&state, &bot_id, RunEnd::Stoploss, &reason).await;
+                    return ControlFlow::Break(());
                 }
-                Err(e) => {
-                    tracing::error!("Bot execution error: {}", e);
-                    stop_bot(&state, &user_id, &format!("execution error: {}", e)).await;
-                    break;
+
+                tick_count += 1;
+
+                // Publish progress for state snapshots
+                let strategy_state = bot.save_state();
+                if let Some(instance) = state.bots.write().await.get_mut(&bot_id) {
+                    instance.runtime = BotRuntime { tick_count, strategy_state };
                 }
+
+                ControlFlow::Continue(())
             }
+            .instrument(tick_span)
+            .await;
 
-            // Check stoploss after trade execution
-            if let Err(reason) = check_stoploss(
-                &state,
-                &user_id,
-                initial_portfolio_value,
-                stoploss_amount,
-            )
-            .await
-            {
-                tracing::warn!("Bot stopped: {}", reason);
-                stop_bot(&state, &user_id, &reason).await;
+            if outcome.is_break() {
                 break;
             }
-
-            tick_count += 1;
         }
 
         tracing::info!("Bot '{}' terminated for user {}", bot.name(), user_id);
-    })
+    }
+    .instrument(span))
+}
+
+/// Publish a bot's run of failed context assemblies for its status
+async fn set_context_failures(state: &AppState, bot_id: &BotId, failures: u32) {
+    if let Some(instance) = state.bots.write().await.get_mut(bot_id) {
+        instance.context_failures = failures;
+    }
 }
 
 /// Assemble BotContext from current state
@@ -135,19 +736,24 @@ async fn assemble_bot_context(
     base_asset: &str,
     quote_asset: &str,
     tick_count: u64,
+    signals: Vec<ExternalSignal>,
+    secondary_asset: Option<&str>,
 ) -> Result<BotContext, String> {
-    // Get price window (raw 5s data, last 720 points = 1 hour)
-    let price_window = state.get_price_window(base_asset, 720).await;
+    // Get price window in quote terms (raw 5s data, the whole configured window)
+    let price_window = state
+        .get_pair_price_window(base_asset, quote_asset, state.price_window_config.points)
+        .await
+        .map_err(|e| format!("No price data for {}/{}: {}", base_asset, quote_asset, e))?;
 
     if price_window.is_empty() {
-        return Err(format!("No price data available for {}", base_asset));
+        return Err(format!("No price data available for {}/{}", base_asset, quote_asset));
     }
 
     // Get current price for the trading pair
     let current_price = state
         .get_pair_price(base_asset, quote_asset)
         .await
-        .ok_or_else(|| format!("Could not get price for {}/{}", base_asset, quote_asset))?;
+        .map_err(|e| format!("Could not get price for {}/{}: {}", base_asset, quote_asset, e))?;
 
     // Get user balances
     let user = state
@@ -158,6 +764,24 @@ async fn assemble_bot_context(
     let base_balance = user.get_balance(base_asset);
     let quote_balance = user.get_balance(quote_asset);
 
+    let since = state.clock.now() - chrono::Duration::hours(HEADLINE_HOURS);
+    let headlines = news_service::recent_headlines(state, base_asset, since).await;
+
+    let secondary = match secondary_asset {
+        Some(asset) => Some(SecondaryPrices {
+            asset: asset.to_string(),
+            price_window: state
+                .get_pair_price_window(asset, quote_asset, state.price_window_config.points)
+                .await
+                .map_err(|e| format!("No price data for {}/{}: {}", asset, quote_asset, e))?,
+            current_price: state
+                .get_pair_price(asset, quote_asset)
+                .await
+                .map_err(|e| format!("Could not get price for {}/{}: {}", asset, quote_asset, e))?,
+        }),
+        None => None,
+    };
+
     Ok(BotContext {
         price_window,
         base_balance,
@@ -166,16 +790,50 @@ async fn assemble_bot_context(
         base_asset: base_asset.to_string(),
         quote_asset: quote_asset.to_string(),
         tick_count,
+        signals,
+        headlines,
+        secondary,
     })
 }
 
 enum ExecutionResult {
-    TradeExecuted,
+    TradeExecuted(Option<i64>), // The fill's trades row id; None for memory-only users
     NoAction,
+    OrderPlaced(String), // Id of the resting limit order
+    DryRun, // A trade the bot would have made
+    Paused, // A trade held back while the bot's user has it paused
+    OutsideSchedule, // A trade the bot's schedule held back
+    ShadowTrade, // Made in an A/B compared bot's shadow account
+    PositionCapped { trade_id: Option<i64>, requested: f64, allowed: f64 }, // A buy cut down (or skipped, 0 allowed) by max_base_position
     InsufficientFunds(String),
 }
 
+/// How an execution is recorded in the decision log: its outcome and the id of the trade it made, if any
+fn decision_outcome(execution: &Result<ExecutionResult, String>) -> (&'static str, Option<i64>) {
+    match execution {
+        Ok(ExecutionResult::TradeExecuted(trade_id)) => ("executed", *trade_id),
+        Ok(ExecutionResult::NoAction) => ("no_action", None),
+        Ok(ExecutionResult::OrderPlaced(_)) => ("order_placed", None),
+        Ok(ExecutionResult::DryRun) => ("dry_run", None),
+        Ok(ExecutionResult::Paused) => ("paused", None),
+        Ok(ExecutionResult::OutsideSchedule) => ("outside_schedule", None),
+        Ok(ExecutionResult::ShadowTrade) => ("shadow_trade", None),
+        Ok(ExecutionResult::PositionCapped { trade_id, .. }) => ("position_capped", *trade_id),
+        Ok(ExecutionResult::InsufficientFunds(_)) => ("insufficient_funds", None),
+        Err(_) => ("error", None),
+    }
+}
+
+/// The quantity a buy is cut down to so the holding stays within `max_base_position`
+/// (0 when it is already there), or None if the whole buy fits
+fn position_cap(quantity: f64, base_balance: f64, max_base_position: Option<f64>) -> Option<f64> {
+    let room = (max_base_position? - base_balance).max(0.0);
+    (quantity > room).then_some(room)
+}
+
 /// Execute bot decision with validation
+/// Buys, limit buys included, are held to `max_base_position` against the balance at execution
+#[allow(clippy::too_many_arguments)]
 async fn execute_bot_decision(
     state: &AppState,
     user_id: &UserId,
@@ -183,58 +841,70 @@ async fn execute_bot_decision(
     base_asset: &str,
     quote_asset: &str,
     current_price: f64,
-    bot_name: &str,
+    source: TradeSource,
+    max_base_position: Option<f64>,
 ) -> Result<ExecutionResult, String> {
-    match decision {
-        BotDecision::DoNothing => Ok(ExecutionResult::NoAction),
+    let user = state
+        .get_user(user_id)
+        .await
+        .ok_or_else(|| "User not found".to_string())?;
+    let base_balance = user.get_balance(base_asset);
 
-        BotDecision::Buy { quote_amount } => {
-            // Convert quote amount to base quantity
-            let base_quantity = quote_amount / current_price;
+    if let BotDecision::PlaceLimit { side, price, quantity } = decision {
+        let mut quantity = *quantity;
+        if *side == TradeSide::Buy {
+            match position_cap(quantity, base_balance, max_base_position) {
+                Some(allowed) if allowed <= 0.0 => {
+                    return Ok(ExecutionResult::PositionCapped { trade_id: None, requested: quantity, allowed: 0.0 });
+                }
+                Some(allowed) => {
+                    tracing::info!("Capping bot limit buy of {:.8} {} to {:.8}", quantity, base_asset, allowed);
+                    quantity = allowed;
+                }
+                None => {}
+            }
+        }
+        return place_bot_order(state, user_id, base_asset, quote_asset, side.clone(), *price, quantity).await;
+    }
 
-            // Validate sufficient quote balance
-            let user = state
-                .get_user(user_id)
-                .await
-                .ok_or_else(|| "User not found".to_string())?;
+    // Market trades as a side and base quantity; None for DoNothing or nothing to close
+    let Some((side, mut base_quantity)) = decision.market_order(current_price, base_balance) else {
+        return Ok(ExecutionResult::NoAction);
+    };
+    if !base_quantity.is_finite() || base_quantity <= 0.0 {
+        return Err(format!("Invalid trade quantity {} from {:?}", base_quantity, decision));
+    }
 
+    // Some of a buy past the position cap: (requested, allowed)
+    let mut capped = None;
+    if side == TradeSide::Buy {
+        if let Some(allowed) = position_cap(base_quantity, base_balance, max_base_position) {
+            if allowed <= 0.0 {
+                return Ok(ExecutionResult::PositionCapped { trade_id: None, requested: base_quantity, allowed });
+            }
+            capped = Some((base_quantity, allowed));
+            base_quantity = allowed;
+        }
+    }
+
+    match side {
+        TradeSide::Buy => {
+            // Validate sufficient quote balance, the taker fee included
+            let cost = base_quantity * current_price;
+            let fee = cost * fee_service::rate_for(state, user_id, &user, Liquidity::Taker).await;
             let quote_balance = user.get_balance(quote_asset);
 
-            if quote_balance < *quote_amount {
+            if quote_balance < cost + fee {
                 return Ok(ExecutionResult::InsufficientFunds(format!(
-                    "Cannot buy: need ${:.2} but only have ${:.2}",
-                    quote_amount, quote_balance
+                    "Cannot buy: need ${:.2} (${:.2} fee included) but only have ${:.2}",
+                    cost + fee,
+                    fee,
+                    quote_balance
                 )));
             }
-
-            // Execute buy trade
-            execute_bot_trade(
-                state,
-                user_id,
-                base_asset,
-                quote_asset,
-                TradeSide::Buy,
-                base_quantity,
-                current_price,
-                bot_name,
-            )
-            .await?;
-
-            Ok(ExecutionResult::TradeExecuted)
         }
-
-        BotDecision::Sell { quote_amount } => {
-            // Convert quote amount to base quantity
-            let base_quantity = quote_amount / current_price;
-
+        TradeSide::Sell => {
             // Validate sufficient base balance
-            let user = state
-                .get_user(user_id)
-                .await
-                .ok_or_else(|| "User not found".to_string())?;
-
-            let base_balance = user.get_balance(base_asset);
-
             if base_balance < base_quantity {
                 // Bot tried to sell more than available - not a hard error, just skip
                 // This is expected behavior (e.g., bot starting with 0 BTC in your example)
@@ -246,26 +916,114 @@ async fn execute_bot_decision(
                 );
                 return Ok(ExecutionResult::NoAction);
             }
+        }
+    }
 
-            // Execute sell trade
-            execute_bot_trade(
-                state,
-                user_id,
-                base_asset,
-                quote_asset,
-                TradeSide::Sell,
-                base_quantity,
-                current_price,
-                bot_name,
-            )
-            .await?;
+    let trade_id = execute_bot_trade(
+        state,
+        user_id,
+        base_asset,
+        quote_asset,
+        side,
+        base_quantity,
+        current_price,
+        source,
+    )
+    .await?;
+
+    Ok(match capped {
+        Some((requested, allowed)) => ExecutionResult::PositionCapped { trade_id, requested, allowed },
+        None => ExecutionResult::TradeExecuted(trade_id),
+    })
+}
 
-            Ok(ExecutionResult::TradeExecuted)
+/// Execute a compared bot's decision against its shadow account, as `execute_bot_decision` would
+/// against the user's balances; shadow accounts keep no orders, so limit orders are skipped
+async fn execute_shadow_decision(
+    state: &AppState,
+    bot_id: &BotId,
+    decision: &BotDecision,
+    current_price: f64,
+    max_base_position: Option<f64>,
+) -> Result<ExecutionResult, String> {
+    let mut accounts = state.shadow_accounts.write().await;
+    let account = accounts.get_mut(bot_id).ok_or_else(|| "Shadow account not found".to_string())?;
+
+    let Some((side, mut base_quantity)) = decision.market_order(current_price, account.base_balance) else {
+        return Ok(ExecutionResult::NoAction);
+    };
+    if !base_quantity.is_finite() || base_quantity <= 0.0 {
+        return Err(format!("Invalid trade quantity {} from {:?}", base_quantity, decision));
+    }
+
+    let mut capped = None;
+    if side == TradeSide::Buy {
+        if let Some(allowed) = position_cap(base_quantity, account.base_balance, max_base_position) {
+            if allowed <= 0.0 {
+                return Ok(ExecutionResult::PositionCapped { trade_id: None, requested: base_quantity, allowed });
+            }
+            capped = Some((base_quantity, allowed));
+            base_quantity = allowed;
         }
     }
+
+    match account.fill(side, base_quantity, current_price) {
+        Ok(true) => Ok(match capped {
+            Some((requested, allowed)) => ExecutionResult::PositionCapped { trade_id: None, requested, allowed },
+            None => ExecutionResult::ShadowTrade,
+        }),
+        Ok(false) => Ok(ExecutionResult::NoAction),
+        Err(msg) => Ok(ExecutionResult::InsufficientFunds(msg)),
+    }
 }
 
-/// Execute a trade for the bot
+/// Rest a bot's limit order on the book, where the order matcher fills it like a user's own
+/// Memory-only users (the demo user, bot replays) keep no orders, so theirs are skipped
+async fn place_bot_order(
+    state: &AppState,
+    user_id: &UserId,
+    base_asset: &str,
+    quote_asset: &str,
+    side: TradeSide,
+    price: f64,
+    quantity: f64,
+) -> Result<ExecutionResult, String> {
+    order_service::validate(quantity, price).map_err(|e| format!("Invalid limit order: {}", e))?;
+    if crate::state::is_memory_only(user_id) {
+        tracing::debug!("Skipping limit order for memory-only user {}", user_id);
+        return Ok(ExecutionResult::NoAction);
+    }
+
+    let pool = state.db.pool();
+    let open_orders = queries::count_open_orders(pool, user_id).await.map_err(|e| e.to_string())?;
+    if open_orders >= order_service::MAX_OPEN_ORDERS {
+        // Not fatal: the bot can try again once some fill or are cancelled
+        tracing::debug!("User {} has {} open orders, skipping the bot's limit order", user_id, open_orders);
+        return Ok(ExecutionResult::NoAction);
+    }
+
+    let order = Order {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user_id.clone(),
+        base_asset: base_asset.to_string(),
+        quote_asset: quote_asset.to_string(),
+        side,
+        order_type: OrderType::Limit,
+        quantity,
+        trigger_price: price,
+        status: OrderStatus::Open,
+        status_reason: None,
+        fill_price: None,
+        created_at: state.clock.now(),
+        closed_at: None,
+    };
+    queries::create_order(pool, &order).await.map_err(|e| e.to_string())?;
+
+    Ok(ExecutionResult::OrderPlaced(order.id))
+}
+
+/// Execute a trade for the bot, returning the id of its trades row
+#[allow(clippy::too_many_arguments)]
 async fn execute_bot_trade(
     state: &AppState,
     user_id: &UserId,
@@ -274,8 +1032,8 @@ async fn execute_bot_trade(
     side: TradeSide,
     quantity: f64,
     price: f64,
-    bot_name: &str,
-) -> Result<(), String> {
+    source: TradeSource,
+) -> Result<Option<i64>, String> {
     // Get USD snapshots for analytics
     let base_usd_price = if base_asset == "USD" {
         Some(1.0)
@@ -300,23 +1058,132 @@ async fn execute_bot_trade(
         price,
         base_usd_price,
         quote_usd_price,
-        Some(bot_name.to_string()), // Mark as bot-executed
+        source,
+        Liquidity::Taker,
     )
     .await
-    .map(|_| ())
+    .map(|(_, trade_id)| trade_id)
     .map_err(|e| format!("{:?}", e))
 }
 
-/// Check if stoploss has been breached
+/// Store a tick's decision without holding up the bot
+/// Memory-only users (the demo user, bot replays) keep no decision log
+fn record_decision(state: &AppState, entry: BotDecisionEntry) {
+    if crate::state::is_memory_only(&entry.user_id) {
+        return;
+    }
+
+    let pool = state.db.pool().clone();
+    tokio::spawn(
+        async move {
+            if let Err(e) = queries::insert_bot_decision(&pool, &entry).await {
+                tracing::error!("Failed to record decision of bot {}: {}", entry.bot_instance_id, e);
+            }
+        }
+        .in_current_span(),
+    );
+}
+
+/// How a bot's run ended, as kept in bot_runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunEnd {
+    Stopped, // By the user, an admin, or a new comparison
+    Stoploss,
+    Failed, // Errors or insufficient funds
+}
+
+impl RunEnd {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunEnd::Stopped => "stopped",
+            RunEnd::Stoploss => "stoploss",
+            RunEnd::Failed => "failed",
+        }
+    }
+}
+
+/// Open the bot's run in bot_runs before its task starts, so the run's errors and end, written
+/// in the background, always find it
+/// Memory-only users keep no run history
+async fn record_run_start(state: &AppState, config: &BotConfigRow, bot_name: &str) {
+    if crate::state::is_memory_only(&config.user_id) {
+        return;
+    }
+
+    if let Err(e) = queries::start_bot_run(state.db.pool(), config, bot_name, state.clock.now()).await {
+        tracing::error!("Failed to record the start of bot {}: {}", config.bot_id, e);
+    }
+}
+
+fn record_run_error(state: &AppState, user_id: &UserId, bot_id: &BotId, message: &str) {
+    if crate::state::is_memory_only(user_id) {
+        return;
+    }
+
+    let (pool, bot_id, message) = (state.db.pool().clone(), bot_id.clone(), message.to_string());
+    tokio::spawn(
+        async move {
+            if let Err(e) = queries::record_bot_run_error(&pool, &bot_id, &message).await {
+                tracing::error!("Failed to record an error of bot {}: {}", bot_id, e);
+            }
+        }
+        .in_current_span(),
+    );
+}
+
+/// Close the bot's run in bot_runs
+pub fn finish_run(state: &AppState, user_id: &UserId, bot_id: &BotId, end: RunEnd, reason: &str) {
+    if crate::state::is_memory_only(user_id) {
+        return;
+    }
+
+    let (pool, bot_id, reason, now) = (state.db.pool().clone(), bot_id.clone(), reason.to_string(), state.clock.now());
+    tokio::spawn(
+        async move {
+            if let Err(e) = queries::finish_bot_run(&pool, &bot_id, end.as_str(), &reason, now).await {
+                tracing::error!("Failed to record the end of bot {}: {}", bot_id, e);
+            }
+        }
+        .in_current_span(),
+    );
+}
+
+/// Check if stoploss has been breached by the bot's own trades
+/// Only what this bot did counts: the user's manual trades and their other bots don't move its loss
 async fn check_stoploss(
     state: &AppState,
     user_id: &UserId,
-    initial_portfolio_value: f64,
+    bot_id: &BotId,
     stoploss_amount: f64,
 ) -> Result<(), String> {
-    let current_portfolio_value = calculate_portfolio_value_usd(state, user_id).await?;
-    let loss = initial_portfolio_value - current_portfolio_value;
+    let user = state.get_user(user_id).await.ok_or_else(|| "User not found".to_string())?;
+    let trades: Vec<&Trade> = user.trade_history.iter().filter(|t| t.source.bot_instance() == Some(bot_id)).collect();
+    let mut pnl = 0.0;
+    for (asset, amount) in bot_holdings(&trades) {
+        if asset == "USD" {
+            pnl += amount;
+        } else if let Some(price) = state.get_latest_price(&asset).await {
+            pnl += amount * price;
+        } else {
+            tracing::warn!("Could not get price for {} when calculating bot {}'s P&L", asset, bot_id);
+        }
+    }
+    stoploss_status(-pnl, stoploss_amount)
+}
 
+/// What a bot's trades, fees included, added to and took from each of the user's balances
+fn bot_holdings(trades: &[&Trade]) -> BTreeMap<Asset, f64> {
+    let mut holdings = BTreeMap::new();
+    for trade in trades {
+        for posting in ledger_service::postings(trade) {
+            *holdings.entry(posting.asset).or_insert(0.0) += posting.amount;
+        }
+    }
+    holdings
+}
+
+/// Whether a loss (in USD) breaches the stoploss
+fn stoploss_status(loss: f64, stoploss_amount: f64) -> Result<(), String> {
     if loss >= stoploss_amount {
         Err(format!(
             "Stoploss breached: lost ${:.2} (limit: ${:.2})",
@@ -359,16 +1226,191 @@ pub async fn calculate_portfolio_value_usd(
     Ok(total_usd)
 }
 
-/// Stop a bot (remove from active_bots map)
-async fn stop_bot(state: &AppState, user_id: &UserId, reason: &str) {
-    let mut state_lock = state.inner.write().await;
-    if let Some(bot_instance) = state_lock.active_bots.remove(user_id) {
-        bot_instance.task_handle.abort(); // Abort the task
+/// Remove a bot's persisted configuration (and its lease) without blocking
+/// Spawned because stop_bot may be running inside the bot task it is about to abort
+pub fn forget_bot_config(state: &AppState, bot_id: &BotId) {
+    let state = state.clone();
+    let bot_id = bot_id.clone();
+    tokio::spawn(
+        async move {
+            if let Err(e) = queries::delete_bot_config(state.db.pool(), &bot_id).await {
+                tracing::error!("Failed to delete config of bot {}: {}", bot_id, e);
+            }
+            if let Some(cluster) = &state.cluster {
+                cluster.release(&state, &cluster_service::bot_lease(&bot_id)).await;
+            }
+        }
+        .in_current_span(),
+    );
+}
+
+/// Stop a bot on this instance only, keeping its persisted configuration
+/// Used when another instance owns (or has stopped) the bot
+pub async fn release_bot(state: &AppState, bot_id: &BotId) {
+    let mut bots = state.bots.write().await;
+    if let Some(bot_instance) = bots.remove(bot_id) {
+        bot_instance.task_handle.abort();
         tracing::info!(
-            "Bot '{}' stopped for user {}: {}",
+            "Bot '{}' ({}) released by this instance for user {}",
             bot_instance.bot_name,
+            bot_id,
+            bot_instance.user_id
+        );
+    }
+}
+
+/// Pause or resume a bot running on this instance, keeping its strategy state; the change is
+/// persisted so a restart keeps it. Returns the bot's name, or None if it doesn't run here
+pub async fn set_paused(state: &AppState, bot_id: &BotId, paused: bool) -> Option<String> {
+    let (user_id, bot_name) = {
+        let mut bots = state.bots.write().await;
+        let instance = bots.get_mut(bot_id)?;
+        if instance.paused == paused {
+            return Some(instance.bot_name.clone());
+        }
+        instance.paused = paused;
+        (instance.user_id.clone(), instance.bot_name.clone())
+    };
+    if let Err(e) = queries::set_bot_paused(state.db.pool(), bot_id, paused).await {
+        tracing::error!("Failed to persist pause of bot {}: {}", bot_id, e);
+    }
+
+    let (activity, action) = if paused {
+        (BotActivity::Paused, AuditAction::BotPaused)
+    } else {
+        (BotActivity::Resumed, AuditAction::BotResumed)
+    };
+    state.emit_bot_event(&user_id, bot_id, &bot_name, activity);
+    audit_service::record(state.db.pool(), Some(&user_id), action, bot_name.clone());
+    tracing::info!("Bot '{}' ({}) {} for user {}", bot_name, bot_id, if paused { "paused" } else { "resumed" }, user_id);
+    Some(bot_name)
+}
+
+/// Tell a user's notification endpoints that one of their bots started
+pub fn notify_started(state: &AppState, config: &BotConfigRow, bot_name: &str) {
+    state.notify(
+        &config.user_id,
+        NotificationKind::BotStarted {
+            bot_name: bot_name.to_string(),
+            base_asset: config.base_asset.clone(),
+            quote_asset: config.quote_asset.clone(),
+            stoploss_amount: config.stoploss_amount,
+            dry_run: config.dry_run,
+        },
+    );
+}
+
+/// Stop a bot (remove it from state.bots)
+pub async fn stop_bot(state: &AppState, bot_id: &BotId, reason: &str) {
+    end_bot(state, bot_id, RunEnd::Stopped, reason).await;
+}
+
+/// Stop a bot, recording how its run ended
+pub async fn end_bot(state: &AppState, bot_id: &BotId, end: RunEnd, reason: &str) {
+    let mut bots = state.bots.write().await;
+    if let Some(bot_instance) = bots.remove(bot_id) {
+        let user_id = &bot_instance.user_id;
+        forget_bot_config(state, bot_id);
+        finish_run(state, user_id, bot_id, end, reason);
+        comparison_service::mark_stopped(state, bot_id, reason).await;
+        state.emit_bot_event(
+            user_id,
+            bot_id,
+            &bot_instance.bot_name,
+            BotActivity::Stopped { reason: reason.to_string() },
+        );
+        tracing::info!(
+            "Bot '{}' ({}) stopped for user {}: {}",
+            bot_instance.bot_name,
+            bot_id,
             user_id,
             reason
         );
+        audit_service::record(
+            state.db.pool(),
+            Some(user_id),
+            AuditAction::BotStopped,
+            format!("{}: {}", bot_instance.bot_name, reason),
+        );
+        // Last: a bot ending itself runs this on its own task, and nothing after the abort would run
+        bot_instance.task_handle.abort();
+    }
+}
+
+/// Stop every bot a user runs on this instance, returning the names of those it stopped
+pub async fn stop_user_bots(state: &AppState, user_id: &UserId, reason: &str) -> Vec<String> {
+    let bots: Vec<(BotId, String)> = state
+        .bots
+        .read()
+        .await
+        .iter()
+        .filter(|(_, instance)| &instance.user_id == user_id)
+        .map(|(bot_id, instance)| (bot_id.clone(), instance.bot_name.clone()))
+        .collect();
+    for (bot_id, _) in &bots {
+        stop_bot(state, bot_id, reason).await;
+    }
+    bots.into_iter().map(|(_, bot_name)| bot_name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn trade(side: TradeSide, quantity: f64, price: f64, fee: f64) -> Trade {
+        Trade {
+            user_id: "u1".to_string(),
+            transaction_type: TransactionType::Trade,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            side,
+            quantity,
+            price,
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            base_usd_price: None,
+            quote_usd_price: None,
+            executed_by_bot: None,
+            source: TradeSource::Manual,
+            fee,
+        }
+    }
+
+    #[test]
+    fn test_position_cap_clamps_a_buy_to_the_room_left() {
+        assert_eq!(position_cap(0.5, 0.75, Some(1.0)), Some(0.25));
+        assert_eq!(position_cap(0.2, 0.0, Some(0.1)), Some(0.1));
+        // A buy that fits, exactly or with room to spare, or no cap at all, goes through whole
+        assert_eq!(position_cap(0.25, 0.75, Some(1.0)), None);
+        assert_eq!(position_cap(0.1, 0.75, Some(1.0)), None);
+        assert_eq!(position_cap(5.0, 100.0, None), None);
+    }
+
+    #[test]
+    fn test_position_cap_skips_a_buy_with_no_room() {
+        assert_eq!(position_cap(0.1, 1.0, Some(1.0)), Some(0.0));
+    }
+
+    #[test]
+    fn test_position_cap_skips_a_buy_already_over_the_cap() {
+        // A holding past the cap, e.g. after it was lowered or from manual buys, never means negative room
+        assert_eq!(position_cap(0.1, 1.5, Some(1.0)), Some(0.0));
+    }
+
+    #[test]
+    fn test_capped_buys_are_logged_as_position_capped() {
+        let clamped = Ok(ExecutionResult::PositionCapped { trade_id: Some(7), requested: 0.5, allowed: 0.2 });
+        assert_eq!(decision_outcome(&clamped), ("position_capped", Some(7)));
+        let skipped = Ok(ExecutionResult::PositionCapped { trade_id: None, requested: 0.5, allowed: 0.0 });
+        assert_eq!(decision_outcome(&skipped), ("position_capped", None));
+    }
+
+    #[test]
+    fn test_bot_holdings_net_its_trades_and_fees() {
+        let buy = trade(TradeSide::Buy, 0.1, 50_000.0, 30.0);
+        let sell = trade(TradeSide::Sell, 0.04, 60_000.0, 14.4);
+        let holdings = bot_holdings(&[&buy, &sell]);
+        assert!((holdings["BTC"] - 0.06).abs() < 1e-12);
+        assert!((holdings["USD"] - (-5_030.0 + 2_400.0 - 14.4)).abs() < 1e-9);
     }
 }
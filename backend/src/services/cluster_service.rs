@@ -0,0 +1,182 @@
+use crate::api_client::ApiError;
+use crate::db::queries;
+use crate::models::{BotId, PricePoint};
+use crate::services::bot_service;
+use crate::services::price_service::PriceSource;
+use crate::state::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Lease held by the single instance that polls Coinbase and publishes prices
+const PRICE_LEASE: &str = "price_poller";
+
+/// Published prices are only needed until followers have picked them up
+const SHARED_PRICE_RETENTION_MINUTES: i64 = 60;
+
+/// Lease name for a running bot
+pub fn bot_lease(bot_id: &BotId) -> String {
+    format!("bot:{}", bot_id)
+}
+
+/// Coordination between backend instances sharing one database
+/// Enabled with `SHARED_STATE=true`; `INSTANCE_ID` and `LEASE_TTL_SECS` are optional
+pub struct Cluster {
+    pub instance_id: String,
+    pub lease_ttl: Duration,
+    price_leader: AtomicBool,
+}
+
+impl Cluster {
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("SHARED_STATE")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let instance_id = std::env::var("INSTANCE_ID")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
+        let lease_ttl_secs = std::env::var("LEASE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+
+        info!("Shared state enabled: instance {} (lease TTL {}s)", instance_id, lease_ttl_secs);
+
+        Some(Self {
+            instance_id,
+            lease_ttl: Duration::from_secs(lease_ttl_secs),
+            price_leader: AtomicBool::new(false),
+        })
+    }
+
+    /// True if this instance currently polls Coinbase for everyone
+    pub fn is_price_leader(&self) -> bool {
+        self.price_leader.load(Ordering::Relaxed)
+    }
+
+    /// Take or renew a lease; database errors count as not acquired
+    pub async fn try_acquire(&self, state: &AppState, name: &str) -> bool {
+        let now = Utc::now().timestamp();
+        let expires_at = now + self.lease_ttl.as_secs() as i64;
+
+        match queries::try_acquire_lease(state.db.pool(), name, &self.instance_id, now, expires_at).await {
+            Ok(acquired) => acquired,
+            Err(e) => {
+                error!("Failed to acquire lease {}: {}", name, e);
+                false
+            }
+        }
+    }
+
+    pub async fn release(&self, state: &AppState, name: &str) {
+        if let Err(e) = queries::release_lease(state.db.pool(), name, &self.instance_id).await {
+            error!("Failed to release lease {}: {}", name, e);
+        }
+    }
+}
+
+/// Fetch the next live price for `asset`
+/// The price leader (or a standalone instance) polls the source and publishes the result;
+/// followers read what the leader published. Ok(None) means nothing new since `last_seen`
+pub async fn fetch_live_price(
+    state: &AppState,
+    source: &mut PriceSource,
+    asset: &str,
+    last_seen: &mut Option<DateTime<Utc>>,
+) -> Result<Option<PricePoint>, ApiError> {
+    let cluster = match &state.cluster {
+        Some(cluster) => cluster,
+        None => return source.fetch(asset, state.clock.now()).await.map(Some),
+    };
+
+    if cluster.is_price_leader() {
+        let point = source.fetch(asset, state.clock.now()).await?;
+        if let Err(e) = queries::insert_shared_price(state.db.pool(), &point).await {
+            error!("Failed to publish {} price: {}", asset, e);
+        }
+        *last_seen = Some(point.timestamp);
+        return Ok(Some(point));
+    }
+
+    let point = queries::latest_shared_price(state.db.pool(), asset)
+        .await
+        .map_err(|e| ApiError::RequestFailed(format!("Shared price lookup failed: {}", e)))?;
+
+    match point {
+        Some(point) if last_seen.is_none_or(|t| point.timestamp > t) => {
+            *last_seen = Some(point.timestamp);
+            Ok(Some(point))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Renew leases, hand over stopped bots and adopt orphaned ones, every third of the lease TTL
+pub async fn run_cluster(state: AppState) {
+    let Some(cluster) = state.cluster.clone() else {
+        return;
+    };
+
+    let mut interval = tokio::time::interval(cluster.lease_ttl / 3);
+
+    loop {
+        interval.tick().await;
+
+        // Price polling leadership
+        let leader = cluster.try_acquire(&state, PRICE_LEASE).await;
+        if leader != cluster.price_leader.swap(leader, Ordering::Relaxed) {
+            info!(
+                "Instance {} {} price polling",
+                cluster.instance_id,
+                if leader { "took over" } else { "handed off" }
+            );
+        }
+        if leader {
+            let cutoff = (Utc::now() - ChronoDuration::minutes(SHARED_PRICE_RETENTION_MINUTES))
+                .format("%Y-%m-%dT%H:%M:%S")
+                .to_string();
+            if let Err(e) = queries::prune_shared_prices(state.db.pool(), &cutoff).await {
+                error!("Failed to prune shared prices: {}", e);
+            }
+        }
+
+        let configs = match queries::load_bot_configs(state.db.pool()).await {
+            Ok(configs) => configs,
+            Err(e) => {
+                error!("Failed to load bot configs: {}", e);
+                continue;
+            }
+        };
+
+        // Renew our bots; drop the ones stopped elsewhere or taken over after a missed renewal
+        let local_bots: Vec<BotId> = {
+            let bots = state.bots.read().await;
+            bots.iter()
+                .filter(|(_, instance)| instance.user_id != "demo_user")
+                .map(|(bot_id, _)| bot_id.clone())
+                .collect()
+        };
+        for bot_id in &local_bots {
+            if !configs.iter().any(|c| &c.bot_id == bot_id) {
+                info!("Bot {} was stopped on another instance", bot_id);
+                bot_service::release_bot(&state, bot_id).await;
+                cluster.release(&state, &bot_lease(bot_id)).await;
+            } else if !cluster.try_acquire(&state, &bot_lease(bot_id)).await {
+                warn!("Lost lease for bot {}, stopping local task", bot_id);
+                bot_service::release_bot(&state, bot_id).await;
+            }
+        }
+
+        // Adopt bots whose owner went away (their lease expired)
+        for config in configs {
+            if !local_bots.contains(&config.bot_id) {
+                bot_service::adopt_bot(&state, config, Default::default()).await;
+            }
+        }
+    }
+}
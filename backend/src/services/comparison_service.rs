@@ -0,0 +1,250 @@
+use crate::models::{BotId, TradeSide, UserId};
+use crate::services::bot_service;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use utoipa::ToSchema;
+
+/// Equity points kept per bot: a week of ticks, then the oldest go
+const MAX_EQUITY_POINTS: usize = 7 * 24 * 60;
+
+/// An A/B compared bot's virtual sub-account: the pair's balances cloned from its user when the
+/// comparison starts, traded in place of the user's own
+/// Kept in memory only; stopped bots keep theirs until the user starts another comparison
+#[derive(Debug, Clone)]
+pub struct ShadowAccount {
+    pub user_id: UserId,
+    pub comparison_id: String,
+    pub bot_name: String,
+    pub strategy: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub base_balance: f64,
+    pub quote_balance: f64,
+    pub starting_value_usd: f64,
+    pub trades: u32,
+    pub equity: VecDeque<EquityPoint>, // Value after each tick, oldest first, from the start
+    pub started_at: DateTime<Utc>,
+    pub stopped_reason: Option<String>, // Set once the bot stops
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value_usd: f64,
+}
+
+impl ShadowAccount {
+    /// Make a market fill at `price` like a live bot would: false for a sell of more than the account
+    /// holds (skipped), an error for a buy it can't afford
+    pub fn fill(&mut self, side: TradeSide, quantity: f64, price: f64) -> Result<bool, String> {
+        let cost = quantity * price;
+        match side {
+            TradeSide::Buy if self.quote_balance < cost => {
+                return Err(format!("Cannot buy: need ${:.2} but only have ${:.2}", cost, self.quote_balance));
+            }
+            TradeSide::Buy => {
+                self.quote_balance -= cost;
+                self.base_balance += quantity;
+            }
+            TradeSide::Sell if self.base_balance < quantity => return Ok(false),
+            TradeSide::Sell => {
+                self.base_balance -= quantity;
+                self.quote_balance += cost;
+            }
+        }
+        self.trades += 1;
+        Ok(true)
+    }
+
+    fn push_equity(&mut self, point: EquityPoint) {
+        self.equity.push_back(point);
+        while self.equity.len() > MAX_EQUITY_POINTS {
+            self.equity.pop_front();
+        }
+    }
+
+    /// The latest value, or the starting one before the first tick
+    pub fn value_usd(&self) -> f64 {
+        self.equity.back().map_or(self.starting_value_usd, |point| point.value_usd)
+    }
+}
+
+/// One side of a comparison
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ComparedBot {
+    pub bot_instance_id: BotId,
+    pub bot_name: String,
+    pub strategy: String,
+    pub running: bool,
+    pub stopped_reason: Option<String>,
+    pub base_balance: f64,
+    pub quote_balance: f64,
+    pub starting_value_usd: f64,
+    pub value_usd: f64,
+    pub return_pct: f64,
+    pub trades: u32,
+    pub equity: Vec<EquityPoint>, // Oldest first
+}
+
+/// Two bots trading cloned balances on the same live prices
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Comparison {
+    pub comparison_id: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub started_at: DateTime<Utc>,
+    pub leader: Option<BotId>, // The bot worth more now; None while they are level
+    pub bots: Vec<ComparedBot>,
+}
+
+impl ComparedBot {
+    fn of(bot_instance_id: &BotId, account: &ShadowAccount) -> Self {
+        let value_usd = account.value_usd();
+        let return_pct = if account.starting_value_usd > 0.0 {
+            (value_usd / account.starting_value_usd - 1.0) * 100.0
+        } else {
+            0.0
+        };
+        Self {
+            bot_instance_id: bot_instance_id.clone(),
+            bot_name: account.bot_name.clone(),
+            strategy: account.strategy.clone(),
+            running: account.stopped_reason.is_none(),
+            stopped_reason: account.stopped_reason.clone(),
+            base_balance: account.base_balance,
+            quote_balance: account.quote_balance,
+            starting_value_usd: account.starting_value_usd,
+            value_usd,
+            return_pct,
+            trades: account.trades,
+            equity: account.equity.iter().copied().collect(),
+        }
+    }
+}
+
+/// The comparison built from its accounts, in the order the bots were started
+fn comparison_of(accounts: Vec<(&BotId, &ShadowAccount)>) -> Option<Comparison> {
+    let (_, first) = accounts.first()?;
+    let bots: Vec<ComparedBot> = accounts.iter().map(|(bot_id, account)| ComparedBot::of(bot_id, account)).collect();
+    let best = bots.iter().map(|bot| bot.value_usd).fold(f64::NEG_INFINITY, f64::max);
+    let leaders: Vec<&ComparedBot> = bots.iter().filter(|bot| bot.value_usd == best).collect();
+    Some(Comparison {
+        comparison_id: first.comparison_id.clone(),
+        base_asset: first.base_asset.clone(),
+        quote_asset: first.quote_asset.clone(),
+        started_at: first.started_at,
+        leader: (leaders.len() == 1).then(|| leaders[0].bot_instance_id.clone()),
+        bots,
+    })
+}
+
+/// USD value of a pair's balances at the latest prices; assets without a price count as nothing
+pub async fn value_usd(state: &AppState, balances: [(&str, f64); 2]) -> f64 {
+    let mut total = 0.0;
+    for (asset, balance) in balances {
+        let price = if asset == "USD" { Some(1.0) } else { state.get_latest_price(asset).await };
+        total += balance * price.unwrap_or(0.0);
+    }
+    total
+}
+
+/// A user's comparison, running or finished
+pub async fn user_comparison(state: &AppState, user_id: &UserId) -> Option<Comparison> {
+    let accounts = state.shadow_accounts.read().await;
+    let mut mine: Vec<(&BotId, &ShadowAccount)> =
+        accounts.iter().filter(|(_, account)| &account.user_id == user_id).collect();
+    mine.sort_by_key(|(bot_id, account)| (account.started_at, account.bot_name.clone(), (*bot_id).clone()));
+    comparison_of(mine)
+}
+
+/// Stop a user's compared bots and drop their accounts, making way for a new comparison
+pub async fn end_user_comparison(state: &AppState, user_id: &UserId) {
+    let bot_ids: Vec<BotId> = state
+        .shadow_accounts
+        .read()
+        .await
+        .iter()
+        .filter(|(_, account)| &account.user_id == user_id)
+        .map(|(bot_id, _)| bot_id.clone())
+        .collect();
+    for bot_id in &bot_ids {
+        bot_service::stop_bot(state, bot_id, "replaced by a new comparison").await;
+    }
+    state.shadow_accounts.write().await.retain(|_, account| &account.user_id != user_id);
+}
+
+/// Value a bot's account after a tick and add it to its equity curve
+/// Returns the starting and current value in USD, or None if the bot has no account
+pub async fn record_equity(state: &AppState, bot_id: &BotId) -> Option<(f64, f64)> {
+    let (base_asset, base_balance, quote_asset, quote_balance) = {
+        let accounts = state.shadow_accounts.read().await;
+        let account = accounts.get(bot_id)?;
+        (account.base_asset.clone(), account.base_balance, account.quote_asset.clone(), account.quote_balance)
+    };
+    let value = value_usd(state, [(&base_asset, base_balance), (&quote_asset, quote_balance)]).await;
+
+    let mut accounts = state.shadow_accounts.write().await;
+    let account = accounts.get_mut(bot_id)?;
+    account.push_equity(EquityPoint { timestamp: state.clock.now(), value_usd: value });
+    Some((account.starting_value_usd, value))
+}
+
+/// Note that a compared bot stopped; its account stays for the comparison
+pub async fn mark_stopped(state: &AppState, bot_id: &BotId, reason: &str) {
+    if let Some(account) = state.shadow_accounts.write().await.get_mut(bot_id) {
+        account.stopped_reason.get_or_insert_with(|| reason.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn account(bot_name: &str) -> ShadowAccount {
+        let started_at = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        ShadowAccount {
+            user_id: "u1".to_string(),
+            comparison_id: "c1".to_string(),
+            bot_name: bot_name.to_string(),
+            strategy: "naive_momentum".to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            base_balance: 0.0,
+            quote_balance: 1_000.0,
+            starting_value_usd: 1_000.0,
+            trades: 0,
+            equity: VecDeque::from([EquityPoint { timestamp: started_at, value_usd: 1_000.0 }]),
+            started_at,
+            stopped_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_shadow_fills_and_comparison() {
+        let mut a = account("A");
+        assert_eq!(a.fill(TradeSide::Buy, 2.0, 100.0), Ok(true));
+        assert_eq!(a.fill(TradeSide::Sell, 3.0, 100.0), Ok(false)); // Skipped, like a live oversell
+        assert!(a.fill(TradeSide::Buy, 10.0, 100.0).is_err());
+        assert_eq!((a.base_balance, a.quote_balance, a.trades), (2.0, 800.0, 1));
+
+        let mut b = account("B");
+        a.push_equity(EquityPoint { timestamp: a.started_at, value_usd: 1_100.0 });
+        b.push_equity(EquityPoint { timestamp: b.started_at, value_usd: 950.0 });
+        b.stopped_reason = Some("stopped by user".to_string());
+
+        let (a_id, b_id) = ("bot-a".to_string(), "bot-b".to_string());
+        let comparison = comparison_of(vec![(&a_id, &a), (&b_id, &b)]).unwrap();
+        assert_eq!(comparison.leader, Some(a_id.clone()));
+        assert_eq!(comparison.bots.iter().map(|bot| bot.return_pct.round()).collect::<Vec<_>>(), vec![10.0, -5.0]);
+        assert!(comparison.bots[0].running && !comparison.bots[1].running);
+        assert_eq!(comparison.bots[0].equity.len(), 2);
+
+        // Level bots have no leader
+        let comparison = comparison_of(vec![(&a_id, &account("A")), (&b_id, &account("B"))]).unwrap();
+        assert_eq!(comparison.leader, None);
+        assert!(comparison_of(Vec::new()).is_none());
+    }
+}
@@ -0,0 +1,319 @@
+use crate::clock;
+use crate::db::queries::{self, Competition, CompetitionEntry, CompetitionTrade};
+use crate::models::{Asset, TradeSide, UserId};
+use crate::services::invariant_service::{self, Legs};
+use crate::services::trading_service::TradeError;
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+pub const MAX_NAME_LENGTH: usize = 100;
+pub const MAX_DESCRIPTION_LENGTH: usize = 2000;
+pub const MIN_STARTING_BALANCE: f64 = 100.0;
+pub const MAX_STARTING_BALANCE: f64 = 10_000_000.0;
+pub const MAX_DURATION_DAYS: i64 = 366;
+
+/// How often ended competitions are checked for final standings
+const FINALIZE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Attempts at a trade that keeps losing the race with the same participant's other trades
+const MAX_TRADE_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompetitionStatus {
+    Upcoming, // Open to join, not to trade
+    Active,
+    Ended, // Standings are final once `finalized_at` is set
+}
+
+pub fn status(competition: &Competition, now: DateTime<Utc>) -> CompetitionStatus {
+    if now < competition.starts_at {
+        CompetitionStatus::Upcoming
+    } else if now < competition.ends_at {
+        CompetitionStatus::Active
+    } else {
+        CompetitionStatus::Ended
+    }
+}
+
+/// Why a competition definition was rejected
+pub fn validate(
+    name: &str,
+    description: &str,
+    starting_balance: f64,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Name must be 1 to {} characters", MAX_NAME_LENGTH));
+    }
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(format!("Description must be at most {} characters", MAX_DESCRIPTION_LENGTH));
+    }
+    if !(MIN_STARTING_BALANCE..=MAX_STARTING_BALANCE).contains(&starting_balance) {
+        return Err(format!(
+            "Starting balance must be between {} and {} USD",
+            MIN_STARTING_BALANCE, MAX_STARTING_BALANCE
+        ));
+    }
+    if ends_at <= starts_at {
+        return Err("A competition must end after it starts".to_string());
+    }
+    if ends_at <= now {
+        return Err("A competition must end in the future".to_string());
+    }
+    if ends_at - starts_at > Duration::days(MAX_DURATION_DAYS) {
+        return Err(format!("A competition can last at most {} days", MAX_DURATION_DAYS));
+    }
+    Ok(())
+}
+
+/// Move balances for a trade of `quantity` base at `price` (quote per base)
+/// Leaves `balances` untouched when it can't be afforded
+pub fn apply_trade(
+    balances: &mut HashMap<Asset, f64>,
+    base_asset: &str,
+    quote_asset: &str,
+    side: &TradeSide,
+    quantity: f64,
+    price: f64,
+) -> Result<(), TradeError> {
+    if !quantity.is_finite() || quantity <= 0.0 {
+        return Err(TradeError::InvalidQuantity);
+    }
+    let cost = quantity * price;
+    let (spent, spend, received, receive) = match side {
+        TradeSide::Buy => (quote_asset, cost, base_asset, quantity),
+        TradeSide::Sell => (base_asset, quantity, quote_asset, cost),
+    };
+
+    if balances.get(spent).copied().unwrap_or(0.0) < spend {
+        return Err(match side {
+            TradeSide::Buy => TradeError::InsufficientFunds,
+            TradeSide::Sell => TradeError::InsufficientAssets,
+        });
+    }
+    *balances.entry(spent.to_string()).or_insert(0.0) -= spend;
+    *balances.entry(received.to_string()).or_insert(0.0) += receive;
+    Ok(())
+}
+
+/// Trade in a participant's competition portfolio at the current pair price
+/// The caller checks that the competition is active and the user has joined
+pub async fn trade(
+    state: &AppState,
+    competition_id: &str,
+    user_id: &UserId,
+    base_asset: &str,
+    quote_asset: &str,
+    side: TradeSide,
+    quantity: f64,
+) -> Result<CompetitionTrade, TradeError> {
+    let pool = state.db.pool();
+    let price = state
+        .get_pair_price(base_asset, quote_asset)
+        .await
+        .map_err(TradeError::PriceUnavailable)?;
+
+    for _ in 0..MAX_TRADE_ATTEMPTS {
+        let mut entry = queries::get_competition_entry(pool, competition_id, user_id)
+            .await
+            .map_err(|_| TradeError::PersistenceFailed)?
+            .ok_or(TradeError::UserNotFound)?;
+        let before = entry.asset_balances.clone();
+        apply_trade(&mut entry.asset_balances, base_asset, quote_asset, &side, quantity, price)?;
+        let legs = Legs::trade(base_asset, quote_asset, &side, quantity, price);
+        let violations = invariant_service::check(&before, &entry.asset_balances, &legs);
+
+        let mut trade = CompetitionTrade {
+            id: 0, // Assigned by the database
+            competition_id: competition_id.to_string(),
+            user_id: user_id.clone(),
+            base_asset: base_asset.to_string(),
+            quote_asset: quote_asset.to_string(),
+            side: side.clone(),
+            quantity,
+            price,
+            timestamp: state.clock.now(),
+        };
+        match queries::record_competition_trade(pool, &entry, &trade).await {
+            Ok(Some(id)) => {
+                trade.id = id;
+                let change = format!(
+                    "{:?} {} {}/{} @ {} in competition {}",
+                    side, quantity, base_asset, quote_asset, price, competition_id
+                );
+                invariant_service::report(state, user_id, change, violations);
+                return Ok(trade);
+            }
+            Ok(None) => continue, // Another trade of theirs landed first; re-check against it
+            Err(e) => {
+                tracing::error!("Failed to save competition trade for {}: {}", user_id, e);
+                return Err(TradeError::PersistenceFailed);
+            }
+        }
+    }
+    Err(TradeError::PersistenceFailed)
+}
+
+/// USD value of a portfolio at the latest prices; assets without a price count as nothing
+pub async fn value_usd(state: &AppState, balances: &HashMap<Asset, f64>) -> f64 {
+    let mut total = 0.0;
+    for (asset, balance) in balances.iter().filter(|(_, b)| **b > 0.0) {
+        if asset == "USD" {
+            total += balance;
+        } else if let Some(price) = state.get_latest_price(asset).await {
+            total += balance * price;
+        }
+    }
+    total
+}
+
+/// One line of a competition leaderboard
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Standing {
+    pub rank: i64,
+    pub username: String,
+    pub value_usd: f64,
+    pub return_pct: f64, // Against the starting balance
+    pub trade_count: i64,
+    #[serde(skip)]
+    pub user_id: UserId,
+}
+
+/// Rank participants by value, best first; ties go to whoever joined first
+/// `entries` come earliest joiner first, with their values
+pub fn rank(entries: Vec<(CompetitionEntry, String, f64)>, starting_balance: f64) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = entries
+        .into_iter()
+        .map(|(entry, username, value_usd)| Standing {
+            rank: 0,
+            username,
+            value_usd,
+            return_pct: (value_usd - starting_balance) / starting_balance * 100.0,
+            trade_count: entry.trade_count,
+            user_id: entry.user_id,
+        })
+        .collect();
+    // Stable, so equal values keep the join order
+    standings.sort_by(|a, b| b.value_usd.total_cmp(&a.value_usd));
+    for (i, standing) in standings.iter_mut().enumerate() {
+        standing.rank = i as i64 + 1;
+    }
+    standings
+}
+
+/// The leaderboard: recorded final values once finalized, otherwise values at the latest prices
+pub async fn standings(state: &AppState, competition: &Competition) -> Result<Vec<Standing>, sqlx::Error> {
+    let entries = queries::list_competition_entries(state.db.pool(), &competition.id).await?;
+
+    let mut valued = Vec::with_capacity(entries.len());
+    for (entry, username) in entries {
+        let value = match (competition.finalized_at, entry.final_value) {
+            (Some(_), Some(value)) => value,
+            _ => value_usd(state, &entry.asset_balances).await,
+        };
+        valued.push((entry, username, value));
+    }
+    Ok(rank(valued, competition.starting_balance))
+}
+
+async fn finalize_ended(state: &AppState) {
+    let pool = state.db.pool();
+    let ended = match queries::competitions_to_finalize(pool, state.clock.now()).await {
+        Ok(ended) => ended,
+        Err(e) => {
+            tracing::error!("Failed to load ended competitions: {}", e);
+            return;
+        }
+    };
+
+    for competition in ended {
+        let standings = match standings(state, &competition).await {
+            Ok(standings) => standings,
+            Err(e) => {
+                tracing::error!("Failed to rank competition {}: {}", competition.id, e);
+                continue;
+            }
+        };
+        let results: Vec<(UserId, f64, i64)> =
+            standings.iter().map(|s| (s.user_id.clone(), s.value_usd, s.rank)).collect();
+
+        match queries::finalize_competition(pool, &competition.id, state.clock.now(), &results).await {
+            Ok(true) => tracing::info!(
+                "Competition {} ({}) finalized with {} participants",
+                competition.name,
+                competition.id,
+                results.len()
+            ),
+            Ok(false) => {} // Another instance got there first
+            Err(e) => tracing::error!("Failed to finalize competition {}: {}", competition.id, e),
+        }
+    }
+}
+
+/// Record final standings of competitions as they end
+/// Values are taken at the first check after the end, within a minute of it
+pub async fn run_finalizer(state: AppState) {
+    let mut interval = clock::interval(&state.clock, FINALIZE_INTERVAL);
+    loop {
+        interval.tick().await;
+        finalize_ended(&state).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_trade_checks_the_balance_it_spends() {
+        let mut balances = HashMap::from([("USD".to_string(), 1000.0)]);
+
+        apply_trade(&mut balances, "BTC", "USD", &TradeSide::Buy, 0.01, 50_000.0).unwrap();
+        assert_eq!(balances["USD"], 500.0);
+        assert_eq!(balances["BTC"], 0.01);
+
+        assert!(matches!(
+            apply_trade(&mut balances, "BTC", "USD", &TradeSide::Buy, 0.02, 50_000.0),
+            Err(TradeError::InsufficientFunds)
+        ));
+        assert!(matches!(
+            apply_trade(&mut balances, "BTC", "USD", &TradeSide::Sell, 0.02, 50_000.0),
+            Err(TradeError::InsufficientAssets)
+        ));
+        assert!(apply_trade(&mut balances, "BTC", "USD", &TradeSide::Sell, 0.0, 50_000.0).is_err());
+        assert_eq!(balances["USD"], 500.0);
+    }
+
+    #[test]
+    fn test_rank_orders_by_value_and_keeps_join_order_on_ties() {
+        let entry = |user: &str| CompetitionEntry {
+            competition_id: "c".to_string(),
+            user_id: user.to_string(),
+            asset_balances: HashMap::new(),
+            version: 0,
+            trade_count: 1,
+            joined_at: Utc::now(),
+            final_value: None,
+            final_rank: None,
+        };
+        let standings = rank(
+            vec![
+                (entry("a"), "alice".to_string(), 900.0),
+                (entry("b"), "bob".to_string(), 1200.0),
+                (entry("c"), "carol".to_string(), 900.0),
+            ],
+            1000.0,
+        );
+
+        let order: Vec<(&str, i64)> = standings.iter().map(|s| (s.username.as_str(), s.rank)).collect();
+        assert_eq!(order, vec![("bob", 1), ("alice", 2), ("carol", 3)]);
+        assert_eq!(standings[0].return_pct, 20.0);
+        assert_eq!(standings[1].return_pct, -10.0);
+    }
+}
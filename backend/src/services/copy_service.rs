@@ -0,0 +1,177 @@
+use crate::db::queries::{self, CopyFollow};
+use crate::models::{Trade, TradeSide, TradeSource, TransactionType};
+use crate::services::bot_service;
+use crate::services::fee_service::{self, Liquidity};
+use crate::services::trading_service;
+use crate::state::{AppState, NotificationKind};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Leaders a user may follow at once
+pub const MAX_FOLLOWING: usize = 10;
+
+/// Largest multiplier on the portfolio-proportional size
+pub const MAX_RATIO: f64 = 10.0;
+
+/// Mirrored trades worth less than this are skipped
+const MIN_MIRROR_USD: f64 = 1.0;
+
+/// True for a trade made by mirroring a leader; these are not mirrored again,
+/// so follow chains and cycles can't cascade
+pub fn is_copy(trade: &Trade) -> bool {
+    matches!(trade.source, TradeSource::Copy { .. })
+}
+
+/// Base quantity the follower trades when the leader trades `leader_quantity`
+/// Scaled by the portfolio sizes and the follow's ratio, then capped at `max_trade_usd` and
+/// at what the follower can afford (`available`, in base units); None if that leaves only dust
+pub fn mirror_quantity(
+    leader_quantity: f64,
+    leader_value_usd: f64,
+    follower_value_usd: f64,
+    follow: &CopyFollow,
+    base_usd_price: f64,
+    available: f64,
+) -> Option<f64> {
+    if leader_value_usd <= 0.0 || follower_value_usd <= 0.0 || base_usd_price <= 0.0 {
+        return None;
+    }
+
+    let mut quantity = leader_quantity * follower_value_usd / leader_value_usd * follow.ratio;
+    if let Some(cap) = follow.max_trade_usd {
+        quantity = quantity.min(cap / base_usd_price);
+    }
+    quantity = quantity.min(available);
+
+    (quantity.is_finite() && quantity * base_usd_price >= MIN_MIRROR_USD).then_some(quantity)
+}
+
+/// Copy one trade into a follower's portfolio
+async fn mirror_to(state: &AppState, trade: &Trade, follow: &CopyFollow, leader_username: &str, leader_value: f64) {
+    let Some(base_usd_price) = trade.base_usd_price else {
+        tracing::warn!("Not mirroring {}/{} trade without a USD price", trade.base_asset, trade.quote_asset);
+        return;
+    };
+    let Some(follower) = state.get_user(&follow.follower_id).await else {
+        return;
+    };
+    let follower_value = match bot_service::calculate_portfolio_value_usd(state, &follow.follower_id).await {
+        Ok(value) => value,
+        Err(e) => {
+            tracing::warn!("Could not value follower {}: {}", follow.follower_id, e);
+            return;
+        }
+    };
+
+    // A buy's fee comes on top of its cost, so the follower can afford a little less
+    let available = match trade.side {
+        TradeSide::Buy => {
+            let fee_rate = fee_service::rate_for(state, &follow.follower_id, &follower, Liquidity::Taker).await;
+            follower.get_balance(&trade.quote_asset) / (trade.price * (1.0 + fee_rate))
+        }
+        TradeSide::Sell => follower.get_balance(&trade.base_asset),
+    };
+    let Some(quantity) =
+        mirror_quantity(trade.quantity, leader_value, follower_value, follow, base_usd_price, available)
+    else {
+        tracing::debug!("Nothing to mirror for follower {}", follow.follower_id);
+        return;
+    };
+
+    let execution = trading_service::execute_trade_internal(
+        state,
+        &follow.follower_id,
+        &trade.base_asset,
+        &trade.quote_asset,
+        trade.side.clone(),
+        quantity,
+        trade.price,
+        trade.base_usd_price,
+        trade.quote_usd_price,
+        TradeSource::Copy { leader: leader_username.to_string() },
+        Liquidity::Taker,
+    );
+
+    match execution.await {
+        Ok((copy, _)) => state.notify(
+            &follow.follower_id,
+            NotificationKind::CopyTrade {
+                leader_id: follow.leader_id.clone(),
+                leader_username: leader_username.to_string(),
+                side: copy.side,
+                base_asset: copy.base_asset,
+                quote_asset: copy.quote_asset,
+                quantity: copy.quantity,
+                price: copy.price,
+            },
+        ),
+        Err(e) => tracing::warn!("Could not mirror trade for follower {}: {:?}", follow.follower_id, e),
+    }
+}
+
+/// Mirror a leader's trade into every follower's portfolio at the same price
+/// Called from the trade-execution path for each executed trade; boxed because
+/// mirrored trades go through that same path
+pub fn mirror_trade(state: AppState, trade: Trade) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        if trade.transaction_type != TransactionType::Trade || is_copy(&trade) {
+            return;
+        }
+
+        let follows = match queries::list_followers(state.db.pool(), &trade.user_id).await {
+            Ok(follows) => follows,
+            Err(e) => {
+                tracing::error!("Failed to load followers of {}: {}", trade.user_id, e);
+                return;
+            }
+        };
+        if follows.is_empty() {
+            return;
+        }
+
+        let Some(leader) = state.get_user(&trade.user_id).await else {
+            return;
+        };
+        // The trade was at market, so it didn't change the leader's portfolio value
+        let leader_value = match bot_service::calculate_portfolio_value_usd(&state, &trade.user_id).await {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("Could not value leader {}: {}", trade.user_id, e);
+                return;
+            }
+        };
+
+        for follow in &follows {
+            mirror_to(&state, &trade, follow, &leader.username, leader_value).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn follow(ratio: f64, max_trade_usd: Option<f64>) -> CopyFollow {
+        CopyFollow {
+            follower_id: "f1".to_string(),
+            leader_id: "l1".to_string(),
+            ratio,
+            max_trade_usd,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_mirror_quantity_scales_and_caps() {
+        // Follower is a tenth of the leader's size
+        assert_eq!(mirror_quantity(1.0, 100_000.0, 10_000.0, &follow(1.0, None), 50_000.0, 10.0), Some(0.1));
+        assert_eq!(mirror_quantity(1.0, 100_000.0, 10_000.0, &follow(0.5, None), 50_000.0, 10.0), Some(0.05));
+        // $1000 cap at $50k per BTC
+        assert_eq!(mirror_quantity(1.0, 100_000.0, 10_000.0, &follow(1.0, Some(1000.0)), 50_000.0, 10.0), Some(0.02));
+        // Can't trade more than the follower has
+        assert_eq!(mirror_quantity(1.0, 100_000.0, 10_000.0, &follow(1.0, None), 50_000.0, 0.04), Some(0.04));
+        // Dust is skipped
+        assert_eq!(mirror_quantity(1.0, 100_000.0, 10_000.0, &follow(1.0, None), 50_000.0, 0.0), None);
+        assert_eq!(mirror_quantity(1.0, 0.0, 10_000.0, &follow(1.0, None), 50_000.0, 10.0), None);
+    }
+}
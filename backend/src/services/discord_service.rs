@@ -0,0 +1,72 @@
+use crate::services::notification_service::{self, truncate, Level};
+use crate::state::Notification;
+use serde_json::{json, Value};
+
+/// Hosts that serve Discord channel webhooks
+const DISCORD_HOSTS: &[&str] = &["discord.com", "discordapp.com", "ptb.discord.com", "canary.discord.com"];
+
+/// Discord's limits on embed text
+const MAX_TITLE_CHARS: usize = 256;
+const MAX_DESCRIPTION_CHARS: usize = 4096;
+
+/// Shown as the sender of every message
+const USERNAME: &str = "Trading Simulator";
+
+/// Why a URL is not a Discord channel webhook (https://discord.com/api/webhooks/<id>/<token>)
+pub fn validate_url(url: &reqwest::Url) -> Result<(), String> {
+    let host = url.host_str().unwrap_or_default();
+    if url.scheme() != "https" || !DISCORD_HOSTS.contains(&host) || !url.path().starts_with("/api/webhooks/") {
+        return Err("Discord endpoints need a channel webhook URL (https://discord.com/api/webhooks/...)".to_string());
+    }
+    Ok(())
+}
+
+fn color(level: Level) -> u32 {
+    match level {
+        Level::Info => 0x3498db,
+        Level::Success => 0x2ecc71,
+        Level::Error => 0xe74c3c,
+    }
+}
+
+/// Body of a Discord webhook execution: one embed, colored by how the news reads
+pub fn message(notification: &Notification) -> Value {
+    let summary = notification_service::summary(&notification.kind);
+    json!({
+        "username": USERNAME,
+        "embeds": [{
+            "title": truncate(&summary.title, MAX_TITLE_CHARS),
+            "description": truncate(&summary.text, MAX_DESCRIPTION_CHARS),
+            "color": color(summary.level),
+            "timestamp": notification.timestamp.to_rfc3339(),
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::NotificationKind;
+
+    #[test]
+    fn test_message_embeds_the_summary() {
+        let notification = Notification {
+            user_id: "u".to_string(),
+            timestamp: chrono::DateTime::from_timestamp(1_736_000_000, 0).unwrap(),
+            kind: NotificationKind::StoplossHit { bot_name: "grid".to_string(), reason: "x".repeat(5000) },
+        };
+
+        let body = message(&notification);
+        let embed = &body["embeds"][0];
+        assert_eq!(embed["title"], "Stoploss hit: grid");
+        assert_eq!(embed["color"], 0xe74c3c);
+        assert_eq!(embed["description"].as_str().unwrap().chars().count(), MAX_DESCRIPTION_CHARS);
+        assert_eq!(embed["timestamp"], "2025-01-04T14:13:20+00:00");
+
+        let url = |s: &str| reqwest::Url::parse(s).unwrap();
+        assert!(validate_url(&url("https://discord.com/api/webhooks/1/abc")).is_ok());
+        assert!(validate_url(&url("http://discord.com/api/webhooks/1/abc")).is_err());
+        assert!(validate_url(&url("https://evil.com/api/webhooks/1/abc")).is_err());
+        assert!(validate_url(&url("https://discord.com/channels/1")).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::services::notification_service;
+use crate::state::{Notification, NotificationKind};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+use tracing::{error, info};
+
+const MAX_ADDRESS_LENGTH: usize = 254;
+const SEND_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Outgoing mail over SMTP
+/// Enabled by `SMTP_HOST` and `SMTP_FROM`; `SMTP_PORT`, `SMTP_USERNAME`, `SMTP_PASSWORD` are optional,
+/// and `SMTP_TLS` is `starttls` (default, port 587), `tls` (port 465) or `none` (port 25, local relays only)
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+/// Why a message was not sent
+#[derive(Debug)]
+pub struct SendError {
+    pub message: String,
+    pub retryable: bool, // Connection trouble or a 4xx reply, rather than a refusal
+}
+
+impl Mailer {
+    /// Returns None if SMTP is not configured (or misconfigured, which is logged)
+    pub fn from_env() -> Option<Self> {
+        let host = std::env::var("SMTP_HOST").ok().filter(|h| !h.is_empty())?;
+        let from = match std::env::var("SMTP_FROM").map(|f| f.parse::<Mailbox>()) {
+            Ok(Ok(from)) => from,
+            Ok(Err(e)) => {
+                error!("Email disabled: SMTP_FROM is not a valid address: {}", e);
+                return None;
+            }
+            Err(_) => {
+                error!("Email disabled: SMTP_HOST is set but SMTP_FROM is not");
+                return None;
+            }
+        };
+        let tls = std::env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string());
+
+        let (builder, default_port) = match tls.as_str() {
+            "tls" => (AsyncSmtpTransport::<Tokio1Executor>::relay(&host), 465),
+            "none" => (Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host)), 25),
+            _ => (AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host), 587),
+        };
+        let mut builder = match builder {
+            Ok(builder) => builder,
+            Err(e) => {
+                error!("Email disabled: cannot set up SMTP for {}: {}", host, e);
+                return None;
+            }
+        };
+
+        let port = std::env::var("SMTP_PORT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_port);
+        builder = builder.port(port).timeout(Some(SEND_TIMEOUT));
+        if let (Ok(username), Ok(password)) = (std::env::var("SMTP_USERNAME"), std::env::var("SMTP_PASSWORD")) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        info!("Email enabled: SMTP {}:{} ({}), from {}", host, port, tls, from);
+        Some(Self { transport: builder.build(), from })
+    }
+
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> Result<(), SendError> {
+        let to: Mailbox = to
+            .parse()
+            .map_err(|e| SendError { message: format!("Invalid address: {}", e), retryable: false })?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| SendError { message: e.to_string(), retryable: false })?;
+
+        self.transport
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| SendError { retryable: !e.is_permanent(), message: e.to_string() })
+    }
+}
+
+/// Endpoint URL for an address: `mailto:` plus the address, which may be given with or without it
+pub fn validate_address(address: &str) -> Result<String, String> {
+    let address = address.trim();
+    let address = address.strip_prefix("mailto:").unwrap_or(address);
+    let valid = address.len() <= MAX_ADDRESS_LENGTH
+        && address.parse::<lettre::Address>().is_ok()
+        && !address.contains(|c: char| c.is_whitespace() || c.is_control());
+    if !valid {
+        return Err("Email endpoints need an email address (e.g. mailto:me@example.com)".to_string());
+    }
+    Ok(format!("mailto:{}", address))
+}
+
+/// The address an email endpoint's URL holds
+pub fn address(url: &str) -> &str {
+    url.strip_prefix("mailto:").unwrap_or(url)
+}
+
+/// Email carries only the rarer, weightier kinds; trades, fills and the user's own starts and
+/// stops would flood an inbox
+pub fn accepts(kind: &NotificationKind) -> bool {
+    matches!(
+        kind,
+        NotificationKind::StoplossHit { .. }
+            | NotificationKind::BotStopped { by_user: false, .. }
+            | NotificationKind::DailySummary { .. }
+            | NotificationKind::Test { .. }
+    )
+}
+
+/// Subject and plain-text body for a notification
+pub fn message(notification: &Notification) -> (String, String) {
+    let summary = notification_service::summary(&notification.kind);
+    let body = format!(
+        "{}\n\n{}\n\nSent by the trading simulator at {}. Delete this email endpoint to stop these messages.\n",
+        summary.title,
+        summary.text,
+        notification.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+    );
+    (format!("[Trading Simulator] {}", summary.title), body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_address_and_low_frequency_kinds() {
+        assert_eq!(validate_address(" me@example.com ").unwrap(), "mailto:me@example.com");
+        assert_eq!(validate_address("mailto:me@example.com").unwrap(), "mailto:me@example.com");
+        assert!(validate_address("me@example.com\r\nBcc: x@y.z").is_err());
+        assert!(validate_address("not-an-address").is_err());
+        assert_eq!(address("mailto:me@example.com"), "me@example.com");
+
+        assert!(accepts(&NotificationKind::StoplossHit { bot_name: "b".to_string(), reason: "r".to_string() }));
+        let stopped = |by_user| NotificationKind::BotStopped { bot_name: "b".to_string(), reason: "r".to_string(), by_user };
+        assert!(accepts(&stopped(false)));
+        assert!(!accepts(&stopped(true)));
+        assert!(!accepts(&NotificationKind::BotTrade {
+            bot_name: "b".to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            price: 1.0,
+            decision: crate::bots::BotDecision::Buy { quote_amount: 1.0 },
+        }));
+    }
+}
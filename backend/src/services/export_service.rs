@@ -0,0 +1,240 @@
+use crate::db::queries::{self, AuditEntry, BotConfigRow, DailySummaryRecord, Order};
+use crate::models::{Asset, Trade, TransactionType, UserData, UserId};
+use crate::pagination::SortOrder;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use zip::write::SimpleFileOptions;
+
+/// Audit actions that make up a bot's history (its trades are in the trade history)
+const BOT_ACTIONS: [&str; 4] = ["bot_started", "bot_stopped", "bot_paused", "bot_resumed"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountProfile {
+    pub user_id: UserId,
+    pub username: String,
+    pub role: Option<String>,
+    pub exported_at: DateTime<Utc>,
+}
+
+/// Everything stored about one account
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountExport {
+    pub profile: AccountProfile,
+    pub balances: BTreeMap<Asset, f64>,
+    pub trades: Vec<Trade>,                       // Deposits and withdrawals included, oldest first
+    pub orders: Vec<Order>,                       // Newest first
+    pub bots: Vec<BotConfigRow>,                  // The running bots, oldest first
+    pub bot_events: Vec<AuditEntry>,              // Bot starts, stops, pauses and resumes, oldest first
+    pub daily_summaries: Vec<DailySummaryRecord>, // Oldest first
+    pub settings: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Gather a user's data from memory (balances and trades) and the database (the rest)
+pub async fn collect(state: &AppState, user_id: &UserId, user: UserData) -> Result<AccountExport, sqlx::Error> {
+    let pool = state.db.pool();
+    let bot_events = queries::get_audit_entries(pool, Some(user_id), None, SortOrder::Asc, i64::MAX)
+        .await?
+        .into_iter()
+        .filter(|entry| BOT_ACTIONS.contains(&entry.action.as_str()))
+        .collect();
+
+    Ok(AccountExport {
+        profile: AccountProfile {
+            user_id: user_id.clone(),
+            username: user.username,
+            role: queries::get_user_role(pool, user_id).await?,
+            exported_at: state.clock.now(),
+        },
+        balances: user.asset_balances.into_iter().collect(),
+        trades: user.trade_history,
+        orders: queries::list_orders(pool, user_id, None).await?,
+        bots: queries::list_user_bot_configs(pool, user_id).await?,
+        bot_events,
+        daily_summaries: queries::get_daily_summaries(pool, user_id, None, SortOrder::Asc, i64::MAX).await?,
+        settings: queries::get_user_settings(pool, user_id).await?.map(|(settings, _)| settings).unwrap_or_default(),
+    })
+}
+
+/// Download name of an export, e.g. `trading_sim-export-alice-20250130.zip`
+pub fn file_name(export: &AccountExport) -> String {
+    let username: String = export
+        .profile
+        .username
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("trading_sim-export-{}-{}.zip", username, export.profile.exported_at.format("%Y%m%d"))
+}
+
+/// One CSV field, quoted when it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut text = header.join(",") + "\n";
+    for row in rows {
+        text += &row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",");
+        text += "\n";
+    }
+    text
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// The CSV files of an export, by name
+fn csv_files(export: &AccountExport) -> Vec<(&'static str, String)> {
+    let balances = csv(
+        &["asset", "balance"],
+        export.balances.iter().map(|(asset, balance)| vec![asset.clone(), balance.to_string()]),
+    );
+    let trades = csv(
+        &["timestamp", "type", "side", "base_asset", "quote_asset", "quantity", "price", "fee", "base_usd_price", "quote_usd_price", "bot"],
+        export.trades.iter().map(|t| {
+            let transaction_type = match t.transaction_type {
+                TransactionType::Trade => "trade",
+                TransactionType::Deposit => "deposit",
+                TransactionType::Withdrawal => "withdrawal",
+            };
+            vec![
+                t.timestamp.to_rfc3339(),
+                transaction_type.to_string(),
+                format!("{:?}", t.side).to_lowercase(),
+                t.base_asset.clone(),
+                t.quote_asset.clone(),
+                t.quantity.to_string(),
+                t.price.to_string(),
+                t.fee.to_string(),
+                optional(t.base_usd_price),
+                optional(t.quote_usd_price),
+                optional(t.executed_by_bot.as_deref()),
+            ]
+        }),
+    );
+    let orders = csv(
+        &["id", "created_at", "type", "side", "base_asset", "quote_asset", "quantity", "trigger_price", "status", "status_reason", "fill_price", "closed_at"],
+        export.orders.iter().map(|o| {
+            vec![
+                o.id.clone(),
+                o.created_at.to_rfc3339(),
+                o.order_type.as_str().to_string(),
+                format!("{:?}", o.side).to_lowercase(),
+                o.base_asset.clone(),
+                o.quote_asset.clone(),
+                o.quantity.to_string(),
+                o.trigger_price.to_string(),
+                o.status.as_str().to_string(),
+                optional(o.status_reason.as_deref()),
+                optional(o.fill_price),
+                optional(o.closed_at.map(|at| at.to_rfc3339())),
+            ]
+        }),
+    );
+    let bot_events = csv(
+        &["created_at", "action", "details"],
+        export.bot_events.iter().map(|e| vec![e.created_at.and_utc().to_rfc3339(), e.action.clone(), e.details.clone()]),
+    );
+    let summaries = csv(
+        &["date", "start_value_usd", "end_value_usd", "net_deposits_usd", "pnl_usd", "pnl_pct", "trades", "volume_usd"],
+        export.daily_summaries.iter().map(|record| {
+            let s = &record.summary;
+            vec![
+                record.date.clone(),
+                s.start_value_usd.to_string(),
+                s.end_value_usd.to_string(),
+                s.net_deposits_usd.to_string(),
+                s.pnl_usd.to_string(),
+                optional(s.pnl_pct),
+                s.trades.to_string(),
+                s.volume_usd.to_string(),
+            ]
+        }),
+    );
+    vec![
+        ("balances.csv", balances),
+        ("trades.csv", trades),
+        ("orders.csv", orders),
+        ("bot_events.csv", bot_events),
+        ("daily_summaries.csv", summaries),
+    ]
+}
+
+/// A zip archive of the export: `account.json` holds all of it, and the CSV files its tables
+/// for spreadsheets
+pub fn archive(export: &AccountExport) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec_pretty(export).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    let csvs = csv_files(export).into_iter().map(|(name, text)| (name, text.into_bytes()));
+    for (name, contents) in std::iter::once(("account.json", json)).chain(csvs) {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(&contents).map_err(|e| e.to_string())?;
+    }
+    let cursor = zip.finish().map_err(|e| e.to_string())?;
+    Ok(cursor.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TradeSide, TradeSource};
+    use chrono::TimeZone;
+    use std::io::Read;
+
+    #[test]
+    fn test_archive_holds_json_and_csv_files() {
+        let at = Utc.with_ymd_and_hms(2025, 1, 30, 12, 0, 0).unwrap();
+        let export = AccountExport {
+            profile: AccountProfile { user_id: "u1".to_string(), username: "alice smith".to_string(), role: None, exported_at: at },
+            balances: BTreeMap::from([("BTC".to_string(), 0.5), ("USD".to_string(), 1000.0)]),
+            trades: vec![Trade {
+                user_id: "u1".to_string(),
+                transaction_type: TransactionType::Trade,
+                base_asset: "BTC".to_string(),
+                quote_asset: "USD".to_string(),
+                side: TradeSide::Buy,
+                quantity: 0.5,
+                price: 40_000.0,
+                timestamp: at,
+                base_usd_price: Some(40_000.0),
+                quote_usd_price: Some(1.0),
+                executed_by_bot: Some("Grid, \"tight\"".to_string()),
+                source: TradeSource::Bot { instance_id: None, name: "Grid, \"tight\"".to_string() },
+                fee: 0.0,
+            }],
+            orders: Vec::new(),
+            bots: Vec::new(),
+            bot_events: Vec::new(),
+            daily_summaries: Vec::new(),
+            settings: serde_json::Map::new(),
+        };
+        assert_eq!(file_name(&export), "trading_sim-export-alice_smith-20250130.zip");
+
+        let bytes = archive(&export).unwrap();
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let names: Vec<&str> = zip.file_names().collect();
+        assert_eq!(names.len(), 6);
+        assert!(names.contains(&"account.json") && names.contains(&"orders.csv"));
+
+        let mut trades = String::new();
+        zip.by_name("trades.csv").unwrap().read_to_string(&mut trades).unwrap();
+        let rows: Vec<&str> = trades.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[1].starts_with("2025-01-30T12:00:00+00:00,trade,buy,BTC,USD,0.5,40000,0,"));
+        assert!(rows[1].ends_with(",\"Grid, \"\"tight\"\"\""));
+
+        let mut json = String::new();
+        zip.by_name("account.json").unwrap().read_to_string(&mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["balances"]["BTC"], 0.5);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::clock;
+use crate::db::queries::{self, FeeTierRecord};
+use crate::models::{Trade, TransactionType, UserData, UserId};
+use crate::state::{self, AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Trailing window of traded volume that sets a user's tier
+pub const VOLUME_WINDOW_DAYS: i64 = 30;
+
+/// How often every user's tier is recalculated
+const RECALCULATION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A rung of the fee schedule: traders at or above `min_volume_usd` over the last 30 days pay its rates
+/// Makers add liquidity (limit orders that rest before filling), takers remove it (market trades, stops)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+pub struct FeeTier {
+    pub name: &'static str,
+    pub min_volume_usd: f64,
+    pub maker_rate: f64, // Fraction of the quote amount, e.g. 0.004 for 0.40%
+    pub taker_rate: f64,
+}
+
+/// Which side of the book a fill took
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Liquidity {
+    Maker, // A limit order that rested before filling
+    Taker, // A market trade, or a stop that became one
+}
+
+impl FeeTier {
+    /// The rate this tier charges a fill
+    pub fn rate(&self, liquidity: Liquidity) -> f64 {
+        match liquidity {
+            Liquidity::Maker => self.maker_rate,
+            Liquidity::Taker => self.taker_rate,
+        }
+    }
+}
+
+/// The fee schedule, lowest volume first, modelled on the tiers of the big spot exchanges
+pub const TIERS: [FeeTier; 6] = [
+    FeeTier { name: "Starter", min_volume_usd: 0.0, maker_rate: 0.004, taker_rate: 0.006 },
+    FeeTier { name: "Active", min_volume_usd: 10_000.0, maker_rate: 0.0025, taker_rate: 0.004 },
+    FeeTier { name: "Trader", min_volume_usd: 50_000.0, maker_rate: 0.0015, taker_rate: 0.0025 },
+    FeeTier { name: "Pro", min_volume_usd: 100_000.0, maker_rate: 0.001, taker_rate: 0.002 },
+    FeeTier { name: "Advanced", min_volume_usd: 1_000_000.0, maker_rate: 0.0008, taker_rate: 0.0018 },
+    FeeTier { name: "VIP", min_volume_usd: 15_000_000.0, maker_rate: 0.0, taker_rate: 0.001 },
+];
+
+/// The tier a 30-day volume earns
+pub fn tier_for(volume_usd: f64) -> FeeTier {
+    TIERS
+        .iter()
+        .rev()
+        .find(|tier| volume_usd >= tier.min_volume_usd)
+        .copied()
+        .unwrap_or(TIERS[0])
+}
+
+/// A tier by name; the lowest for a name no longer in the schedule
+pub fn tier_named(name: &str) -> FeeTier {
+    TIERS.iter().find(|tier| tier.name == name).copied().unwrap_or(TIERS[0])
+}
+
+/// The tier above, if any
+pub fn next_tier(tier: &FeeTier) -> Option<FeeTier> {
+    TIERS.iter().find(|next| next.min_volume_usd > tier.min_volume_usd).copied()
+}
+
+/// USD traded in the window ending at `now`; deposits, withdrawals and trades without a USD price don't count
+pub fn volume_usd(trades: &[Trade], now: DateTime<Utc>) -> f64 {
+    let since = now - ChronoDuration::days(VOLUME_WINDOW_DAYS);
+    trades
+        .iter()
+        .filter(|t| t.transaction_type == TransactionType::Trade && t.timestamp > since && t.timestamp <= now)
+        .filter_map(|t| {
+            let usd_price = t.base_usd_price.or((t.quote_asset == "USD").then_some(t.price))?;
+            Some(t.quantity * usd_price)
+        })
+        .sum()
+}
+
+/// Work out a user's tier from their trade history at `now`
+pub fn calculate(user: &UserData, now: DateTime<Utc>) -> FeeTierRecord {
+    let volume_30d_usd = volume_usd(&user.trade_history, now);
+    FeeTierRecord {
+        tier: tier_for(volume_30d_usd).name.to_string(),
+        volume_30d_usd,
+        calculated_at: now,
+    }
+}
+
+/// A user's tier as of the last daily recalculation, worked out now if there hasn't been one
+/// Guests are never stored
+pub async fn current(state: &AppState, user_id: &UserId, user: &UserData) -> Result<FeeTierRecord, sqlx::Error> {
+    if state::is_memory_only(user_id) {
+        return Ok(calculate(user, state.clock.now()));
+    }
+    if let Some(record) = queries::get_fee_tier(state.db.pool(), user_id).await? {
+        return Ok(record);
+    }
+    let record = calculate(user, state.clock.now());
+    queries::save_fee_tier(state.db.pool(), user_id, &record).await?;
+    Ok(record)
+}
+
+/// The rate a user's fills pay right now, from their current tier
+/// Falls back to working the tier out from their history if the stored one can't be read
+pub async fn rate_for(state: &AppState, user_id: &UserId, user: &UserData, liquidity: Liquidity) -> f64 {
+    let record = match current(state, user_id, user).await {
+        Ok(record) => record,
+        Err(e) => {
+            tracing::error!("Failed to load the fee tier of {}: {}", user_id, e);
+            calculate(user, state.clock.now())
+        }
+    };
+    tier_named(&record.tier).rate(liquidity)
+}
+
+async fn recalculate(state: &AppState) {
+    let now = state.clock.now();
+    let records: Vec<(UserId, FeeTierRecord)> = state
+        .users
+        .read()
+        .await
+        .iter()
+        .filter(|(user_id, _)| !state::is_memory_only(user_id))
+        .map(|(user_id, user)| (user_id.clone(), calculate(user, now)))
+        .collect();
+
+    for (user_id, record) in &records {
+        if let Err(e) = queries::save_fee_tier(state.db.pool(), user_id, record).await {
+            tracing::error!("Failed to save the fee tier of {}: {}", user_id, e);
+        }
+    }
+    tracing::info!("Recalculated fee tiers for {} users", records.len());
+}
+
+/// Recalculate every user's tier at startup and then once a day
+pub async fn run_recalculation(state: AppState) {
+    let mut interval = clock::interval(&state.clock, RECALCULATION_INTERVAL);
+    loop {
+        interval.tick().await;
+        recalculate(&state).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TradeSide, TradeSource};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_thirty_day_volume_sets_the_tier() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let trade = |days_ago: i64, transaction_type, quote_asset: &str, base_usd_price| Trade {
+            user_id: "u1".to_string(),
+            transaction_type,
+            base_asset: "BTC".to_string(),
+            quote_asset: quote_asset.to_string(),
+            side: TradeSide::Buy,
+            quantity: 0.2,
+            price: 50_000.0,
+            timestamp: now - ChronoDuration::days(days_ago),
+            base_usd_price,
+            quote_usd_price: None,
+            executed_by_bot: None,
+            source: TradeSource::Manual,
+            fee: 0.0,
+        };
+        let trades = [
+            trade(1, TransactionType::Trade, "USD", None),             // 10,000
+            trade(29, TransactionType::Trade, "ETH", Some(60_000.0)), // 12,000
+            trade(31, TransactionType::Trade, "USD", None),            // Outside the window
+            trade(2, TransactionType::Deposit, "USD", None),
+            trade(3, TransactionType::Trade, "ETH", None),             // No USD price
+        ];
+        assert_eq!(volume_usd(&trades, now), 22_000.0);
+
+        assert_eq!(tier_for(0.0).name, "Starter");
+        assert_eq!(tier_for(22_000.0).name, "Active");
+        assert_eq!(tier_for(50_000.0).name, "Trader");
+        assert_eq!(tier_for(1e9).name, "VIP");
+        assert_eq!(next_tier(&tier_for(22_000.0)).map(|t| t.name), Some("Trader"));
+        assert_eq!(next_tier(&tier_for(1e9)), None);
+        assert_eq!(tier_named("Retired"), TIERS[0]);
+        // Higher tiers never cost more
+        assert!(TIERS.windows(2).all(|w| w[1].maker_rate <= w[0].maker_rate && w[1].taker_rate <= w[0].taker_rate));
+    }
+}
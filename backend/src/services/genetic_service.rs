@@ -0,0 +1,339 @@
+use crate::bots;
+use crate::models::PricePoint;
+use crate::services::backtest_service::BacktestParams;
+use crate::services::mock_price_service::Rng;
+use crate::services::optimizer_service::{self, GridResult, RankBy};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// Backtests one run may make (population times generations, at most)
+pub const MAX_GENETIC_BACKTESTS: usize = 500;
+
+const MAX_GENES: usize = 10;
+const MAX_POPULATION: usize = 50;
+const MAX_GENERATIONS: usize = 30;
+const ELITES: usize = 2; // Best individuals carried over unchanged
+const TOURNAMENT_SIZE: usize = 3;
+const RESULTS_KEPT: usize = 10;
+
+/// Range a numeric parameter evolves in
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Gene {
+    pub min: f64,
+    pub max: f64,
+    #[serde(default)]
+    pub integer: bool, // Whole numbers only, e.g. for tick counts
+}
+
+impl Gene {
+    /// `value` clamped to the range, and rounded for an integer gene
+    fn fit(&self, value: f64) -> f64 {
+        let value = value.clamp(self.min, self.max);
+        if self.integer {
+            value.round().clamp(self.min.ceil(), self.max.floor())
+        } else {
+            value
+        }
+    }
+
+    fn to_json(self, value: f64) -> Value {
+        if self.integer {
+            Value::from(value as i64)
+        } else {
+            Value::from(value)
+        }
+    }
+}
+
+/// How the population evolves; fields left out keep these defaults
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Evolution {
+    pub population: usize,    // Parameter sets per generation
+    pub generations: usize,   // Generations bred, the first one random
+    pub mutation_rate: f64,   // Chance each gene of a child is nudged
+    pub seed: Option<u64>,    // Fixes the run, so it can be repeated; random if left out
+}
+
+impl Default for Evolution {
+    fn default() -> Self {
+        Self { population: 20, generations: 10, mutation_rate: 0.2, seed: None }
+    }
+}
+
+/// Check the genes and evolution settings, before anything runs
+pub fn validate(genes: &BTreeMap<String, Gene>, evolution: &Evolution) -> Result<(), String> {
+    if genes.is_empty() || genes.len() > MAX_GENES {
+        return Err(format!("genes must give ranges for 1 to {} parameters", MAX_GENES));
+    }
+    for (name, gene) in genes {
+        if !gene.min.is_finite() || !gene.max.is_finite() || gene.min > gene.max {
+            return Err(format!("genes.{} needs a finite min no greater than its max", name));
+        }
+        if gene.integer && gene.min.ceil() > gene.max.floor() {
+            return Err(format!("genes.{} holds no whole number", name));
+        }
+    }
+    if !(4..=MAX_POPULATION).contains(&evolution.population) {
+        return Err(format!("population must be between 4 and {}", MAX_POPULATION));
+    }
+    if !(1..=MAX_GENERATIONS).contains(&evolution.generations) {
+        return Err(format!("generations must be between 1 and {}", MAX_GENERATIONS));
+    }
+    if evolution.population * evolution.generations > MAX_GENETIC_BACKTESTS {
+        return Err(format!(
+            "{} generations of {} is {} backtests; the limit is {}",
+            evolution.generations,
+            evolution.population,
+            evolution.population * evolution.generations,
+            MAX_GENETIC_BACKTESTS
+        ));
+    }
+    if !(0.0..=1.0).contains(&evolution.mutation_rate) {
+        return Err("mutation_rate must be between 0 and 1".to_string());
+    }
+    Ok(())
+}
+
+/// The parameters each gene's bounds make, merged over `base`, to check before evolving
+pub fn bounds(base: &Value, genes: &BTreeMap<String, Gene>) -> Vec<Value> {
+    genes
+        .iter()
+        .flat_map(|(name, gene)| [gene.min, gene.max].map(|value| merge(base, &[(name, *gene, gene.fit(value))])))
+        .collect()
+}
+
+/// A parameters object: `base` with the genes' values set
+fn merge(base: &Value, values: &[(&String, Gene, f64)]) -> Value {
+    let mut parameters = base.as_object().cloned().unwrap_or_default();
+    for (name, gene, value) in values {
+        parameters.insert(name.to_string(), gene.to_json(*value));
+    }
+    Value::Object(parameters)
+}
+
+/// One parameter set: a value per gene, in the genes' order
+type Genome = Vec<f64>;
+
+fn random_genome(genes: &[Gene], rng: &mut Rng) -> Genome {
+    genes.iter().map(|gene| gene.fit(gene.min + (gene.max - gene.min) * rng.unit())).collect()
+}
+
+/// Whether score `a` beats `b`; a missing score loses to any other
+fn fitter(a: Option<f64>, b: Option<f64>, rank_by: RankBy) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => rank_by.better(a, b),
+        (a, b) => a.is_some() && b.is_none(),
+    }
+}
+
+/// The best of a few individuals picked at random
+fn tournament<'a>(scored: &'a [(Genome, Option<f64>)], rank_by: RankBy, rng: &mut Rng) -> &'a Genome {
+    let mut best = &scored[rng.next_u64() as usize % scored.len()];
+    for _ in 1..TOURNAMENT_SIZE {
+        let other = &scored[rng.next_u64() as usize % scored.len()];
+        if fitter(other.1, best.1, rank_by) {
+            best = other;
+        }
+    }
+    &best.0
+}
+
+/// The next generation: the elites as they are, then children of tournament winners, each gene
+/// from either parent and sometimes nudged by up to about a tenth of its range
+fn breed(
+    mut scored: Vec<(Genome, Option<f64>)>,
+    genes: &[Gene],
+    evolution: &Evolution,
+    rank_by: RankBy,
+    rng: &mut Rng,
+) -> Vec<Genome> {
+    scored.sort_by(|a, b| {
+        if fitter(a.1, b.1, rank_by) {
+            std::cmp::Ordering::Less
+        } else if fitter(b.1, a.1, rank_by) {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    });
+
+    let mut next: Vec<Genome> = scored.iter().take(ELITES).map(|(genome, _)| genome.clone()).collect();
+    while next.len() < evolution.population {
+        let (mother, father) = (tournament(&scored, rank_by, rng), tournament(&scored, rank_by, rng));
+        let child = genes
+            .iter()
+            .enumerate()
+            .map(|(i, gene)| {
+                let value = if rng.unit() < 0.5 { mother[i] } else { father[i] };
+                if rng.unit() < evolution.mutation_rate {
+                    gene.fit(value + rng.normal() * (gene.max - gene.min) / 10.0)
+                } else {
+                    value
+                }
+            })
+            .collect();
+        next.push(child);
+    }
+    next
+}
+
+/// How one generation scored
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct GenerationStats {
+    pub generation: usize, // From 1
+    pub best_score: Option<f64>,
+    pub mean_score: Option<f64>, // Over the members with a score
+    pub backtests: usize,        // New parameter sets run; repeats reuse earlier results
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GeneticResult {
+    pub rank_by: RankBy,
+    pub seed: u64,
+    pub ticks: usize,     // Price points replayed per backtest
+    pub backtests: usize, // Distinct parameter sets run
+    pub invalid: usize,   // Bred parameter sets the strategy rejected, never run
+    pub best: Option<GridResult>, // The fittest parameter set, its score being its fitness; None if none was valid
+    pub results: Vec<GridResult>, // The fittest found, best first
+    pub generations: Vec<GenerationStats>,
+}
+
+/// Evolve the genes' parameters over `series`, each generation backtested in parallel like a grid
+/// Fitness is the `rank_by` metric; the fittest are kept and the rest bred from them
+pub async fn evolve(
+    params: BacktestParams,
+    series: Vec<PricePoint>,
+    genes: &BTreeMap<String, Gene>,
+    evolution: Evolution,
+    rank_by: RankBy,
+) -> Result<GeneticResult, String> {
+    let seed = evolution.seed.unwrap_or_else(|| uuid::Uuid::new_v4().as_u64_pair().0);
+    let mut rng = Rng::new(seed);
+    let names: Vec<&String> = genes.keys().collect();
+    let ranges: Vec<Gene> = genes.values().copied().collect();
+    let parameters_of = |genome: &Genome| -> Value {
+        let values: Vec<(&String, Gene, f64)> =
+            names.iter().zip(&ranges).zip(genome).map(|((name, gene), value)| (*name, *gene, *value)).collect();
+        merge(&params.parameters, &values)
+    };
+
+    // Results by parameters, so a set bred again isn't run again; None for sets the strategy rejects
+    let mut evaluated: HashMap<String, Option<GridResult>> = HashMap::new();
+    let mut population: Vec<Genome> = (0..evolution.population).map(|_| random_genome(&ranges, &mut rng)).collect();
+    let mut generations = Vec::new();
+
+    for generation in 1..=evolution.generations {
+        let mut candidates = Vec::new();
+        for genome in &population {
+            let parameters = parameters_of(genome);
+            let key = parameters.to_string();
+            if evaluated.contains_key(&key) || candidates.iter().any(|(p, _)| *p == parameters) {
+                continue;
+            }
+            match bots::create_bot(&params.strategy, params.stoploss_amount, &parameters) {
+                Ok(bot) => candidates.push((parameters, bot)),
+                Err(_) => {
+                    evaluated.insert(key, None);
+                }
+            }
+        }
+        let backtests = candidates.len();
+        if !candidates.is_empty() {
+            let run = optimizer_service::optimize(params.clone(), series.clone(), &BTreeMap::new(), candidates, rank_by).await?;
+            for result in run.results {
+                evaluated.insert(result.parameters.to_string(), Some(result));
+            }
+        }
+
+        let scored: Vec<(Genome, Option<f64>)> = population
+            .iter()
+            .map(|genome| {
+                let score = evaluated.get(&parameters_of(genome).to_string()).cloned().flatten().and_then(|r| r.score);
+                (genome.clone(), score)
+            })
+            .collect();
+        let scores: Vec<f64> = scored.iter().filter_map(|(_, score)| *score).collect();
+        generations.push(GenerationStats {
+            generation,
+            best_score: scores.iter().copied().reduce(|a, b| if rank_by.better(b, a) { b } else { a }),
+            mean_score: (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64),
+            backtests,
+        });
+
+        if generation < evolution.generations {
+            population = breed(scored, &ranges, &evolution, rank_by, &mut rng);
+        }
+    }
+
+    let invalid = evaluated.values().filter(|result| result.is_none()).count();
+    let mut results: Vec<GridResult> = evaluated.into_values().flatten().collect();
+    let backtests = results.len();
+    optimizer_service::rank(&mut results, rank_by);
+    results.truncate(RESULTS_KEPT);
+
+    Ok(GeneticResult {
+        rank_by,
+        seed,
+        ticks: series.len(),
+        backtests,
+        invalid,
+        best: results.first().cloned(),
+        results,
+        generations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gene(min: f64, max: f64, integer: bool) -> Gene {
+        Gene { min, max, integer }
+    }
+
+    #[test]
+    fn test_population_converges_on_the_fittest_parameters() {
+        // Fitness peaks at x = 7 (a whole number) and y = 0.25
+        let genes = [gene(0.0, 20.0, true), gene(0.0, 1.0, false)];
+        let fitness = |genome: &Genome| Some(-(genome[0] - 7.0).powi(2) - (genome[1] - 0.25).powi(2) * 10.0);
+        let evolution = Evolution { population: 20, generations: 15, mutation_rate: 0.3, seed: Some(42) };
+        let mut rng = Rng::new(42);
+
+        let mut population: Vec<Genome> = (0..evolution.population).map(|_| random_genome(&genes, &mut rng)).collect();
+        for _ in 1..evolution.generations {
+            let scored = population.iter().map(|genome| (genome.clone(), fitness(genome))).collect();
+            population = breed(scored, &genes, &evolution, RankBy::TotalReturn, &mut rng);
+        }
+
+        assert_eq!(population.len(), 20);
+        assert!(population.iter().all(|genome| genome[0].fract() == 0.0 && (0.0..=1.0).contains(&genome[1])));
+        let best = population.iter().max_by(|a, b| fitness(a).partial_cmp(&fitness(b)).unwrap()).unwrap();
+        assert_eq!(best[0], 7.0);
+        assert!((best[1] - 0.25).abs() < 0.1);
+
+        // Lower is fitter for drawdown, and a missing score loses
+        assert!(fitter(Some(1.0), Some(2.0), RankBy::MaxDrawdown));
+        assert!(fitter(Some(-5.0), None, RankBy::TotalReturn));
+    }
+
+    #[test]
+    fn test_genes_and_evolution_validate() {
+        let genes = BTreeMap::from([("trend_ticks".to_string(), gene(2.0, 6.0, true))]);
+        assert!(validate(&genes, &Evolution::default()).is_ok());
+        assert_eq!(
+            bounds(&json!({"cooldown_ticks": 0}), &genes),
+            vec![json!({"cooldown_ticks": 0, "trend_ticks": 2}), json!({"cooldown_ticks": 0, "trend_ticks": 6})]
+        );
+
+        assert!(validate(&BTreeMap::new(), &Evolution::default()).is_err());
+        assert!(validate(&BTreeMap::from([("a".to_string(), gene(3.0, 1.0, false))]), &Evolution::default()).is_err());
+        assert!(validate(&BTreeMap::from([("a".to_string(), gene(1.2, 1.8, true))]), &Evolution::default()).is_err());
+        assert!(validate(&genes, &Evolution { population: 50, generations: 11, ..Default::default() }).is_err());
+        assert!(validate(&genes, &Evolution { mutation_rate: 1.5, ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use crate::api_client::ApiClient;
+use crate::db::queries::{self, PriceCandle};
+use crate::db::DbPool;
+use crate::models::PricePoint;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Candle lengths in seconds, as offered by the exchange candles API
+pub const SUPPORTED_INTERVALS: &[i64] = &[60, 300, 900, 3600, 21600, 86400];
+/// Rows one CSV import may hold
+pub const MAX_IMPORT_ROWS: usize = 500_000;
+/// Size of one CSV upload
+pub const MAX_IMPORT_BYTES: usize = 32 * 1024 * 1024;
+/// Candles one exchange import may request (the API returns 300 per request)
+pub const MAX_FETCH_CANDLES: i64 = 100_000;
+/// Rejected rows described in the summary; the rest are only counted
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Outcome of one import
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportSummary {
+    pub asset: String,
+    pub interval_secs: i64,
+    pub received: usize,     // Rows or candles read from the source
+    pub inserted: u64,       // New candles stored
+    pub duplicates: usize,   // Repeated in the source or already stored
+    pub rejected: usize,     // Failed validation
+    pub errors: Vec<String>, // The first rejections, with their line numbers
+}
+
+/// Candles read from a source, before they are stored
+#[derive(Debug, Default)]
+pub struct CandleBatch {
+    pub candles: Vec<PriceCandle>,
+    pub received: usize,
+    pub rejected: usize,
+    pub errors: Vec<String>,
+}
+
+impl CandleBatch {
+    /// Keep a valid candle, or count the rejection
+    fn push(&mut self, candle: Result<PriceCandle, String>, interval_secs: i64, location: impl FnOnce() -> String) {
+        self.received += 1;
+        match candle.and_then(|c| validate_candle(&c, interval_secs).map(|_| c)) {
+            Ok(candle) => self.candles.push(candle),
+            Err(e) => {
+                self.rejected += 1;
+                if self.errors.len() < MAX_REPORTED_ERRORS {
+                    self.errors.push(format!("{}: {}", location(), e));
+                }
+            }
+        }
+    }
+}
+
+pub fn validate_interval(interval_secs: i64) -> Result<(), String> {
+    if SUPPORTED_INTERVALS.contains(&interval_secs) {
+        Ok(())
+    } else {
+        Err(format!("interval_secs must be one of {:?}", SUPPORTED_INTERVALS))
+    }
+}
+
+/// Prices are positive, high and low bound open and close, and the candle opens on an interval boundary
+fn validate_candle(candle: &PriceCandle, interval_secs: i64) -> Result<(), String> {
+    let prices = [candle.open, candle.high, candle.low, candle.close];
+    if prices.iter().any(|p| !p.is_finite() || *p <= 0.0) {
+        return Err("Prices must be positive numbers".to_string());
+    }
+    if candle.high < candle.open.max(candle.close) || candle.low > candle.open.min(candle.close) {
+        return Err("High and low must bound open and close".to_string());
+    }
+    if candle.volume.is_some_and(|v| !v.is_finite() || v < 0.0) {
+        return Err("Volume must be zero or more".to_string());
+    }
+    if candle.timestamp.timestamp() % interval_secs != 0 {
+        return Err(format!("{} is not on a {}s candle boundary", candle.timestamp, interval_secs));
+    }
+    if candle.timestamp > Utc::now() {
+        return Err(format!("{} is in the future", candle.timestamp));
+    }
+    Ok(())
+}
+
+/// Unix seconds or milliseconds, RFC 3339, "YYYY-MM-DD HH:MM:SS" or "YYYY-MM-DD" (all UTC)
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(unix) = value.parse::<i64>() {
+        let secs = if unix.abs() >= 100_000_000_000 { unix / 1000 } else { unix };
+        return DateTime::from_timestamp(secs, 0);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+/// Where each field sits in a CSV row
+struct Columns {
+    timestamp: usize,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+}
+
+impl Columns {
+    /// Without a header, rows are timestamp, open, high, low, close and an optional volume
+    const DEFAULT: Columns = Columns { timestamp: 0, open: 1, high: 2, low: 3, close: 4, volume: Some(5) };
+
+    fn from_header(header: &[&str]) -> Result<Self, String> {
+        let find = |names: &[&str]| header.iter().position(|h| names.contains(&h.to_lowercase().as_str()));
+        let require = |names: &[&str]| find(names).ok_or_else(|| format!("Missing column: {}", names[0]));
+        Ok(Columns {
+            timestamp: require(&["timestamp", "time", "date", "datetime", "unix"])?,
+            open: require(&["open", "o"])?,
+            high: require(&["high", "h"])?,
+            low: require(&["low", "l"])?,
+            close: require(&["close", "c"])?,
+            volume: find(&["volume", "vol", "v"]),
+        })
+    }
+
+    fn parse(&self, fields: &[&str]) -> Result<PriceCandle, String> {
+        let field = |index: usize| fields.get(index).copied().filter(|f| !f.is_empty());
+        let number = |index: usize, name: &str| {
+            field(index)
+                .and_then(|f| f.parse::<f64>().ok())
+                .ok_or_else(|| format!("Invalid {}", name))
+        };
+        let timestamp = field(self.timestamp)
+            .and_then(parse_timestamp)
+            .ok_or_else(|| "Invalid timestamp".to_string())?;
+        // Volume is optional, even in a file that has the column
+        let volume = match self.volume.filter(|&index| field(index).is_some()) {
+            Some(index) => Some(number(index, "volume")?),
+            None => None,
+        };
+
+        Ok(PriceCandle {
+            timestamp,
+            open: number(self.open, "open")?,
+            high: number(self.high, "high")?,
+            low: number(self.low, "low")?,
+            close: number(self.close, "close")?,
+            volume,
+        })
+    }
+}
+
+fn split_fields(line: &str) -> Vec<&str> {
+    line.split(',').map(|f| f.trim().trim_matches('"')).collect()
+}
+
+/// Read OHLCV rows from CSV text; a first row that isn't data is taken as the header
+/// Invalid rows are rejected one by one rather than failing the whole file
+pub fn parse_csv(text: &str, interval_secs: i64) -> Result<CandleBatch, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .peekable();
+
+    let Some(&(_, first)) = lines.peek() else {
+        return Err("The file is empty".to_string());
+    };
+    let first = split_fields(first);
+    let columns = if parse_timestamp(first[0]).is_some() {
+        Columns::DEFAULT
+    } else {
+        lines.next();
+        Columns::from_header(&first)?
+    };
+
+    let mut batch = CandleBatch::default();
+    for (index, line) in lines {
+        if batch.received == MAX_IMPORT_ROWS {
+            return Err(format!("At most {} rows can be imported at once", MAX_IMPORT_ROWS));
+        }
+        batch.push(columns.parse(&split_fields(line)), interval_secs, || format!("Line {}", index + 1));
+    }
+    Ok(batch)
+}
+
+/// Check a range to fetch from the exchange, returning its end clamped to now
+pub fn validate_fetch_range(
+    interval_secs: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, String> {
+    validate_interval(interval_secs)?;
+    let end = end.min(now);
+    if start >= end {
+        return Err("start must be before end (and in the past)".to_string());
+    }
+    let count = (end - start).num_seconds() / interval_secs;
+    if count > MAX_FETCH_CANDLES {
+        return Err(format!(
+            "That range is {} candles; fetch at most {} at once (use a longer interval or a shorter range)",
+            count, MAX_FETCH_CANDLES
+        ));
+    }
+    Ok(end)
+}
+
+/// Fetch `[start, end]` (checked with `validate_fetch_range`) from the exchange candles API
+pub async fn fetch_exchange(
+    api_client: &ApiClient,
+    asset: &str,
+    interval_secs: i64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<CandleBatch, String> {
+    let candles = api_client
+        .fetch_ohlc_candles_paged(asset, start, end, interval_secs)
+        .await
+        .map_err(|e| format!("Exchange request failed: {}", e))?;
+
+    let mut batch = CandleBatch::default();
+    for candle in candles {
+        let at = candle.timestamp;
+        let candle = PriceCandle {
+            timestamp: candle.timestamp,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+        };
+        batch.push(Ok(candle), interval_secs, || at.to_rfc3339());
+    }
+    Ok(batch)
+}
+
+/// Sort by open time and drop repeats, keeping the first; returns how many were dropped
+fn dedupe(candles: &mut Vec<PriceCandle>) -> usize {
+    let before = candles.len();
+    candles.sort_by_key(|c| c.timestamp);
+    candles.dedup_by_key(|c| c.timestamp);
+    before - candles.len()
+}
+
+/// Store a batch, skipping candles already imported for the same asset, length and open time
+pub async fn store(
+    pool: &DbPool,
+    asset: &str,
+    interval_secs: i64,
+    mut batch: CandleBatch,
+    source: &str,
+) -> Result<ImportSummary, sqlx::Error> {
+    let repeated = dedupe(&mut batch.candles);
+    let inserted = queries::insert_price_candles(pool, asset, interval_secs, &batch.candles, source).await?;
+
+    Ok(ImportSummary {
+        asset: asset.to_string(),
+        interval_secs,
+        received: batch.received,
+        inserted,
+        duplicates: repeated + (batch.candles.len() - inserted as usize),
+        rejected: batch.rejected,
+        errors: batch.errors,
+    })
+}
+
+/// Imported USD close prices of `asset` in `[start, end]`, oldest first
+/// Uses the candle length reaching back furthest, preferring shorter candles on a tie
+pub async fn close_series(
+    pool: &DbPool,
+    asset: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<PricePoint>, sqlx::Error> {
+    let mut best: Vec<PriceCandle> = Vec::new();
+    for &interval_secs in SUPPORTED_INTERVALS {
+        let candles = queries::get_price_candles(pool, asset, interval_secs, start.timestamp(), end.timestamp()).await?;
+        let reaches_further = match (candles.first(), best.first()) {
+            (Some(candidate), Some(current)) => candidate.timestamp < current.timestamp,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if reaches_further {
+            best = candles;
+        }
+    }
+
+    Ok(best
+        .into_iter()
+        .map(|c| PricePoint {
+            timestamp: c.timestamp,
+            asset: asset.to_string(),
+            price: c.close,
+            volume: c.volume,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_maps_header_and_rejects_bad_rows() {
+        let csv = "Date,Close,Open,High,Low,Volume\n\
+                   2024-01-01,105,100,110,95,12.5\n\
+                   1704153600000,100,105,108,99,\n\
+                   2024-01-03,120,100,110,95,1\n\
+                   2024-01-04 12:00:00,100,100,100,100,1\n\
+                   2024-01-05,abc,100,100,100,1\n";
+        let batch = parse_csv(csv, 86400).unwrap();
+
+        assert_eq!(batch.received, 5);
+        assert_eq!(batch.candles.len(), 2);
+        assert_eq!(batch.candles[0].timestamp.to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!((batch.candles[0].open, batch.candles[0].close), (100.0, 105.0));
+        assert_eq!(batch.candles[0].volume, Some(12.5));
+        // Millisecond timestamps, and an empty volume
+        assert_eq!(batch.candles[1].timestamp.to_rfc3339(), "2024-01-02T00:00:00+00:00");
+        assert_eq!(batch.candles[1].volume, None);
+
+        assert_eq!(batch.rejected, 3);
+        assert!(batch.errors[0].starts_with("Line 4: High and low"));
+        assert!(batch.errors[1].starts_with("Line 5:") && batch.errors[1].contains("boundary"));
+        assert_eq!(batch.errors[2], "Line 6: Invalid close");
+
+        assert!(parse_csv("time,open,high,low\n", 60).is_err());
+        assert!(parse_csv("\n\n", 60).is_err());
+    }
+
+    #[test]
+    fn test_headerless_rows_are_deduplicated_in_order() {
+        let csv = "1704067260,2,3,1,2\n1704067200,1,1,1,1\n1704067260,9,9,9,9\n";
+        let mut batch = parse_csv(csv, 60).unwrap();
+        assert_eq!(batch.rejected, 0);
+
+        assert_eq!(dedupe(&mut batch.candles), 1);
+        let closes: Vec<f64> = batch.candles.iter().map(|c| c.close).collect();
+        assert_eq!(closes, vec![1.0, 2.0]);
+
+        assert!(validate_interval(3600).is_ok());
+        assert!(validate_interval(120).is_err());
+
+        let now = batch.candles[1].timestamp;
+        let day_ago = now - chrono::Duration::days(1);
+        assert_eq!(validate_fetch_range(60, day_ago, now + chrono::Duration::days(1), now), Ok(now));
+        assert!(validate_fetch_range(60, now, day_ago, now).is_err());
+        assert!(validate_fetch_range(60, now - chrono::Duration::days(365), now, now).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::db::queries::{self, InvariantViolation};
+use crate::models::{Asset, Trade, TradeSide, TransactionType, UserId};
+use crate::state::AppState;
+use std::collections::{BTreeSet, HashMap};
+use tracing::Instrument;
+
+/// Balances are f64, so amounts agree when within this share of the larger one (or of 1.0)
+const TOLERANCE: f64 = 1e-9;
+
+/// Accounting rules every balance change must keep
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    /// Quantity and price are finite and positive
+    ValidAmounts,
+    /// No balance ends up below zero; there is no margin
+    NoNegativeBalance,
+    /// Each asset moved by exactly its leg of the transaction, and nothing else moved
+    TradeLegs,
+    /// Valued at the fill price, a trade gives and receives the same, less its fee
+    ValueConserved,
+}
+
+impl Rule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rule::ValidAmounts => "valid_amounts",
+            Rule::NoNegativeBalance => "negative_balance",
+            Rule::TradeLegs => "trade_legs",
+            Rule::ValueConserved => "value_conserved",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub rule: Rule,
+    pub details: String,
+}
+
+/// What a transaction should do to the balances
+#[derive(Debug, Clone)]
+pub struct Legs {
+    pub base_asset: Asset,
+    pub quote_asset: Asset,
+    pub base: f64,  // Signed change of the base asset
+    pub quote: f64, // Signed change of the quote asset, fee included
+    pub quantity: f64,
+    pub price: f64,
+    pub fee: f64,   // In the quote asset
+}
+
+impl Legs {
+    /// Legs of a trade of `quantity` base at `price` (quote per base)
+    pub fn trade(base_asset: &str, quote_asset: &str, side: &TradeSide, quantity: f64, price: f64) -> Self {
+        let (base, quote) = match side {
+            TradeSide::Buy => (quantity, -quantity * price),
+            TradeSide::Sell => (-quantity, quantity * price),
+        };
+        Self {
+            base_asset: base_asset.to_string(),
+            quote_asset: quote_asset.to_string(),
+            base,
+            quote,
+            quantity,
+            price,
+            fee: 0.0,
+        }
+    }
+
+    /// Legs of a recorded transaction; deposits and withdrawals move only their own asset
+    pub fn of(trade: &Trade) -> Self {
+        match trade.transaction_type {
+            TransactionType::Trade => {
+                let legs = Self::trade(&trade.base_asset, &trade.quote_asset, &trade.side, trade.quantity, trade.price);
+                Self { quote: legs.quote - trade.fee, fee: trade.fee, ..legs }
+            }
+            TransactionType::Deposit | TransactionType::Withdrawal => {
+                let sign = if trade.transaction_type == TransactionType::Deposit { 1.0 } else { -1.0 };
+                Self {
+                    base_asset: trade.base_asset.clone(),
+                    quote_asset: trade.base_asset.clone(),
+                    base: sign * trade.quantity,
+                    quote: 0.0,
+                    quantity: trade.quantity,
+                    price: trade.price,
+                    fee: 0.0,
+                }
+            }
+        }
+    }
+
+    fn is_trade(&self) -> bool {
+        self.base_asset != self.quote_asset
+    }
+
+    fn expected_change(&self, asset: &str) -> f64 {
+        let mut change = 0.0;
+        if asset == self.base_asset {
+            change += self.base;
+        }
+        if asset == self.quote_asset {
+            change += self.quote;
+        }
+        change
+    }
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= TOLERANCE * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Check a balance change against the transaction that made it
+pub fn check(before: &HashMap<Asset, f64>, after: &HashMap<Asset, f64>, legs: &Legs) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let balance = |balances: &HashMap<Asset, f64>, asset: &str| balances.get(asset).copied().unwrap_or(0.0);
+
+    if !(legs.quantity.is_finite() && legs.quantity > 0.0 && legs.price.is_finite() && legs.price > 0.0) {
+        violations.push(Violation {
+            rule: Rule::ValidAmounts,
+            details: format!("quantity {} at price {}", legs.quantity, legs.price),
+        });
+    }
+
+    // Sorted, so the same change always reports the same way
+    let assets: BTreeSet<&str> = before
+        .keys()
+        .chain(after.keys())
+        .map(String::as_str)
+        .chain([legs.base_asset.as_str(), legs.quote_asset.as_str()])
+        .collect();
+    for asset in assets {
+        let (was, now) = (balance(before, asset), balance(after, asset));
+        // Only a balance this change drove below zero is its fault
+        if now < -TOLERANCE && now < was {
+            violations.push(Violation {
+                rule: Rule::NoNegativeBalance,
+                details: format!("{} went from {} to {}", asset, was, now),
+            });
+        }
+        let expected = legs.expected_change(asset);
+        if !close(now - was, expected) {
+            violations.push(Violation {
+                rule: Rule::TradeLegs,
+                details: format!("{} changed by {}, expected {}", asset, now - was, expected),
+            });
+        }
+    }
+
+    if legs.is_trade() {
+        let base_change = balance(after, &legs.base_asset) - balance(before, &legs.base_asset);
+        let quote_change = balance(after, &legs.quote_asset) - balance(before, &legs.quote_asset);
+        let net = base_change * legs.price + quote_change;
+        if !close(net, -legs.fee) {
+            violations.push(Violation {
+                rule: Rule::ValueConserved,
+                details: format!(
+                    "net {} {} at {} against a fee of {}",
+                    net, legs.quote_asset, legs.price, legs.fee
+                ),
+            });
+        }
+    }
+    violations
+}
+
+/// Log violations and keep them for the admin endpoint, without blocking the caller
+/// Memory-only users (the demo user, bot replays) are logged but not recorded
+pub fn report(state: &AppState, user_id: &UserId, change: String, violations: Vec<Violation>) {
+    if violations.is_empty() {
+        return;
+    }
+    for violation in &violations {
+        tracing::error!(
+            "Invariant {} broken for user {} by {}: {}",
+            violation.rule.as_str(),
+            user_id,
+            change,
+            violation.details
+        );
+    }
+    if crate::state::is_memory_only(user_id) {
+        return;
+    }
+
+    let pool = state.db.pool().clone();
+    let detected_at = state.clock.now();
+    let user_id = user_id.clone();
+    tokio::spawn(
+        async move {
+            for violation in violations {
+                let row = InvariantViolation {
+                    id: 0, // Assigned by the database
+                    user_id: user_id.clone(),
+                    rule: violation.rule.as_str().to_string(),
+                    details: violation.details,
+                    change: change.clone(),
+                    detected_at,
+                };
+                if let Err(e) = queries::insert_invariant_violation(&pool, &row).await {
+                    tracing::error!("Failed to record invariant violation for {}: {}", user_id, e);
+                }
+            }
+        }
+        .in_current_span(),
+    );
+}
+
+/// How a transaction reads in a violation, e.g. "Buy 0.1 BTC/USD @ 50000"
+pub fn describe(trade: &Trade) -> String {
+    match trade.transaction_type {
+        TransactionType::Trade => {
+            format!("{:?} {} {}/{} @ {}", trade.side, trade.quantity, trade.base_asset, trade.quote_asset, trade.price)
+        }
+        _ => format!("{:?} {} {}", trade.transaction_type, trade.quantity, trade.base_asset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balances(entries: &[(&str, f64)]) -> HashMap<Asset, f64> {
+        entries.iter().map(|(asset, amount)| (asset.to_string(), *amount)).collect()
+    }
+
+    fn rules(violations: &[Violation]) -> Vec<Rule> {
+        violations.iter().map(|v| v.rule).collect()
+    }
+
+    #[test]
+    fn test_correct_changes_pass() {
+        let buy = Legs::trade("BTC", "USD", &TradeSide::Buy, 0.1, 50_000.0);
+        let before = balances(&[("USD", 10_000.0)]);
+        assert!(check(&before, &balances(&[("USD", 5_000.0), ("BTC", 0.1)]), &buy).is_empty());
+
+        let sell = Legs::trade("ETH", "BTC", &TradeSide::Sell, 2.0, 0.05);
+        let before = balances(&[("ETH", 2.0), ("BTC", 0.0), ("USD", 10.0)]);
+        assert!(check(&before, &balances(&[("ETH", 0.0), ("BTC", 0.1), ("USD", 10.0)]), &sell).is_empty());
+
+        let deposit = Legs { base: 100.0, quote: 0.0, ..Legs::trade("USD", "USD", &TradeSide::Buy, 100.0, 1.0) };
+        assert!(check(&balances(&[]), &balances(&[("USD", 100.0)]), &deposit).is_empty());
+    }
+
+    #[test]
+    fn test_broken_changes_are_flagged() {
+        let buy = Legs::trade("BTC", "USD", &TradeSide::Buy, 0.1, 50_000.0);
+
+        // Spent money it didn't have
+        let overdrawn = check(&balances(&[("USD", 1_000.0)]), &balances(&[("USD", -4_000.0), ("BTC", 0.1)]), &buy);
+        assert_eq!(rules(&overdrawn), vec![Rule::NoNegativeBalance]);
+
+        // Got the coin without paying for it
+        let free = check(&balances(&[("USD", 10_000.0)]), &balances(&[("USD", 10_000.0), ("BTC", 0.1)]), &buy);
+        assert_eq!(rules(&free), vec![Rule::TradeLegs, Rule::ValueConserved]);
+
+        // Something else moved too
+        let stray = check(
+            &balances(&[("USD", 10_000.0), ("ETH", 1.0)]),
+            &balances(&[("USD", 5_000.0), ("BTC", 0.1), ("ETH", 0.0)]),
+            &buy,
+        );
+        assert_eq!(rules(&stray), vec![Rule::TradeLegs]);
+
+        let zero = Legs::trade("BTC", "USD", &TradeSide::Buy, 0.0, 50_000.0);
+        let before = balances(&[("USD", 10_000.0)]);
+        assert_eq!(rules(&check(&before, &before, &zero)), vec![Rule::ValidAmounts]);
+    }
+}
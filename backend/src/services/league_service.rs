@@ -0,0 +1,132 @@
+use crate::clock;
+use crate::db::queries::{self, Competition, League};
+use crate::services::competition_service::{MAX_DESCRIPTION_LENGTH, MAX_NAME_LENGTH, MAX_STARTING_BALANCE, MIN_STARTING_BALANCE};
+use crate::services::timezone_service::Period;
+use crate::state::AppState;
+use chrono::{DateTime, Offset, Utc};
+use std::collections::HashMap;
+
+/// How often leagues are checked for a season to start and members to enter
+const SEASON_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Why a league definition was rejected; otherwise how long its seasons last
+pub fn validate(name: &str, description: &str, season_length: &str, starting_balance: f64) -> Result<Period, String> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Name must be 1 to {} characters", MAX_NAME_LENGTH));
+    }
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(format!("Description must be at most {} characters", MAX_DESCRIPTION_LENGTH));
+    }
+    if !(MIN_STARTING_BALANCE..=MAX_STARTING_BALANCE).contains(&starting_balance) {
+        return Err(format!(
+            "Starting balance must be between {} and {} USD",
+            MIN_STARTING_BALANCE, MAX_STARTING_BALANCE
+        ));
+    }
+    match Period::parse(season_length) {
+        Some(Period::Day) | None => Err("Seasons last a week or a month".to_string()),
+        Some(period) => Ok(period),
+    }
+}
+
+/// The league's season running at `now`: a competition over the UTC week or month holding it
+/// Its id comes from the league and the period, so every instance builds the same season
+pub fn season_at(league: &League, now: DateTime<Utc>) -> Competition {
+    let period = Period::parse(&league.season_length).unwrap_or(Period::Month);
+    let (label, starts_at, ends_at) = period.bounds(now, Utc.fix());
+    Competition {
+        id: format!("{}-{}", league.id, label),
+        name: format!("{} {}", league.name, label),
+        description: league.description.clone(),
+        starting_balance: league.starting_balance,
+        starts_at,
+        ends_at,
+        created_by: league.created_by.clone(),
+        created_at: now,
+        finalized_at: None,
+        league_id: Some(league.id.clone()),
+    }
+}
+
+/// The league's current season, started if no instance has yet, with every member entered
+/// Members who opted in since get a fresh portfolio of the starting balance
+pub async fn current_season(state: &AppState, league: &League) -> Result<Competition, sqlx::Error> {
+    let pool = state.db.pool();
+    let now = state.clock.now();
+    let season = season_at(league, now);
+    if queries::create_competition(pool, &season).await? {
+        tracing::info!("League {} ({}) started season {}", league.name, league.id, season.id);
+    }
+
+    let balances = HashMap::from([("USD".to_string(), league.starting_balance)]);
+    let entered = queries::enter_league_members(pool, &season, &balances, now).await?;
+    if entered > 0 {
+        tracing::info!("Entered {} members in season {}", entered, season.id);
+    }
+    Ok(queries::get_competition(pool, &season.id).await?.unwrap_or(season))
+}
+
+async fn start_seasons(state: &AppState) {
+    let leagues = match queries::list_leagues(state.db.pool()).await {
+        Ok(leagues) => leagues,
+        Err(e) => {
+            tracing::error!("Failed to load leagues: {}", e);
+            return;
+        }
+    };
+
+    for (league, _) in leagues {
+        if let Err(e) = current_season(state, &league).await {
+            tracing::error!("Failed to start the season of league {}: {}", league.id, e);
+        }
+    }
+}
+
+/// Roll leagues over to their next season as the last one ends, within a minute of it
+/// The ended season is a competition, so the competition finalizer archives its final standings
+pub async fn run_scheduler(state: AppState) {
+    let mut interval = clock::interval(&state.clock, SEASON_INTERVAL);
+    loop {
+        interval.tick().await;
+        start_seasons(&state).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_seasons_follow_the_utc_calendar() {
+        let league = |season_length: &str| League {
+            id: "weekly".to_string(),
+            name: "Weekly Cup".to_string(),
+            description: String::new(),
+            season_length: season_length.to_string(),
+            starting_balance: 10_000.0,
+            created_by: "admin".to_string(),
+            created_at: Utc::now(),
+        };
+        let now = Utc.with_ymd_and_hms(2025, 2, 5, 13, 0, 0).unwrap();
+
+        let season = season_at(&league("week"), now);
+        assert_eq!(season.id, "weekly-2025-W06");
+        assert_eq!(season.name, "Weekly Cup 2025-W06");
+        assert_eq!(season.starts_at, Utc.with_ymd_and_hms(2025, 2, 3, 0, 0, 0).unwrap());
+        assert_eq!(season.ends_at, Utc.with_ymd_and_hms(2025, 2, 10, 0, 0, 0).unwrap());
+        assert_eq!(season.league_id.as_deref(), Some("weekly"));
+
+        // The next season starts as this one ends
+        let next = season_at(&league("week"), season.ends_at);
+        assert_eq!((next.id.as_str(), next.starts_at), ("weekly-2025-W07", season.ends_at));
+
+        let monthly = season_at(&league("month"), now);
+        assert_eq!(monthly.id, "weekly-2025-02");
+        assert_eq!(monthly.ends_at, Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap());
+
+        assert_eq!(validate("Cup", "", "month", 10_000.0), Ok(Period::Month));
+        assert!(validate("Cup", "", "day", 10_000.0).is_err());
+        assert!(validate("Cup", "", "week", 1.0).is_err());
+    }
+}
@@ -0,0 +1,405 @@
+use crate::db::queries::LedgerEntry;
+use crate::models::{Asset, Trade, TradeSide, TransactionType};
+use crate::services::timezone_service::Period;
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// Rebuilt and stored balances agree when this close
+const TOLERANCE: f64 = 1e-9;
+
+/// Why a ledger entry was posted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Opening, // Balances held before a user's first entry
+    Trade,
+    Fee, // A trade's fee, posted apart from its quote leg
+    Deposit,
+    Withdrawal,
+    Reset, // Portfolio reset by an admin
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryKind::Opening => "opening",
+            EntryKind::Trade => "trade",
+            EntryKind::Fee => "fee",
+            EntryKind::Deposit => "deposit",
+            EntryKind::Withdrawal => "withdrawal",
+            EntryKind::Reset => "reset",
+        }
+    }
+}
+
+/// A credit (positive) or debit (negative) of one asset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posting {
+    pub asset: Asset,
+    pub amount: f64,
+    pub kind: EntryKind,
+}
+
+impl Posting {
+    fn new(asset: &str, amount: f64, kind: EntryKind) -> Self {
+        Self { asset: asset.to_string(), amount, kind }
+    }
+}
+
+/// The postings a transaction makes: both legs of a trade and its fee, or the one asset a deposit or withdrawal moves
+pub fn postings(trade: &Trade) -> Vec<Posting> {
+    match trade.transaction_type {
+        TransactionType::Trade => {
+            let cost = trade.quantity * trade.price;
+            let (base, quote) = match trade.side {
+                TradeSide::Buy => (trade.quantity, -cost),
+                TradeSide::Sell => (-trade.quantity, cost),
+            };
+            let mut postings = vec![
+                Posting::new(&trade.base_asset, base, EntryKind::Trade),
+                Posting::new(&trade.quote_asset, quote, EntryKind::Trade),
+            ];
+            if trade.fee > 0.0 {
+                postings.push(Posting::new(&trade.quote_asset, -trade.fee, EntryKind::Fee));
+            }
+            postings
+        }
+        TransactionType::Deposit => vec![Posting::new(&trade.base_asset, trade.quantity, EntryKind::Deposit)],
+        TransactionType::Withdrawal => vec![Posting::new(&trade.base_asset, -trade.quantity, EntryKind::Withdrawal)],
+    }
+}
+
+/// Post to balances
+pub fn apply(balances: &mut HashMap<Asset, f64>, postings: &[Posting]) {
+    for posting in postings {
+        *balances.entry(posting.asset.clone()).or_insert(0.0) += posting.amount;
+    }
+}
+
+/// The first asset `postings` would take below zero, counting each asset's net change
+/// A sell's fee comes out of its proceeds, so it needs no quote balance of its own
+pub fn overdrawn(balances: &HashMap<Asset, f64>, postings: &[Posting]) -> Option<Asset> {
+    let mut changes: BTreeMap<&str, f64> = BTreeMap::new();
+    for posting in postings {
+        *changes.entry(&posting.asset).or_insert(0.0) += posting.amount;
+    }
+    changes
+        .into_iter()
+        .find(|(asset, change)| *change < 0.0 && balances.get(*asset).copied().unwrap_or(0.0) < -change)
+        .map(|(asset, _)| asset.to_string())
+}
+
+/// The balances `postings` were made from, given the balances they led to
+pub fn unapply(balances: &HashMap<Asset, f64>, postings: &[Posting]) -> HashMap<Asset, f64> {
+    let mut before = balances.clone();
+    for posting in postings {
+        *before.entry(posting.asset.clone()).or_insert(0.0) -= posting.amount;
+    }
+    before
+}
+
+/// Postings of `kind` taking `before` to `after`, by asset name
+pub fn difference(before: &HashMap<Asset, f64>, after: &HashMap<Asset, f64>, kind: EntryKind) -> Vec<Posting> {
+    let mut changes: BTreeMap<&str, f64> = BTreeMap::new();
+    for (asset, balance) in after {
+        *changes.entry(asset.as_str()).or_insert(0.0) += balance;
+    }
+    for (asset, balance) in before {
+        *changes.entry(asset.as_str()).or_insert(0.0) -= balance;
+    }
+    changes
+        .into_iter()
+        .filter(|(_, amount)| *amount != 0.0)
+        .map(|(asset, amount)| Posting::new(asset, amount, kind))
+        .collect()
+}
+
+/// Hex SHA-256 chaining `entry` to the hash of the user's previous entry ("" for the first)
+pub fn entry_hash(previous: &str, entry: &LedgerEntry) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    // Fields are separated so that moving text from one to the next changes the hash
+    for field in [
+        entry.user_id.as_str(),
+        entry.asset.as_str(),
+        &entry.amount.to_bits().to_string(),
+        &entry.balance.to_bits().to_string(),
+        entry.kind.as_str(),
+        &entry.trade_id.map(|id| id.to_string()).unwrap_or_default(),
+        &entry.created_at.timestamp().to_string(),
+    ] {
+        hasher.update([0x1f]);
+        hasher.update(field.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BalanceMismatch {
+    pub asset: Asset,
+    pub stored: f64,
+    pub rebuilt: f64,
+}
+
+/// A user's stored balances checked against their ledger
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Reconciliation {
+    pub entries: usize,
+    pub chain_intact: bool,
+    pub first_broken_entry: Option<i64>, // Id of the first entry whose hash doesn't follow from the ones before
+    pub rebuilt: HashMap<Asset, f64>,    // Sum of each asset's entries
+    pub mismatches: Vec<BalanceMismatch>,
+}
+
+/// Rebuild balances from `entries` (a user's, oldest first), verify their hash chain, and compare with `stored`
+/// Users with no entries yet have nothing to disagree with
+pub fn reconcile(entries: &[LedgerEntry], stored: &HashMap<Asset, f64>) -> Reconciliation {
+    let mut rebuilt: HashMap<Asset, f64> = HashMap::new();
+    let mut previous = String::new();
+    let mut first_broken_entry = None;
+    for entry in entries {
+        *rebuilt.entry(entry.asset.clone()).or_insert(0.0) += entry.amount;
+        if first_broken_entry.is_none() && entry_hash(&previous, entry) != entry.hash {
+            first_broken_entry = Some(entry.id);
+        }
+        previous = entry.hash.clone();
+    }
+
+    let mismatches = if entries.is_empty() {
+        Vec::new()
+    } else {
+        let mut assets: Vec<&Asset> = rebuilt.keys().chain(stored.keys()).collect();
+        assets.sort();
+        assets.dedup();
+        assets
+            .into_iter()
+            .map(|asset| BalanceMismatch {
+                asset: asset.clone(),
+                stored: stored.get(asset).copied().unwrap_or(0.0),
+                rebuilt: rebuilt.get(asset).copied().unwrap_or(0.0),
+            })
+            .filter(|m| (m.stored - m.rebuilt).abs() > TOLERANCE * m.stored.abs().max(m.rebuilt.abs()).max(1.0))
+            .collect()
+    };
+
+    Reconciliation {
+        entries: entries.len(),
+        chain_intact: first_broken_entry.is_none(),
+        first_broken_entry,
+        rebuilt,
+        mismatches,
+    }
+}
+
+/// One asset's movements over a statement period
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct StatementLine {
+    pub asset: Asset,
+    pub opening: f64, // Balance before the period's first entry
+    pub credits: f64,
+    pub debits: f64, // As a positive amount
+    pub closing: f64,
+    pub entries: usize,
+}
+
+/// A user's ledger over one day or month of their local calendar
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PeriodStatement {
+    pub period: String, // Local "YYYY-MM-DD" or "YYYY-MM"
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub lines: Vec<StatementLine>, // Assets with entries in the period, by name
+}
+
+/// Group `entries` (a user's, oldest first) into periods of the calendar at `offset`, newest first
+/// Periods without entries are left out
+pub fn statements(entries: &[LedgerEntry], period: Period, offset: FixedOffset) -> Vec<PeriodStatement> {
+    let mut statements: Vec<PeriodStatement> = Vec::new();
+    for entry in entries {
+        let (label, start, end) = period.bounds(entry.created_at, offset);
+        if statements.last().is_none_or(|statement| statement.period != label) {
+            statements.push(PeriodStatement { period: label, start, end, lines: Vec::new() });
+        }
+        let Some(statement) = statements.last_mut() else { continue };
+        let index = match statement.lines.iter().position(|line| line.asset == entry.asset) {
+            Some(index) => index,
+            None => {
+                statement.lines.push(StatementLine {
+                    asset: entry.asset.clone(),
+                    opening: entry.balance - entry.amount,
+                    credits: 0.0,
+                    debits: 0.0,
+                    closing: 0.0,
+                    entries: 0,
+                });
+                statement.lines.len() - 1
+            }
+        };
+        let line = &mut statement.lines[index];
+        if entry.amount >= 0.0 {
+            line.credits += entry.amount;
+        } else {
+            line.debits -= entry.amount;
+        }
+        line.closing = entry.balance;
+        line.entries += 1;
+    }
+    for statement in &mut statements {
+        statement.lines.sort_by(|a, b| a.asset.cmp(&b.asset));
+    }
+    statements.reverse();
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TradeSource;
+    use chrono::{TimeZone, Utc};
+
+    fn trade(transaction_type: TransactionType, side: TradeSide, base: &str, quantity: f64, price: f64) -> Trade {
+        Trade {
+            user_id: "u1".to_string(),
+            transaction_type,
+            base_asset: base.to_string(),
+            quote_asset: "USD".to_string(),
+            side,
+            quantity,
+            price,
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            base_usd_price: None,
+            quote_usd_price: None,
+            executed_by_bot: None,
+            source: TradeSource::Manual,
+            fee: 0.0,
+        }
+    }
+
+    /// Entries as queries::append_ledger writes them: running balances, chained hashes
+    fn chain(postings: &[Posting]) -> Vec<LedgerEntry> {
+        let mut balances: HashMap<Asset, f64> = HashMap::new();
+        let mut previous = String::new();
+        postings
+            .iter()
+            .enumerate()
+            .map(|(i, posting)| {
+                let balance = balances.entry(posting.asset.clone()).or_insert(0.0);
+                *balance += posting.amount;
+                let mut entry = LedgerEntry {
+                    id: i as i64 + 1,
+                    user_id: "u1".to_string(),
+                    asset: posting.asset.clone(),
+                    amount: posting.amount,
+                    balance: *balance,
+                    kind: posting.kind.as_str().to_string(),
+                    trade_id: None,
+                    created_at: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+                    hash: String::new(),
+                };
+                entry.hash = entry_hash(&previous, &entry);
+                previous = entry.hash.clone();
+                entry
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_postings_rebuild_the_balances() {
+        let mut balances = HashMap::from([("USD".to_string(), 10_000.0)]);
+        let mut posted = difference(&HashMap::new(), &balances, EntryKind::Opening);
+        for t in [
+            trade(TransactionType::Trade, TradeSide::Buy, "BTC", 0.1, 50_000.0),
+            trade(TransactionType::Deposit, TradeSide::Buy, "USD", 500.0, 1.0),
+            trade(TransactionType::Trade, TradeSide::Sell, "BTC", 0.05, 60_000.0),
+            trade(TransactionType::Withdrawal, TradeSide::Sell, "USD", 1_000.0, 1.0),
+        ] {
+            let before = balances.clone();
+            let p = postings(&t);
+            apply(&mut balances, &p);
+            assert_eq!(unapply(&balances, &p).get("USD"), before.get("USD"));
+            posted.extend(p);
+        }
+        assert_eq!(balances["USD"], 7_500.0);
+        assert_eq!(balances["BTC"], 0.05);
+
+        let report = reconcile(&chain(&posted), &balances);
+        assert!(report.chain_intact && report.mismatches.is_empty());
+        assert_eq!(report.entries, 7);
+
+        // Reset to a fresh portfolio
+        let fresh = HashMap::from([("USD".to_string(), 10_000.0)]);
+        let reset = difference(&balances, &fresh, EntryKind::Reset);
+        assert_eq!(reset, vec![Posting::new("BTC", -0.05, EntryKind::Reset), Posting::new("USD", 2_500.0, EntryKind::Reset)]);
+    }
+
+    #[test]
+    fn test_overdrawn_counts_the_fee_with_the_cost() {
+        let balances = HashMap::from([("USD".to_string(), 5_000.0), ("BTC".to_string(), 0.1)]);
+        let mut buy = trade(TransactionType::Trade, TradeSide::Buy, "BTC", 0.1, 50_000.0);
+        assert_eq!(overdrawn(&balances, &postings(&buy)), None);
+        buy.fee = 30.0;
+        assert_eq!(overdrawn(&balances, &postings(&buy)), Some("USD".to_string()));
+
+        let mut sell = trade(TransactionType::Trade, TradeSide::Sell, "BTC", 0.1, 50_000.0);
+        sell.fee = 30.0;
+        assert_eq!(overdrawn(&HashMap::from([("BTC".to_string(), 0.1)]), &postings(&sell)), None);
+        sell.quantity = 0.2;
+        assert_eq!(overdrawn(&balances, &postings(&sell)), Some("BTC".to_string()));
+
+        let withdrawal = trade(TransactionType::Withdrawal, TradeSide::Sell, "USD", 5_000.01, 1.0);
+        assert_eq!(overdrawn(&balances, &postings(&withdrawal)), Some("USD".to_string()));
+    }
+
+    #[test]
+    fn test_tampering_and_drift_are_found() {
+        let deposit = postings(&trade(TransactionType::Deposit, TradeSide::Buy, "USD", 100.0, 1.0));
+        let posted: Vec<Posting> = (0..3).flat_map(|_| deposit.clone()).collect();
+        let mut entries = chain(&posted);
+        let stored = HashMap::from([("USD".to_string(), 300.0)]);
+        assert!(reconcile(&entries, &stored).chain_intact);
+
+        let drifted = reconcile(&entries, &HashMap::from([("USD".to_string(), 250.0)]));
+        assert_eq!(drifted.mismatches.len(), 1);
+        assert_eq!(drifted.mismatches[0].rebuilt, 300.0);
+
+        entries[1].amount = 50.0;
+        let report = reconcile(&entries, &stored);
+        assert_eq!(report.first_broken_entry, Some(2));
+        assert_eq!(report.mismatches.len(), 1);
+
+        // Removing an entry breaks the next one's link
+        let mut entries = chain(&posted);
+        entries.remove(1);
+        assert_eq!(reconcile(&entries, &HashMap::new()).first_broken_entry, Some(3));
+    }
+
+    #[test]
+    fn test_statements_split_at_local_midnight() {
+        let mut posted = difference(&HashMap::new(), &HashMap::from([("USD".to_string(), 1_000.0)]), EntryKind::Opening);
+        posted.extend(postings(&trade(TransactionType::Trade, TradeSide::Buy, "BTC", 0.01, 50_000.0)));
+        posted.extend(postings(&trade(TransactionType::Withdrawal, TradeSide::Sell, "USD", 100.0, 1.0)));
+        let mut entries = chain(&posted);
+        // Opening at 20:00 UTC on Jan 1st, the trade at 23:30 and the withdrawal at 01:00 on Jan 2nd
+        for (entry, minutes) in entries.iter_mut().zip([0, 210, 210, 300]) {
+            entry.created_at = Utc.with_ymd_and_hms(2025, 1, 1, 20, 0, 0).unwrap() + chrono::Duration::minutes(minutes);
+        }
+
+        let utc = statements(&entries, Period::Day, FixedOffset::east_opt(0).unwrap());
+        let days: Vec<&str> = utc.iter().map(|s| s.period.as_str()).collect();
+        assert_eq!(days, vec!["2025-01-02", "2025-01-01"]);
+        let usd = &utc[1].lines[1];
+        assert_eq!((usd.opening, usd.credits, usd.debits, usd.closing, usd.entries), (0.0, 1_000.0, 500.0, 500.0, 2));
+        assert_eq!(utc[0].lines.len(), 1);
+
+        // At UTC-5 all three fall on Jan 1st
+        let new_york = statements(&entries, Period::Day, FixedOffset::west_opt(5 * 3600).unwrap());
+        assert_eq!(new_york.len(), 1);
+        assert_eq!(new_york[0].period, "2025-01-01");
+        assert_eq!(new_york[0].start, Utc.with_ymd_and_hms(2025, 1, 1, 5, 0, 0).unwrap());
+        let assets: Vec<&str> = new_york[0].lines.iter().map(|l| l.asset.as_str()).collect();
+        assert_eq!(assets, vec!["BTC", "USD"]);
+        assert_eq!(new_york[0].lines[1].closing, 400.0);
+    }
+}
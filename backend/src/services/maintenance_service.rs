@@ -0,0 +1,307 @@
+use crate::clock;
+use crate::db::{queries, DbBackend};
+use crate::models::{add_volume, Candle, PricePoint, TransactionType};
+use crate::state::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, DurationRound, Months, Utc};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+/// Retention thresholds, read from the environment
+/// Archiving trades and pruning the audit log are opt-in (unset or 0 = keep forever); bot decisions,
+/// one row per bot per minute, are pruned after 30 days unless set to 0
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    pub interval: Duration,                // How often in-memory price data is compacted
+    pub db_interval: Duration,             // How often archiving/pruning/vacuum runs
+    pub raw_price_hours: i64,              // 5s points older than this are folded into 5-minute candles
+    pub candle_hours: i64,                 // Candles older than this are dropped
+    pub trade_archive_months: Option<u32>, // Trades older than this move to trades_archive
+    pub audit_log_days: Option<i64>,       // Audit entries older than this are deleted
+    pub bot_decision_days: Option<i64>,    // Bot decisions older than this are deleted
+    pub vacuum: bool,                      // VACUUM the SQLite file after DB maintenance
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        let trade_archive_months: u32 = env_or("RETENTION_TRADE_ARCHIVE_MONTHS", 0);
+        let audit_log_days: i64 = env_or("RETENTION_AUDIT_LOG_DAYS", 0);
+        let bot_decision_days: i64 = env_or("RETENTION_BOT_DECISION_DAYS", 30);
+
+        Self {
+            interval: Duration::from_secs(env_or("MAINTENANCE_INTERVAL_SECS", 300)),
+            db_interval: Duration::from_secs(env_or("MAINTENANCE_DB_INTERVAL_HOURS", 24u64) * 3600),
+            raw_price_hours: env_or("RETENTION_RAW_PRICE_HOURS", 2),
+            candle_hours: env_or("RETENTION_CANDLE_HOURS", 24),
+            trade_archive_months: (trade_archive_months > 0).then_some(trade_archive_months),
+            audit_log_days: (audit_log_days > 0).then_some(audit_log_days),
+            bot_decision_days: (bot_decision_days > 0).then_some(bot_decision_days),
+            vacuum: env_or("RETENTION_VACUUM", true),
+        }
+    }
+}
+
+/// Run maintenance forever: price compaction every `interval`, DB jobs every `db_interval`
+pub async fn run_maintenance(state: AppState, config: RetentionConfig) {
+    info!("Starting maintenance jobs: {:?}", config);
+
+    let mut interval = clock::interval(&state.clock, config.interval);
+    let mut last_db_run: Option<Instant> = None;
+
+    loop {
+        interval.tick().await;
+
+        compact_price_data(&state, &config).await;
+
+        if last_db_run.is_none_or(|t| t.elapsed() >= config.db_interval) {
+            last_db_run = Some(Instant::now());
+            run_db_maintenance(&state, &config).await;
+        }
+    }
+}
+
+/// Fold old 5s points into 5-minute candles, then drop candles past their retention
+async fn compact_price_data(state: &AppState, config: &RetentionConfig) {
+    let now = state.clock.now();
+    // Never fold points the configured price window is meant to keep
+    let raw_cutoff = now - ChronoDuration::hours(config.raw_price_hours).max(state.price_window_config.duration());
+    let candle_cutoff = now - ChronoDuration::hours(config.candle_hours);
+
+    let mut prices = state.prices.write().await;
+    let prices = &mut *prices;
+    let mut compacted = 0;
+    let mut added = 0;
+    let mut dropped = 0;
+
+    for (asset, window) in prices.price_window.iter_mut() {
+        // Points arrive (roughly) in time order, so expired ones sit at the front
+        let mut old_points = Vec::new();
+        while window.front().is_some_and(|p| p.timestamp < raw_cutoff) {
+            old_points.extend(window.pop_front());
+        }
+        if old_points.is_empty() {
+            continue;
+        }
+
+        let candle_window = prices.candle_window.entry(asset.clone()).or_default();
+        let ohlc_candles = prices.ohlc_candles_5m.entry(asset.clone()).or_default();
+        let (candle_points, new_candles) = compress_price_points(
+            &old_points,
+            candle_window.make_contiguous(),
+            ohlc_candles.make_contiguous(),
+        );
+        compacted += old_points.len();
+        added += candle_points.len();
+        candle_window.extend(candle_points);
+        ohlc_candles.extend(new_candles);
+
+        // Windows are read newest-last, so keep them in time order
+        candle_window.make_contiguous().sort_by_key(|p| p.timestamp);
+        ohlc_candles.make_contiguous().sort_by_key(|c| c.timestamp);
+    }
+
+    for window in prices.candle_window.values_mut() {
+        let before = window.len();
+        window.retain(|p| p.timestamp >= candle_cutoff);
+        dropped += before - window.len();
+    }
+    for window in prices.ohlc_candles_1m.values_mut().chain(prices.ohlc_candles_5m.values_mut()) {
+        let before = window.len();
+        window.retain(|c| c.timestamp >= candle_cutoff);
+        dropped += before - window.len();
+    }
+
+    if compacted > 0 || dropped > 0 {
+        info!(
+            "Compacted {} raw price points into {} candles, dropped {} expired candles",
+            compacted,
+            added,
+            dropped
+        );
+    }
+}
+
+/// Aggregate raw points into 5-minute buckets per asset
+/// Buckets already covered by an existing candle are skipped
+/// Returns (candle_window points, 5-minute OHLC candles) to add
+fn compress_price_points(
+    points: &[PricePoint],
+    candle_window: &[PricePoint],
+    ohlc_candles: &[Candle],
+) -> (Vec<PricePoint>, Vec<Candle>) {
+    let bucket_of = |t: DateTime<Utc>| t.duration_trunc(ChronoDuration::minutes(5)).unwrap_or(t);
+
+    let mut buckets: BTreeMap<(String, DateTime<Utc>), Vec<&PricePoint>> = BTreeMap::new();
+    for point in points {
+        buckets
+            .entry((point.asset.clone(), bucket_of(point.timestamp)))
+            .or_default()
+            .push(point);
+    }
+
+    let mut new_points = Vec::new();
+    let mut new_candles = Vec::new();
+
+    for ((asset, start), mut bucket) in buckets {
+        bucket.sort_by_key(|p| p.timestamp);
+        let last = bucket[bucket.len() - 1];
+
+        let has_point = candle_window
+            .iter()
+            .any(|p| p.asset == asset && bucket_of(p.timestamp) == start);
+        let volume = bucket.iter().map(|p| p.volume).fold(None, add_volume);
+        if !has_point {
+            new_points.push(PricePoint { volume, ..last.clone() });
+        }
+
+        let has_candle = ohlc_candles
+            .iter()
+            .any(|c| c.asset == asset && bucket_of(c.timestamp) == start);
+        if !has_candle {
+            new_candles.push(Candle {
+                timestamp: start,
+                asset: asset.clone(),
+                open: bucket[0].price,
+                high: bucket.iter().map(|p| p.price).fold(f64::MIN, f64::max),
+                low: bucket.iter().map(|p| p.price).fold(f64::MAX, f64::min),
+                close: last.price,
+                volume,
+            });
+        }
+    }
+
+    (new_points, new_candles)
+}
+
+/// Archive old trades, prune the audit log and expired sessions, and vacuum, as configured
+pub async fn run_db_maintenance(state: &AppState, config: &RetentionConfig) {
+    let pool = state.db.pool();
+    let now = Utc::now();
+    let mut changed = false;
+
+    if let Some(months) = config.trade_archive_months {
+        if let Some(cutoff) = now.checked_sub_months(Months::new(months)) {
+            // Stored timestamps are RFC 3339, so a prefix compares correctly as text
+            let cutoff_str = cutoff.format("%Y-%m-%dT%H:%M:%S").to_string();
+            match queries::archive_trades_before(pool, &cutoff_str).await {
+                Ok(count) => {
+                    info!("Archived {} trades older than {}", count, cutoff_str);
+                    changed |= count > 0;
+
+                    // Keep in-memory history consistent with the trades table
+                    let mut users = state.users.write().await;
+                    for user in users.values_mut() {
+                        user.trade_history
+                            .retain(|t| t.transaction_type != TransactionType::Trade || t.timestamp >= cutoff);
+                    }
+                }
+                Err(e) => error!("Failed to archive trades: {}", e),
+            }
+        }
+    }
+
+    if let Some(days) = config.audit_log_days {
+        let cutoff = (now - ChronoDuration::days(days)).format("%Y-%m-%d %H:%M:%S").to_string();
+        match queries::prune_audit_log_before(pool, &cutoff).await {
+            Ok(count) => {
+                info!("Pruned {} audit entries older than {}", count, cutoff);
+                changed |= count > 0;
+            }
+            Err(e) => error!("Failed to prune audit log: {}", e),
+        }
+    }
+
+    if let Some(days) = config.bot_decision_days {
+        let cutoff = now - ChronoDuration::days(days);
+        match queries::prune_bot_decisions_before(pool, cutoff.timestamp()).await {
+            Ok(count) => {
+                info!("Pruned {} bot decisions older than {}", count, cutoff);
+                changed |= count > 0;
+            }
+            Err(e) => error!("Failed to prune bot decisions: {}", e),
+        }
+    }
+
+    // Expired sessions and reset tokens are useless, so they are always removed
+    match queries::prune_expired_sessions(pool, now.timestamp()).await {
+        Ok(count) => {
+            if count > 0 {
+                info!("Removed {} expired sessions", count);
+            }
+            changed |= count > 0;
+        }
+        Err(e) => error!("Failed to prune sessions: {}", e),
+    }
+
+    match queries::prune_expired_password_resets(pool, now.timestamp()).await {
+        Ok(count) => changed |= count > 0,
+        Err(e) => error!("Failed to prune password resets: {}", e),
+    }
+
+    if config.vacuum && changed && state.db.backend() == DbBackend::Sqlite {
+        match queries::vacuum(pool).await {
+            Ok(()) => info!("Vacuumed SQLite database"),
+            Err(e) => error!("Failed to vacuum database: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn point(asset: &str, minute: u32, second: u32, price: f64) -> PricePoint {
+        PricePoint {
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 12, minute, second).unwrap(),
+            asset: asset.to_string(),
+            price,
+            volume: None,
+        }
+    }
+
+    #[test]
+    fn test_compress_groups_by_asset_and_bucket() {
+        let points = vec![
+            point("BTC", 0, 0, 100.0),
+            point("BTC", 2, 0, 120.0),
+            point("BTC", 4, 55, 110.0),
+            point("BTC", 5, 0, 90.0),
+            point("ETH", 1, 0, 10.0),
+        ];
+
+        let (new_points, candles) = compress_price_points(&points, &[], &[]);
+
+        assert_eq!(new_points.len(), 3);
+        assert_eq!(candles.len(), 3);
+
+        let btc = candles
+            .iter()
+            .find(|c| c.asset == "BTC" && c.timestamp.format("%M").to_string() == "00")
+            .unwrap();
+        assert_eq!(btc.open, 100.0);
+        assert_eq!(btc.high, 120.0);
+        assert_eq!(btc.low, 100.0);
+        assert_eq!(btc.close, 110.0);
+    }
+
+    #[test]
+    fn test_compress_skips_covered_buckets() {
+        let points = vec![point("BTC", 1, 0, 100.0), point("BTC", 6, 0, 101.0)];
+        let existing = vec![point("BTC", 3, 0, 99.0)];
+
+        let (new_points, candles) = compress_price_points(&points, &existing, &[]);
+
+        assert_eq!(new_points.len(), 1);
+        assert_eq!(new_points[0].price, 101.0);
+        assert_eq!(candles.len(), 2);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::clock::{Clock, SimulatedClock};
+use crate::models::{Asset, PricePoint};
+use crate::services::price_service::LiveCandles;
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// xorshift64*: deterministic, so the same seed makes the same market
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift
+        Self(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1)
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal, by Box-Muller
+    pub fn normal(&mut self) -> f64 {
+        let u = 1.0 - self.unit();
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * self.unit()).cos()
+    }
+}
+
+/// `steps` prices from `from` to `to` in equal steps, both included
+pub fn ramp(from: f64, to: f64, steps: usize) -> Vec<f64> {
+    match steps {
+        0 => Vec::new(),
+        1 => vec![to],
+        _ => (0..steps).map(|i| from + (to - from) * i as f64 / (steps - 1) as f64).collect(),
+    }
+}
+
+/// `steps` prices of a random walk from `start`, each moving by about `volatility` (a fraction) from the last
+pub fn walk(seed: u64, start: f64, volatility: f64, steps: usize) -> Vec<f64> {
+    let mut rng = Rng::new(seed);
+    let mut price = start;
+    (0..steps)
+        .map(|_| {
+            price *= (volatility * rng.normal()).exp();
+            price
+        })
+        .collect()
+}
+
+/// Prices played into the app as if they came from the exchange, on a simulated clock
+/// Stands in for the price feeds in tests: the same feed events and candle tiers follow as for live prices
+pub struct MockPriceSource {
+    state: AppState,
+    clock: Arc<SimulatedClock>,
+    interval: Duration,
+    feeds: HashMap<Asset, LiveCandles>,
+}
+
+impl MockPriceSource {
+    /// `state` must run on `clock`; ticks are one poll interval apart
+    pub fn new(state: AppState, clock: Arc<SimulatedClock>) -> Self {
+        let interval = Duration::seconds(state.config.prices.poll_interval_secs as i64);
+        Self { state, clock, interval, feeds: HashMap::new() }
+    }
+
+    /// Space ticks `interval` apart instead, e.g. one per bot tick
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// One poll: each price is published an interval on, then the clock moves there
+    /// Prices land first, so whatever the clock wakes sees them
+    pub async fn tick(&mut self, prices: &[(&str, f64)]) {
+        let at = self.clock.now() + self.interval;
+        for (asset, price) in prices {
+            let point = PricePoint { timestamp: at, asset: asset.to_string(), price: *price, volume: None };
+            self.feeds.entry(asset.to_string()).or_default().ingest(&self.state, point).await;
+        }
+        self.clock.set(at);
+    }
+
+    /// One tick per price of `asset`
+    pub async fn play(&mut self, asset: &str, prices: &[f64]) {
+        for price in prices {
+            self.tick(&[(asset, *price)]).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_are_exact_and_repeatable() {
+        assert_eq!(ramp(100.0, 120.0, 5), vec![100.0, 105.0, 110.0, 115.0, 120.0]);
+        assert_eq!(ramp(100.0, 120.0, 1), vec![120.0]);
+        assert!(ramp(100.0, 120.0, 0).is_empty());
+
+        let prices = walk(42, 50_000.0, 0.01, 500);
+        assert_eq!(prices.len(), 500);
+        assert_eq!(prices, walk(42, 50_000.0, 0.01, 500));
+        assert_ne!(prices, walk(43, 50_000.0, 0.01, 500));
+        assert!(prices.iter().all(|p| *p > 0.0));
+    }
+}
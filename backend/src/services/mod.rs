@@ -2,3 +2,44 @@ pub mod price_service;
 pub mod trading_service;
 pub mod auth_service;
 pub mod bot_service;
+pub mod comparison_service;
+pub mod oauth_service;
+pub mod audit_service;
+pub mod maintenance_service;
+pub mod backup_service;
+pub mod cluster_service;
+pub mod session_service;
+pub mod snapshot_service;
+pub mod stats_service;
+pub mod alert_service;
+pub mod webhook_service;
+pub mod copy_service;
+pub mod portfolio_service;
+pub mod order_service;
+pub mod backtest_service;
+pub mod settings_service;
+pub mod history_service;
+pub mod performance_service;
+pub mod optimizer_service;
+pub mod replay_service;
+pub mod walk_forward_service;
+pub mod genetic_service;
+pub mod notification_service;
+pub mod discord_service;
+pub mod email_service;
+pub mod slack_service;
+pub mod competition_service;
+pub mod achievement_service;
+pub mod mock_price_service;
+pub mod seed_service;
+pub mod invariant_service;
+pub mod ledger_service;
+pub mod news_service;
+pub mod summary_service;
+pub mod risk_service;
+pub mod timezone_service;
+pub mod export_service;
+pub mod league_service;
+pub mod strategy_service;
+pub mod fee_service;
+pub mod stablecoin_service;
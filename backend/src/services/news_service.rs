@@ -0,0 +1,167 @@
+use crate::{clock, config::NewsConfig, db::queries, models::{Asset, NewsItem}, state::AppState};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// Headlines kept in memory for bots: the last day's, up to this many
+const CACHED_HOURS: i64 = 24;
+const CACHED_ITEMS: i64 = 500;
+
+#[derive(Deserialize)]
+struct PostsResponse {
+    results: Vec<Post>,
+}
+
+#[derive(Deserialize)]
+struct Post {
+    id: i64,
+    title: String,
+    url: String,
+    published_at: String,
+    source: Option<PostSource>,
+    #[serde(default)]
+    currencies: Vec<PostCurrency>,
+}
+
+#[derive(Deserialize)]
+struct PostSource {
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct PostCurrency {
+    code: String,
+}
+
+/// Headlines from a CryptoPanic `/posts/` response; posts with an unreadable date are skipped
+pub fn parse_posts(body: &str) -> Result<Vec<NewsItem>, String> {
+    let response: PostsResponse = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    Ok(response
+        .results
+        .into_iter()
+        .filter_map(|post| {
+            let published_at = DateTime::parse_from_rfc3339(&post.published_at).ok()?.with_timezone(&Utc);
+            let mut assets: Vec<Asset> = post.currencies.into_iter().map(|c| c.code.to_uppercase()).collect();
+            assets.sort();
+            assets.dedup();
+            Some(NewsItem {
+                id: format!("cryptopanic:{}", post.id),
+                title: post.title,
+                url: post.url,
+                source: post.source.map(|s| s.title).unwrap_or_default(),
+                assets,
+                published_at,
+            })
+        })
+        .collect())
+}
+
+/// The latest posts about any of `assets`
+async fn fetch_posts(
+    client: &reqwest::Client,
+    config: &NewsConfig,
+    token: &str,
+    assets: &[Asset],
+) -> Result<Vec<NewsItem>, String> {
+    let response = client
+        .get(format!("{}/posts/", config.api_url))
+        .query(&[("auth_token", token), ("currencies", &assets.join(",")), ("public", "true")])
+        .header("User-Agent", "rust-trading-simulator/1.0")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request failed: {}", e))?;
+    let body = response.text().await.map_err(|e| format!("Request failed: {}", e))?;
+    parse_posts(&body)
+}
+
+/// Reload the in-memory headlines from the database
+async fn refresh_cache(state: &AppState) {
+    let now = state.clock.now();
+    let since = now - ChronoDuration::hours(CACHED_HOURS);
+    match queries::get_news_items(state.db.pool(), None, since.timestamp(), now.timestamp(), CACHED_ITEMS).await {
+        Ok(items) => *state.news.write().await = items,
+        Err(e) => error!("Failed to load news: {}", e),
+    }
+}
+
+/// Fetch headlines about the polled assets every `news.poll_interval_secs`
+/// Does nothing unless a CryptoPanic token is configured
+pub async fn run_news_polling(state: AppState) {
+    let config = state.config.news.clone();
+    let Some(token) = config.api_token.clone() else {
+        info!("News feed disabled: no CryptoPanic token configured");
+        return;
+    };
+    let client = reqwest::Client::new();
+
+    let mut interval = clock::interval(&state.clock, Duration::from_secs(config.poll_interval_secs));
+    loop {
+        interval.tick().await;
+        let assets = state.polled_assets().await;
+        if assets.is_empty() {
+            continue;
+        }
+        match fetch_posts(&client, &config, &token, &assets).await {
+            Ok(items) => match queries::insert_news_items(state.db.pool(), &items, state.clock.now().timestamp()).await {
+                Ok(inserted) if inserted > 0 => info!("Stored {} new headlines", inserted),
+                Ok(_) => {}
+                Err(e) => error!("Failed to store news: {}", e),
+            },
+            Err(e) => error!("Failed to fetch news: {}", e),
+        }
+        refresh_cache(&state).await;
+    }
+}
+
+/// Cached headlines mentioning `asset` published after `since`, newest first
+pub async fn recent_headlines(state: &AppState, asset: &str, since: DateTime<Utc>) -> Vec<NewsItem> {
+    state
+        .news
+        .read()
+        .await
+        .iter()
+        .filter(|item| item.published_at > since && item.assets.iter().any(|a| a == asset))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_posts() {
+        let body = r#"{
+            "count": 3,
+            "next": null,
+            "results": [
+                {
+                    "kind": "news",
+                    "id": 101,
+                    "title": "Bitcoin tops $60k",
+                    "url": "https://cryptopanic.com/news/101/",
+                    "published_at": "2025-01-01T12:30:00Z",
+                    "source": {"title": "CoinDesk", "domain": "coindesk.com"},
+                    "currencies": [{"code": "btc", "title": "Bitcoin"}, {"code": "ETH", "title": "Ethereum"}]
+                },
+                {"id": 102, "title": "No coins named", "url": "https://cryptopanic.com/news/102/",
+                 "published_at": "2025-01-01T13:00:00+02:00"},
+                {"id": 103, "title": "Undated", "url": "https://cryptopanic.com/news/103/", "published_at": "soon"}
+            ]
+        }"#;
+        let items = parse_posts(body).unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id, "cryptopanic:101");
+        assert_eq!(items[0].source, "CoinDesk");
+        assert_eq!(items[0].assets, vec!["BTC", "ETH"]);
+        assert_eq!(items[0].published_at, Utc.with_ymd_and_hms(2025, 1, 1, 12, 30, 0).unwrap());
+        assert!(items[1].assets.is_empty() && items[1].source.is_empty());
+        assert_eq!(items[1].published_at, Utc.with_ymd_and_hms(2025, 1, 1, 11, 0, 0).unwrap());
+
+        assert!(parse_posts(r#"{"detail": "Token not found"}"#).is_err());
+    }
+}
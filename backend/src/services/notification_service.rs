@@ -0,0 +1,615 @@
+use crate::assets;
+use crate::bots::BotDecision;
+use crate::db::queries::{self, NotificationDelivery, NotificationEndpoint};
+use crate::models::{NotificationChannel, TradeSide};
+use crate::services::timezone_service::{self, parse_time, TIMEZONE_SETTING};
+use crate::services::{discord_service, email_service, slack_service};
+use crate::state::{self, AppState, Notification, NotificationKind};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// Endpoints a user may register
+pub const MAX_ENDPOINTS_PER_USER: i64 = 5;
+
+/// Attempts per delivery before it is logged as failed
+pub const MAX_ATTEMPTS: u32 = 5;
+
+const MAX_URL_LENGTH: usize = 2048;
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(2); // Doubled after each failed attempt
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body, keyed by the endpoint secret>`
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+const EVENT_HEADER: &str = "X-Notification-Event";
+
+/// The normalized URL, if endpoints on `channel` may deliver to it
+/// Email endpoints hold a `mailto:` URL
+pub fn validate_url(channel: NotificationChannel, url: &str) -> Result<String, String> {
+    if channel == NotificationChannel::Email {
+        return email_service::validate_address(url);
+    }
+    let url = url.trim();
+    if url.len() > MAX_URL_LENGTH {
+        return Err(format!("URL must be at most {} characters", MAX_URL_LENGTH));
+    }
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err("URL must be http(s) with a host".to_string());
+    }
+    match channel {
+        NotificationChannel::Discord => discord_service::validate_url(&parsed)?,
+        NotificationChannel::Slack => slack_service::validate_url(&parsed)?,
+        NotificationChannel::Webhook | NotificationChannel::Email => {}
+    }
+    Ok(parsed.to_string())
+}
+
+/// A fresh signing secret (kept as-is: signing needs it, unlike a login secret)
+pub fn issue_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// HMAC-SHA256 (RFC 2104); sha2 is already a dependency, a MAC crate is not
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let inner = Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x36))
+        .chain_update(data)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|b| b ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+/// Signature header value for a payload
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hex::encode(hmac_sha256(secret.as_bytes(), body)))
+}
+
+/// How a notification reads to a person
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Success,
+    Error,
+}
+
+/// A notification as a headline and a sentence, for channels people read
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub title: String,
+    pub text: String,
+    pub level: Level,
+}
+
+/// At most `max_chars` characters, ending in an ellipsis if cut
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max_chars - 1).collect();
+    cut.push('…');
+    cut
+}
+
+fn side_verb(side: &TradeSide) -> &'static str {
+    match side {
+        TradeSide::Buy => "buy",
+        TradeSide::Sell => "sell",
+    }
+}
+
+pub fn summary(kind: &NotificationKind) -> Summary {
+    let (title, text, level) = match kind {
+        NotificationKind::PriceAlert { asset, condition, threshold, price, change_pct, .. } => (
+            format!("Price alert: {}", asset),
+            match change_pct {
+                Some(change) => format!(
+                    "{} moved {:+.2}% (alert at {}%), now {}",
+                    asset,
+                    change,
+                    threshold,
+                    assets::format(*price, "USD")
+                ),
+                None => format!(
+                    "{} is {} {} (now {})",
+                    asset,
+                    condition.as_str(),
+                    threshold,
+                    assets::format(*price, "USD")
+                ),
+            },
+            Level::Info,
+        ),
+        NotificationKind::CopyTrade { leader_username, side, base_asset, quote_asset, quantity, price, .. } => (
+            "Trade copied".to_string(),
+            format!(
+                "Copied {}: {} {} at {}",
+                leader_username,
+                side_verb(side),
+                assets::format(*quantity, base_asset),
+                assets::format(*price, quote_asset)
+            ),
+            Level::Success,
+        ),
+        NotificationKind::OrderFilled { order_type, side, base_asset, quote_asset, quantity, price, .. } => (
+            "Order filled".to_string(),
+            format!(
+                "{} {} order filled: {} at {}",
+                side_verb(side),
+                order_type.as_str(),
+                assets::format(*quantity, base_asset),
+                assets::format(*price, quote_asset)
+            ),
+            Level::Success,
+        ),
+        NotificationKind::OrderFailed { order_type, side, base_asset, quantity, reason, .. } => (
+            "Order failed".to_string(),
+            format!(
+                "{} {} order for {} failed: {}",
+                side_verb(side),
+                order_type.as_str(),
+                assets::format(*quantity, base_asset),
+                reason
+            ),
+            Level::Error,
+        ),
+        NotificationKind::BotStarted { bot_name, base_asset, quote_asset, stoploss_amount, dry_run } => (
+            format!("Bot started: {}", bot_name),
+            format!(
+                "{} started on {}/{} with a {} stoploss{}",
+                bot_name,
+                base_asset,
+                quote_asset,
+                assets::format(*stoploss_amount, quote_asset),
+                if *dry_run { " as a dry run" } else { "" }
+            ),
+            Level::Info,
+        ),
+        NotificationKind::BotTrade { bot_name, base_asset, quote_asset, price, decision } => {
+            let action = match decision {
+                BotDecision::Buy { quote_amount } => {
+                    format!("bought {} worth of {}", assets::format(*quote_amount, quote_asset), base_asset)
+                }
+                BotDecision::Sell { quote_amount } => {
+                    format!("sold {} worth of {}", assets::format(*quote_amount, quote_asset), base_asset)
+                }
+                BotDecision::BuyBase { quantity } => format!("bought {}", assets::format(*quantity, base_asset)),
+                BotDecision::SellBase { quantity } => format!("sold {}", assets::format(*quantity, base_asset)),
+                BotDecision::ClosePosition => format!("closed its {} position", base_asset),
+                BotDecision::PlaceLimit { .. } | BotDecision::DoNothing => "held".to_string(),
+            };
+            (
+                format!("Bot trade: {}", bot_name),
+                format!("{} {} at {}", bot_name, action, assets::format(*price, quote_asset)),
+                Level::Success,
+            )
+        }
+        NotificationKind::StoplossHit { bot_name, reason } => {
+            (format!("Stoploss hit: {}", bot_name), format!("{} stopped: {}", bot_name, reason), Level::Error)
+        }
+        NotificationKind::BotStopped { bot_name, reason, by_user } => (
+            format!("Bot stopped: {}", bot_name),
+            format!("{} stopped: {}", bot_name, reason),
+            if *by_user { Level::Info } else { Level::Error },
+        ),
+        NotificationKind::AchievementEarned { title, details, .. } => {
+            (format!("Achievement earned: {}", title), details.clone(), Level::Success)
+        }
+        NotificationKind::DailySummary { date, end_value_usd, pnl_usd, pnl_pct, trades } => (
+            format!("Daily summary: {}", date),
+            format!(
+                "Portfolio {}, {}{}{} over the day with {} trade{}",
+                assets::format(*end_value_usd, "USD"),
+                if *pnl_usd < 0.0 { "-" } else { "+" },
+                assets::format(pnl_usd.abs(), "USD"),
+                pnl_pct.map(|pct| format!(" ({:+.2}%)", pct)).unwrap_or_default(),
+                trades,
+                if *trades == 1 { "" } else { "s" }
+            ),
+            Level::Info,
+        ),
+        NotificationKind::Test { message } => ("Test notification".to_string(), message.clone(), Level::Info),
+    };
+    Summary { title, text, level }
+}
+
+/// Settings key holding a user's [`Routing`]
+pub const ROUTING_SETTING: &str = "notification_routing";
+
+/// A user's choice of which kinds go to which channels, and when to hold off
+/// e.g. `{"rules": {"bot_trade": ["discord"], "stoploss_hit": ["email", "slack"]},
+/// "quiet_hours": {"start": "22:00", "end": "07:00", "utc_offset_minutes": 60, "allow": ["stoploss_hit"]}}`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Routing {
+    #[serde(default)]
+    rules: HashMap<String, Vec<NotificationChannel>>, // Kinds without a rule go to every channel that takes them
+    #[serde(default)]
+    quiet_hours: Option<QuietHours>,
+    #[serde(skip)]
+    utc_offset_minutes: i32, // The user's timezone, for quiet hours without an offset of their own
+}
+
+/// A daily window of local time during which only the `allow`ed kinds are delivered
+/// Wraps past midnight when `start` is later than `end`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QuietHours {
+    start: String, // "HH:MM"
+    end: String,
+    utc_offset_minutes: Option<i32>, // The user's timezone when omitted
+    #[serde(default)]
+    allow: Vec<String>,
+}
+
+fn check_kind(kind: &str) -> Result<(), String> {
+    if !NotificationKind::ROUTABLE.contains(&kind) {
+        return Err(format!(
+            "Unknown notification kind {:?} (expected one of {})",
+            kind,
+            NotificationKind::ROUTABLE.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+impl Routing {
+    /// Parse and check a stored routing setting
+    pub fn parse(value: &serde_json::Value) -> Result<Self, String> {
+        let routing: Routing = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+        for kind in routing.rules.keys() {
+            check_kind(kind)?;
+        }
+        if let Some(quiet) = &routing.quiet_hours {
+            if parse_time(&quiet.start)? == parse_time(&quiet.end)? {
+                return Err("Quiet hours must start and end at different times".to_string());
+            }
+            if let Some(minutes) = quiet.utc_offset_minutes {
+                timezone_service::offset(minutes)?;
+            }
+            for kind in &quiet.allow {
+                check_kind(kind)?;
+            }
+        }
+        Ok(routing)
+    }
+
+    /// Whether endpoints on `channel` should get this kind
+    /// A rule overrides the channel's defaults, so a user may opt email in to trades
+    pub fn routes(&self, channel: NotificationChannel, kind: &NotificationKind) -> bool {
+        match self.rules.get(kind.name()) {
+            Some(channels) => channels.contains(&channel),
+            None => accepts(channel, kind),
+        }
+    }
+
+    /// Whether this kind is held back at `at`
+    pub fn is_quiet(&self, kind: &NotificationKind, at: DateTime<Utc>) -> bool {
+        let Some(quiet) = &self.quiet_hours else {
+            return false;
+        };
+        if quiet.allow.iter().any(|allowed| allowed == kind.name()) {
+            return false;
+        }
+        // Checked in parse; a stored value that no longer parses means no quiet hours
+        let (Ok(start), Ok(end)) = (parse_time(&quiet.start), parse_time(&quiet.end)) else {
+            return false;
+        };
+        let offset = quiet.utc_offset_minutes.unwrap_or(self.utc_offset_minutes);
+        let local = (at + ChronoDuration::minutes(offset as i64)).time();
+        if start < end {
+            start <= local && local < end
+        } else {
+            local >= start || local < end
+        }
+    }
+}
+
+/// The user's routing in their timezone, or the defaults if they have none (or it no longer parses)
+async fn load_routing(state: &AppState, user_id: &str) -> Routing {
+    let settings = match queries::get_user_settings(state.db.pool(), &user_id.to_string()).await {
+        Ok(settings) => settings,
+        Err(e) => {
+            tracing::error!("Failed to load notification routing for {}: {}", user_id, e);
+            return Routing::default();
+        }
+    };
+    let Some((settings, _)) = settings else {
+        return Routing::default();
+    };
+    let mut routing = match settings.get(ROUTING_SETTING) {
+        Some(value) => Routing::parse(value).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring invalid notification routing for {}: {}", user_id, e);
+            Routing::default()
+        }),
+        None => Routing::default(),
+    };
+    if let Some(offset) = settings.get(TIMEZONE_SETTING).and_then(|value| timezone_service::parse(value).ok()) {
+        routing.utc_offset_minutes = offset.local_minus_utc() / 60;
+    }
+    routing
+}
+
+/// Whether a failed attempt is worth repeating: rate limits and server errors may pass
+fn retryable(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// What a channel is sent for one notification
+enum Payload {
+    Http { body: String, signature: Option<String> },
+    Email { subject: String, body: String },
+}
+
+/// How one delivery attempt went
+struct Outcome {
+    response_status: Option<i64>, // HTTP status, if there was a response
+    error: Option<String>,        // None once delivered
+    retry: bool,                  // Whether another attempt may go better
+}
+
+async fn post(
+    client: &reqwest::Client,
+    endpoint: &NotificationEndpoint,
+    event: &str,
+    body: &str,
+    signature: Option<&str>,
+) -> Outcome {
+    let mut request = client
+        .post(&endpoint.url)
+        .timeout(REQUEST_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, event);
+    if let Some(signature) = signature {
+        request = request.header(SIGNATURE_HEADER, signature);
+    }
+
+    match request.body(body.to_string()).send().await {
+        Ok(response) => {
+            let status = response.status();
+            let error = (!status.is_success()).then(|| format!("Endpoint answered {}", status));
+            Outcome { response_status: Some(status.as_u16() as i64), error, retry: retryable(status) }
+        }
+        Err(e) => Outcome { response_status: None, error: Some(e.to_string()), retry: true },
+    }
+}
+
+async fn mail(state: &AppState, endpoint: &NotificationEndpoint, subject: &str, body: &str) -> Outcome {
+    let Some(mailer) = &state.mailer else {
+        return Outcome { response_status: None, error: Some("Email is not configured".to_string()), retry: false };
+    };
+    match mailer.send(email_service::address(&endpoint.url), subject, body.to_string()).await {
+        Ok(()) => Outcome { response_status: None, error: None, retry: false },
+        Err(e) => Outcome { response_status: None, error: Some(e.message), retry: e.retryable },
+    }
+}
+
+/// Push one notification to one endpoint, retrying with exponential backoff, and log the outcome
+pub async fn deliver(
+    state: &AppState,
+    client: &reqwest::Client,
+    endpoint: &NotificationEndpoint,
+    notification: &Notification,
+    max_attempts: u32,
+) -> NotificationDelivery {
+    let event = notification.kind.name().to_string();
+    // Only generic webhooks are signed; chat services authenticate by their URL
+    let payload = match endpoint.channel {
+        NotificationChannel::Webhook => {
+            let payload = serde_json::to_string(notification).unwrap_or_default();
+            let signature = sign(&endpoint.secret, payload.as_bytes());
+            Payload::Http { body: payload, signature: Some(signature) }
+        }
+        NotificationChannel::Discord => {
+            Payload::Http { body: discord_service::message(notification).to_string(), signature: None }
+        }
+        NotificationChannel::Slack => {
+            Payload::Http { body: slack_service::message(notification).to_string(), signature: None }
+        }
+        NotificationChannel::Email => {
+            let (subject, body) = email_service::message(notification);
+            Payload::Email { subject, body }
+        }
+    };
+
+    let mut delivery = NotificationDelivery {
+        id: 0, // Assigned by the database
+        endpoint_id: endpoint.id.clone(),
+        user_id: endpoint.user_id.clone(),
+        event: event.clone(),
+        status: "failed".to_string(),
+        attempts: 0,
+        response_status: None,
+        error: None,
+        created_at: notification.timestamp,
+    };
+
+    let mut delay = FIRST_RETRY_DELAY;
+    for attempt in 1..=max_attempts {
+        if attempt > 1 {
+            // Retries wait in real time, whatever clock raised the notification
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+        delivery.attempts = attempt as i64;
+
+        let outcome = match &payload {
+            Payload::Http { body, signature } => post(client, endpoint, &event, body, signature.as_deref()).await,
+            Payload::Email { subject, body } => mail(state, endpoint, subject, body).await,
+        };
+        delivery.response_status = outcome.response_status;
+        delivery.error = outcome.error;
+        if delivery.error.is_none() {
+            delivery.status = "delivered".to_string();
+            break;
+        }
+        if !outcome.retry {
+            break;
+        }
+    }
+
+    if delivery.error.is_some() {
+        tracing::warn!(
+            "Notification {} to endpoint {} failed after {} attempts: {}",
+            event,
+            endpoint.id,
+            delivery.attempts,
+            delivery.error.as_deref().unwrap_or_default()
+        );
+    }
+    match queries::insert_notification_delivery(state.db.pool(), &delivery).await {
+        Ok(id) => delivery.id = id,
+        Err(e) => tracing::error!("Failed to log delivery to endpoint {}: {}", endpoint.id, e),
+    }
+    delivery
+}
+
+/// Log a delivery held back by quiet hours, so the user can see what they missed
+async fn suppress(state: &AppState, endpoint: &NotificationEndpoint, notification: &Notification) {
+    let delivery = NotificationDelivery {
+        id: 0,
+        endpoint_id: endpoint.id.clone(),
+        user_id: endpoint.user_id.clone(),
+        event: notification.kind.name().to_string(),
+        status: "suppressed".to_string(),
+        attempts: 0,
+        response_status: None,
+        error: None,
+        created_at: notification.timestamp,
+    };
+    if let Err(e) = queries::insert_notification_delivery(state.db.pool(), &delivery).await {
+        tracing::error!("Failed to log delivery to endpoint {}: {}", endpoint.id, e);
+    }
+}
+
+/// Whether endpoints on `channel` get notifications of this kind by default
+pub fn accepts(channel: NotificationChannel, kind: &NotificationKind) -> bool {
+    match channel {
+        NotificationChannel::Webhook | NotificationChannel::Discord | NotificationChannel::Slack => true,
+        NotificationChannel::Email => email_service::accepts(kind),
+    }
+}
+
+/// Fan each notification out to its user's endpoints
+/// Every instance dispatches the notifications raised on it, so each is delivered once
+pub async fn run_dispatcher(state: AppState) {
+    let mut notifications = state.notifications.subscribe();
+    let client = reqwest::Client::new();
+
+    loop {
+        let notification = match notifications.recv().await {
+            Ok(notification) => notification,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Notification dispatcher lagged, skipped {} notifications", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if state::is_memory_only(&notification.user_id) {
+            continue;
+        }
+
+        let endpoints = match queries::list_notification_endpoints(state.db.pool(), &notification.user_id).await {
+            Ok(endpoints) => endpoints,
+            Err(e) => {
+                tracing::error!("Failed to load notification endpoints for {}: {}", notification.user_id, e);
+                continue;
+            }
+        };
+
+        if endpoints.is_empty() {
+            continue;
+        }
+        let routing = load_routing(&state, &notification.user_id).await;
+        let quiet = routing.is_quiet(&notification.kind, notification.timestamp);
+
+        // Each delivery retries on its own, so a slow endpoint holds up nobody else
+        for endpoint in endpoints.into_iter().filter(|e| routing.routes(e.channel, &notification.kind)) {
+            if quiet {
+                suppress(&state, &endpoint, &notification).await;
+                continue;
+            }
+            let (state, client, notification) = (state.clone(), client.clone(), notification.clone());
+            tokio::spawn(async move {
+                deliver(&state, &client, &endpoint, &notification, MAX_ATTEMPTS).await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_matches_rfc_4231() {
+        // RFC 4231 test cases 2 and 6 (a key longer than the block)
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+        assert!(sign("Jefe", b"what do ya want for nothing?").starts_with("sha256=5bdcc146"));
+    }
+
+    #[test]
+    fn test_routing_rules_and_quiet_hours() {
+        let stoploss = NotificationKind::StoplossHit { bot_name: "b".to_string(), reason: "r".to_string() };
+        let stopped = NotificationKind::BotStopped { bot_name: "b".to_string(), reason: "r".to_string(), by_user: false };
+        let routing = Routing::parse(&serde_json::json!({
+            "rules": {"stoploss_hit": ["discord", "email"]},
+            "quiet_hours": {"start": "22:00", "end": "07:00", "utc_offset_minutes": 60, "allow": ["stoploss_hit"]},
+        }))
+        .unwrap();
+
+        assert!(routing.routes(NotificationChannel::Email, &stoploss));
+        assert!(!routing.routes(NotificationChannel::Slack, &stoploss));
+        // No rule: the channel's defaults
+        assert!(routing.routes(NotificationChannel::Slack, &stopped));
+
+        let at = |h: u32, m: u32| DateTime::from_timestamp(1_736_035_200 + (h * 3600 + m * 60) as i64, 0).unwrap();
+        assert!(routing.is_quiet(&stopped, at(21, 0))); // 22:00 local
+        assert!(routing.is_quiet(&stopped, at(5, 59)));
+        assert!(!routing.is_quiet(&stopped, at(6, 0)));
+        assert!(!routing.is_quiet(&stoploss, at(23, 0)));
+        // Without an offset of their own, quiet hours follow the user's timezone
+        let mut local = Routing::parse(&serde_json::json!({"quiet_hours": {"start": "22:00", "end": "07:00"}})).unwrap();
+        assert!(!local.is_quiet(&stopped, at(21, 0)));
+        local.utc_offset_minutes = 60;
+        assert!(local.is_quiet(&stopped, at(21, 0)));
+
+        assert!(Routing::parse(&serde_json::json!({"rules": {"stoploss_hit": ["telegram"]}})).is_err());
+        assert!(Routing::parse(&serde_json::json!({"rules": {"lunch": ["email"]}})).is_err());
+        assert!(Routing::parse(&serde_json::json!({"quiet_hours": {"start": "25:00", "end": "07:00"}})).is_err());
+        assert!(Routing::parse(&serde_json::json!({"quiet": {}})).is_err());
+    }
+
+    #[test]
+    fn test_validate_url() {
+        let webhook = NotificationChannel::Webhook;
+        assert_eq!(validate_url(webhook, " https://example.com/hook ").unwrap(), "https://example.com/hook");
+        assert!(validate_url(webhook, "http://localhost:8080/").is_ok());
+        assert!(validate_url(webhook, "ftp://example.com/").is_err());
+        assert!(validate_url(webhook, "not a url").is_err());
+        assert!(validate_url(webhook, &format!("https://example.com/{}", "a".repeat(MAX_URL_LENGTH))).is_err());
+        assert!(validate_url(NotificationChannel::Discord, "https://example.com/hook").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::services::auth_service::AuthError;
+use serde::Deserialize;
+
+/// Supported external identity providers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OAuthProvider {
+    GitHub,
+    Google,
+}
+
+impl OAuthProvider {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "github" => Some(OAuthProvider::GitHub),
+            "google" => Some(OAuthProvider::Google),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OAuthProvider::GitHub => "github",
+            OAuthProvider::Google => "google",
+        }
+    }
+
+    fn env_prefix(&self) -> &'static str {
+        match self {
+            OAuthProvider::GitHub => "OAUTH_GITHUB",
+            OAuthProvider::Google => "OAUTH_GOOGLE",
+        }
+    }
+
+    fn authorize_endpoint(&self) -> &'static str {
+        match self {
+            OAuthProvider::GitHub => "https://github.com/login/oauth/authorize",
+            OAuthProvider::Google => "https://accounts.google.com/o/oauth2/v2/auth",
+        }
+    }
+
+    fn token_endpoint(&self) -> &'static str {
+        match self {
+            OAuthProvider::GitHub => "https://github.com/login/oauth/access_token",
+            OAuthProvider::Google => "https://oauth2.googleapis.com/token",
+        }
+    }
+
+    fn scope(&self) -> &'static str {
+        match self {
+            OAuthProvider::GitHub => "read:user",
+            OAuthProvider::Google => "openid email profile",
+        }
+    }
+}
+
+/// Client credentials for a provider, read from the environment
+/// `<PREFIX>_CLIENT_ID`, `<PREFIX>_CLIENT_SECRET`, and shared `OAUTH_REDIRECT_BASE`
+pub struct OAuthConfig {
+    pub provider: OAuthProvider,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+}
+
+impl OAuthConfig {
+    /// Returns None if the provider is not configured
+    pub fn from_env(provider: OAuthProvider) -> Option<Self> {
+        let prefix = provider.env_prefix();
+        let client_id = std::env::var(format!("{}_CLIENT_ID", prefix)).ok()?;
+        let client_secret = std::env::var(format!("{}_CLIENT_SECRET", prefix)).ok()?;
+        let redirect_base = std::env::var("OAUTH_REDIRECT_BASE")
+            .unwrap_or_else(|_| "http://localhost:3000".to_string());
+
+        Some(Self {
+            provider,
+            client_id,
+            client_secret,
+            redirect_uri: format!(
+                "{}/api/auth/oauth/{}/callback",
+                redirect_base.trim_end_matches('/'),
+                provider.name()
+            ),
+        })
+    }
+
+    /// URL the browser is redirected to in order to start the flow
+    pub fn authorize_url(&self, csrf_state: &str) -> String {
+        let mut params = vec![
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("scope", self.provider.scope()),
+            ("state", csrf_state),
+        ];
+        if self.provider == OAuthProvider::Google {
+            params.push(("response_type", "code"));
+        }
+
+        reqwest::Url::parse_with_params(self.provider.authorize_endpoint(), &params)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| self.provider.authorize_endpoint().to_string())
+    }
+}
+
+/// Identity returned by the provider after a successful login
+#[derive(Debug, Clone)]
+pub struct ExternalIdentity {
+    pub subject: String,            // Stable provider-side user id
+    pub preferred_username: String, // Used as the local username on first login
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: u64,
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GoogleUser {
+    sub: String,
+    email: Option<String>,
+    name: Option<String>,
+}
+
+/// Exchange the authorization code for an access token and fetch the user's identity
+pub async fn exchange_code(config: &OAuthConfig, code: &str) -> Result<ExternalIdentity, AuthError> {
+    let client = reqwest::Client::new();
+
+    let token: TokenResponse = client
+        .post(config.provider.token_endpoint())
+        .header("Accept", "application/json")
+        .form(&[
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
+            ("code", code),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("grant_type", "authorization_code"),
+        ])
+        .send()
+        .await
+        .map_err(|e| AuthError::OAuthFailed(format!("Token request failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| AuthError::OAuthFailed(format!("Invalid token response: {}", e)))?;
+
+    match config.provider {
+        OAuthProvider::GitHub => {
+            let user: GitHubUser = client
+                .get("https://api.github.com/user")
+                .bearer_auth(&token.access_token)
+                .header("User-Agent", "rust-trading-simulator/1.0")
+                .send()
+                .await
+                .map_err(|e| AuthError::OAuthFailed(format!("User request failed: {}", e)))?
+                .json()
+                .await
+                .map_err(|e| AuthError::OAuthFailed(format!("Invalid user response: {}", e)))?;
+
+            Ok(ExternalIdentity {
+                subject: user.id.to_string(),
+                preferred_username: user.login,
+            })
+        }
+        OAuthProvider::Google => {
+            let user: GoogleUser = client
+                .get("https://openidconnect.googleapis.com/v1/userinfo")
+                .bearer_auth(&token.access_token)
+                .send()
+                .await
+                .map_err(|e| AuthError::OAuthFailed(format!("User request failed: {}", e)))?
+                .json()
+                .await
+                .map_err(|e| AuthError::OAuthFailed(format!("Invalid user response: {}", e)))?;
+
+            let preferred_username = user
+                .email
+                .or(user.name)
+                .unwrap_or_else(|| format!("google-{}", user.sub));
+
+            Ok(ExternalIdentity {
+                subject: user.sub,
+                preferred_username,
+            })
+        }
+    }
+}
+
+/// Encode key/value pairs as an `application/x-www-form-urlencoded` query string
+pub fn encode_query(params: &[(&str, &str)]) -> String {
+    reqwest::Url::parse_with_params("http://localhost/", params)
+        .ok()
+        .and_then(|url| url.query().map(|q| q.to_string()))
+        .unwrap_or_default()
+}
@@ -0,0 +1,303 @@
+use crate::bots::{self, BotConfigError, TradingBot};
+use crate::models::PricePoint;
+use crate::services::backtest_service::{self, BacktestMetrics, BacktestParams};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use utoipa::ToSchema;
+
+/// Backtests one optimization may run
+pub const MAX_GRID_COMBINATIONS: usize = 200;
+
+/// A combination's parameters and the bot built from them
+pub type Candidate = (Value, Box<dyn TradingBot>);
+
+/// What results are ranked by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RankBy {
+    #[default]
+    TotalReturn,
+    Cagr,
+    Sharpe,
+    MaxDrawdown, // Lowest first
+    WinRate,
+    ProfitFactor,
+}
+
+impl RankBy {
+    /// The metric ranked by; None sorts last
+    fn score(&self, metrics: &BacktestMetrics) -> Option<f64> {
+        let performance = &metrics.performance;
+        match self {
+            RankBy::TotalReturn => Some(performance.total_return_pct),
+            RankBy::Cagr => performance.cagr_pct,
+            RankBy::Sharpe => performance.sharpe_ratio,
+            RankBy::MaxDrawdown => Some(performance.max_drawdown_pct),
+            RankBy::WinRate => performance.win_rate_pct,
+            RankBy::ProfitFactor => performance.profit_factor,
+        }
+    }
+
+    /// Whether `a` ranks above `b`
+    pub fn better(&self, a: f64, b: f64) -> bool {
+        if *self == RankBy::MaxDrawdown {
+            a < b
+        } else {
+            a > b
+        }
+    }
+}
+
+/// One combination's backtest
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GridResult {
+    pub rank: usize, // 1 is best
+    #[schema(value_type = Object)]
+    pub parameters: Value,
+    pub score: Option<f64>, // The rank_by metric
+    pub metrics: BacktestMetrics,
+    pub stopped_reason: Option<String>,
+}
+
+/// Best score for each pair of values of two grid parameters, over all values of the others
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Heatmap {
+    pub x_parameter: String,
+    pub y_parameter: String,
+    #[schema(value_type = Vec<Object>)]
+    pub x_values: Vec<Value>,
+    #[schema(value_type = Vec<Object>)]
+    pub y_values: Vec<Value>,
+    pub scores: Vec<Vec<Option<f64>>>, // scores[y][x]
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct OptimizationResult {
+    pub rank_by: RankBy,
+    pub combinations: usize,
+    pub ticks: usize, // Price points replayed per combination
+    pub results: Vec<GridResult>, // Best first
+    pub heatmaps: Vec<Heatmap>,   // One per pair of grid parameters
+}
+
+/// Every combination of the grid's values, each merged over `base`
+pub fn combinations(base: &Value, grid: &BTreeMap<String, Vec<Value>>) -> Result<Vec<Value>, String> {
+    let base = match base {
+        Value::Null => serde_json::Map::new(),
+        Value::Object(map) => map.clone(),
+        _ => return Err("parameters must be an object".to_string()),
+    };
+    if grid.is_empty() {
+        return Err("grid must list values for at least one parameter".to_string());
+    }
+    let mut count: usize = 1;
+    for (name, values) in grid {
+        if values.is_empty() {
+            return Err(format!("grid.{} has no values", name));
+        }
+        count = count.saturating_mul(values.len());
+    }
+    if count > MAX_GRID_COMBINATIONS {
+        return Err(format!("The grid has {} combinations; the limit is {}", count, MAX_GRID_COMBINATIONS));
+    }
+
+    let mut combos = vec![base];
+    for (name, values) in grid {
+        combos = combos
+            .into_iter()
+            .flat_map(|combo| {
+                values.iter().map(move |value| {
+                    let mut combo = combo.clone();
+                    combo.insert(name.clone(), value.clone());
+                    combo
+                })
+            })
+            .collect();
+    }
+    Ok(combos.into_iter().map(Value::Object).collect())
+}
+
+/// A bot per combination, so invalid values are reported before anything runs
+pub fn build_bots(
+    strategy: &str,
+    stoploss_amount: f64,
+    combinations: Vec<Value>,
+) -> Result<Vec<Candidate>, BotConfigError> {
+    combinations
+        .into_iter()
+        .map(|parameters| {
+            let bot = bots::create_bot(strategy, stoploss_amount, &parameters).map_err(|e| match e {
+                BotConfigError::InvalidParameters(msg) => {
+                    BotConfigError::InvalidParameters(format!("{} (in {})", msg, parameters))
+                }
+                other => other,
+            })?;
+            Ok((parameters, bot))
+        })
+        .collect()
+}
+
+/// Order results best first and number them
+pub fn rank(results: &mut [GridResult], rank_by: RankBy) {
+    results.sort_by(|a, b| match (a.score, b.score) {
+        (Some(x), Some(y)) if rank_by.better(x, y) => std::cmp::Ordering::Less,
+        (Some(x), Some(y)) if rank_by.better(y, x) => std::cmp::Ordering::Greater,
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        _ => std::cmp::Ordering::Equal,
+    });
+    for (i, result) in results.iter_mut().enumerate() {
+        result.rank = i + 1;
+    }
+}
+
+/// A heatmap for every pair of grid parameters
+fn heatmaps(grid: &BTreeMap<String, Vec<Value>>, results: &[GridResult], rank_by: RankBy) -> Vec<Heatmap> {
+    let names: Vec<&String> = grid.keys().collect();
+    let mut maps = Vec::new();
+    for (i, x) in names.iter().enumerate() {
+        for y in &names[i + 1..] {
+            let (x_values, y_values) = (&grid[*x], &grid[*y]);
+            let mut scores = vec![vec![None; x_values.len()]; y_values.len()];
+            for result in results {
+                let Some(score) = result.score else { continue };
+                let xi = x_values.iter().position(|v| result.parameters.get(*x) == Some(v));
+                let yi = y_values.iter().position(|v| result.parameters.get(*y) == Some(v));
+                if let (Some(xi), Some(yi)) = (xi, yi) {
+                    let cell: &mut Option<f64> = &mut scores[yi][xi];
+                    if cell.is_none_or(|best| rank_by.better(score, best)) {
+                        *cell = Some(score);
+                    }
+                }
+            }
+            maps.push(Heatmap {
+                x_parameter: x.to_string(),
+                y_parameter: y.to_string(),
+                x_values: x_values.clone(),
+                y_values: y_values.clone(),
+                scores,
+            });
+        }
+    }
+    maps
+}
+
+/// Backtest every candidate over `series` (from `backtest_service::pair_series`), and rank the results
+/// Each replay is CPU-bound, so they run on blocking threads, one per core at a time
+pub async fn optimize(
+    params: BacktestParams,
+    series: Vec<PricePoint>,
+    grid: &BTreeMap<String, Vec<Value>>,
+    candidates: Vec<Candidate>,
+    rank_by: RankBy,
+) -> Result<OptimizationResult, String> {
+    let ticks = series.len();
+    let series = Arc::new(series);
+    let params = Arc::new(params);
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let permits = Arc::new(Semaphore::new(workers));
+
+    let combinations = candidates.len();
+    let mut tasks = JoinSet::new();
+    for (parameters, mut bot) in candidates {
+        let (series, params, permits) = (series.clone(), params.clone(), permits.clone());
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.ok()?;
+            let result = tokio::task::spawn_blocking(move || backtest_service::simulate(bot.as_mut(), &series, &params))
+                .await
+                .ok()?;
+            Some((parameters, result))
+        });
+    }
+
+    let mut results = Vec::with_capacity(combinations);
+    while let Some(joined) = tasks.join_next().await {
+        let Some((parameters, result)) = joined.ok().flatten() else {
+            return Err("A backtest crashed".to_string());
+        };
+        results.push(GridResult {
+            rank: 0,
+            parameters,
+            score: rank_by.score(&result.metrics),
+            metrics: result.metrics,
+            stopped_reason: result.stopped_reason,
+        });
+    }
+    rank(&mut results, rank_by);
+
+    Ok(OptimizationResult {
+        rank_by,
+        combinations,
+        ticks,
+        heatmaps: heatmaps(grid, &results, rank_by),
+        results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::performance_service::PerformanceMetrics;
+    use serde_json::json;
+
+    fn grid(entries: &[(&str, Value)]) -> BTreeMap<String, Vec<Value>> {
+        entries
+            .iter()
+            .map(|(name, values)| (name.to_string(), values.as_array().cloned().unwrap_or_default()))
+            .collect()
+    }
+
+    fn result(parameters: Value, total_return_pct: f64) -> GridResult {
+        GridResult {
+            rank: 0,
+            parameters,
+            score: Some(total_return_pct),
+            metrics: BacktestMetrics {
+                final_value: 0.0,
+                buy_and_hold_return_pct: 0.0,
+                trade_count: 0,
+                performance: PerformanceMetrics { total_return_pct, ..Default::default() },
+            },
+            stopped_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_combinations_cover_the_grid_within_the_limit() {
+        let g = grid(&[("trend_ticks", json!([2, 3, 4])), ("step_pct", json!([0.5, 1.0]))]);
+
+        let combos = combinations(&json!({"cooldown_ticks": 0}), &g).unwrap();
+
+        assert_eq!(combos.len(), 6);
+        assert!(combos.contains(&json!({"cooldown_ticks": 0, "trend_ticks": 4, "step_pct": 0.5})));
+        assert!(combinations(&json!({}), &grid(&[("trend_ticks", json!([]))])).is_err());
+        assert!(combinations(&json!({}), &BTreeMap::new()).is_err());
+        let big = grid(&[("a", json!((0..15).collect::<Vec<_>>())), ("b", json!((0..15).collect::<Vec<_>>()))]);
+        assert!(combinations(&json!({}), &big).is_err());
+    }
+
+    #[test]
+    fn test_rank_and_heatmap_keep_the_best_score_per_cell() {
+        let g = grid(&[("a", json!([1, 2])), ("b", json!([10, 20])), ("c", json!([0, 1]))]);
+        let mut results = vec![
+            result(json!({"a": 1, "b": 10, "c": 0}), 5.0),
+            result(json!({"a": 1, "b": 10, "c": 1}), 8.0),
+            result(json!({"a": 2, "b": 20, "c": 0}), -3.0),
+        ];
+
+        rank(&mut results, RankBy::TotalReturn);
+        let maps = heatmaps(&g, &results, RankBy::TotalReturn);
+
+        assert_eq!(results[0].score, Some(8.0));
+        assert_eq!(results[2].rank, 3);
+        // a/b, a/c and b/c
+        assert_eq!(maps.len(), 3);
+        assert_eq!(maps[0].scores, vec![vec![Some(8.0), None], vec![None, Some(-3.0)]]);
+        // Lower drawdown ranks first
+        assert!(RankBy::MaxDrawdown.better(1.0, 2.0));
+    }
+}
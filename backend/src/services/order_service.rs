@@ -0,0 +1,283 @@
+use crate::db::queries::{self, Order};
+use crate::models::{OrderType, PricePoint, TradeSide, TradeSource, UserData};
+use crate::services::fee_service::Liquidity;
+use crate::services::trading_service::{self, TradeError};
+use crate::state::{AppState, NotificationKind};
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::Instrument;
+use utoipa::ToSchema;
+
+/// Orders a user may have open at once
+pub const MAX_OPEN_ORDERS: i64 = 50;
+
+/// How an order's fill is charged: a limit rested on the book and makes, a stop trades at market and takes
+pub fn liquidity(order_type: OrderType) -> Liquidity {
+    match order_type {
+        OrderType::Limit => Liquidity::Maker,
+        OrderType::Stop => Liquidity::Taker,
+    }
+}
+
+/// Why an order definition was rejected
+pub fn validate(quantity: f64, trigger_price: f64) -> Result<(), String> {
+    if !quantity.is_finite() || quantity <= 0.0 {
+        return Err("Quantity must be positive".to_string());
+    }
+    if !trigger_price.is_finite() || trigger_price <= 0.0 {
+        return Err("Price must be positive".to_string());
+    }
+    Ok(())
+}
+
+/// Whether an order triggers at `price` (base in quote terms)
+/// Limits buy low and sell high; stops buy on a breakout and sell on a breakdown
+pub fn triggered(order_type: OrderType, side: &TradeSide, trigger_price: f64, price: f64) -> bool {
+    match (order_type, side) {
+        (OrderType::Limit, TradeSide::Buy) | (OrderType::Stop, TradeSide::Sell) => price <= trigger_price,
+        (OrderType::Limit, TradeSide::Sell) | (OrderType::Stop, TradeSide::Buy) => price >= trigger_price,
+    }
+}
+
+/// What a trade would cost before placing it
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TradePreview {
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,                 // Execution price: the market, or the order's trigger price
+    pub market_price: f64,
+    pub quote_amount: f64,          // quantity * price
+    pub fee: f64,                   // In quote asset terms
+    pub total: f64,                 // Quote spent on a buy, or received on a sell, after the fee
+    pub total_usd: Option<f64>,     // None without a USD price for the quote asset
+    pub base_balance_after: f64,
+    pub quote_balance_after: f64,
+    pub sufficient_balance: bool,   // Against current balances; orders reserve nothing until they fill
+}
+
+/// Cost and resulting balances of trading `quantity` at `price`, paying `fee_rate` of the quote amount
+#[allow(clippy::too_many_arguments)]
+pub fn preview(
+    user: &UserData,
+    base_asset: &str,
+    quote_asset: &str,
+    side: TradeSide,
+    quantity: f64,
+    price: f64,
+    market_price: f64,
+    quote_usd_price: Option<f64>,
+    fee_rate: f64,
+) -> TradePreview {
+    let quote_amount = quantity * price;
+    let fee = quote_amount * fee_rate;
+    let base_balance = user.get_balance(base_asset);
+    let quote_balance = user.get_balance(quote_asset);
+
+    let (total, base_balance_after, quote_balance_after, sufficient_balance) = match side {
+        TradeSide::Buy => {
+            let total = quote_amount + fee;
+            (total, base_balance + quantity, quote_balance - total, quote_balance >= total)
+        }
+        TradeSide::Sell => {
+            let total = quote_amount - fee;
+            (total, base_balance - quantity, quote_balance + total, base_balance >= quantity)
+        }
+    };
+
+    TradePreview {
+        base_asset: base_asset.to_string(),
+        quote_asset: quote_asset.to_string(),
+        side,
+        quantity,
+        price,
+        market_price,
+        quote_amount,
+        fee,
+        total,
+        total_usd: quote_usd_price.map(|usd| total * usd),
+        base_balance_after,
+        quote_balance_after,
+        sufficient_balance,
+    }
+}
+
+fn failure_reason(err: &TradeError) -> String {
+    match err {
+        TradeError::InsufficientFunds => "Insufficient funds".to_string(),
+        TradeError::InsufficientAssets => "Insufficient assets".to_string(),
+        TradeError::UserNotFound => "User not found".to_string(),
+        TradeError::PriceUnavailable(e) => format!("Price unavailable: {}", e),
+        TradeError::PersistenceFailed => "Trade could not be saved".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Claim a triggered order and trade it at `price`
+async fn fill(state: &AppState, order: Order, price: f64) {
+    // Only the instance that claims the order trades it, so each order fills once
+    match queries::fill_order(state.db.pool(), &order.id, price, state.clock.now()).await {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            tracing::error!("Failed to claim order {}: {}", order.id, e);
+            return;
+        }
+    }
+
+    let base_usd_price = if order.base_asset == "USD" {
+        Some(1.0)
+    } else {
+        state.get_latest_price(&order.base_asset).await
+    };
+
+    let quote_usd_price = if order.quote_asset == "USD" {
+        Some(1.0)
+    } else {
+        state.get_latest_price(&order.quote_asset).await
+    };
+
+    let result = trading_service::execute_trade_internal(
+        state,
+        &order.user_id,
+        &order.base_asset,
+        &order.quote_asset,
+        order.side.clone(),
+        order.quantity,
+        price,
+        base_usd_price,
+        quote_usd_price,
+        TradeSource::Manual, // Orders are the user's own trades
+        liquidity(order.order_type),
+    )
+    .await;
+
+    match result {
+        Ok((trade, _)) => {
+            tracing::info!(
+                "Order {} filled for user {}: {:?} {} {}/{} at {}",
+                order.id,
+                order.user_id,
+                trade.side,
+                trade.quantity,
+                trade.base_asset,
+                trade.quote_asset,
+                trade.price
+            );
+            state.notify(
+                &order.user_id,
+                NotificationKind::OrderFilled {
+                    order_id: order.id,
+                    order_type: order.order_type,
+                    side: trade.side,
+                    base_asset: trade.base_asset,
+                    quote_asset: trade.quote_asset,
+                    quantity: trade.quantity,
+                    price: trade.price,
+                },
+            );
+        }
+        Err(err) => {
+            let reason = failure_reason(&err);
+            tracing::warn!("Order {} for user {} failed: {}", order.id, order.user_id, reason);
+            if let Err(e) = queries::fail_order(state.db.pool(), &order.id, &reason).await {
+                tracing::error!("Failed to record order {} failure: {}", order.id, e);
+            }
+            state.notify(
+                &order.user_id,
+                NotificationKind::OrderFailed {
+                    order_id: order.id,
+                    order_type: order.order_type,
+                    side: order.side,
+                    base_asset: order.base_asset,
+                    quote_asset: order.quote_asset,
+                    quantity: order.quantity,
+                    reason,
+                },
+            );
+        }
+    }
+}
+
+/// Fill every open order on a pair involving the point's asset whose trigger now holds
+async fn check_orders(state: &AppState, point: &PricePoint) {
+    let orders = match queries::open_orders_for_asset(state.db.pool(), &point.asset).await {
+        Ok(orders) => orders,
+        Err(e) => {
+            tracing::error!("Failed to load orders for {}: {}", point.asset, e);
+            return;
+        }
+    };
+
+    for order in orders {
+        // Cross pairs move with either leg, so price the pair rather than using the point
+        let Ok(price) = state.get_pair_price(&order.base_asset, &order.quote_asset).await else {
+            continue;
+        };
+        if triggered(order.order_type, &order.side, order.trigger_price, price) {
+            fill(state, order, price).await;
+        }
+    }
+}
+
+/// Check open orders against every live price as it arrives
+pub async fn run_orders(state: AppState) {
+    let mut feed = state.price_feed.subscribe();
+
+    loop {
+        match feed.recv().await {
+            Ok(point) => {
+                check_orders(&state, &point)
+                    .instrument(tracing::info_span!("orders", asset = %point.asset))
+                    .await
+            }
+            // Missed prices are superseded by the next one; triggers are level-based
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Order matcher lagged, skipped {} prices", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triggers_by_type_and_side() {
+        assert!(triggered(OrderType::Limit, &TradeSide::Buy, 50_000.0, 49_000.0));
+        assert!(!triggered(OrderType::Limit, &TradeSide::Buy, 50_000.0, 51_000.0));
+        assert!(triggered(OrderType::Limit, &TradeSide::Sell, 60_000.0, 60_000.0));
+        assert!(!triggered(OrderType::Limit, &TradeSide::Sell, 60_000.0, 59_000.0));
+
+        assert!(triggered(OrderType::Stop, &TradeSide::Sell, 45_000.0, 44_000.0));
+        assert!(!triggered(OrderType::Stop, &TradeSide::Sell, 45_000.0, 46_000.0));
+        assert!(triggered(OrderType::Stop, &TradeSide::Buy, 70_000.0, 71_000.0));
+        assert!(!triggered(OrderType::Stop, &TradeSide::Buy, 70_000.0, 69_000.0));
+    }
+
+    #[test]
+    fn test_preview_balances() {
+        let user = UserData::new("trader".to_string(), 10_000.0);
+
+        let buy = preview(&user, "BTC", "USD", TradeSide::Buy, 0.1, 50_000.0, 51_000.0, Some(1.0), 0.004);
+        assert_eq!(buy.fee, 20.0);
+        assert_eq!(buy.total, 5_020.0);
+        assert_eq!(buy.quote_balance_after, 4_980.0);
+        assert_eq!(buy.base_balance_after, 0.1);
+        assert!(buy.sufficient_balance);
+
+        // The fee counts against the balance too
+        let all_in = preview(&user, "BTC", "USD", TradeSide::Buy, 0.2, 50_000.0, 50_000.0, Some(1.0), 0.004);
+        assert!(!all_in.sufficient_balance);
+
+        let sell = preview(&user, "BTC", "USD", TradeSide::Sell, 0.1, 50_000.0, 50_000.0, Some(1.0), 0.004);
+        assert_eq!(sell.total, 4_980.0);
+        assert!(!sell.sufficient_balance);
+        assert!(validate(0.1, 50_000.0).is_ok());
+        assert!(validate(0.0, 50_000.0).is_err());
+        assert!(validate(0.1, f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::models::TradeSide;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use utoipa::ToSchema;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// CAGR over less than this is extrapolated from noise and left out
+const MIN_CAGR_SPAN_SECS: i64 = 24 * 60 * 60;
+
+/// Positions below this share of the portfolio (float dust after selling out) aren't exposure
+const DUST_FRACTION: f64 = 1e-9;
+
+/// One point of an equity curve, oldest first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquitySample {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    pub flow: f64,     // Cash added since the previous sample (negative when withdrawn); not a return
+    pub exposed: bool, // Holding a position until the next sample
+    pub position: f64, // Value of the largest holding other than cash (USD, or a backtest's quote asset)
+}
+
+/// A fill in one asset, priced in the currency the equity curve is in
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub timestamp: DateTime<Utc>,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,
+}
+
+/// A sell matched against the buys it closes (first in, first out)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosedTrade {
+    pub exit: DateTime<Utc>,
+    pub pnl: f64,
+    pub duration_secs: f64, // Quantity-weighted time the sold units were held
+}
+
+/// Risk and return figures shared by backtests and live portfolios
+/// Figures that need more data than there is (or would divide by zero) are null
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct PerformanceMetrics {
+    pub total_return_pct: f64,                // Time-weighted, so deposits and withdrawals aren't returns
+    pub cagr_pct: Option<f64>,                // Annualized return; null for spans under a day
+    pub sharpe_ratio: Option<f64>,            // Annualized mean over volatility of per-sample returns, risk-free rate 0
+    pub max_drawdown_pct: f64,                // Largest peak-to-trough fall, as a positive percent
+    pub win_rate_pct: Option<f64>,            // Share of closed trades with a profit
+    pub profit_factor: Option<f64>,           // Gross profit over gross loss; null without losing trades
+    pub avg_trade_duration_secs: Option<f64>, // Mean holding time of closed trades
+    pub exposure_pct: f64,                    // Share of the time a position was held
+    pub closed_trades: usize,
+}
+
+/// Whether `invested` out of `value` counts as holding a position
+pub fn is_exposed(invested: f64, value: f64) -> bool {
+    value > 0.0 && invested / value > DUST_FRACTION
+}
+
+/// Match sells against earlier buys, first in first out, one closed trade per sell
+/// Sells with nothing bought before them in `fills` (an unknown cost basis) are skipped
+pub fn closed_trades(fills: &[Fill]) -> Vec<ClosedTrade> {
+    let mut lots: VecDeque<Fill> = VecDeque::new();
+    let mut closed = Vec::new();
+
+    for fill in fills {
+        match fill.side {
+            TradeSide::Buy => lots.push_back(fill.clone()),
+            TradeSide::Sell => {
+                let mut remaining = fill.quantity;
+                let mut matched = 0.0;
+                let mut pnl = 0.0;
+                let mut held_secs = 0.0;
+                while remaining > 0.0 {
+                    let Some(lot) = lots.front_mut() else { break };
+                    let quantity = remaining.min(lot.quantity);
+                    matched += quantity;
+                    pnl += quantity * (fill.price - lot.price);
+                    held_secs += quantity * (fill.timestamp - lot.timestamp).num_seconds() as f64;
+                    remaining -= quantity;
+                    lot.quantity -= quantity;
+                    if lot.quantity <= fill.quantity * DUST_FRACTION {
+                        lots.pop_front();
+                    }
+                }
+                if matched > 0.0 {
+                    closed.push(ClosedTrade { exit: fill.timestamp, pnl, duration_secs: held_secs / matched });
+                }
+            }
+        }
+    }
+    closed
+}
+
+/// Largest peak-to-trough fall of `values`, as a positive percent
+pub fn max_drawdown_pct(values: impl IntoIterator<Item = f64>) -> f64 {
+    let mut peak = f64::MIN;
+    let mut worst: f64 = 0.0;
+    for value in values {
+        peak = peak.max(value);
+        if peak > 0.0 {
+            worst = worst.max((peak - value) / peak * 100.0);
+        }
+    }
+    worst
+}
+
+/// Per-sample returns net of cash flows
+fn sample_returns(samples: &[EquitySample]) -> Vec<f64> {
+    samples
+        .windows(2)
+        .filter(|pair| pair[0].value > 0.0)
+        .map(|pair| (pair[1].value - pair[1].flow) / pair[0].value - 1.0)
+        .collect()
+}
+
+/// Returns chained into a growth index starting at 1, so deposits and withdrawals don't move it
+pub fn growth_index(samples: &[EquitySample]) -> Vec<f64> {
+    chain(&sample_returns(samples))
+}
+
+fn chain(returns: &[f64]) -> Vec<f64> {
+    let mut index = Vec::with_capacity(returns.len() + 1);
+    index.push(1.0);
+    for r in returns {
+        index.push(index[index.len() - 1] * (1.0 + r));
+    }
+    index
+}
+
+/// Metrics for an equity curve and the trades closed over it
+pub fn compute(samples: &[EquitySample], trades: &[ClosedTrade]) -> PerformanceMetrics {
+    let returns = sample_returns(samples);
+    let index = chain(&returns);
+    let growth = index[index.len() - 1];
+
+    let span_secs = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => (last.timestamp - first.timestamp).num_seconds(),
+        _ => 0,
+    };
+    let years = span_secs as f64 / SECONDS_PER_YEAR;
+    let cagr_pct = (span_secs >= MIN_CAGR_SPAN_SECS && growth > 0.0)
+        .then(|| (growth.powf(1.0 / years) - 1.0) * 100.0)
+        .filter(|cagr| cagr.is_finite());
+
+    let sharpe_ratio = (returns.len() >= 2 && span_secs > 0)
+        .then(|| {
+            let n = returns.len() as f64;
+            let mean = returns.iter().sum::<f64>() / n;
+            let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            let periods_per_year = n / years;
+            mean / variance.sqrt() * periods_per_year.sqrt()
+        })
+        .filter(|sharpe| sharpe.is_finite());
+
+    let exposed_secs: i64 = samples
+        .windows(2)
+        .filter(|pair| pair[0].exposed)
+        .map(|pair| (pair[1].timestamp - pair[0].timestamp).num_seconds())
+        .sum();
+
+    let gross_profit: f64 = trades.iter().map(|t| t.pnl).filter(|pnl| *pnl > 0.0).sum();
+    let gross_loss: f64 = -trades.iter().map(|t| t.pnl).filter(|pnl| *pnl < 0.0).sum::<f64>();
+    let count = trades.len() as f64;
+
+    PerformanceMetrics {
+        total_return_pct: (growth - 1.0) * 100.0,
+        cagr_pct,
+        sharpe_ratio,
+        max_drawdown_pct: max_drawdown_pct(index),
+        win_rate_pct: (!trades.is_empty())
+            .then(|| trades.iter().filter(|t| t.pnl > 0.0).count() as f64 / count * 100.0),
+        profit_factor: (gross_loss > 0.0).then(|| gross_profit / gross_loss),
+        avg_trade_duration_secs: (!trades.is_empty())
+            .then(|| trades.iter().map(|t| t.duration_secs).sum::<f64>() / count),
+        exposure_pct: if span_secs > 0 { exposed_secs as f64 / span_secs as f64 * 100.0 } else { 0.0 },
+        closed_trades: trades.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn at(days: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::days(days)
+    }
+
+    fn fill(days: i64, side: TradeSide, quantity: f64, price: f64) -> Fill {
+        Fill { timestamp: at(days), side, quantity, price }
+    }
+
+    #[test]
+    fn test_closed_trades_match_sells_first_in_first_out() {
+        let fills = [
+            fill(0, TradeSide::Sell, 1.0, 90.0), // Nothing bought yet
+            fill(1, TradeSide::Buy, 1.0, 100.0),
+            fill(3, TradeSide::Buy, 1.0, 200.0),
+            fill(5, TradeSide::Sell, 1.5, 150.0),
+            fill(7, TradeSide::Sell, 0.5, 210.0),
+        ];
+
+        let closed = closed_trades(&fills);
+
+        assert_eq!(closed.len(), 2);
+        // 1 @ 100 held 4 days and 0.5 @ 200 held 2 days, sold at 150
+        assert!((closed[0].pnl - 25.0).abs() < 1e-9);
+        assert!((closed[0].duration_secs - 86_400.0 * 10.0 / 3.0).abs() < 1e-6);
+        // The other 0.5 @ 200, held 4 days
+        assert!((closed[1].pnl - 5.0).abs() < 1e-9);
+        assert!((closed[1].duration_secs - 86_400.0 * 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compute_ignores_cash_flows_and_measures_exposure() {
+        let sample = |days, value, flow, exposed| EquitySample { timestamp: at(days), value, flow, exposed, position: 0.0 };
+        // +10%, then a 1100 deposit (no return), then -50%
+        let samples = [
+            sample(0, 1000.0, 0.0, true),
+            sample(1, 1100.0, 0.0, false),
+            sample(2, 2200.0, 1100.0, true),
+            sample(4, 1100.0, 0.0, false),
+        ];
+        let trades = [
+            ClosedTrade { exit: at(1), pnl: 30.0, duration_secs: 100.0 },
+            ClosedTrade { exit: at(2), pnl: -10.0, duration_secs: 300.0 },
+        ];
+
+        let metrics = compute(&samples, &trades);
+
+        assert!((metrics.total_return_pct - -45.0).abs() < 1e-9);
+        assert!((metrics.max_drawdown_pct - 50.0).abs() < 1e-9);
+        assert!(metrics.cagr_pct.is_some_and(|cagr| cagr < -99.0));
+        assert!(metrics.sharpe_ratio.is_some_and(|sharpe| sharpe < 0.0));
+        assert_eq!(metrics.exposure_pct, 75.0);
+        assert_eq!(metrics.win_rate_pct, Some(50.0));
+        assert_eq!(metrics.profit_factor, Some(3.0));
+        assert_eq!(metrics.avg_trade_duration_secs, Some(200.0));
+
+        // An hour's gain isn't annualized
+        let hour_later = EquitySample { timestamp: at(0) + Duration::hours(1), ..sample(0, 1010.0, 0.0, false) };
+        let short = [sample(0, 1000.0, 0.0, false), hour_later];
+        assert_eq!(compute(&short, &[]).cagr_pct, None);
+        assert_eq!(compute(&short, &[]).win_rate_pct, None);
+    }
+}
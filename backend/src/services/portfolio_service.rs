@@ -0,0 +1,331 @@
+use crate::models::{Asset, PricePoint, Trade, TradeSide, TransactionType, UserData};
+use crate::services::performance_service::{self, ClosedTrade, EquitySample, Fill, PerformanceMetrics};
+use crate::state::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// Asset the buy-and-hold benchmark holds (always polled)
+pub const BENCHMARK_ASSET: &str = "BTC";
+
+/// Price history only reaches back this far (the hourly candle tier), so "all" stops here too
+const MAX_HISTORY_DAYS: i64 = 30;
+
+/// Shortest span "all" covers, so a new account still gets a readable curve
+const MIN_ALL_RANGE_HOURS: i64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryRange {
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl HistoryRange {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "24h" => Some(HistoryRange::Day),
+            "7d" => Some(HistoryRange::Week),
+            "30d" => Some(HistoryRange::Month),
+            "all" => Some(HistoryRange::All),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryRange::Day => "24h",
+            HistoryRange::Week => "7d",
+            HistoryRange::Month => "30d",
+            HistoryRange::All => "all",
+        }
+    }
+
+    /// How far back the curve goes; "all" starts at the user's first transaction
+    fn duration(&self, user: &UserData, now: DateTime<Utc>) -> ChronoDuration {
+        let max = ChronoDuration::days(MAX_HISTORY_DAYS);
+        match self {
+            HistoryRange::Day => ChronoDuration::hours(24),
+            HistoryRange::Week => ChronoDuration::days(7),
+            HistoryRange::Month => max,
+            HistoryRange::All => user
+                .trade_history
+                .iter()
+                .map(|t| t.timestamp)
+                .min()
+                .map(|first| (now - first).clamp(ChronoDuration::hours(MIN_ALL_RANGE_HOURS), max))
+                .unwrap_or(max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct EquityPoint {
+    pub timestamp: i64,     // Unix seconds
+    pub value_usd: f64,     // Portfolio value at this time
+    pub benchmark_usd: f64, // Value had the portfolio been held in the benchmark asset instead
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PortfolioHistory {
+    pub range: String,
+    pub benchmark_asset: String,
+    pub points: Vec<EquityPoint>, // Oldest first
+    pub metrics: PerformanceMetrics, // Over the same range, computed as for backtests
+}
+
+/// Latest price at or before `at`; before the series starts, its first price
+fn price_at(series: &[PricePoint], at: DateTime<Utc>) -> Option<f64> {
+    let idx = series.partition_point(|p| p.timestamp <= at);
+    series.get(idx.saturating_sub(1)).map(|p| p.price)
+}
+
+/// Reverse a transaction's effect on balances
+fn undo(balances: &mut HashMap<Asset, f64>, trade: &Trade) {
+    let mut adjust = |asset: &str, delta: f64| *balances.entry(asset.to_string()).or_insert(0.0) += delta;
+    match trade.transaction_type {
+        TransactionType::Deposit => adjust("USD", -trade.quantity),
+        TransactionType::Withdrawal => adjust("USD", trade.quantity),
+        TransactionType::Trade => {
+            let quote_cost = trade.quantity * trade.price;
+            match trade.side {
+                TradeSide::Buy => {
+                    adjust(&trade.base_asset, -trade.quantity);
+                    adjust(&trade.quote_asset, quote_cost);
+                }
+                TradeSide::Sell => {
+                    adjust(&trade.base_asset, trade.quantity);
+                    adjust(&trade.quote_asset, -quote_cost);
+                }
+            }
+        }
+    }
+}
+
+/// USD in (positive) or out (negative) of the account; trades move value around inside it
+fn external_flow(trade: &Trade) -> f64 {
+    match trade.transaction_type {
+        TransactionType::Deposit => trade.quantity,
+        TransactionType::Withdrawal => -trade.quantity,
+        TransactionType::Trade => 0.0,
+    }
+}
+
+/// USD value of each balance at `at`
+fn holdings_usd<'a>(
+    balances: &'a HashMap<Asset, f64>,
+    series: &'a HashMap<Asset, Vec<PricePoint>>,
+    at: DateTime<Utc>,
+) -> impl Iterator<Item = (&'a str, f64)> + 'a {
+    balances.iter().map(move |(asset, balance)| {
+        if asset == "USD" {
+            (asset.as_str(), *balance)
+        } else {
+            // Assets no longer polled have no series and count as zero
+            (asset.as_str(), balance * series.get(asset).and_then(|s| price_at(s, at)).unwrap_or(0.0))
+        }
+    })
+}
+
+/// Portfolio value at each benchmark price point, from current balances walked back through `trades`
+/// The benchmark buys the benchmark asset with the starting value and with every later deposit,
+/// and sells it for withdrawals, so both curves see the same cash flows
+/// Also returns the curve as performance samples, with those cash flows and whether anything but USD was held
+fn equity_curve(
+    balances: &HashMap<Asset, f64>,
+    trades: &[Trade],
+    series: &HashMap<Asset, Vec<PricePoint>>,
+) -> (Vec<EquityPoint>, Vec<EquitySample>) {
+    let Some(benchmark) = series.get(BENCHMARK_ASSET).filter(|s| !s.is_empty()) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let mut trades: Vec<&Trade> = trades.iter().collect();
+    trades.sort_by_key(|t| t.timestamp);
+
+    // Newest first: undo every transaction after each point
+    let mut balances = balances.clone();
+    let mut remaining = trades.len();
+    let mut values = vec![0.0; benchmark.len()];
+    let mut invested = vec![0.0; benchmark.len()];
+    let mut largest = vec![0.0; benchmark.len()];
+    for (i, point) in benchmark.iter().enumerate().rev() {
+        while remaining > 0 && trades[remaining - 1].timestamp > point.timestamp {
+            remaining -= 1;
+            undo(&mut balances, trades[remaining]);
+        }
+        for (asset, value) in holdings_usd(&balances, series, point.timestamp) {
+            values[i] += value;
+            if asset != "USD" {
+                invested[i] += value;
+                largest[i] = f64::max(largest[i], value);
+            }
+        }
+    }
+
+    // Oldest first: hold the benchmark, adjusting for cash moved in or out between points
+    let mut units = values[0] / benchmark[0].price;
+    let mut next_trade = trades.partition_point(|t| t.timestamp <= benchmark[0].timestamp);
+    let mut points = Vec::with_capacity(benchmark.len());
+    let mut samples = Vec::with_capacity(benchmark.len());
+    for (((point, value_usd), invested), position) in benchmark.iter().zip(values).zip(invested).zip(largest) {
+        let mut flow = 0.0;
+        while next_trade < trades.len() && trades[next_trade].timestamp <= point.timestamp {
+            flow += external_flow(trades[next_trade]);
+            next_trade += 1;
+        }
+        units += flow / point.price;
+        points.push(EquityPoint {
+            timestamp: point.timestamp.timestamp(),
+            value_usd,
+            benchmark_usd: units * point.price,
+        });
+        samples.push(EquitySample {
+            timestamp: point.timestamp,
+            value: value_usd,
+            flow,
+            exposed: performance_service::is_exposed(invested, value_usd),
+            position,
+        });
+    }
+    (points, samples)
+}
+
+/// Closed trades per base asset in USD terms, oldest first
+/// Trades without a USD price for the base asset are left out
+fn closed_trades(trades: &[Trade]) -> Vec<ClosedTrade> {
+    let mut fills: BTreeMap<&str, Vec<Fill>> = BTreeMap::new();
+    for trade in trades.iter().filter(|t| t.transaction_type == TransactionType::Trade) {
+        let usd_price = trade.base_usd_price.or((trade.quote_asset == "USD").then_some(trade.price));
+        if let Some(price) = usd_price {
+            fills.entry(&trade.base_asset).or_default().push(Fill {
+                timestamp: trade.timestamp,
+                side: trade.side.clone(),
+                quantity: trade.quantity,
+                price,
+            });
+        }
+    }
+
+    let mut closed: Vec<ClosedTrade> = fills
+        .into_values()
+        .flat_map(|mut fills| {
+            fills.sort_by_key(|f| f.timestamp);
+            performance_service::closed_trades(&fills)
+        })
+        .collect();
+    closed.sort_by_key(|t| t.exit);
+    closed
+}
+
+/// A user's equity curve over `range`, with the samples its performance figures come from
+/// Rebuilt from the in-memory price tiers, so it reaches back at most 30 days
+pub async fn equity(state: &AppState, user: &UserData, range: HistoryRange) -> (Vec<EquityPoint>, Vec<EquitySample>) {
+    let duration = range.duration(user, Utc::now());
+
+    let mut assets: Vec<&str> = user
+        .asset_balances
+        .keys()
+        .chain(user.trade_history.iter().flat_map(|t| [&t.base_asset, &t.quote_asset]))
+        .map(String::as_str)
+        .filter(|asset| *asset != "USD")
+        .collect();
+    assets.push(BENCHMARK_ASSET);
+    assets.sort_unstable();
+    assets.dedup();
+
+    let mut series = HashMap::new();
+    for asset in assets {
+        series.insert(asset.to_string(), state.get_close_series(asset, duration).await);
+    }
+
+    equity_curve(&user.asset_balances, &user.trade_history, &series)
+}
+
+/// A user's portfolio value over `range` next to the buy-and-hold benchmark
+pub async fn portfolio_history(state: &AppState, user: &UserData, range: HistoryRange) -> PortfolioHistory {
+    let (points, samples) = equity(state, user, range).await;
+    // Buys before the range still count as cost basis for sells inside it
+    let closed: Vec<ClosedTrade> = match samples.first() {
+        Some(first) => closed_trades(&user.trade_history).into_iter().filter(|t| t.exit >= first.timestamp).collect(),
+        None => Vec::new(),
+    };
+
+    PortfolioHistory {
+        range: range.as_str().to_string(),
+        benchmark_asset: BENCHMARK_ASSET.to_string(),
+        points,
+        metrics: performance_service::compute(&samples, &closed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TradeSource;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    fn btc(hour: u32, price: f64) -> PricePoint {
+        PricePoint { timestamp: at(hour), asset: "BTC".to_string(), price, volume: None }
+    }
+
+    fn transaction(hour: u32, transaction_type: TransactionType, side: TradeSide, quantity: f64, price: f64) -> Trade {
+        let base = if transaction_type == TransactionType::Trade { "BTC" } else { "USD" };
+        Trade {
+            user_id: "u1".to_string(),
+            transaction_type,
+            base_asset: base.to_string(),
+            quote_asset: "USD".to_string(),
+            side,
+            quantity,
+            price,
+            timestamp: at(hour) + ChronoDuration::minutes(30),
+            base_usd_price: Some(price),
+            quote_usd_price: Some(1.0),
+            executed_by_bot: None,
+            source: TradeSource::Manual,
+            fee: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_equity_curve_replays_trades_and_deposits() {
+        // 1000 USD at the start; buys 1 BTC at 500 after 10:00, deposits 1000 after 11:00
+        let trades = vec![
+            transaction(10, TransactionType::Trade, TradeSide::Buy, 1.0, 500.0),
+            transaction(11, TransactionType::Deposit, TradeSide::Buy, 1000.0, 1.0),
+        ];
+        let balances = HashMap::from([("USD".to_string(), 1500.0), ("BTC".to_string(), 1.0)]);
+        let series = HashMap::from([(
+            "BTC".to_string(),
+            vec![btc(10, 500.0), btc(11, 1000.0), btc(12, 2000.0)],
+        )]);
+
+        let (curve, samples) = equity_curve(&balances, &trades, &series);
+
+        let values: Vec<f64> = curve.iter().map(|p| p.value_usd).collect();
+        assert_eq!(values, vec![1000.0, 1500.0, 3500.0]);
+        // Benchmark: 2 BTC from the start, 0.5 more bought with the deposit at 2000
+        let benchmark: Vec<f64> = curve.iter().map(|p| p.benchmark_usd).collect();
+        assert_eq!(benchmark, vec![1000.0, 2000.0, 5000.0]);
+        // The deposit is a cash flow, not a return: 1000 -> 1500 -> (3500 - 1000)
+        let flows: Vec<f64> = samples.iter().map(|s| s.flow).collect();
+        assert_eq!(flows, vec![0.0, 0.0, 1000.0]);
+        assert!(!samples[0].exposed && samples[1].exposed);
+        let metrics = performance_service::compute(&samples, &closed_trades(&trades));
+        assert!((metrics.total_return_pct - 150.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_history_range_parse() {
+        assert_eq!(HistoryRange::parse("7d"), Some(HistoryRange::Week));
+        assert_eq!(HistoryRange::parse("all").map(|r| r.as_str()), Some("all"));
+        assert_eq!(HistoryRange::parse("1y"), None);
+    }
+}
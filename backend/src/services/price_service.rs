@@ -1,23 +1,73 @@
-use crate::{api_client::ApiClient, models::{PricePoint, Candle}, state::AppState};
-use chrono::{Duration as ChronoDuration, Utc};
+use crate::{api_client::{ApiClient, ApiError}, assets, clock, db::queries, models::{add_volume, Asset, PricePoint, Candle}, services::cluster_service, state::AppState};
+use crate::services::stablecoin_service::{self, PegSimulator};
+use chrono::{DateTime, Duration as ChronoDuration, DurationRound, Utc};
+use serde::Serialize;
+use std::collections::{hash_map::Entry, HashMap};
 use std::time::Duration;
-use tokio::time;
-use tracing::{error, info};
+use tracing::{error, info, warn, Instrument};
+use utoipa::ToSchema;
+
+/// Aggregates live ticks into OHLC candles of a fixed period, aligned to wall-clock
+/// boundaries so live candles line up with backfilled ones
+struct CandleBuilder {
+    period: ChronoDuration,
+    current: Option<Candle>,
+}
+
+impl CandleBuilder {
+    fn new(period: ChronoDuration) -> Self {
+        Self { period, current: None }
+    }
+
+    fn bucket_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        timestamp.duration_trunc(self.period).unwrap_or(timestamp)
+    }
+
+    /// Add a tick; returns the previous candle once a tick lands in a new period
+    fn push(&mut self, point: &PricePoint) -> Option<Candle> {
+        let start = self.bucket_start(point.timestamp);
+
+        if let Some(candle) = self.current.as_mut().filter(|c| c.timestamp == start) {
+            candle.high = candle.high.max(point.price);
+            candle.low = candle.low.min(point.price);
+            candle.close = point.price;
+            candle.volume = add_volume(candle.volume, point.volume);
+            return None;
+        }
+
+        self.current.replace(Candle {
+            timestamp: start,
+            asset: point.asset.clone(),
+            open: point.price,
+            high: point.price,
+            low: point.price,
+            close: point.price,
+            volume: point.volume,
+        })
+    }
+}
 
-async fn backfill_and_poll_asset(state: AppState, asset: &str) {
-    let api_client = ApiClient::new();
-    let now = Utc::now();
+/// Most assets polled at once, tracked ones included; each costs a Coinbase request per tick
+pub const MAX_POLLED_ASSETS: usize = 20;
 
-    // STEP 1: Backfill 1 hour of high-frequency 5-second data (for 1h chart)
-    info!("Backfilling {} high-frequency data for last 1 hour...", asset);
-    let one_hour_ago = now - ChronoDuration::hours(1);
+/// How often feeds are matched to watchlists changed on other instances
+const WATCHLIST_SYNC_INTERVAL_SECS: u64 = 60;
 
-    match api_client.fetch_historical_candles(asset, one_hour_ago, now, 60).await {
+/// Load history for every resolution tier from Coinbase (simulated if unavailable)
+async fn backfill_asset(state: &AppState, api_client: &ApiClient, asset: &str) {
+    let now = state.clock.now();
+
+    // STEP 1: Backfill the high-frequency window (1 hour of 5-second points unless configured otherwise)
+    let window = state.price_window_config;
+    info!("Backfilling {} high-frequency data for last {} minutes...", asset, window.duration().num_minutes());
+    let window_start = now - window.duration();
+
+    match api_client.fetch_ohlc_candles_paged(asset, window_start, now, 60).await {
         Ok(candles) => {
             info!("Fetched {} one-minute candles for {} from Coinbase", candles.len(), asset);
 
-            // Interpolate to 5-second intervals (12x multiplication: 60 candles × 12 = 720 points)
-            let interpolated = crate::api_client::ApiClient::interpolate_candles(asset, candles, 5);
+            // Interpolate to the polling interval (12 points per one-minute candle at 5 seconds)
+            let interpolated = crate::api_client::ApiClient::interpolate_candles(asset, &candles, window.tick_secs);
             info!("Interpolated {} 5-second data points for {}", interpolated.len(), asset);
 
             for point in interpolated {
@@ -27,13 +77,13 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
             info!("Backfilled {} high-frequency data successfully", asset);
         }
         Err(e) => {
-            error!("Failed to fetch {} 1h data: {}", asset, e);
+            error!("Failed to fetch {} high-frequency data: {}", asset, e);
 
-            // Fallback: generate 1 hour of simulated 5-second data
+            // Fallback: generate a window of simulated high-frequency data
             if let Ok(current_price) = api_client.fetch_price(asset, "USD").await {
                 let base_price = current_price.price;
-                for i in (0..720).rev() {
-                    let time_offset = ChronoDuration::seconds((i * 5) as i64);
+                for i in (0..window.points).rev() {
+                    let time_offset = ChronoDuration::seconds(i as i64 * window.tick_secs);
                     let timestamp = now - time_offset;
                     let trend = (i as f64 / 100.0).sin() * base_price * 0.01;
                     let short_term = (i as f64 / 20.0).sin() * base_price * 0.005;
@@ -44,6 +94,7 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
                         timestamp,
                         asset: asset.to_string(),
                         price,
+                        volume: None,
                     }).await;
                 }
                 info!("Backfilled {} with simulated high-frequency data", asset);
@@ -65,6 +116,7 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
                     timestamp: *timestamp,
                     asset: asset.to_string(),
                     price: *price,
+                    volume: None,
                 }).await;
             }
 
@@ -88,6 +140,7 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
                         timestamp,
                         asset: asset.to_string(),
                         price,
+                        volume: None,
                     }).await;
                 }
                 info!("Backfilled {} with simulated low-frequency candles", asset);
@@ -95,9 +148,9 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
         }
     }
 
-    // STEP 3: Backfill 1 hour of 1-minute OHLC candles (for 1h candlestick view)
-    info!("Backfilling {} 1-minute OHLC candles for last 1 hour...", asset);
-    match api_client.fetch_ohlc_candles(asset, one_hour_ago, now, 60).await {
+    // STEP 3: Backfill 24 hours of 1-minute OHLC candles (for 1h candlestick view and 24h indicators)
+    info!("Backfilling {} 1-minute OHLC candles for last 24 hours...", asset);
+    match api_client.fetch_ohlc_candles_paged(asset, twenty_four_hours_ago, now, 60).await {
         Ok(candles) => {
             info!("Fetched {} 1-minute OHLC candles for {} from Coinbase", candles.len(), asset);
             for candle in candles {
@@ -106,7 +159,7 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
             info!("Backfilled {} 1-minute OHLC candles successfully", asset);
         }
         Err(e) => {
-            error!("Failed to fetch {} 1h OHLC candle data: {}", asset, e);
+            error!("Failed to fetch {} 24h 1-minute OHLC candle data: {}", asset, e);
         }
     }
 
@@ -125,105 +178,102 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
         }
     }
 
-    let mut interval = time::interval(Duration::from_secs(5));
-    info!("Starting live {} price polling (5s interval)", asset);
+    // STEP 5: Backfill 7 days of 15-minute OHLC candles (for 7d views)
+    info!("Backfilling {} 15-minute OHLC candles for last 7 days...", asset);
+    match api_client.fetch_ohlc_candles_paged(asset, now - ChronoDuration::days(7), now, 900).await {
+        Ok(candles) => {
+            info!("Fetched {} 15-minute OHLC candles for {} from Coinbase", candles.len(), asset);
+            for candle in candles {
+                state.add_ohlc_candle_15m(candle).await;
+            }
+        }
+        Err(e) => {
+            error!("Failed to fetch {} 7d OHLC candle data: {}", asset, e);
+        }
+    }
+
+    // STEP 6: Backfill 30 days of 1-hour OHLC candles (for 30d views)
+    info!("Backfilling {} 1-hour OHLC candles for last 30 days...", asset);
+    match api_client.fetch_ohlc_candles_paged(asset, now - ChronoDuration::days(30), now, 3600).await {
+        Ok(candles) => {
+            info!("Fetched {} 1-hour OHLC candles for {} from Coinbase", candles.len(), asset);
+            for candle in candles {
+                state.add_ohlc_candle_1h(candle).await;
+            }
+        }
+        Err(e) => {
+            error!("Failed to fetch {} 30d OHLC candle data: {}", asset, e);
+        }
+    }
+}
 
-    let mut tick_counter = 0u32;
+/// Where a feed's live prices come from: Coinbase, or for a stablecoin its simulated peg,
+/// always in synthetic mode and otherwise whenever Coinbase has no price for it
+pub struct PriceSource {
+    api_client: ApiClient,
+    peg: Option<PegSimulator>,
+    synthetic: bool,
+}
 
-    // OHLC accumulators for 1-minute candles
-    let mut current_1m_open: Option<f64> = None;
-    let mut current_1m_high: f64 = 0.0;
-    let mut current_1m_low: f64 = f64::INFINITY;
-    let mut current_1m_close: f64 = 0.0;
-    let mut current_1m_start: Option<chrono::DateTime<Utc>> = None;
+impl PriceSource {
+    pub fn new(state: &AppState, asset: &str) -> Self {
+        let stablecoin = assets::is_stablecoin(asset);
+        Self {
+            api_client: ApiClient::new(&state.config.exchange),
+            peg: stablecoin.then(|| stablecoin_service::simulator(asset, state.clock.now())),
+            synthetic: stablecoin && state.config.prices.synthetic_stablecoins,
+        }
+    }
 
-    // OHLC accumulators for 5-minute candles
-    let mut current_5m_open: Option<f64> = None;
-    let mut current_5m_high: f64 = 0.0;
-    let mut current_5m_low: f64 = f64::INFINITY;
-    let mut current_5m_close: f64 = 0.0;
-    let mut current_5m_start: Option<chrono::DateTime<Utc>> = None;
+    /// The current USD price of `asset`, timestamped `now` when simulated
+    pub async fn fetch(&mut self, asset: &str, now: DateTime<Utc>) -> Result<PricePoint, ApiError> {
+        let Some(peg) = self.peg.as_mut() else {
+            return self.api_client.fetch_price(asset, "USD").await;
+        };
+        if !self.synthetic {
+            match self.api_client.fetch_price(asset, "USD").await {
+                Ok(point) => return Ok(point),
+                Err(e) => warn!("Failed to fetch {} price, simulating its peg: {}", asset, e),
+            }
+        }
+        Ok(PricePoint { timestamp: now, asset: asset.to_string(), price: peg.next_price(), volume: None })
+    }
+}
 
-    loop {
-        interval.tick().await;
-        tick_counter += 1;
+async fn backfill_and_poll_asset(state: AppState, asset: Asset) {
+    let asset = asset.as_str();
+    let api_client = ApiClient::new(&state.config.exchange);
+    let mut source = PriceSource::new(&state, asset);
+
+    // History restored from a crash snapshot only misses the downtime; keep it
+    if state.get_latest_price(asset).await.is_some() {
+        info!("Using {} price history recovered from snapshot", asset);
+    } else {
+        if !source.synthetic {
+            backfill_asset(&state, &api_client, asset).await;
+        }
+        // A stablecoin Coinbase couldn't backfill (or a synthetic one) starts from its simulated peg
+        let missing = state.get_latest_price(asset).await.is_none();
+        if let Some(peg) = source.peg.as_mut().filter(|_| missing) {
+            stablecoin_service::backfill(&state, asset, peg).await;
+        }
+    }
 
-        match api_client.fetch_price(asset, "USD").await {
-            Ok(price_point) => {
-                let price = price_point.price;
-                let timestamp = price_point.timestamp;
+    let poll_secs = state.config.prices.poll_interval_secs;
+    let mut interval = clock::interval(&state.clock, Duration::from_secs(poll_secs));
+    info!("Starting live {} price polling ({}s interval)", asset, poll_secs);
 
-                info!("Fetched {} price: ${:.2}", asset, price);
-                state.add_price_point(price_point.clone()).await;
+    let mut last_shared_price: Option<chrono::DateTime<Utc>> = None;
+    let mut candles = LiveCandles::new();
 
-                // Update 1-minute OHLC accumulator
-                if current_1m_open.is_none() {
-                    current_1m_open = Some(price);
-                    current_1m_start = Some(timestamp);
-                }
-                current_1m_high = current_1m_high.max(price);
-                current_1m_low = current_1m_low.min(price);
-                current_1m_close = price;
-
-                // Update 5-minute OHLC accumulator
-                if current_5m_open.is_none() {
-                    current_5m_open = Some(price);
-                    current_5m_start = Some(timestamp);
-                }
-                current_5m_high = current_5m_high.max(price);
-                current_5m_low = current_5m_low.min(price);
-                current_5m_close = price;
-
-                // Every 1 minute (12 ticks at 5-second intervals), emit 1-minute OHLC candle
-                if tick_counter % 12 == 0 {
-                    if let (Some(open), Some(start_time)) = (current_1m_open, current_1m_start) {
-                        let candle = Candle {
-                            timestamp: start_time,
-                            asset: asset.to_string(),
-                            open,
-                            high: current_1m_high,
-                            low: current_1m_low,
-                            close: current_1m_close,
-                        };
-                        state.add_ohlc_candle_1m(candle).await;
-                        info!("Added {} 1-minute OHLC candle: O={:.2} H={:.2} L={:.2} C={:.2}",
-                              asset, open, current_1m_high, current_1m_low, current_1m_close);
-
-                        // Reset 1-minute accumulator
-                        current_1m_open = None;
-                        current_1m_high = 0.0;
-                        current_1m_low = f64::INFINITY;
-                        current_1m_start = None;
-                    }
-                }
+    loop {
+        interval.tick().await;
 
-                // Every 5 minutes (60 ticks at 5-second intervals), emit 5-minute OHLC candle
-                if tick_counter % 60 == 0 {
-                    // Add to old candle_window for backward compatibility
-                    state.add_candle(price_point).await;
-                    info!("Added {} 5-minute candle", asset);
-
-                    // Add 5-minute OHLC candle
-                    if let (Some(open), Some(start_time)) = (current_5m_open, current_5m_start) {
-                        let candle = Candle {
-                            timestamp: start_time,
-                            asset: asset.to_string(),
-                            open,
-                            high: current_5m_high,
-                            low: current_5m_low,
-                            close: current_5m_close,
-                        };
-                        state.add_ohlc_candle_5m(candle).await;
-                        info!("Added {} 5-minute OHLC candle: O={:.2} H={:.2} L={:.2} C={:.2}",
-                              asset, open, current_5m_high, current_5m_low, current_5m_close);
-
-                        // Reset 5-minute accumulator
-                        current_5m_open = None;
-                        current_5m_high = 0.0;
-                        current_5m_low = f64::INFINITY;
-                        current_5m_start = None;
-                    }
-                }
+        match cluster_service::fetch_live_price(&state, &mut source, asset, &mut last_shared_price).await {
+            Ok(None) => {} // Following another instance and it hasn't published a newer price yet
+            Ok(Some(price_point)) => {
+                info!("Fetched {} price: ${:.2}", asset, price_point.price);
+                candles.ingest(&state, price_point).await;
             }
             Err(e) => {
                 error!("Failed to fetch {} price: {}", asset, e);
@@ -233,17 +283,299 @@ async fn backfill_and_poll_asset(state: AppState, asset: &str) {
     }
 }
 
+/// The candle tiers of one asset, built from its live ticks; a candle is stored once its period ends
+pub struct LiveCandles {
+    builder_1m: CandleBuilder,
+    builder_5m: CandleBuilder,
+    builder_15m: CandleBuilder,
+    builder_1h: CandleBuilder,
+}
+
+impl Default for LiveCandles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveCandles {
+    pub fn new() -> Self {
+        Self {
+            builder_1m: CandleBuilder::new(ChronoDuration::minutes(1)),
+            builder_5m: CandleBuilder::new(ChronoDuration::minutes(5)),
+            builder_15m: CandleBuilder::new(ChronoDuration::minutes(15)),
+            builder_1h: CandleBuilder::new(ChronoDuration::hours(1)),
+        }
+    }
+
+    /// Publish a live price and store the candles it completes
+    pub async fn ingest(&mut self, state: &AppState, price_point: PricePoint) {
+        state.publish_price(price_point.clone()).await;
+
+        if let Some(candle) = self.builder_1m.push(&price_point) {
+            state.add_ohlc_candle_1m(candle).await;
+        }
+
+        if let Some(candle) = self.builder_5m.push(&price_point) {
+            info!("Added {} 5-minute OHLC candle: O={:.2} H={:.2} L={:.2} C={:.2}",
+                  candle.asset, candle.open, candle.high, candle.low, candle.close);
+
+            // Add to old candle_window for backward compatibility
+            state.add_candle(PricePoint {
+                timestamp: candle.timestamp,
+                asset: candle.asset.clone(),
+                price: candle.close,
+                volume: candle.volume,
+            }).await;
+            state.add_ohlc_candle_5m(candle).await;
+        }
+
+        if let Some(candle) = self.builder_15m.push(&price_point) {
+            state.add_ohlc_candle_15m(candle).await;
+        }
+
+        if let Some(candle) = self.builder_1h.push(&price_point) {
+            state.add_ohlc_candle_1h(candle).await;
+        }
+    }
+}
+
+fn spawn_feed(state: &AppState, asset: &str) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(
+        backfill_and_poll_asset(state.clone(), asset.to_string())
+            // Detached from the request that added the asset; the feed outlives it
+            .instrument(tracing::info_span!(parent: None, "price_feed", asset = %asset)),
+    )
+}
+
+/// Assets to poll: the tracked ones (always polled), then watched ones by popularity, up to the cap
+pub fn wanted_assets(tracked: &[Asset], watched: &[Asset]) -> Vec<Asset> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for asset in watched {
+        *counts.entry(asset.as_str()).or_default() += 1;
+    }
+    let mut ranked: Vec<(&str, usize)> = counts
+        .into_iter()
+        .filter(|(asset, _)| !tracked.iter().any(|t| t == asset))
+        .collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    tracked
+        .iter()
+        .map(String::as_str)
+        .chain(ranked.into_iter().map(|(asset, _)| asset))
+        .take(MAX_POLLED_ASSETS)
+        .map(str::to_string)
+        .collect()
+}
+
+/// False only when Coinbase answers but doesn't quote `asset` in USD; if it can't be reached
+/// the asset gets the benefit of the doubt (its feed falls back to simulated history)
+pub async fn quotes_asset(state: &AppState, asset: &str) -> bool {
+    !matches!(
+        ApiClient::new(&state.config.exchange).fetch_price(asset, "USD").await,
+        Err(crate::api_client::ApiError::ParseError(_))
+    )
+}
+
+/// Start feeds for newly watched assets and stop those nobody watches any more
+/// Price history of a stopped feed is kept, so re-watching it only misses the gap
+pub async fn sync_price_feeds(state: &AppState) {
+    let watched = match queries::watched_assets(state.db.pool()).await {
+        Ok(watched) => watched,
+        Err(e) => {
+            error!("Failed to load watched assets: {}", e);
+            return;
+        }
+    };
+    let wanted = wanted_assets(&state.config.prices.tracked_assets, &watched);
+
+    let mut feeds = state.price_feeds.lock().await;
+    feeds.retain(|asset, handle| {
+        let keep = wanted.contains(asset);
+        if !keep {
+            info!("Stopping {} price polling: no longer watched", asset);
+            handle.abort();
+        }
+        keep
+    });
+    for asset in wanted {
+        if let Entry::Vacant(entry) = feeds.entry(asset) {
+            let handle = spawn_feed(state, entry.key());
+            entry.insert(handle);
+        }
+    }
+}
+
 pub async fn start_price_polling(state: AppState) {
     // Spawn separate tasks for each asset
-    let btc_state = state.clone();
-    tokio::spawn(async move {
-        backfill_and_poll_asset(btc_state, "BTC").await;
-    });
+    sync_price_feeds(&state).await;
+    info!("Started price polling for {}", state.polled_assets().await.join(", "));
 
-    let eth_state = state.clone();
-    tokio::spawn(async move {
-        backfill_and_poll_asset(eth_state, "ETH").await;
-    });
+    // Watchlists edited on this instance sync immediately; this catches the rest
+    let mut interval = clock::interval(&state.clock, Duration::from_secs(WATCHLIST_SYNC_INTERVAL_SECS));
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        sync_price_feeds(&state).await;
+    }
+}
+
+/// Ticker statistics cover this much history
+pub const TICKER_WINDOW_HOURS: i64 = 24;
+
+/// The familiar exchange ticker: last price against the one 24 hours ago, with the range in between
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TickerStats {
+    pub asset: Asset,
+    pub last: f64,
+    pub open: f64, // First price in the window
+    pub high: f64,
+    pub low: f64,
+    pub change: f64,
+    pub change_pct: f64,
+    pub since: DateTime<Utc>, // Start of the data covered; later than 24 hours ago until that much has been recorded
+    pub as_of: DateTime<Utc>, // Time of the last price
+}
+
+/// An asset's ticker as of now, from the in-memory candles and ticks
+pub async fn current_ticker(state: &AppState, asset: &str) -> Option<TickerStats> {
+    let minutes = (TICKER_WINDOW_HOURS * 60) as usize;
+    let candles = state.get_ohlc_candles_1m(asset, minutes).await;
+    let ticks = state.get_price_window(asset, state.price_window_config.points).await;
+    ticker_stats(asset, &candles, &ticks, state.clock.now())
+}
+
+/// Stats over 1-minute candles and recent ticks from the last `TICKER_WINDOW_HOURS` before `now`
+/// None without a price in the window
+pub fn ticker_stats(asset: &str, candles: &[Candle], ticks: &[PricePoint], now: DateTime<Utc>) -> Option<TickerStats> {
+    let start = now - ChronoDuration::hours(TICKER_WINDOW_HOURS);
+    // Ticks inside a candle's minute repeat what it holds, so using both is harmless
+    let ranges = candles
+        .iter()
+        .filter(|c| c.timestamp >= start)
+        .map(|c| (c.timestamp, c.timestamp + ChronoDuration::minutes(1), c.open, c.high, c.low, c.close))
+        .chain(
+            ticks
+                .iter()
+                .filter(|p| p.timestamp >= start)
+                .map(|p| (p.timestamp, p.timestamp, p.price, p.price, p.price, p.price)),
+        );
+
+    let mut stats: Option<TickerStats> = None;
+    for (opened, closed, open, high, low, close) in ranges {
+        let stats = stats.get_or_insert_with(|| TickerStats {
+            asset: asset.to_string(),
+            last: close,
+            open,
+            high,
+            low,
+            change: 0.0,
+            change_pct: 0.0,
+            since: opened,
+            as_of: closed,
+        });
+        if opened < stats.since {
+            stats.since = opened;
+            stats.open = open;
+        }
+        if closed >= stats.as_of {
+            stats.as_of = closed;
+            stats.last = close;
+        }
+        stats.high = stats.high.max(high);
+        stats.low = stats.low.min(low);
+    }
+
+    stats.map(|mut stats| {
+        stats.change = stats.last - stats.open;
+        stats.change_pct = if stats.open > 0.0 { stats.change * 100.0 / stats.open } else { 0.0 };
+        stats
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn tick(secs: i64, price: f64) -> PricePoint {
+        PricePoint {
+            timestamp: Utc.timestamp_opt(1_700_000_040 + secs, 0).unwrap(),
+            asset: "BTC".to_string(),
+            price,
+            volume: None,
+        }
+    }
+
+    #[test]
+    fn test_candle_builder_emits_completed_period() {
+        let mut builder = CandleBuilder::new(ChronoDuration::minutes(1));
+
+        // 1_700_000_040 is a minute boundary
+        assert!(builder.push(&tick(0, 100.0)).is_none());
+        assert!(builder.push(&tick(5, 105.0)).is_none());
+        assert!(builder.push(&tick(30, 95.0)).is_none());
+        assert!(builder.push(&tick(55, 101.0)).is_none());
+
+        let candle = builder.push(&tick(60, 102.0)).expect("minute rolled over");
+        assert_eq!(candle.timestamp, Utc.timestamp_opt(1_700_000_040, 0).unwrap());
+        assert_eq!((candle.open, candle.high, candle.low, candle.close), (100.0, 105.0, 95.0, 101.0));
+
+        // The new period starts from the tick that closed the old one
+        let next = builder.current.as_ref().unwrap();
+        assert_eq!((next.open, next.close), (102.0, 102.0));
+    }
 
-    info!("Started price polling for BTC and ETH");
+    #[test]
+    fn test_ticker_stats_cover_the_last_day() {
+        let now = Utc.timestamp_opt(1_700_000_040 + 86_400, 0).unwrap();
+        let candle = |minutes_ago: i64, open: f64, high: f64, low: f64, close: f64| Candle {
+            timestamp: now - ChronoDuration::minutes(minutes_ago),
+            asset: "BTC".to_string(),
+            open,
+            high,
+            low,
+            close,
+            volume: None,
+        };
+        let candles = vec![
+            candle(24 * 60 + 1, 10.0, 500.0, 1.0, 90.0), // Opened before the window
+            candle(24 * 60, 100.0, 104.0, 99.0, 103.0),
+            candle(600, 103.0, 120.0, 80.0, 110.0),
+            candle(2, 110.0, 111.0, 108.0, 109.0),
+        ];
+        let ticks = vec![tick(86_400 - 60, 109.0), tick(86_400 - 5, 115.0)];
+
+        let stats = ticker_stats("BTC", &candles, &ticks, now).unwrap();
+        assert_eq!((stats.open, stats.high, stats.low, stats.last), (100.0, 120.0, 80.0, 115.0));
+        assert_eq!(stats.change, 15.0);
+        assert_eq!(stats.change_pct, 15.0);
+        assert_eq!(stats.since, now - ChronoDuration::hours(24));
+        assert_eq!(stats.as_of, ticks[1].timestamp);
+
+        assert!(ticker_stats("BTC", &candles[..1], &[], now).is_none());
+    }
+
+    #[test]
+    fn test_wanted_assets_keeps_tracked_and_ranks_watched() {
+        let watched: Vec<Asset> = ["SOL", "DOGE", "SOL", "BTC", "ADA"].iter().map(|a| a.to_string()).collect();
+        let tracked = vec!["BTC".to_string(), "ETH".to_string()];
+        assert_eq!(wanted_assets(&tracked, &watched), vec!["BTC", "ETH", "SOL", "ADA", "DOGE"]);
+        assert_eq!(wanted_assets(&tracked, &[]), vec!["BTC", "ETH"]);
+    }
+
+    #[test]
+    fn test_candle_builder_aligns_to_period_boundaries() {
+        let mut builder = CandleBuilder::new(ChronoDuration::minutes(15));
+
+        // Starting mid-period still buckets to the wall-clock boundary
+        builder.push(&tick(7 * 60, 100.0));
+        let start = builder.current.as_ref().unwrap().timestamp;
+        assert_eq!(start.timestamp() % 900, 0);
+
+        // A gap of several periods emits just the last in-progress candle
+        let candle = builder.push(&tick(3 * 3600, 110.0)).unwrap();
+        assert_eq!(candle.timestamp, start);
+        assert_eq!(candle.close, 100.0);
+    }
 }
@@ -0,0 +1,364 @@
+use crate::bots::TradingBot;
+use crate::clock::{Clock, SimulatedClock};
+use crate::db::queries::BotConfigRow;
+use crate::models::{PricePoint, TransactionType, UserData, UserId};
+use crate::services::backtest_service::{self, BacktestParams, BacktestTrade};
+use crate::services::bot_service;
+use crate::services::performance_service::{self, EquitySample, Fill, PerformanceMetrics};
+use crate::state::{AppState, BotActivity, BotRuntime, REPLAY_USER_PREFIX};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::broadcast::error::TryRecvError;
+use tracing::Instrument;
+use utoipa::ToSchema;
+
+/// Playback speeds, as multiples of real time
+pub const MIN_REPLAY_SPEED: u32 = 10;
+pub const MAX_REPLAY_SPEED: u32 = 1000;
+
+/// Real time one replay may take
+const MAX_REPLAY_WALL_SECS: i64 = 60 * 60;
+
+/// Gaps in the recorded prices longer than this are played back as if they were this long
+const MAX_GAP_SECS: i64 = 60 * 60;
+
+/// The replay clock moves in steps this long (in market time), so bots tick on schedule between prices
+const CLOCK_STEP_SECS: i64 = 5;
+
+/// Replays running at once across all users (each runs a bot task and a driver)
+const MAX_CONCURRENT_REPLAYS: usize = 10;
+
+/// Finished replays are forgotten after this long
+const RUN_TTL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Replays a user may keep (oldest are dropped first)
+const MAX_RUNS_PER_USER: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// A bot run through the live bot pipeline against recorded prices, in a sandbox state
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReplayRun {
+    pub id: String,
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub status: ReplayStatus,
+    pub params: BacktestParams,
+    pub speed: u32,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub created: std::time::Instant,
+    pub replayed_to: Option<DateTime<Utc>>, // Recorded time played back so far
+    pub ticks: u64,                         // Bot ticks so far
+    pub final_value: Option<f64>,           // In quote asset terms, once finished
+    pub metrics: Option<PerformanceMetrics>, // Once finished
+    pub trades: Vec<BacktestTrade>,         // Once finished, oldest first
+    pub stopped_reason: Option<String>,     // Set if the bot stopped before the end of the range
+    pub error: Option<String>,              // Set if failed
+}
+
+/// Real seconds a replay of `params` at `speed` takes
+fn wall_secs(params: &BacktestParams, speed: u32) -> i64 {
+    (params.end - params.start).num_seconds() / i64::from(speed.max(1))
+}
+
+pub fn validate_speed(params: &BacktestParams, speed: u32) -> Result<(), String> {
+    if !(MIN_REPLAY_SPEED..=MAX_REPLAY_SPEED).contains(&speed) {
+        return Err(format!("speed must be between {} and {}", MIN_REPLAY_SPEED, MAX_REPLAY_SPEED));
+    }
+    if wall_secs(params, speed) > MAX_REPLAY_WALL_SECS {
+        return Err(format!(
+            "At {}x this range would take over {} minutes; shorten it or speed it up",
+            speed,
+            MAX_REPLAY_WALL_SECS / 60
+        ));
+    }
+    Ok(())
+}
+
+/// Base and quote USD prices between `start` and `end`, merged oldest first
+async fn recorded_prices(state: &AppState, params: &BacktestParams) -> Vec<PricePoint> {
+    let mut points = backtest_service::usd_series(state, &params.base_asset, params.start, params.end).await;
+    if params.quote_asset != "USD" {
+        points.extend(backtest_service::usd_series(state, &params.quote_asset, params.start, params.end).await);
+        points.sort_by_key(|p| p.timestamp);
+    }
+    points
+}
+
+/// Portfolio value and the base asset held, both in quote terms, at the sandbox's latest prices
+async fn quote_value(sandbox: &AppState, user_id: &UserId, params: &BacktestParams) -> Option<(f64, f64)> {
+    let user = sandbox.get_user(user_id).await?;
+    let price = sandbox.get_pair_price(&params.base_asset, &params.quote_asset).await.ok()?;
+    let invested = user.get_balance(&params.base_asset) * price;
+    Some((user.get_balance(&params.quote_asset) + invested, invested))
+}
+
+/// Update the run, unless it has been dropped; returns whether it was cancelled
+async fn update(state: &AppState, id: &str, f: impl FnOnce(&mut ReplayRun)) -> bool {
+    let mut runs = state.replays.lock().await;
+    match runs.get_mut(id) {
+        Some(run) if run.status == ReplayStatus::Cancelled => true,
+        Some(run) => {
+            f(run);
+            false
+        }
+        None => true,
+    }
+}
+
+/// Record a replay and start it in the background; the caller polls `get_run` for progress
+/// Errs if the user already has a replay running or too many are running overall
+pub async fn start(
+    state: &AppState,
+    user_id: &UserId,
+    params: BacktestParams,
+    speed: u32,
+    bot: Box<dyn TradingBot>,
+) -> Result<ReplayRun, String> {
+    let run = ReplayRun {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id: user_id.clone(),
+        status: ReplayStatus::Running,
+        params,
+        speed,
+        created_at: Utc::now(),
+        created: std::time::Instant::now(),
+        replayed_to: None,
+        ticks: 0,
+        final_value: None,
+        metrics: None,
+        trades: Vec::new(),
+        stopped_reason: None,
+        error: None,
+    };
+
+    {
+        let mut runs = state.replays.lock().await;
+        runs.retain(|_, r| r.status == ReplayStatus::Running || r.created.elapsed() < RUN_TTL);
+
+        let running = runs.values().filter(|r| r.status == ReplayStatus::Running);
+        if running.clone().any(|r| r.user_id == *user_id) {
+            return Err("A replay is already running; wait for it or cancel it".to_string());
+        }
+        if running.count() >= MAX_CONCURRENT_REPLAYS {
+            return Err("Too many replays are running; try again shortly".to_string());
+        }
+
+        let mut own: Vec<(std::time::Instant, String)> = runs
+            .values()
+            .filter(|r| r.user_id == *user_id)
+            .map(|r| (r.created, r.id.clone()))
+            .collect();
+        own.sort();
+        for (_, id) in own.iter().take((own.len() + 1).saturating_sub(MAX_RUNS_PER_USER)) {
+            runs.remove(id);
+        }
+        runs.insert(run.id.clone(), run.clone());
+    }
+
+    let task_state = state.clone();
+    let id = run.id.clone();
+    tokio::spawn(
+        async move {
+            if let Err(e) = drive(&task_state, &id, bot).await {
+                tracing::warn!("Replay {} failed: {}", id, e);
+                update(&task_state, &id, |run| {
+                    run.status = ReplayStatus::Failed;
+                    run.error = Some(e);
+                })
+                .await;
+            }
+        }
+        .in_current_span(),
+    );
+
+    Ok(run)
+}
+
+/// Play the recorded prices into a sandbox at the run's speed while its bot ticks there as live
+async fn drive(state: &AppState, id: &str, bot: Box<dyn TradingBot>) -> Result<(), String> {
+    let Some(run) = state.replays.lock().await.get(id).cloned() else {
+        return Ok(());
+    };
+    let params = run.params;
+    let points = recorded_prices(state, &params).await;
+    if !points.iter().any(|p| p.asset == params.base_asset) {
+        return Err(format!(
+            "No {}/{} prices between {} and {}",
+            params.base_asset, params.quote_asset, params.start, params.end
+        ));
+    }
+
+    let clock = Arc::new(SimulatedClock::new(params.start));
+    let sandbox = state.sandbox(clock.clone());
+    let user_id = format!("{}{}", REPLAY_USER_PREFIX, id);
+    sandbox.users.write().await.insert(
+        user_id.clone(),
+        UserData {
+            username: "Replay".to_string(),
+            asset_balances: HashMap::from([(params.quote_asset.clone(), params.initial_balance)]),
+            trade_history: Vec::new(),
+        },
+    );
+
+    // Prices up to the first one the pair can be priced at come before the bot starts
+    let mut remaining = points.into_iter();
+    for point in remaining.by_ref() {
+        clock.set(point.timestamp);
+        sandbox.publish_price(point).await;
+        if sandbox.get_pair_price(&params.base_asset, &params.quote_asset).await.is_ok() {
+            break;
+        }
+    }
+    let initial_portfolio_value_usd = bot_service::calculate_portfolio_value_usd(&sandbox, &user_id).await?;
+
+    let mut events = sandbox.bot_events.subscribe();
+    let config = BotConfigRow {
+        bot_id: id.to_string(), // The sandbox runs this one bot
+        user_id: user_id.clone(),
+        strategy: params.strategy.clone(),
+        base_asset: params.base_asset.clone(),
+        quote_asset: params.quote_asset.clone(),
+        parameters: params.parameters.clone(),
+        stoploss_amount: params.stoploss_amount,
+        initial_portfolio_value_usd,
+        dry_run: false,
+        trade_limits: Default::default(),
+        max_base_position: None,
+        schedule: None,
+        paused: false,
+    };
+    bot_service::launch_bot(&sandbox, &config, bot, BotRuntime::default()).await;
+
+    let mut samples = Vec::new();
+    let mut stopped_reason = None;
+    let mut cancelled = false;
+    for point in remaining {
+        // Walk the clock up to the next price (skipping the excess of long gaps), pacing it to the speed
+        let gap_end = clock.now() + chrono::Duration::seconds(MAX_GAP_SECS);
+        while clock.now() < point.timestamp.min(gap_end) {
+            let step = chrono::Duration::seconds(CLOCK_STEP_SECS).min(point.timestamp.min(gap_end) - clock.now());
+            let wall = step.num_milliseconds().max(0) as u64 / u64::from(run.speed);
+            tokio::time::sleep(std::time::Duration::from_millis(wall)).await;
+            clock.advance(step);
+        }
+        clock.set(point.timestamp);
+        let timestamp = point.timestamp;
+        sandbox.publish_price(point).await;
+
+        if let Some((value, invested)) = quote_value(&sandbox, &user_id, &params).await {
+            samples.push(EquitySample {
+                timestamp,
+                value,
+                flow: 0.0,
+                exposed: performance_service::is_exposed(invested, value),
+                position: invested,
+            });
+        }
+        loop {
+            match events.try_recv() {
+                Ok(event) => {
+                    if let BotActivity::Stopped { reason } = event.activity {
+                        stopped_reason = Some(reason);
+                    }
+                }
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+
+        let ticks = sandbox.bots.read().await.get(&config.bot_id).map(|bot| bot.runtime.tick_count);
+        cancelled = update(state, id, |run| {
+            run.replayed_to = Some(timestamp);
+            run.ticks = ticks.unwrap_or(run.ticks);
+        })
+        .await;
+        if cancelled || ticks.is_none() {
+            break;
+        }
+    }
+
+    // Stops the bot noticed before this are the bot's own (stoploss, funds, errors)
+    let reason = if cancelled { "replay cancelled" } else { "replay finished" };
+    bot_service::stop_bot(&sandbox, &config.bot_id, reason).await;
+
+    let user = sandbox.get_user(&user_id).await.ok_or("Replay user vanished")?;
+    let trades: Vec<BacktestTrade> = user
+        .trade_history
+        .iter()
+        .filter(|t| t.transaction_type == TransactionType::Trade)
+        .map(|t| BacktestTrade { timestamp: t.timestamp, side: t.side.clone(), quantity: t.quantity, price: t.price })
+        .collect();
+    let fills: Vec<Fill> = trades
+        .iter()
+        .map(|t| Fill { timestamp: t.timestamp, side: t.side.clone(), quantity: t.quantity, price: t.price })
+        .collect();
+    let final_value = quote_value(&sandbox, &user_id, &params).await.map(|(value, _)| value);
+    let metrics = performance_service::compute(&samples, &performance_service::closed_trades(&fills));
+
+    let mut runs = state.replays.lock().await;
+    if let Some(run) = runs.get_mut(id) {
+        if run.status == ReplayStatus::Running {
+            run.status = ReplayStatus::Completed;
+        }
+        run.final_value = final_value;
+        run.metrics = Some(metrics);
+        run.trades = trades;
+        run.stopped_reason = stopped_reason;
+    }
+    Ok(())
+}
+
+/// One of a user's replays
+pub async fn get_run(state: &AppState, user_id: &UserId, id: &str) -> Option<ReplayRun> {
+    let runs = state.replays.lock().await;
+    runs.get(id).filter(|r| r.user_id == *user_id).cloned()
+}
+
+/// Stop a running replay; its bot is stopped and the results so far are kept
+pub async fn cancel(state: &AppState, user_id: &UserId, id: &str) -> Option<ReplayRun> {
+    let mut runs = state.replays.lock().await;
+    let run = runs.get_mut(id).filter(|r| r.user_id == *user_id)?;
+    if run.status == ReplayStatus::Running {
+        run.status = ReplayStatus::Cancelled;
+    }
+    Some(run.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_speed_bounds_and_wall_time_limit() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let params = BacktestParams {
+            strategy: "naive_momentum".to_string(),
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            start,
+            end: start + Duration::days(1),
+            initial_balance: 10_000.0,
+            stoploss_amount: 1_000.0,
+            parameters: serde_json::json!({}),
+        };
+
+        assert!(validate_speed(&params, 100).is_ok());
+        assert!(validate_speed(&params, 5).is_err());
+        assert!(validate_speed(&params, 5000).is_err());
+        // A day at 10x is 2.4 hours
+        assert!(validate_speed(&params, 10).is_err());
+        assert_eq!(wall_secs(&params, 1000), 86);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::models::{BotId, Trade, TransactionType};
+use crate::services::backtest_service::BacktestResult;
+use crate::services::performance_service::{self, EquitySample};
+use chrono::DateTime;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+// Upper bounds of grades A to D for each component; anything higher is an F
+const TURNOVER_BOUNDS: [f64; 4] = [0.5, 1.0, 2.0, 5.0];
+const CONCENTRATION_BOUNDS: [f64; 4] = [25.0, 50.0, 75.0, 90.0];
+const DRAWDOWN_BOUNDS: [f64; 4] = [5.0, 10.0, 20.0, 35.0];
+const LEVERAGE_BOUNDS: [f64; 4] = [2.0, 5.0, 10.0, 20.0];
+
+/// A for the least risk, F for the most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub enum Grade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl Grade {
+    const ALL: [Grade; 5] = [Grade::A, Grade::B, Grade::C, Grade::D, Grade::F];
+
+    /// The grade of `value` against the upper bounds of A to D
+    fn of(value: f64, bounds: [f64; 4]) -> Self {
+        bounds.iter().position(|bound| value <= *bound).map_or(Grade::F, |i| Self::ALL[i])
+    }
+
+    fn points(self) -> f64 {
+        (4 - Self::ALL.iter().position(|g| *g == self).unwrap_or(4)) as f64
+    }
+
+    fn from_points(points: f64) -> Self {
+        Self::ALL[(4.0 - points.round()).clamp(0.0, 4.0) as usize]
+    }
+}
+
+/// One dimension of a bot's risk
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RiskComponent {
+    pub name: String, // "turnover", "concentration", "drawdown" or "leverage"
+    pub value: f64,
+    pub grade: Grade,
+    pub description: String, // What the value measures
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RiskScore {
+    pub grade: Grade, // The components' grades averaged
+    pub components: Vec<RiskComponent>,
+}
+
+/// Grade the risk a bot took over `samples` (oldest first, positions in the same currency as the values)
+/// while trading `volume` under `stoploss_amount`
+/// There is no margin, so leverage is measured against the loss the bot was allowed: a position
+/// ten times the stoploss is stopped out by a 10% move
+pub fn score(samples: &[EquitySample], volume: f64, stoploss_amount: f64) -> RiskScore {
+    let span_secs = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => (last.timestamp - first.timestamp).num_seconds() as f64,
+        _ => 0.0,
+    };
+    let mean_value = samples.iter().map(|s| s.value).sum::<f64>() / samples.len().max(1) as f64;
+    let turnover = if mean_value > 0.0 {
+        volume / mean_value / (span_secs / SECONDS_PER_DAY).max(1.0)
+    } else {
+        0.0
+    };
+    let concentration = samples
+        .iter()
+        .filter(|s| s.value > 0.0)
+        .map(|s| (s.position / s.value * 100.0).min(100.0))
+        .fold(0.0, f64::max);
+    let drawdown = performance_service::max_drawdown_pct(performance_service::growth_index(samples));
+    let peak_position = samples.iter().map(|s| s.position).fold(0.0, f64::max);
+    let leverage = if stoploss_amount > 0.0 { peak_position / stoploss_amount } else { 0.0 };
+
+    let component = |name: &str, value: f64, bounds, description: &str| RiskComponent {
+        name: name.to_string(),
+        value,
+        grade: Grade::of(value, bounds),
+        description: description.to_string(),
+    };
+    let components = vec![
+        component("turnover", turnover, TURNOVER_BOUNDS, "Traded volume per day, as a multiple of the average portfolio value"),
+        component("concentration", concentration, CONCENTRATION_BOUNDS, "Largest share of the portfolio held in one asset, in percent"),
+        component("drawdown", drawdown, DRAWDOWN_BOUNDS, "Largest peak-to-trough fall, in percent"),
+        component("leverage", leverage, LEVERAGE_BOUNDS, "Largest position as a multiple of the stoploss amount"),
+    ];
+    let points = components.iter().map(|c| c.grade.points()).sum::<f64>() / components.len() as f64;
+    RiskScore { grade: Grade::from_points(points), components }
+}
+
+/// Score a finished backtest, in its quote asset
+pub fn score_backtest(result: &BacktestResult, stoploss_amount: f64) -> RiskScore {
+    let samples: Vec<EquitySample> = result
+        .equity
+        .iter()
+        .filter_map(|p| {
+            Some(EquitySample {
+                timestamp: DateTime::from_timestamp(p.timestamp, 0)?,
+                value: p.value,
+                flow: 0.0,
+                exposed: performance_service::is_exposed(p.position_value, p.value),
+                position: p.position_value,
+            })
+        })
+        .collect();
+    let volume: f64 = result.trades.iter().map(|t| t.quantity * t.price).sum();
+    score(&samples, volume, stoploss_amount)
+}
+
+/// Score a live bot from its owner's equity curve and the trades it made (in USD)
+/// Trades are the bot's by their source's instance id, so another bot running the same strategy
+/// doesn't count; trades without a USD price are left out of the volume
+pub fn score_bot(samples: &[EquitySample], trades: &[Trade], bot_id: &BotId, stoploss_amount: f64) -> RiskScore {
+    let start = samples.first().map(|s| s.timestamp);
+    let volume: f64 = trades
+        .iter()
+        .filter(|t| {
+            t.transaction_type == TransactionType::Trade
+                && t.source.bot_instance() == Some(bot_id)
+                && start.is_some_and(|start| t.timestamp > start)
+        })
+        .filter_map(|t| {
+            let usd_price = t.base_usd_price.or((t.quote_asset == "USD").then_some(t.price));
+            usd_price.map(|price| t.quantity * price)
+        })
+        .sum();
+    score(samples, volume, stoploss_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn test_score_grades_each_component() {
+        let at = |days| Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::days(days);
+        let sample = |days, value, position| EquitySample { timestamp: at(days), value, flow: 0.0, exposed: position > 0.0, position };
+        // Two days; a fifth of the portfolio in one position at most, a 15% fall at the end
+        let samples = [sample(0, 1000.0, 0.0), sample(1, 1000.0, 200.0), sample(2, 850.0, 0.0)];
+
+        let report = score(&samples, 2850.0, 50.0);
+        let grades: Vec<(&str, Grade)> = report.components.iter().map(|c| (c.name.as_str(), c.grade)).collect();
+        assert_eq!(
+            grades,
+            vec![("turnover", Grade::C), ("concentration", Grade::A), ("drawdown", Grade::C), ("leverage", Grade::B)]
+        );
+        assert_eq!(report.components[0].value, 1.5);
+        assert_eq!(report.components[3].value, 4.0);
+        assert_eq!(report.grade, Grade::B);
+
+        // Idle bots take no risk; going all in with 25 times the stoploss is the other extreme
+        assert_eq!(score(&samples[..1], 0.0, 100.0).grade, Grade::A);
+        let all_in = [sample(0, 1000.0, 1000.0), sample(1, 500.0, 500.0)];
+        assert_eq!(score(&all_in, 10_000.0, 40.0).grade, Grade::F);
+    }
+
+    #[test]
+    fn test_bot_volume_counts_only_the_bots_own_trades() {
+        use crate::models::{TradeSide, TradeSource};
+        let at = |hours| Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hours);
+        let sample = |hours| EquitySample { timestamp: at(hours), value: 1000.0, flow: 0.0, exposed: false, position: 0.0 };
+        let samples = [sample(0), sample(24)];
+        let trade = |bot_id: &str, quantity: f64| Trade {
+            user_id: "u1".to_string(),
+            transaction_type: TransactionType::Trade,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            side: TradeSide::Buy,
+            quantity,
+            price: 50_000.0,
+            timestamp: at(1),
+            base_usd_price: Some(50_000.0),
+            quote_usd_price: Some(1.0),
+            executed_by_bot: Some("Naive Momentum".to_string()),
+            source: TradeSource::bot(&bot_id.to_string(), "Naive Momentum"),
+            fee: 0.0,
+        };
+        // Two bots of the same strategy: only the first one's $500 counts toward its turnover
+        let trades = [trade("bot-a", 0.01), trade("bot-b", 0.02)];
+        let report = score_bot(&samples, &trades, &"bot-a".to_string(), 100.0);
+        assert_eq!(report.components[0].value, score(&samples, 500.0, 100.0).components[0].value);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::db::queries::{self, BotConfigRow, PriceCandle};
+use crate::db::DbPool;
+use crate::models::{Asset, Trade, TradeSide, TradeSource, TransactionType, UserData};
+use crate::services::auth_service::{self, AuthError};
+use crate::services::history_service::{self, CandleBatch};
+use crate::services::mock_price_service::Rng;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use std::collections::HashMap;
+
+/// Every seeded account signs in with this password
+pub const SEED_PASSWORD: &str = "demo-password";
+pub const MAX_SEED_USERS: usize = 20;
+pub const MAX_SEED_DAYS: i64 = 90;
+
+/// Synthetic history is hourly, like imported exchange candles
+const CANDLE_SECS: i64 = 3600;
+
+/// Assets given price history, with their price at the start and hourly volatility
+const SEED_ASSETS: &[(&str, f64, f64)] = &[("BTC", 60_000.0, 0.008), ("ETH", 3_000.0, 0.011), ("SOL", 150.0, 0.016)];
+
+const USERNAMES: &[&str] = &[
+    "alice", "bob", "carol", "dave", "erin", "frank", "grace", "heidi", "ivan", "judy", "mallory", "niaj", "olivia",
+    "peggy", "rupert", "sybil", "trent", "victor", "walter", "yvonne",
+];
+
+/// The first users run a bot each: (strategy, bot name as recorded on its trades, base asset, stoploss as a share of the portfolio)
+const SEED_BOTS: &[(&str, &str, &str, f64)] =
+    &[("naive_momentum", "Naive Momentum", "BTC", 0.25), ("naive_momentum", "Naive Momentum", "ETH", 0.2)];
+
+#[derive(Debug, Clone)]
+pub struct SeedOptions {
+    pub users: usize,
+    pub days: i64,
+    pub seed: u64, // Same seed, same data
+}
+
+impl Default for SeedOptions {
+    fn default() -> Self {
+        Self { users: 5, days: 28, seed: 1 }
+    }
+}
+
+/// `hours` hourly candles ending with the one open at `end`, a random walk from `start_price`
+fn price_walk(rng: &mut Rng, start_price: f64, volatility: f64, hours: i64, end: DateTime<Utc>) -> Vec<PriceCandle> {
+    let mut candles = Vec::with_capacity(hours as usize);
+    let mut price = start_price;
+    for hour in (0..hours).rev() {
+        let open = price;
+        let close = open * (volatility * rng.normal()).exp();
+        let wick = |rng: &mut Rng| 1.0 + volatility * rng.unit();
+        candles.push(PriceCandle {
+            timestamp: end - Duration::hours(hour),
+            open,
+            high: open.max(close) * wick(rng),
+            low: open.min(close) / wick(rng),
+            close,
+            volume: Some(100.0 + 900.0 * rng.unit()),
+        });
+        price = close;
+    }
+    candles
+}
+
+/// One made-up account
+#[derive(Debug)]
+pub struct SeedUser {
+    pub username: String,
+    pub asset_balances: HashMap<Asset, f64>,
+    pub trades: Vec<Trade>,                               // Oldest first; `user_id` is filled in when stored
+    pub bot: Option<BotConfigRow>,                       // Valued at the last seeded prices
+}
+
+#[derive(Debug)]
+pub struct SeedData {
+    pub candles: Vec<(&'static str, Vec<PriceCandle>)>,
+    pub users: Vec<SeedUser>,
+}
+
+/// Trades at the candle closes: each user trades at their own pace and size, and some add
+/// or take out cash along the way. Users with a bot also have bot fills on its asset
+fn trade_history(rng: &mut Rng, index: usize, starting_balance: f64, candles: &[(&str, Vec<PriceCandle>)]) -> SeedUser {
+    let mut user = UserData::new(format!("{}_demo", USERNAMES[index]), starting_balance);
+    let bot = SEED_BOTS.get(index);
+    let trade_chance = 0.02 + 0.06 * rng.unit(); // Per hour
+    let size = 0.05 + 0.25 * rng.unit();          // Share of the spendable balance per trade
+    let hours = candles[0].1.len();
+
+    for hour in 0..hours {
+        // Now and then some cash comes in, or goes out if there is enough of it
+        if rng.unit() < 0.002 {
+            let amount = (starting_balance * 0.1 * (1.0 + rng.unit())).round();
+            let (side, transaction_type, delta) = if rng.unit() < 0.7 {
+                (TradeSide::Buy, TransactionType::Deposit, amount)
+            } else {
+                (TradeSide::Sell, TransactionType::Withdrawal, -amount)
+            };
+            if user.get_balance("USD") + delta >= 0.0 {
+                *user.asset_balances.entry("USD".to_string()).or_insert(0.0) += delta;
+                user.trade_history.push(Trade {
+                    user_id: String::new(),
+                    transaction_type,
+                    base_asset: "USD".to_string(),
+                    quote_asset: "USD".to_string(),
+                    side,
+                    quantity: amount,
+                    price: 1.0,
+                    timestamp: candles[0].1[hour].timestamp,
+                    base_usd_price: Some(1.0),
+                    quote_usd_price: Some(1.0),
+                    executed_by_bot: None,
+                    source: TradeSource::Manual,
+                    fee: 0.0,
+                });
+            }
+        }
+
+        // Bots trade their asset more often and in smaller steps, from the last week on
+        let by_bot = bot.is_some_and(|_| hour + 24 * 7 >= hours && rng.unit() < 0.15);
+        if !by_bot && rng.unit() >= trade_chance {
+            continue;
+        }
+        let (asset, series) = match bot.filter(|_| by_bot) {
+            Some((_, _, base, _)) => candles.iter().find(|(a, _)| a == base).unwrap_or(&candles[0]),
+            None => &candles[(rng.next_u64() % candles.len() as u64) as usize],
+        };
+        let price = series[hour].close;
+        let held = user.get_balance(asset);
+        let side = if held * price < 1.0 || rng.unit() < 0.55 { TradeSide::Buy } else { TradeSide::Sell };
+        let step = if by_bot { 0.05 } else { size };
+        let quantity = match side {
+            TradeSide::Buy => user.get_balance("USD") * step / price,
+            TradeSide::Sell => held * (step * 2.0).min(1.0),
+        };
+        if quantity * price < 1.0 {
+            continue;
+        }
+        let (usd_delta, asset_delta) = match side {
+            TradeSide::Buy => (-quantity * price, quantity),
+            TradeSide::Sell => (quantity * price, -quantity),
+        };
+        *user.asset_balances.entry("USD".to_string()).or_insert(0.0) += usd_delta;
+        *user.asset_balances.entry(asset.to_string()).or_insert(0.0) += asset_delta;
+        let executor = bot.filter(|_| by_bot).map(|(_, name, _, _)| name.to_string());
+        user.trade_history.push(Trade {
+            user_id: String::new(),
+            transaction_type: TransactionType::Trade,
+            base_asset: asset.to_string(),
+            quote_asset: "USD".to_string(),
+            side,
+            quantity,
+            price,
+            timestamp: series[hour].timestamp,
+            base_usd_price: Some(price),
+            quote_usd_price: Some(1.0),
+            source: TradeSource::from_executor(executor.clone(), None),
+            executed_by_bot: executor,
+            fee: 0.0, // Seeded history leaves fees out
+        });
+    }
+
+    let bot = bot.map(|(strategy, _, base, stoploss)| {
+        let value: f64 = user
+            .asset_balances
+            .iter()
+            .map(|(asset, balance)| match candles.iter().find(|(a, _)| a == asset) {
+                Some((_, series)) => balance * series[hours - 1].close,
+                None => *balance,
+            })
+            .sum();
+        BotConfigRow {
+            bot_id: uuid::Uuid::new_v4().to_string(),
+            user_id: String::new(),
+            strategy: strategy.to_string(),
+            base_asset: base.to_string(),
+            quote_asset: "USD".to_string(),
+            parameters: serde_json::json!({}),
+            stoploss_amount: (value * stoploss).round(),
+            initial_portfolio_value_usd: value,
+            dry_run: false,
+            trade_limits: Default::default(),
+            max_base_position: None,
+            schedule: None,
+            paused: false,
+        }
+    });
+
+    SeedUser { username: user.username, asset_balances: user.asset_balances, trades: user.trade_history, bot }
+}
+
+/// Price history and accounts for `options`, ending at the hour `now` falls in
+pub fn generate(options: &SeedOptions, starting_balance: f64, now: DateTime<Utc>) -> SeedData {
+    let mut rng = Rng::new(options.seed);
+    let end = now.duration_trunc(Duration::seconds(CANDLE_SECS)).unwrap_or(now);
+    let hours = options.days * 24;
+
+    let candles: Vec<(&str, Vec<PriceCandle>)> = SEED_ASSETS
+        .iter()
+        .map(|(asset, price, volatility)| (*asset, price_walk(&mut rng, *price, *volatility, hours, end)))
+        .collect();
+    let users = (0..options.users).map(|i| trade_history(&mut rng, i, starting_balance, &candles)).collect();
+
+    SeedData { candles, users }
+}
+
+/// What `seed` wrote
+#[derive(Debug, Default)]
+pub struct SeedSummary {
+    pub candles: u64,
+    pub users: Vec<String>,   // Created
+    pub skipped: Vec<String>, // Already there, left alone
+    pub trades: usize,
+    pub bots: usize,
+}
+
+/// Store generated data; users whose name is taken are skipped, so seeding twice adds nothing
+pub async fn seed(pool: &DbPool, options: &SeedOptions, starting_balance: f64) -> Result<SeedSummary, String> {
+    if !(1..=MAX_SEED_USERS).contains(&options.users) {
+        return Err(format!("users must be between 1 and {}", MAX_SEED_USERS));
+    }
+    if !(1..=MAX_SEED_DAYS).contains(&options.days) {
+        return Err(format!("days must be between 1 and {}", MAX_SEED_DAYS));
+    }
+    let data = generate(options, starting_balance, Utc::now());
+    let mut summary = SeedSummary::default();
+
+    for (asset, candles) in data.candles {
+        let batch = CandleBatch { received: candles.len(), candles, ..Default::default() };
+        let stored = history_service::store(pool, asset, CANDLE_SECS, batch, "seed").await.map_err(|e| e.to_string())?;
+        summary.candles += stored.inserted;
+    }
+
+    for seeded in data.users {
+        let user_id = auth_service::generate_user_id();
+        match queries::create_user(pool, &user_id, &seeded.username, SEED_PASSWORD, starting_balance).await {
+            Ok(()) => {}
+            Err(AuthError::UserAlreadyExists) => {
+                summary.skipped.push(seeded.username);
+                continue;
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+
+        let trades: Vec<Trade> = seeded.trades.into_iter().map(|trade| Trade { user_id: user_id.clone(), ..trade }).collect();
+        let user = UserData { username: seeded.username.clone(), asset_balances: seeded.asset_balances, trade_history: Vec::new() };
+        queries::record_transactions(pool, &user_id, &user, &trades).await.map_err(|e| e.to_string())?;
+        summary.trades += trades.len();
+
+        if let Some(config) = seeded.bot {
+            queries::save_bot_config(pool, &BotConfigRow { user_id: user_id.clone(), ..config }).await.map_err(|e| e.to_string())?;
+            summary.bots += 1;
+        }
+        summary.users.push(seeded.username);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_is_deterministic_and_never_overspends() {
+        let options = SeedOptions { users: 4, days: 14, seed: 7 };
+        let now = Utc::now();
+        let a = generate(&options, 10_000.0, now);
+        let b = generate(&options, 10_000.0, now);
+
+        assert_eq!(a.candles.len(), SEED_ASSETS.len());
+        for ((_, x), (_, y)) in a.candles.iter().zip(&b.candles) {
+            assert_eq!(x.len(), 14 * 24);
+            assert_eq!(x.last().unwrap().close, y.last().unwrap().close);
+            assert!(x.iter().all(|c| c.high >= c.open.max(c.close) && c.low <= c.open.min(c.close) && c.low > 0.0));
+            assert!(x.iter().all(|c| c.timestamp.timestamp() % CANDLE_SECS == 0 && c.timestamp <= now));
+        }
+
+        assert_eq!(a.users.len(), 4);
+        assert_eq!(a.users[0].username, "alice_demo");
+        assert!(a.users[0].bot.is_some() && a.users[2].bot.is_none());
+        assert!(a.users[0].trades.iter().any(|t| t.executed_by_bot.is_some()));
+        for user in &a.users {
+            assert!(!user.trades.is_empty());
+            assert!(user.trades.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+            assert!(user.asset_balances.values().all(|b| *b > -1e-9));
+        }
+        assert_eq!(a.users[3].trades.len(), b.users[3].trades.len());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::db::{queries, DbPool};
+use crate::models::UserId;
+use axum::http::{header, HeaderMap};
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+use uuid::Uuid;
+
+/// Sessions last 30 days unless `SESSION_TTL_HOURS` says otherwise
+const DEFAULT_SESSION_TTL_HOURS: i64 = 24 * 30;
+
+/// Password reset tokens expire this long after they are mailed
+pub const PASSWORD_RESET_TTL_MINUTES: i64 = 30;
+
+/// A session signed in this recently confirms who is asking where there is no password to re-enter
+pub const REAUTH_WINDOW_MINUTES: i64 = 10;
+
+/// Longest User-Agent kept for display; anything beyond is truncated
+const MAX_USER_AGENT_LEN: usize = 256;
+
+/// A session's last use is written at most this often, not on every request
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+fn session_ttl() -> Duration {
+    let hours = std::env::var("SESSION_TTL_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|h: &i64| *h > 0)
+        .unwrap_or(DEFAULT_SESSION_TTL_HOURS);
+    Duration::hours(hours)
+}
+
+/// A freshly issued session; `token` is shown to the client exactly once
+pub struct IssuedSession {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Client details recorded with a session so users can tell their devices apart
+pub struct DeviceInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl DeviceInfo {
+    pub fn from_request(headers: &HeaderMap, addr: SocketAddr) -> Self {
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect());
+
+        Self {
+            user_agent,
+            ip_address: Some(addr.ip().to_string()),
+        }
+    }
+}
+
+/// Tokens are stored hashed, so a leaked database can't be used to log in
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Token from an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// Start a session for a user who just logged in or signed up
+pub async fn create_session(
+    pool: &DbPool,
+    user_id: &UserId,
+    device: DeviceInfo,
+) -> Result<IssuedSession, sqlx::Error> {
+    // Two v4 UUIDs give 244 random bits
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let now = Utc::now();
+    let session = queries::SessionInfo {
+        id: Uuid::new_v4().to_string(),
+        user_agent: device.user_agent,
+        ip_address: device.ip_address,
+        created_at: now,
+        expires_at: now + session_ttl(),
+        last_used_at: Some(now),
+    };
+
+    queries::create_session(pool, &session, user_id, &hash_token(&token)).await?;
+
+    Ok(IssuedSession {
+        token,
+        expires_at: session.expires_at,
+    })
+}
+
+/// A password reset token for the user, replacing any outstanding one; only its hash is stored
+pub async fn issue_password_reset(pool: &DbPool, user_id: &UserId) -> Result<String, sqlx::Error> {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let now = Utc::now();
+    let expires_at = now + Duration::minutes(PASSWORD_RESET_TTL_MINUTES);
+
+    queries::create_password_reset(pool, user_id, &hash_token(&token), now.timestamp(), expires_at.timestamp())
+        .await?;
+    Ok(token)
+}
+
+/// Owner of an unexpired password reset token; the token can't be used again
+pub async fn redeem_password_reset(pool: &DbPool, token: &str) -> Result<Option<UserId>, sqlx::Error> {
+    queries::take_password_reset(pool, &hash_token(token.trim()), Utc::now().timestamp()).await
+}
+
+/// User owning an unexpired session token
+pub async fn resolve(pool: &DbPool, token: &str) -> Result<Option<UserId>, sqlx::Error> {
+    queries::find_session_user(pool, &hash_token(token), Utc::now().timestamp()).await
+}
+
+/// When an unexpired session token was signed in
+pub async fn signed_in_at(pool: &DbPool, token: &str) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let created_at = queries::find_session_created_at(pool, &hash_token(token), Utc::now().timestamp()).await?;
+    Ok(created_at.and_then(|secs| DateTime::from_timestamp(secs, 0)))
+}
+
+/// Record that a session token was presented, in the background so the request doesn't wait on it
+pub fn touch(pool: &DbPool, token: &str) {
+    let pool = pool.clone();
+    let token_hash = hash_token(token);
+    let now = Utc::now().timestamp();
+
+    tokio::spawn(async move {
+        if let Err(e) = queries::touch_session(&pool, &token_hash, now, now - LAST_USED_RESOLUTION_SECS).await {
+            tracing::error!("Failed to record session use: {}", e);
+        }
+    });
+}
+
+/// Active sessions of a user, each flagged if it is the one making the request
+pub async fn list(
+    pool: &DbPool,
+    user_id: &UserId,
+    current_token: Option<&str>,
+) -> Result<Vec<(queries::SessionInfo, bool)>, sqlx::Error> {
+    let current_hash = current_token.map(hash_token);
+    let sessions = queries::list_sessions(pool, user_id, Utc::now().timestamp()).await?;
+
+    Ok(sessions
+        .into_iter()
+        .map(|(session, token_hash)| {
+            let current = current_hash.as_deref() == Some(token_hash.as_str());
+            (session, current)
+        })
+        .collect())
+}
+
+/// End the session a token belongs to (logout)
+pub async fn revoke_token(pool: &DbPool, token: &str) -> Result<bool, sqlx::Error> {
+    queries::delete_session_by_token(pool, &hash_token(token)).await
+}
+
+/// Revoke all of a user's sessions except the one making the request (all of them without a token)
+pub async fn revoke_others(pool: &DbPool, user_id: &UserId, current_token: Option<&str>) -> Result<u64, sqlx::Error> {
+    match current_token {
+        Some(token) => queries::delete_other_sessions(pool, user_id, &hash_token(token)).await,
+        None => queries::delete_sessions_for_user(pool, user_id).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_hash_token_is_stable_and_hides_token() {
+        let hash = hash_token("abc");
+        assert_eq!(hash, hash_token("abc"));
+        assert_ne!(hash, hash_token("abd"));
+        assert_eq!(hash.len(), 64);
+        assert!(!hash.contains("abc"));
+    }
+
+    #[test]
+    fn test_bearer_token_parsing() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer tok123"));
+        assert_eq!(bearer_token(&headers), Some("tok123"));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic dXNlcjpwYXNz"));
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer "));
+        assert_eq!(bearer_token(&headers), None);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::services::notification_service::{Routing, ROUTING_SETTING};
+use crate::services::summary_service::{Schedule, SUMMARY_SETTING};
+use crate::services::timezone_service::{self, TIMEZONE_SETTING};
+use serde_json::{Map, Value};
+
+/// Settings a user may store
+pub const MAX_SETTINGS: usize = 50;
+/// Serialized size of a user's whole settings object
+pub const MAX_SETTINGS_BYTES: usize = 16 * 1024;
+const MAX_KEY_LEN: usize = 50;
+
+/// Keys are lowercase snake_case, e.g. "chart_indicators"
+fn valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Merge `patch` into `settings`: each key replaces the stored value, and null removes it
+/// Returns the keys that changed, or why the patch was rejected (leaving `settings` untouched)
+pub fn apply_patch(settings: &mut Map<String, Value>, patch: Map<String, Value>) -> Result<Vec<String>, String> {
+    if let Some(key) = patch.keys().find(|key| !valid_key(key)) {
+        return Err(format!(
+            "Invalid setting name: {} (use 1 to {} lowercase letters, digits and underscores)",
+            key, MAX_KEY_LEN
+        ));
+    }
+
+    if let Some(routing) = patch.get(ROUTING_SETTING).filter(|value| !value.is_null()) {
+        Routing::parse(routing).map_err(|e| format!("Invalid {}: {}", ROUTING_SETTING, e))?;
+    }
+    if let Some(schedule) = patch.get(SUMMARY_SETTING).filter(|value| !value.is_null()) {
+        Schedule::parse(schedule).map_err(|e| format!("Invalid {}: {}", SUMMARY_SETTING, e))?;
+    }
+    if let Some(timezone) = patch.get(TIMEZONE_SETTING).filter(|value| !value.is_null()) {
+        timezone_service::parse(timezone).map_err(|e| format!("Invalid {}: {}", TIMEZONE_SETTING, e))?;
+    }
+
+    let mut merged = settings.clone();
+    let mut changed = Vec::new();
+    for (key, value) in patch {
+        let previous = if value.is_null() {
+            merged.remove(&key)
+        } else {
+            merged.insert(key.clone(), value.clone())
+        };
+        if previous.unwrap_or(Value::Null) != value {
+            changed.push(key);
+        }
+    }
+
+    if merged.len() > MAX_SETTINGS {
+        return Err(format!("At most {} settings can be stored", MAX_SETTINGS));
+    }
+    if Value::Object(merged.clone()).to_string().len() > MAX_SETTINGS_BYTES {
+        return Err(format!("Settings can take at most {} KB", MAX_SETTINGS_BYTES / 1024));
+    }
+
+    *settings = merged;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_patch_replaces_and_removes_keys() {
+        let mut settings = object(json!({"theme": "light", "chart_indicators": ["sma_20"]}));
+
+        let patch = object(json!({"theme": "dark", "chart_indicators": null, "currency": "USD"}));
+        let mut changed = apply_patch(&mut settings, patch).unwrap();
+        changed.sort();
+        assert_eq!(changed, vec!["chart_indicators", "currency", "theme"]);
+        assert_eq!(Value::Object(settings.clone()), json!({"theme": "dark", "currency": "USD"}));
+
+        // Unchanged values and removing missing keys are not changes
+        let changed = apply_patch(&mut settings, object(json!({"theme": "dark", "missing": null}))).unwrap();
+        assert!(changed.is_empty());
+    }
+
+    #[test]
+    fn test_patch_rejects_bad_keys_and_oversized_settings() {
+        let mut settings = object(json!({"theme": "dark"}));
+
+        assert!(apply_patch(&mut settings, object(json!({"Theme": "light"}))).is_err());
+        assert!(apply_patch(&mut settings, object(json!({"": 1}))).is_err());
+
+        let big = "x".repeat(MAX_SETTINGS_BYTES);
+        assert!(apply_patch(&mut settings, object(json!({"notes": big}))).is_err());
+
+        let many: Map<String, Value> = (0..MAX_SETTINGS).map(|i| (format!("key_{}", i), json!(i))).collect();
+        assert!(apply_patch(&mut settings, many).is_err());
+
+        // Rejected patches leave the settings as they were
+        assert_eq!(Value::Object(settings), json!({"theme": "dark"}));
+    }
+}
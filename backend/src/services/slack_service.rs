@@ -0,0 +1,75 @@
+use crate::services::notification_service::{self, truncate, Level};
+use crate::state::Notification;
+use serde_json::{json, Value};
+
+/// Slack's limits on block text
+const MAX_HEADER_CHARS: usize = 150;
+const MAX_SECTION_CHARS: usize = 3000;
+
+/// Why a URL is not a Slack incoming webhook (https://hooks.slack.com/services/...)
+pub fn validate_url(url: &reqwest::Url) -> Result<(), String> {
+    let path_ok = url.path().starts_with("/services/") || url.path().starts_with("/workflows/");
+    if url.scheme() != "https" || url.host_str() != Some("hooks.slack.com") || !path_ok {
+        return Err("Slack endpoints need an incoming webhook URL (https://hooks.slack.com/services/...)".to_string());
+    }
+    Ok(())
+}
+
+/// Slack treats &, < and > as markup in text
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn emoji(level: Level) -> &'static str {
+    match level {
+        Level::Info => ":information_source:",
+        Level::Success => ":white_check_mark:",
+        Level::Error => ":rotating_light:",
+    }
+}
+
+/// Body of an incoming webhook post: a header, the text, and when it happened
+/// `text` is what Slack shows in desktop and mobile alerts
+pub fn message(notification: &Notification) -> Value {
+    let summary = notification_service::summary(&notification.kind);
+    let title = truncate(&format!("{} {}", emoji(summary.level), summary.title), MAX_HEADER_CHARS);
+    json!({
+        "text": format!("{}: {}", escape(&summary.title), escape(&summary.text)),
+        "blocks": [
+            { "type": "header", "text": { "type": "plain_text", "text": title, "emoji": true } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": truncate(&escape(&summary.text), MAX_SECTION_CHARS) } },
+            {
+                "type": "context",
+                "elements": [{
+                    "type": "mrkdwn",
+                    "text": notification.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                }],
+            },
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::NotificationKind;
+
+    #[test]
+    fn test_message_blocks_escape_the_summary() {
+        let notification = Notification {
+            user_id: "u".to_string(),
+            timestamp: chrono::DateTime::from_timestamp(1_736_000_000, 0).unwrap(),
+            kind: NotificationKind::BotStopped { bot_name: "grid".to_string(), reason: "<b> & co".to_string(), by_user: false },
+        };
+
+        let body = message(&notification);
+        assert_eq!(body["blocks"][0]["text"]["text"], ":rotating_light: Bot stopped: grid");
+        assert_eq!(body["blocks"][1]["text"]["text"], "grid stopped: &lt;b&gt; &amp; co");
+        assert_eq!(body["blocks"][2]["elements"][0]["text"], "2025-01-04 14:13:20 UTC");
+
+        let url = |s: &str| reqwest::Url::parse(s).unwrap();
+        assert!(validate_url(&url("https://hooks.slack.com/services/T0/B0/abc")).is_ok());
+        assert!(validate_url(&url("https://hooks.slack.com.evil.com/services/T0")).is_err());
+        assert!(validate_url(&url("http://hooks.slack.com/services/T0/B0/abc")).is_err());
+    }
+}
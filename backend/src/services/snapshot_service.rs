@@ -0,0 +1,234 @@
+use crate::clock;
+use crate::db::queries;
+use crate::models::{BotId, UserData, UserId};
+use crate::state::{AppState, BotRuntime, PriceStore};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Crash-recovery snapshots, read from the environment
+/// `SNAPSHOT_INTERVAL_SECS` (0 = disabled), `SNAPSHOT_PATH`, `SNAPSHOT_MAX_PRICE_AGE_SECS`
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    pub interval: Option<Duration>,
+    pub path: PathBuf,
+    pub max_price_age: Duration, // Older price data is discarded and backfilled instead
+}
+
+impl SnapshotConfig {
+    pub fn from_env() -> Self {
+        let interval_secs: u64 = std::env::var("SNAPSHOT_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        let path = std::env::var("SNAPSHOT_PATH")
+            .unwrap_or_else(|_| "/app/data/state_snapshot.json".to_string());
+        let max_price_age_secs: u64 = std::env::var("SNAPSHOT_MAX_PRICE_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(900);
+
+        Self {
+            interval: (interval_secs > 0).then(|| Duration::from_secs(interval_secs)),
+            path: PathBuf::from(path),
+            max_price_age: Duration::from_secs(max_price_age_secs),
+        }
+    }
+}
+
+/// A running bot as captured in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotSnapshot {
+    pub strategy: String, // Only resumed if the persisted bot still uses this strategy
+    pub runtime: BotRuntime,
+}
+
+/// Written every interval; borrows the price store so it is serialized under the read lock
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    taken_at: DateTime<Utc>,
+    users: HashMap<UserId, UserData>, // Only users whose DB write hasn't landed yet
+    bots: HashMap<BotId, BotSnapshot>,
+    prices: &'a PriceStore,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    taken_at: DateTime<Utc>,
+    users: HashMap<UserId, UserData>,
+    bots: HashMap<BotId, BotSnapshot>,
+    prices: PriceStore,
+}
+
+/// Serialize the recoverable in-memory state
+/// Each lock is taken on its own, so the parts may be a few milliseconds apart
+async fn take_snapshot(state: &AppState) -> Result<Vec<u8>, serde_json::Error> {
+    let pending: Vec<UserId> = state.pending_writes.lock().await.keys().cloned().collect();
+
+    let users = {
+        let users = state.users.read().await;
+        pending
+            .into_iter()
+            .filter_map(|id| users.get(&id).cloned().map(|user| (id, user)))
+            .collect()
+    };
+
+    let bots = {
+        let bots = state.bots.read().await;
+        bots.iter()
+            .map(|(bot_id, bot)| {
+                let snapshot = BotSnapshot {
+                    strategy: bot.strategy.clone(),
+                    runtime: bot.runtime.clone(),
+                };
+                (bot_id.clone(), snapshot)
+            })
+            .collect()
+    };
+
+    let prices = state.prices.read().await;
+    serde_json::to_vec(&SnapshotRef {
+        taken_at: Utc::now(),
+        users,
+        bots,
+        prices: &prices,
+    })
+}
+
+/// Write a snapshot every `config.interval`
+/// The file is replaced atomically, so a crash mid-write leaves the previous snapshot intact
+pub async fn run_snapshots(state: AppState, config: SnapshotConfig) {
+    let Some(period) = config.interval else {
+        info!("State snapshots disabled (SNAPSHOT_INTERVAL_SECS=0)");
+        return;
+    };
+
+    info!("Writing state snapshots every {:?} to {}", period, config.path.display());
+
+    let tmp_path = config.path.with_extension("tmp");
+    let mut interval = clock::interval(&state.clock, period);
+
+    loop {
+        interval.tick().await;
+
+        let bytes = match take_snapshot(&state).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("Failed to serialize state snapshot: {}", e);
+                continue;
+            }
+        };
+
+        let written = async {
+            tokio::fs::write(&tmp_path, &bytes).await?;
+            tokio::fs::rename(&tmp_path, &config.path).await
+        };
+        if let Err(e) = written.await {
+            error!("Failed to write state snapshot {}: {}", config.path.display(), e);
+        }
+    }
+}
+
+/// A snapshot user may only overwrite the database if the database hasn't moved on since:
+/// trades are written through immediately, so a trade newer than the snapshot means it's stale
+fn should_restore_user(db_user: &UserData, taken_at: DateTime<Utc>) -> bool {
+    db_user.trade_history.iter().all(|t| t.timestamp <= taken_at)
+}
+
+/// Restore the last snapshot (called once at startup, before prices are polled and bots respawn)
+/// Returns the runtime state of bots that were running, for `bot_service::restore_bots`
+pub async fn recover(state: &AppState, config: &SnapshotConfig) -> HashMap<BotId, BotSnapshot> {
+    let bytes = match tokio::fs::read(&config.path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+        Err(e) => {
+            error!("Failed to read state snapshot {}: {}", config.path.display(), e);
+            return HashMap::new();
+        }
+    };
+
+    let snapshot: Snapshot = match serde_json::from_slice(&bytes) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            warn!("Ignoring unreadable state snapshot {}: {}", config.path.display(), e);
+            return HashMap::new();
+        }
+    };
+
+    let age = (Utc::now() - snapshot.taken_at).to_std().unwrap_or_default();
+    info!("Recovering from state snapshot taken {:?} ago", age);
+
+    // With shared state the database is authoritative; other instances kept writing to it
+    if state.cluster.is_none() {
+        restore_users(state, snapshot.users, snapshot.taken_at).await;
+    }
+
+    if age <= config.max_price_age {
+        *state.prices.write().await = snapshot.prices;
+        info!("Restored price history from snapshot");
+    } else {
+        info!("Snapshot prices are too old to reuse, backfilling instead");
+    }
+
+    snapshot.bots
+}
+
+/// Re-apply user changes that were still waiting on the database at the time of the crash
+async fn restore_users(state: &AppState, users: HashMap<UserId, UserData>, taken_at: DateTime<Utc>) {
+    for (user_id, user) in users {
+        let restore = {
+            let users = state.users.read().await;
+            // Users deleted since the snapshot stay deleted
+            users.get(&user_id).is_some_and(|db_user| should_restore_user(db_user, taken_at))
+        };
+        if !restore {
+            continue;
+        }
+
+        if let Err(e) = queries::save_user(state.db.pool(), &user_id, &user).await {
+            error!("Failed to restore user {} from snapshot: {}", user_id, e);
+            continue;
+        }
+        state.users.write().await.insert(user_id.clone(), user);
+        info!("Restored unsaved changes for user {} from snapshot", user_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Trade, TradeSide, TradeSource, TransactionType};
+    use chrono::Duration as ChronoDuration;
+
+    fn user_with_trade_at(timestamp: DateTime<Utc>) -> UserData {
+        let mut user = UserData::new("alice".to_string(), 10_000.0);
+        user.trade_history.push(Trade {
+            user_id: "u1".to_string(),
+            transaction_type: TransactionType::Trade,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            side: TradeSide::Buy,
+            quantity: 1.0,
+            price: 100.0,
+            timestamp,
+            base_usd_price: None,
+            quote_usd_price: None,
+            executed_by_bot: None,
+            source: TradeSource::Manual,
+            fee: 0.0,
+        });
+        user
+    }
+
+    #[test]
+    fn test_snapshot_user_restored_only_if_db_has_no_newer_trades() {
+        let taken_at = Utc::now();
+
+        assert!(should_restore_user(&UserData::new("alice".to_string(), 10_000.0), taken_at));
+        assert!(should_restore_user(&user_with_trade_at(taken_at - ChronoDuration::seconds(10)), taken_at));
+        assert!(!should_restore_user(&user_with_trade_at(taken_at + ChronoDuration::seconds(1)), taken_at));
+    }
+}
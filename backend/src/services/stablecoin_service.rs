@@ -0,0 +1,118 @@
+use crate::models::PricePoint;
+use crate::services::mock_price_service::Rng;
+use crate::state::AppState;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use tracing::info;
+
+/// Chance per tick of a depeg, about once every four days at the default 5s polls
+const DEPEG_CHANCE: f64 = 1.0 / 70_000.0;
+
+/// How far under $1 a depeg knocks the price: 3% plus up to 9% more
+const DEPEG_MIN_DEPTH: f64 = 0.03;
+const DEPEG_EXTRA_DEPTH: f64 = 0.09;
+
+/// Share of the deviation kept each tick: near the peg noise dies out within seconds,
+/// a depeg takes a couple of hours to win back half its loss
+const PEG_REVERSION: f64 = 0.9;
+const DEPEG_REVERSION: f64 = 0.9995;
+
+/// Per-tick noise, about a hundredth of a cent; settles to a spread of a few hundredths around $1
+const PEG_NOISE: f64 = 0.0001;
+
+/// Within this of $1 the coin counts as back on its peg
+const REPEG_THRESHOLD: f64 = 0.002;
+
+/// A stablecoin's USD price: small noise around $1, pulled back to the peg, and now and then a
+/// depeg it recovers from over hours
+pub struct PegSimulator {
+    rng: Rng,
+    deviation: f64, // Price minus $1
+    depegged: bool,
+    depeg_chance: f64,
+}
+
+impl PegSimulator {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: Rng::new(seed), deviation: 0.0, depegged: false, depeg_chance: DEPEG_CHANCE }
+    }
+
+    /// Depeg with this chance per tick instead, e.g. 0 for a coin that never breaks
+    pub fn with_depeg_chance(mut self, chance: f64) -> Self {
+        self.depeg_chance = chance;
+        self
+    }
+
+    /// The next tick's price
+    pub fn next_price(&mut self) -> f64 {
+        if self.rng.unit() < self.depeg_chance {
+            self.deviation = -(DEPEG_MIN_DEPTH + DEPEG_EXTRA_DEPTH * self.rng.unit());
+            self.depegged = true;
+        }
+        let reversion = if self.depegged { DEPEG_REVERSION } else { PEG_REVERSION };
+        self.deviation = self.deviation * reversion + PEG_NOISE * self.rng.normal();
+        if self.deviation.abs() < REPEG_THRESHOLD {
+            self.depegged = false;
+        }
+        1.0 + self.deviation
+    }
+
+    /// Whether a depeg is still playing out
+    pub fn is_depegged(&self) -> bool {
+        self.depegged
+    }
+}
+
+/// A simulator for `asset` seeded from the time, so every start (and every coin) trades differently
+pub fn simulator(asset: &str, now: DateTime<Utc>) -> PegSimulator {
+    let seed = asset.bytes().fold(now.timestamp() as u64, |seed, byte| seed.rotate_left(8) ^ byte as u64);
+    PegSimulator::new(seed)
+}
+
+/// Fill the high-frequency window and 24 hours of 5-minute candles with simulated prices,
+/// for a stablecoin with no history from Coinbase
+pub async fn backfill(state: &AppState, asset: &str, simulator: &mut PegSimulator) {
+    let now = state.clock.now();
+    let point = |timestamp, price| PricePoint { timestamp, asset: asset.to_string(), price, volume: None };
+
+    for i in (1..=288).rev() {
+        state.add_candle(point(now - ChronoDuration::minutes(i * 5), simulator.next_price())).await;
+    }
+    let window = state.price_window_config;
+    for i in (1..=window.points as i64).rev() {
+        state.add_price_point(point(now - ChronoDuration::seconds(i * window.tick_secs), simulator.next_price())).await;
+    }
+    info!("Backfilled {} with simulated peg prices", asset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peg_holds_and_recovers_from_depegs() {
+        let mut steady = PegSimulator::new(7).with_depeg_chance(0.0);
+        let prices: Vec<f64> = (0..50_000).map(|_| steady.next_price()).collect();
+        assert!(prices.iter().all(|p| (p - 1.0).abs() < 0.002));
+        assert!(prices.iter().any(|p| *p != 1.0));
+
+        let mut simulator = PegSimulator::new(7).with_depeg_chance(1.0);
+        let depegged = simulator.next_price();
+        assert!((0.87..0.98).contains(&depegged), "{}", depegged);
+        assert!(simulator.is_depegged());
+
+        // Recovery is gradual, and complete within a day of 5s ticks
+        let mut simulator = simulator.with_depeg_chance(0.0);
+        let after_an_hour = (0..720).map(|_| simulator.next_price()).last().unwrap();
+        assert!(after_an_hour > depegged && after_an_hour < 0.995);
+        for _ in 0..17_280 {
+            simulator.next_price();
+        }
+        assert!(!simulator.is_depegged());
+        assert!((simulator.next_price() - 1.0).abs() < REPEG_THRESHOLD);
+
+        // Same seed, same market
+        let mut a = PegSimulator::new(3);
+        let mut b = PegSimulator::new(3);
+        assert!((0..1000).all(|_| a.next_price() == b.next_price()));
+    }
+}
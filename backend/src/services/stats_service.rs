@@ -0,0 +1,182 @@
+use crate::db::{queries, Database};
+use crate::models::{TransactionType, UserData, UserId};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use utoipa::ToSchema;
+
+/// A feed is unhealthy once its latest price is older than this (it polls every 5s)
+const FEED_STALE_AFTER_SECS: i64 = 60;
+
+/// Trades are bucketed per hour over this many hours
+const TRADE_HISTORY_HOURS: i64 = 24;
+
+/// Platform-wide figures that are safe to show publicly
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PlatformStats {
+    pub total_users: usize,
+    pub active_users_24h: usize, // Users with any transaction in the last 24 hours
+    pub active_bots: usize,
+    pub bots_by_strategy: BTreeMap<String, usize>,
+    pub total_trades: usize,
+    pub total_volume_usd: f64, // Simulated trade volume, at trade-time USD prices
+    pub trades_per_hour: Vec<HourlyTrades>, // Last 24 hours, oldest first
+    pub feeds: Vec<FeedHealth>,
+}
+
+#[derive(Debug, PartialEq, Serialize, ToSchema)]
+pub struct HourlyTrades {
+    pub hour: DateTime<Utc>,
+    pub trades: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedHealth {
+    pub asset: String,
+    pub latest_price: Option<f64>,
+    pub last_update: Option<DateTime<Utc>>,
+    pub age_secs: Option<i64>,
+    pub healthy: bool,
+}
+
+/// Aggregate user activity; bots and feeds are filled in by `platform_stats`
+fn aggregate_users(users: &HashMap<UserId, UserData>, now: DateTime<Utc>) -> PlatformStats {
+    let day_ago = now - Duration::hours(24);
+    let current_hour = now.duration_trunc(Duration::hours(1)).unwrap_or(now);
+    let first_hour = current_hour - Duration::hours(TRADE_HISTORY_HOURS - 1);
+
+    let mut hourly = vec![0usize; TRADE_HISTORY_HOURS as usize];
+    let mut total_trades = 0;
+    let mut active_users_24h = 0;
+    let mut total_volume_usd = 0.0;
+
+    for user in users.values() {
+        if user.trade_history.iter().any(|t| t.timestamp > day_ago) {
+            active_users_24h += 1;
+        }
+        total_volume_usd += user.total_trade_volume_usd();
+
+        for trade in user.trade_history.iter().filter(|t| t.transaction_type == TransactionType::Trade) {
+            total_trades += 1;
+            if trade.timestamp >= first_hour && trade.timestamp <= now {
+                let bucket = (trade.timestamp - first_hour).num_hours() as usize;
+                hourly[bucket] += 1;
+            }
+        }
+    }
+
+    PlatformStats {
+        total_users: users.len(),
+        active_users_24h,
+        active_bots: 0,
+        bots_by_strategy: BTreeMap::new(),
+        total_trades,
+        total_volume_usd,
+        trades_per_hour: hourly
+            .into_iter()
+            .enumerate()
+            .map(|(i, trades)| HourlyTrades {
+                hour: first_hour + Duration::hours(i as i64),
+                trades,
+            })
+            .collect(),
+        feeds: Vec::new(),
+    }
+}
+
+async fn feed_health(state: &AppState, now: DateTime<Utc>) -> Vec<FeedHealth> {
+    let assets = state.polled_assets().await;
+    let mut feeds = Vec::with_capacity(assets.len());
+    for asset in &assets {
+        let latest = state.get_price_window(asset, 1).await.pop();
+        let age_secs = latest.as_ref().map(|p| (now - p.timestamp).num_seconds());
+        feeds.push(FeedHealth {
+            asset: asset.clone(),
+            latest_price: latest.as_ref().map(|p| p.price),
+            last_update: latest.map(|p| p.timestamp),
+            age_secs,
+            healthy: age_secs.is_some_and(|age| age <= FEED_STALE_AFTER_SECS),
+        });
+    }
+    feeds
+}
+
+/// Current platform stats from in-memory state
+/// Each lock is taken on its own, so counts may be a moment apart
+pub async fn platform_stats(state: &AppState) -> PlatformStats {
+    let now = Utc::now();
+
+    let mut stats = aggregate_users(&*state.users.read().await, now);
+
+    {
+        let bots = state.bots.read().await;
+        stats.active_bots = bots.len();
+        for bot in bots.values() {
+            *stats.bots_by_strategy.entry(bot.strategy.clone()).or_default() += 1;
+        }
+    }
+
+    stats.feeds = feed_health(state, now).await;
+    stats
+}
+
+/// On-disk size of the database, if the backend can report it
+pub async fn database_size_bytes(db: &Database) -> Option<i64> {
+    match queries::database_size_bytes(db.pool(), db.backend()).await {
+        Ok(size) => Some(size),
+        Err(e) => {
+            tracing::warn!("Could not read database size: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Trade, TradeSide, TradeSource};
+
+    fn trade(at: DateTime<Utc>, transaction_type: TransactionType) -> Trade {
+        Trade {
+            user_id: "u1".to_string(),
+            transaction_type,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            side: TradeSide::Buy,
+            quantity: 2.0,
+            price: 100.0,
+            timestamp: at,
+            base_usd_price: Some(100.0),
+            quote_usd_price: Some(1.0),
+            executed_by_bot: None,
+            source: TradeSource::Manual,
+            fee: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_buckets_trades_by_hour_and_counts_active_users() {
+        let now = Utc::now().duration_trunc(Duration::hours(1)).unwrap() + Duration::minutes(30);
+
+        let mut active = UserData::new("active".to_string(), 10_000.0);
+        active.trade_history.push(trade(now - Duration::minutes(10), TransactionType::Trade));
+        active.trade_history.push(trade(now - Duration::hours(2), TransactionType::Trade));
+        active.trade_history.push(trade(now - Duration::hours(1), TransactionType::Deposit));
+
+        let mut idle = UserData::new("idle".to_string(), 10_000.0);
+        idle.trade_history.push(trade(now - Duration::days(3), TransactionType::Trade));
+
+        let users = HashMap::from([("a".to_string(), active), ("b".to_string(), idle)]);
+        let stats = aggregate_users(&users, now);
+
+        assert_eq!(stats.total_users, 2);
+        assert_eq!(stats.active_users_24h, 1);
+        assert_eq!(stats.total_trades, 3);
+        assert_eq!(stats.total_volume_usd, 600.0);
+        assert_eq!(stats.trades_per_hour.len(), 24);
+        assert_eq!(stats.trades_per_hour[23].trades, 1); // current hour
+        assert_eq!(stats.trades_per_hour[21].trades, 1); // two hours ago
+        assert_eq!(stats.trades_per_hour.iter().map(|h| h.trades).sum::<usize>(), 2);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::bots::risk_managed::TradeLimits;
+use crate::bots::schedule::BotSchedule;
+use crate::bots::{self, BotConfigError};
+use crate::db::queries::BotConfigRow;
+use crate::services::backtest_service::BacktestParams;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Marks a document as a strategy configuration
+pub const FORMAT: &str = "trading_sim.strategy";
+/// Bumped when the format changes in a way older documents can't be read as
+pub const FORMAT_VERSION: u32 = 1;
+
+const MAX_NAME_LENGTH: usize = 100;
+
+/// A bot strategy with its parameters and risk limits, portable between accounts,
+/// live bots and the backtester
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StrategyConfig {
+    pub format: String, // Always "trading_sim.strategy"
+    pub version: u32,   // FORMAT_VERSION when exported
+    #[serde(default)]
+    pub name: String, // Free label for sharing, e.g. "Slow momentum"
+    pub strategy: String, // Bot strategy key, e.g. "naive_momentum"
+    pub base_asset: String,
+    pub quote_asset: String,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub parameters: serde_json::Value, // Every setting, defaults included, once exported or validated
+    pub risk: RiskLimits,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<BotSchedule>, // When live bots may trade; backtests ignore it
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RiskLimits {
+    pub stoploss_amount: f64, // USD; the bot stops once the portfolio has lost this much
+    #[serde(default, skip_serializing_if = "TradeLimits::is_unlimited")]
+    pub trade_limits: TradeLimits, // Live bots only; backtests ignore them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_base_position: Option<f64>, // Live bots only, in the base asset
+}
+
+/// Why a configuration can't be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    Format(String),
+    Bot(BotConfigError),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Format(msg) => write!(f, "{}", msg),
+            ConfigError::Bot(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl StrategyConfig {
+    fn new(strategy: &str, base_asset: &str, quote_asset: &str, parameters: &serde_json::Value, risk: RiskLimits) -> Self {
+        Self {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            name: String::new(),
+            strategy: strategy.to_string(),
+            base_asset: base_asset.to_string(),
+            quote_asset: quote_asset.to_string(),
+            parameters: parameters.clone(),
+            risk,
+            schedule: None,
+        }
+    }
+
+    /// The configuration of a running bot
+    pub fn from_bot(config: &BotConfigRow) -> Result<Self, ConfigError> {
+        let risk = RiskLimits {
+            stoploss_amount: config.stoploss_amount,
+            trade_limits: config.trade_limits,
+            max_base_position: config.max_base_position,
+        };
+        Self {
+            schedule: config.schedule.clone(),
+            ..Self::new(&config.strategy, &config.base_asset, &config.quote_asset, &config.parameters, risk)
+        }
+        .validate()
+    }
+
+    /// The configuration a backtest ran
+    pub fn from_backtest(params: &BacktestParams) -> Result<Self, ConfigError> {
+        let risk = RiskLimits {
+            stoploss_amount: params.stoploss_amount,
+            trade_limits: TradeLimits::default(),
+            max_base_position: None,
+        };
+        Self::new(&params.strategy, &params.base_asset, &params.quote_asset, &params.parameters, risk).validate()
+    }
+
+    /// Check a configuration can be loaded, and normalize it: assets uppercased and every parameter filled in
+    pub fn validate(self) -> Result<Self, ConfigError> {
+        if self.format != FORMAT {
+            return Err(ConfigError::Format(format!("Not a strategy configuration (format must be \"{}\")", FORMAT)));
+        }
+        if self.version == 0 || self.version > FORMAT_VERSION {
+            return Err(ConfigError::Format(format!(
+                "Unsupported version {} (this server reads up to {})",
+                self.version, FORMAT_VERSION
+            )));
+        }
+        if self.name.chars().count() > MAX_NAME_LENGTH {
+            return Err(ConfigError::Format(format!("Name must be at most {} characters", MAX_NAME_LENGTH)));
+        }
+        let base_asset = self.base_asset.trim().to_uppercase();
+        let quote_asset = self.quote_asset.trim().to_uppercase();
+        if base_asset.is_empty() || quote_asset.is_empty() || base_asset == quote_asset {
+            return Err(ConfigError::Format("base_asset and quote_asset must be two different assets".to_string()));
+        }
+        if !self.risk.stoploss_amount.is_finite() || self.risk.stoploss_amount <= 0.0 {
+            return Err(ConfigError::Format("risk.stoploss_amount must be positive".to_string()));
+        }
+        self.risk.trade_limits.validate().map_err(|e| ConfigError::Format(format!("risk.trade_limits: {}", e)))?;
+        if self.risk.max_base_position.is_some_and(|cap| !cap.is_finite() || cap <= 0.0) {
+            return Err(ConfigError::Format("risk.max_base_position must be positive".to_string()));
+        }
+        if let Some(schedule) = &self.schedule {
+            schedule.validate().map_err(|e| ConfigError::Format(format!("schedule: {}", e)))?;
+        }
+        let parameters = bots::resolve_parameters(&self.strategy, &self.parameters).map_err(ConfigError::Bot)?;
+
+        Ok(Self {
+            name: self.name.trim().to_string(),
+            base_asset,
+            quote_asset,
+            parameters,
+            ..self
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_normalizes_and_rejects_foreign_documents() {
+        let document = json!({
+            "format": "trading_sim.strategy",
+            "version": 1,
+            "strategy": "naive_momentum",
+            "base_asset": " btc",
+            "quote_asset": "usd",
+            "parameters": {"trend_ticks": 5},
+            "risk": {"stoploss_amount": 250.0}
+        });
+        let config: StrategyConfig = serde_json::from_value(document.clone()).unwrap();
+        let config = config.validate().unwrap();
+        assert_eq!((config.base_asset.as_str(), config.quote_asset.as_str()), ("BTC", "USD"));
+        assert_eq!(config.parameters, json!({"trend_ticks": 5, "step_pct": 1.0, "cooldown_ticks": 3}));
+        // Validating again changes nothing
+        assert_eq!(config.clone().validate().unwrap(), config);
+
+        let with = |key: &str, value: serde_json::Value| {
+            let mut document = document.clone();
+            document[key] = value;
+            serde_json::from_value::<StrategyConfig>(document).unwrap().validate()
+        };
+        assert!(matches!(with("format", json!("other")), Err(ConfigError::Format(_))));
+        assert!(matches!(with("version", json!(2)), Err(ConfigError::Format(_))));
+        assert!(matches!(with("risk", json!({"stoploss_amount": 0.0})), Err(ConfigError::Format(_))));
+        assert!(matches!(
+            with("risk", json!({"stoploss_amount": 250.0, "trade_limits": {"max_trades_per_hour": 0}})),
+            Err(ConfigError::Format(_))
+        ));
+        assert!(matches!(
+            with("risk", json!({"stoploss_amount": 250.0, "max_base_position": -1.0})),
+            Err(ConfigError::Format(_))
+        ));
+        assert!(matches!(with("strategy", json!("martingale")), Err(ConfigError::Bot(BotConfigError::UnknownStrategy(_)))));
+        assert!(matches!(
+            with("parameters", json!({"trend_ticks": 50})),
+            Err(ConfigError::Bot(BotConfigError::InvalidParameters(_)))
+        ));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::assets;
+use crate::clock;
+use crate::db::queries;
+use crate::models::{Trade, TransactionType, UserData, UserId};
+use crate::services::performance_service::EquitySample;
+use crate::services::portfolio_service::{self, HistoryRange};
+use crate::services::price_service::{self, TickerStats};
+use crate::services::timezone_service::{self, parse_time};
+use crate::state::{AppState, NotificationKind};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Settings key holding a user's [`Schedule`]; no summaries without it
+pub const SUMMARY_SETTING: &str = "daily_summary";
+
+/// How often the job looks for summaries that have come due
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Assets listed as the day's biggest movers
+const MAX_MOVERS: usize = 3;
+
+/// When a user wants their summary, e.g. `{"time": "18:00", "utc_offset_minutes": 60}`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schedule {
+    time: String,                    // Local "HH:MM"
+    utc_offset_minutes: Option<i32>, // The user's timezone when omitted
+}
+
+impl Schedule {
+    /// Parse and check a stored schedule setting
+    pub fn parse(value: &serde_json::Value) -> Result<Self, String> {
+        let schedule: Schedule = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+        parse_time(&schedule.time)?;
+        if let Some(minutes) = schedule.utc_offset_minutes {
+            timezone_service::offset(minutes)?;
+        }
+        Ok(schedule)
+    }
+
+    /// Whether the schedule goes by the user's timezone setting rather than an offset of its own
+    pub fn uses_timezone(&self) -> bool {
+        self.utc_offset_minutes.is_none()
+    }
+
+    /// The local date whose summary is due at `now`: today's, once its time has passed
+    /// `timezone` (minutes east of UTC) applies when the schedule has no offset of its own
+    pub fn due(&self, now: DateTime<Utc>, timezone: i32) -> Option<NaiveDate> {
+        let time: NaiveTime = parse_time(&self.time).ok()?;
+        let offset = self.utc_offset_minutes.unwrap_or(timezone);
+        let local = (now + ChronoDuration::minutes(offset as i64)).naive_utc();
+        (local.time() >= time).then_some(local.date())
+    }
+}
+
+/// One bot's trading over the day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BotDay {
+    pub bot_name: String,
+    pub trades: usize,
+    pub volume_usd: f64,
+}
+
+/// An asset's move over the day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Mover {
+    pub asset: String,
+    pub last: f64,
+    pub change_pct: f64,
+}
+
+/// A user's last 24 hours, USD amounts rounded to cents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DailySummary {
+    pub date: String, // The user's local date, "YYYY-MM-DD"
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub start_value_usd: f64,
+    pub end_value_usd: f64,
+    pub net_deposits_usd: f64, // Deposits less withdrawals, left out of the P&L
+    pub pnl_usd: f64,
+    pub pnl_pct: Option<f64>, // None when the day started with nothing
+    pub trades: usize,        // Manual and bot trades alike
+    pub volume_usd: f64,      // Trades without a USD price are left out
+    pub bots: Vec<BotDay>,    // By bot name
+    pub movers: Vec<Mover>,   // Polled assets, biggest move first
+}
+
+/// Summarize a day from its equity samples (oldest first), the user's transactions and the tickers of the polled assets
+pub fn compile(
+    date: NaiveDate,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    samples: &[EquitySample],
+    transactions: &[Trade],
+    tickers: Vec<TickerStats>,
+) -> DailySummary {
+    let start_value_usd = samples.first().map_or(0.0, |s| s.value);
+    let end_value_usd = samples.last().map_or(0.0, |s| s.value);
+    // A sample's flow came in after the one before it, so the first one's is outside the day
+    let net_deposits_usd: f64 = samples.iter().skip(1).map(|s| s.flow).sum();
+    let pnl_usd = end_value_usd - start_value_usd - net_deposits_usd;
+
+    let trades: Vec<&Trade> = transactions
+        .iter()
+        .filter(|t| t.transaction_type == TransactionType::Trade && t.timestamp > period_start && t.timestamp <= period_end)
+        .collect();
+    let volume = |trade: &Trade| {
+        let usd_price = trade.base_usd_price.or((trade.quote_asset == "USD").then_some(trade.price));
+        usd_price.map_or(0.0, |price| trade.quantity * price)
+    };
+    let mut bots: BTreeMap<&str, BotDay> = BTreeMap::new();
+    for trade in &trades {
+        if let Some(bot_name) = &trade.executed_by_bot {
+            let day = bots.entry(bot_name).or_insert_with(|| BotDay {
+                bot_name: bot_name.clone(),
+                trades: 0,
+                volume_usd: 0.0,
+            });
+            day.trades += 1;
+            day.volume_usd += volume(trade);
+        }
+    }
+
+    let usd = |amount: f64| assets::round(amount, "USD");
+    let mut movers: Vec<Mover> = tickers
+        .into_iter()
+        .map(|t| Mover { last: usd(t.last), asset: t.asset, change_pct: t.change_pct })
+        .collect();
+    movers.sort_by(|a, b| b.change_pct.abs().total_cmp(&a.change_pct.abs()).then(a.asset.cmp(&b.asset)));
+    movers.truncate(MAX_MOVERS);
+
+    DailySummary {
+        date: date.to_string(),
+        period_start,
+        period_end,
+        start_value_usd: usd(start_value_usd),
+        end_value_usd: usd(end_value_usd),
+        net_deposits_usd: usd(net_deposits_usd),
+        pnl_usd: usd(pnl_usd),
+        pnl_pct: (start_value_usd > 0.0).then(|| pnl_usd * 100.0 / start_value_usd),
+        trades: trades.len(),
+        volume_usd: usd(trades.iter().map(|t| volume(t)).sum()),
+        bots: bots
+            .into_values()
+            .map(|day| BotDay { volume_usd: usd(day.volume_usd), ..day })
+            .collect(),
+        movers,
+    }
+}
+
+/// Compile a user's summary for `date` from the last 24 hours
+async fn summarize(state: &AppState, user: &UserData, date: NaiveDate) -> DailySummary {
+    let period_end = state.clock.now();
+    let (_, samples) = portfolio_service::equity(state, user, HistoryRange::Day).await;
+    let mut tickers = Vec::new();
+    for asset in state.polled_assets().await {
+        tickers.extend(price_service::current_ticker(state, &asset).await);
+    }
+    compile(date, period_end - ChronoDuration::hours(24), period_end, &samples, &user.trade_history, tickers)
+}
+
+/// Compile, store and announce a user's summary if it is due and not yet done
+async fn send_if_due(state: &AppState, user_id: &UserId, schedule: &Schedule) -> Result<(), sqlx::Error> {
+    // Only schedules without an offset of their own need the timezone setting
+    let timezone = if schedule.uses_timezone() {
+        timezone_service::user_offset(state, user_id).await.local_minus_utc() / 60
+    } else {
+        0
+    };
+    let Some(date) = schedule.due(state.clock.now(), timezone) else {
+        return Ok(());
+    };
+    let pool = state.db.pool();
+    if queries::has_daily_summary(pool, user_id, &date.to_string()).await? {
+        return Ok(());
+    }
+    let Some(user) = state.get_user(user_id).await else {
+        return Ok(());
+    };
+
+    let summary = summarize(state, &user, date).await;
+    // Another instance may have got there first
+    if queries::insert_daily_summary(pool, user_id, &summary, state.clock.now().timestamp()).await? {
+        state.notify(
+            user_id,
+            NotificationKind::DailySummary {
+                date: summary.date.clone(),
+                end_value_usd: summary.end_value_usd,
+                pnl_usd: summary.pnl_usd,
+                pnl_pct: summary.pnl_pct,
+                trades: summary.trades,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Every minute, compile the summaries that have come due
+pub async fn run_daily_summaries(state: AppState) {
+    let mut interval = clock::interval(&state.clock, Duration::from_secs(CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let schedules = match queries::users_with_setting(state.db.pool(), SUMMARY_SETTING).await {
+            Ok(schedules) => schedules,
+            Err(e) => {
+                tracing::error!("Failed to load daily summary schedules: {}", e);
+                continue;
+            }
+        };
+        for (user_id, value) in schedules {
+            // Checked when saved; a stored value that no longer parses means no summaries
+            let Ok(schedule) = Schedule::parse(&value) else {
+                continue;
+            };
+            if let Err(e) = send_if_due(&state, &user_id, &schedule).await {
+                tracing::error!("Failed to compile daily summary for {}: {}", user_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TradeSide, TradeSource};
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 2, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_schedule_is_due_after_local_time() {
+        let schedule = Schedule::parse(&json!({"time": "18:00", "utc_offset_minutes": -300})).unwrap();
+        let jan_1 = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        assert_eq!(schedule.due(at(22), 0), None); // 17:00 local
+        assert_eq!(schedule.due(at(23), 0), Some(NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()));
+        assert_eq!(schedule.due(at(3), 0), Some(jan_1)); // 22:00 the day before
+        // Without its own offset, the user's timezone applies
+        let in_timezone = Schedule::parse(&json!({"time": "18:00"})).unwrap();
+        assert_eq!(in_timezone.due(at(22), 0), Some(NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()));
+        assert_eq!(in_timezone.due(at(22), -300), None);
+
+        assert!(Schedule::parse(&json!({"time": "25:00"})).is_err());
+        assert!(Schedule::parse(&json!({"time": "08:00", "utc_offset_minutes": 900})).is_err());
+        assert!(Schedule::parse(&json!({"time": "08:00", "zone": "UTC"})).is_err());
+    }
+
+    #[test]
+    fn test_compile_separates_deposits_from_pnl() {
+        let sample = |hour, value, flow| EquitySample { timestamp: at(hour), value, flow, exposed: true, position: 0.0 };
+        // A 500 deposit at 12:00; the rest is gain
+        let samples = [sample(0, 10_000.0, 0.0), sample(12, 10_700.0, 500.0), sample(23, 11_000.0, 0.0)];
+        let trade = |hour, bot: Option<&str>| Trade {
+            user_id: "u1".to_string(),
+            transaction_type: TransactionType::Trade,
+            base_asset: "BTC".to_string(),
+            quote_asset: "USD".to_string(),
+            side: TradeSide::Buy,
+            quantity: 0.1,
+            price: 50_000.0,
+            timestamp: at(hour),
+            base_usd_price: None,
+            quote_usd_price: None,
+            executed_by_bot: bot.map(str::to_string),
+            source: TradeSource::from_executor(bot.map(str::to_string), None),
+            fee: 0.0,
+        };
+        let transactions = [trade(0, None), trade(5, None), trade(6, Some("momentum")), trade(7, Some("momentum"))];
+        let ticker = |asset: &str, change_pct| TickerStats {
+            asset: asset.to_string(),
+            last: 1.0,
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            change: 0.0,
+            change_pct,
+            since: at(0),
+            as_of: at(23),
+        };
+        let tickers = vec![ticker("BTC", 2.0), ticker("ETH", -6.0), ticker("SOL", 4.0), ticker("DOGE", 0.5)];
+
+        let summary = compile(NaiveDate::from_ymd_opt(2025, 1, 2).unwrap(), at(0), at(23), &samples, &transactions, tickers);
+        assert_eq!(summary.net_deposits_usd, 500.0);
+        assert_eq!(summary.pnl_usd, 500.0);
+        assert_eq!(summary.pnl_pct, Some(5.0));
+        // The trade at the period's start belongs to the day before
+        assert_eq!(summary.trades, 3);
+        assert_eq!(summary.volume_usd, 15_000.0);
+        assert_eq!(summary.bots, vec![BotDay { bot_name: "momentum".to_string(), trades: 2, volume_usd: 10_000.0 }]);
+        let movers: Vec<&str> = summary.movers.iter().map(|m| m.asset.as_str()).collect();
+        assert_eq!(movers, vec!["ETH", "SOL", "BTC"]);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::db::queries;
+use crate::state::AppState;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, FixedOffset, NaiveDate, NaiveTime, Offset, Utc};
+
+/// Settings key holding the user's timezone as minutes east of UTC, e.g. `60`
+/// Reports, statements and scheduled summaries go by the local calendar it gives; UTC without it
+pub const TIMEZONE_SETTING: &str = "utc_offset_minutes";
+
+/// Bound on the UTC offsets of local times in settings
+pub const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
+
+/// A local time in settings, "HH:MM"
+pub fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| format!("Invalid time {:?} (use HH:MM)", value))
+}
+
+/// The fixed offset of `minutes` east of UTC, if within ±14 hours
+pub fn offset(minutes: i32) -> Result<FixedOffset, String> {
+    if minutes.abs() > MAX_UTC_OFFSET_MINUTES {
+        return Err("utc_offset_minutes must be within ±14 hours".to_string());
+    }
+    FixedOffset::east_opt(minutes * 60).ok_or_else(|| "Invalid UTC offset".to_string())
+}
+
+/// Parse and check a stored timezone setting
+pub fn parse(value: &serde_json::Value) -> Result<FixedOffset, String> {
+    let minutes = value
+        .as_i64()
+        .and_then(|m| i32::try_from(m).ok())
+        .ok_or_else(|| "Expected whole minutes east of UTC, e.g. 60 or -300".to_string())?;
+    offset(minutes)
+}
+
+/// The user's timezone, UTC if they set none (or it no longer parses)
+pub async fn user_offset(state: &AppState, user_id: &str) -> FixedOffset {
+    let utc = Utc.fix();
+    match queries::get_user_settings(state.db.pool(), &user_id.to_string()).await {
+        Ok(Some((settings, _))) => settings.get(TIMEZONE_SETTING).and_then(|value| parse(value).ok()).unwrap_or(utc),
+        Ok(None) => utc,
+        Err(e) => {
+            tracing::error!("Failed to load timezone for {}: {}", user_id, e);
+            utc
+        }
+    }
+}
+
+/// A calendar period that reports are grouped by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Day,
+    Week, // Monday to Sunday
+    Month,
+}
+
+impl Period {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "day" => Some(Period::Day),
+            "week" => Some(Period::Week),
+            "month" => Some(Period::Month),
+            _ => None,
+        }
+    }
+
+    /// First local date of the period holding `date`
+    fn first_date(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - ChronoDuration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// First local date of the period after the one starting on `first`
+    fn next_date(&self, first: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => first + ChronoDuration::days(1),
+            Period::Week => first + ChronoDuration::days(7),
+            Period::Month => first.checked_add_months(chrono::Months::new(1)).unwrap_or(first),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+
+    /// The period holding `at` on the local calendar: its label ("2025-01-31", ISO week "2025-W05" or "2025-01"),
+    /// and when it starts and ends (local midnights, in UTC)
+    pub fn bounds(&self, at: DateTime<Utc>, offset: FixedOffset) -> (String, DateTime<Utc>, DateTime<Utc>) {
+        let first = self.first_date(at.with_timezone(&offset).date_naive());
+        let midnight = |date: NaiveDate| {
+            (date.and_time(NaiveTime::MIN) - ChronoDuration::seconds(offset.local_minus_utc() as i64)).and_utc()
+        };
+        let label = match self {
+            Period::Day => first.format("%Y-%m-%d").to_string(),
+            Period::Week => first.format("%G-W%V").to_string(),
+            Period::Month => first.format("%Y-%m").to_string(),
+        };
+        (label, midnight(first), midnight(self.next_date(first)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn test_periods_follow_the_local_calendar() {
+        let new_york = parse(&json!(-300)).unwrap();
+        // 03:00 UTC on Feb 1st is still January 31st in New York
+        let at = Utc.with_ymd_and_hms(2025, 2, 1, 3, 0, 0).unwrap();
+
+        let (label, start, end) = Period::Day.bounds(at, new_york);
+        assert_eq!(label, "2025-01-31");
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 1, 31, 5, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 2, 1, 5, 0, 0).unwrap());
+
+        let (label, start, end) = Period::Month.bounds(at, new_york);
+        assert_eq!(label, "2025-01");
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 1, 1, 5, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 2, 1, 5, 0, 0).unwrap());
+
+        let utc = parse(&json!(0)).unwrap();
+        assert_eq!(Period::Month.bounds(at, utc).0, "2025-02");
+        // Saturday Feb 1st is in the week starting Monday Jan 27th
+        let (label, start, end) = Period::Week.bounds(at, utc);
+        assert_eq!(label, "2025-W05");
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 1, 27, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 2, 3, 0, 0, 0).unwrap());
+
+        assert!(parse(&json!(900)).is_err());
+        assert!(parse(&json!("+01:00")).is_err());
+    }
+}
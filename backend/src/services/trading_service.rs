@@ -1,5 +1,9 @@
 use crate::models::*;
-use crate::state::AppState;
+use crate::services::audit_service::{self, AuditAction};
+use crate::services::copy_service;
+use crate::services::fee_service::{self, Liquidity};
+use crate::state::{AppState, PairPriceError, TransactionError};
+use tracing::Instrument;
 
 #[derive(Debug)]
 pub enum TradeError {
@@ -7,10 +11,21 @@ pub enum TradeError {
     InsufficientAssets,
     InvalidQuantity,
     UserNotFound,
-    PriceUnavailable,
+    PriceUnavailable(PairPriceError),
     DepositTooSmall,
     DepositTooLarge,
     WithdrawalExceedsBalance,
+    PersistenceFailed,
+}
+
+impl From<TransactionError> for TradeError {
+    fn from(err: TransactionError) -> Self {
+        match err {
+            TransactionError::UserNotFound => TradeError::UserNotFound,
+            TransactionError::InsufficientBalance(_) => TradeError::InsufficientFunds,
+            TransactionError::Persistence => TradeError::PersistenceFailed,
+        }
+    }
 }
 
 /// Execute a trade for manual (UI) trades
@@ -30,7 +45,7 @@ pub async fn execute_trade(
     let price = state
         .get_pair_price(base_asset, quote_asset)
         .await
-        .ok_or(TradeError::PriceUnavailable)?;
+        .map_err(TradeError::PriceUnavailable)?;
 
     // Capture USD prices at trade time for analytics
     let base_usd_price = if base_asset == "USD" {
@@ -55,12 +70,17 @@ pub async fn execute_trade(
         price,
         base_usd_price,
         quote_usd_price,
-        None, // No bot name for manual trades
+        TradeSource::Manual, // Placed by the user
+        Liquidity::Taker,
     )
     .await
+    .map(|(trade, _)| trade)
 }
 
 /// Internal trade execution with full control (used by bots)
+/// Charges the fee of the user's tier for `liquidity`, on top of a buy's cost or out of a sell's proceeds
+/// Returns the trade with the id of its trades row, None for memory-only users
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn execute_trade_internal(
     state: &AppState,
     user_id: &UserId,
@@ -71,21 +91,23 @@ pub(crate) async fn execute_trade_internal(
     price: f64,
     base_usd_price: Option<f64>,
     quote_usd_price: Option<f64>,
-    executed_by_bot: Option<String>,
-) -> Result<Trade, TradeError> {
+    source: TradeSource,
+    liquidity: Liquidity,
+) -> Result<(Trade, Option<i64>), TradeError> {
     if quantity <= 0.0 {
         return Err(TradeError::InvalidQuantity);
     }
 
     let quote_cost = price * quantity;
 
-    // Check balances first before attempting the trade
+    // Check balances first before attempting the trade; apply_transaction checks again under the lock
     let user = state.get_user(user_id).await.ok_or(TradeError::UserNotFound)?;
+    let fee = quote_cost * fee_service::rate_for(state, user_id, &user, liquidity).await;
 
     match side {
         TradeSide::Buy => {
             let quote_balance = user.get_balance(quote_asset);
-            if quote_balance < quote_cost {
+            if quote_balance < quote_cost + fee {
                 return Err(TradeError::InsufficientFunds);
             }
         }
@@ -106,36 +128,51 @@ pub(crate) async fn execute_trade_internal(
         side: side.clone(),
         quantity,
         price,
-        timestamp: chrono::Utc::now(),
+        timestamp: state.clock.now(),
         base_usd_price,
         quote_usd_price,
-        executed_by_bot,
+        executed_by_bot: source.executor(),
+        source,
+        fee,
     };
 
-    // Execute the trade and record it in history
-    state
-        .update_user(user_id, |user| {
-            match side {
-                TradeSide::Buy => {
-                    // Deduct quote asset
-                    *user.asset_balances.entry(quote_asset.to_string()).or_insert(0.0) -= quote_cost;
-                    // Add base asset
-                    *user.asset_balances.entry(base_asset.to_string()).or_insert(0.0) += quantity;
-                }
-                TradeSide::Sell => {
-                    // Deduct base asset
-                    *user.asset_balances.entry(base_asset.to_string()).or_insert(0.0) -= quantity;
-                    // Add quote asset
-                    *user.asset_balances.entry(quote_asset.to_string()).or_insert(0.0) += quote_cost;
-                }
-            }
-            // Add trade to history
+    // Post the trade's legs and record it in history (persisted before returning)
+    let trade_id = state
+        .apply_transaction(user_id, &trade, |user| {
             user.trade_history.push(trade.clone());
         })
         .await
-        .map_err(|_| TradeError::UserNotFound)?;
+        .map_err(|e| match e {
+            TransactionError::InsufficientBalance(asset) if asset == trade.base_asset => TradeError::InsufficientAssets,
+            e => e.into(),
+        })?;
 
-    Ok(trade)
+    audit_service::record(
+        state.db.pool(),
+        Some(user_id),
+        AuditAction::Trade,
+        format!(
+            "{:?} {} {}/{} @ {} fee {}{}",
+            trade.side,
+            trade.quantity,
+            trade.base_asset,
+            trade.quote_asset,
+            trade.price,
+            trade.fee,
+            trade
+                .executed_by_bot
+                .as_ref()
+                .map(|bot| format!(" (bot: {})", bot))
+                .unwrap_or_default()
+        ),
+    );
+
+    // Copy-trading followers get their share without holding up this trade
+    if !copy_service::is_copy(&trade) {
+        tokio::spawn(copy_service::mirror_trade(state.clone(), trade.clone()).in_current_span());
+    }
+
+    Ok((trade, trade_id))
 }
 
 pub async fn deposit(
@@ -159,20 +196,22 @@ pub async fn deposit(
         side: TradeSide::Buy,  // Semantically "buying" USD
         quantity: amount,
         price: 1.0,
-        timestamp: chrono::Utc::now(),
+        timestamp: state.clock.now(),
         base_usd_price: Some(1.0),
         quote_usd_price: Some(1.0),
         executed_by_bot: None,
+        source: TradeSource::Manual,
+        fee: 0.0,
     };
 
-    // Add USD to balance and record transaction
+    // Credit the USD and record the transaction
     state
-        .update_user(user_id, |user| {
-            *user.asset_balances.entry("USD".to_string()).or_insert(0.0) += amount;
+        .apply_transaction(user_id, &transaction, |user| {
             user.trade_history.push(transaction.clone());
         })
-        .await
-        .map_err(|_| TradeError::UserNotFound)?;
+        .await?;
+
+    audit_service::record(state.db.pool(), Some(user_id), AuditAction::Deposit, format!("{:.2} USD", amount));
 
     Ok(transaction)
 }
@@ -203,20 +242,26 @@ pub async fn withdraw(
         side: TradeSide::Sell,  // Semantically "selling" USD
         quantity: amount,
         price: 1.0,
-        timestamp: chrono::Utc::now(),
+        timestamp: state.clock.now(),
         base_usd_price: Some(1.0),
         quote_usd_price: Some(1.0),
         executed_by_bot: None,
+        source: TradeSource::Manual,
+        fee: 0.0,
     };
 
-    // Deduct USD from balance and record transaction
+    // Debit the USD and record the transaction
     state
-        .update_user(user_id, |user| {
-            *user.asset_balances.entry("USD".to_string()).or_insert(0.0) -= amount;
+        .apply_transaction(user_id, &transaction, |user| {
             user.trade_history.push(transaction.clone());
         })
         .await
-        .map_err(|_| TradeError::UserNotFound)?;
+        .map_err(|e| match e {
+            TransactionError::InsufficientBalance(_) => TradeError::WithdrawalExceedsBalance,
+            e => e.into(),
+        })?;
+
+    audit_service::record(state.db.pool(), Some(user_id), AuditAction::Withdrawal, format!("{:.2} USD", amount));
 
     Ok(transaction)
 }
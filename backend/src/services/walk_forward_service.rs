@@ -0,0 +1,210 @@
+use crate::bots;
+use crate::models::PricePoint;
+use crate::services::backtest_service::{self, BacktestEquityPoint, BacktestMetrics, BacktestParams};
+use crate::services::optimizer_service::{self, RankBy, MAX_GRID_COMBINATIONS};
+use crate::services::performance_service::{self, EquitySample, PerformanceMetrics};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// In-sample/out-of-sample windows one analysis may walk through
+pub const MAX_WINDOWS: usize = 20;
+
+/// Backtests one analysis may run (combinations times windows)
+pub const MAX_WALK_FORWARD_BACKTESTS: usize = 10 * MAX_GRID_COMBINATIONS;
+
+/// One step of the walk: where parameters were chosen, and how they then did
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowBounds {
+    pub in_sample_start: DateTime<Utc>,
+    pub out_of_sample_start: DateTime<Utc>, // Also the end of the in-sample window
+    pub out_of_sample_end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WalkForwardWindow {
+    pub in_sample_start: DateTime<Utc>,
+    pub out_of_sample_start: DateTime<Utc>,
+    pub out_of_sample_end: DateTime<Utc>,
+    #[schema(value_type = Object)]
+    pub parameters: Value, // Best in-sample combination
+    pub in_sample_score: Option<f64>, // Its rank_by metric in-sample
+    pub in_sample: BacktestMetrics,
+    pub out_of_sample: BacktestMetrics,
+    pub stopped_reason: Option<String>, // Set if the bot stopped early out of sample
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WalkForwardResult {
+    pub rank_by: RankBy,
+    pub combinations: usize,
+    pub windows: Vec<WalkForwardWindow>, // Oldest first
+    pub skipped_windows: usize,          // Windows without prices on one side
+    pub out_of_sample: PerformanceMetrics, // Over the out-of-sample windows chained together
+    pub out_of_sample_equity: Vec<BacktestEquityPoint>, // Chained, each window starting where the last ended
+    pub efficiency_pct: Option<f64>, // Out-of-sample return per day over in-sample return per day; null unless in-sample gained
+}
+
+/// Rolling windows over `start..end`: `in_sample` long, each followed by `out_of_sample`,
+/// stepping by `out_of_sample` so the out-of-sample windows tile the range after the first in-sample one
+pub fn windows(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    in_sample: Duration,
+    out_of_sample: Duration,
+) -> Result<Vec<WindowBounds>, String> {
+    if in_sample <= Duration::zero() || out_of_sample <= Duration::zero() {
+        return Err("in_sample_days and out_of_sample_days must be positive".to_string());
+    }
+    let mut bounds = Vec::new();
+    let mut in_sample_start = start;
+    while in_sample_start + in_sample + out_of_sample <= end {
+        bounds.push(WindowBounds {
+            in_sample_start,
+            out_of_sample_start: in_sample_start + in_sample,
+            out_of_sample_end: in_sample_start + in_sample + out_of_sample,
+        });
+        if bounds.len() > MAX_WINDOWS {
+            return Err(format!("The range holds over {} windows; lengthen them or shorten the range", MAX_WINDOWS));
+        }
+        in_sample_start += out_of_sample;
+    }
+    if bounds.is_empty() {
+        return Err("The range is shorter than one in-sample and one out-of-sample window".to_string());
+    }
+    Ok(bounds)
+}
+
+/// Prices from `from` up to (not including) `to`
+fn slice(series: &[PricePoint], from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<PricePoint> {
+    let first = series.partition_point(|p| p.timestamp < from);
+    let last = series.partition_point(|p| p.timestamp < to);
+    series[first..last].to_vec()
+}
+
+/// Optimize over each in-sample window, then backtest the winner over the out-of-sample window after it
+/// Only the out-of-sample figures say how the strategy would have done, since it never saw those prices
+pub async fn walk_forward(
+    params: BacktestParams,
+    series: Vec<PricePoint>,
+    grid: &BTreeMap<String, Vec<Value>>,
+    bounds: Vec<WindowBounds>,
+    rank_by: RankBy,
+) -> Result<WalkForwardResult, String> {
+    let combinations = optimizer_service::combinations(&params.parameters, grid)?;
+    let combination_count = combinations.len();
+
+    let mut windows = Vec::new();
+    let mut skipped_windows = 0;
+    let mut samples: Vec<EquitySample> = Vec::new();
+    let mut equity = Vec::new();
+    let mut closed = Vec::new();
+    let mut growth = 1.0; // Chained out-of-sample growth before the current window
+    let (mut in_sample_return, mut out_of_sample_return) = (0.0, 0.0);
+
+    for window in bounds {
+        let in_sample_series = slice(&series, window.in_sample_start, window.out_of_sample_start);
+        let out_of_sample_series = slice(&series, window.out_of_sample_start, window.out_of_sample_end);
+        if in_sample_series.is_empty() || out_of_sample_series.is_empty() {
+            skipped_windows += 1;
+            continue;
+        }
+
+        // Bots keep state between ticks, so every window gets fresh ones
+        let candidates = optimizer_service::build_bots(&params.strategy, params.stoploss_amount, combinations.clone())
+            .map_err(|e| e.to_string())?;
+        let in_sample_params =
+            BacktestParams { start: window.in_sample_start, end: window.out_of_sample_start, ..params.clone() };
+        let optimized =
+            optimizer_service::optimize(in_sample_params, in_sample_series, grid, candidates, rank_by).await?;
+        let Some(best) = optimized.results.into_iter().next() else {
+            return Err("The optimizer returned no results".to_string());
+        };
+
+        let out_of_sample_params = BacktestParams {
+            start: window.out_of_sample_start,
+            end: window.out_of_sample_end,
+            parameters: best.parameters.clone(),
+            ..params.clone()
+        };
+        let mut bot = bots::create_bot(&params.strategy, params.stoploss_amount, &best.parameters)
+            .map_err(|e| e.to_string())?;
+        let (result, window_samples, window_closed) = tokio::task::spawn_blocking(move || {
+            backtest_service::simulate_detailed(bot.as_mut(), &out_of_sample_series, &out_of_sample_params)
+        })
+        .await
+        .map_err(|_| "A backtest crashed".to_string())?;
+
+        // Scale the window onto the chain, so each starts at the value the last one ended with
+        let scale = growth;
+        samples.extend(window_samples.iter().map(|s| EquitySample { value: s.value * scale, position: s.position * scale, ..*s }));
+        equity.extend(result.equity.iter().map(|p| BacktestEquityPoint {
+            timestamp: p.timestamp,
+            value: p.value * scale,
+            hold_value: p.hold_value * scale,
+            position_value: p.position_value * scale,
+        }));
+        closed.extend(window_closed.into_iter().map(|mut t| {
+            t.pnl *= scale;
+            t
+        }));
+        if params.initial_balance > 0.0 {
+            growth *= result.metrics.final_value / params.initial_balance;
+        }
+
+        in_sample_return += best.metrics.performance.total_return_pct;
+        out_of_sample_return += result.metrics.performance.total_return_pct;
+        windows.push(WalkForwardWindow {
+            in_sample_start: window.in_sample_start,
+            out_of_sample_start: window.out_of_sample_start,
+            out_of_sample_end: window.out_of_sample_end,
+            parameters: best.parameters,
+            in_sample_score: best.score,
+            in_sample: best.metrics,
+            out_of_sample: result.metrics,
+            stopped_reason: result.stopped_reason,
+        });
+    }
+
+    // Every window has the same lengths, so the per-day ratio is the ratio of mean returns over the lengths
+    let efficiency_pct = windows.first().and_then(|w| {
+        let in_sample_days = (w.out_of_sample_start - w.in_sample_start).num_seconds() as f64 / 86_400.0;
+        let out_of_sample_days = (w.out_of_sample_end - w.out_of_sample_start).num_seconds() as f64 / 86_400.0;
+        (in_sample_return > 0.0)
+            .then(|| (out_of_sample_return / out_of_sample_days) / (in_sample_return / in_sample_days) * 100.0)
+    });
+
+    Ok(WalkForwardResult {
+        rank_by,
+        combinations: combination_count,
+        windows,
+        skipped_windows,
+        out_of_sample: performance_service::compute(&samples, &closed),
+        out_of_sample_equity: equity,
+        efficiency_pct,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_windows_roll_by_the_out_of_sample_length() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+        let bounds = windows(start, start + Duration::days(10), Duration::days(3), Duration::days(2)).unwrap();
+
+        // 3+2, then stepping 2 days: out of sample 3-5, 5-7, 7-9 (9-11 would overrun)
+        assert_eq!(bounds.len(), 3);
+        assert_eq!(bounds[1].in_sample_start, start + Duration::days(2));
+        assert_eq!(bounds[1].out_of_sample_start, start + Duration::days(5));
+        assert_eq!(bounds[2].out_of_sample_end, start + Duration::days(9));
+        assert!(windows(start, start + Duration::days(4), Duration::days(3), Duration::days(2)).is_err());
+        assert!(windows(start, start + Duration::days(400), Duration::days(3), Duration::days(1)).is_err());
+        assert!(windows(start, start + Duration::days(4), Duration::zero(), Duration::days(2)).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::assets::AssetInfo;
+use crate::models::{Asset, TradeSide};
+use crate::services::session_service;
+use uuid::Uuid;
+
+/// Quote currencies recognized at the end of a run-together ticker, longest first
+const QUOTE_SUFFIXES: &[&str] = &["USDT", "USDC", "USD", "BTC", "ETH"];
+
+/// A fresh webhook secret and the hash stored for it (the secret itself is shown once)
+pub fn issue_secret() -> (String, String) {
+    let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let hash = session_service::hash_token(&secret);
+    (secret, hash)
+}
+
+/// (base, quote) pair for a TradingView ticker, both of them assets in `registry`
+/// Accepts "BTCUSD", "COINBASE:BTCUSD", "BTC-USD", "BTC/USD", "ETHBTC" or a bare "BTC" (quoted in USD)
+/// Stablecoins are assets of their own, so "SOLUSDT" trades SOL against USDT
+pub fn parse_symbol(symbol: &str, registry: &[AssetInfo]) -> Option<(Asset, Asset)> {
+    let symbol = symbol.trim().to_uppercase();
+    // Drop the exchange prefix
+    let ticker = symbol.rsplit(':').next().unwrap_or_default();
+
+    let (base, quote) = match ticker.split_once(['-', '/']) {
+        Some((base, quote)) => (base, quote),
+        None => QUOTE_SUFFIXES
+            .iter()
+            .find_map(|suffix| {
+                ticker
+                    .strip_suffix(suffix)
+                    .filter(|base| !base.is_empty())
+                    .map(|base| (base, *suffix))
+            })
+            .unwrap_or((ticker, "USD")),
+    };
+
+    let known = |s: &str| registry.iter().any(|info| info.asset == s);
+    if !known(base) || !known(quote) || base == quote {
+        return None;
+    }
+    Some((base.to_string(), quote.to_string()))
+}
+
+/// Trade side from an alert's action ("buy"/"sell", any case)
+pub fn parse_action(action: &str) -> Option<TradeSide> {
+    match action.trim().to_lowercase().as_str() {
+        "buy" => Some(TradeSide::Buy),
+        "sell" => Some(TradeSide::Sell),
+        _ => None,
+    }
+}
+
+/// Positive order size; TradingView placeholders may render it as a number or a string
+pub fn parse_size(size: &serde_json::Value) -> Option<f64> {
+    let size = match size {
+        serde_json::Value::Number(n) => n.as_f64()?,
+        serde_json::Value::String(s) => s.trim().parse().ok()?,
+        _ => return None,
+    };
+    (size.is_finite() && size > 0.0).then_some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(base: &str, quote: &str) -> Option<(Asset, Asset)> {
+        Some((base.to_string(), quote.to_string()))
+    }
+
+    #[test]
+    fn test_parse_symbol_forms() {
+        let registry = crate::assets::registry(&[]);
+        let parse = |symbol: &str| parse_symbol(symbol, &registry);
+        assert_eq!(parse("BTCUSD"), pair("BTC", "USD"));
+        assert_eq!(parse("COINBASE:ethusd"), pair("ETH", "USD"));
+        assert_eq!(parse("BINANCE:SOLUSDT"), pair("SOL", "USDT"));
+        assert_eq!(parse("BTC-USDC"), pair("BTC", "USDC"));
+        assert_eq!(parse("BTC-USD"), pair("BTC", "USD"));
+        assert_eq!(parse("ETH/BTC"), pair("ETH", "BTC"));
+        assert_eq!(parse("ETHBTC"), pair("ETH", "BTC"));
+        assert_eq!(parse("BTC"), pair("BTC", "USD"));
+        assert_eq!(parse("USD"), None);
+        assert_eq!(parse("BTC-"), None);
+        assert_eq!(parse(""), None);
+        // Assets outside the registry
+        assert_eq!(parse("PEPEUSD"), None);
+        assert_eq!(parse("BTC-EUR"), None);
+        assert_eq!(parse_symbol("PEPEUSD", &crate::assets::registry(&["PEPE".to_string()])), pair("PEPE", "USD"));
+    }
+
+    #[test]
+    fn test_parse_size_accepts_numbers_and_strings() {
+        assert_eq!(parse_size(&serde_json::json!(0.5)), Some(0.5));
+        assert_eq!(parse_size(&serde_json::json!(" 2 ")), Some(2.0));
+        assert_eq!(parse_size(&serde_json::json!(0)), None);
+        assert_eq!(parse_size(&serde_json::json!("-1")), None);
+        assert_eq!(parse_size(&serde_json::json!("NaN")), None);
+        assert_eq!(parse_size(&serde_json::json!(null)), None);
+        assert!(matches!(parse_action("BUY"), Some(TradeSide::Buy)));
+        assert!(parse_action("hold").is_none());
+    }
+}
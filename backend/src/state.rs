@@ -1,41 +1,474 @@
 use crate::models::*;
+use crate::bots::ExternalSignal;
+use crate::clock::{Clock, SystemClock};
+use crate::config::{Config, PriceConfig};
 use crate::db::Database;
-use std::collections::HashMap;
+use crate::services::backtest_service::BacktestRun;
+use crate::services::cluster_service::Cluster;
+use crate::services::comparison_service::ShadowAccount;
+use crate::services::{invariant_service, ledger_service};
+use crate::services::email_service::Mailer;
+use crate::services::replay_service::ReplayRun;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, Mutex, RwLock};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
 
-const PRICE_WINDOW_SIZE: usize = 17280; // 24h * 60min * 12 (5s intervals) - high frequency
-const CANDLE_WINDOW_SIZE: usize = 288;  // 24h * 12 (5min intervals) - low frequency
-const OHLC_CANDLE_1M_SIZE: usize = 60;  // 1 hour of 1-minute candles for 1h view
-const OHLC_CANDLE_5M_SIZE: usize = 288; // 24 hours of 5-minute candles for 8h/24h views
+// Resolution tiers (per asset): longer ranges are only kept at coarser resolution
+// The 5s tier is sized by PriceWindowConfig
+const CANDLE_WINDOW_SIZE: usize = 288;   // 24h * 12 (5min intervals) - low frequency
+const OHLC_CANDLE_1M_SIZE: usize = 1440; // 24 hours of 1-minute candles (1h view, 24h indicators)
+const OHLC_CANDLE_5M_SIZE: usize = 288;  // 24 hours of 5-minute candles for 8h/24h views
+const OHLC_CANDLE_15M_SIZE: usize = 672; // 7 days of 15-minute candles
+const OHLC_CANDLE_1H_SIZE: usize = 720;  // 30 days of 1-hour candles
+const PRICE_FEED_CAPACITY: usize = 256;  // Live updates buffered per slow subscriber
+const BALANCE_EVENT_CAPACITY: usize = 256;
+const BOT_EVENT_CAPACITY: usize = 256;
+const NOTIFICATION_CAPACITY: usize = 256;
 
+const MIN_PRICE_WINDOW_MINUTES: usize = 5; // Enough points for the longest indicator period
+const MAX_WINDOW_MEMORY_SHARE: u64 = 10;   // Percent of available memory the 5s tier may use
+
+/// Length and spacing of the high-frequency price window (`window_minutes`, `poll_interval_secs`)
+/// Everything that reads raw tick data sizes itself from this instead of its own constants
+#[derive(Debug, Clone, Copy)]
+pub struct PriceWindowConfig {
+    pub points: usize,  // Per asset
+    pub tick_secs: i64, // Polling interval, and the spacing of backfilled points
+}
+
+impl PriceWindowConfig {
+    pub fn new(prices: &PriceConfig) -> Self {
+        let tick_secs = prices.poll_interval_secs as i64;
+        let minutes = prices.window_minutes.max(MIN_PRICE_WINDOW_MINUTES);
+        let requested = minutes * 60 / tick_secs as usize;
+
+        let points = clamp_window_to_memory(
+            requested,
+            available_memory_bytes(),
+            prices.tracked_assets.len(),
+            MIN_PRICE_WINDOW_MINUTES * 60 / tick_secs as usize,
+        );
+        if points < requested {
+            tracing::warn!(
+                "A {} minute price window would not fit in available memory, using {} points per asset",
+                minutes,
+                points
+            );
+        }
+        let config = Self { points, tick_secs };
+        tracing::info!(
+            "Price window: {} points per asset ({} minutes)",
+            points,
+            config.duration().num_minutes()
+        );
+        config
+    }
+
+    /// Time span covered by a full window
+    pub fn duration(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.points as i64 * self.tick_secs)
+    }
+
+    /// Points needed to cover `range`, capped at the window length
+    pub fn points_for(&self, range: chrono::Duration) -> usize {
+        ((range.num_seconds() / self.tick_secs).max(0) as usize).min(self.points)
+    }
+}
+
+/// `MemAvailable` from /proc/meminfo (None where unavailable, e.g. outside Linux)
+fn available_memory_bytes() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|l| l.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Largest window (at most `requested`) whose points for all assets fit in the allowed
+/// share of available memory; never below `min_points`
+fn clamp_window_to_memory(requested: usize, available: Option<u64>, assets: usize, min_points: usize) -> usize {
+    let Some(available) = available else {
+        return requested;
+    };
+
+    // Each point also owns a small heap allocation for its asset name
+    let bytes_per_point = (std::mem::size_of::<PricePoint>() + 16) as u64 * assets.max(1) as u64;
+    let budget = available / 100 * MAX_WINDOW_MEMORY_SHARE;
+
+    requested.min((budget / bytes_per_point) as usize).max(min_points)
+}
+
+/// Append to an asset's series, dropping its oldest entries once `capacity` is exceeded
+fn push_capped<T>(series: &mut HashMap<Asset, VecDeque<T>>, asset: Asset, item: T, capacity: usize) {
+    let window = series.entry(asset).or_default();
+    window.push_back(item);
+    while window.len() > capacity {
+        window.pop_front();
+    }
+}
+
+/// Append a candle, merging it into the newest one if both cover the same period
+/// (a backfilled in-progress candle and the live candle that completes it)
+fn push_candle(series: &mut HashMap<Asset, VecDeque<Candle>>, candle: Candle, capacity: usize) {
+    let window = series.entry(candle.asset.clone()).or_default();
+    if let Some(last) = window.back_mut().filter(|c| c.timestamp == candle.timestamp) {
+        last.high = last.high.max(candle.high);
+        last.low = last.low.min(candle.low);
+        last.close = candle.close;
+        return;
+    }
+    window.push_back(candle);
+    if window.len() > capacity {
+        window.pop_front();
+    }
+}
+
+/// The newest `limit` entries of an asset's series, oldest first
+fn latest<T: Clone>(series: &HashMap<Asset, VecDeque<T>>, asset: &str, limit: usize) -> Vec<T> {
+    series.get(asset).map(|window| latest_of(window, limit)).unwrap_or_default()
+}
+
+fn latest_of<T: Clone>(window: &VecDeque<T>, limit: usize) -> Vec<T> {
+    window.iter().skip(window.len().saturating_sub(limit)).cloned().collect()
+}
+
+/// Key of a directly quoted pair series in the price store, e.g. "ETH-BTC"
+/// (USD-quoted series are keyed by the bare asset)
+pub fn pair_key(base: &str, quote: &str) -> String {
+    format!("{}-{}", base, quote)
+}
+
+/// Why a pair price couldn't be determined
+#[derive(Debug, Clone, PartialEq)]
+pub enum PairPriceError {
+    SameAsset,            // base and quote are the same asset
+    MissingPrice(Asset),  // No usable USD price for this leg yet
+}
+
+impl std::fmt::Display for PairPriceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PairPriceError::SameAsset => write!(f, "Base and quote asset must differ"),
+            PairPriceError::MissingPrice(asset) => write!(f, "No price available for {}", asset),
+        }
+    }
+}
+
+/// Price of base in quote terms from the latest prices returned by `lookup`
+/// A direct pair quote wins; otherwise both legs are converted via USD
+fn resolve_pair_price<F>(base: &str, quote: &str, lookup: F) -> Result<f64, PairPriceError>
+where
+    F: Fn(&str) -> Option<f64>,
+{
+    if base == quote {
+        return Err(PairPriceError::SameAsset);
+    }
+    if let Some(direct) = lookup(&pair_key(base, quote)).filter(|p| *p > 0.0) {
+        return Ok(direct);
+    }
+
+    let usd_price = |asset: &str| {
+        if asset == "USD" {
+            return Ok(1.0);
+        }
+        lookup(asset)
+            .filter(|p| *p > 0.0)
+            .ok_or_else(|| PairPriceError::MissingPrice(asset.to_string()))
+    };
+
+    Ok(usd_price(base)? / usd_price(quote)?)
+}
+
+/// Cross-pair series: each base point divided by the latest quote point at or before it
+/// Both inputs are oldest first; base points with no earlier quote point are skipped
+fn derive_pair_window<'a>(
+    base: &[PricePoint],
+    quote: impl IntoIterator<Item = &'a PricePoint>,
+    key: &str,
+) -> Vec<PricePoint> {
+    let mut quote = quote.into_iter().peekable();
+    let mut last_quote: Option<f64> = None;
+
+    base.iter()
+        .filter_map(|point| {
+            while let Some(q) = quote.next_if(|q| q.timestamp <= point.timestamp) {
+                last_quote = Some(q.price);
+            }
+            let quote_price = last_quote.filter(|p| *p > 0.0)?;
+            Some(PricePoint {
+                timestamp: point.timestamp,
+                asset: key.to_string(),
+                price: point.price / quote_price,
+                volume: point.volume, // Still in base units
+            })
+        })
+        .collect()
+}
+
+/// Users of bot replays are prefixed with this, followed by the replay id
+pub const REPLAY_USER_PREFIX: &str = "replay:";
+
+/// Users kept only in memory: nothing about them is persisted or audited
+/// The demo user resets on restart; replay users live in a replay's sandbox state
+pub fn is_memory_only(user_id: &str) -> bool {
+    user_id == "demo_user" || user_id.starts_with(REPLAY_USER_PREFIX)
+}
+
+/// Shared application state
+/// Users, prices, bots and OAuth flows sit behind separate locks so price ingestion,
+/// trading and bot checks don't block each other. Never hold two of them at once
 #[derive(Clone)]
 pub struct AppState {
-    pub inner: Arc<RwLock<AppStateInner>>,
+    pub users: Arc<RwLock<HashMap<UserId, UserData>>>,
+    pub prices: Arc<RwLock<PriceStore>>,
+    pub bots: Arc<RwLock<HashMap<BotId, BotInstance>>>, // Running bots by instance id, several per user
+    pub shadow_accounts: Arc<RwLock<HashMap<BotId, ShadowAccount>>>, // Virtual sub-accounts of A/B compared bots
+    pub pending_oauth: Arc<Mutex<HashMap<String, PendingOAuth>>>, // OAuth flows awaiting callback
+    pub backtests: Arc<Mutex<HashMap<String, BacktestRun>>>, // Backtest runs by id, running or recently finished
+    pub replays: Arc<Mutex<HashMap<String, ReplayRun>>>, // Bot replays by id, running or recently finished
+    pub pending_writes: Arc<Mutex<HashMap<UserId, usize>>>, // User saves not yet in the DB (in-flight count)
+    pub price_window_config: PriceWindowConfig,
+    pub clock: Arc<dyn Clock>, // Market time: the system clock, or a replay's simulated one
+    pub price_feeds: Arc<Mutex<HashMap<Asset, JoinHandle<()>>>>, // Polling task per asset (see price_service)
+    pub price_feed: broadcast::Sender<PricePoint>, // Live prices as they arrive (not backfill)
+    pub balance_events: broadcast::Sender<BalanceEvent>, // Balance changes of all users
+    pub bot_events: broadcast::Sender<BotEvent>, // Bot activity of all users
+    pub notifications: broadcast::Sender<Notification>, // Things users asked to be told about
+    pub news: Arc<RwLock<Vec<NewsItem>>>, // The last day's headlines, newest first (see news_service)
     pub db: Database,
+    pub cluster: Option<Arc<Cluster>>, // Set when several instances share the database
+    pub mailer: Option<Arc<Mailer>>, // Set when SMTP is configured
+    pub config: Arc<Config>,
 }
 
 /// Bot instance information for a running bot
 pub struct BotInstance {
+    pub user_id: UserId,
     pub bot_name: String,
+    pub strategy: String, // Registry key used to recreate the bot (e.g. "naive_momentum")
     pub trading_pair: (String, String), // (base_asset, quote_asset)
     pub stoploss_amount: f64,
     pub initial_portfolio_value_usd: f64, // Portfolio value when bot started
+    pub dry_run: bool, // Decisions are logged but never traded
+    pub max_base_position: Option<f64>,
+    pub schedule: Option<crate::bots::schedule::BotSchedule>,
+    pub paused: bool, // Paused by its user; the task keeps ticking but doesn't trade
+    pub runtime: BotRuntime, // Updated by the bot task after every tick
+    pub pending_signals: Vec<ExternalSignal>, // Drained into the next tick's context; not snapshotted
+    pub context_failures: u32, // Ticks in a row that failed to assemble a context; reset by the next good one
     pub task_handle: JoinHandle<()>,
 }
 
-pub struct AppStateInner {
-    pub users: HashMap<UserId, UserData>,
-    pub price_window: Vec<PricePoint>,     // High-frequency: 5-second data (last 1-2 hours of real data)
-    pub candle_window: Vec<PricePoint>,    // Low-frequency: 5-minute candles (24 hours of historical data)
-    pub ohlc_candles_1m: Vec<Candle>,      // 1-minute OHLC candles for 1h candlestick view
-    pub ohlc_candles_5m: Vec<Candle>,      // 5-minute OHLC candles for 8h/24h candlestick views
-    pub active_bots: HashMap<UserId, BotInstance>, // One bot per user maximum
+/// Progress of a running bot, carried over a crash via the state snapshot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BotRuntime {
+    pub tick_count: u64,
+    pub strategy_state: Option<serde_json::Value>, // From TradingBot::save_state
+}
+
+/// Why a user's balances changed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceChange {
+    Initial, // Current balances, sent when a stream connects
+    Trade,
+    BotTrade,
+    CopyTrade, // Mirrored from a followed user
+    Deposit,
+    Withdrawal,
+    Reset, // Portfolio reset by an admin
+}
+
+impl BalanceChange {
+    pub fn of(trade: &Trade) -> Self {
+        match trade.transaction_type {
+            TransactionType::Trade if crate::services::copy_service::is_copy(trade) => BalanceChange::CopyTrade,
+            TransactionType::Trade if trade.executed_by_bot.is_some() => BalanceChange::BotTrade,
+            TransactionType::Trade => BalanceChange::Trade,
+            TransactionType::Deposit => BalanceChange::Deposit,
+            TransactionType::Withdrawal => BalanceChange::Withdrawal,
+        }
+    }
+}
+
+/// A user's balances after a change, pushed to their event stream
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceEvent {
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub reason: BalanceChange,
+    pub asset_balances: HashMap<Asset, f64>,
+    pub trade: Option<Trade>, // The transaction that caused the change, if any
+}
+
+/// What a bot did, streamed to its owner's activity feed
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BotActivity {
+    Started { base_asset: Asset, quote_asset: Asset, stoploss_amount: f64, tick_count: u64 },
+    Tick { tick: u64, price: f64, decision: crate::bots::BotDecision },
+    TradeExecuted { tick: u64, price: f64, decision: crate::bots::BotDecision },
+    PositionCapped { tick: u64, requested: f64, allowed: f64, max_base_position: f64 }, // Base quantities; 0 allowed = skipped
+    StoplossHit { reason: String },
+    Error { message: String },
+    Paused,
+    Resumed,
+    Stopped { reason: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BotEvent {
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub bot_instance_id: BotId,
+    pub bot_name: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub activity: BotActivity,
+}
+
+/// Something a user asked to be told about, delivered on their event stream
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NotificationKind {
+    PriceAlert {
+        alert_id: String,
+        asset: Asset,
+        condition: AlertCondition,
+        threshold: f64,
+        price: f64,
+        change_pct: Option<f64>,
+    },
+    CopyTrade {
+        leader_id: UserId,
+        leader_username: String,
+        side: TradeSide,
+        base_asset: Asset,
+        quote_asset: Asset,
+        quantity: f64,
+        price: f64,
+    },
+    OrderFilled {
+        order_id: String,
+        order_type: OrderType,
+        side: TradeSide,
+        base_asset: Asset,
+        quote_asset: Asset,
+        quantity: f64,
+        price: f64,
+    },
+    OrderFailed {
+        order_id: String,
+        order_type: OrderType,
+        side: TradeSide,
+        base_asset: Asset,
+        quote_asset: Asset,
+        quantity: f64,
+        reason: String,
+    },
+    BotStarted {
+        bot_name: String,
+        base_asset: Asset,
+        quote_asset: Asset,
+        stoploss_amount: f64,
+        dry_run: bool,
+    },
+    BotTrade {
+        bot_name: String,
+        base_asset: Asset,
+        quote_asset: Asset,
+        price: f64,
+        decision: crate::bots::BotDecision,
+    },
+    StoplossHit {
+        bot_name: String,
+        reason: String,
+    },
+    BotStopped {
+        bot_name: String,
+        reason: String, // Why the bot stopped: errors, insufficient funds, or the user stopping it
+        by_user: bool,
+    },
+    AchievementEarned {
+        achievement: String, // Catalog id, e.g. "first_trade"
+        title: String,
+        details: String,
+    },
+    DailySummary {
+        date: String, // The user's local date; the full summary is at /api/portfolio/summaries
+        end_value_usd: f64,
+        pnl_usd: f64,
+        pnl_pct: Option<f64>,
+        trades: usize,
+    },
+    Test {
+        message: String, // Sent only to the endpoint being tested
+    },
+}
+
+impl NotificationKind {
+    /// Kinds users can route; `test` goes only to the endpoint being tested
+    pub const ROUTABLE: &'static [&'static str] = &[
+        "price_alert",
+        "copy_trade",
+        "order_filled",
+        "order_failed",
+        "bot_started",
+        "bot_trade",
+        "stoploss_hit",
+        "bot_stopped",
+        "achievement_earned",
+        "daily_summary",
+    ];
+
+    /// The `kind` tag, e.g. "price_alert"
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotificationKind::PriceAlert { .. } => "price_alert",
+            NotificationKind::CopyTrade { .. } => "copy_trade",
+            NotificationKind::OrderFilled { .. } => "order_filled",
+            NotificationKind::OrderFailed { .. } => "order_failed",
+            NotificationKind::BotStarted { .. } => "bot_started",
+            NotificationKind::BotTrade { .. } => "bot_trade",
+            NotificationKind::StoplossHit { .. } => "stoploss_hit",
+            NotificationKind::BotStopped { .. } => "bot_stopped",
+            NotificationKind::AchievementEarned { .. } => "achievement_earned",
+            NotificationKind::DailySummary { .. } => "daily_summary",
+            NotificationKind::Test { .. } => "test",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    #[serde(skip)]
+    pub user_id: UserId,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten)]
+    pub kind: NotificationKind,
+}
+
+/// In-flight OAuth authorization (keyed by the CSRF `state` parameter)
+pub struct PendingOAuth {
+    pub provider: String,
+    pub created_at: std::time::Instant,
+}
+
+/// Price series, kept per asset, oldest first
+#[derive(Default, Serialize, Deserialize)]
+pub struct PriceStore {
+    pub price_window: HashMap<Asset, VecDeque<PricePoint>>, // High-frequency: 5-second data (last 1-2 hours of real data)
+    pub candle_window: HashMap<Asset, VecDeque<PricePoint>>, // Low-frequency: 5-minute candles (24 hours of historical data)
+    pub ohlc_candles_1m: HashMap<Asset, VecDeque<Candle>>, // 1-minute OHLC candles for 1h candlestick view
+    pub ohlc_candles_5m: HashMap<Asset, VecDeque<Candle>>, // 5-minute OHLC candles for 8h/24h candlestick views
+    pub ohlc_candles_15m: HashMap<Asset, VecDeque<Candle>>, // 15-minute OHLC candles for 7d views
+    pub ohlc_candles_1h: HashMap<Asset, VecDeque<Candle>>, // 1-hour OHLC candles for 30d views
 }
 
 impl AppState {
-    pub async fn new(db: Database) -> Self {
+    pub async fn new(db: Database, config: Config) -> Self {
         // Delete demo user from database if it exists (demo user should reset on restart)
         if let Err(e) = crate::db::queries::delete_user(db.pool(), &"demo_user".to_string()).await {
             tracing::debug!("No demo user to delete: {}", e);
@@ -50,197 +483,525 @@ impl AppState {
             });
 
         // Always create fresh demo user in memory only (not persisted)
-        let demo_user = UserData::new("Demo User".to_string());
+        let demo_user = UserData::new("Demo User".to_string(), config.accounts.starting_balance);
         users.insert("demo_user".to_string(), demo_user);
 
         tracing::info!("Initialized with {} authenticated users + demo user", users.len() - 1);
 
         Self {
-            inner: Arc::new(RwLock::new(AppStateInner {
-                users,
-                price_window: Vec::with_capacity(PRICE_WINDOW_SIZE),
-                candle_window: Vec::with_capacity(CANDLE_WINDOW_SIZE),
-                ohlc_candles_1m: Vec::with_capacity(OHLC_CANDLE_1M_SIZE * 2), // BTC + ETH
-                ohlc_candles_5m: Vec::with_capacity(OHLC_CANDLE_5M_SIZE * 2), // BTC + ETH
-                active_bots: HashMap::new(),
-            })),
+            users: Arc::new(RwLock::new(users)),
+            prices: Arc::new(RwLock::new(PriceStore::default())),
+            bots: Arc::new(RwLock::new(HashMap::new())),
+            shadow_accounts: Arc::new(RwLock::new(HashMap::new())),
+            pending_oauth: Arc::new(Mutex::new(HashMap::new())),
+            backtests: Arc::new(Mutex::new(HashMap::new())),
+            replays: Arc::new(Mutex::new(HashMap::new())),
+            pending_writes: Arc::new(Mutex::new(HashMap::new())),
+            price_window_config: PriceWindowConfig::new(&config.prices),
+            clock: Arc::new(SystemClock),
+            price_feeds: Arc::new(Mutex::new(HashMap::new())),
+            price_feed: broadcast::channel(PRICE_FEED_CAPACITY).0,
+            balance_events: broadcast::channel(BALANCE_EVENT_CAPACITY).0,
+            bot_events: broadcast::channel(BOT_EVENT_CAPACITY).0,
+            notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            news: Arc::new(RwLock::new(Vec::new())),
             db,
+            cluster: Cluster::from_env().map(Arc::new),
+            mailer: Mailer::from_env().map(Arc::new),
+            config: Arc::new(config),
         }
     }
 
-    pub async fn add_price_point(&self, point: PricePoint) {
-        let mut state = self.inner.write().await;
-        state.price_window.push(point);
-        
-        // Maintain sliding window (24h)
-        if state.price_window.len() > PRICE_WINDOW_SIZE {
-            state.price_window.remove(0);
+    /// An empty state for a bot replay: its own users, prices, bots and event channels,
+    /// sharing only the database (which memory-only users never write to)
+    /// Runs on `clock`, so the replay decides how fast its bots' time passes
+    pub fn sandbox(&self, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            users: Arc::new(RwLock::new(HashMap::new())),
+            prices: Arc::new(RwLock::new(PriceStore::default())),
+            bots: Arc::new(RwLock::new(HashMap::new())),
+            shadow_accounts: Arc::new(RwLock::new(HashMap::new())),
+            pending_oauth: Arc::new(Mutex::new(HashMap::new())),
+            backtests: Arc::new(Mutex::new(HashMap::new())),
+            replays: Arc::new(Mutex::new(HashMap::new())),
+            pending_writes: Arc::new(Mutex::new(HashMap::new())),
+            price_window_config: self.price_window_config,
+            clock,
+            price_feeds: Arc::new(Mutex::new(HashMap::new())),
+            price_feed: broadcast::channel(PRICE_FEED_CAPACITY).0,
+            balance_events: broadcast::channel(BALANCE_EVENT_CAPACITY).0,
+            bot_events: broadcast::channel(BOT_EVENT_CAPACITY).0,
+            notifications: broadcast::channel(NOTIFICATION_CAPACITY).0,
+            news: Arc::new(RwLock::new(Vec::new())),
+            db: self.db.clone(),
+            cluster: None,
+            mailer: None,
+            config: self.config.clone(),
         }
     }
 
+    pub async fn add_price_point(&self, point: PricePoint) {
+        let mut prices = self.prices.write().await;
+        // Maintain sliding window (older data lives in the candle tiers)
+        push_capped(&mut prices.price_window, point.asset.clone(), point, self.price_window_config.points);
+    }
+
+    /// Record a live price and push it to feed subscribers
+    pub async fn publish_price(&self, point: PricePoint) {
+        self.add_price_point(point.clone()).await;
+        // No receivers is fine: nobody is connected
+        let _ = self.price_feed.send(point);
+    }
+
+    /// Assets currently polled, sorted
+    pub async fn polled_assets(&self) -> Vec<Asset> {
+        let mut assets: Vec<Asset> = self.price_feeds.lock().await.keys().cloned().collect();
+        assets.sort();
+        assets
+    }
+
     pub async fn get_latest_price(&self, asset: &str) -> Option<f64> {
-        let state = self.inner.read().await;
-        state.price_window
-            .iter()
-            .rev()
-            .find(|p| p.asset == asset)
-            .map(|p| p.price)
-    }
-
-    /// Get price for a trading pair (base/quote)
-    /// For USD pairs: returns direct price (e.g., BTC-USD)
-    /// For cross pairs: calculates via USD (e.g., BTC-ETH = BTC-USD / ETH-USD)
-    pub async fn get_pair_price(&self, base: &str, quote: &str) -> Option<f64> {
+        let prices = self.prices.read().await;
+        prices.price_window.get(asset)?.back().map(|p| p.price)
+    }
+
+    /// When the asset last ticked; every candle tier is updated from the same tick
+    pub async fn get_latest_price_time(&self, asset: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        let prices = self.prices.read().await;
+        prices.price_window.get(asset)?.back().map(|p| p.timestamp)
+    }
+
+    /// Get price for a trading pair (base in terms of quote)
+    /// Uses a directly quoted series (e.g. "ETH-BTC") when one is tracked,
+    /// otherwise derives it via USD (BTC/ETH = BTC-USD / ETH-USD)
+    pub async fn get_pair_price(&self, base: &str, quote: &str) -> Result<f64, PairPriceError> {
+        let prices = self.prices.read().await;
+        resolve_pair_price(base, quote, |key| {
+            prices.price_window.get(key)?.back().map(|p| p.price)
+        })
+    }
+
+    /// Get the recent price series for a trading pair, in quote terms
+    /// Derived series pair each base point with the latest quote point at or before it
+    pub async fn get_pair_price_window(
+        &self,
+        base: &str,
+        quote: &str,
+        limit: usize,
+    ) -> Result<Vec<PricePoint>, PairPriceError> {
+        if base == quote {
+            return Err(PairPriceError::SameAsset);
+        }
         if quote == "USD" {
-            // Direct USD price
-            self.get_latest_price(base).await
-        } else if base == "USD" {
-            // Inverted (e.g., USD/BTC = 1 / BTC-USD)
-            self.get_latest_price(quote).await.map(|p| 1.0 / p)
-        } else {
-            // Cross-pair calculation: BTC/ETH = BTC-USD / ETH-USD
-            let base_usd = self.get_latest_price(base).await?;
-            let quote_usd = self.get_latest_price(quote).await?;
-            Some(base_usd / quote_usd)
+            return Ok(self.get_price_window(base, limit).await);
+        }
+
+        let prices = self.prices.read().await;
+        if let Some(direct) = prices.price_window.get(&pair_key(base, quote)) {
+            return Ok(latest_of(direct, limit));
+        }
+
+        let leg = |asset: &str| {
+            prices
+                .price_window
+                .get(asset)
+                .filter(|w| !w.is_empty())
+                .ok_or_else(|| PairPriceError::MissingPrice(asset.to_string()))
+        };
+
+        if base == "USD" {
+            let quote_window = leg(quote)?;
+            return Ok(latest_of(quote_window, limit)
+                .into_iter()
+                .filter(|p| p.price > 0.0)
+                .map(|p| PricePoint { price: 1.0 / p.price, asset: pair_key(base, quote), volume: None, ..p })
+                .collect());
         }
+
+        let base_window = leg(base)?;
+        let quote_window = leg(quote)?;
+        let base_points = latest_of(base_window, limit);
+
+        // Start from the last quote point before the first base point
+        let first = base_points.first().map(|p| p.timestamp);
+        let start = first
+            .map(|t| quote_window.partition_point(|q| q.timestamp <= t).saturating_sub(1))
+            .unwrap_or(0);
+
+        Ok(derive_pair_window(&base_points, quote_window.range(start..), &pair_key(base, quote)))
     }
 
     pub async fn get_price_window(&self, asset: &str, limit: usize) -> Vec<PricePoint> {
-        let state = self.inner.read().await;
-        state.price_window
-            .iter()
-            .filter(|p| p.asset == asset)
-            .rev()
-            .take(limit)
-            .cloned()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev()
-            .collect()
+        let prices = self.prices.read().await;
+        latest(&prices.price_window, asset, limit)
     }
 
     /// Add a 5-minute candle to the candle window (for longer-term data)
     pub async fn add_candle(&self, point: PricePoint) {
-        let mut state = self.inner.write().await;
-        let asset = point.asset.clone();
-        state.candle_window.push(point);
-
-        // Maintain sliding window per asset (24h of 5-minute candles = 288 points per asset)
-        let asset_count = state.candle_window.iter().filter(|p| p.asset == asset).count();
-        if asset_count > CANDLE_WINDOW_SIZE {
-            // Find and remove the oldest candle for this specific asset
-            if let Some(index) = state.candle_window.iter().position(|p| p.asset == asset) {
-                state.candle_window.remove(index);
-            }
-        }
+        let mut prices = self.prices.write().await;
+        // 24h of 5-minute candles = 288 points per asset
+        push_capped(&mut prices.candle_window, point.asset.clone(), point, CANDLE_WINDOW_SIZE);
     }
 
     /// Get 5-minute candles for a specific asset
     pub async fn get_candle_window(&self, asset: &str, limit: usize) -> Vec<PricePoint> {
-        let state = self.inner.read().await;
-        state.candle_window
-            .iter()
-            .filter(|p| p.asset == asset)
-            .rev()
-            .take(limit)
-            .cloned()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev()
-            .collect()
+        let prices = self.prices.read().await;
+        latest(&prices.candle_window, asset, limit)
     }
 
     /// Add 1-minute OHLC candle (for 1h candlestick view)
     pub async fn add_ohlc_candle_1m(&self, candle: Candle) {
-        let mut state = self.inner.write().await;
-        let asset = candle.asset.clone();
-        state.ohlc_candles_1m.push(candle);
-
-        // Maintain window per asset (60 candles per asset)
-        let asset_count = state.ohlc_candles_1m.iter().filter(|c| c.asset == asset).count();
-        if asset_count > OHLC_CANDLE_1M_SIZE {
-            if let Some(index) = state.ohlc_candles_1m.iter().position(|c| c.asset == asset) {
-                state.ohlc_candles_1m.remove(index);
-            }
-        }
+        let mut prices = self.prices.write().await;
+        push_candle(&mut prices.ohlc_candles_1m, candle, OHLC_CANDLE_1M_SIZE);
     }
 
     /// Get 1-minute OHLC candles for a specific asset
     pub async fn get_ohlc_candles_1m(&self, asset: &str, limit: usize) -> Vec<Candle> {
-        let state = self.inner.read().await;
-        state.ohlc_candles_1m
-            .iter()
-            .filter(|c| c.asset == asset)
-            .rev()
-            .take(limit)
-            .cloned()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev()
-            .collect()
+        let prices = self.prices.read().await;
+        latest(&prices.ohlc_candles_1m, asset, limit)
     }
 
     /// Add 5-minute OHLC candle (for 8h/24h candlestick views)
     pub async fn add_ohlc_candle_5m(&self, candle: Candle) {
-        let mut state = self.inner.write().await;
-        let asset = candle.asset.clone();
-        state.ohlc_candles_5m.push(candle);
-
-        // Maintain window per asset (288 candles per asset)
-        let asset_count = state.ohlc_candles_5m.iter().filter(|c| c.asset == asset).count();
-        if asset_count > OHLC_CANDLE_5M_SIZE {
-            if let Some(index) = state.ohlc_candles_5m.iter().position(|c| c.asset == asset) {
-                state.ohlc_candles_5m.remove(index);
-            }
-        }
+        let mut prices = self.prices.write().await;
+        push_candle(&mut prices.ohlc_candles_5m, candle, OHLC_CANDLE_5M_SIZE);
     }
 
     /// Get 5-minute OHLC candles for a specific asset
     pub async fn get_ohlc_candles_5m(&self, asset: &str, limit: usize) -> Vec<Candle> {
-        let state = self.inner.read().await;
-        state.ohlc_candles_5m
-            .iter()
-            .filter(|c| c.asset == asset)
-            .rev()
-            .take(limit)
-            .cloned()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .rev()
-            .collect()
+        let prices = self.prices.read().await;
+        latest(&prices.ohlc_candles_5m, asset, limit)
+    }
+
+    /// Add 15-minute OHLC candle (for 7d views)
+    pub async fn add_ohlc_candle_15m(&self, candle: Candle) {
+        let mut prices = self.prices.write().await;
+        push_candle(&mut prices.ohlc_candles_15m, candle, OHLC_CANDLE_15M_SIZE);
+    }
+
+    /// Get 15-minute OHLC candles for a specific asset
+    pub async fn get_ohlc_candles_15m(&self, asset: &str, limit: usize) -> Vec<Candle> {
+        let prices = self.prices.read().await;
+        latest(&prices.ohlc_candles_15m, asset, limit)
+    }
+
+    /// Add 1-hour OHLC candle (for 30d views)
+    pub async fn add_ohlc_candle_1h(&self, candle: Candle) {
+        let mut prices = self.prices.write().await;
+        push_candle(&mut prices.ohlc_candles_1h, candle, OHLC_CANDLE_1H_SIZE);
+    }
+
+    /// Get 1-hour OHLC candles for a specific asset
+    pub async fn get_ohlc_candles_1h(&self, asset: &str, limit: usize) -> Vec<Candle> {
+        let prices = self.prices.read().await;
+        latest(&prices.ohlc_candles_1h, asset, limit)
+    }
+
+    /// Close prices covering `range`, from the finest tier that reaches that far back:
+    /// 5s ticks within the price window, 1-minute candles up to 24h, 15-minute up to 7 days, then hourly
+    pub async fn get_close_series(&self, asset: &str, range: chrono::Duration) -> Vec<PricePoint> {
+        let cutoff = self.clock.now() - range;
+        let prices = self.prices.read().await;
+
+        if range <= self.price_window_config.duration() {
+            return latest(&prices.price_window, asset, self.price_window_config.points_for(range))
+                .into_iter()
+                .filter(|p| p.timestamp >= cutoff)
+                .collect();
+        }
+
+        let candles = if range <= chrono::Duration::hours(24) {
+            &prices.ohlc_candles_1m
+        } else if range <= chrono::Duration::days(7) {
+            &prices.ohlc_candles_15m
+        } else {
+            &prices.ohlc_candles_1h
+        };
+
+        candles
+            .get(asset)
+            .map(|window| {
+                window
+                    .iter()
+                    .filter(|c| c.timestamp >= cutoff)
+                    .map(|c| PricePoint {
+                        timestamp: c.timestamp,
+                        asset: c.asset.clone(),
+                        price: c.close,
+                        volume: c.volume,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     pub async fn get_user(&self, user_id: &UserId) -> Option<UserData> {
-        let state = self.inner.read().await;
-        state.users.get(user_id).cloned()
+        self.refresh_user(user_id).await;
+        let users = self.users.read().await;
+        users.get(user_id).cloned()
+    }
+
+    /// With shared state, reload a user from the database so changes made by
+    /// other instances (and users created there) are visible here
+    async fn refresh_user(&self, user_id: &UserId) {
+        if self.cluster.is_none() || is_memory_only(user_id) {
+            return;
+        }
+
+        match crate::db::queries::get_user(self.db.pool(), user_id).await {
+            Ok(Some(user)) => {
+                self.users.write().await.insert(user_id.clone(), user);
+            }
+            Ok(None) => {
+                self.users.write().await.remove(user_id);
+            }
+            Err(e) => tracing::error!("Failed to refresh user {}: {}", user_id, e),
+        }
     }
 
     pub async fn update_user<F>(&self, user_id: &UserId, f: F) -> Result<(), String>
     where
         F: FnOnce(&mut UserData),
     {
-        let mut state = self.inner.write().await;
-        match state.users.get_mut(user_id) {
-            Some(user) => {
-                f(user);
-
-                // Persist to database (but NOT demo_user - it's memory-only)
-                if user_id != "demo_user" {
-                    let user_clone = user.clone();
-                    let db_pool = self.db.pool().clone();
-                    let user_id_clone = user_id.clone();
-
-                    // Spawn task to save to DB without blocking
-                    tokio::spawn(async move {
-                        if let Err(e) = crate::db::queries::save_user(&db_pool, &user_id_clone, &user_clone).await {
-                            tracing::error!("Failed to persist user {} to database: {}", user_id_clone, e);
+        self.refresh_user(user_id).await;
+        let user_clone = {
+            let mut users = self.users.write().await;
+            let user = users.get_mut(user_id).ok_or("User not found".to_string())?;
+            f(user);
+            user.clone()
+        };
+
+        // Persist to database (but not memory-only users like demo_user)
+        if !is_memory_only(user_id) {
+            let db_pool = self.db.pool().clone();
+            let user_id_clone = user_id.clone();
+            let pending_writes = self.pending_writes.clone();
+
+            // Until the save lands the user is included in state snapshots
+            *pending_writes.lock().await.entry(user_id.clone()).or_default() += 1;
+
+            // Spawn task to save to DB without blocking (still logged under the caller's span)
+            tokio::spawn(
+                async move {
+                    if let Err(e) = crate::db::queries::save_user(&db_pool, &user_id_clone, &user_clone).await {
+                        // Stays pending so snapshots keep carrying it
+                        tracing::error!("Failed to persist user {} to database: {}", user_id_clone, e);
+                        return;
+                    }
+
+                    let mut pending = pending_writes.lock().await;
+                    if let Some(count) = pending.get_mut(&user_id_clone) {
+                        *count -= 1;
+                        if *count == 0 {
+                            pending.remove(&user_id_clone);
                         }
-                    });
+                    }
                 }
+                .in_current_span(),
+            );
+        }
+
+        Ok(())
+    }
 
-                Ok(())
+    /// Notify the user's event streams of their new balances
+    pub fn emit_balance_event(
+        &self,
+        user_id: &UserId,
+        reason: BalanceChange,
+        asset_balances: HashMap<Asset, f64>,
+        trade: Option<Trade>,
+    ) {
+        // No receivers is fine: nobody is listening
+        let _ = self.balance_events.send(BalanceEvent {
+            user_id: user_id.clone(),
+            reason,
+            asset_balances,
+            trade,
+        });
+    }
+
+    /// Notify the user's bot activity streams
+    pub fn emit_bot_event(&self, user_id: &UserId, bot_id: &BotId, bot_name: &str, activity: BotActivity) {
+        // No receivers is fine: nobody is listening
+        let _ = self.bot_events.send(BotEvent {
+            user_id: user_id.clone(),
+            bot_instance_id: bot_id.clone(),
+            bot_name: bot_name.to_string(),
+            timestamp: self.clock.now(),
+            activity,
+        });
+    }
+
+    /// Notify the user's event streams
+    pub fn notify(&self, user_id: &UserId, kind: NotificationKind) {
+        // No receivers is fine: nobody is listening
+        let _ = self.notifications.send(Notification {
+            user_id: user_id.clone(),
+            timestamp: self.clock.now(),
+            kind,
+        });
+    }
+
+    /// Post a transaction's ledger entries to the user's balances, run `f` for anything else it
+    /// changes (its history entry), and write it through to the database with its trade row
+    /// The write lock is held until the commit so concurrent changes reach the DB in order;
+    /// if the commit fails the in-memory user is restored. Changes that break an accounting
+    /// invariant are reported to invariant_service once they stand
+    /// Debits are checked against the balances under the lock, so two changes racing past their
+    /// callers' checks can't overdraw the account
+    /// Returns the id of the new trades row, None for memory-only users
+    pub async fn apply_transaction<F>(&self, user_id: &UserId, trade: &Trade, f: F) -> Result<Option<i64>, TransactionError>
+    where
+        F: FnOnce(&mut UserData),
+    {
+        self.refresh_user(user_id).await;
+        let mut users = self.users.write().await;
+        let user = users.get_mut(user_id).ok_or(TransactionError::UserNotFound)?;
+
+        let postings = ledger_service::postings(trade);
+        if let Some(asset) = ledger_service::overdrawn(&user.asset_balances, &postings) {
+            return Err(TransactionError::InsufficientBalance(asset));
+        }
+
+        let previous = user.clone();
+        ledger_service::apply(&mut user.asset_balances, &postings);
+        f(user);
+
+        let legs = invariant_service::Legs::of(trade);
+        let violations = invariant_service::check(&previous.asset_balances, &user.asset_balances, &legs);
+
+        // demo_user and replay users are memory-only
+        if is_memory_only(user_id) {
+            invariant_service::report(self, user_id, invariant_service::describe(trade), violations);
+            self.emit_balance_event(user_id, BalanceChange::of(trade), user.asset_balances.clone(), Some(trade.clone()));
+            return Ok(None);
+        }
+
+        let trade_id = match crate::db::queries::record_transaction(self.db.pool(), user_id, user, trade).await {
+            Ok(trade_id) => trade_id,
+            Err(e) => {
+                tracing::error!("Failed to persist transaction for user {}: {}", user_id, e);
+                *user = previous;
+                return Err(TransactionError::Persistence);
+            }
+        };
+
+        // Flagged, not refused: the change is already committed like any other
+        invariant_service::report(self, user_id, invariant_service::describe(trade), violations);
+        self.emit_balance_event(user_id, BalanceChange::of(trade), user.asset_balances.clone(), Some(trade.clone()));
+        Ok(Some(trade_id))
+    }
+
+    /// Replace a user's portfolio with a fresh one at `starting_balance`, clearing their trades
+    /// The ledger records the reset, so balances can still be rebuilt from it
+    pub async fn reset_portfolio(&self, user_id: &UserId, starting_balance: f64) -> Result<(), TransactionError> {
+        self.refresh_user(user_id).await;
+        let mut users = self.users.write().await;
+        let user = users.get_mut(user_id).ok_or(TransactionError::UserNotFound)?;
+
+        let fresh = UserData::new(user.username.clone(), starting_balance);
+        if !is_memory_only(user_id) {
+            let now = self.clock.now();
+            if let Err(e) = crate::db::queries::reset_portfolio(self.db.pool(), user_id, user, &fresh, now).await {
+                tracing::error!("Failed to reset portfolio for user {}: {}", user_id, e);
+                return Err(TransactionError::Persistence);
             }
-            None => Err("User not found".to_string()),
         }
+        *user = fresh;
+
+        self.emit_balance_event(user_id, BalanceChange::Reset, user.asset_balances.clone(), None);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum TransactionError {
+    UserNotFound,
+    InsufficientBalance(Asset), // The asset a debit would have taken below zero; nothing was changed
+    Persistence,                // Already logged; in-memory state was rolled back
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn point(asset: &str, second: u32, price: f64) -> PricePoint {
+        PricePoint {
+            timestamp: Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, second).unwrap(),
+            asset: asset.to_string(),
+            price,
+            volume: None,
+        }
+    }
+
+    fn lookup<'a>(prices: &'a [(&'a str, f64)]) -> impl Fn(&str) -> Option<f64> + 'a {
+        move |key| prices.iter().find(|(k, _)| *k == key).map(|(_, p)| *p)
+    }
+
+    #[test]
+    fn test_price_window_clamped_to_memory() {
+        // No memory information: keep what was asked for
+        assert_eq!(clamp_window_to_memory(720, None, 2, 60), 720);
+        // Plenty of memory
+        assert_eq!(clamp_window_to_memory(720, Some(1 << 30), 2, 60), 720);
+        // A window of millions of points doesn't fit in 64 MiB
+        let clamped = clamp_window_to_memory(10_000_000, Some(64 << 20), 2, 60);
+        assert!(clamped < 10_000_000);
+        assert!(clamped as u64 * 2 * std::mem::size_of::<PricePoint>() as u64 <= (64 << 20) / 10);
+        // But never below the minimum window
+        assert_eq!(clamp_window_to_memory(10_000_000, Some(1024), 2, 60), 60);
+    }
+
+    #[test]
+    fn test_price_window_points_for_range() {
+        let config = PriceWindowConfig { points: 720, tick_secs: 5 };
+        assert_eq!(config.duration(), chrono::Duration::hours(1));
+        assert_eq!(config.points_for(chrono::Duration::minutes(10)), 120);
+        assert_eq!(config.points_for(chrono::Duration::hours(8)), 720);
+    }
+
+    #[test]
+    fn test_pair_price_via_usd() {
+        let prices = [("BTC", 50000.0), ("ETH", 2500.0)];
+        assert_eq!(resolve_pair_price("BTC", "USD", lookup(&prices)), Ok(50000.0));
+        assert_eq!(resolve_pair_price("USD", "ETH", lookup(&prices)), Ok(1.0 / 2500.0));
+        assert_eq!(resolve_pair_price("BTC", "ETH", lookup(&prices)), Ok(20.0));
+    }
+
+    #[test]
+    fn test_pair_price_prefers_direct_quote() {
+        let prices = [("BTC", 50000.0), ("ETH", 2500.0), ("ETH-BTC", 0.051)];
+        assert_eq!(resolve_pair_price("ETH", "BTC", lookup(&prices)), Ok(0.051));
+        assert_eq!(resolve_pair_price("BTC", "ETH", lookup(&prices)), Ok(20.0));
+    }
+
+    #[test]
+    fn test_pair_price_errors() {
+        let prices = [("BTC", 50000.0), ("ETH", 0.0)];
+        assert_eq!(resolve_pair_price("BTC", "BTC", lookup(&prices)), Err(PairPriceError::SameAsset));
+        assert_eq!(
+            resolve_pair_price("BTC", "SOL", lookup(&prices)),
+            Err(PairPriceError::MissingPrice("SOL".to_string()))
+        );
+        assert_eq!(
+            resolve_pair_price("BTC", "ETH", lookup(&prices)),
+            Err(PairPriceError::MissingPrice("ETH".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_derive_pair_window_uses_latest_earlier_quote() {
+        let base = vec![point("BTC", 0, 100.0), point("BTC", 5, 110.0), point("BTC", 10, 120.0)];
+        let quote = vec![point("ETH", 3, 10.0), point("ETH", 8, 20.0)];
+
+        let derived = derive_pair_window(&base, &quote, "BTC-ETH");
+
+        // The first base point has no earlier quote and is skipped
+        assert_eq!(derived.len(), 2);
+        assert_eq!(derived[0].price, 11.0);
+        assert_eq!(derived[1].price, 6.0);
+        assert_eq!(derived[1].asset, "BTC-ETH");
     }
 }
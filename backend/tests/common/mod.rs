@@ -0,0 +1,188 @@
+//! Boots the whole app for a test: an in-memory SQLite database, the real router served on a
+//! local port, the event handlers, and prices from a `MockPriceSource` on a simulated clock
+//! `restart` boots a second app on the same database, as a deploy would
+
+use backend::app;
+use backend::clock::SimulatedClock;
+use backend::config::Config;
+use backend::db::Database;
+use backend::models::BotId;
+use backend::services::bot_service::{self, BOT_TICK_INTERVAL};
+use backend::services::mock_price_service::MockPriceSource;
+use backend::state::AppState;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Real time a test waits for the app to catch up before failing
+const SETTLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+pub struct TestApp {
+    pub state: AppState,
+    pub prices: MockPriceSource, // One tick per bot tick
+    clock: Arc<SimulatedClock>,
+    db_url: String,
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl TestApp {
+    pub async fn start() -> Self {
+        Self::start_with(Config::default()).await
+    }
+
+    /// Start with settings other than the defaults
+    pub async fn start_with(config: Config) -> Self {
+        let (db_url, db) = new_database().await;
+        Self::boot(db_url, db, config, Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()).await
+    }
+
+    /// Start on a database the test has prepared, e.g. one left at an older migration
+    pub async fn start_on(db_url: String, db: Database) -> Self {
+        Self::boot(db_url, db, Config::default(), Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()).await
+    }
+
+    /// Boot a new app on this one's database, where this one left the clock, and restore its
+    /// bots the way startup does; this app stays up but its clock no longer moves
+    pub async fn restart(&self) -> Self {
+        let db = Database::new(&self.db_url).await.expect("in-memory database");
+        let app = Self::boot(self.db_url.clone(), db, (*self.state.config).clone(), self.state.clock.now()).await;
+        bot_service::restore_bots(app.state.clone(), HashMap::new()).await;
+        app
+    }
+
+    /// Migrate, then serve the app on a clock starting at `now`
+    async fn boot(db_url: String, db: Database, config: Config, now: DateTime<Utc>) -> Self {
+        db.run_migrations().await.expect("migrations");
+
+        let clock = Arc::new(SimulatedClock::new(now));
+        let state = AppState {
+            clock: clock.clone(),
+            cluster: None,
+            mailer: None,
+            ..AppState::new(db, config).await
+        };
+        app::spawn_event_handlers(&state);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("local port");
+        let addr = listener.local_addr().expect("bound address");
+        let router = app::router(state.clone());
+        tokio::spawn(async move {
+            axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await
+        });
+
+        let interval = Duration::from_std(BOT_TICK_INTERVAL).expect("bot tick interval");
+        Self {
+            prices: MockPriceSource::new(state.clone(), clock.clone()).with_interval(interval),
+            clock,
+            state,
+            db_url,
+            base_url: format!("http://{}/api", addr),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> (StatusCode, Value) {
+        let response = request.send().await.expect("request reaches the app");
+        let status = response.status();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+
+    pub async fn get(&self, path: &str) -> (StatusCode, Value) {
+        self.send(self.client.get(format!("{}{}", self.base_url, path))).await
+    }
+
+    /// GET with a session token as the bearer
+    pub async fn get_with_session(&self, path: &str, token: &str) -> (StatusCode, Value) {
+        self.send(self.client.get(format!("{}{}", self.base_url, path)).bearer_auth(token)).await
+    }
+
+    pub async fn post(&self, path: &str, body: Value) -> (StatusCode, Value) {
+        self.send(self.client.post(format!("{}{}", self.base_url, path)).json(&body)).await
+    }
+
+    /// POST with a session token as the bearer
+    pub async fn post_with_session(&self, path: &str, token: &str, body: Value) -> (StatusCode, Value) {
+        self.send(self.client.post(format!("{}{}", self.base_url, path)).bearer_auth(token).json(&body)).await
+    }
+
+    pub async fn delete(&self, path: &str, body: Value) -> (StatusCode, Value) {
+        self.send(self.client.delete(format!("{}{}", self.base_url, path)).json(&body)).await
+    }
+
+    /// DELETE with a session token as the bearer
+    pub async fn delete_with_session(&self, path: &str, token: &str, body: Value) -> (StatusCode, Value) {
+        self.send(self.client.delete(format!("{}{}", self.base_url, path)).bearer_auth(token).json(&body)).await
+    }
+
+    /// Sign up and return the new user's id
+    pub async fn signup(&self, username: &str) -> String {
+        self.signup_with_session(username).await.0
+    }
+
+    /// Sign up and return the new user's id and session token
+    pub async fn signup_with_session(&self, username: &str) -> (String, String) {
+        let (status, body) =
+            self.post("/signup", serde_json::json!({ "username": username, "password": "e2e-password" })).await;
+        assert_eq!(status, StatusCode::OK, "signup failed: {}", body);
+        let field = |name: &str| body[name].as_str().unwrap_or_else(|| panic!("{} in {}", name, body)).to_string();
+        (field("user_id"), field("session_token"))
+    }
+
+    /// Publish one bot tick's worth of prices and wait until every running bot has acted on them
+    pub async fn tick(&mut self, prices: &[(&str, f64)]) {
+        let before = self.bot_ticks().await;
+        self.prices.tick(prices).await;
+        self.settle_bots(before).await;
+    }
+
+    /// Move the clock on without publishing prices, as during a gap in the feed
+    pub fn advance(&self, by: Duration) {
+        self.clock.advance(by);
+    }
+
+    /// Wait for bots started since the last tick to make their first, immediate, tick
+    pub async fn wait_for_bots(&self) {
+        self.settle_bots(HashMap::new()).await;
+    }
+
+    /// Each running bot's tick count, by bot instance
+    async fn bot_ticks(&self) -> HashMap<BotId, u64> {
+        let bots = self.state.bots.read().await;
+        bots.iter().map(|(bot_id, bot)| (bot_id.clone(), bot.runtime.tick_count)).collect()
+    }
+
+    /// Until each bot has ticked past `before` (0 for bots missing from it) or stopped
+    async fn settle_bots(&self, before: HashMap<BotId, u64>) {
+        eventually("bots to tick", || async {
+            let bots = self.state.bots.read().await;
+            bots.iter().all(|(bot_id, bot)| bot.runtime.tick_count > before.get(bot_id).copied().unwrap_or(0))
+        })
+        .await;
+    }
+}
+
+/// A fresh in-memory database, not yet migrated, and its URL
+/// The memdb VFS shares a database named with a leading slash between all connections to it
+pub async fn new_database() -> (String, Database) {
+    let url = format!("sqlite:/e2e-{}?vfs=memdb", uuid::Uuid::new_v4());
+    let db = Database::new(&url).await.expect("in-memory database");
+    (url, db)
+}
+
+/// Poll `check` until it holds; panics after `SETTLE_TIMEOUT` of real time
+pub async fn eventually<F, Fut>(what: &str, mut check: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + SETTLE_TIMEOUT;
+    while !check().await {
+        assert!(tokio::time::Instant::now() < deadline, "timed out waiting for {}", what);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+}
@@ -0,0 +1,1053 @@
+mod common;
+
+use backend::config::Config;
+use backend::db::queries;
+use backend::models::{NewsItem, UserData};
+use backend::services::ledger_service;
+use backend::services::maintenance_service::{self, RetentionConfig};
+use backend::services::mock_price_service::ramp;
+use backend::services::session_service::{self, DeviceInfo};
+use backend::db::Database;
+use common::{eventually, new_database, TestApp};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+
+#[tokio::test]
+async fn test_market_trade_fills_at_the_scripted_price() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("trader").await;
+
+    // No price yet, so nothing to trade at
+    let (status, _) = app.post(&format!("/trade?user_id={}", user_id), json!({"asset": "BTC", "side": "Buy", "quantity": 0.1})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    app.tick(&[("BTC", 50_000.0)]).await;
+    let (status, trade) = app.post(&format!("/trade?user_id={}", user_id), json!({"asset": "BTC", "side": "Buy", "quantity": 0.1})).await;
+    assert_eq!(status, StatusCode::OK, "{}", trade);
+    assert_eq!(trade["price"], 50_000.0);
+
+    app.tick(&[("BTC", 60_000.0)]).await;
+    let (status, trade) = app.post(&format!("/trade?user_id={}", user_id), json!({"asset": "BTC", "side": "Sell", "quantity": 0.1})).await;
+    assert_eq!(status, StatusCode::OK, "{}", trade);
+    assert_eq!(trade["price"], 60_000.0);
+
+    let (_, ticker) = app.get("/price/stats?asset=BTC").await;
+    assert_eq!((ticker["open"].as_f64(), ticker["last"].as_f64()), (Some(50_000.0), Some(60_000.0)));
+    assert_eq!(ticker["change_pct"], 20.0);
+
+    // Headlines within the chart's hour, for the asset asked about
+    let now = app.state.clock.now();
+    let headline = |id: &str, asset: &str, minutes_ago: i64| NewsItem {
+        id: id.to_string(),
+        title: format!("{} news", asset),
+        url: format!("https://example.com/{}", id),
+        source: "Example".to_string(),
+        assets: vec![asset.to_string()],
+        published_at: now - chrono::Duration::minutes(minutes_ago),
+    };
+    let items = [headline("a", "BTC", 10), headline("b", "ETH", 5), headline("c", "BTC", 90)];
+    queries::insert_news_items(app.state.db.pool(), &items, now.timestamp()).await.unwrap();
+    let (_, news) = app.get("/news?asset=btc&timeframe=1h").await;
+    assert_eq!(news["headlines"].as_array().map(Vec::len), Some(1), "{}", news);
+    assert_eq!(news["headlines"][0]["timestamp"], (now - chrono::Duration::minutes(10)).timestamp());
+
+    // Both trades pay the Starter tier's 0.60% taker fee
+    let (buy_fee, sell_fee) = (5_000.0 * 0.006, 6_000.0 * 0.006);
+    let (_, portfolio) = app.get(&format!("/portfolio?user_id={}", user_id)).await;
+    assert_eq!(portfolio["asset_balances"]["USD"], 10_000.0 - 5_000.0 - buy_fee + 6_000.0 - sell_fee);
+    assert_eq!(portfolio["asset_balances"]["BTC"], 0.0);
+    let (_, trades) = app.get(&format!("/trades?user_id={}&sort=asc", user_id)).await;
+    let fees: Vec<f64> = trades["items"].as_array().unwrap().iter().map(|t| t["fee"].as_f64().unwrap()).collect();
+    assert_eq!(fees, vec![buy_fee, sell_fee]);
+
+    // The opening USD, then both legs of each trade and its fee
+    let (_, ledger) = app.get(&format!("/ledger?user_id={}&sort=asc", user_id)).await;
+    let entries: Vec<(&str, &str, f64)> = ledger["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["kind"].as_str().unwrap(), e["asset"].as_str().unwrap(), e["amount"].as_f64().unwrap()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            ("opening", "USD", 10_000.0),
+            ("trade", "BTC", 0.1),
+            ("trade", "USD", -5_000.0),
+            ("fee", "USD", -buy_fee),
+            ("trade", "BTC", -0.1),
+            ("trade", "USD", 6_000.0),
+            ("fee", "USD", -sell_fee),
+        ]
+    );
+    let stored = app.state.get_user(&user_id).await.unwrap().asset_balances;
+    let entries = queries::load_ledger(app.state.db.pool(), &user_id).await.unwrap();
+    let reconciliation = ledger_service::reconcile(&entries, &stored);
+    assert!(reconciliation.chain_intact && reconciliation.mismatches.is_empty(), "{:?}", reconciliation);
+}
+
+async fn find_order(app: &TestApp, user_id: &str, order_id: &str) -> Value {
+    let (_, orders) = app.get(&format!("/orders?user_id={}", user_id)).await;
+    orders.as_array().unwrap().iter().find(|o| o["id"] == order_id).cloned().expect("order listed")
+}
+
+#[tokio::test]
+async fn test_limit_order_fills_once_the_price_reaches_it() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("limit_buyer").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    let (status, order) = app
+        .post(
+            &format!("/orders?user_id={}", user_id),
+            json!({"asset": "BTC", "side": "Buy", "order_type": "limit", "quantity": 0.1, "price": 45_000.0}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", order);
+    let order_id = order["id"].as_str().unwrap().to_string();
+
+    // Falling towards the limit, but not there yet
+    for price in ramp(49_000.0, 46_000.0, 4) {
+        app.tick(&[("BTC", price)]).await;
+    }
+    assert_eq!(find_order(&app, &user_id, &order_id).await["status"], "open");
+
+    app.tick(&[("BTC", 45_000.0)]).await;
+    eventually("the order to fill", || async { find_order(&app, &user_id, &order_id).await["status"] == "filled" }).await;
+    assert_eq!(find_order(&app, &user_id, &order_id).await["fill_price"], 45_000.0);
+
+    // A limit that rested on the book pays the 0.40% maker fee
+    let (_, portfolio) = app.get(&format!("/portfolio?user_id={}", user_id)).await;
+    assert_eq!(portfolio["asset_balances"]["USD"], 10_000.0 - 4_500.0 - 4_500.0 * 0.004);
+    assert_eq!(portfolio["asset_balances"]["BTC"], 0.1);
+}
+
+#[tokio::test]
+async fn test_bot_buys_the_trend_and_stops_at_its_stoploss() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("bot_owner").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    // A $1,000 buy on every two rising prices, no cooldown
+    let (status, body) = app
+        .post(
+            "/bot/start",
+            json!({
+                "user_id": user_id,
+                "bot_name": "naive_momentum",
+                "base_asset": "BTC",
+                "quote_asset": "USD",
+                "stoploss_amount": 1_000.0,
+                "parameters": {"trend_ticks": 2, "step_pct": 100.0, "cooldown_ticks": 0},
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot_id = body["bot_instance_id"].as_str().unwrap().to_string();
+    app.wait_for_bots().await;
+
+    for price in ramp(51_000.0, 53_000.0, 3) {
+        app.tick(&[("BTC", price)]).await;
+    }
+    let (_, status) = app.get(&format!("/bot/status?user_id={}&bot_instance_id={}", user_id, bot_id)).await;
+    assert_eq!(status["is_active"], true);
+    assert_eq!(status["bot_instance_id"], bot_id.as_str());
+
+    // The bot sells into the crash, but what it bought has lost more than the stoploss by then
+    app.tick(&[("BTC", 30_000.0)]).await;
+    let (_, status) = app.get(&format!("/bot/status?user_id={}", user_id)).await;
+    assert_eq!(status["is_active"], false);
+
+    let (_, trades) = app.get(&format!("/trades?user_id={}&sort=asc", user_id)).await;
+    let fills: Vec<(String, f64)> = trades["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            assert_eq!(t["executed_by_bot"], "Naive Momentum");
+            assert_eq!(t["source"], json!({"kind": "bot", "instance_id": bot_id, "name": "Naive Momentum"}));
+            (t["side"].as_str().unwrap().to_string(), t["price"].as_f64().unwrap())
+        })
+        .collect();
+    assert_eq!(
+        fills,
+        vec![
+            ("Buy".to_string(), 51_000.0),
+            ("Buy".to_string(), 52_000.0),
+            ("Buy".to_string(), 53_000.0),
+            ("Sell".to_string(), 30_000.0),
+        ]
+    );
+
+    let (_, by_bot) = app.get(&format!("/trades?user_id={}&source=bot&bot_instance_id={}", user_id, bot_id)).await;
+    assert_eq!(by_bot["items"].as_array().unwrap().len(), 4);
+    let (_, manual) = app.get(&format!("/trades?user_id={}&source=manual", user_id)).await;
+    assert!(manual["items"].as_array().unwrap().is_empty());
+
+    // Three $1,000 buys and a $1,000 sell, each paying the 0.60% taker fee
+    let (_, portfolio) = app.get(&format!("/portfolio?user_id={}", user_id)).await;
+    let usd = portfolio["asset_balances"]["USD"].as_f64().unwrap();
+    assert!((usd - (10_000.0 - 3.0 * 1_006.0 + 994.0)).abs() < 1e-6, "{}", usd);
+
+    // The run is closed as a stoploss end
+    let runs = format!("/bot/runs?user_id={}&bot_instance_id={}", user_id, bot_id);
+    eventually("the run to close", || async { !app.get(&runs).await.1["items"][0]["stopped_at"].is_null() }).await;
+    let (_, runs) = app.get(&runs).await;
+    assert_eq!(runs["items"].as_array().map(Vec::len), Some(1));
+    assert_eq!(runs["items"][0]["status"], "stoploss");
+    assert_eq!(runs["items"][0]["errors"], 0);
+
+    // The bot stopped itself, and its bookkeeping outlived its task
+    let pool = app.state.db.pool();
+    eventually("the stop to be audited", || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM audit_log WHERE user_id = $1 AND action = 'bot_stopped'")
+            .bind(&user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+            == 1
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_bot_run_records_its_errors_and_the_user_stop() {
+    let app = TestApp::start().await;
+    let user_id = app.signup("runner").await;
+
+    let (status, body) = app
+        .post(
+            "/bot/start",
+            json!({"user_id": user_id, "bot_name": "naive_momentum", "base_asset": "BTC", "quote_asset": "USD", "stoploss_amount": 1_000.0}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot_id = body["bot_instance_id"].as_str().unwrap().to_string();
+
+    // No BTC price yet: the bot can't assemble its context and retries
+    let runs = format!("/bot/runs?user_id={}&bot_instance_id={}", user_id, bot_id);
+    eventually("the bot's first error", || async { app.get(&runs).await.1["items"][0]["errors"].as_i64() >= Some(1) }).await;
+
+    let (status, body) = app.post(&format!("/bot/stop?user_id={}&bot_instance_id={}", user_id, bot_id), json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    eventually("the run to close", || async { app.get(&runs).await.1["items"][0]["status"] != "running" }).await;
+
+    let (_, runs) = app.get(&runs).await;
+    let run = &runs["items"][0];
+    assert_eq!((run["status"].as_str(), run["stop_reason"].as_str()), (Some("stopped"), Some("stopped by user")));
+    assert!(run["last_error"].as_str().is_some_and(|e| e.contains("BTC/USD")), "{}", run);
+    assert!(!run["stopped_at"].is_null());
+}
+
+#[tokio::test]
+async fn test_paused_bot_holds_its_trades_and_picks_up_where_it_left_off() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("pauser").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    let (status, body) = app
+        .post(
+            "/bot/start",
+            json!({
+                "user_id": user_id,
+                "bot_name": "naive_momentum",
+                "base_asset": "BTC",
+                "quote_asset": "USD",
+                "stoploss_amount": 5_000.0,
+                "parameters": {"trend_ticks": 3, "step_pct": 100.0, "cooldown_ticks": 0},
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot_id = body["bot_instance_id"].as_str().unwrap().to_string();
+    app.wait_for_bots().await;
+
+    let bot = format!("user_id={}&bot_instance_id={}", user_id, bot_id);
+    let (status, body) = app.post(&format!("/bot/pause?{}", bot), json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // Paused, the bot still sees the trend build and decides to buy, but nothing trades
+    for price in ramp(51_000.0, 52_000.0, 2) {
+        app.tick(&[("BTC", price)]).await;
+    }
+    let (_, status) = app.get(&format!("/bot/status?{}", bot)).await;
+    assert_eq!((status["is_active"].as_bool(), status["paused"].as_str()), (Some(true), Some("paused by user")));
+    let decisions = format!("/bot/decisions?{}&sort=asc", bot);
+    eventually("the held-back buy to be logged", || async {
+        app.get(&decisions).await.1["items"].as_array().unwrap().iter().any(|d| d["outcome"] == "paused")
+    })
+    .await;
+    let (_, trades) = app.get(&format!("/trades?user_id={}", user_id)).await;
+    assert!(trades["items"].as_array().unwrap().is_empty(), "{}", trades);
+
+    // Resumed, it still has the prices it saw while paused, so the next rise trades without warming up again
+    let (status, body) = app.post(&format!("/bot/resume?{}", bot), json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, status) = app.get(&format!("/bot/status?{}", bot)).await;
+    assert!(status["paused"].is_null(), "{}", status);
+    app.tick(&[("BTC", 53_000.0)]).await;
+
+    let (_, trades) = app.get(&format!("/trades?user_id={}", user_id)).await;
+    let fills: Vec<(&str, f64)> = trades["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| (t["side"].as_str().unwrap(), t["price"].as_f64().unwrap()))
+        .collect();
+    assert_eq!(fills, vec![("Buy", 53_000.0)]);
+    eventually("the resumed buy to be logged", || async {
+        app.get(&decisions).await.1["items"].as_array().unwrap().iter().any(|d| d["outcome"] == "executed")
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_deleting_an_account_removes_its_archived_trades() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("leaver").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+    let (status, trade) = app.post(&format!("/trade?user_id={}", user_id), json!({"asset": "BTC", "side": "Buy", "quantity": 0.1})).await;
+    assert_eq!(status, StatusCode::OK, "{}", trade);
+
+    let pool = app.state.db.pool();
+    let archived = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM trades_archive WHERE user_id = $1")
+            .bind(&user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    };
+    let cutoff = (app.state.clock.now() + chrono::Duration::days(1)).to_rfc3339();
+    assert_eq!(queries::archive_trades_before(pool, &cutoff).await.unwrap(), 1);
+    assert_eq!(archived().await, 1);
+
+    let (status, body) = app.delete(&format!("/account?user_id={}", user_id), json!({"password": "e2e-password"})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(archived().await, 0);
+}
+
+#[tokio::test]
+async fn test_admin_routes_check_the_session_not_the_user_id() {
+    let app = TestApp::start().await;
+    let (admin_id, admin_token) = app.signup_with_session("boss").await;
+    let (_, other_token) = app.signup_with_session("mallory").await;
+    queries::promote_admins(app.state.db.pool(), &["boss".to_string()]).await.unwrap();
+
+    let users = format!("/admin/users?user_id={}", admin_id);
+    let (status, _) = app.get(&users).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    // Knowing the admin's id is not enough
+    let (status, _) = app.get_with_session(&users, &other_token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) = app.get_with_session(&users, &admin_token).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let usernames: Vec<&str> = body.as_array().unwrap().iter().filter_map(|u| u["username"].as_str()).collect();
+    assert!(usernames.contains(&"boss") && usernames.contains(&"mallory"), "{:?}", usernames);
+}
+
+#[tokio::test]
+async fn test_every_admin_route_turns_away_missing_and_non_admin_sessions() {
+    let app = TestApp::start().await;
+    let (admin_id, admin_token) = app.signup_with_session("boss").await;
+    let (user_id, user_token) = app.signup_with_session("mallory").await;
+    queries::promote_admins(app.state.db.pool(), &["boss".to_string()]).await.unwrap();
+
+    let reads = [
+        "/admin/users",
+        "/admin/stats",
+        "/admin/backup",
+        "/admin/backup?format=json",
+        "/admin/invariants",
+        "/admin/bot-runs",
+        &format!("/admin/ledger?target_user_id={}", admin_id),
+        "/admin/history",
+        "/audit?all=true",
+        &format!("/audit?target_user_id={}", admin_id),
+    ];
+    let writes = [format!("/admin/users/{}/reset", admin_id), format!("/admin/users/{}/stop-bot", admin_id)];
+    // As the caller's own user_id, then claiming the admin's
+    let with_user = |path: &str, user_id: &str| {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        format!("{}{}user_id={}", path, separator, user_id)
+    };
+    for caller in [&user_id, &admin_id] {
+        for path in reads.iter().copied().map(|path| with_user(path, caller)) {
+            assert_eq!(app.get(&path).await.0, StatusCode::UNAUTHORIZED, "{}", path);
+            assert_eq!(app.get_with_session(&path, &user_token).await.0, StatusCode::FORBIDDEN, "{}", path);
+        }
+        for path in writes.iter().map(|path| with_user(path, caller)) {
+            assert_eq!(app.post(&path, json!({})).await.0, StatusCode::UNAUTHORIZED, "{}", path);
+            let (status, _) = app.post_with_session(&path, &user_token, json!({})).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", path);
+        }
+    }
+
+    // Only the admin's own session downloads a backup (as JSON: the in-memory database has no file
+    // to snapshot), and only that download is audited
+    let (status, backup) = app.get_with_session(&format!("/admin/backup?format=json&user_id={}", admin_id), &admin_token).await;
+    assert_eq!(status, StatusCode::OK, "{}", backup);
+    let (status, log) = app.get_with_session(&format!("/audit?all=true&user_id={}", admin_id), &admin_token).await;
+    assert_eq!(status, StatusCode::OK, "{}", log);
+    let exports = log["items"].as_array().unwrap().iter().filter(|e| e["action"] == "backup_exported").count();
+    assert_eq!(exports, 1, "{}", log);
+}
+
+#[tokio::test]
+async fn test_session_routes_only_serve_the_sessions_owner() {
+    let app = TestApp::start().await;
+    let (victim_id, victim_token) = app.signup_with_session("victim").await;
+    let (_, other_token) = app.signup_with_session("snoop").await;
+
+    let sessions = format!("/sessions?user_id={}", victim_id);
+    let (status, _) = app.get(&sessions).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = app.get_with_session(&sessions, &other_token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+
+    let (status, listed) = app.get_with_session(&sessions, &victim_token).await;
+    assert_eq!(status, StatusCode::OK, "{}", listed);
+    assert_eq!(listed.as_array().map(Vec::len), Some(1));
+    assert_eq!(listed[0]["current"], true);
+    let session_id = listed[0]["id"].as_str().unwrap();
+
+    // Another user's token can't log the victim out, one session or all of them
+    let (status, _) = app.delete_with_session(&format!("/sessions/{}?user_id={}", session_id, victim_id), &other_token, json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.delete_with_session(&sessions, &other_token, json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.delete(&sessions, json!({})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = app.get_with_session("/session", &victim_token).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = app.delete_with_session(&sessions, &victim_token, json!({})).await;
+    assert_eq!((status, body["revoked"].as_u64()), (StatusCode::OK, Some(1)));
+    let (status, _) = app.get_with_session("/session", &victim_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_using_a_session_records_when_it_was_last_used() {
+    let app = TestApp::start().await;
+    let (user_id, token) = app.signup_with_session("regular").await;
+
+    // Pretend the session has sat idle for a day
+    let idle_since = chrono::Utc::now() - chrono::Duration::days(1);
+    sqlx::query("UPDATE sessions SET last_used_at = $1 WHERE user_id = $2")
+        .bind(idle_since.timestamp())
+        .bind(&user_id)
+        .execute(app.state.db.pool())
+        .await
+        .unwrap();
+    let sessions = format!("/sessions?user_id={}", user_id);
+    let last_used = || async {
+        let (_, listed) = app.get_with_session(&sessions, &token).await;
+        chrono::DateTime::parse_from_rfc3339(listed[0]["last_used_at"].as_str().unwrap()).unwrap()
+    };
+
+    // Any request carrying the token counts as a use
+    let (status, _) = app.get_with_session(&format!("/portfolio?user_id={}", user_id), &token).await;
+    assert_eq!(status, StatusCode::OK);
+    eventually("the use to be recorded", || async { last_used().await > idle_since + chrono::Duration::hours(23) }).await;
+}
+
+#[tokio::test]
+async fn test_account_export_needs_the_users_own_session() {
+    let app = TestApp::start().await;
+    let (user_id, token) = app.signup_with_session("exporter").await;
+    let (_, other_token) = app.signup_with_session("nosy").await;
+
+    let export = format!("/account/export?user_id={}", user_id);
+    let (status, _) = app.get(&export).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = app.get_with_session(&export, &other_token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = app.get(&format!("{}&token={}", export, other_token)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The zip isn't JSON, so only the status comes back
+    let (status, _) = app.get_with_session(&export, &token).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = app.get(&format!("{}&token={}", export, token)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_higher_volume_tiers_pay_lower_fees() {
+    let mut app = TestApp::start().await;
+    let starter = app.signup("small_fry").await;
+    let pro = app.signup("whale").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    // As the daily recalculation would store it for $150k of 30-day volume
+    let record = queries::FeeTierRecord { tier: "Pro".to_string(), volume_30d_usd: 150_000.0, calculated_at: app.state.clock.now() };
+    queries::save_fee_tier(app.state.db.pool(), &pro, &record).await.unwrap();
+
+    let buy = json!({"asset": "BTC", "side": "Buy", "quantity": 0.1});
+    let mut fees = Vec::new();
+    for user_id in [&starter, &pro] {
+        let (_, preview) = app.post(&format!("/trade/preview?user_id={}", user_id), buy.clone()).await;
+        let (status, trade) = app.post(&format!("/trade?user_id={}", user_id), buy.clone()).await;
+        assert_eq!(status, StatusCode::OK, "{}", trade);
+        // The preview quotes the fee the trade is then charged
+        assert_eq!(preview["fee"], trade["fee"]);
+        let (_, portfolio) = app.get(&format!("/portfolio?user_id={}", user_id)).await;
+        assert_eq!(portfolio["asset_balances"]["USD"], 10_000.0 - 5_000.0 - trade["fee"].as_f64().unwrap());
+        fees.push(trade["fee"].as_f64().unwrap());
+    }
+    // 0.60% taker for Starter, 0.20% for Pro
+    assert_eq!(fees, vec![5_000.0 * 0.006, 5_000.0 * 0.002]);
+}
+
+#[tokio::test]
+async fn test_funding_totals_start_from_the_configured_balance() {
+    let mut config = Config::default();
+    config.accounts.starting_balance = 25_000.0;
+    let app = TestApp::start_with(config).await;
+    let user_id = app.signup("funded").await;
+
+    let (status, body) = app.post(&format!("/deposit?user_id={}", user_id), json!({"amount": 500.0})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app.post(&format!("/withdrawal?user_id={}", user_id), json!({"amount": 200.0})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (_, funds) = app.get(&format!("/funds?user_id={}", user_id)).await;
+    assert_eq!(funds["usd_balance"], 25_300.0);
+    assert_eq!(funds["lifetime_funding"], 25_500.0);
+    assert_eq!(funds["net_funding"], 25_300.0);
+}
+
+#[tokio::test]
+async fn test_archiving_trades_keeps_deposits_and_funding_totals() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("archivist").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    let (status, body) = app.post(&format!("/deposit?user_id={}", user_id), json!({"amount": 500.0})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app.post(&format!("/withdrawal?user_id={}", user_id), json!({"amount": 200.0})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, trade) = app.post(&format!("/trade?user_id={}", user_id), json!({"asset": "BTC", "side": "Buy", "quantity": 0.1})).await;
+    assert_eq!(status, StatusCode::OK, "{}", trade);
+    let (_, before) = app.get(&format!("/funds?user_id={}", user_id)).await;
+
+    // Everything above happened on the simulated 2025 clock, months before the wall-clock cutoff
+    let config = RetentionConfig { trade_archive_months: Some(1), vacuum: false, ..RetentionConfig::from_env() };
+    maintenance_service::run_db_maintenance(&app.state, &config).await;
+
+    let archived: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM trades_archive WHERE user_id = $1")
+        .bind(&user_id)
+        .fetch_one(app.state.db.pool())
+        .await
+        .unwrap();
+    assert_eq!(archived, 1);
+
+    let (_, after) = app.get(&format!("/funds?user_id={}", user_id)).await;
+    for field in ["lifetime_funding", "lifetime_withdrawals", "net_funding"] {
+        assert_eq!(after[field], before[field], "{}", field);
+    }
+
+    let (_, trades) = app.get(&format!("/trades?user_id={}", user_id)).await;
+    let mut kinds: Vec<_> = trades["items"].as_array().unwrap().iter().map(|t| t["transaction_type"].clone()).collect();
+    kinds.sort_by_key(|k| k.to_string());
+    assert_eq!(kinds, [json!("Deposit"), json!("Withdrawal")]);
+}
+
+#[tokio::test]
+async fn test_racing_trades_cannot_overdraw_the_account() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("racer").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    // Each buy costs $2,515 with the taker fee, so only three of the five fit in $10,000
+    let path = format!("/trade?user_id={}", user_id);
+    let buy = || app.post(&path, json!({"asset": "BTC", "side": "Buy", "quantity": 0.05}));
+    let results = tokio::join!(buy(), buy(), buy(), buy(), buy());
+    let results = [results.0, results.1, results.2, results.3, results.4];
+    let filled = results.iter().filter(|(status, _)| *status == StatusCode::OK).count();
+    assert_eq!(filled, 3, "{:?}", results);
+    for (status, body) in results.iter().filter(|(status, _)| *status != StatusCode::OK) {
+        assert_eq!(*status, StatusCode::BAD_REQUEST, "{}", body);
+    }
+
+    let (_, portfolio) = app.get(&format!("/portfolio?user_id={}", user_id)).await;
+    let usd = portfolio["asset_balances"]["USD"].as_f64().unwrap();
+    assert!((usd - (10_000.0 - 3.0 * 2_515.0)).abs() < 1e-6, "{}", usd);
+}
+
+#[tokio::test]
+async fn test_manual_losses_do_not_count_toward_a_bots_stoploss() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("hands_on").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    // A bot that waits for a long trend, so it never trades here
+    let (status, body) = app
+        .post(
+            "/bot/start",
+            json!({
+                "user_id": user_id,
+                "bot_name": "naive_momentum",
+                "base_asset": "BTC",
+                "quote_asset": "USD",
+                "stoploss_amount": 100.0,
+                "parameters": {"trend_ticks": 20},
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    app.wait_for_bots().await;
+
+    let (status, trade) = app.post(&format!("/trade?user_id={}", user_id), json!({"asset": "BTC", "side": "Buy", "quantity": 0.1})).await;
+    assert_eq!(status, StatusCode::OK, "{}", trade);
+    // The user's own BTC loses $2,000, twenty times the bot's stoploss
+    app.tick(&[("BTC", 30_000.0)]).await;
+    app.tick(&[("BTC", 30_000.0)]).await;
+
+    let (_, status) = app.get(&format!("/bot/status?user_id={}", user_id)).await;
+    assert_eq!(status["is_active"], true);
+}
+
+#[tokio::test]
+async fn test_two_bots_of_one_user_run_independently() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("two_bots").await;
+    app.tick(&[("BTC", 50_000.0), ("ETH", 2_500.0)]).await;
+
+    let start = |asset: &str, parameters: Value| {
+        app.post(
+            "/bot/start",
+            json!({
+                "user_id": user_id,
+                "bot_name": "naive_momentum",
+                "base_asset": asset,
+                "quote_asset": "USD",
+                "stoploss_amount": 1_000.0,
+                "parameters": parameters,
+            }),
+        )
+    };
+    let (status, btc_bot) = start("BTC", json!({"trend_ticks": 2, "step_pct": 100.0, "cooldown_ticks": 0})).await;
+    assert_eq!(status, StatusCode::OK, "{}", btc_bot);
+    let (status, eth_bot) = start("ETH", json!({"trend_ticks": 20})).await;
+    assert_eq!(status, StatusCode::OK, "{}", eth_bot);
+    let (btc_bot, eth_bot) = (btc_bot["bot_instance_id"].as_str().unwrap(), eth_bot["bot_instance_id"].as_str().unwrap());
+    app.wait_for_bots().await;
+
+    // The BTC bot buys the rise and hits its stoploss in the crash; the ETH bot sees a flat price
+    for price in ramp(51_000.0, 53_000.0, 3) {
+        app.tick(&[("BTC", price), ("ETH", 2_500.0)]).await;
+    }
+    app.tick(&[("BTC", 30_000.0), ("ETH", 2_500.0)]).await;
+
+    let (_, bots) = app.get(&format!("/bots?user_id={}", user_id)).await;
+    let running: Vec<&str> = bots.as_array().unwrap().iter().map(|b| b["bot_instance_id"].as_str().unwrap()).collect();
+    assert_eq!(running, [eth_bot]);
+
+    for (bot_id, count) in [(btc_bot, 4), (eth_bot, 0)] {
+        let (_, trades) = app.get(&format!("/trades?user_id={}&source=bot&bot_instance_id={}", user_id, bot_id)).await;
+        assert_eq!(trades["items"].as_array().map(Vec::len), Some(count), "{}", bot_id);
+    }
+
+    // And the ETH bot keeps ticking on its own
+    app.tick(&[("BTC", 30_000.0), ("ETH", 2_500.0)]).await;
+    let (_, status) = app.get(&format!("/bot/status?user_id={}&bot_instance_id={}", user_id, eth_bot)).await;
+    assert_eq!(status["is_active"], true);
+}
+
+#[tokio::test]
+async fn test_oauth_accounts_delete_with_a_fresh_session() {
+    let app = TestApp::start().await;
+    let pool = app.state.db.pool();
+    let user_id = "oauth-leaver".to_string();
+    let username = queries::create_oauth_user(pool, &user_id, "leaver", "github", "42", 10_000.0).await.unwrap();
+    app.state.users.write().await.insert(user_id.clone(), UserData::new(username, 10_000.0));
+    let device = || DeviceInfo { user_agent: None, ip_address: None };
+    let token = session_service::create_session(pool, &user_id, device()).await.unwrap().token;
+    let path = format!("/account?user_id={}", user_id);
+
+    // No password to check, so only the user's own session will do
+    let (status, _) = app.delete(&path, json!({})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, other) = app.signup_with_session("bystander").await;
+    let (status, _) = app.delete_with_session(&path, &other, json!({})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Signed in too long ago
+    sqlx::query("UPDATE sessions SET created_at = created_at - 3600 WHERE user_id = $1")
+        .bind(&user_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    let (status, body) = app.delete_with_session(&path, &token, json!({})).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "reauthentication_required");
+
+    let fresh = session_service::create_session(pool, &user_id, device()).await.unwrap().token;
+    let (status, body) = app.delete_with_session(&path, &fresh, json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(app.state.get_user(&user_id).await.is_none());
+}
+
+#[tokio::test]
+async fn test_deleting_an_account_removes_its_rows_and_is_audited() {
+    let mut app = TestApp::start().await;
+    let (user_id, token) = app.signup_with_session("departing").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    let (status, body) = app.post(&format!("/trade?user_id={}", user_id), json!({"asset": "BTC", "side": "Buy", "quantity": 0.1})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot = json!({"user_id": user_id, "bot_name": "naive_momentum", "base_asset": "BTC", "quote_asset": "USD", "stoploss_amount": 1_000.0});
+    let (status, body) = app.post("/bot/start", bot).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    app.wait_for_bots().await;
+
+    let pool = app.state.db.pool();
+    let count = |table: &'static str| {
+        let user_id = user_id.clone();
+        async move {
+            sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {} WHERE user_id = $1", table))
+                .bind(&user_id)
+                .fetch_one(pool)
+                .await
+                .unwrap()
+        }
+    };
+    let tables = ["users", "trades", "ledger_entries", "sessions", "bots", "bot_runs", "audit_log"];
+    for table in tables {
+        assert!(count(table).await > 0, "{} has no rows to delete", table);
+    }
+
+    let (status, body) = app.delete_with_session(&format!("/account?user_id={}", user_id), &token, json!({"password": "e2e-password"})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(app.state.get_user(&user_id).await.is_none());
+
+    // The bot's stop is audited in the background too, and must not leave a row behind
+    eventually("the deletion to be audited", || async {
+        sqlx::query_scalar::<_, String>("SELECT details FROM audit_log WHERE action = 'account_deleted'")
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+            .is_some_and(|details| details.starts_with(&user_id))
+    })
+    .await;
+    for table in tables {
+        assert_eq!(count(table).await, 0, "{}", table);
+    }
+}
+
+#[tokio::test]
+async fn test_webhook_signals_are_stamped_with_market_time() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("charting").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    let (status, webhook) = app.post(&format!("/webhooks/tradingview/secret?user_id={}", user_id), json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", webhook);
+    let bot = json!({"user_id": user_id, "bot_name": "signal_follower", "base_asset": "BTC", "quote_asset": "USD", "stoploss_amount": 1_000.0});
+    let (status, body) = app.post("/bot/start", bot).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot_id = body["bot_instance_id"].as_str().unwrap().to_string();
+    app.wait_for_bots().await;
+
+    let alert = json!({"secret": webhook["secret"], "symbol": "BTCUSD", "action": "buy", "size": 0.01, "mode": "signal"});
+    let (status, body) = app.post("/webhooks/tradingview", alert).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    // Queued for the bot's next tick, on the simulated clock rather than the wall clock
+    let bots = app.state.bots.read().await;
+    let signals = &bots[&bot_id].pending_signals;
+    assert_eq!(signals.len(), 1);
+    assert_eq!(signals[0].received_at, app.state.clock.now());
+}
+
+#[tokio::test]
+async fn test_trades_are_tagged_and_filtered_by_source() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("many_hands").await;
+    let leader_id = app.signup("leader").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    let (status, body) = app.post(&format!("/follows?user_id={}", user_id), json!({"leader": "leader"})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot = json!({
+        "user_id": user_id,
+        "bot_name": "naive_momentum",
+        "base_asset": "BTC",
+        "quote_asset": "USD",
+        "stoploss_amount": 1_000.0,
+        "parameters": {"trend_ticks": 2, "step_pct": 100.0, "cooldown_ticks": 0},
+    });
+    let (status, body) = app.post("/bot/start", bot).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot_id = body["bot_instance_id"].as_str().unwrap().to_string();
+    app.wait_for_bots().await;
+
+    // One bot buy, one from the trade form, one from a TradingView alert and one copied from the leader
+    app.tick(&[("BTC", 51_000.0)]).await;
+    let buy = json!({"asset": "BTC", "side": "Buy", "quantity": 0.01});
+    let (status, body) = app.post(&format!("/trade?user_id={}", user_id), buy.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, webhook) = app.post(&format!("/webhooks/tradingview/secret?user_id={}", user_id), json!({})).await;
+    let alert = json!({"secret": webhook["secret"], "symbol": "BTCUSD", "action": "buy", "size": 0.01});
+    let (status, body) = app.post("/webhooks/tradingview", alert).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app.post(&format!("/trade?user_id={}", leader_id), buy).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let all = format!("/trades?user_id={}&sort=asc", user_id);
+    eventually("the leader's trade to be copied", || async { app.get(&all).await.1["items"].as_array().unwrap().len() == 4 })
+        .await;
+    let (_, trades) = app.get(&all).await;
+    let sources: Vec<&Value> = trades["items"].as_array().unwrap().iter().map(|t| &t["source"]).collect();
+    assert_eq!(
+        sources,
+        vec![
+            &json!({"kind": "bot", "instance_id": bot_id, "name": "Naive Momentum"}),
+            &json!({"kind": "manual"}),
+            &json!({"kind": "manual"}), // The user's own alert, so it counts as theirs
+            &json!({"kind": "copy", "leader": "leader"}),
+        ]
+    );
+
+    let count = |source: &'static str| {
+        let path = format!("/trades?user_id={}&source={}", user_id, source);
+        let app = &app;
+        async move { app.get(&path).await.1["items"].as_array().unwrap().len() }
+    };
+    assert_eq!((count("manual").await, count("bot").await, count("copy").await), (2, 1, 1));
+    let (_, leader_trades) = app.get(&format!("/trades?user_id={}&source=copy", leader_id)).await;
+    assert!(leader_trades["items"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_bots_retry_a_gap_in_prices_and_stop_if_it_lasts() {
+    let mut config = Config::default();
+    config.bots.max_context_failures = 3;
+    config.bots.retry_backoff_secs = 5;
+    let mut app = TestApp::start_with(config).await;
+    let user_id = app.signup("gappy").await;
+
+    // Neither asset has a price yet, so both bots fail their first tick
+    let mut bot_ids = Vec::new();
+    for asset in ["BTC", "ETH"] {
+        let bot = json!({"user_id": user_id, "bot_name": "naive_momentum", "base_asset": asset, "quote_asset": "USD", "stoploss_amount": 1_000.0});
+        let (status, body) = app.post("/bot/start", bot).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        bot_ids.push(body["bot_instance_id"].as_str().unwrap().to_string());
+    }
+    let (btc_bot, eth_bot) = (&bot_ids[0], &bot_ids[1]);
+    let failures = |bot_id: &str| {
+        let (app, bot_id) = (&app, bot_id.to_string());
+        async move { app.state.bots.read().await.get(&bot_id).map(|bot| bot.context_failures) }
+    };
+    for bot_id in &bot_ids {
+        eventually("the first failed tick", || async { failures(bot_id).await == Some(1) }).await;
+    }
+
+    // Retried after the 5s backoff, well before the next tick, and failed again
+    app.advance(chrono::Duration::seconds(5));
+    for bot_id in &bot_ids {
+        eventually("the retry", || async { failures(bot_id).await == Some(2) }).await;
+    }
+    let (_, status) = app.get(&format!("/bot/status?user_id={}&bot_instance_id={}", user_id, btc_bot)).await;
+    assert_eq!((status["is_active"].as_bool(), status["context_failures"].as_u64()), (Some(true), Some(2)));
+
+    // BTC prices come back in time for the BTC bot; ETH's third failure in a row stops its bot
+    app.tick(&[("BTC", 50_000.0)]).await;
+    let (_, status) = app.get(&format!("/bot/status?user_id={}&bot_instance_id={}", user_id, btc_bot)).await;
+    assert_eq!((status["is_active"].as_bool(), status["context_failures"].as_u64()), (Some(true), Some(0)));
+    assert!(!app.state.bots.read().await.contains_key(eth_bot));
+
+    let run = |bot_id: &str| format!("/bot/runs?user_id={}&bot_instance_id={}", user_id, bot_id);
+    let eth_runs = run(eth_bot);
+    eventually("the ETH run to close", || async { !app.get(&eth_runs).await.1["items"][0]["stopped_at"].is_null() }).await;
+    let (_, runs) = app.get(&eth_runs).await;
+    assert_eq!((runs["items"][0]["status"].as_str(), runs["items"][0]["errors"].as_u64()), (Some("failed"), Some(3)));
+    let btc_runs = run(btc_bot);
+    eventually("the BTC run's errors", || async { app.get(&btc_runs).await.1["items"][0]["errors"] == 2 }).await;
+    let (_, runs) = app.get(&btc_runs).await;
+    assert!(runs["items"][0]["stopped_at"].is_null(), "{}", runs);
+}
+
+/// A database migrated only up to, not including, migration `version`, as a server that
+/// predates it left it
+async fn database_before_migration(version: i64) -> (String, Database) {
+    let (url, db) = new_database().await;
+    let mut migrator = sqlx::migrate!("./migrations");
+    migrator.migrations = Cow::Owned(migrator.migrations.iter().filter(|m| m.version < version).cloned().collect());
+    migrator.run(db.pool()).await.expect("earlier migrations");
+    (url, db)
+}
+
+#[tokio::test]
+async fn test_running_bots_come_back_after_a_restart() {
+    let mut app = TestApp::start().await;
+    let user_id = app.signup("deployed").await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+
+    let bot = json!({
+        "user_id": user_id,
+        "bot_name": "naive_momentum",
+        "base_asset": "BTC",
+        "quote_asset": "USD",
+        "stoploss_amount": 2_000.0,
+        "parameters": {"trend_ticks": 2, "step_pct": 100.0, "cooldown_ticks": 0},
+        "max_base_position": 0.05,
+    });
+    let (status, body) = app.post("/bot/start", bot).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot_id = body["bot_instance_id"].as_str().unwrap().to_string();
+    app.wait_for_bots().await;
+    app.tick(&[("BTC", 51_000.0)]).await;
+    let before = app.state.get_user(&user_id).await.unwrap();
+    assert_eq!(before.trade_history.len(), 1);
+
+    // The new server loads the account and respawns the bot once its pair has a price
+    let mut app = app.restart().await;
+    let after = app.state.get_user(&user_id).await.unwrap();
+    assert_eq!((after.asset_balances, after.trade_history.len()), (before.asset_balances, 1));
+    app.tick(&[("BTC", 52_000.0)]).await;
+    eventually("the bot to be restored", || async { app.state.bots.read().await.contains_key(&bot_id) }).await;
+    {
+        let bots = app.state.bots.read().await;
+        let restored = &bots[&bot_id];
+        assert_eq!((restored.user_id.as_str(), restored.strategy.as_str()), (user_id.as_str(), "naive_momentum"));
+        assert_eq!(restored.trading_pair, ("BTC".to_string(), "USD".to_string()));
+        assert_eq!((restored.stoploss_amount, restored.max_base_position), (2_000.0, Some(0.05)));
+    }
+
+    // With its parameters and position cap: the next rise buys $2,000 again, cut down to the cap
+    app.tick(&[("BTC", 53_000.0)]).await;
+    let user = app.state.get_user(&user_id).await.unwrap();
+    assert_eq!(user.trade_history.len(), 2);
+    assert!((user.asset_balances["BTC"] - 0.05).abs() < 1e-9, "{:?}", user.asset_balances);
+    assert_eq!(user.trade_history[1].source.bot_instance(), Some(&bot_id));
+}
+
+#[tokio::test]
+async fn test_a_restored_bot_keeps_its_run_until_it_stops() {
+    let app = TestApp::start().await;
+    let user_id = app.signup("long_runner").await;
+    let bot = json!({"user_id": user_id, "bot_name": "naive_momentum", "base_asset": "BTC", "quote_asset": "USD", "stoploss_amount": 1_000.0});
+    let (status, body) = app.post("/bot/start", bot).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let bot_id = body["bot_instance_id"].as_str().unwrap().to_string();
+    let runs = format!("/bot/runs?user_id={}&bot_instance_id={}", user_id, bot_id);
+    let (_, before) = app.get(&runs).await;
+    assert_eq!(before["items"].as_array().map(Vec::len), Some(1), "{}", before);
+
+    // The relaunch resumes the same run rather than opening a second one
+    let mut app = app.restart().await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+    eventually("the bot to be restored", || async { app.state.bots.read().await.contains_key(&bot_id) }).await;
+    let (_, after) = app.get(&runs).await;
+    assert_eq!(after["items"].as_array().map(Vec::len), Some(1), "{}", after);
+    assert_eq!((after["items"][0]["status"].as_str(), &after["items"][0]["started_at"]), (Some("running"), &before["items"][0]["started_at"]));
+
+    let (status, body) = app.post(&format!("/bot/stop?user_id={}&bot_instance_id={}", user_id, bot_id), json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    eventually("the run to close", || async { app.get(&runs).await.1["items"][0]["status"] == "stopped" }).await;
+
+    // A stopped bot stays stopped across the next restart, and its run stays on record
+    let mut app = app.restart().await;
+    app.tick(&[("BTC", 50_000.0)]).await;
+    assert!(queries::load_bot_configs(app.state.db.pool()).await.unwrap().is_empty());
+    assert!(app.state.bots.read().await.is_empty());
+    let (_, after) = app.get(&runs).await;
+    assert_eq!((after["items"].as_array().map(Vec::len), after["items"][0]["status"].as_str()), (Some(1), Some("stopped")));
+}
+
+#[tokio::test]
+async fn test_bot_leases_are_shared_through_the_database() {
+    let first = TestApp::start().await;
+    let second = first.restart().await;
+    let (a, b) = (first.state.db.pool(), second.state.db.pool());
+    let lease = "bot:shared";
+    let now = 1_700_000_000;
+
+    // Held by one instance until it expires; the holder can renew it
+    assert!(queries::try_acquire_lease(a, lease, "instance-a", now, now + 30).await.unwrap());
+    assert!(!queries::try_acquire_lease(b, lease, "instance-b", now + 10, now + 40).await.unwrap());
+    assert!(queries::try_acquire_lease(a, lease, "instance-a", now + 20, now + 50).await.unwrap());
+    assert!(!queries::try_acquire_lease(b, lease, "instance-b", now + 40, now + 70).await.unwrap());
+
+    // Once it lapses another instance adopts it, and the old holder can't release or retake it
+    assert!(queries::try_acquire_lease(b, lease, "instance-b", now + 51, now + 81).await.unwrap());
+    queries::release_lease(a, lease, "instance-a").await.unwrap();
+    assert!(!queries::try_acquire_lease(a, lease, "instance-a", now + 60, now + 90).await.unwrap());
+
+    // Released by its holder, it's free at once
+    queries::release_lease(b, lease, "instance-b").await.unwrap();
+    assert!(queries::try_acquire_lease(a, lease, "instance-a", now + 61, now + 91).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_trade_history_blobs_move_into_the_trades_table() {
+    let (url, db) = database_before_migration(20250127000001).await;
+    let history = json!([
+        // Recorded before pairs, with "asset" instead of "base_asset" and no quote
+        {"asset": "BTC", "side": "Buy", "quantity": 0.1, "price": 40_000.0, "timestamp": "2024-12-01T00:00:00Z"},
+        {
+            "transaction_type": "Trade", "base_asset": "ETH", "quote_asset": "BTC", "side": "Sell",
+            "quantity": 2.0, "price": 0.05, "timestamp": "2024-12-02T00:00:00Z",
+            "base_usd_price": 2_000.0, "quote_usd_price": 40_000.0, "executed_by_bot": "Naive Momentum",
+        },
+    ]);
+    sqlx::query("INSERT INTO users (user_id, username, cash_balance, asset_balances, trade_history) VALUES ($1, $2, $3, $4, $5)")
+        .bind("legacy")
+        .bind("old_timer")
+        .bind(6_000.0)
+        .bind(json!({"USD": 6_000.0, "BTC": 0.2, "ETH": 3.0}).to_string())
+        .bind(history.to_string())
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+    let app = TestApp::start_on(url, db).await;
+    let (_, trades) = app.get("/trades?user_id=legacy&sort=asc").await;
+    let rows: Vec<(&str, &str, &str, f64, f64)> = trades["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            let field = |name: &str| t[name].as_str().unwrap();
+            (field("base_asset"), field("quote_asset"), field("side"), t["quantity"].as_f64().unwrap(), t["price"].as_f64().unwrap())
+        })
+        .collect();
+    assert_eq!(rows, vec![("BTC", "USD", "Buy", 0.1, 40_000.0), ("ETH", "BTC", "Sell", 2.0, 0.05)]);
+    assert_eq!(trades["items"][0]["source"], json!({"kind": "manual"}));
+    assert_eq!(trades["items"][1]["source"]["name"], "Naive Momentum");
+    assert_eq!(trades["items"][1]["base_usd_price"], 2_000.0);
+    assert_eq!(trades["items"][1]["timestamp"], "2024-12-02T00:00:00Z");
+
+    let blob: String = sqlx::query_scalar("SELECT trade_history FROM users WHERE user_id = 'legacy'")
+        .fetch_one(app.state.db.pool())
+        .await
+        .unwrap();
+    assert_eq!(blob, "[]");
+}
+
+#[tokio::test]
+async fn test_cash_balances_fold_into_asset_balances() {
+    let (url, db) = database_before_migration(20250131000001).await;
+    let insert = "INSERT INTO users (user_id, username, cash_balance, asset_balances) VALUES ($1, $2, $3, $4)";
+    // Only ever had cash_balance for its dollars, and one whose USD entry already took over
+    for (user_id, cash, balances) in [("cash_only", 2_500.0, json!({"BTC": 0.5})), ("has_usd", 10_000.0, json!({"USD": 7_000.0}))] {
+        sqlx::query(insert).bind(user_id).bind(user_id).bind(cash).bind(balances.to_string()).execute(db.pool()).await.unwrap();
+    }
+
+    let app = TestApp::start_on(url, db).await;
+    let (_, portfolio) = app.get("/portfolio?user_id=cash_only").await;
+    assert_eq!((portfolio["asset_balances"]["USD"].as_f64(), portfolio["asset_balances"]["BTC"].as_f64()), (Some(2_500.0), Some(0.5)));
+    let (_, portfolio) = app.get("/portfolio?user_id=has_usd").await;
+    assert_eq!(portfolio["asset_balances"]["USD"], 7_000.0);
+
+    let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info('users')")
+        .fetch_all(app.state.db.pool())
+        .await
+        .unwrap();
+    assert!(!columns.iter().any(|c| c == "cash_balance"), "{:?}", columns);
+}
@@ -1,4 +1,6 @@
 use dioxus::prelude::*;
+use futures_util::{future::{select, Either}, SinkExt, StreamExt};
+use gloo_net::{eventsource::futures::EventSource, websocket::{futures::WebSocket, Message}};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use chrono::{self, Timelike};
@@ -9,21 +11,113 @@ enum AppView {
     Dashboard,
     Markets,
     Trading(String), // Trading view for specific asset
+    Backtest,
+    Funds,
+    Settings,
     About,
 }
 
+/// One polled asset from `GET /api/stats` (the rest of the feed health is not needed here)
 #[derive(Clone, Debug, Deserialize)]
-struct PriceResponse {
+struct FeedResponse {
+    asset: String,
+    latest_price: Option<f64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct StatsResponse {
+    feeds: Vec<FeedResponse>,
+}
+
+/// Message from the `/api/ws/prices` WebSocket
+#[derive(Clone, Debug, Deserialize)]
+struct PriceUpdate {
     asset: String,
     price: f64,
 }
 
+/// `balance` event from the `/api/events` stream
+#[derive(Clone, Debug, Deserialize)]
+struct BalanceUpdate {
+    reason: String, // "initial" on (re)connect, then what changed the balances
+    asset_balances: HashMap<String, f64>,
+    trade: Option<Trade>,
+}
+
+/// `notification` event from the `/api/events` stream
+/// Fields beyond `kind` depend on it (price_alert, copy_trade, order_filled, order_failed,
+/// bot_started, bot_trade, stoploss_hit, bot_stopped, achievement_earned, daily_summary)
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct NotificationEvent {
+    kind: String,
+    timestamp: String,
+    asset: Option<String>,
+    condition: Option<String>,
+    threshold: Option<f64>,
+    leader_username: Option<String>,
+    order_type: Option<String>,
+    side: Option<TradeSide>,
+    base_asset: Option<String>,
+    quote_asset: Option<String>,
+    quantity: Option<f64>,
+    price: Option<f64>,
+    reason: Option<String>, // Why an order failed
+    title: Option<String>,  // Achievement earned
+    details: Option<String>,
+    date: Option<String>, // Daily summary
+    pnl_usd: Option<f64>,
+    pnl_pct: Option<f64>,
+    trades: Option<usize>,
+}
+
+/// A bot's trade decision, as sent on the `/api/ws/bot` WebSocket
+#[derive(Clone, Debug, Deserialize)]
+struct BotDecisionMessage {
+    action: String, // "buy", "sell", "buy_base", "sell_base", "close_position", "place_limit" or "do_nothing"
+    #[serde(default)]
+    quote_amount: f64,
+    #[serde(default)]
+    quantity: f64, // Base units, for buy_base and sell_base
+}
+
+/// Message from the `/api/ws/bot` WebSocket
+/// Fields beyond `event` depend on it (status, started, tick, trade_executed, position_capped, stoploss_hit, error, stopped)
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct BotStreamMessage {
+    event: String,
+    bot_instance_id: String,
+    bot_name: String,
+    timestamp: String,
+    base_asset: Option<String>,
+    quote_asset: Option<String>,
+    decision: Option<BotDecisionMessage>,
+    reason: Option<String>,
+    message: Option<String>,
+    allowed: Option<f64>,           // Base quantity a position_capped buy was cut to
+    max_base_position: Option<f64>,
+}
+
+/// An entry in the notification center, also shown briefly as a toast
+#[derive(Clone, Debug, PartialEq)]
+struct AppNotification {
+    id: u64,
+    timestamp: String,
+    message: String,
+    level: &'static str, // "info", "success" or "error"
+}
+
+/// Returned by signup/login, and by `GET /api/session` (without a token) when resuming
 #[derive(Clone, Debug, Deserialize)]
 struct AuthResponse {
     user_id: String,
     username: String,
+    #[serde(default)]
+    session_token: String,
 }
 
+/// Error body shared by every API route (`code` and `details` are not needed here)
 #[derive(Clone, Debug, Deserialize)]
 struct ErrorResponse {
     error: String,
@@ -47,6 +141,35 @@ struct PricePoint {
     price: f64,
 }
 
+/// One point of `GET /api/portfolio/history`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct EquityPoint {
+    timestamp: i64, // Unix timestamp in seconds
+    value_usd: f64,
+    benchmark_usd: f64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct PortfolioHistoryResponse {
+    benchmark_asset: String,
+    points: Vec<EquityPoint>,
+    metrics: PerformanceMetrics,
+}
+
+/// Risk and return figures for a backtest or the portfolio's equity curve (null where there isn't enough data)
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+struct PerformanceMetrics {
+    total_return_pct: f64,
+    cagr_pct: Option<f64>,
+    sharpe_ratio: Option<f64>,
+    max_drawdown_pct: f64,
+    win_rate_pct: Option<f64>,
+    profit_factor: Option<f64>,
+    avg_trade_duration_secs: Option<f64>,
+    exposure_pct: f64,
+    closed_trades: usize,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 struct Candle {
     timestamp: i64, // Unix timestamp in seconds
@@ -63,6 +186,8 @@ struct PriceChartProps {
     timeframe: String, // "1h", "8h", or "24h"
     #[props(optional)]
     indicator_data: Option<IndicatorResponse>,
+    #[props(default)]
+    overlays: Vec<(String, &'static str)>, // Indicator keys drawn over the price, with their colors
 }
 
 #[derive(Clone, PartialEq, Props)]
@@ -78,6 +203,32 @@ struct CandlestickChartProps {
 struct RSIPanelProps {
     timestamps: Vec<i64>,
     rsi_values: Vec<Option<f64>>,
+    label: String, // e.g. "RSI(14)"
+}
+
+/// `/api/price/stats`: the 24h ticker for an asset in USD
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct TickerStats {
+    asset: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    change: f64,
+    change_pct: f64,
+}
+
+/// `/api/news`: a headline, timestamped like the chart's points
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct Headline {
+    timestamp: i64,
+    title: String,
+    url: String,
+    source: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct NewsResponse {
+    headlines: Vec<Headline>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -86,6 +237,40 @@ struct PriceHistoryResponse {
     prices: Vec<PricePoint>,
 }
 
+/// A chart indicator, as stored in the user's `chart_indicators` setting
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+struct IndicatorSetting {
+    kind: String, // "sma", "ema" or "rsi"
+    period: u32,
+    enabled: bool,
+}
+
+impl IndicatorSetting {
+    fn new(kind: &str, period: u32) -> Self {
+        Self { kind: kind.to_string(), period, enabled: false }
+    }
+
+    /// Name in the `indicators=` query and the response, e.g. "sma_20"
+    fn key(&self) -> String {
+        format!("{}_{}", self.kind, self.period)
+    }
+
+    fn label(&self) -> String {
+        format!("{}({})", self.kind.to_uppercase(), self.period)
+    }
+
+    /// RSI gets its own panel; the others are drawn over the price
+    fn is_overlay(&self) -> bool {
+        self.kind != "rsi"
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct UserSettings {
+    #[serde(default)]
+    settings: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 struct IndicatorResponse {
     asset: String,
@@ -109,11 +294,38 @@ fn default_transaction_type() -> TransactionType {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 struct UserData {
     username: String,
-    cash_balance: f64,
     asset_balances: HashMap<String, f64>,
     trade_history: Vec<Trade>,
 }
 
+/// Response of `GET /api/funds`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct FundsSummary {
+    usd_balance: f64,
+    lifetime_funding: f64, // Starting balance plus every deposit
+    lifetime_deposits: f64,
+    lifetime_withdrawals: f64,
+    net_funding: f64,
+    transfers: Vec<Trade>, // Latest deposits and withdrawals, newest first
+}
+
+/// One of the user's active logins, from `GET /api/sessions`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct SessionEntry {
+    id: String,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    created_at: String,
+    last_used_at: Option<String>,
+    current: bool, // The session this browser is using
+}
+
+/// Response of `POST /api/account/password`
+#[derive(Clone, Debug, Deserialize)]
+struct ChangePasswordResponse {
+    sessions_revoked: u64,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 struct Trade {
     user_id: String,
@@ -152,6 +364,14 @@ struct StartBotRequest {
     base_asset: String,
     quote_asset: String,
     stoploss_amount: f64,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    parameters: serde_json::Value, // Strategy settings; null keeps the defaults
+    dry_run: bool,
+    trade_limits: serde_json::Value, // Only the limits filled in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_base_position: Option<f64>,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    schedule: serde_json::Value, // Active hours; null to trade at any time
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -160,13 +380,23 @@ struct BotResponse {
     message: String,
 }
 
+/// BotStatusResponse.paused of a bot its user paused, as opposed to one outside its schedule
+const PAUSED_BY_USER: &str = "paused by user";
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 struct BotStatusResponse {
     is_active: bool,
+    bot_instance_id: Option<String>,
     bot_name: Option<String>,
     trading_pair: Option<String>,
     stoploss_amount: Option<f64>,
     initial_portfolio_value: Option<f64>,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    paused: Option<String>, // Why the bot isn't trading right now, e.g. "paused by schedule"
+    #[serde(default)]
+    context_failures: u32, // Ticks in a row it couldn't get prices for; retried until the server's limit
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
@@ -185,2089 +415,5387 @@ struct TradeRequest {
 }
 
 #[derive(Clone, Debug, Serialize)]
-struct DepositRequest {
-    amount: f64,
+struct OrderRequest {
+    asset: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quote_asset: Option<String>,
+    side: String,
+    order_type: String, // "limit" or "stop"
+    quantity: f64,
+    price: f64,
 }
 
 #[derive(Clone, Debug, Serialize)]
-struct WithdrawalRequest {
-    amount: f64,
+struct PreviewRequest {
+    asset: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quote_asset: Option<String>,
+    side: String,
+    quantity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order_type: Option<String>, // None for a market trade
+    #[serde(skip_serializing_if = "Option::is_none")]
+    price: Option<f64>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
-struct TradeErrorResponse {
-    error: String,
+/// Estimated cost of a trade from `POST /api/trade/preview`, in quote asset terms
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct TradePreview {
+    price: f64,
+    quote_amount: f64,
+    fee: f64,
+    total: f64,
+    total_usd: Option<f64>,
+    sufficient_balance: bool,
 }
 
-const API_BASE: &str = "http://localhost:3000/api";
+/// A resting limit or stop order
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct Order {
+    id: String,
+    base_asset: String,
+    quote_asset: String,
+    side: TradeSide,
+    order_type: String,
+    quantity: f64,
+    trigger_price: f64,
+}
 
-// Color scheme constants
-const COLOR_NAVY: &str = "#1a237e";
-const COLOR_PAGE_BG: &str = "#FBFCF8"; // #e9eeff
-const COLOR_CONTENT_BG: &str = "#fefefe";
-const COLOR_DARK_GREY: &str = "#424242";
-const COLOR_LIGHT_GREY: &str = "#757575";
-const COLOR_GREEN: &str = "#4caf50";
-const COLOR_RED: &str = "#f44336";
+#[derive(Clone, Debug, Serialize)]
+struct BacktestRequest {
+    strategy: String,
+    asset: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quote_asset: Option<String>,
+    start: String, // RFC 3339
+    end: String,
+    initial_balance: f64,
+    stoploss_amount: f64,
+    parameters: serde_json::Value, // Strategy settings
+}
 
-// Typography - Inter for headers, system fonts for body
-const FONT_HEADER: &str = "'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif";
-const FONT_BODY: &str = "-apple-system, BlinkMacSystemFont, 'Segoe UI', 'Roboto', sans-serif";
+/// What a run replayed (the fields the Backtest page shows)
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BacktestRunParams {
+    strategy: String,
+    base_asset: String,
+    quote_asset: String,
+    start: String,
+    end: String,
+}
 
-fn format_timestamp(timestamp: &str) -> String {
-    // Parse ISO 8601 timestamp and format it nicely
-    // Example input: "2025-01-22T10:30:00.123456789Z"
-    // Example output: "Jan 22, 10:30"
-    if let Some(date_part) = timestamp.split('T').next() {
-        if let Some(time_part) = timestamp.split('T').nth(1) {
-            let time = time_part.split(':').take(2).collect::<Vec<_>>().join(":");
-            if let Some(stripped_date) = date_part.strip_prefix("2025-") {
-                return format!("{} {}", stripped_date, time);
-            }
-        }
-    }
-    // Fallback to showing the raw timestamp if parsing fails
-    timestamp.to_string()
+/// A run from `GET /api/backtest`, without its equity curve and trades
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BacktestRunSummary {
+    id: String,
+    status: String,
+    params: BacktestRunParams,
+    created_at: String,
+    metrics: Option<BacktestMetrics>,
 }
 
-#[component]
-fn PriceChart(props: PriceChartProps) -> Element {
-    // Clone props data to satisfy lifetime requirements for event handlers
-    let prices = props.prices.clone();
-    let quote_asset = props.quote_asset.clone();
+/// A run from `POST /api/backtest` / `GET /api/backtest/{id}`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BacktestRun {
+    id: String,
+    status: String, // "running", "completed" or "failed"
+    params: BacktestRunParams,
+    result: Option<BacktestResult>,
+    error: Option<String>,
+}
 
-    // Debug: Log if we have indicator data
-    if let Some(ref ind_data) = props.indicator_data {
-        web_sys::console::log_1(&format!("PriceChart received indicators: {:?}", ind_data.indicators.keys().collect::<Vec<_>>()).into());
-    } else {
-        web_sys::console::log_1(&"PriceChart: No indicator data".into());
-    }
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BacktestResult {
+    ticks: usize,
+    metrics: BacktestMetrics,
+    equity: Vec<BacktestEquityPoint>,
+    trades: Vec<BacktestTrade>,
+    stopped_reason: Option<String>,
+}
 
-    if prices.is_empty() {
-        return rsx! { p { "No data available" } };
-    }
+/// One graded dimension from `GET /api/backtest/{id}/risk`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct RiskComponent {
+    name: String, // "turnover", "concentration", "drawdown" or "leverage"
+    value: f64,
+    grade: String,
+    description: String,
+}
 
-    // Hover state for crosshair and tooltip
-    let mut hover_x = use_signal(|| None::<f64>);
-    let mut hover_y = use_signal(|| None::<f64>);
-    let mut hover_price = use_signal(|| None::<f64>);
-    let mut hover_time = use_signal(|| None::<i64>);
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct RiskScore {
+    grade: String, // "A" (least risk) to "F"
+    components: Vec<RiskComponent>,
+}
 
-    // Calculate chart dimensions
-    let width = 1000.0;
-    let height = 300.0;
-    let padding_left = 80.0;
-    let padding_right = 40.0;
-    let padding_top = 40.0;
-    let padding_bottom = 60.0;
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BacktestMetrics {
+    final_value: f64,
+    buy_and_hold_return_pct: f64,
+    trade_count: usize,
+    #[serde(flatten)]
+    performance: PerformanceMetrics,
+}
 
-    // Find min and max values for scaling
-    let min_price = prices.iter().map(|p| p.price).fold(f64::INFINITY, f64::min);
-    let max_price = prices.iter().map(|p| p.price).fold(f64::NEG_INFINITY, f64::max);
-    let price_range = if (max_price - min_price).abs() < 0.01 { 1.0 } else { max_price - min_price };
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BacktestEquityPoint {
+    timestamp: i64,
+    value: f64,
+    hold_value: f64,
+}
 
-    // Generate path data for the line
-    let mut path_data = String::from("M ");
-    for (i, point) in prices.iter().enumerate() {
-        let x = padding_left + (i as f64 / (prices.len() - 1) as f64) * (width - padding_left - padding_right);
-        let y = height - padding_bottom - ((point.price - min_price) / price_range) * (height - padding_top - padding_bottom);
-        if i == 0 {
-            path_data.push_str(&format!("{} {} ", x, y));
-        } else {
-            path_data.push_str(&format!("L {} {} ", x, y));
-        }
-    }
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BacktestTrade {
+    timestamp: String,
+    side: TradeSide,
+    quantity: f64,
+    price: f64,
+}
 
-    // Generate horizontal grid lines (5 lines)
-    let mut h_grid_lines = Vec::new();
-    for i in 0..5 {
-        let y = padding_top + (i as f64 / 4.0) * (height - padding_top - padding_bottom);
-        let price = max_price - (i as f64 / 4.0) * price_range;
-        h_grid_lines.push((y, price));
-    }
+/// `POST /api/backtest/optimize`: a backtest plus the values to try per parameter
+#[derive(Clone, Debug, Serialize)]
+struct OptimizeRequest {
+    #[serde(flatten)]
+    backtest: BacktestRequest,
+    grid: HashMap<String, Vec<serde_json::Value>>,
+    rank_by: String,
+}
 
-    // Generate vertical grid lines and time labels (6 marks with real timestamps)
-    let mut v_grid_lines = Vec::new();
-    let time_span = prices.last().unwrap().timestamp - prices.first().unwrap().timestamp;
-    for i in 0..6 {
-        let x = padding_left + (i as f64 / 5.0) * (width - padding_left - padding_right);
-        let timestamp = prices.first().unwrap().timestamp + ((time_span as f64 * i as f64 / 5.0) as i64);
-        v_grid_lines.push((x, timestamp));
-    }
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct OptimizationResult {
+    rank_by: String,
+    combinations: usize,
+    ticks: usize,
+    results: Vec<GridResult>, // Best first
+    heatmaps: Vec<Heatmap>,
+}
 
-    // Precompute fixed coordinates
-    let chart_top = padding_top;
-    let chart_bottom = height - padding_bottom;
-    let chart_left = padding_left;
-    let chart_right = width - padding_right;
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct GridResult {
+    rank: usize,
+    parameters: serde_json::Value,
+    score: Option<f64>,
+    metrics: BacktestMetrics,
+}
 
-    // Price label (show currency symbol for USD, otherwise show asset name)
-    let price_label = if quote_asset == "USD" {
-        "Price ($)".to_string()
-    } else {
-        format!("Price ({})", quote_asset)
-    };
+/// Best score per pair of values of two parameters; scores[y][x]
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct Heatmap {
+    x_parameter: String,
+    y_parameter: String,
+    x_values: Vec<serde_json::Value>,
+    y_values: Vec<serde_json::Value>,
+    scores: Vec<Vec<Option<f64>>>,
+}
 
-    rsx! {
-        div {
-            style: "position: relative;",
-            svg {
-                width: "{width}",
-                height: "{height}",
-                view_box: "0 0 {width} {height}",
-                style: "display: block; margin: 0 auto; background: white; cursor: crosshair;",
-                onmousemove: move |evt| {
-                    let rect_x = evt.data().element_coordinates().x;
-                    let rect_y = evt.data().element_coordinates().y;
+/// `POST /api/backtest/walk-forward`: an optimization repeated over rolling windows
+#[derive(Clone, Debug, Serialize)]
+struct WalkForwardRequest {
+    #[serde(flatten)]
+    optimize: OptimizeRequest,
+    in_sample_days: u32,
+    out_of_sample_days: u32,
+}
 
-                    // Check if within chart bounds
-                    if rect_x >= chart_left && rect_x <= chart_right && rect_y >= chart_top && rect_y <= chart_bottom {
-                        hover_x.set(Some(rect_x));
-                        hover_y.set(Some(rect_y));
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct WalkForwardResult {
+    combinations: usize,
+    windows: Vec<WalkForwardWindow>,
+    skipped_windows: usize,
+    out_of_sample: PerformanceMetrics,
+    out_of_sample_equity: Vec<BacktestEquityPoint>,
+    efficiency_pct: Option<f64>,
+}
 
-                        // Calculate price from y position
-                        let price = max_price - ((rect_y - chart_top) / (chart_bottom - chart_top)) * price_range;
-                        hover_price.set(Some(price));
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct WalkForwardWindow {
+    out_of_sample_start: String,
+    out_of_sample_end: String,
+    parameters: serde_json::Value,
+    in_sample: BacktestMetrics,
+    out_of_sample: BacktestMetrics,
+    stopped_reason: Option<String>,
+}
 
-                        // Calculate time from x position
-                        let time_idx = ((rect_x - chart_left) / (chart_right - chart_left) * (prices.len() - 1) as f64) as usize;
-                        if time_idx < prices.len() {
-                            hover_time.set(Some(prices[time_idx].timestamp));
-                        }
-                    } else {
-                        hover_x.set(None);
-                        hover_y.set(None);
-                        hover_price.set(None);
-                        hover_time.set(None);
-                    }
-                },
-                onmouseleave: move |_| {
-                    hover_x.set(None);
-                    hover_y.set(None);
-                    hover_price.set(None);
-                    hover_time.set(None);
-                },
+/// `POST /api/backtest/replay`: a backtest played through the live bot pipeline at `speed`× real time
+#[derive(Clone, Debug, Serialize)]
+struct ReplayRequest {
+    #[serde(flatten)]
+    backtest: BacktestRequest,
+    speed: u32,
+}
 
-                // Horizontal grid lines with price labels
-                for (y, price) in h_grid_lines.iter() {
-                    line {
-                        x1: "{chart_left}",
-                        y1: "{y}",
-                        x2: "{chart_right}",
-                        y2: "{y}",
-                        stroke: "#e0e0e0",
-                        stroke_width: "1"
-                    }
-                    text {
-                        x: "{chart_left - 10.0}",
-                        y: "{y + 4.0}",
-                        font_size: "12",
-                        fill: "#666",
-                        text_anchor: "end",
-                        {
-                            if quote_asset == "USD" {
-                                format!("${:.2}", price)
-                            } else {
-                                format!("{:.4}", price)
-                            }
-                        }
-                    }
-                }
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct ReplayRun {
+    id: String,
+    status: String, // "running", "completed", "cancelled" or "failed"
+    speed: u32,
+    replayed_to: Option<String>,
+    ticks: u64,
+    final_value: Option<f64>,
+    metrics: Option<PerformanceMetrics>,
+    trades: Vec<BacktestTrade>,
+    stopped_reason: Option<String>,
+    error: Option<String>,
+}
 
-                // Vertical grid lines with time labels
-                for (x, timestamp) in v_grid_lines.iter() {
-                    line {
-                        x1: "{x}",
-                        y1: "{chart_top}",
-                        x2: "{x}",
-                        y2: "{chart_bottom}",
-                        stroke: "#e0e0e0",
-                        stroke_width: "1"
-                    }
-                    text {
-                        x: "{x}",
-                        y: "{chart_bottom + 20.0}",
-                        font_size: "12",
-                        fill: "#666",
-                        text_anchor: "middle",
-                        {
-                            // Format timestamp as HH:MM
-                            let dt = chrono::DateTime::from_timestamp(*timestamp, 0).unwrap();
-                            format!("{:02}:{:02}", dt.hour(), dt.minute())
-                        }
-                    }
-                }
+/// Ranking choices for the optimizer: (API value, label)
+const RANK_BY_OPTIONS: [(&str, &str); 6] = [
+    ("total_return", "Total Return"),
+    ("sharpe", "Sharpe Ratio"),
+    ("cagr", "CAGR"),
+    ("max_drawdown", "Max Drawdown (lowest)"),
+    ("win_rate", "Win Rate"),
+    ("profit_factor", "Profit Factor"),
+];
 
-                // Chart border
-                rect {
-                    x: "{chart_left}",
-                    y: "{chart_top}",
-                    width: "{chart_right - chart_left}",
-                    height: "{chart_bottom - chart_top}",
-                    fill: "none",
-                    stroke: "#999",
-                    stroke_width: "2"
-                }
+#[derive(Clone, Debug, Serialize)]
+struct DepositRequest {
+    amount: f64,
+}
 
-                // Price line
-                path {
-                    d: "{path_data}",
-                    fill: "none",
-                    stroke: "#2196F3",
-                    stroke_width: "2",
-                }
+#[derive(Clone, Debug, Serialize)]
+struct WithdrawalRequest {
+    amount: f64,
+}
 
-                // Indicator overlays (SMA/EMA)
-                if let Some(ref indicators) = props.indicator_data {
-                    // SMA(20) overlay - Orange
-                    if let Some(sma_20) = indicators.indicators.get("sma_20") {
-                        {
-                            let mut sma_path = String::from("M ");
-                            let mut first_valid = true;
-                            for (i, value_opt) in sma_20.iter().enumerate() {
-                                if let Some(value) = value_opt {
-                                    let x = padding_left + (i as f64 / (sma_20.len() - 1) as f64) * (width - padding_left - padding_right);
-                                    let y = height - padding_bottom - ((value - min_price) / price_range) * (height - padding_top - padding_bottom);
-                                    if first_valid {
-                                        sma_path.push_str(&format!("{} {} ", x, y));
-                                        first_valid = false;
-                                    } else {
-                                        sma_path.push_str(&format!("L {} {} ", x, y));
-                                    }
-                                }
-                            }
-                            rsx! {
-                                path {
-                                    d: "{sma_path}",
-                                    fill: "none",
-                                    stroke: "#FF9800",
-                                    stroke_width: "2",
-                                    opacity: "0.8"
-                                }
-                            }
-                        }
-                    }
+const API_BASE: &str = "http://localhost:3000/api";
 
-                    // SMA(50) overlay - Purple
-                    if let Some(sma_50) = indicators.indicators.get("sma_50") {
-                        {
-                            let mut sma_path = String::from("M ");
-                            let mut first_valid = true;
-                            for (i, value_opt) in sma_50.iter().enumerate() {
-                                if let Some(value) = value_opt {
-                                    let x = padding_left + (i as f64 / (sma_50.len() - 1) as f64) * (width - padding_left - padding_right);
-                                    let y = height - padding_bottom - ((value - min_price) / price_range) * (height - padding_top - padding_bottom);
-                                    if first_valid {
-                                        sma_path.push_str(&format!("{} {} ", x, y));
-                                        first_valid = false;
-                                    } else {
-                                        sma_path.push_str(&format!("L {} {} ", x, y));
-                                    }
-                                }
-                            }
-                            rsx! {
-                                path {
-                                    d: "{sma_path}",
-                                    fill: "none",
-                                    stroke: "#9C27B0",
-                                    stroke_width: "2",
-                                    opacity: "0.8"
-                                }
-                            }
-                        }
-                    }
+/// Display metadata for one asset from `GET /api/assets`
+#[derive(Clone, Debug, Deserialize)]
+struct AssetInfo {
+    asset: String,
+    name: String,
+    symbol: String,
+    decimals: usize, // Amounts of the asset, and prices quoted in it, are shown to this many places
+    icon: String,
+}
 
-                    // EMA(12) overlay - Teal
-                    if let Some(ema_12) = indicators.indicators.get("ema_12") {
-                        {
-                            let mut ema_path = String::from("M ");
-                            let mut first_valid = true;
-                            for (i, value_opt) in ema_12.iter().enumerate() {
-                                if let Some(value) = value_opt {
-                                    let x = padding_left + (i as f64 / (ema_12.len() - 1) as f64) * (width - padding_left - padding_right);
-                                    let y = height - padding_bottom - ((value - min_price) / price_range) * (height - padding_top - padding_bottom);
-                                    if first_valid {
-                                        ema_path.push_str(&format!("{} {} ", x, y));
-                                        first_valid = false;
-                                    } else {
-                                        ema_path.push_str(&format!("L {} {} ", x, y));
-                                    }
-                                }
-                            }
-                            rsx! {
-                                path {
-                                    d: "{ema_path}",
-                                    fill: "none",
-                                    stroke: "#009688",
-                                    stroke_width: "2",
-                                    opacity: "0.8"
-                                }
-                            }
-                        }
-                    }
+/// A strategy bots can run, from `GET /api/bots/available`
+#[derive(Clone, Debug, Deserialize)]
+struct AvailableBot {
+    strategy: String,
+    name: String,
+    description: String,
+    #[serde(default)]
+    default_parameters: serde_json::Value,
+}
 
-                    // EMA(26) overlay - Deep Orange
-                    if let Some(ema_26) = indicators.indicators.get("ema_26") {
-                        {
-                            let mut ema_path = String::from("M ");
-                            let mut first_valid = true;
-                            for (i, value_opt) in ema_26.iter().enumerate() {
-                                if let Some(value) = value_opt {
-                                    let x = padding_left + (i as f64 / (ema_26.len() - 1) as f64) * (width - padding_left - padding_right);
-                                    let y = height - padding_bottom - ((value - min_price) / price_range) * (height - padding_top - padding_bottom);
-                                    if first_valid {
-                                        ema_path.push_str(&format!("{} {} ", x, y));
-                                        first_valid = false;
-                                    } else {
-                                        ema_path.push_str(&format!("L {} {} ", x, y));
-                                    }
-                                }
-                            }
-                            rsx! {
-                                path {
-                                    d: "{ema_path}",
-                                    fill: "none",
-                                    stroke: "#FF5722",
-                                    stroke_width: "2",
-                                    opacity: "0.8"
-                                }
-                            }
-                        }
-                    }
-                }
+/// Asset metadata by ticker, loaded at startup; empty until then
+static ASSET_INFO: GlobalSignal<HashMap<String, AssetInfo>> = Signal::global(HashMap::new);
 
-                // Crosshair lines
-                if let Some(x) = hover_x() {
-                    line {
-                        x1: "{x}",
-                        y1: "{chart_top}",
-                        x2: "{x}",
-                        y2: "{chart_bottom}",
-                        stroke: "#666",
-                        stroke_width: "1",
-                        stroke_dasharray: "4,4",
-                        pointer_events: "none"
-                    }
-                }
-                if let Some(y) = hover_y() {
-                    line {
-                        x1: "{chart_left}",
-                        y1: "{y}",
-                        x2: "{chart_right}",
-                        y2: "{y}",
-                        stroke: "#666",
-                        stroke_width: "1",
-                        stroke_dasharray: "4,4",
-                        pointer_events: "none"
-                    }
-                }
+// Stream reconnect delay: doubled after each failed attempt, reset once messages arrive
+const RECONNECT_MIN_MS: u32 = 1_000;
+const RECONNECT_MAX_MS: u32 = 30_000;
 
-                // Axis labels
-                text {
-                    x: "{chart_left - 60.0}",
-                    y: "{(chart_top + chart_bottom) / 2.0}",
-                    font_size: "14",
-                    fill: "#333",
-                    text_anchor: "middle",
-                    transform: "rotate(-90 {chart_left - 60.0} {(chart_top + chart_bottom) / 2.0})",
-                    "{price_label}"
-                }
-                text {
-                    x: "{(chart_left + chart_right) / 2.0}",
-                    y: "{height - 10.0}",
-                    font_size: "14",
-                    fill: "#333",
-                    text_anchor: "middle",
-                    "Time"
-                }
-            }
+/// Shown until the first `/api/stats` response says which assets the backend polls
+const DEFAULT_ASSETS: &[&str] = &["BTC", "ETH"];
+/// The one cross pair offered (priced from both USD feeds) when both assets are polled
+const CROSS_PAIR: (&str, &str) = ("BTC", "ETH");
 
-            // Tooltip
-            if let Some(price) = hover_price() {
-                if let Some(time) = hover_time() {
-                    if let (Some(x), Some(y)) = (hover_x(), hover_y()) {
-                        div {
-                            style: "position: absolute; left: {x + 10.0}px; top: {y - 40.0}px; background: rgba(0,0,0,0.8); color: white; padding: 8px 12px; border-radius: 4px; font-size: 12px; pointer-events: none; white-space: nowrap;",
-                            div {
-                                {
-                                    let dt = chrono::DateTime::from_timestamp(time, 0).unwrap();
-                                    format!("{:02}:{:02}:{:02}", dt.hour(), dt.minute(), dt.second())
-                                }
-                            }
-                            div {
-                                {
-                                    if quote_asset == "USD" {
-                                        format!("${:.2}", price)
-                                    } else {
-                                        format!("{:.4} {}", price, quote_asset)
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+/// localStorage key holding the session token, so a reload stays logged in
+const SESSION_STORAGE_KEY: &str = "session_token";
+const THEME_STORAGE_KEY: &str = "theme";
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+fn load_session_token() -> Option<String> {
+    local_storage()?.get_item(SESSION_STORAGE_KEY).ok()?.filter(|t| !t.is_empty())
+}
+
+/// The theme last used in this browser, so the login page and guests keep it
+fn load_theme() -> Theme {
+    local_storage()
+        .and_then(|storage| storage.get_item(THEME_STORAGE_KEY).ok().flatten())
+        .and_then(|theme| Theme::parse(&theme))
+        .unwrap_or(Theme::Light)
+}
+
+fn store_theme(theme: Theme) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.set_item(THEME_STORAGE_KEY, theme.as_str());
     }
 }
 
-fn CandlestickChart(props: CandlestickChartProps) -> Element {
-    let candles = props.candles.clone();
-    let quote_asset = props.quote_asset.clone();
+/// Remember the token, or forget it with None
+fn store_session_token(token: Option<&str>) {
+    let Some(storage) = local_storage() else {
+        return;
+    };
+    let _ = match token {
+        Some(token) => storage.set_item(SESSION_STORAGE_KEY, token),
+        None => storage.remove_item(SESSION_STORAGE_KEY),
+    };
+}
 
-    if candles.is_empty() {
-        return rsx! { p { "No candlestick data available" } };
+/// Attach the session token (guests have none)
+fn with_session(request: reqwest::RequestBuilder, token: &str) -> reqwest::RequestBuilder {
+    if token.is_empty() {
+        request
+    } else {
+        request.bearer_auth(token)
     }
+}
 
-    // Hover state
-    let mut hover_candle_idx = use_signal(|| None::<usize>);
+fn api_get(url: String, token: &str) -> reqwest::RequestBuilder {
+    with_session(reqwest::Client::new().get(url), token)
+}
 
-    let width = 1000.0;
-    let height = 300.0;
-    let padding_left = 80.0;
-    let padding_right = 40.0;
-    let padding_top = 40.0;
-    let padding_bottom = 60.0;
+fn api_post(url: String, token: &str) -> reqwest::RequestBuilder {
+    with_session(reqwest::Client::new().post(url), token)
+}
 
-    // Find min/max prices
-    let mut min_price = f64::INFINITY;
-    let mut max_price = f64::NEG_INFINITY;
-    for candle in &candles {
-        min_price = min_price.min(candle.low);
-        max_price = max_price.max(candle.high);
-    }
-    let price_range = if (max_price - min_price).abs() < 0.01 { 1.0 } else { max_price - min_price };
+fn api_delete(url: String, token: &str) -> reqwest::RequestBuilder {
+    with_session(reqwest::Client::new().delete(url), token)
+}
 
-    let chart_width = width - padding_left - padding_right;
-    let candle_spacing = chart_width / candles.len() as f64;
-    let candle_width = (candle_spacing * 0.7).max(2.0);
+fn api_patch(url: String, token: &str) -> reqwest::RequestBuilder {
+    with_session(reqwest::Client::new().patch(url), token)
+}
 
-    let price_label = if quote_asset == "USD" {
-        "Price ($)".to_string()
-    } else {
-        format!("Price ({})", quote_asset)
-    };
+/// WebSocket URL for an API path (browsers can't set headers on WebSockets or EventSources,
+/// so streams that need the session take it as `?token=`)
+fn ws_url(path: &str) -> String {
+    format!("{}{}", API_BASE.replacen("http", "ws", 1), path)
+}
 
-    // Build SVG elements as strings
-    let mut svg_elements = String::new();
+async fn reconnect_after(delay: &mut u32) {
+    gloo_timers::future::TimeoutFuture::new(*delay).await;
+    *delay = (*delay * 2).min(RECONNECT_MAX_MS);
+}
 
-    // Grid lines and labels
-    for i in 0..5 {
-        let y = padding_top + (i as f64 / 4.0) * (height - padding_top - padding_bottom);
-        let price = max_price - (i as f64 / 4.0) * price_range;
-        svg_elements.push_str(&format!(
-            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#e0e0e0\" stroke-width=\"1\"/>",
-            padding_left, y, width - padding_right, y
-        ));
-        svg_elements.push_str(&format!(
-            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"12\" fill=\"#666\">{:.2}</text>",
-            padding_left - 10.0, y + 5.0, price
-        ));
-    }
+// Color scheme constants; each names a CSS variable whose value comes from the theme
+const COLOR_NAVY: &str = "var(--color-navy)";
+const COLOR_PAGE_BG: &str = "var(--color-page-bg)";
+const COLOR_CONTENT_BG: &str = "var(--color-content-bg)";
+const COLOR_DARK_GREY: &str = "var(--color-dark-grey)";
+const COLOR_LIGHT_GREY: &str = "var(--color-light-grey)";
+const COLOR_GREEN: &str = "var(--color-green)";
+const COLOR_RED: &str = "var(--color-red)";
+
+// Theme palettes: (variable, value)
+// Styles without a constant use var(--color-border), var(--color-divider), var(--color-surface) and var(--color-success-bg) directly
+const LIGHT_PALETTE: &[(&str, &str)] = &[
+    ("navy", "#1a237e"),
+    ("page-bg", "#FBFCF8"),
+    ("content-bg", "#fefefe"),
+    ("dark-grey", "#424242"),
+    ("light-grey", "#757575"),
+    ("green", "#4caf50"),
+    ("red", "#f44336"),
+    ("border", "#ddd"),
+    ("divider", "#e0e0e0"),
+    ("surface", "#f5f5f5"),
+    ("success-bg", "#e8f5e9"),
+];
+const DARK_PALETTE: &[(&str, &str)] = &[
+    ("navy", "#5c6bc0"),
+    ("page-bg", "#121212"),
+    ("content-bg", "#1e1e1e"),
+    ("dark-grey", "#e0e0e0"),
+    ("light-grey", "#9e9e9e"),
+    ("green", "#66bb6a"),
+    ("red", "#ef5350"),
+    ("border", "#424242"),
+    ("divider", "#333333"),
+    ("surface", "#2a2a2a"),
+    ("success-bg", "#1b3320"),
+];
+
+/// Light or dark colors, stored in the user's `theme` setting
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Theme {
+    Light,
+    Dark,
+}
 
-    let time_span = candles.last().unwrap().timestamp - candles.first().unwrap().timestamp;
-    for i in 0..6 {
-        let x = padding_left + (i as f64 / 5.0) * chart_width;
-        let timestamp = candles.first().unwrap().timestamp + ((time_span as f64 * i as f64 / 5.0) as i64);
-        let dt = chrono::DateTime::from_timestamp(timestamp, 0).unwrap();
-        svg_elements.push_str(&format!(
-            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#e0e0e0\" stroke-width=\"1\"/>",
-            x, padding_top, x, height - padding_bottom
-        ));
-        svg_elements.push_str(&format!(
-            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\" fill=\"#666\">{:02}:{:02}</text>",
-            x, height - padding_bottom + 20.0, dt.hour(), dt.minute()
-        ));
+impl Theme {
+    fn as_str(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
     }
 
-    // Draw candlesticks
-    for (i, candle) in candles.iter().enumerate() {
-        let x_center = padding_left + (i as f64 + 0.5) * candle_spacing;
-        let open_y = height - padding_bottom - ((candle.open - min_price) / price_range) * (height - padding_top - padding_bottom);
-        let close_y = height - padding_bottom - ((candle.close - min_price) / price_range) * (height - padding_top - padding_bottom);
-        let high_y = height - padding_bottom - ((candle.high - min_price) / price_range) * (height - padding_top - padding_bottom);
-        let low_y = height - padding_bottom - ((candle.low - min_price) / price_range) * (height - padding_top - padding_bottom);
-
-        let is_bullish = candle.close >= candle.open;
-        let color = if is_bullish { "#26a69a" } else { "#ef5350" };
-        let body_height = (open_y - close_y).abs().max(1.0);
-        let body_y = open_y.min(close_y);
-
-        // High-low wick
-        svg_elements.push_str(&format!(
-            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"1\"/>",
-            x_center, high_y, x_center, low_y, color
-        ));
-        // Body
-        svg_elements.push_str(&format!(
-            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
-            x_center - candle_width / 2.0, body_y, candle_width, body_height, color, color
-        ));
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            _ => None,
+        }
     }
 
-    // Axis labels
-    svg_elements.push_str(&format!(
-        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\" font-weight=\"bold\" fill=\"#333\" transform=\"rotate(-90 {} {})\">{}</text>",
-        padding_left / 2.0, height / 2.0, padding_left / 2.0, height / 2.0, price_label
-    ));
-    svg_elements.push_str(&format!(
-        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\" font-weight=\"bold\" fill=\"#333\">Time</text>",
-        width / 2.0, height - 10.0
-    ));
+    fn toggled(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
 
-    // Clone values needed for closures
-    let padding_left_clone = padding_left;
-    let chart_width_clone = chart_width;
-    let candles_len = candles.len();
+    /// Stylesheet defining the palette's CSS variables for the whole page
+    fn stylesheet(self) -> String {
+        let palette = match self {
+            Theme::Light => LIGHT_PALETTE,
+            Theme::Dark => DARK_PALETTE,
+        };
+        let variables: String = palette
+            .iter()
+            .map(|(name, value)| format!("--color-{}: {}; ", name, value))
+            .collect();
+        format!(
+            ":root {{ {}color-scheme: {}; }} body {{ background: var(--color-page-bg); color: var(--color-dark-grey); }}",
+            variables,
+            self.as_str()
+        )
+    }
+}
 
-    // Build tooltip HTML if hovering
-    let tooltip_html = if let Some(idx) = hover_candle_idx() {
-        if idx < candles.len() {
-            let candle = &candles[idx];
-            let dt = chrono::DateTime::from_timestamp(candle.timestamp, 0).unwrap();
-            let x_pos = padding_left_clone + (idx as f64 + 0.5) * candle_spacing;
-            Some(format!(
-                "<div style=\"position: absolute; left: {}px; top: {}px; background: rgba(0,0,0,0.85); color: white; padding: 8px; border-radius: 4px; pointer-events: none; font-size: 12px; white-space: nowrap; z-index: 1000; transform: translateX(-50%);\"><div>{}</div><div>Open: ${:.2}</div><div>High: ${:.2}</div><div>Low: ${:.2}</div><div>Close: ${:.2}</div></div>",
-                x_pos, 10.0,
-                dt.format("%Y-%m-%d %H:%M"),
-                candle.open, candle.high, candle.low, candle.close
-            ))
-        } else {
-            None
-        }
-    } else {
-        None
-    };
+// Market colors, in the order markets are listed
+const MARKET_COLORS: &[&str] = &["#2196F3", "#9c27b0", "#ff9800", "#009688", "#e91e63", "#795548", "#607d8b", "#3f51b5"];
+// Pie chart slices for held assets (cash is always green)
+const SLICE_COLORS: &[&str] = &["#5C6BC0", "#42A5F5", "#AB47BC", "#FFA726", "#26A69A", "#EF5350", "#8D6E63", "#78909C"];
 
-    rsx! {
-        div {
-            style: "position: relative;",
-            div {
-                dangerous_inner_html: format!(
-                    "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" style=\"display: block; margin: 0 auto; background: white;\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#fefefe\"/>{}</svg>",
-                    width, height, width, height,
-                    padding_left, padding_top,
-                    width - padding_left - padding_right,
-                    height - padding_top - padding_bottom,
-                    svg_elements
-                )
-            }
+// Typography - Inter for headers, system fonts for body
+const FONT_HEADER: &str = "'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif";
+const FONT_BODY: &str = "-apple-system, BlinkMacSystemFont, 'Segoe UI', 'Roboto', sans-serif";
 
-            // Transparent overlay for mouse events
-            div {
-                style: format!(
-                    "position: absolute; left: {}px; top: {}px; width: {}px; height: {}px; cursor: crosshair;",
-                    padding_left, padding_top, chart_width, height - padding_top - padding_bottom
-                ),
-                onmousemove: move |evt| {
-                    let rect_x = evt.data.element_coordinates().x;
-                    let candle_idx = (rect_x / (chart_width_clone / candles_len as f64)).floor() as usize;
-                    if candle_idx < candles_len {
-                        hover_candle_idx.set(Some(candle_idx));
-                    }
-                },
-                onmouseleave: move |_| {
-                    hover_candle_idx.set(None);
-                }
-            }
+/// Indicator kinds the indicators endpoint computes
+const INDICATOR_KINDS: [&str; 3] = ["sma", "ema", "rsi"];
+/// Periods the indicators endpoint accepts
+const INDICATOR_PERIODS: std::ops::RangeInclusive<u32> = 2..=200;
+const MAX_CHART_INDICATORS: usize = 8;
+/// Overlay line colors, by position among the enabled overlays
+const OVERLAY_COLORS: [&str; 6] = ["#FF9800", "#9C27B0", "#009688", "#FF5722", "#795548", "#3F51B5"];
+
+/// The indicators offered before a user configures any
+fn default_chart_indicators() -> Vec<IndicatorSetting> {
+    vec![
+        IndicatorSetting::new("sma", 20),
+        IndicatorSetting::new("sma", 50),
+        IndicatorSetting::new("ema", 12),
+        IndicatorSetting::new("ema", 26),
+        IndicatorSetting::new("rsi", 14),
+    ]
+}
 
-            // Tooltip
-            if let Some(html) = tooltip_html {
-                div {
-                    dangerous_inner_html: html
-                }
-            }
+/// Keys of the enabled indicators, once each, in the order configured
+fn enabled_indicator_keys(indicators: &[IndicatorSetting]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for key in indicators.iter().filter(|i| i.enabled).map(|i| i.key()) {
+        if !keys.contains(&key) {
+            keys.push(key);
         }
     }
+    keys
 }
 
-#[component]
-fn RSIPanel(props: RSIPanelProps) -> Element {
-    if props.rsi_values.is_empty() {
-        return rsx! { p { "No RSI data available" } };
-    }
+/// Enabled price overlays with their line colors
+fn overlay_indicators(indicators: &[IndicatorSetting]) -> Vec<(String, &'static str)> {
+    let overlays: Vec<IndicatorSetting> = indicators.iter().filter(|i| i.is_overlay()).cloned().collect();
+    enabled_indicator_keys(&overlays)
+        .into_iter()
+        .enumerate()
+        .map(|(i, key)| (key, OVERLAY_COLORS[i % OVERLAY_COLORS.len()]))
+        .collect()
+}
 
-    let width = 1000.0;
-    let height = 120.0;
-    let padding_left = 60.0;
-    let padding_right = 20.0;
-    let padding_top = 10.0;
-    let padding_bottom = 30.0;
+/// Notifications kept in the notification center
+const MAX_NOTIFICATIONS: usize = 50;
+/// How long a toast stays up
+const TOAST_MS: u32 = 6_000;
 
-    // RSI range is 0-100
-    let min_rsi = 0.0;
-    let max_rsi = 100.0;
-    let rsi_range = max_rsi - min_rsi;
+fn notification_color(level: &str) -> &'static str {
+    match level {
+        "success" => COLOR_GREEN,
+        "error" => COLOR_RED,
+        _ => COLOR_NAVY,
+    }
+}
 
-    let mut svg_elements = String::new();
+/// Places `asset` amounts are shown to, from the registry (cents and 6 places until it loads)
+fn asset_decimals(asset: &str) -> usize {
+    ASSET_INFO
+        .read()
+        .get(asset)
+        .map_or(if asset == "USD" { 2 } else { 6 }, |info| info.decimals)
+}
 
-    // Draw reference lines at 30 and 70
-    // Line at 70 (overbought)
-    let y_70 = height - padding_bottom - ((70.0 - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
-    svg_elements.push_str(&format!(
-        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#ef5350\" stroke-width=\"1\" stroke-dasharray=\"4,2\" opacity=\"0.6\"/>",
-        padding_left, y_70, width - padding_right, y_70
-    ));
-    svg_elements.push_str(&format!(
-        "<text x=\"{}\" y=\"{}\" fill=\"#ef5350\" font-size=\"10\" text-anchor=\"end\">70</text>",
-        padding_left - 5.0, y_70 + 4.0
-    ));
+/// `amount` to the asset's display decimals, without the ticker
+fn format_amount(amount: f64, asset: &str) -> String {
+    format!("{:.*}", asset_decimals(asset), amount)
+}
 
-    // Line at 30 (oversold)
-    let y_30 = height - padding_bottom - ((30.0 - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
-    svg_elements.push_str(&format!(
-        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#66BB6A\" stroke-width=\"1\" stroke-dasharray=\"4,2\" opacity=\"0.6\"/>",
-        padding_left, y_30, width - padding_right, y_30
-    ));
-    svg_elements.push_str(&format!(
-        "<text x=\"{}\" y=\"{}\" fill=\"#66BB6A\" font-size=\"10\" text-anchor=\"end\">30</text>",
-        padding_left - 5.0, y_30 + 4.0
-    ));
+/// A USD amount as dollars, e.g. "$12.50"
+fn usd_amount(amount: f64) -> String {
+    format!("${}", format_amount(amount, "USD"))
+}
 
-    // Draw Y-axis labels
-    for &val in &[0.0, 50.0, 100.0] {
-        let y = height - padding_bottom - ((val - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
-        svg_elements.push_str(&format!(
-            "<text x=\"{}\" y=\"{}\" fill=\"#666\" font-size=\"10\" text-anchor=\"end\">{}</text>",
-            padding_left - 5.0, y + 4.0, val as i32
-        ));
-        svg_elements.push_str(&format!(
-            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#969696\" stroke-width=\"1\"/>",
-            padding_left, y, width - padding_right, y
-        ));
+/// A timezone as its offset from UTC, e.g. "UTC-05:00"
+fn utc_offset_label(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    format!("UTC{}{:02}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
+
+/// Amount of the quote asset, as dollars when it is USD
+fn quote_amount_label(amount: f64, quote_asset: &str) -> String {
+    if quote_asset == "USD" {
+        usd_amount(amount)
+    } else {
+        format!("{} {}", format_amount(amount, quote_asset), quote_asset)
     }
+}
 
-    // Draw RSI line
-    let mut rsi_path = String::from("M ");
-    let mut first_valid = true;
-    for (i, value_opt) in props.rsi_values.iter().enumerate() {
-        if let Some(value) = value_opt {
-            let x = padding_left + (i as f64 / (props.rsi_values.len() - 1) as f64) * (width - padding_left - padding_right);
-            let y = height - padding_bottom - ((value - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
-            if first_valid {
-                rsi_path.push_str(&format!("{} {} ", x, y));
-                first_valid = false;
-            } else {
-                rsi_path.push_str(&format!("L {} {} ", x, y));
+/// Toast text and level for a bot stream message; None for ticks and status snapshots
+/// `pair` is the running bot's market, for events that don't carry it
+fn bot_event_message(event: &BotStreamMessage, pair: (&str, &str)) -> Option<(String, &'static str)> {
+    let name = &event.bot_name;
+    match event.event.as_str() {
+        "started" => Some((
+            format!(
+                "{} started on {}/{}",
+                name,
+                event.base_asset.as_deref().unwrap_or(pair.0),
+                event.quote_asset.as_deref().unwrap_or(pair.1)
+            ),
+            "info",
+        )),
+        "trade_executed" => {
+            let decision = event.decision.as_ref()?;
+            let action = match decision.action.as_str() {
+                "buy" => format!("bought {} {}", quote_amount_label(decision.quote_amount, pair.1), pair.0),
+                "sell" => format!("sold {} {}", quote_amount_label(decision.quote_amount, pair.1), pair.0),
+                "buy_base" => format!("bought {} {}", format_amount(decision.quantity, pair.0), pair.0),
+                "sell_base" => format!("sold {} {}", format_amount(decision.quantity, pair.0), pair.0),
+                "close_position" => format!("closed its {} position", pair.0),
+                _ => return None,
+            };
+            Some((format!("{} {}", name, action), "success"))
+        }
+        "position_capped" => {
+            let cap = format_amount(event.max_base_position.unwrap_or_default(), pair.0);
+            match event.allowed {
+                Some(allowed) if allowed > 0.0 => None, // Reported by the trade_executed that follows
+                _ => Some((format!("{} skipped a buy: already holds its {} {} limit", name, cap, pair.0), "info")),
             }
         }
+        "stoploss_hit" => Some((format!("Bot stopped: {}", event.reason.as_deref().unwrap_or("stoploss breached")), "error")),
+        "error" => Some((format!("{} error: {}", name, event.message.as_deref().unwrap_or("unknown")), "error")),
+        // A stoploss was already reported by stoploss_hit
+        "stopped" => match event.reason.as_deref() {
+            Some(reason) if reason.starts_with("Stoploss breached") => None,
+            Some(reason) => Some((format!("{} stopped: {}", name, reason), "info")),
+            None => Some((format!("{} stopped", name), "info")),
+        },
+        _ => None,
     }
-    svg_elements.push_str(&format!(
-        "<path d=\"{}\" fill=\"none\" stroke=\"#9C27B0\" stroke-width=\"2\"/>",
-        rsi_path
-    ));
+}
 
-    // Y-axis label
-    svg_elements.push_str(&format!(
-        "<text x=\"{}\" y=\"{}\" fill=\"#666\" font-size=\"12\" text-anchor=\"middle\" font-weight=\"bold\">RSI(14)</text>",
-        padding_left / 2.0, height / 2.0
-    ));
+/// Toast text and level for an account notification
+fn notification_message(n: &NotificationEvent) -> Option<(String, &'static str)> {
+    let base = n.base_asset.as_deref().unwrap_or("");
+    let quote = n.quote_asset.as_deref().unwrap_or("USD");
+    let side = match n.side {
+        Some(TradeSide::Buy) => "Buy",
+        Some(TradeSide::Sell) => "Sell",
+        None => "",
+    };
+    match n.kind.as_str() {
+        "price_alert" => Some((
+            format!(
+                "Alert: {} {} {} (now {})",
+                n.asset.as_deref().unwrap_or(""),
+                n.condition.as_deref().unwrap_or("").replace('_', " "),
+                n.threshold.unwrap_or(0.0),
+                usd_amount(n.price.unwrap_or(0.0))
+            ),
+            "info",
+        )),
+        "copy_trade" => Some((
+            format!(
+                "Copied {}: {} {} {} at {}",
+                n.leader_username.as_deref().unwrap_or("leader"),
+                side.to_lowercase(),
+                format_amount(n.quantity.unwrap_or(0.0), base),
+                base,
+                quote_amount_label(n.price.unwrap_or(0.0), quote)
+            ),
+            "success",
+        )),
+        "order_filled" => Some((
+            format!(
+                "{} {} order filled: {} {} at {}",
+                side,
+                n.order_type.as_deref().unwrap_or(""),
+                format_amount(n.quantity.unwrap_or(0.0), base),
+                base,
+                quote_amount_label(n.price.unwrap_or(0.0), quote)
+            ),
+            "success",
+        )),
+        "order_failed" => Some((
+            format!(
+                "{} {} order for {} failed: {}",
+                side,
+                n.order_type.as_deref().unwrap_or(""),
+                base,
+                n.reason.as_deref().unwrap_or("unknown reason")
+            ),
+            "error",
+        )),
+        "achievement_earned" => Some((
+            format!(
+                "Achievement unlocked: {} ({})",
+                n.title.as_deref().unwrap_or(""),
+                n.details.as_deref().unwrap_or("")
+            ),
+            "success",
+        )),
+        "daily_summary" => Some((
+            format!(
+                "Daily summary for {}: {}{} with {} trades",
+                n.date.as_deref().unwrap_or("today"),
+                if n.pnl_usd.unwrap_or(0.0) < 0.0 { "-" } else { "+" },
+                quote_amount_label(n.pnl_usd.unwrap_or(0.0).abs(), "USD"),
+                n.trades.unwrap_or(0)
+            ),
+            "info",
+        )),
+        // Pushed to webhook endpoints; the bot activity feed already toasts these
+        "bot_started" | "bot_trade" | "stoploss_hit" | "bot_stopped" => None,
+        _ => None,
+    }
+}
 
-    rsx! {
-        div {
-            style: "margin-top: 20px;",
-            div {
-                dangerous_inner_html: format!(
-                    "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" style=\"display: block; margin: 0 auto; background: white;\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#fefefe\"/>{}</svg>",
-                    width, height, width, height,
-                    padding_left, padding_top,
-                    width - padding_left - padding_right,
-                    height - padding_top - padding_bottom,
-                    svg_elements
-                )
+fn format_timestamp(timestamp: &str) -> String {
+    // Parse ISO 8601 timestamp and format it nicely
+    // Example input: "2025-01-22T10:30:00.123456789Z"
+    // Example output: "Jan 22, 10:30"
+    if let Some(date_part) = timestamp.split('T').next() {
+        if let Some(time_part) = timestamp.split('T').nth(1) {
+            let time = time_part.split(':').take(2).collect::<Vec<_>>().join(":");
+            if let Some(stripped_date) = date_part.strip_prefix("2025-") {
+                return format!("{} {}", stripped_date, time);
             }
         }
     }
+    // Fallback to showing the raw timestamp if parsing fails
+    timestamp.to_string()
 }
 
-#[derive(Clone, PartialEq, Props)]
-struct HeaderProps {
-    current_view: AppView,
-    username: String,
-    on_navigate: EventHandler<AppView>,
-    on_logout: EventHandler<()>,
+/// Markets to offer: every polled asset against USD, then the cross pair if both legs are polled
+fn market_list(assets: &[String]) -> Vec<String> {
+    let mut markets = assets.to_vec();
+    let (base, quote) = CROSS_PAIR;
+    if assets.iter().any(|a| a == base) && assets.iter().any(|a| a == quote) {
+        markets.push(format!("{}/{}", base, quote));
+    }
+    markets
 }
 
-#[component]
-fn Header(props: HeaderProps) -> Element {
-    let mut show_markets_dropdown = use_signal(|| false);
+/// Split a market ("BTC" or "BTC/ETH") into base and quote assets
+fn split_market(market: &str) -> (String, String) {
+    match market.split_once('/') {
+        Some((base, quote)) => (base.to_string(), quote.to_string()),
+        None => (market.to_string(), "USD".to_string()),
+    }
+}
 
-    rsx! {
-        div {
-            style: format!(
-                "background: {}; color: white; padding: 15px 30px; display: flex; justify-content: space-between; align-items: center; box-shadow: 0 2px 4px rgba(0,0,0,0.1);",
-                COLOR_NAVY
-            ),
+/// The asset's full name from the registry, or its ticker
+fn asset_name(asset: &str) -> String {
+    ASSET_INFO.read().get(asset).map_or_else(|| asset.to_string(), |info| info.name.clone())
+}
 
-            // Left: App title (clickable to Dashboard)
-            div {
-                onclick: move |_| props.on_navigate.call(AppView::Dashboard),
-                style: format!("font-size: 24px; font-weight: 600; cursor: pointer; font-family: {};", FONT_HEADER),
-                "Trading Simulator"
-            }
+/// Price of `base` in `quote`, from the USD prices (0 while either is unknown)
+fn market_price(prices: &HashMap<String, f64>, base: &str, quote: &str) -> f64 {
+    let base_usd = prices.get(base).copied().unwrap_or(0.0);
+    if quote == "USD" {
+        return base_usd;
+    }
+    let quote_usd = prices.get(quote).copied().unwrap_or(0.0);
+    if base_usd > 0.0 && quote_usd > 0.0 { base_usd / quote_usd } else { 0.0 }
+}
 
-            // Right: Navigation
-            div {
-                style: "display: flex; gap: 20px; align-items: center; position: relative;",
-
-                // Dashboard link
-                div {
-                    onclick: move |_| props.on_navigate.call(AppView::Dashboard),
-                    style: format!(
-                        "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
-                        if matches!(props.current_view, AppView::Dashboard) { "rgba(255,255,255,0.2)" } else { "transparent" },
-                        FONT_BODY
-                    ),
-                    "Dashboard"
-                }
+/// Price history of `base` in `quote`, matching timestamps of the two USD histories
+fn market_history(histories: &HashMap<String, Vec<PricePoint>>, base: &str, quote: &str) -> Vec<PricePoint> {
+    let base_hist = histories.get(base).cloned().unwrap_or_default();
+    if quote == "USD" {
+        return base_hist;
+    }
+    let quote_hist = histories.get(quote).map(Vec::as_slice).unwrap_or(&[]);
+    base_hist
+        .iter()
+        .filter_map(|base_point| {
+            let quote_point = quote_hist.iter().find(|q| q.timestamp == base_point.timestamp)?;
+            (quote_point.price > 0.0).then(|| PricePoint {
+                timestamp: base_point.timestamp,
+                price: base_point.price / quote_point.price,
+            })
+        })
+        .collect()
+}
 
-                // Markets dropdown
-                div {
-                    style: "position: relative;",
-                    div {
-                        onclick: move |_| show_markets_dropdown.set(!show_markets_dropdown()),
-                        style: format!(
-                            "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
-                            if matches!(props.current_view, AppView::Markets | AppView::Trading(_)) { "rgba(255,255,255,0.2)" } else { "transparent" },
-                            FONT_BODY
-                        ),
-                        "Markets ▾"
-                    }
+/// Notification groups that can be muted in the `notification_preferences` setting, with their labels
+const NOTIFICATION_CATEGORIES: &[(&str, &str)] = &[
+    ("alerts", "Price alerts"),
+    ("orders", "Order fills and failures"),
+    ("copy_trades", "Copied trades"),
+    ("bots", "Bot activity"),
+    ("achievements", "Achievements"),
+    ("summaries", "Daily summaries"),
+];
+
+/// Preference group of an account notification kind
+fn notification_category(kind: &str) -> &'static str {
+    match kind {
+        "price_alert" => "alerts",
+        "copy_trade" => "copy_trades",
+        "bot_started" | "bot_trade" | "stoploss_hit" | "bot_stopped" => "bots",
+        "achievement_earned" => "achievements",
+        "daily_summary" => "summaries",
+        _ => "orders",
+    }
+}
 
-                    if show_markets_dropdown() {
-                        div {
-                            style: format!(
-                                "position: absolute; top: 100%; right: 0; margin-top: 8px; background: {}; border-radius: 4px; box-shadow: 0 4px 12px rgba(0,0,0,0.15); min-width: 150px; z-index: 1000;",
-                                COLOR_CONTENT_BG
-                            ),
-                            div {
-                                onclick: move |_| {
-                                    show_markets_dropdown.set(false);
-                                    props.on_navigate.call(AppView::Markets);
-                                },
-                                style: format!("padding: 12px 16px; cursor: pointer; color: {}; font-family: {}; border-bottom: 1px solid #e0e0e0;", COLOR_DARK_GREY, FONT_BODY),
-                                "All Markets"
-                            }
-                            div {
-                                onclick: move |_| {
-                                    show_markets_dropdown.set(false);
-                                    props.on_navigate.call(AppView::Trading("BTC".to_string()));
-                                },
-                                style: format!("padding: 12px 16px; cursor: pointer; color: {}; font-family: {}; border-bottom: 1px solid #e0e0e0;", COLOR_DARK_GREY, FONT_BODY),
-                                "BTC/USD"
-                            }
-                            div {
-                                onclick: move |_| {
-                                    show_markets_dropdown.set(false);
-                                    props.on_navigate.call(AppView::Trading("ETH".to_string()));
-                                },
-                                style: format!("padding: 12px 16px; cursor: pointer; color: {}; font-family: {}; border-bottom: 1px solid #e0e0e0;", COLOR_DARK_GREY, FONT_BODY),
-                                "ETH/USD"
-                            }
-                            div {
-                                onclick: move |_| {
-                                    show_markets_dropdown.set(false);
-                                    props.on_navigate.call(AppView::Trading("BTC/ETH".to_string()));
-                                },
-                                style: format!("padding: 12px 16px; cursor: pointer; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                "BTC/ETH"
-                            }
-                        }
-                    }
-                }
+/// A USD amount shown in the user's display currency: USD itself or any priced asset
+fn display_value(usd: f64, currency: &str, prices: &HashMap<String, f64>) -> String {
+    match prices.get(currency).copied().filter(|price| *price > 0.0) {
+        Some(price) if currency != "USD" => format!("{} {}", format_amount(usd / price, currency), currency),
+        _ => usd_amount(usd),
+    }
+}
 
-                // About link
-                div {
-                    onclick: move |_| props.on_navigate.call(AppView::About),
-                    style: format!(
-                        "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
-                        if matches!(props.current_view, AppView::About) { "rgba(255,255,255,0.2)" } else { "transparent" },
-                        FONT_BODY
-                    ),
-                    "About"
-                }
+/// Comma-separated numbers as grid values; whole numbers stay integers so count parameters accept them
+fn parse_grid_values(text: &str) -> Vec<serde_json::Value> {
+    text.split(',')
+        .map(str::trim)
+        .filter_map(|value| match value.parse::<i64>() {
+            Ok(n) => Some(serde_json::json!(n)),
+            Err(_) => value.parse::<f64>().ok().map(|x| serde_json::json!(x)),
+        })
+        .collect()
+}
 
-                // Logout link
-                div {
-                    onclick: move |_| props.on_logout.call(()),
-                    style: format!("cursor: pointer; padding: 8px 16px; border-radius: 4px; background: transparent; font-family: {};", FONT_BODY),
-                    "Logout"
-                }
-            }
+/// Heatmap cell colour from red (worst) to green (best)
+fn heat_color(score: Option<f64>, worst: f64, best: f64) -> String {
+    match score {
+        Some(score) => {
+            let t = if best == worst { 1.0 } else { ((score - worst) / (best - worst)).clamp(0.0, 1.0) };
+            format!("hsl({:.0}, 60%, 45%)", t * 120.0)
         }
+        None => "var(--color-surface)".to_string(),
     }
 }
 
-#[derive(Clone, PartialEq, Props)]
-struct StatusBarProps {
-    username: String,
-    bot_status: Option<BotStatusResponse>,
+/// A length of time in its two largest units, e.g. "2d 5h" or "12m 30s"
+fn format_duration(secs: f64) -> String {
+    let secs = secs.max(0.0).round() as u64;
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m {}s", minutes, secs % 60)
+    }
 }
 
-#[component]
-fn StatusBar(props: StatusBarProps) -> Element {
-    let bot_display = if let Some(ref status) = props.bot_status {
-        if status.is_active {
-            format!(
-                "Status: {} Bot running in {}",
-                status.bot_name.as_ref().unwrap_or(&"Unknown".to_string()),
-                status.trading_pair.as_ref().unwrap_or(&"Unknown".to_string())
-            )
-        } else {
-            "Status: No bot running in account..".to_string()
-        }
-    } else {
-        "Status: No bot running in account..".to_string()
+/// Metric cards for performance figures: (label, value, whether it is good news if that matters)
+/// Metric cards for a risk grade: green for A and B, red for D and F
+fn risk_cards(score: &RiskScore) -> Vec<(String, String, Option<bool>)> {
+    let good = |grade: &str| match grade {
+        "A" | "B" => Some(true),
+        "D" | "F" => Some(false),
+        _ => None,
     };
+    let mut cards = vec![("Risk Grade".to_string(), score.grade.clone(), good(&score.grade))];
+    cards.extend(score.components.iter().map(|c| {
+        let (label, value) = match c.name.as_str() {
+            "turnover" => ("Turnover", format!("{:.2}× / day", c.value)),
+            "concentration" => ("Concentration", format!("{:.0}%", c.value)),
+            "drawdown" => ("Drawdown Risk", format!("{:.2}%", c.value)),
+            "leverage" => ("Leverage", format!("{:.1}× stoploss", c.value)),
+            other => (other, format!("{:.2}", c.value)),
+        };
+        (label.to_string(), format!("{} · {}", c.grade, value), good(&c.grade))
+    }));
+    cards
+}
 
-    rsx! {
-        div {
-            style: format!(
-                "position: fixed; bottom: 0; left: 0; right: 0; background: {}; color: white; padding: 10px 30px; display: flex; justify-content: space-between; align-items: center; box-shadow: 0 -2px 4px rgba(0,0,0,0.1); font-family: {}; font-size: 14px; z-index: 1000;",
-                COLOR_LIGHT_GREY,
-                FONT_BODY
-            ),
-            div {
-                "Logged in as: {props.username}"
-            }
-            div {
-                "{bot_display}"
-            }
-        }
-    }
+fn performance_cards(metrics: &PerformanceMetrics) -> Vec<(String, String, Option<bool>)> {
+    let dash = || "—".to_string();
+    vec![
+        ("Total Return".to_string(), format!("{:+.2}%", metrics.total_return_pct), Some(metrics.total_return_pct >= 0.0)),
+        ("CAGR".to_string(), metrics.cagr_pct.map_or_else(dash, |cagr| format!("{:+.2}%", cagr)), metrics.cagr_pct.map(|cagr| cagr >= 0.0)),
+        ("Sharpe Ratio".to_string(), metrics.sharpe_ratio.map_or_else(dash, |sharpe| format!("{:.2}", sharpe)), metrics.sharpe_ratio.map(|sharpe| sharpe >= 0.0)),
+        ("Max Drawdown".to_string(), format!("{:.2}%", metrics.max_drawdown_pct), None),
+        ("Win Rate".to_string(), metrics.win_rate_pct.map_or_else(dash, |rate| format!("{:.1}% of {}", rate, metrics.closed_trades)), None),
+        ("Profit Factor".to_string(), metrics.profit_factor.map_or_else(dash, |factor| format!("{:.2}", factor)), metrics.profit_factor.map(|factor| factor >= 1.0)),
+        ("Avg Trade Duration".to_string(), metrics.avg_trade_duration_secs.map_or_else(dash, format_duration), None),
+        ("Exposure".to_string(), format!("{:.1}%", metrics.exposure_pct), None),
+    ]
 }
 
-#[derive(Clone, PartialEq, Props)]
-struct PortfolioPieChartProps {
-    btc_balance: f64,
-    eth_balance: f64,
-    cash_balance: f64,
-    btc_price: f64,
-    eth_price: f64,
+/// Estimated USD value of all balances; assets without a known price count as zero
+fn portfolio_value_usd(balances: &HashMap<String, f64>, prices: &HashMap<String, f64>) -> f64 {
+    balances
+        .iter()
+        .map(|(asset, balance)| {
+            if asset == "USD" {
+                *balance
+            } else {
+                balance * prices.get(asset).copied().unwrap_or(0.0)
+            }
+        })
+        .sum()
 }
 
 #[component]
-fn PortfolioPieChart(props: PortfolioPieChartProps) -> Element {
-    // Calculate USD values
-    let btc_value = props.btc_balance * props.btc_price;
-    let eth_value = props.eth_balance * props.eth_price;
-    let cash_value = props.cash_balance;
-    let total_value = btc_value + eth_value + cash_value;
+fn PriceChart(props: PriceChartProps) -> Element {
+    // Clone props data to satisfy lifetime requirements for event handlers
+    let prices = props.prices.clone();
+    let quote_asset = props.quote_asset.clone();
 
-    if total_value == 0.0 {
-        return rsx! {
-            div {
-                style: "text-align: center; padding: 20px; color: #666;",
-                "No assets to display"
-            }
-        };
+    // Debug: Log if we have indicator data
+    if let Some(ref ind_data) = props.indicator_data {
+        web_sys::console::log_1(&format!("PriceChart received indicators: {:?}", ind_data.indicators.keys().collect::<Vec<_>>()).into());
+    } else {
+        web_sys::console::log_1(&"PriceChart: No indicator data".into());
     }
 
-    // Calculate percentages and angles
-    let btc_pct = (btc_value / total_value) * 100.0;
-    let eth_pct = (eth_value / total_value) * 100.0;
-    let cash_pct = (cash_value / total_value) * 100.0;
-
-    // Colors from neutral palette
-    let btc_color = "#5C6BC0"; // Indigo
-    let eth_color = "#42A5F5"; // Blue
-    let cash_color = "#66BB6A"; // Green
-
-    // SVG pie chart
-    let size = 200.0;
-    let center = size / 2.0;
-    let radius = 80.0;
-
-    // Calculate pie slices
-    let mut current_angle = 0.0;
-
-    // BTC slice
-    let btc_angle = (btc_pct / 100.0) * 360.0;
-    let btc_end_angle = current_angle + btc_angle;
-
-    // ETH slice
-    current_angle = btc_end_angle;
-    let eth_angle = (eth_pct / 100.0) * 360.0;
-    let eth_end_angle = current_angle + eth_angle;
-
-    // Cash slice takes the rest
-    let _current_angle = eth_end_angle;
-
-    fn get_arc_path(cx: f64, cy: f64, r: f64, start_angle: f64, end_angle: f64) -> String {
-        let start_rad = (start_angle - 90.0) * std::f64::consts::PI / 180.0;
-        let end_rad = (end_angle - 90.0) * std::f64::consts::PI / 180.0;
-
-        let x1 = cx + r * start_rad.cos();
-        let y1 = cy + r * start_rad.sin();
-        let x2 = cx + r * end_rad.cos();
-        let y2 = cy + r * end_rad.sin();
-
-        let large_arc = if end_angle - start_angle > 180.0 { 1 } else { 0 };
-
-        format!("M {},{} L {},{} A {},{} 0 {},{} {},{} Z",
-                cx, cy, x1, y1, r, r, large_arc, 1, x2, y2)
+    if prices.is_empty() {
+        return rsx! { p { "No data available" } };
     }
 
-    let mut svg_elements = String::new();
+    // Hover state for crosshair and tooltip
+    let mut hover_x = use_signal(|| None::<f64>);
+    let mut hover_y = use_signal(|| None::<f64>);
+    let mut hover_price = use_signal(|| None::<f64>);
+    let mut hover_time = use_signal(|| None::<i64>);
 
-    // BTC slice
-    if btc_pct > 0.0 {
-        svg_elements.push_str(&format!(
-            "<path d=\"{}\" fill=\"{}\" />",
-            get_arc_path(center, center, radius, 0.0, btc_end_angle),
-            btc_color
-        ));
-    }
+    // Calculate chart dimensions
+    let width = 1000.0;
+    let height = 300.0;
+    let padding_left = 80.0;
+    let padding_right = 40.0;
+    let padding_top = 40.0;
+    let padding_bottom = 60.0;
 
-    // ETH slice
-    if eth_pct > 0.0 {
-        svg_elements.push_str(&format!(
-            "<path d=\"{}\" fill=\"{}\" />",
-            get_arc_path(center, center, radius, btc_end_angle, eth_end_angle),
-            eth_color
-        ));
-    }
+    // Find min and max values for scaling
+    let min_price = prices.iter().map(|p| p.price).fold(f64::INFINITY, f64::min);
+    let max_price = prices.iter().map(|p| p.price).fold(f64::NEG_INFINITY, f64::max);
+    let price_range = if (max_price - min_price).abs() < 0.01 { 1.0 } else { max_price - min_price };
 
-    // Cash slice
-    if cash_pct > 0.0 {
-        svg_elements.push_str(&format!(
-            "<path d=\"{}\" fill=\"{}\" />",
-            get_arc_path(center, center, radius, eth_end_angle, 360.0),
-            cash_color
-        ));
+    // Generate path data for the line
+    let mut path_data = String::from("M ");
+    for (i, point) in prices.iter().enumerate() {
+        let x = padding_left + (i as f64 / (prices.len() - 1) as f64) * (width - padding_left - padding_right);
+        let y = height - padding_bottom - ((point.price - min_price) / price_range) * (height - padding_top - padding_bottom);
+        if i == 0 {
+            path_data.push_str(&format!("{} {} ", x, y));
+        } else {
+            path_data.push_str(&format!("L {} {} ", x, y));
+        }
     }
 
-    rsx! {
-        div {
-            style: "display: flex; flex-direction: column; align-items: center;",
-
-            // SVG Pie Chart
-            div {
-                dangerous_inner_html: format!(
-                    "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\"><g>{}</g></svg>",
-                    size, size, size, size, svg_elements
-                )
+    // Generate indicator overlay paths, skipping points before each indicator has enough data
+    let mut overlay_paths = Vec::new();
+    if let Some(ref indicators) = props.indicator_data {
+        for (key, color) in &props.overlays {
+            let Some(values) = indicators.indicators.get(key) else {
+                continue;
+            };
+            if values.len() < 2 {
+                continue;
             }
-
-            // Legend
-            div {
-                style: format!("margin-top: 15px; font-size: 13px; font-family: {};", FONT_BODY),
-                if btc_pct > 0.0 {
-                    div {
-                        style: "display: flex; align-items: center; gap: 8px; margin-bottom: 5px;",
-                        div { style: format!("width: 16px; height: 16px; background: {}; border-radius: 2px;", btc_color) }
-                        span { "BTC: {btc_pct:.1}%" }
-                    }
-                }
-                if eth_pct > 0.0 {
-                    div {
-                        style: "display: flex; align-items: center; gap: 8px; margin-bottom: 5px;",
-                        div { style: format!("width: 16px; height: 16px; background: {}; border-radius: 2px;", eth_color) }
-                        span { "ETH: {eth_pct:.1}%" }
-                    }
-                }
-                if cash_pct > 0.0 {
-                    div {
-                        style: "display: flex; align-items: center; gap: 8px;",
-                        div { style: format!("width: 16px; height: 16px; background: {}; border-radius: 2px;", cash_color) }
-                        span { "USD: {cash_pct:.1}%" }
+            let mut overlay_path = String::from("M ");
+            let mut first_valid = true;
+            for (i, value_opt) in values.iter().enumerate() {
+                if let Some(value) = value_opt {
+                    let x = padding_left + (i as f64 / (values.len() - 1) as f64) * (width - padding_left - padding_right);
+                    let y = height - padding_bottom - ((value - min_price) / price_range) * (height - padding_top - padding_bottom);
+                    if first_valid {
+                        overlay_path.push_str(&format!("{} {} ", x, y));
+                        first_valid = false;
+                    } else {
+                        overlay_path.push_str(&format!("L {} {} ", x, y));
                     }
                 }
             }
+            if !first_valid {
+                overlay_paths.push((overlay_path, *color));
+            }
         }
     }
-}
 
-#[derive(Clone, PartialEq, Props)]
-struct ExpandableSectionProps {
-    title: String,
-    children: Element,
-}
+    // Generate horizontal grid lines (5 lines)
+    let mut h_grid_lines = Vec::new();
+    for i in 0..5 {
+        let y = padding_top + (i as f64 / 4.0) * (height - padding_top - padding_bottom);
+        let price = max_price - (i as f64 / 4.0) * price_range;
+        h_grid_lines.push((y, price));
+    }
 
-#[component]
-fn ExpandableSection(props: ExpandableSectionProps) -> Element {
-    let mut is_expanded = use_signal(|| false);
+    // Generate vertical grid lines and time labels (6 marks with real timestamps)
+    let mut v_grid_lines = Vec::new();
+    let time_span = prices.last().unwrap().timestamp - prices.first().unwrap().timestamp;
+    for i in 0..6 {
+        let x = padding_left + (i as f64 / 5.0) * (width - padding_left - padding_right);
+        let timestamp = prices.first().unwrap().timestamp + ((time_span as f64 * i as f64 / 5.0) as i64);
+        v_grid_lines.push((x, timestamp));
+    }
+
+    // Precompute fixed coordinates
+    let chart_top = padding_top;
+    let chart_bottom = height - padding_bottom;
+    let chart_left = padding_left;
+    let chart_right = width - padding_right;
+
+    // Price label (show currency symbol for USD, otherwise show asset name)
+    let price_label = if quote_asset == "USD" {
+        "Price ($)".to_string()
+    } else {
+        format!("Price ({})", quote_asset)
+    };
 
     rsx! {
         div {
-            style: format!("background: {}; border-radius: 8px; overflow: hidden; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-bottom: 20px;", COLOR_CONTENT_BG),
-
-            // Header (clickable to expand/collapse)
-            div {
-                onclick: move |_| is_expanded.set(!is_expanded()),
-                style: format!(
-                    "padding: 15px 20px; cursor: pointer; display: flex; justify-content: space-between; align-items: center; background: {}; font-family: {}; font-weight: 600; color: {}; user-select: none;",
-                    if is_expanded() { COLOR_PAGE_BG } else { COLOR_CONTENT_BG },
-                    FONT_BODY,
-                    COLOR_DARK_GREY
-                ),
-                span { "{props.title}" }
-                span { style: "font-size: 20px;", if is_expanded() { "−" } else { "+" } }
-            }
-
-            // Content (only show when expanded)
-            if is_expanded() {
-                div {
-                    style: "padding: 20px;",
-                    {props.children}
-                }
-            }
-        }
-    }
-}
+            style: "position: relative;",
+            svg {
+                width: "{width}",
+                height: "{height}",
+                view_box: "0 0 {width} {height}",
+                style: "display: block; margin: 0 auto; background: var(--color-content-bg); cursor: crosshair;",
+                onmousemove: move |evt| {
+                    let rect_x = evt.data().element_coordinates().x;
+                    let rect_y = evt.data().element_coordinates().y;
 
-fn App() -> Element {
-    let mut current_view = use_signal(|| AppView::Auth);
-    let mut user_id = use_signal(|| String::new());
-    let mut username = use_signal(|| String::new());
+                    // Check if within chart bounds
+                    if rect_x >= chart_left && rect_x <= chart_right && rect_y >= chart_top && rect_y <= chart_bottom {
+                        hover_x.set(Some(rect_x));
+                        hover_y.set(Some(rect_y));
 
-    // Multi-asset price tracking
-    let mut btc_price = use_signal(|| 0.0);
-    let mut eth_price = use_signal(|| 0.0);
-    let mut btc_history = use_signal(|| Vec::<PricePoint>::new());
-    let mut eth_history = use_signal(|| Vec::<PricePoint>::new());
+                        // Calculate price from y position
+                        let price = max_price - ((rect_y - chart_top) / (chart_bottom - chart_top)) * price_range;
+                        hover_price.set(Some(price));
 
-    let mut portfolio = use_signal(|| None::<UserData>);
-    let mut quantity = use_signal(|| String::from("0.01"));
-    let mut status = use_signal(|| String::from(""));
-    let mut deposit_amount = use_signal(|| String::from("100"));
-    let mut withdrawal_amount = use_signal(|| String::from("100"));
+                        // Calculate time from x position
+                        let time_idx = ((rect_x - chart_left) / (chart_right - chart_left) * (prices.len() - 1) as f64) as usize;
+                        if time_idx < prices.len() {
+                            hover_time.set(Some(prices[time_idx].timestamp));
+                        }
+                    } else {
+                        hover_x.set(None);
+                        hover_y.set(None);
+                        hover_price.set(None);
+                        hover_time.set(None);
+                    }
+                },
+                onmouseleave: move |_| {
+                    hover_x.set(None);
+                    hover_y.set(None);
+                    hover_price.set(None);
+                    hover_time.set(None);
+                },
 
-    // Auth form state
-    let mut auth_username = use_signal(|| String::new());
-    let mut auth_password = use_signal(|| String::new());
-    let mut auth_error = use_signal(|| String::new());
+                // Horizontal grid lines with price labels
+                for (y, price) in h_grid_lines.iter() {
+                    line {
+                        x1: "{chart_left}",
+                        y1: "{y}",
+                        x2: "{chart_right}",
+                        y2: "{y}",
+                        style: "stroke: var(--color-divider)",
+                        stroke_width: "1"
+                    }
+                    text {
+                        x: "{chart_left - 10.0}",
+                        y: "{y + 4.0}",
+                        font_size: "12",
+                        style: "fill: var(--color-light-grey)",
+                        text_anchor: "end",
+                        {
+                            if quote_asset == "USD" {
+                                usd_amount(*price)
+                            } else {
+                                format_amount(*price, &quote_asset)
+                            }
+                        }
+                    }
+                }
 
-    // Bot state
-    let mut bot_status = use_signal(|| None::<BotStatusResponse>);
-    let mut bot_stoploss = use_signal(|| String::from("1000"));
-    let mut selected_bot = use_signal(|| String::from("naive_momentum"));
+                // Vertical grid lines with time labels
+                for (x, timestamp) in v_grid_lines.iter() {
+                    line {
+                        x1: "{x}",
+                        y1: "{chart_top}",
+                        x2: "{x}",
+                        y2: "{chart_bottom}",
+                        style: "stroke: var(--color-divider)",
+                        stroke_width: "1"
+                    }
+                    text {
+                        x: "{x}",
+                        y: "{chart_bottom + 20.0}",
+                        font_size: "12",
+                        style: "fill: var(--color-light-grey)",
+                        text_anchor: "middle",
+                        {
+                            // Format timestamp as HH:MM
+                            let dt = chrono::DateTime::from_timestamp(*timestamp, 0).unwrap();
+                            format!("{:02}:{:02}", dt.hour(), dt.minute())
+                        }
+                    }
+                }
 
-    // Chart state
-    let mut selected_timeframe = use_signal(|| String::from("1h"));
-    let mut chart_type = use_signal(|| String::from("line")); // "line" or "candlestick"
-    let mut candle_history = use_signal(|| Vec::<Candle>::new());
+                // Chart border
+                rect {
+                    x: "{chart_left}",
+                    y: "{chart_top}",
+                    width: "{chart_right - chart_left}",
+                    height: "{chart_bottom - chart_top}",
+                    fill: "none",
+                    style: "stroke: var(--color-light-grey)",
+                    stroke_width: "2"
+                }
 
-    // Indicator state
-    let mut indicator_data = use_signal(|| None::<IndicatorResponse>);
-    let mut show_sma_20 = use_signal(|| false);
-    let mut show_sma_50 = use_signal(|| false);
-    let mut show_ema_12 = use_signal(|| false);
-    let mut show_ema_26 = use_signal(|| false);
-    let mut show_rsi_14 = use_signal(|| false);
+                // Price line
+                path {
+                    d: "{path_data}",
+                    fill: "none",
+                    stroke: "#2196F3",
+                    stroke_width: "2",
+                }
 
-    // Fetch BTC price on mount and every 5 seconds
-    use_effect(move || {
-        spawn(async move {
-            loop {
-                if let Ok(resp) = reqwest::get(format!("{}/price?asset=BTC", API_BASE)).await {
-                    if let Ok(data) = resp.json::<PriceResponse>().await {
-                        btc_price.set(data.price);
+                // Indicator overlays (SMA/EMA)
+                for (overlay_path, color) in overlay_paths {
+                    path {
+                        d: "{overlay_path}",
+                        fill: "none",
+                        stroke: "{color}",
+                        stroke_width: "2",
+                        opacity: "0.8"
                     }
                 }
-                gloo_timers::future::TimeoutFuture::new(5_000).await;
-            }
-        });
-    });
 
-    // Fetch ETH price on mount and every 5 seconds
-    use_effect(move || {
-        spawn(async move {
-            loop {
-                if let Ok(resp) = reqwest::get(format!("{}/price?asset=ETH", API_BASE)).await {
-                    if let Ok(data) = resp.json::<PriceResponse>().await {
-                        eth_price.set(data.price);
+                // Crosshair lines
+                if let Some(x) = hover_x() {
+                    line {
+                        x1: "{x}",
+                        y1: "{chart_top}",
+                        x2: "{x}",
+                        y2: "{chart_bottom}",
+                        style: "stroke: var(--color-light-grey)",
+                        stroke_width: "1",
+                        stroke_dasharray: "4,4",
+                        pointer_events: "none"
                     }
                 }
-                gloo_timers::future::TimeoutFuture::new(5_000).await;
-            }
-        });
-    });
-
-    // Fetch BTC price history when timeframe changes
-    let fetch_btc_history = move || {
-        let timeframe = selected_timeframe();
-        web_sys::console::log_1(&format!("Fetching BTC history with timeframe: {}", timeframe).into());
-        spawn(async move {
-            let url = format!("{}/price/history?asset=BTC&timeframe={}", API_BASE, timeframe);
-            web_sys::console::log_1(&format!("BTC URL: {}", url).into());
-            if let Ok(resp) = reqwest::get(&url).await {
-                if let Ok(data) = resp.json::<PriceHistoryResponse>().await {
-                    web_sys::console::log_1(&format!("BTC history received: {} points", data.prices.len()).into());
-                    btc_history.set(data.prices);
+                if let Some(y) = hover_y() {
+                    line {
+                        x1: "{chart_left}",
+                        y1: "{y}",
+                        x2: "{chart_right}",
+                        y2: "{y}",
+                        style: "stroke: var(--color-light-grey)",
+                        stroke_width: "1",
+                        stroke_dasharray: "4,4",
+                        pointer_events: "none"
+                    }
                 }
-            }
-        });
-    };
-
-    // Re-fetch BTC history when timeframe changes
-    use_effect(move || {
-        selected_timeframe();  // Track dependency
-        fetch_btc_history();
-    });
 
-    // Periodic BTC history refresh (every 30 seconds)
-    use_effect(move || {
-        spawn(async move {
-            loop {
-                gloo_timers::future::TimeoutFuture::new(30_000).await;
-                fetch_btc_history();
+                // Axis labels
+                text {
+                    x: "{chart_left - 60.0}",
+                    y: "{(chart_top + chart_bottom) / 2.0}",
+                    font_size: "14",
+                    style: "fill: var(--color-dark-grey)",
+                    text_anchor: "middle",
+                    transform: "rotate(-90 {chart_left - 60.0} {(chart_top + chart_bottom) / 2.0})",
+                    "{price_label}"
+                }
+                text {
+                    x: "{(chart_left + chart_right) / 2.0}",
+                    y: "{height - 10.0}",
+                    font_size: "14",
+                    style: "fill: var(--color-dark-grey)",
+                    text_anchor: "middle",
+                    "Time"
+                }
             }
-        });
-    });
 
-    // Fetch ETH price history when timeframe changes
-    let fetch_eth_history = move || {
-        let timeframe = selected_timeframe();
-        web_sys::console::log_1(&format!("Fetching ETH history with timeframe: {}", timeframe).into());
-        spawn(async move {
-            let url = format!("{}/price/history?asset=ETH&timeframe={}", API_BASE, timeframe);
-            web_sys::console::log_1(&format!("ETH URL: {}", url).into());
-            if let Ok(resp) = reqwest::get(&url).await {
-                if let Ok(data) = resp.json::<PriceHistoryResponse>().await {
-                    web_sys::console::log_1(&format!("ETH history received: {} points", data.prices.len()).into());
-                    eth_history.set(data.prices);
+            // Tooltip
+            if let Some(price) = hover_price() {
+                if let Some(time) = hover_time() {
+                    if let (Some(x), Some(y)) = (hover_x(), hover_y()) {
+                        div {
+                            style: "position: absolute; left: {x + 10.0}px; top: {y - 40.0}px; background: rgba(0,0,0,0.8); color: white; padding: 8px 12px; border-radius: 4px; font-size: 12px; pointer-events: none; white-space: nowrap;",
+                            div {
+                                {
+                                    let dt = chrono::DateTime::from_timestamp(time, 0).unwrap();
+                                    format!("{:02}:{:02}:{:02}", dt.hour(), dt.minute(), dt.second())
+                                }
+                            }
+                            div {
+                                {quote_amount_label(price, &quote_asset)}
+                            }
+                        }
+                    }
                 }
             }
-        });
-    };
+        }
+    }
+}
 
-    // Re-fetch ETH history when timeframe changes
-    use_effect(move || {
-        selected_timeframe();  // Track dependency
-        fetch_eth_history();
-    });
+fn CandlestickChart(props: CandlestickChartProps) -> Element {
+    let candles = props.candles.clone();
+    let quote_asset = props.quote_asset.clone();
 
-    // Periodic ETH history refresh (every 30 seconds)
-    use_effect(move || {
-        spawn(async move {
-            loop {
-                gloo_timers::future::TimeoutFuture::new(30_000).await;
-                fetch_eth_history();
-            }
-        });
-    });
+    if candles.is_empty() {
+        return rsx! { p { "No candlestick data available" } };
+    }
 
-    // Fetch candlestick data for the selected market (base/quote)
-    let fetch_candle_history = move |asset: &str| {
-        let timeframe = selected_timeframe();
-        let asset = asset.to_string();
-        spawn(async move {
-            let url = format!("{}/price/candles?asset={}&timeframe={}", API_BASE, asset, timeframe);
-            if let Ok(resp) = reqwest::get(&url).await {
-                #[derive(Deserialize)]
-                struct CandleHistoryResponse {
-                    candles: Vec<Candle>,
-                }
-                if let Ok(data) = resp.json::<CandleHistoryResponse>().await {
-                    candle_history.set(data.candles);
-                }
-            }
-        });
-    };
+    // Hover state
+    let mut hover_candle_idx = use_signal(|| None::<usize>);
 
-    // Fetch indicator data for the selected market
-    let mut fetch_indicators = move |asset: &str| {
-        let timeframe = selected_timeframe();
+    let width = 1000.0;
+    let height = 300.0;
+    let padding_left = 80.0;
+    let padding_right = 40.0;
+    let padding_top = 40.0;
+    let padding_bottom = 60.0;
 
-        // Only fetch if timeframe is 1h (indicators only supported for 1h)
-        if timeframe != "1h" {
-            indicator_data.set(None);
-            return;
-        }
+    // Find min/max prices
+    let mut min_price = f64::INFINITY;
+    let mut max_price = f64::NEG_INFINITY;
+    for candle in &candles {
+        min_price = min_price.min(candle.low);
+        max_price = max_price.max(candle.high);
+    }
+    let price_range = if (max_price - min_price).abs() < 0.01 { 1.0 } else { max_price - min_price };
 
-        // Build indicators list based on toggles
-        let mut indicators = Vec::new();
-        if show_sma_20() {
-            indicators.push("sma_20");
-        }
-        if show_sma_50() {
-            indicators.push("sma_50");
-        }
-        if show_ema_12() {
-            indicators.push("ema_12");
-        }
-        if show_ema_26() {
-            indicators.push("ema_26");
-        }
-        if show_rsi_14() {
-            indicators.push("rsi_14");
-        }
+    let chart_width = width - padding_left - padding_right;
+    let candle_spacing = chart_width / candles.len() as f64;
+    let candle_width = (candle_spacing * 0.7).max(2.0);
 
-        // If no indicators selected, clear data
-        if indicators.is_empty() {
-            indicator_data.set(None);
-            return;
-        }
+    let price_label = if quote_asset == "USD" {
+        "Price ($)".to_string()
+    } else {
+        format!("Price ({})", quote_asset)
+    };
 
-        let indicators_param = indicators.join(",");
-        let asset = asset.to_string();
+    // Build SVG elements as strings
+    let mut svg_elements = String::new();
 
-        spawn(async move {
-            let url = format!(
-                "{}/indicators?asset={}&timeframe={}&indicators={}",
-                API_BASE, asset, timeframe, indicators_param
-            );
-            web_sys::console::log_1(&format!("Fetching indicators: {}", url).into());
-            match reqwest::get(&url).await {
-                Ok(resp) => {
-                    web_sys::console::log_1(&format!("Response status: {}", resp.status()).into());
-                    match resp.json::<IndicatorResponse>().await {
-                        Ok(data) => {
-                            web_sys::console::log_1(&format!("Received indicator data with {} indicators", data.indicators.len()).into());
-                            indicator_data.set(Some(data));
-                        }
-                        Err(e) => {
-                            web_sys::console::log_1(&format!("Failed to parse indicator response: {:?}", e).into());
-                        }
-                    }
-                }
-                Err(e) => {
-                    web_sys::console::log_1(&format!("Failed to fetch indicators: {:?}", e).into());
-                }
-            }
-        });
-    };
+    // Grid lines and labels
+    for i in 0..5 {
+        let y = padding_top + (i as f64 / 4.0) * (height - padding_top - padding_bottom);
+        let price = max_price - (i as f64 / 4.0) * price_range;
+        svg_elements.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" style=\"stroke: var(--color-divider)\" stroke-width=\"1\"/>",
+            padding_left, y, width - padding_right, y
+        ));
+        svg_elements.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"12\" style=\"fill: var(--color-light-grey)\">{}</text>",
+            padding_left - 10.0, y + 5.0, format_amount(price, &quote_asset)
+        ));
+    }
 
-    // Re-fetch candle data when timeframe changes (only when in candlestick mode)
-    use_effect(move || {
-        let _timeframe = selected_timeframe();
-        let current_chart_type = chart_type();
+    let time_span = candles.last().unwrap().timestamp - candles.first().unwrap().timestamp;
+    for i in 0..6 {
+        let x = padding_left + (i as f64 / 5.0) * chart_width;
+        let timestamp = candles.first().unwrap().timestamp + ((time_span as f64 * i as f64 / 5.0) as i64);
+        let dt = chrono::DateTime::from_timestamp(timestamp, 0).unwrap();
+        svg_elements.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" style=\"stroke: var(--color-divider)\" stroke-width=\"1\"/>",
+            x, padding_top, x, height - padding_bottom
+        ));
+        svg_elements.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\" style=\"fill: var(--color-light-grey)\">{:02}:{:02}</text>",
+            x, height - padding_bottom + 20.0, dt.hour(), dt.minute()
+        ));
+    }
 
-        // Fetch immediately if we're in candlestick mode and in Trading view
-        if current_chart_type == "candlestick" {
-            if let AppView::Trading(asset) = &*current_view.peek() {
-                // Extract base asset from trading pair
-                let base_asset = if asset.contains('/') {
-                    asset.split('/').next().unwrap_or("BTC")
-                } else {
-                    asset.as_str()
-                };
-                fetch_candle_history(base_asset);
-            }
-        }
-    });
+    // Draw candlesticks
+    for (i, candle) in candles.iter().enumerate() {
+        let x_center = padding_left + (i as f64 + 0.5) * candle_spacing;
+        let open_y = height - padding_bottom - ((candle.open - min_price) / price_range) * (height - padding_top - padding_bottom);
+        let close_y = height - padding_bottom - ((candle.close - min_price) / price_range) * (height - padding_top - padding_bottom);
+        let high_y = height - padding_bottom - ((candle.high - min_price) / price_range) * (height - padding_top - padding_bottom);
+        let low_y = height - padding_bottom - ((candle.low - min_price) / price_range) * (height - padding_top - padding_bottom);
 
-    // Periodic candle history refresh based on timeframe
-    // 1h view: refresh every 60 seconds (new 1-minute candle)
-    // 8h/24h views: refresh every 5 minutes (new 5-minute candle)
-    use_effect(move || {
-        spawn(async move {
-            loop {
-                let timeframe = selected_timeframe();
-                let current_chart_type = chart_type();
+        let is_bullish = candle.close >= candle.open;
+        let color = if is_bullish { "#26a69a" } else { "#ef5350" };
+        let body_height = (open_y - close_y).abs().max(1.0);
+        let body_y = open_y.min(close_y);
 
-                // Only poll if in candlestick mode
-                if current_chart_type == "candlestick" {
-                    // Determine refresh interval based on timeframe
-                    let interval_ms = match timeframe.as_str() {
-                        "1h" => 60_000,  // 1 minute for 1h view
-                        "8h" | "24h" => 300_000,  // 5 minutes for 8h/24h views
-                        _ => 60_000,  // Default to 1 minute
-                    };
+        // High-low wick
+        svg_elements.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"1\"/>",
+            x_center, high_y, x_center, low_y, color
+        ));
+        // Body
+        svg_elements.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+            x_center - candle_width / 2.0, body_y, candle_width, body_height, color, color
+        ));
+    }
 
-                    gloo_timers::future::TimeoutFuture::new(interval_ms).await;
+    // Axis labels
+    svg_elements.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\" font-weight=\"bold\" style=\"fill: var(--color-dark-grey)\" transform=\"rotate(-90 {} {})\">{}</text>",
+        padding_left / 2.0, height / 2.0, padding_left / 2.0, height / 2.0, price_label
+    ));
+    svg_elements.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"14\" font-weight=\"bold\" style=\"fill: var(--color-dark-grey)\">Time</text>",
+        width / 2.0, height - 10.0
+    ));
 
-                    // Fetch candles for the current trading pair
-                    if let AppView::Trading(asset) = &*current_view.peek() {
-                        // Extract base asset from trading pair
-                        let base_asset = if asset.contains('/') {
-                            asset.split('/').next().unwrap_or("BTC")
-                        } else {
-                            asset.as_str()
-                        };
-                        fetch_candle_history(base_asset);
+    // Clone values needed for closures
+    let padding_left_clone = padding_left;
+    let chart_width_clone = chart_width;
+    let candles_len = candles.len();
+
+    // Build tooltip HTML if hovering
+    let tooltip_html = if let Some(idx) = hover_candle_idx() {
+        if idx < candles.len() {
+            let candle = &candles[idx];
+            let dt = chrono::DateTime::from_timestamp(candle.timestamp, 0).unwrap();
+            let x_pos = padding_left_clone + (idx as f64 + 0.5) * candle_spacing;
+            Some(format!(
+                "<div style=\"position: absolute; left: {}px; top: {}px; background: rgba(0,0,0,0.85); color: white; padding: 8px; border-radius: 4px; pointer-events: none; font-size: 12px; white-space: nowrap; z-index: 1000; transform: translateX(-50%);\"><div>{}</div><div>Open: ${:.2}</div><div>High: ${:.2}</div><div>Low: ${:.2}</div><div>Close: ${:.2}</div></div>",
+                x_pos, 10.0,
+                dt.format("%Y-%m-%d %H:%M"),
+                candle.open, candle.high, candle.low, candle.close
+            ))
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    rsx! {
+        div {
+            style: "position: relative;",
+            div {
+                dangerous_inner_html: format!(
+                    "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" style=\"display: block; margin: 0 auto; background: var(--color-content-bg);\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" style=\"fill: var(--color-content-bg)\"/>{}</svg>",
+                    width, height, width, height,
+                    padding_left, padding_top,
+                    width - padding_left - padding_right,
+                    height - padding_top - padding_bottom,
+                    svg_elements
+                )
+            }
+
+            // Transparent overlay for mouse events
+            div {
+                style: format!(
+                    "position: absolute; left: {}px; top: {}px; width: {}px; height: {}px; cursor: crosshair;",
+                    padding_left, padding_top, chart_width, height - padding_top - padding_bottom
+                ),
+                onmousemove: move |evt| {
+                    let rect_x = evt.data.element_coordinates().x;
+                    let candle_idx = (rect_x / (chart_width_clone / candles_len as f64)).floor() as usize;
+                    if candle_idx < candles_len {
+                        hover_candle_idx.set(Some(candle_idx));
                     }
-                } else {
-                    // If not in candlestick mode, just wait a bit before checking again
-                    gloo_timers::future::TimeoutFuture::new(5_000).await;
+                },
+                onmouseleave: move |_| {
+                    hover_candle_idx.set(None);
                 }
             }
-        });
-    });
-
-    // Fetch indicators when toggles or timeframe changes
-    use_effect(move || {
-        let (_tf, _sma20, _sma50, _ema12, _ema26, _rsi14) = (
-            selected_timeframe(),
-            show_sma_20(),
-            show_sma_50(),
-            show_ema_12(),
-            show_ema_26(),
-            show_rsi_14()
-        );
 
-        if let AppView::Trading(asset) = &*current_view.peek() {
-            let base_asset = if asset.contains('/') {
-                asset.split('/').next().unwrap_or("BTC")
-            } else {
-                asset.as_str()
-            };
-            fetch_indicators(base_asset);
+            // Tooltip
+            if let Some(html) = tooltip_html {
+                div {
+                    dangerous_inner_html: html
+                }
+            }
         }
-    });
+    }
+}
 
-    // Auth handlers
-    let mut handle_login = move || {
-        // Validate inputs
-        let uname = auth_username();
-        let pwd = auth_password();
+#[component]
+fn RSIPanel(props: RSIPanelProps) -> Element {
+    if props.rsi_values.is_empty() {
+        return rsx! { p { "No RSI data available" } };
+    }
 
-        if uname.trim().is_empty() || pwd.trim().is_empty() {
-            auth_error.set("Username and password are required".to_string());
-            return;
-        }
+    let width = 1000.0;
+    let height = 120.0;
+    let padding_left = 60.0;
+    let padding_right = 20.0;
+    let padding_top = 10.0;
+    let padding_bottom = 30.0;
 
-        spawn(async move {
-            auth_error.set(String::new());
+    // RSI range is 0-100
+    let min_rsi = 0.0;
+    let max_rsi = 100.0;
+    let rsi_range = max_rsi - min_rsi;
 
-            let client = reqwest::Client::new();
-            let login_req = LoginRequest {
-                username: uname,
-                password: pwd,
-            };
+    let mut svg_elements = String::new();
 
-            match client
-                .post(format!("{}/login", API_BASE))
-                .json(&login_req)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        if let Ok(auth_resp) = response.json::<AuthResponse>().await {
-                            user_id.set(auth_resp.user_id);
-                            username.set(auth_resp.username);
-                            current_view.set(AppView::Dashboard);
-                        }
-                    } else {
-                        if let Ok(err_resp) = response.json::<ErrorResponse>().await {
-                            auth_error.set(err_resp.error);
-                        } else {
-                            auth_error.set("Login failed".to_string());
-                        }
-                    }
-                }
-                Err(e) => auth_error.set(format!("Error: {}", e)),
-            }
-        });
-    };
+    // Draw reference lines at 30 and 70
+    // Line at 70 (overbought)
+    let y_70 = height - padding_bottom - ((70.0 - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
+    svg_elements.push_str(&format!(
+        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#ef5350\" stroke-width=\"1\" stroke-dasharray=\"4,2\" opacity=\"0.6\"/>",
+        padding_left, y_70, width - padding_right, y_70
+    ));
+    svg_elements.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" fill=\"#ef5350\" font-size=\"10\" text-anchor=\"end\">70</text>",
+        padding_left - 5.0, y_70 + 4.0
+    ));
 
-    let mut handle_signup = move || {
-        // Validate inputs
-        let uname = auth_username();
-        let pwd = auth_password();
+    // Line at 30 (oversold)
+    let y_30 = height - padding_bottom - ((30.0 - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
+    svg_elements.push_str(&format!(
+        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#66BB6A\" stroke-width=\"1\" stroke-dasharray=\"4,2\" opacity=\"0.6\"/>",
+        padding_left, y_30, width - padding_right, y_30
+    ));
+    svg_elements.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" fill=\"#66BB6A\" font-size=\"10\" text-anchor=\"end\">30</text>",
+        padding_left - 5.0, y_30 + 4.0
+    ));
 
-        if uname.trim().is_empty() || pwd.trim().is_empty() {
-            auth_error.set("Username and password are required".to_string());
-            return;
-        }
+    // Draw Y-axis labels
+    for &val in &[0.0, 50.0, 100.0] {
+        let y = height - padding_bottom - ((val - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
+        svg_elements.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" style=\"fill: var(--color-light-grey)\" font-size=\"10\" text-anchor=\"end\">{}</text>",
+            padding_left - 5.0, y + 4.0, val as i32
+        ));
+        svg_elements.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" style=\"stroke: var(--color-light-grey)\" stroke-width=\"1\"/>",
+            padding_left, y, width - padding_right, y
+        ));
+    }
 
-        if pwd.len() < 6 {
-            auth_error.set("Password must be at least 6 characters".to_string());
-            return;
+    // Draw RSI line
+    let mut rsi_path = String::from("M ");
+    let mut first_valid = true;
+    for (i, value_opt) in props.rsi_values.iter().enumerate() {
+        if let Some(value) = value_opt {
+            let x = padding_left + (i as f64 / (props.rsi_values.len() - 1) as f64) * (width - padding_left - padding_right);
+            let y = height - padding_bottom - ((value - min_rsi) / rsi_range) * (height - padding_top - padding_bottom);
+            if first_valid {
+                rsi_path.push_str(&format!("{} {} ", x, y));
+                first_valid = false;
+            } else {
+                rsi_path.push_str(&format!("L {} {} ", x, y));
+            }
         }
+    }
+    svg_elements.push_str(&format!(
+        "<path d=\"{}\" fill=\"none\" stroke=\"#9C27B0\" stroke-width=\"2\"/>",
+        rsi_path
+    ));
 
-        spawn(async move {
-            auth_error.set(String::new());
-
-            let client = reqwest::Client::new();
-            let signup_req = SignupRequest {
-                username: uname.clone(),
-                password: pwd,
-            };
+    // Y-axis label
+    svg_elements.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" style=\"fill: var(--color-light-grey)\" font-size=\"12\" text-anchor=\"middle\" font-weight=\"bold\">{}</text>",
+        padding_left / 2.0, height / 2.0, props.label
+    ));
 
-            match client
-                .post(format!("{}/signup", API_BASE))
-                .json(&signup_req)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        if let Ok(auth_resp) = response.json::<AuthResponse>().await {
-                            user_id.set(auth_resp.user_id);
-                            username.set(auth_resp.username);
-                            current_view.set(AppView::Dashboard);
-                        }
-                    } else {
-                        if let Ok(err_resp) = response.json::<ErrorResponse>().await {
-                            auth_error.set(err_resp.error);
-                        } else {
-                            auth_error.set("Signup failed".to_string());
-                        }
-                    }
-                }
-                Err(e) => auth_error.set(format!("Error: {}", e)),
+    rsx! {
+        div {
+            style: "margin-top: 20px;",
+            div {
+                dangerous_inner_html: format!(
+                    "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" style=\"display: block; margin: 0 auto; background: var(--color-content-bg);\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" style=\"fill: var(--color-content-bg)\"/>{}</svg>",
+                    width, height, width, height,
+                    padding_left, padding_top,
+                    width - padding_left - padding_right,
+                    height - padding_top - padding_bottom,
+                    svg_elements
+                )
             }
-        });
-    };
+        }
+    }
+}
 
-    let mut handle_guest = move || {
-        user_id.set("demo_user".to_string());
-        username.set("Guest".to_string());
-        current_view.set(AppView::Dashboard);
-    };
+#[derive(Clone, PartialEq, Props)]
+struct HeaderProps {
+    current_view: AppView,
+    username: String,
+    markets: Vec<String>,
+    unread_notifications: usize,
+    theme: Theme,
+    on_navigate: EventHandler<AppView>,
+    on_toggle_theme: EventHandler<()>,
+    on_toggle_notifications: EventHandler<()>,
+    on_logout: EventHandler<()>,
+}
 
-    let mut handle_logout = move || {
-        user_id.set(String::new());
-        username.set(String::new());
-        auth_username.set(String::new());
-        auth_password.set(String::new());
-        auth_error.set(String::new());
-        current_view.set(AppView::Auth);
-    };
+#[component]
+fn Header(props: HeaderProps) -> Element {
+    let mut show_markets_dropdown = use_signal(|| false);
 
-    // Fetch portfolio
-    let fetch_portfolio = move || {
-        let uid = user_id();
-        spawn(async move {
-            if let Ok(resp) = reqwest::get(format!("{}/portfolio?user_id={}", API_BASE, uid)).await {
-                if let Ok(data) = resp.json::<UserData>().await {
-                    portfolio.set(Some(data));
-                }
-            }
-        });
-    };
+    rsx! {
+        div {
+            style: format!(
+                "background: {}; color: white; padding: 15px 30px; display: flex; justify-content: space-between; align-items: center; box-shadow: 0 2px 4px rgba(0,0,0,0.1);",
+                COLOR_NAVY
+            ),
 
-    use_effect(move || {
-        // Fetch portfolio when logged in (Dashboard or Trading view)
-        match current_view() {
-            AppView::Dashboard | AppView::Trading(_) => {
-                fetch_portfolio();
+            // Left: App title (clickable to Dashboard)
+            div {
+                onclick: move |_| props.on_navigate.call(AppView::Dashboard),
+                style: format!("font-size: 24px; font-weight: 600; cursor: pointer; font-family: {};", FONT_HEADER),
+                "Trading Simulator"
             }
-            _ => {}
-        }
-    });
 
-    // Poll portfolio every 10 seconds when bot is active
-    use_effect(move || {
-        spawn(async move {
-            loop {
-                gloo_timers::future::TimeoutFuture::new(10_000).await;
+            // Right: Navigation
+            div {
+                style: "display: flex; gap: 20px; align-items: center; position: relative;",
 
-                // Only poll if in trading view and bot is active
-                if matches!(current_view(), AppView::Trading(_)) {
-                    if let Some(status) = bot_status() {
-                        if status.is_active {
-                            fetch_portfolio();
+                // Dashboard link
+                div {
+                    onclick: move |_| props.on_navigate.call(AppView::Dashboard),
+                    style: format!(
+                        "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
+                        if matches!(props.current_view, AppView::Dashboard) { "rgba(255,255,255,0.2)" } else { "transparent" },
+                        FONT_BODY
+                    ),
+                    "Dashboard"
+                }
+
+                // Markets dropdown
+                div {
+                    style: "position: relative;",
+                    div {
+                        onclick: move |_| show_markets_dropdown.set(!show_markets_dropdown()),
+                        style: format!(
+                            "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
+                            if matches!(props.current_view, AppView::Markets | AppView::Trading(_)) { "rgba(255,255,255,0.2)" } else { "transparent" },
+                            FONT_BODY
+                        ),
+                        "Markets ▾"
+                    }
+
+                    if show_markets_dropdown() {
+                        div {
+                            style: format!(
+                                "position: absolute; top: 100%; right: 0; margin-top: 8px; background: {}; border-radius: 4px; box-shadow: 0 4px 12px rgba(0,0,0,0.15); min-width: 150px; z-index: 1000;",
+                                COLOR_CONTENT_BG
+                            ),
+                            div {
+                                onclick: move |_| {
+                                    show_markets_dropdown.set(false);
+                                    props.on_navigate.call(AppView::Markets);
+                                },
+                                style: format!("padding: 12px 16px; cursor: pointer; color: {}; font-family: {}; border-bottom: 1px solid var(--color-divider);", COLOR_DARK_GREY, FONT_BODY),
+                                "All Markets"
+                            }
+                            for market in props.markets.clone() {
+                                div {
+                                    key: "{market}",
+                                    onclick: {
+                                        let market = market.clone();
+                                        move |_| {
+                                            show_markets_dropdown.set(false);
+                                            props.on_navigate.call(AppView::Trading(market.clone()));
+                                        }
+                                    },
+                                    style: format!("padding: 12px 16px; cursor: pointer; color: {}; font-family: {}; border-bottom: 1px solid var(--color-divider);", COLOR_DARK_GREY, FONT_BODY),
+                                    if market.contains('/') { "{market}" } else { "{market}/USD" }
+                                }
+                            }
                         }
                     }
                 }
-            }
-        });
-    });
 
-    let execute_trade = move |side: &str, asset: &str, quote_asset_opt: Option<String>| {
-        let side = side.to_string();
-        let asset = asset.to_string();
-        let qty = quantity().parse::<f64>().unwrap_or(0.0);
-        let uid = user_id();
+                // Backtest link
+                div {
+                    onclick: move |_| props.on_navigate.call(AppView::Backtest),
+                    style: format!(
+                        "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
+                        if matches!(props.current_view, AppView::Backtest) { "rgba(255,255,255,0.2)" } else { "transparent" },
+                        FONT_BODY
+                    ),
+                    "Backtest"
+                }
 
-        spawn(async move {
-            let trade = TradeRequest {
-                asset: asset.clone(),
-                quote_asset: quote_asset_opt,
-                side: side.clone(),
-                quantity: qty,
-            };
-
-            let client = reqwest::Client::new();
-            match client
-                .post(format!("{}/trade?user_id={}", API_BASE, uid.clone()))
-                .json(&trade)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        status.set(format!("{} successful!", side));
-                        // Refetch portfolio after successful trade
-                        if let Ok(resp) = reqwest::get(format!("{}/portfolio?user_id={}", API_BASE, uid)).await {
-                            if let Ok(data) = resp.json::<UserData>().await {
-                                portfolio.set(Some(data));
-                            }
-                        }
-                    } else {
-                        // Capture status before consuming response
-                        let status_code = response.status();
-                        // Try to parse the error message from the response
-                        if let Ok(error_resp) = response.json::<TradeErrorResponse>().await {
-                            status.set(error_resp.error);
-                        } else {
-                            status.set(format!("Trade failed: {}", status_code));
-                        }
-                    }
+                // Funds link
+                div {
+                    onclick: move |_| props.on_navigate.call(AppView::Funds),
+                    style: format!(
+                        "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
+                        if matches!(props.current_view, AppView::Funds) { "rgba(255,255,255,0.2)" } else { "transparent" },
+                        FONT_BODY
+                    ),
+                    "Funds"
                 }
-                Err(e) => status.set(format!("Error: {}", e)),
-            }
-        });
-    };
 
-    let execute_deposit = move || {
-        let amount = deposit_amount().parse::<f64>().unwrap_or(0.0);
-        let uid = user_id();
+                // Settings link
+                div {
+                    onclick: move |_| props.on_navigate.call(AppView::Settings),
+                    style: format!(
+                        "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
+                        if matches!(props.current_view, AppView::Settings) { "rgba(255,255,255,0.2)" } else { "transparent" },
+                        FONT_BODY
+                    ),
+                    "Settings"
+                }
 
-        spawn(async move {
-            let request = DepositRequest { amount };
-            let client = reqwest::Client::new();
-            match client
-                .post(format!("{}/deposit?user_id={}", API_BASE, uid.clone()))
-                .json(&request)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        status.set(format!("Deposit of ${:.2} successful!", amount));
-                        // Refetch portfolio
-                        if let Ok(resp) = reqwest::get(format!("{}/portfolio?user_id={}", API_BASE, uid)).await {
-                            if let Ok(data) = resp.json::<UserData>().await {
-                                portfolio.set(Some(data));
-                            }
-                        }
-                    } else {
-                        if let Ok(error_resp) = response.json::<TradeErrorResponse>().await {
-                            status.set(error_resp.error);
-                        } else {
-                            status.set("Deposit failed".to_string());
-                        }
-                    }
+                // About link
+                div {
+                    onclick: move |_| props.on_navigate.call(AppView::About),
+                    style: format!(
+                        "cursor: pointer; padding: 8px 16px; border-radius: 4px; background: {}; font-family: {};",
+                        if matches!(props.current_view, AppView::About) { "rgba(255,255,255,0.2)" } else { "transparent" },
+                        FONT_BODY
+                    ),
+                    "About"
                 }
-                Err(e) => status.set(format!("Error: {}", e)),
-            }
-        });
-    };
 
-    let execute_withdrawal = move || {
-        let amount = withdrawal_amount().parse::<f64>().unwrap_or(0.0);
-        let uid = user_id();
+                // Theme toggle, showing the theme it switches to
+                div {
+                    onclick: move |_| props.on_toggle_theme.call(()),
+                    title: if props.theme == Theme::Dark { "Switch to light mode" } else { "Switch to dark mode" },
+                    style: format!("cursor: pointer; padding: 8px 12px; border-radius: 4px; font-family: {};", FONT_BODY),
+                    if props.theme == Theme::Dark { "☀" } else { "🌙" }
+                }
 
-        spawn(async move {
-            let request = WithdrawalRequest { amount };
-            let client = reqwest::Client::new();
-            match client
-                .post(format!("{}/withdrawal?user_id={}", API_BASE, uid.clone()))
-                .json(&request)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        status.set(format!("Withdrawal of ${:.2} successful!", amount));
-                        // Refetch portfolio
-                        if let Ok(resp) = reqwest::get(format!("{}/portfolio?user_id={}", API_BASE, uid)).await {
-                            if let Ok(data) = resp.json::<UserData>().await {
-                                portfolio.set(Some(data));
-                            }
-                        }
-                    } else {
-                        if let Ok(error_resp) = response.json::<TradeErrorResponse>().await {
-                            status.set(error_resp.error);
-                        } else {
-                            status.set("Withdrawal failed".to_string());
+                // Notification center toggle, with the unread count
+                div {
+                    onclick: move |_| props.on_toggle_notifications.call(()),
+                    style: format!("cursor: pointer; padding: 8px 12px; border-radius: 4px; position: relative; font-family: {};", FONT_BODY),
+                    "🔔"
+                    if props.unread_notifications > 0 {
+                        span {
+                            style: format!("position: absolute; top: 0; right: 0; background: {}; color: white; border-radius: 10px; padding: 1px 6px; font-size: 11px; font-weight: bold;", COLOR_RED),
+                            "{props.unread_notifications}"
                         }
                     }
                 }
-                Err(e) => status.set(format!("Error: {}", e)),
-            }
-        });
-    };
 
-    // Fetch bot status when in Trading view
-    let fetch_bot_status = move || {
-        let uid = user_id();
-        spawn(async move {
-            if let Ok(resp) = reqwest::get(format!("{}/bot/status?user_id={}", API_BASE, uid)).await {
-                if let Ok(data) = resp.json::<BotStatusResponse>().await {
-                    bot_status.set(Some(data));
+                // Logout link
+                div {
+                    onclick: move |_| props.on_logout.call(()),
+                    style: format!("cursor: pointer; padding: 8px 16px; border-radius: 4px; background: transparent; font-family: {};", FONT_BODY),
+                    "Logout"
                 }
             }
-        });
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Props)]
+struct StatusBarProps {
+    username: String,
+    running_bots: Option<Vec<BotStatusResponse>>,
+}
+
+#[component]
+fn StatusBar(props: StatusBarProps) -> Element {
+    let bots = props.running_bots.unwrap_or_default();
+    let bot_display = match bots.as_slice() {
+        [] => "Status: No bot running in account..".to_string(),
+        [status] => format!(
+            "Status: {} Bot running in {}",
+            status.bot_name.as_ref().unwrap_or(&"Unknown".to_string()),
+            status.trading_pair.as_ref().unwrap_or(&"Unknown".to_string())
+        ),
+        _ => format!(
+            "Status: {} bots running in {}",
+            bots.len(),
+            bots.iter().filter_map(|status| status.trading_pair.clone()).collect::<Vec<_>>().join(", ")
+        ),
     };
 
-    use_effect(move || {
-        // Poll bot status every 5 seconds when in Trading view
-        match current_view() {
-            AppView::Trading(_) => {
-                fetch_bot_status();
-                spawn(async move {
-                    loop {
-                        gloo_timers::future::TimeoutFuture::new(5_000).await;
-                        if matches!(current_view(), AppView::Trading(_)) {
-                            fetch_bot_status();
-                        } else {
-                            break;
-                        }
-                    }
-                });
+    rsx! {
+        div {
+            style: format!(
+                "position: fixed; bottom: 0; left: 0; right: 0; background: {}; color: white; padding: 10px 30px; display: flex; justify-content: space-between; align-items: center; box-shadow: 0 -2px 4px rgba(0,0,0,0.1); font-family: {}; font-size: 14px; z-index: 1000;",
+                COLOR_LIGHT_GREY,
+                FONT_BODY
+            ),
+            div {
+                "Logged in as: {props.username}"
+            }
+            div {
+                "{bot_display}"
             }
-            _ => {}
         }
-    });
+    }
+}
 
-    let start_bot = move |base_asset: String, quote_asset: String| {
-        let stoploss = bot_stoploss().parse::<f64>().unwrap_or(1000.0);
-        let bot_name = selected_bot();
-        let uid = user_id();
+#[derive(Clone, PartialEq, Props)]
+struct PortfolioPieChartProps {
+    holdings: Vec<(String, f64)>, // (asset, USD value) for each non-cash asset
+    cash_balance: f64,
+}
 
-        spawn(async move {
-            let request = StartBotRequest {
-                user_id: uid.clone(),
-                bot_name,
-                base_asset,
-                quote_asset,
-                stoploss_amount: stoploss,
-            };
+#[component]
+fn PortfolioPieChart(props: PortfolioPieChartProps) -> Element {
+    // Slices in USD: held assets first, cash takes the rest
+    let cash_color = "#66BB6A"; // Green
+    let mut slices: Vec<(String, f64, &str)> = props
+        .holdings
+        .iter()
+        .enumerate()
+        .map(|(i, (asset, value))| (asset.clone(), *value, SLICE_COLORS[i % SLICE_COLORS.len()]))
+        .collect();
+    slices.push(("USD".to_string(), props.cash_balance, cash_color));
+    slices.retain(|(_, value, _)| *value > 0.0);
 
-            let client = reqwest::Client::new();
-            match client
-                .post(format!("{}/bot/start", API_BASE))
-                .json(&request)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        if let Ok(bot_resp) = response.json::<BotResponse>().await {
-                            status.set(bot_resp.message);
-                            // Immediately fetch updated bot status
-                            if let Ok(resp) = reqwest::get(format!("{}/bot/status?user_id={}", API_BASE, uid)).await {
-                                if let Ok(data) = resp.json::<BotStatusResponse>().await {
-                                    bot_status.set(Some(data));
-                                }
-                            }
-                        }
-                    } else {
-                        if let Ok(error) = response.text().await {
-                            status.set(format!("Bot start failed: {}", error));
-                        }
-                    }
-                }
-                Err(e) => status.set(format!("Error: {}", e)),
+    let total_value: f64 = slices.iter().map(|(_, value, _)| value).sum();
+
+    if total_value == 0.0 {
+        return rsx! {
+            div {
+                style: "text-align: center; padding: 20px; color: var(--color-light-grey);",
+                "No assets to display"
             }
-        });
-    };
+        };
+    }
 
-    let stop_bot = move || {
-        let uid = user_id();
+    // SVG pie chart
+    let size = 200.0;
+    let center = size / 2.0;
+    let radius = 80.0;
 
-        spawn(async move {
-            let client = reqwest::Client::new();
-            match client
-                .post(format!("{}/bot/stop?user_id={}", API_BASE, uid.clone()))
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        if let Ok(bot_resp) = response.json::<BotResponse>().await {
-                            status.set(bot_resp.message);
-                            // Immediately fetch updated bot status
-                            if let Ok(resp) = reqwest::get(format!("{}/bot/status?user_id={}", API_BASE, uid)).await {
-                                if let Ok(data) = resp.json::<BotStatusResponse>().await {
-                                    bot_status.set(Some(data));
-                                }
-                            }
-                        }
-                    } else {
-                        if let Ok(error) = response.text().await {
-                            status.set(format!("Bot stop failed: {}", error));
-                        }
-                    }
-                }
-                Err(e) => status.set(format!("Error: {}", e)),
-            }
-        });
-    };
+    fn get_arc_path(cx: f64, cy: f64, r: f64, start_angle: f64, end_angle: f64) -> String {
+        let start_rad = (start_angle - 90.0) * std::f64::consts::PI / 180.0;
+        let end_rad = (end_angle - 90.0) * std::f64::consts::PI / 180.0;
 
-    rsx! {
-        // Add Inter font from Google Fonts
-        head {
-            link {
-                rel: "stylesheet",
-                href: "https://fonts.googleapis.com/css2?family=Inter:wght@400;600;700&display=swap"
-            }
-        }
+        let x1 = cx + r * start_rad.cos();
+        let y1 = cy + r * start_rad.sin();
+        let x2 = cx + r * end_rad.cos();
+        let y2 = cy + r * end_rad.sin();
 
-        div {
-            style: format!("min-height: 100vh; background: {}; font-family: {};", COLOR_PAGE_BG, FONT_BODY),
+        let large_arc = if end_angle - start_angle > 180.0 { 1 } else { 0 };
 
-            // Header (only show when not on Auth page)
-            if !matches!(current_view(), AppView::Auth) {
-                Header {
-                    current_view: current_view(),
-                    username: username(),
-                    on_navigate: move |view: AppView| current_view.set(view),
-                    on_logout: move |_| handle_logout()
-                }
-            }
+        format!("M {},{} L {},{} A {},{} 0 {},{} {},{} Z",
+                cx, cy, x1, y1, r, r, large_arc, 1, x2, y2)
+    }
 
-            // Main content area
+    let mut svg_elements = String::new();
+    let mut legend = Vec::new();
+    let mut current_angle = 0.0;
+
+    for (i, (asset, value, color)) in slices.iter().enumerate() {
+        let pct = (value / total_value) * 100.0;
+        // Last slice closes the circle exactly
+        let end_angle = if i + 1 == slices.len() { 360.0 } else { current_angle + pct / 100.0 * 360.0 };
+        svg_elements.push_str(&format!(
+            "<path d=\"{}\" fill=\"{}\" />",
+            get_arc_path(center, center, radius, current_angle, end_angle),
+            color
+        ));
+        current_angle = end_angle;
+        legend.push((asset.clone(), pct, color.to_string()));
+    }
+
+    rsx! {
+        div {
+            style: "display: flex; flex-direction: column; align-items: center;",
+
+            // SVG Pie Chart
             div {
-                style: if matches!(current_view(), AppView::Auth) {
-                    "".to_string()
-                } else {
-                    "padding-bottom: 60px;".to_string() // Add padding for status bar
+                dangerous_inner_html: format!(
+                    "<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\"><g>{}</g></svg>",
+                    size, size, size, size, svg_elements
+                )
+            }
+
+            // Legend
+            div {
+                style: format!("margin-top: 15px; font-size: 13px; font-family: {};", FONT_BODY),
+                for (asset, pct, color) in legend {
+                    div {
+                        key: "{asset}",
+                        style: "display: flex; align-items: center; gap: 8px; margin-bottom: 5px;",
+                        div { style: format!("width: 16px; height: 16px; background: {}; border-radius: 2px;", color) }
+                        span { "{asset}: {pct:.1}%" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Small price line for a market card
+#[derive(Clone, PartialEq, Props)]
+struct SparklineProps {
+    prices: Vec<PricePoint>,
+    color: String,
+}
+
+#[component]
+fn Sparkline(props: SparklineProps) -> Element {
+    if props.prices.is_empty() {
+        return rsx! {
+            div {
+                style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center; color: {}; font-family: {};", COLOR_PAGE_BG, COLOR_LIGHT_GREY, FONT_BODY),
+                "Loading chart..."
+            }
+        };
+    }
+
+    let prices = &props.prices;
+    let min = prices.iter().map(|p| p.price).fold(f64::INFINITY, f64::min);
+    let max = prices.iter().map(|p| p.price).fold(f64::NEG_INFINITY, f64::max);
+    let range = if (max - min).abs() < 0.01 { 1.0 } else { max - min };
+    let steps = (prices.len() - 1).max(1) as f64;
+
+    let mut path = String::from("M ");
+    for (i, point) in prices.iter().enumerate() {
+        let x = (i as f64 / steps) * 300.0;
+        let y = 100.0 - ((point.price - min) / range) * 100.0;
+        if i == 0 {
+            path.push_str(&format!("{} {} ", x, y));
+        } else {
+            path.push_str(&format!("L {} {} ", x, y));
+        }
+    }
+
+    rsx! {
+        div { style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center;", COLOR_PAGE_BG),
+            svg {
+                width: "100%",
+                height: "100",
+                view_box: "0 0 300 100",
+                path {
+                    d: "{path}",
+                    fill: "none",
+                    stroke: "{props.color}",
+                    stroke_width: "2"
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Props)]
+struct MetricGridProps {
+    cards: Vec<(String, String, Option<bool>)>, // (label, value, green/red if set)
+}
+
+#[component]
+fn MetricGrid(props: MetricGridProps) -> Element {
+    rsx! {
+        div { style: "display: grid; grid-template-columns: repeat(auto-fill, minmax(150px, 1fr)); gap: 15px; margin-bottom: 20px;",
+            for (label, value, positive) in props.cards {
+                div { key: "{label}", style: "padding: 15px; background: var(--color-surface); border-radius: 4px;",
+                    div { style: format!("font-size: 13px; color: {};", COLOR_LIGHT_GREY), "{label}" }
+                    div {
+                        style: format!("font-size: 20px; font-weight: bold; color: {};", match positive {
+                            Some(true) => COLOR_GREEN,
+                            Some(false) => COLOR_RED,
+                            None => COLOR_DARK_GREY,
+                        }),
+                        "{value}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Props)]
+struct EquityChartProps {
+    points: Vec<EquityPoint>,
+    range: String, // "24h", "7d", "30d", or "all"
+    benchmark_asset: String,
+    show_benchmark: bool,
+}
+
+#[component]
+fn EquityChart(props: EquityChartProps) -> Element {
+    let points = &props.points;
+    if points.len() < 2 {
+        return rsx! {
+            p { style: format!("color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY), "Not enough price history yet" }
+        };
+    }
+
+    let width = 1000.0;
+    let height = 260.0;
+    let padding_left = 80.0;
+    let padding_right = 40.0;
+    let padding_top = 20.0;
+    let padding_bottom = 40.0;
+    let chart_left = padding_left;
+    let chart_right = width - padding_right;
+    let chart_top = padding_top;
+    let chart_bottom = height - padding_bottom;
+
+    // Scale to both lines when the benchmark is shown, so they share an axis
+    let values = points.iter().flat_map(|p| {
+        if props.show_benchmark { vec![p.value_usd, p.benchmark_usd] } else { vec![p.value_usd] }
+    });
+    let min_value = values.clone().fold(f64::INFINITY, f64::min);
+    let max_value = values.fold(f64::NEG_INFINITY, f64::max);
+    let value_range = if (max_value - min_value).abs() < 0.01 { 1.0 } else { max_value - min_value };
+
+    let line_path = |value_of: fn(&EquityPoint) -> f64| {
+        let mut path = String::from("M ");
+        for (i, point) in points.iter().enumerate() {
+            let x = chart_left + (i as f64 / (points.len() - 1) as f64) * (chart_right - chart_left);
+            let y = chart_bottom - ((value_of(point) - min_value) / value_range) * (chart_bottom - chart_top);
+            if i == 0 {
+                path.push_str(&format!("{} {} ", x, y));
+            } else {
+                path.push_str(&format!("L {} {} ", x, y));
+            }
+        }
+        path
+    };
+    let value_path = line_path(|p| p.value_usd);
+    let benchmark_path = line_path(|p| p.benchmark_usd);
+
+    // 5 value labels and 6 time labels
+    let h_grid_lines: Vec<(f64, f64)> = (0..5)
+        .map(|i| {
+            let y = chart_top + (i as f64 / 4.0) * (chart_bottom - chart_top);
+            (y, max_value - (i as f64 / 4.0) * value_range)
+        })
+        .collect();
+    let first = points[0].timestamp;
+    let time_span = points[points.len() - 1].timestamp - first;
+    let v_grid_lines: Vec<(f64, String)> = (0..6)
+        .map(|i| {
+            let x = chart_left + (i as f64 / 5.0) * (chart_right - chart_left);
+            let timestamp = first + (time_span as f64 * i as f64 / 5.0) as i64;
+            let dt = chrono::DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+            // Clock time within a day, dates beyond
+            let label = if props.range == "24h" {
+                format!("{:02}:{:02}", dt.hour(), dt.minute())
+            } else {
+                dt.format("%b %d").to_string()
+            };
+            (x, label)
+        })
+        .collect();
+
+    let start_value = points[0].value_usd;
+    let end_value = points[points.len() - 1].value_usd;
+    let change_pct = if start_value > 0.0 { (end_value - start_value) / start_value * 100.0 } else { 0.0 };
+    let benchmark_start = points[0].benchmark_usd;
+    let benchmark_end = points[points.len() - 1].benchmark_usd;
+    let benchmark_pct = if benchmark_start > 0.0 { (benchmark_end - benchmark_start) / benchmark_start * 100.0 } else { 0.0 };
+
+    rsx! {
+        div {
+            p {
+                style: format!("margin: 0 0 10px 0; font-size: 14px; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
+                span {
+                    style: format!("color: {}; font-weight: 600;", if change_pct >= 0.0 { COLOR_GREEN } else { COLOR_RED }),
+                    "Portfolio {change_pct:+.2}%"
+                }
+                if props.show_benchmark {
+                    span { style: format!("margin-left: 20px; color: {};", COLOR_LIGHT_GREY), "Hold {props.benchmark_asset} {benchmark_pct:+.2}%" }
+                }
+            }
+            svg {
+                width: "100%",
+                view_box: "0 0 {width} {height}",
+                style: "display: block; background: var(--color-content-bg);",
+
+                for (y, value) in h_grid_lines.iter() {
+                    line { x1: "{chart_left}", y1: "{y}", x2: "{chart_right}", y2: "{y}", style: "stroke: var(--color-divider)", stroke_width: "1" }
+                    text {
+                        x: "{chart_left - 10.0}",
+                        y: "{y + 4.0}",
+                        font_size: "12",
+                        style: "fill: var(--color-light-grey)",
+                        text_anchor: "end",
+                        "${value:.0}"
+                    }
+                }
+                for (x, label) in v_grid_lines.iter() {
+                    text {
+                        x: "{x}",
+                        y: "{chart_bottom + 20.0}",
+                        font_size: "12",
+                        style: "fill: var(--color-light-grey)",
+                        text_anchor: "middle",
+                        "{label}"
+                    }
+                }
+                rect {
+                    x: "{chart_left}",
+                    y: "{chart_top}",
+                    width: "{chart_right - chart_left}",
+                    height: "{chart_bottom - chart_top}",
+                    fill: "none",
+                    style: "stroke: var(--color-light-grey)",
+                    stroke_width: "2"
+                }
+                if props.show_benchmark {
+                    path { d: "{benchmark_path}", fill: "none", stroke: "#ff9800", stroke_width: "2", stroke_dasharray: "6 4" }
+                }
+                path { d: "{value_path}", fill: "none", style: format!("stroke: {};", COLOR_NAVY), stroke_width: "2" }
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Props)]
+struct ExpandableSectionProps {
+    title: String,
+    children: Element,
+}
+
+#[component]
+fn ExpandableSection(props: ExpandableSectionProps) -> Element {
+    let mut is_expanded = use_signal(|| false);
+
+    rsx! {
+        div {
+            style: format!("background: {}; border-radius: 8px; overflow: hidden; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-bottom: 20px;", COLOR_CONTENT_BG),
+
+            // Header (clickable to expand/collapse)
+            div {
+                onclick: move |_| is_expanded.set(!is_expanded()),
+                style: format!(
+                    "padding: 15px 20px; cursor: pointer; display: flex; justify-content: space-between; align-items: center; background: {}; font-family: {}; font-weight: 600; color: {}; user-select: none;",
+                    if is_expanded() { COLOR_PAGE_BG } else { COLOR_CONTENT_BG },
+                    FONT_BODY,
+                    COLOR_DARK_GREY
+                ),
+                span { "{props.title}" }
+                span { style: "font-size: 20px;", if is_expanded() { "−" } else { "+" } }
+            }
+
+            // Content (only show when expanded)
+            if is_expanded() {
+                div {
+                    style: "padding: 20px;",
+                    {props.children}
+                }
+            }
+        }
+    }
+}
+
+fn App() -> Element {
+    let mut current_view = use_signal(|| AppView::Auth);
+    let mut user_id = use_signal(|| String::new());
+    let mut username = use_signal(|| String::new());
+    let mut session_token = use_signal(|| String::new());
+
+    // Multi-asset price tracking, keyed by asset; `assets` is whatever the backend polls
+    let mut assets = use_signal(|| DEFAULT_ASSETS.iter().map(|a| a.to_string()).collect::<Vec<_>>());
+    let mut prices = use_signal(HashMap::<String, f64>::new);
+    let mut histories = use_signal(HashMap::<String, Vec<PricePoint>>::new);
+
+    let mut portfolio = use_signal(|| None::<UserData>);
+    let mut equity_history = use_signal(Vec::<EquityPoint>::new);
+    let mut equity_metrics = use_signal(PerformanceMetrics::default);
+    let mut equity_range = use_signal(|| String::from("24h"));
+    let mut show_benchmark = use_signal(|| false);
+    let mut benchmark_asset = use_signal(|| String::from("BTC"));
+    let mut quantity = use_signal(|| String::from("0.01"));
+    let mut status = use_signal(|| String::from(""));
+    let mut deposit_amount = use_signal(|| String::from("100"));
+    let mut withdrawal_amount = use_signal(|| String::from("100"));
+    let mut funds = use_signal(|| None::<FundsSummary>);
+
+    // Order ticket
+    let mut trade_side = use_signal(|| String::from("Buy"));
+    let mut order_type = use_signal(|| String::from("market")); // "market", "limit" or "stop"
+    let mut order_price = use_signal(String::new);
+    let mut trade_preview = use_signal(|| None::<TradePreview>);
+    let mut open_orders = use_signal(Vec::<Order>::new);
+
+    // Notification center (newest first) and the toasts currently shown
+    let mut notifications = use_signal(Vec::<AppNotification>::new);
+    let mut toasts = use_signal(Vec::<AppNotification>::new);
+    let mut unread_notifications = use_signal(|| 0usize);
+    let mut show_notifications = use_signal(|| false);
+    let mut next_notification_id = use_signal(|| 0u64);
+    // Categories switched off in the user's settings; missing ones are on
+    let mut notification_preferences = use_signal(HashMap::<String, bool>::new);
+
+    // Record a notification and, unless its category is muted, toast it for a few seconds
+    let notify = move |message: String, level: &'static str, timestamp: String, category: &'static str| {
+        spawn(async move {
+            let id = *next_notification_id.peek();
+            next_notification_id.set(id + 1);
+            let notification = AppNotification { id, timestamp, message, level };
+
+            {
+                let mut list = notifications.write();
+                list.insert(0, notification.clone());
+                list.truncate(MAX_NOTIFICATIONS);
+            }
+            if !*show_notifications.peek() {
+                unread_notifications += 1;
+            }
+            if !notification_preferences.peek().get(category).copied().unwrap_or(true) {
+                return;
+            }
+            toasts.write().push(notification);
+
+            gloo_timers::future::TimeoutFuture::new(TOAST_MS).await;
+            toasts.write().retain(|t| t.id != id);
+        });
+    };
+
+    // Auth form state
+    let mut auth_username = use_signal(|| String::new());
+    let mut auth_password = use_signal(|| String::new());
+    let mut auth_error = use_signal(|| String::new());
+
+    // Bot state
+    let mut running_bots = use_signal(|| None::<Vec<BotStatusResponse>>); // None until first loaded
+    let mut bot_stoploss = use_signal(|| String::from("1000"));
+    let mut bot_dry_run = use_signal(|| false); // Start bots that only log their decisions
+    // Optional trade limits for new bots; blank means no limit
+    let mut bot_max_position = use_signal(String::new);
+    let mut bot_max_trade = use_signal(String::new);
+    let mut bot_max_trades_per_hour = use_signal(String::new);
+    let mut bot_max_base_position = use_signal(String::new);
+    // Optional active hours (UTC) for new bots; blank means any time
+    let mut bot_active_from = use_signal(String::new);
+    let mut bot_active_to = use_signal(String::new);
+    let mut bot_weekdays_only = use_signal(|| false);
+    let mut selected_bot = use_signal(|| String::from("naive_momentum"));
+    let mut available_bots = use_signal(Vec::<AvailableBot>::new); // Registered strategies; empty until loaded
+    let mut strategy_config = use_signal(String::new); // Strategy configuration JSON to copy or start from
+    let mut bot_script = use_signal(String::new); // Rhai source for the script strategy, the example until edited
+
+    // Backtest form (dates are UTC days) and the latest run
+    let mut backtest_strategy = use_signal(|| String::from("naive_momentum"));
+    let mut backtest_market = use_signal(|| String::from("BTC"));
+    let mut backtest_start = use_signal(|| (chrono::Utc::now() - chrono::Duration::days(7)).format("%Y-%m-%d").to_string());
+    let mut backtest_end = use_signal(|| chrono::Utc::now().format("%Y-%m-%d").to_string());
+    let mut backtest_balance = use_signal(|| String::from("10000"));
+    let mut backtest_stoploss = use_signal(|| String::from("1000"));
+    let mut backtest_trend_ticks = use_signal(|| String::from("3"));
+    let mut backtest_step_pct = use_signal(|| String::from("1"));
+    let mut backtest_cooldown = use_signal(|| String::from("3"));
+    let mut backtest_run = use_signal(|| None::<BacktestRun>);
+    let mut backtest_risk = use_signal(|| None::<(String, RiskScore)>); // Run id and its risk grade
+    let mut backtest_history = use_signal(Vec::<BacktestRunSummary>::new);
+    let mut backtest_error = use_signal(String::new);
+    let mut optimize_trend_ticks = use_signal(|| String::from("2, 3, 4, 5"));
+    let mut optimize_step_pct = use_signal(|| String::from("0.5, 1, 2"));
+    let mut optimize_cooldown = use_signal(|| String::from("0, 3"));
+    let mut optimize_rank_by = use_signal(|| String::from("total_return"));
+    let mut optimize_running = use_signal(|| false);
+    let mut optimize_result = use_signal(|| None::<OptimizationResult>);
+    let mut optimize_error = use_signal(String::new);
+    let mut walk_forward_in_sample = use_signal(|| String::from("5"));
+    let mut walk_forward_out_of_sample = use_signal(|| String::from("1"));
+    let mut walk_forward_running = use_signal(|| false);
+    let mut walk_forward_result = use_signal(|| None::<WalkForwardResult>);
+    let mut walk_forward_error = use_signal(String::new);
+    let mut replay_speed = use_signal(|| String::from("100"));
+    let mut replay_run = use_signal(|| None::<ReplayRun>);
+    let mut replay_error = use_signal(String::new);
+
+    // Chart state
+    let mut selected_timeframe = use_signal(|| String::from("1h"));
+    let mut chart_type = use_signal(|| String::from("line")); // "line" or "candlestick"
+    let mut candle_history = use_signal(|| Vec::<Candle>::new());
+    let mut ticker = use_signal(|| None::<TickerStats>);
+    let mut headlines = use_signal(Vec::<Headline>::new);
+
+    // Indicator state
+    let mut indicator_data = use_signal(|| None::<IndicatorResponse>);
+    let mut chart_indicators = use_signal(default_chart_indicators);
+    let mut theme = use_signal(load_theme);
+    let mut show_indicator_settings = use_signal(|| false);
+    let mut new_indicator_kind = use_signal(|| "sma".to_string());
+    let mut new_indicator_period = use_signal(|| "20".to_string());
+
+    // Account settings page
+    let mut display_currency = use_signal(|| "USD".to_string());
+    let mut daily_summary_time = use_signal(String::new); // Local "HH:MM"; empty when off
+    let mut utc_offset_minutes = use_signal(|| None::<i32>); // The saved timezone; UTC when unset
+    let mut current_password = use_signal(String::new);
+    let mut new_password = use_signal(String::new);
+    let mut confirm_password = use_signal(String::new);
+    let mut password_status = use_signal(String::new);
+    let mut sessions = use_signal(Vec::<SessionEntry>::new);
+    let mut delete_password = use_signal(String::new);
+    let mut delete_status = use_signal(String::new);
+
+    // Live prices over a WebSocket; send it the asset list when new assets start being polled
+    let price_stream = use_coroutine(move |mut subscriptions: UnboundedReceiver<Vec<String>>| async move {
+        let mut delay = RECONNECT_MIN_MS;
+        loop {
+            match WebSocket::open(&ws_url("/ws/prices")) {
+                Ok(socket) => {
+                    let (mut requests, mut updates) = socket.split();
+                    loop {
+                        match select(updates.next(), subscriptions.next()).await {
+                            Either::Left((Some(Ok(Message::Text(text))), _)) => {
+                                if let Ok(update) = serde_json::from_str::<PriceUpdate>(&text) {
+                                    prices.write().insert(update.asset, update.price);
+                                    delay = RECONNECT_MIN_MS;
+                                }
+                            }
+                            Either::Left((Some(Ok(Message::Bytes(_))), _)) => {}
+                            Either::Left((Some(Err(e)), _)) => {
+                                web_sys::console::log_1(&format!("Price stream closed: {}", e).into());
+                                break;
+                            }
+                            Either::Left((None, _)) => break,
+                            Either::Right((Some(assets), _)) => {
+                                let request = serde_json::json!({ "action": "subscribe", "assets": assets });
+                                if requests.send(Message::Text(request.to_string())).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Either::Right((None, _)) => return,
+                        }
+                    }
+                }
+                Err(e) => web_sys::console::log_1(&format!("Could not open price stream: {}", e).into()),
+            }
+            reconnect_after(&mut delay).await;
+        }
+    });
+
+    // Fetch polled assets (and their latest prices, until the stream sends some) on mount and every minute
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                if let Ok(resp) = reqwest::get(format!("{}/stats", API_BASE)).await {
+                    if let Ok(data) = resp.json::<StatsResponse>().await {
+                        let polled: Vec<String> = data.feeds.iter().map(|f| f.asset.clone()).collect();
+                        // Only notify on change, since the history effect below tracks the list
+                        if !polled.is_empty() && *assets.peek() != polled {
+                            price_stream.send(polled.clone());
+                            assets.set(polled);
+                        }
+                        let mut current = prices.write();
+                        for feed in data.feeds {
+                            if let Some(price) = feed.latest_price {
+                                current.entry(feed.asset).or_insert(price);
+                            }
+                        }
+                    }
+                }
+                gloo_timers::future::TimeoutFuture::new(60_000).await;
+            }
+        });
+    });
+
+    // Fetch asset display metadata on mount and when the polled assets change
+    use_effect(move || {
+        assets();  // Track dependency
+        spawn(async move {
+            if let Ok(resp) = reqwest::get(format!("{}/assets", API_BASE)).await {
+                if let Ok(list) = resp.json::<Vec<AssetInfo>>().await {
+                    *ASSET_INFO.write() = list.into_iter().map(|info| (info.asset.clone(), info)).collect();
+                }
+            }
+        });
+    });
+
+    // Fetch the registered bot strategies once on mount
+    use_effect(move || {
+        spawn(async move {
+            if let Ok(resp) = reqwest::get(format!("{}/bots/available", API_BASE)).await {
+                if let Ok(list) = resp.json::<Vec<AvailableBot>>().await {
+                    let example = list
+                        .iter()
+                        .find(|bot| bot.strategy == "script")
+                        .and_then(|bot| bot.default_parameters.get("script")?.as_str().map(str::to_string));
+                    if let Some(example) = example {
+                        bot_script.set(example);
+                    }
+                    available_bots.set(list);
+                }
+            }
+        });
+    });
+
+    // Fetch price history for every polled asset
+    let fetch_histories = move || {
+        let timeframe = selected_timeframe.peek().clone();
+        for asset in assets.peek().iter().cloned() {
+            let timeframe = timeframe.clone();
+            spawn(async move {
+                let url = format!("{}/price/history?asset={}&timeframe={}", API_BASE, asset, timeframe);
+                if let Ok(resp) = reqwest::get(&url).await {
+                    if let Ok(data) = resp.json::<PriceHistoryResponse>().await {
+                        web_sys::console::log_1(&format!("{} history received: {} points", asset, data.prices.len()).into());
+                        histories.write().insert(asset, data.prices);
+                    }
+                }
+            });
+        }
+    };
+
+    // Re-fetch histories when the timeframe or the polled assets change
+    use_effect(move || {
+        selected_timeframe();  // Track dependency
+        assets();  // Track dependency
+        fetch_histories();
+    });
+
+    // Periodic history refresh (every 30 seconds)
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(30_000).await;
+                fetch_histories();
+            }
+        });
+    });
+
+    // Market shown in the Trading view; changes only when a different market is picked
+    let selected_market = use_memo(move || match current_view() {
+        AppView::Trading(market) => Some(market),
+        _ => None,
+    });
+
+    // Fetch candlestick data for the selected market (base/quote)
+    let fetch_candle_history = move |asset: &str| {
+        let timeframe = selected_timeframe();
+        let asset = asset.to_string();
+        spawn(async move {
+            let url = format!("{}/price/candles?asset={}&timeframe={}", API_BASE, asset, timeframe);
+            if let Ok(resp) = reqwest::get(&url).await {
+                #[derive(Deserialize)]
+                struct CandleHistoryResponse {
+                    candles: Vec<Candle>,
+                }
+                if let Ok(data) = resp.json::<CandleHistoryResponse>().await {
+                    candle_history.set(data.candles);
+                }
+            }
+        });
+    };
+
+    // Fetch indicator data for the selected market
+    let mut fetch_indicators = move |asset: &str| {
+        let timeframe = selected_timeframe();
+
+        // Only fetch if timeframe is 1h (indicators only supported for 1h)
+        if timeframe != "1h" {
+            indicator_data.set(None);
+            return;
+        }
+
+        // Build indicators list from the enabled chart indicators
+        let indicators = enabled_indicator_keys(&chart_indicators());
+
+        // If no indicators selected, clear data
+        if indicators.is_empty() {
+            indicator_data.set(None);
+            return;
+        }
+
+        let indicators_param = indicators.join(",");
+        let asset = asset.to_string();
+
+        spawn(async move {
+            let url = format!(
+                "{}/indicators?asset={}&timeframe={}&indicators={}",
+                API_BASE, asset, timeframe, indicators_param
+            );
+            web_sys::console::log_1(&format!("Fetching indicators: {}", url).into());
+            match reqwest::get(&url).await {
+                Ok(resp) => {
+                    web_sys::console::log_1(&format!("Response status: {}", resp.status()).into());
+                    match resp.json::<IndicatorResponse>().await {
+                        Ok(data) => {
+                            web_sys::console::log_1(&format!("Received indicator data with {} indicators", data.indicators.len()).into());
+                            indicator_data.set(Some(data));
+                        }
+                        Err(e) => {
+                            web_sys::console::log_1(&format!("Failed to parse indicator response: {:?}", e).into());
+                        }
+                    }
+                }
+                Err(e) => {
+                    web_sys::console::log_1(&format!("Failed to fetch indicators: {:?}", e).into());
+                }
+            }
+        });
+    };
+
+    // 24h ticker for the selected USD market, refreshed every minute
+    let fetch_ticker = move |asset: String| {
+        spawn(async move {
+            let url = format!("{}/price/stats?asset={}", API_BASE, asset);
+            let stats = match reqwest::get(&url).await {
+                Ok(resp) if resp.status().is_success() => resp.json::<TickerStats>().await.ok(),
+                _ => None,
+            };
+            // The market may have changed while this was in flight
+            let current = selected_market.peek().clone().map(|m| split_market(&m));
+            if current.is_some_and(|(base, quote)| base == asset && quote == "USD") {
+                ticker.set(stats);
+            }
+        });
+    };
+
+    use_effect(move || {
+        ticker.set(None);
+        if let Some((base_asset, quote_asset)) = selected_market().map(|m| split_market(&m)) {
+            if quote_asset == "USD" {
+                fetch_ticker(base_asset);
+            }
+        }
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(60_000).await;
+                if let Some((base_asset, quote_asset)) = selected_market.peek().clone().map(|m| split_market(&m)) {
+                    if quote_asset == "USD" {
+                        fetch_ticker(base_asset);
+                    }
+                }
+            }
+        });
+    });
+
+    // Headlines about the selected market's base asset over the chart's timeframe
+    use_effect(move || {
+        let timeframe = selected_timeframe();
+        headlines.set(Vec::new());
+        if let Some((base_asset, _)) = selected_market().map(|m| split_market(&m)) {
+            spawn(async move {
+                let url = format!("{}/news?asset={}&timeframe={}&limit=10", API_BASE, base_asset, timeframe);
+                if let Ok(resp) = reqwest::get(&url).await {
+                    if let Ok(news) = resp.json::<NewsResponse>().await {
+                        headlines.set(news.headlines);
+                    }
+                }
+            });
+        }
+    });
+
+    // Re-fetch candle data when timeframe changes (only when in candlestick mode)
+    use_effect(move || {
+        let _timeframe = selected_timeframe();
+        let current_chart_type = chart_type();
+        let market = selected_market();
+
+        // Fetch immediately if we're in candlestick mode and in Trading view
+        if current_chart_type == "candlestick" {
+            if let Some(market) = market {
+                let (base_asset, _) = split_market(&market);
+                fetch_candle_history(&base_asset);
+            }
+        }
+    });
+
+    // Periodic candle history refresh based on timeframe
+    // 1h view: refresh every 60 seconds (new 1-minute candle)
+    // 8h/24h views: refresh every 5 minutes (new 5-minute candle)
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                let timeframe = selected_timeframe();
+                let current_chart_type = chart_type();
+
+                // Only poll if in candlestick mode
+                if current_chart_type == "candlestick" {
+                    // Determine refresh interval based on timeframe
+                    let interval_ms = match timeframe.as_str() {
+                        "1h" => 60_000,  // 1 minute for 1h view
+                        "8h" | "24h" => 300_000,  // 5 minutes for 8h/24h views
+                        _ => 60_000,  // Default to 1 minute
+                    };
+
+                    gloo_timers::future::TimeoutFuture::new(interval_ms).await;
+
+                    // Fetch candles for the current trading pair
+                    if let Some(market) = selected_market.peek().clone() {
+                        let (base_asset, _) = split_market(&market);
+                        fetch_candle_history(&base_asset);
+                    }
+                } else {
+                    // If not in candlestick mode, just wait a bit before checking again
+                    gloo_timers::future::TimeoutFuture::new(5_000).await;
+                }
+            }
+        });
+    });
+
+    // Fetch indicators when the chart indicators or timeframe change
+    use_effect(move || {
+        let (market, _tf, _indicators) = (
+            selected_market(),
+            selected_timeframe(),
+            chart_indicators()
+        );
+
+        if let Some(market) = market {
+            let (base_asset, _) = split_market(&market);
+            fetch_indicators(&base_asset);
+        }
+    });
+
+    // Resume a stored session, so reloading the page doesn't log the user out
+    use_effect(move || {
+        let Some(token) = load_session_token() else {
+            return;
+        };
+        spawn(async move {
+            match api_get(format!("{}/session", API_BASE), &token).send().await {
+                Ok(response) if response.status().is_success() => {
+                    if let Ok(session) = response.json::<AuthResponse>().await {
+                        session_token.set(token);
+                        user_id.set(session.user_id);
+                        username.set(session.username);
+                        current_view.set(AppView::Dashboard);
+                    }
+                }
+                // Expired or revoked: log in again
+                Ok(_) => store_session_token(None),
+                // Server unreachable: keep the token for the next attempt
+                Err(e) => web_sys::console::log_1(&format!("Could not resume session: {}", e).into()),
+            }
+        });
+    });
+
+    // Load the user's settings on login; guests and logged-out users get the defaults
+    use_effect(move || {
+        let token = session_token();
+        if token.is_empty() {
+            chart_indicators.set(default_chart_indicators());
+            display_currency.set("USD".to_string());
+            notification_preferences.set(HashMap::new());
+            daily_summary_time.set(String::new());
+            utc_offset_minutes.set(None);
+            return;
+        }
+        let uid = user_id.peek().clone();
+        spawn(async move {
+            let url = format!("{}/settings?user_id={}", API_BASE, uid);
+            let Ok(resp) = api_get(url, &token).send().await else {
+                return;
+            };
+            let Ok(data) = resp.json::<UserSettings>().await else {
+                return;
+            };
+            let indicators = data
+                .settings
+                .get("chart_indicators")
+                .and_then(|value| serde_json::from_value::<Vec<IndicatorSetting>>(value.clone()).ok());
+            if let Some(indicators) = indicators {
+                chart_indicators.set(indicators);
+            }
+            let saved_theme = data.settings.get("theme").and_then(|value| value.as_str()).and_then(Theme::parse);
+            if let Some(saved_theme) = saved_theme {
+                store_theme(saved_theme);
+                theme.set(saved_theme);
+            }
+            if let Some(currency) = data.settings.get("display_currency").and_then(|value| value.as_str()) {
+                display_currency.set(currency.to_string());
+            }
+            let preferences = data
+                .settings
+                .get("notification_preferences")
+                .and_then(|value| serde_json::from_value::<HashMap<String, bool>>(value.clone()).ok());
+            if let Some(preferences) = preferences {
+                notification_preferences.set(preferences);
+            }
+            let summary_time = data.settings.get("daily_summary").and_then(|value| value["time"].as_str());
+            daily_summary_time.set(summary_time.unwrap_or_default().to_string());
+            let offset = data.settings.get("utc_offset_minutes").and_then(|value| value.as_i64());
+            utc_offset_minutes.set(offset.map(|minutes| minutes as i32));
+        });
+    });
+
+    // Change the chart indicators, saving them to the user's settings (guests share one account, so only locally)
+    let mut set_chart_indicators = move |indicators: Vec<IndicatorSetting>| {
+        chart_indicators.set(indicators.clone());
+        let token = session_token.peek().clone();
+        if token.is_empty() {
+            return;
+        }
+        let uid = user_id.peek().clone();
+        spawn(async move {
+            let url = format!("{}/settings?user_id={}", API_BASE, uid);
+            let body = serde_json::json!({ "chart_indicators": indicators });
+            if let Err(e) = api_patch(url, &token).json(&body).send().await {
+                web_sys::console::log_1(&format!("Failed to save chart indicators: {}", e).into());
+            }
+        });
+    };
+
+    // Switch between light and dark, saving the choice in this browser and the user's settings
+    let mut toggle_theme = move || {
+        let next = theme.peek().toggled();
+        theme.set(next);
+        store_theme(next);
+        let token = session_token.peek().clone();
+        if token.is_empty() {
+            return;
+        }
+        let uid = user_id.peek().clone();
+        spawn(async move {
+            let url = format!("{}/settings?user_id={}", API_BASE, uid);
+            let body = serde_json::json!({ "theme": next.as_str() });
+            if let Err(e) = api_patch(url, &token).json(&body).send().await {
+                web_sys::console::log_1(&format!("Failed to save theme: {}", e).into());
+            }
+        });
+    };
+
+    // Save one setting for a logged-in user (guests keep changes for this visit only)
+    let save_setting = move |key: &'static str, value: serde_json::Value| {
+        let token = session_token.peek().clone();
+        if token.is_empty() {
+            return;
+        }
+        let uid = user_id.peek().clone();
+        spawn(async move {
+            let url = format!("{}/settings?user_id={}", API_BASE, uid);
+            let body = serde_json::json!({ key: value });
+            if let Err(e) = api_patch(url, &token).json(&body).send().await {
+                web_sys::console::log_1(&format!("Failed to save {}: {}", key, e).into());
+            }
+        });
+    };
+
+    // Auth handlers
+    let mut handle_login = move || {
+        // Validate inputs
+        let uname = auth_username();
+        let pwd = auth_password();
+
+        if uname.trim().is_empty() || pwd.trim().is_empty() {
+            auth_error.set("Username and password are required".to_string());
+            return;
+        }
+
+        spawn(async move {
+            auth_error.set(String::new());
+
+            let client = reqwest::Client::new();
+            let login_req = LoginRequest {
+                username: uname,
+                password: pwd,
+            };
+
+            match client
+                .post(format!("{}/login", API_BASE))
+                .json(&login_req)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        if let Ok(auth_resp) = response.json::<AuthResponse>().await {
+                            store_session_token(Some(&auth_resp.session_token));
+                            session_token.set(auth_resp.session_token);
+                            user_id.set(auth_resp.user_id);
+                            username.set(auth_resp.username);
+                            current_view.set(AppView::Dashboard);
+                        }
+                    } else {
+                        if let Ok(err_resp) = response.json::<ErrorResponse>().await {
+                            auth_error.set(err_resp.error);
+                        } else {
+                            auth_error.set("Login failed".to_string());
+                        }
+                    }
+                }
+                Err(e) => auth_error.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let mut handle_signup = move || {
+        // Validate inputs
+        let uname = auth_username();
+        let pwd = auth_password();
+
+        if uname.trim().is_empty() || pwd.trim().is_empty() {
+            auth_error.set("Username and password are required".to_string());
+            return;
+        }
+
+        if pwd.len() < 6 {
+            auth_error.set("Password must be at least 6 characters".to_string());
+            return;
+        }
+
+        spawn(async move {
+            auth_error.set(String::new());
+
+            let client = reqwest::Client::new();
+            let signup_req = SignupRequest {
+                username: uname.clone(),
+                password: pwd,
+            };
+
+            match client
+                .post(format!("{}/signup", API_BASE))
+                .json(&signup_req)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        if let Ok(auth_resp) = response.json::<AuthResponse>().await {
+                            store_session_token(Some(&auth_resp.session_token));
+                            session_token.set(auth_resp.session_token);
+                            user_id.set(auth_resp.user_id);
+                            username.set(auth_resp.username);
+                            current_view.set(AppView::Dashboard);
+                        }
+                    } else {
+                        if let Ok(err_resp) = response.json::<ErrorResponse>().await {
+                            auth_error.set(err_resp.error);
+                        } else {
+                            auth_error.set("Signup failed".to_string());
+                        }
+                    }
+                }
+                Err(e) => auth_error.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let mut handle_guest = move || {
+        session_token.set(String::new());
+        user_id.set("demo_user".to_string());
+        username.set("Guest".to_string());
+        current_view.set(AppView::Dashboard);
+    };
+
+    let mut handle_logout = move || {
+        // End the session server-side too, so the token can't be reused
+        let token = session_token();
+        if !token.is_empty() {
+            spawn(async move {
+                let _ = api_post(format!("{}/logout", API_BASE), &token).send().await;
+            });
+        }
+        store_session_token(None);
+        session_token.set(String::new());
+        user_id.set(String::new());
+        username.set(String::new());
+        auth_username.set(String::new());
+        auth_password.set(String::new());
+        auth_error.set(String::new());
+        current_view.set(AppView::Auth);
+    };
+
+    // Fetch portfolio
+    let fetch_portfolio = move || {
+        let uid = user_id();
+        let token = session_token();
+        spawn(async move {
+            if let Ok(resp) = api_get(format!("{}/portfolio?user_id={}", API_BASE, uid), &token).send().await {
+                if let Ok(data) = resp.json::<UserData>().await {
+                    portfolio.set(Some(data));
+                }
+            }
+        });
+    };
+
+    let fetch_open_orders = move || {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let url = format!("{}/orders?user_id={}&status=open", API_BASE, uid);
+            if let Ok(resp) = api_get(url, &token).send().await {
+                if let Ok(data) = resp.json::<Vec<Order>>().await {
+                    open_orders.set(data);
+                }
+            }
+        });
+    };
+
+    let fetch_funds = move || {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            if let Ok(resp) = api_get(format!("{}/funds?user_id={}", API_BASE, uid), &token).send().await {
+                if let Ok(data) = resp.json::<FundsSummary>().await {
+                    funds.set(Some(data));
+                }
+            }
+        });
+    };
+
+    let fetch_backtests = move || {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            if let Ok(resp) = api_get(format!("{}/backtest?user_id={}", API_BASE, uid), &token).send().await {
+                if let Ok(data) = resp.json::<Vec<BacktestRunSummary>>().await {
+                    backtest_history.set(data);
+                }
+            }
+        });
+    };
+
+    let fetch_sessions = move || {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            if let Ok(resp) = api_get(format!("{}/sessions?user_id={}", API_BASE, uid), &token).send().await {
+                if let Ok(data) = resp.json::<Vec<SessionEntry>>().await {
+                    sessions.set(data);
+                }
+            }
+        });
+    };
+
+    let revoke_session = move |session_id: String| {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let url = format!("{}/sessions/{}?user_id={}", API_BASE, session_id, uid);
+            if let Err(e) = api_delete(url, &token).send().await {
+                web_sys::console::log_1(&format!("Failed to revoke session: {}", e).into());
+            }
+            fetch_sessions();
+        });
+    };
+
+    // Revoke every session, this one included, then return to the login page
+    let logout_everywhere = move || {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let _ = api_delete(format!("{}/sessions?user_id={}", API_BASE, uid), &token).send().await;
+            let mut handle_logout = handle_logout;
+            handle_logout();
+        });
+    };
+
+    let mut change_password = move || {
+        let current = current_password();
+        let new = new_password();
+        if current.is_empty() || new.is_empty() {
+            password_status.set("Enter your current and new password".to_string());
+            return;
+        }
+        if new != confirm_password() {
+            password_status.set("New passwords don't match".to_string());
+            return;
+        }
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let url = format!("{}/account/password?user_id={}", API_BASE, uid);
+            let body = serde_json::json!({ "current_password": current, "new_password": new });
+            match api_post(url, &token).json(&body).send().await {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        let revoked = response.json::<ChangePasswordResponse>().await.map(|r| r.sessions_revoked).unwrap_or(0);
+                        password_status.set(format!("Password changed; {} other session(s) signed out", revoked));
+                        current_password.set(String::new());
+                        new_password.set(String::new());
+                        confirm_password.set(String::new());
+                        fetch_sessions();
+                    } else if let Ok(error_resp) = response.json::<ErrorResponse>().await {
+                        password_status.set(error_resp.error);
+                    }
+                }
+                Err(e) => password_status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let delete_account = move || {
+        let password = delete_password();
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let url = format!("{}/account?user_id={}", API_BASE, uid);
+            // Accounts signed up through GitHub or Google have no password; a recent sign-in confirms instead
+            let body = if password.is_empty() {
+                serde_json::json!({})
+            } else {
+                serde_json::json!({ "password": password })
+            };
+            match api_delete(url, &token).json(&body).send().await {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        delete_password.set(String::new());
+                        delete_status.set(String::new());
+                        let mut handle_logout = handle_logout;
+                        handle_logout();
+                    } else if let Ok(error_resp) = response.json::<ErrorResponse>().await {
+                        delete_status.set(error_resp.error);
+                    }
+                }
+                Err(e) => delete_status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    use_effect(move || {
+        // Fetch portfolio when logged in (Dashboard or Trading view), and open orders for the ticket
+        match current_view() {
+            AppView::Dashboard => {
+                fetch_portfolio();
+            }
+            AppView::Trading(_) => {
+                fetch_portfolio();
+                fetch_open_orders();
+            }
+            AppView::Funds => {
+                fetch_funds();
+            }
+            AppView::Backtest => {
+                fetch_backtests();
+            }
+            AppView::Settings => {
+                fetch_sessions();
+            }
+            _ => {}
+        }
+    });
+
+    // Fetch the equity curve for the chosen range
+    let fetch_equity_history = move || {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        let range = equity_range.peek().clone();
+        spawn(async move {
+            let url = format!("{}/portfolio/history?user_id={}&range={}", API_BASE, uid, range);
+            if let Ok(resp) = api_get(url, &token).send().await {
+                if let Ok(data) = resp.json::<PortfolioHistoryResponse>().await {
+                    benchmark_asset.set(data.benchmark_asset);
+                    equity_history.set(data.points);
+                    equity_metrics.set(data.metrics);
+                }
+            }
+        });
+    };
+
+    // Re-fetch on the Dashboard, and whenever the range changes there
+    use_effect(move || {
+        equity_range();  // Track dependency
+        if current_view() == AppView::Dashboard {
+            fetch_equity_history();
+        }
+    });
+
+    // Refresh the equity curve every minute while the Dashboard is open
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(60_000).await;
+                if *current_view.peek() == AppView::Dashboard {
+                    fetch_equity_history();
+                }
+            }
+        });
+    });
+
+    // Balance changes pushed as server-sent events, for accounts with a session token
+    let account_events = use_coroutine(move |mut sessions: UnboundedReceiver<String>| async move {
+        let mut token = String::new();
+        let mut delay = RECONNECT_MIN_MS;
+        'connect: loop {
+            // Guests have no token: wait for a login
+            while token.is_empty() {
+                match sessions.next().await {
+                    Some(next) => token = next,
+                    None => return,
+                }
+            }
+
+            let url = format!("{}/events?token={}", API_BASE, token);
+            let subscriptions = EventSource::new(&url).and_then(|mut source| {
+                let balances = source.subscribe("balance")?;
+                let resyncs = source.subscribe("resync")?;
+                let notifications = source.subscribe("notification")?;
+                Ok((source, futures_util::stream::select_all([balances, resyncs, notifications])))
+            });
+            match subscriptions {
+                Ok((_source, mut events)) => loop {
+                    match select(events.next(), sessions.next()).await {
+                        Either::Left((Some(Ok((kind, message))), _)) => {
+                            delay = RECONNECT_MIN_MS;
+                            if kind == "resync" {
+                                fetch_portfolio();
+                                fetch_open_orders();
+                                continue;
+                            }
+                            if kind == "notification" {
+                                let Some(notification) = message
+                                    .data()
+                                    .as_string()
+                                    .and_then(|data| serde_json::from_str::<NotificationEvent>(&data).ok())
+                                else {
+                                    continue;
+                                };
+                                // Fills also arrive as balance events; failures only here
+                                if notification.kind.starts_with("order_") {
+                                    fetch_open_orders();
+                                }
+                                if let Some((text, level)) = notification_message(&notification) {
+                                    notify(text, level, notification.timestamp, notification_category(&notification.kind));
+                                }
+                                continue;
+                            }
+                            let Some(update) = message
+                                .data()
+                                .as_string()
+                                .and_then(|data| serde_json::from_str::<BalanceUpdate>(&data).ok())
+                            else {
+                                continue;
+                            };
+                            if update.reason == "initial" {
+                                // (Re)connected: changes may have been missed, so refetch the history too
+                                fetch_portfolio();
+                            } else if let Some(p) = portfolio.write().as_mut() {
+                                p.asset_balances = update.asset_balances;
+                                p.trade_history.extend(update.trade);
+                            }
+                        }
+                        Either::Left((Some(Err(e)), _)) => {
+                            web_sys::console::log_1(&format!("Event stream closed: {}", e).into());
+                            break;
+                        }
+                        Either::Left((None, _)) => break,
+                        // Logged in as someone else, or out: reconnect (or wait) right away
+                        Either::Right((Some(next), _)) => {
+                            token = next;
+                            delay = RECONNECT_MIN_MS;
+                            continue 'connect;
+                        }
+                        Either::Right((None, _)) => return,
+                    }
+                },
+                Err(e) => web_sys::console::log_1(&format!("Could not open event stream: {}", e).into()),
+            }
+            reconnect_after(&mut delay).await;
+        }
+    });
+
+    // Guests have no event stream: poll the portfolio every 10 seconds while their bot runs
+    // or orders are open
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(10_000).await;
+
+                // Only poll if in trading view and bot is active
+                if session_token.peek().is_empty() && matches!(current_view(), AppView::Trading(_)) {
+                    if running_bots().is_some_and(|bots| !bots.is_empty()) {
+                        fetch_portfolio();
+                    }
+                    if !open_orders.peek().is_empty() {
+                        fetch_portfolio();
+                        fetch_open_orders();
+                    }
+                }
+            }
+        });
+    });
+
+    let execute_trade = move |side: &str, asset: &str, quote_asset_opt: Option<String>| {
+        let side = side.to_string();
+        let asset = asset.to_string();
+        let qty = quantity().parse::<f64>().unwrap_or(0.0);
+        let uid = user_id();
+        let token = session_token();
+
+        spawn(async move {
+            let trade = TradeRequest {
+                asset: asset.clone(),
+                quote_asset: quote_asset_opt,
+                side: side.clone(),
+                quantity: qty,
+            };
+
+            match api_post(format!("{}/trade?user_id={}", API_BASE, uid.clone()), &token)
+                .json(&trade)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        status.set(format!("{} successful!", side));
+                        // Refetch portfolio after successful trade
+                        if let Ok(resp) = api_get(format!("{}/portfolio?user_id={}", API_BASE, uid), &token).send().await {
+                            if let Ok(data) = resp.json::<UserData>().await {
+                                portfolio.set(Some(data));
+                            }
+                        }
+                    } else {
+                        // Capture status before consuming response
+                        let status_code = response.status();
+                        // Try to parse the error message from the response
+                        if let Ok(error_resp) = response.json::<ErrorResponse>().await {
+                            status.set(error_resp.error);
+                        } else {
+                            status.set(format!("Trade failed: {}", status_code));
+                        }
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let place_order = move |asset: &str, quote_asset_opt: Option<String>| {
+        let request = OrderRequest {
+            asset: asset.to_string(),
+            quote_asset: quote_asset_opt,
+            side: trade_side(),
+            order_type: order_type(),
+            quantity: quantity().parse::<f64>().unwrap_or(0.0),
+            price: order_price().parse::<f64>().unwrap_or(0.0),
+        };
+        let uid = user_id();
+        let token = session_token();
+
+        spawn(async move {
+            match api_post(format!("{}/orders?user_id={}", API_BASE, uid), &token)
+                .json(&request)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        status.set(format!("{} {} order placed", request.side, request.order_type));
+                        fetch_open_orders();
+                    } else {
+                        let status_code = response.status();
+                        if let Ok(error_resp) = response.json::<ErrorResponse>().await {
+                            status.set(error_resp.error);
+                        } else {
+                            status.set(format!("Order failed: {}", status_code));
+                        }
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let cancel_order = move |order_id: String| {
+        let uid = user_id();
+        let token = session_token();
+
+        spawn(async move {
+            match api_delete(format!("{}/orders/{}?user_id={}", API_BASE, order_id, uid), &token).send().await {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        status.set("Order cancelled".to_string());
+                    } else if let Ok(error_resp) = response.json::<ErrorResponse>().await {
+                        status.set(error_resp.error);
+                    }
+                    // Refresh either way: a failed cancel usually means it just filled
+                    fetch_open_orders();
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    // Re-estimate the ticket whenever it changes (and, for market trades, when the price moves)
+    use_effect(move || {
+        let Some(market) = selected_market() else {
+            return;
+        };
+        let side = trade_side();
+        let kind = order_type();
+        let qty = quantity().parse::<f64>().ok().filter(|q| *q > 0.0);
+        let price = order_price().parse::<f64>().ok().filter(|p| *p > 0.0);
+        if kind == "market" {
+            prices(); // Track dependency
+        }
+
+        let Some(qty) = qty else {
+            trade_preview.set(None);
+            return;
+        };
+        if kind != "market" && price.is_none() {
+            trade_preview.set(None);
+            return;
+        }
+
+        let (base_asset, quote_asset) = split_market(&market);
+        let request = PreviewRequest {
+            asset: base_asset,
+            quote_asset: (quote_asset != "USD").then_some(quote_asset),
+            side,
+            quantity: qty,
+            order_type: (kind != "market").then_some(kind),
+            price,
+        };
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let preview = match api_post(format!("{}/trade/preview?user_id={}", API_BASE, uid), &token)
+                .json(&request)
+                .send()
+                .await
+            {
+                Ok(resp) if resp.status().is_success() => resp.json::<TradePreview>().await.ok(),
+                _ => None,
+            };
+            trade_preview.set(preview);
+        });
+    });
+
+    let execute_deposit = move || {
+        let amount = deposit_amount().parse::<f64>().unwrap_or(0.0);
+        let uid = user_id();
+        let token = session_token();
+
+        spawn(async move {
+            let request = DepositRequest { amount };
+            match api_post(format!("{}/deposit?user_id={}", API_BASE, uid.clone()), &token)
+                .json(&request)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        status.set(format!("Deposit of {} successful!", usd_amount(amount)));
+                        fetch_funds();
+                        // Refetch portfolio
+                        if let Ok(resp) = api_get(format!("{}/portfolio?user_id={}", API_BASE, uid), &token).send().await {
+                            if let Ok(data) = resp.json::<UserData>().await {
+                                portfolio.set(Some(data));
+                            }
+                        }
+                    } else {
+                        if let Ok(error_resp) = response.json::<ErrorResponse>().await {
+                            status.set(error_resp.error);
+                        } else {
+                            status.set("Deposit failed".to_string());
+                        }
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let execute_withdrawal = move || {
+        let amount = withdrawal_amount().parse::<f64>().unwrap_or(0.0);
+        let uid = user_id();
+        let token = session_token();
+
+        spawn(async move {
+            let request = WithdrawalRequest { amount };
+            match api_post(format!("{}/withdrawal?user_id={}", API_BASE, uid.clone()), &token)
+                .json(&request)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        status.set(format!("Withdrawal of {} successful!", usd_amount(amount)));
+                        fetch_funds();
+                        // Refetch portfolio
+                        if let Ok(resp) = api_get(format!("{}/portfolio?user_id={}", API_BASE, uid), &token).send().await {
+                            if let Ok(data) = resp.json::<UserData>().await {
+                                portfolio.set(Some(data));
+                            }
+                        }
+                    } else {
+                        if let Ok(error_resp) = response.json::<ErrorResponse>().await {
+                            status.set(error_resp.error);
+                        } else {
+                            status.set("Withdrawal failed".to_string());
+                        }
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    // Fetch bot status when in Trading view
+    let fetch_bot_status = move || {
+        let uid = user_id();
+        let token = session_token();
+        spawn(async move {
+            if let Ok(resp) = api_get(format!("{}/bots?user_id={}", API_BASE, uid), &token).send().await {
+                if let Ok(data) = resp.json::<Vec<BotStatusResponse>>().await {
+                    running_bots.set(Some(data));
+                }
+            }
+        });
+    };
+
+    // Bot starts and stops pushed over a WebSocket, for accounts with a session token
+    let bot_events = use_coroutine(move |mut sessions: UnboundedReceiver<String>| async move {
+        let mut token = String::new();
+        let mut delay = RECONNECT_MIN_MS;
+        'connect: loop {
+            // Guests have no token: wait for a login
+            while token.is_empty() {
+                match sessions.next().await {
+                    Some(next) => token = next,
+                    None => return,
+                }
+            }
+
+            match WebSocket::open(&format!("{}?token={}", ws_url("/ws/bot"), token)) {
+                Ok(mut socket) => loop {
+                    match select(socket.next(), sessions.next()).await {
+                        Either::Left((Some(Ok(Message::Text(text))), _)) => {
+                            delay = RECONNECT_MIN_MS;
+                            let Ok(message) = serde_json::from_str::<BotStreamMessage>(&text) else {
+                                continue;
+                            };
+                            let pair = running_bots
+                                .peek()
+                                .iter()
+                                .flatten()
+                                .find(|status| status.bot_instance_id.as_deref() == Some(message.bot_instance_id.as_str()))
+                                .and_then(|status| status.trading_pair.clone())
+                                .unwrap_or_default();
+                            let (base, quote) = pair.split_once('/').unwrap_or((pair.as_str(), "USD"));
+                            if let Some((text, level)) = bot_event_message(&message, (base, quote)) {
+                                notify(text, level, message.timestamp.clone(), "bots");
+                            }
+                            // Ticks and fills don't change the status; fills arrive as balance events
+                            if matches!(message.event.as_str(), "status" | "started" | "stopped" | "stoploss_hit") {
+                                fetch_bot_status();
+                            }
+                        }
+                        Either::Left((Some(Ok(Message::Bytes(_))), _)) => {}
+                        Either::Left((Some(Err(e)), _)) => {
+                            web_sys::console::log_1(&format!("Bot stream closed: {}", e).into());
+                            break;
+                        }
+                        Either::Left((None, _)) => break,
+                        Either::Right((Some(next), _)) => {
+                            token = next;
+                            delay = RECONNECT_MIN_MS;
+                            continue 'connect;
+                        }
+                        Either::Right((None, _)) => return,
+                    }
+                },
+                Err(e) => web_sys::console::log_1(&format!("Could not open bot stream: {}", e).into()),
+            }
+            reconnect_after(&mut delay).await;
+        }
+    });
+
+    // (Re)connect the account streams whenever the session changes
+    use_effect(move || {
+        let token = session_token();
+        account_events.send(token.clone());
+        bot_events.send(token);
+    });
+
+    // Fetch bot status on entering the Trading view
+    use_effect(move || {
+        if matches!(current_view(), AppView::Trading(_)) {
+            fetch_bot_status();
+        }
+    });
+
+    // Guests have no bot stream: poll bot status every 5 seconds in the Trading view
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                gloo_timers::future::TimeoutFuture::new(5_000).await;
+                if session_token.peek().is_empty() && matches!(*current_view.peek(), AppView::Trading(_)) {
+                    fetch_bot_status();
+                }
+            }
+        });
+    });
+
+    // The backtest form as a request
+    let backtest_request = move || {
+        let (base_asset, quote_asset) = split_market(&backtest_market());
+        let start = backtest_start();
+        let end = backtest_end();
+        BacktestRequest {
+            strategy: backtest_strategy(),
+            asset: base_asset,
+            quote_asset: (quote_asset != "USD").then_some(quote_asset),
+            start: format!("{}T00:00:00Z", start),
+            // The whole end day, but not past now
+            end: chrono::DateTime::parse_from_rfc3339(&format!("{}T23:59:59Z", end))
+                .map(|end| end.with_timezone(&chrono::Utc).min(chrono::Utc::now()).to_rfc3339())
+                .unwrap_or_default(),
+            initial_balance: backtest_balance().parse::<f64>().unwrap_or(0.0),
+            stoploss_amount: backtest_stoploss().parse::<f64>().unwrap_or(0.0),
+            // The momentum fields are the only tuning the form has; the script strategy runs the bot panel's script
+            parameters: match backtest_strategy().as_str() {
+                "naive_momentum" => serde_json::json!({
+                    "trend_ticks": backtest_trend_ticks().parse::<u32>().unwrap_or(0),
+                    "step_pct": backtest_step_pct().parse::<f64>().unwrap_or(0.0),
+                    "cooldown_ticks": backtest_cooldown().parse::<u32>().unwrap_or(0),
+                }),
+                "script" => serde_json::json!({ "script": bot_script() }),
+                _ => serde_json::Value::Null,
+            },
+        }
+    };
+
+    // Start a backtest, then poll it until it finishes
+    let mut run_backtest = move || {
+        let request = backtest_request();
+        let uid = user_id();
+        let token = session_token();
+        backtest_error.set(String::new());
+
+        spawn(async move {
+            let response = match api_post(format!("{}/backtest?user_id={}", API_BASE, uid), &token)
+                .json(&request)
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    backtest_error.set(format!("Error: {}", e));
+                    return;
+                }
+            };
+            if !response.status().is_success() {
+                let status_code = response.status();
+                match response.json::<ErrorResponse>().await {
+                    Ok(error_resp) => backtest_error.set(error_resp.error),
+                    Err(_) => backtest_error.set(format!("Backtest failed: {}", status_code)),
+                }
+                return;
+            }
+            let Ok(mut run) = response.json::<BacktestRun>().await else {
+                backtest_error.set("Unexpected backtest response".to_string());
+                return;
+            };
+
+            backtest_run.set(Some(run.clone()));
+            while run.status == "running" {
+                gloo_timers::future::TimeoutFuture::new(1_000).await;
+                let url = format!("{}/backtest/{}?user_id={}", API_BASE, run.id, uid);
+                match api_get(url, &token).send().await {
+                    Ok(resp) if resp.status().is_success() => match resp.json::<BacktestRun>().await {
+                        Ok(next) => run = next,
+                        Err(_) => break,
+                    },
+                    _ => {
+                        backtest_error.set("Lost track of the backtest".to_string());
+                        break;
+                    }
+                }
+                backtest_run.set(Some(run.clone()));
+            }
+            if let Some(error) = run.error {
+                backtest_error.set(error);
+            }
+            fetch_backtests();
+        });
+    };
+
+    // Fetch the risk grade of the shown run once it completes
+    use_effect(move || {
+        let Some(run) = backtest_run().filter(|run| run.result.is_some()) else {
+            backtest_risk.set(None);
+            return;
+        };
+        if backtest_risk.peek().as_ref().is_some_and(|(id, _)| *id == run.id) {
+            return;
+        }
+        backtest_risk.set(None);
+        let url = format!("{}/backtest/{}/risk?user_id={}", API_BASE, run.id, user_id.peek());
+        let token = session_token.peek().clone();
+        spawn(async move {
+            if let Ok(resp) = api_get(url, &token).send().await {
+                if let Ok(score) = resp.json::<RiskScore>().await {
+                    backtest_risk.set(Some((run.id, score)));
+                }
+            }
+        });
+    });
+
+    // The optimizer form as a request over the backtest form's range
+    let optimize_request = move || {
+        let grid = HashMap::from([
+            ("trend_ticks".to_string(), parse_grid_values(&optimize_trend_ticks())),
+            ("step_pct".to_string(), parse_grid_values(&optimize_step_pct())),
+            ("cooldown_ticks".to_string(), parse_grid_values(&optimize_cooldown())),
+        ]);
+        OptimizeRequest { backtest: backtest_request(), grid, rank_by: optimize_rank_by() }
+    };
+
+    // Backtest every combination of the optimizer's values over the form's range
+    let mut run_optimize = move || {
+        let request = optimize_request();
+        let uid = user_id();
+        let token = session_token();
+        optimize_error.set(String::new());
+        optimize_running.set(true);
+
+        spawn(async move {
+            let url = format!("{}/backtest/optimize?user_id={}", API_BASE, uid);
+            match api_post(url, &token).json(&request).send().await {
+                Ok(response) if response.status().is_success() => match response.json::<OptimizationResult>().await {
+                    Ok(result) => optimize_result.set(Some(result)),
+                    Err(_) => optimize_error.set("Unexpected optimizer response".to_string()),
+                },
+                Ok(response) => {
+                    let status_code = response.status();
+                    match response.json::<ErrorResponse>().await {
+                        Ok(error_resp) => optimize_error.set(error_resp.error),
+                        Err(_) => optimize_error.set(format!("Optimization failed: {}", status_code)),
+                    }
+                }
+                Err(e) => optimize_error.set(format!("Error: {}", e)),
+            }
+            optimize_running.set(false);
+        });
+    };
+
+    // Optimize over rolling in-sample windows and test each winner on the window after it
+    let mut run_walk_forward = move || {
+        let request = WalkForwardRequest {
+            optimize: optimize_request(),
+            in_sample_days: walk_forward_in_sample().parse::<u32>().unwrap_or(0),
+            out_of_sample_days: walk_forward_out_of_sample().parse::<u32>().unwrap_or(0),
+        };
+        let uid = user_id();
+        let token = session_token();
+        walk_forward_error.set(String::new());
+        walk_forward_running.set(true);
+
+        spawn(async move {
+            let url = format!("{}/backtest/walk-forward?user_id={}", API_BASE, uid);
+            match api_post(url, &token).json(&request).send().await {
+                Ok(response) if response.status().is_success() => match response.json::<WalkForwardResult>().await {
+                    Ok(result) => walk_forward_result.set(Some(result)),
+                    Err(_) => walk_forward_error.set("Unexpected walk-forward response".to_string()),
+                },
+                Ok(response) => {
+                    let status_code = response.status();
+                    match response.json::<ErrorResponse>().await {
+                        Ok(error_resp) => walk_forward_error.set(error_resp.error),
+                        Err(_) => walk_forward_error.set(format!("Walk-forward failed: {}", status_code)),
+                    }
+                }
+                Err(e) => walk_forward_error.set(format!("Error: {}", e)),
+            }
+            walk_forward_running.set(false);
+        });
+    };
+
+    // Start a live replay of the form's range, then poll it until it finishes
+    let mut run_replay = move || {
+        let request = ReplayRequest { backtest: backtest_request(), speed: replay_speed().parse::<u32>().unwrap_or(0) };
+        let uid = user_id();
+        let token = session_token();
+        replay_error.set(String::new());
+
+        spawn(async move {
+            let url = format!("{}/backtest/replay?user_id={}", API_BASE, uid);
+            let mut run = match api_post(url, &token).json(&request).send().await {
+                Ok(response) if response.status().is_success() => match response.json::<ReplayRun>().await {
+                    Ok(run) => run,
+                    Err(_) => {
+                        replay_error.set("Unexpected replay response".to_string());
+                        return;
+                    }
+                },
+                Ok(response) => {
+                    let status_code = response.status();
+                    match response.json::<ErrorResponse>().await {
+                        Ok(error_resp) => replay_error.set(error_resp.error),
+                        Err(_) => replay_error.set(format!("Replay failed: {}", status_code)),
+                    }
+                    return;
+                }
+                Err(e) => {
+                    replay_error.set(format!("Error: {}", e));
+                    return;
+                }
+            };
+
+            replay_run.set(Some(run.clone()));
+            while run.status == "running" {
+                gloo_timers::future::TimeoutFuture::new(1_000).await;
+                let url = format!("{}/backtest/replay/{}?user_id={}", API_BASE, run.id, uid);
+                match api_get(url, &token).send().await {
+                    Ok(resp) if resp.status().is_success() => match resp.json::<ReplayRun>().await {
+                        Ok(next) => run = next,
+                        Err(_) => break,
+                    },
+                    _ => {
+                        replay_error.set("Lost track of the replay".to_string());
+                        break;
+                    }
+                }
+                replay_run.set(Some(run.clone()));
+            }
+            if let Some(error) = run.error {
+                replay_error.set(error);
+            }
+        });
+    };
+
+    // Stop the running replay; polling picks up its final state
+    let cancel_replay = move |id: String| {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let url = format!("{}/backtest/replay/{}?user_id={}", API_BASE, id, uid);
+            if api_delete(url, &token).send().await.is_err() {
+                replay_error.set("Failed to cancel the replay".to_string());
+            }
+        });
+    };
+
+    // Copy a combination's values into the backtest form
+    let mut apply_parameters = move |parameters: serde_json::Value| {
+        let field = |name: &str| parameters.get(name).map(|value| value.to_string());
+        if let Some(value) = field("trend_ticks") {
+            backtest_trend_ticks.set(value);
+        }
+        if let Some(value) = field("step_pct") {
+            backtest_step_pct.set(value);
+        }
+        if let Some(value) = field("cooldown_ticks") {
+            backtest_cooldown.set(value);
+        }
+    };
+
+    // Show an earlier run's results
+    let load_backtest = move |id: String| {
+        let uid = user_id.peek().clone();
+        let token = session_token.peek().clone();
+        spawn(async move {
+            let url = format!("{}/backtest/{}?user_id={}", API_BASE, id, uid);
+            let Ok(resp) = api_get(url, &token).send().await else {
+                return;
+            };
+            match resp.json::<BacktestRun>().await {
+                Ok(run) => {
+                    let params = &run.params;
+                    backtest_market.set(if params.quote_asset == "USD" {
+                        params.base_asset.clone()
+                    } else {
+                        format!("{}/{}", params.base_asset, params.quote_asset)
+                    });
+                    backtest_error.set(run.error.clone().unwrap_or_default());
+                    backtest_run.set(Some(run));
+                }
+                Err(_) => {
+                    backtest_error.set("That run has expired".to_string());
+                    fetch_backtests();
+                }
+            }
+        });
+    };
+
+    let start_bot = move |base_asset: String, quote_asset: String| {
+        let stoploss = bot_stoploss().parse::<f64>().unwrap_or(1000.0);
+        let bot_name = selected_bot();
+        let parameters = if bot_name == "script" {
+            serde_json::json!({ "script": bot_script() })
+        } else {
+            serde_json::Value::Null
+        };
+        let mut trade_limits = serde_json::Map::new();
+        if let Ok(value) = bot_max_position().trim().parse::<f64>() {
+            trade_limits.insert("max_position_value".to_string(), value.into());
+        }
+        if let Ok(value) = bot_max_trade().trim().parse::<f64>() {
+            trade_limits.insert("max_trade_value".to_string(), value.into());
+        }
+        if let Ok(value) = bot_max_trades_per_hour().trim().parse::<u32>() {
+            trade_limits.insert("max_trades_per_hour".to_string(), value.into());
+        }
+        let (active_from, active_to) = (bot_active_from(), bot_active_to());
+        let schedule = if active_from.is_empty() || active_to.is_empty() {
+            serde_json::Value::Null
+        } else if bot_weekdays_only() {
+            serde_json::json!({ "start": active_from, "end": active_to, "days": ["mon", "tue", "wed", "thu", "fri"] })
+        } else {
+            serde_json::json!({ "start": active_from, "end": active_to })
+        };
+        let uid = user_id();
+        let token = session_token();
+
+        spawn(async move {
+            let request = StartBotRequest {
+                user_id: uid.clone(),
+                bot_name,
+                base_asset,
+                quote_asset,
+                stoploss_amount: stoploss,
+                parameters,
+                dry_run: bot_dry_run(),
+                trade_limits: serde_json::Value::Object(trade_limits),
+                max_base_position: bot_max_base_position().trim().parse::<f64>().ok(),
+                schedule,
+            };
+
+            match api_post(format!("{}/bot/start", API_BASE), &token)
+                .json(&request)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        if let Ok(bot_resp) = response.json::<BotResponse>().await {
+                            status.set(bot_resp.message);
+                            // Immediately fetch the updated bot list
+                            fetch_bot_status();
+                        }
+                    } else {
+                        if let Ok(err_resp) = response.json::<ErrorResponse>().await {
+                            status.set(format!("Bot start failed: {}", err_resp.error));
+                        }
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let export_strategy = move |bot_instance_id: String| {
+        let uid = user_id();
+        let token = session_token();
+        spawn(async move {
+            let url = format!("{}/bot/strategy?user_id={}&bot_instance_id={}", API_BASE, uid, bot_instance_id);
+            match api_get(url, &token).send().await {
+                Ok(response) if response.status().is_success() => {
+                    if let Ok(config) = response.json::<serde_json::Value>().await {
+                        strategy_config.set(serde_json::to_string_pretty(&config).unwrap_or_default());
+                    }
+                }
+                Ok(response) => {
+                    if let Ok(err_resp) = response.json::<ErrorResponse>().await {
+                        status.set(format!("Export failed: {}", err_resp.error));
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let mut import_strategy = move || {
+        let uid = user_id();
+        let token = session_token();
+        let config = match serde_json::from_str::<serde_json::Value>(&strategy_config()) {
+            Ok(config) => config,
+            Err(e) => {
+                status.set(format!("Invalid configuration: {}", e));
+                return;
+            }
+        };
+        spawn(async move {
+            match api_post(format!("{}/bot/strategy?user_id={}", API_BASE, uid), &token)
+                .json(&config)
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => {
+                    if let Ok(bot_resp) = response.json::<BotResponse>().await {
+                        status.set(bot_resp.message);
+                        strategy_config.set(String::new());
+                        fetch_bot_status();
+                    }
+                }
+                Ok(response) => {
+                    if let Ok(err_resp) = response.json::<ErrorResponse>().await {
+                        status.set(format!("Bot start failed: {}", err_resp.error));
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    let stop_bot = move |bot_instance_id: String| {
+        let uid = user_id();
+        let token = session_token();
+
+        spawn(async move {
+            match api_post(format!("{}/bot/stop?user_id={}&bot_instance_id={}", API_BASE, uid, bot_instance_id), &token)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        if let Ok(bot_resp) = response.json::<BotResponse>().await {
+                            status.set(bot_resp.message);
+                            // Immediately fetch the updated bot list
+                            fetch_bot_status();
+                        }
+                    } else {
+                        if let Ok(err_resp) = response.json::<ErrorResponse>().await {
+                            status.set(format!("Bot stop failed: {}", err_resp.error));
+                        }
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    // Pause (true) or resume (false) a bot; paused bots keep their strategy state
+    let pause_bot = move |bot_instance_id: String, pause: bool| {
+        let uid = user_id();
+        let token = session_token();
+        let action = if pause { "pause" } else { "resume" };
+
+        spawn(async move {
+            match api_post(format!("{}/bot/{}?user_id={}&bot_instance_id={}", API_BASE, action, uid, bot_instance_id), &token)
+                .send()
+                .await
+            {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        if let Ok(bot_resp) = response.json::<BotResponse>().await {
+                            status.set(bot_resp.message);
+                            fetch_bot_status();
+                        }
+                    } else if let Ok(err_resp) = response.json::<ErrorResponse>().await {
+                        status.set(format!("Bot {} failed: {}", action, err_resp.error));
+                    }
+                }
+                Err(e) => status.set(format!("Error: {}", e)),
+            }
+        });
+    };
+
+    rsx! {
+        // Add Inter font from Google Fonts
+        head {
+            link {
+                rel: "stylesheet",
+                href: "https://fonts.googleapis.com/css2?family=Inter:wght@400;600;700&display=swap"
+            }
+            // Theme colors
+            style { {theme().stylesheet()} }
+        }
+
+        div {
+            style: format!("min-height: 100vh; background: {}; font-family: {};", COLOR_PAGE_BG, FONT_BODY),
+
+            // Header (only show when not on Auth page)
+            if !matches!(current_view(), AppView::Auth) {
+                Header {
+                    current_view: current_view(),
+                    username: username(),
+                    markets: market_list(&assets()),
+                    unread_notifications: unread_notifications(),
+                    theme: theme(),
+                    on_navigate: move |view: AppView| current_view.set(view),
+                    on_toggle_theme: move |_| toggle_theme(),
+                    on_toggle_notifications: move |_| {
+                        show_notifications.set(!show_notifications());
+                        unread_notifications.set(0);
+                    },
+                    on_logout: move |_| handle_logout()
+                }
+            }
+
+            // Notification center
+            if show_notifications() && !matches!(current_view(), AppView::Auth) {
+                div {
+                    style: format!("position: fixed; top: 70px; right: 30px; width: 380px; max-height: 420px; overflow-y: auto; background: {}; border-radius: 8px; box-shadow: 0 4px 16px rgba(0,0,0,0.2); z-index: 1500; font-family: {};", COLOR_CONTENT_BG, FONT_BODY),
+                    div { style: "display: flex; justify-content: space-between; align-items: center; padding: 12px 16px; border-bottom: 1px solid var(--color-divider);",
+                        span { style: format!("font-weight: 600; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Notifications" }
+                        if !notifications().is_empty() {
+                            button {
+                                onclick: move |_| notifications.write().clear(),
+                                style: format!("background: none; border: none; color: {}; cursor: pointer; font-size: 13px;", COLOR_LIGHT_GREY),
+                                "Clear"
+                            }
+                        }
+                    }
+                    if notifications().is_empty() {
+                        p { style: format!("padding: 16px; margin: 0; color: {}; font-size: 14px;", COLOR_LIGHT_GREY), "No notifications yet" }
+                    }
+                    for n in notifications() {
+                        div { key: "{n.id}",
+                            style: format!("padding: 10px 16px; border-bottom: 1px solid var(--color-divider); border-left: 4px solid {};", notification_color(n.level)),
+                            div { style: format!("font-size: 14px; color: {};", COLOR_DARK_GREY), "{n.message}" }
+                            div { style: format!("font-size: 12px; color: {}; margin-top: 2px;", COLOR_LIGHT_GREY), "{format_timestamp(&n.timestamp)}" }
+                        }
+                    }
+                }
+            }
+
+            // Toasts
+            div {
+                style: "position: fixed; top: 80px; right: 20px; display: flex; flex-direction: column; gap: 10px; z-index: 2000;",
+                for toast in toasts() {
+                    div { key: "{toast.id}",
+                        style: format!("min-width: 280px; max-width: 380px; padding: 12px 16px; background: {}; color: white; border-radius: 6px; box-shadow: 0 4px 12px rgba(0,0,0,0.2); display: flex; justify-content: space-between; align-items: flex-start; gap: 12px; font-family: {}; font-size: 14px;", notification_color(toast.level), FONT_BODY),
+                        span { "{toast.message}" }
+                        span {
+                            onclick: move |_| toasts.write().retain(|t| t.id != toast.id),
+                            style: "cursor: pointer; font-weight: bold;",
+                            "×"
+                        }
+                    }
+                }
+            }
+
+            // Main content area
+            div {
+                style: if matches!(current_view(), AppView::Auth) {
+                    "".to_string()
+                } else {
+                    "padding-bottom: 60px;".to_string() // Add padding for status bar
+                },
+
+                match current_view() {
+                    AppView::Auth => rsx! {
+                        // New landing page with navy background and two-column layout
+                        div {
+                            style: format!(
+                                "min-height: 100vh; background: {}; display: flex; align-items: center; justify-content: center; padding: 40px;",
+                                COLOR_NAVY
+                            ),
+                            div {
+                                style: "max-width: 1200px; width: 100%; display: flex; gap: 60px; align-items: center; flex-wrap: wrap;",
+
+                                // Left column: App branding
+                                div {
+                                    style: format!("flex: 1; min-width: 300px; color: white; font-family: {};", FONT_HEADER),
+                                    h1 {
+                                        style: "font-size: 64px; font-weight: 700; margin: 0 0 10px 0; line-height: 1.2;",
+                                        "Trading"
+                                    }
+                                    h1 {
+                                        style: "font-size: 64px; font-weight: 700; margin: 0 0 60px 0; line-height: 1.2;",
+                                        "Simulator"
+                                    }
+                                    p {
+                                        style: "font-size: 16px; opacity: 0.8; margin: 0;",
+                                        "John Prominski"
+                                    }
+                                    p {
+                                        style: "font-size: 16px; opacity: 0.8; margin: 5px 0 0 0;",
+                                        "2025"
+                                    }
+                                    p {
+                                        style: "font-size: 16px; opacity: 0.8; margin: 5px 0 0 0;",
+                                        "Full-stack Rust Project"
+                                    }
+                                }
+
+                                // Right column: Login component
+                                div {
+                                    style: format!("flex: 1; min-width: 350px; background: {}; padding: 40px; border-radius: 12px; box-shadow: 0 8px 24px rgba(0,0,0,0.2);", COLOR_CONTENT_BG),
+                                    h2 {
+                                        style: format!("margin: 0 0 30px 0; font-family: {}; color: {}; font-size: 28px;", FONT_HEADER, COLOR_DARK_GREY),
+                                        "Welcome"
+                                    }
+
+                                    div { style: "margin-bottom: 20px;",
+                                        input {
+                                            r#type: "text",
+                                            placeholder: "Username",
+                                            value: "{auth_username}",
+                                            oninput: move |e| auth_username.set(e.value()),
+                                            style: format!("width: 100%; padding: 12px; margin-bottom: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 16px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                        }
+                                        input {
+                                            r#type: "password",
+                                            placeholder: "Password",
+                                            value: "{auth_password}",
+                                            oninput: move |e| auth_password.set(e.value()),
+                                            style: format!("width: 100%; padding: 12px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 16px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                        }
+                                    }
+
+                                    div { style: "display: flex; flex-direction: column; gap: 12px; margin-bottom: 20px;",
+                                        button {
+                                            onclick: move |_| handle_login(),
+                                            style: format!("padding: 14px; background: {}; color: white; border: none; border-radius: 6px; cursor: pointer; font-size: 16px; font-weight: 600; font-family: {};", COLOR_NAVY, FONT_BODY),
+                                            "Login"
+                                        }
+                                        button {
+                                            onclick: move |_| handle_signup(),
+                                            style: format!("padding: 14px; background: {}; color: white; border: none; border-radius: 6px; cursor: pointer; font-size: 16px; font-weight: 600; font-family: {};", COLOR_GREEN, FONT_BODY),
+                                            "Sign Up"
+                                        }
+                                    }
+
+                                    div { style: "border-top: 1px solid var(--color-border); padding-top: 20px; margin-top: 20px;",
+                                        button {
+                                            onclick: move |_| handle_guest(),
+                                            style: format!("width: 100%; padding: 14px; background: {}; color: white; border: none; border-radius: 6px; cursor: pointer; font-size: 16px; font-weight: 600; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                            "Continue as Guest"
+                                        }
+                                        p { style: format!("margin-top: 10px; font-size: 14px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                            "Guest profile resets on app restart"
+                                        }
+                                    }
+
+                                    if !auth_error().is_empty() {
+                                        p { style: format!("margin-top: 15px; color: {}; font-weight: bold; font-family: {};", COLOR_RED, FONT_BODY), "{auth_error}" }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                AppView::Dashboard => rsx! {
+                    div {
+                        style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; font-family: {};", FONT_BODY),
+
+                        h1 {
+                            style: format!("margin: 0 0 30px 0; font-family: {}; color: {}; font-size: 32px;", FONT_HEADER, COLOR_DARK_GREY),
+                            "Dashboard"
+                        }
+
+                        if let Some(p) = portfolio() {
+                            // Calculate total portfolio value in USD
+                            {
+                                let current_prices = prices();
+                                let total_value_usd = portfolio_value_usd(&p.asset_balances, &current_prices);
+                                let usd_bal = p.asset_balances.get("USD").copied().unwrap_or(0.0);
+
+                                // Non-cash balances, largest USD value first
+                                let mut holdings: Vec<(String, f64, f64)> = p.asset_balances.iter()
+                                    .filter(|(asset, _)| asset.as_str() != "USD")
+                                    .map(|(asset, balance)| {
+                                        let value = balance * current_prices.get(asset).copied().unwrap_or(0.0);
+                                        (asset.clone(), *balance, value)
+                                    })
+                                    .collect();
+                                holdings.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+                                let pie_holdings: Vec<(String, f64)> = holdings.iter()
+                                    .map(|(asset, _, value)| (asset.clone(), *value))
+                                    .collect();
+
+                                rsx! {
+                                    // 3-Column Portfolio Section
+                                    div {
+                                        style: format!("background: {}; padding: 30px; border-radius: 8px; margin-bottom: 30px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                        h2 {
+                                            style: format!("margin: 0 0 25px 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
+                                            "Portfolio"
+                                        }
+
+                                        div {
+                                            style: "display: grid; grid-template-columns: repeat(auto-fit, minmax(300px, 1fr)); gap: 25px;",
+
+                                            // Column 1: Total Value & Available Cash
+                                            div {
+                                                style: format!("background: {}; padding: 20px; border-radius: 6px; border: 1px solid var(--color-divider);", COLOR_PAGE_BG),
+                                                h3 {
+                                                    style: format!("margin: 0 0 15px 0; font-family: {}; color: {}; font-size: 16px; font-weight: 600;", FONT_BODY, COLOR_DARK_GREY),
+                                                    "Value Summary"
+                                                }
+                                                div {
+                                                    style: "margin-bottom: 15px;",
+                                                    p {
+                                                        style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                        "Estimated Total Value"
+                                                    }
+                                                    p {
+                                                        style: format!("margin: 5px 0 0 0; font-size: 28px; font-weight: bold; color: {}; font-family: {};", COLOR_GREEN, FONT_HEADER),
+                                                        {display_value(total_value_usd, &display_currency(), &current_prices)}
+                                                    }
+                                                }
+                                                div {
+                                                    p {
+                                                        style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                        "Available Cash"
+                                                    }
+                                                    p {
+                                                        style: format!("margin: 5px 0 0 0; font-size: 20px; font-weight: 600; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
+                                                        "{usd_amount(usd_bal)}"
+                                                    }
+                                                }
+                                            }
+
+                                            // Column 2: Asset Balances List
+                                            div {
+                                                style: format!("background: {}; padding: 20px; border-radius: 6px; border: 1px solid var(--color-divider);", COLOR_PAGE_BG),
+                                                h3 {
+                                                    style: format!("margin: 0 0 15px 0; font-family: {}; color: {}; font-size: 16px; font-weight: 600;", FONT_BODY, COLOR_DARK_GREY),
+                                                    "Asset Balances"
+                                                }
+                                                div {
+                                                    style: "display: flex; flex-direction: column; gap: 10px;",
+                                                    div {
+                                                        style: "display: flex; justify-content: space-between; align-items: center; padding: 8px 0; border-bottom: 1px solid var(--color-divider);",
+                                                        span {
+                                                            style: format!("font-weight: 600; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
+                                                            "USD"
+                                                        }
+                                                        span {
+                                                            style: format!("color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
+                                                            "{usd_amount(usd_bal)}"
+                                                        }
+                                                    }
+                                                    for (asset, balance, _) in holdings {
+                                                        div {
+                                                            key: "{asset}",
+                                                            style: "display: flex; justify-content: space-between; align-items: center; padding: 8px 0; border-bottom: 1px solid var(--color-divider);",
+                                                            span {
+                                                                style: format!("font-weight: 600; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
+                                                                "{asset}"
+                                                            }
+                                                            span {
+                                                                style: format!("color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
+                                                                "{format_amount(balance, &asset)}"
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+
+                                            // Column 3: Pie Chart
+                                            div {
+                                                style: format!("background: {}; padding: 20px; border-radius: 6px; border: 1px solid var(--color-divider); display: flex; flex-direction: column; align-items: center; justify-content: center;", COLOR_PAGE_BG),
+                                                h3 {
+                                                    style: format!("margin: 0 0 15px 0; font-family: {}; color: {}; font-size: 16px; font-weight: 600; width: 100%; text-align: center;", FONT_BODY, COLOR_DARK_GREY),
+                                                    "Composition"
+                                                }
+                                                PortfolioPieChart {
+                                                    holdings: pie_holdings,
+                                                    cash_balance: usd_bal
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    // Portfolio value over time
+                                    div {
+                                        style: format!("background: {}; padding: 30px; border-radius: 8px; margin-bottom: 30px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                        div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 20px;",
+                                            h2 {
+                                                style: format!("margin: 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
+                                                "Portfolio Value"
+                                            }
+                                            div { style: "display: flex; gap: 15px; align-items: center;",
+                                                label {
+                                                    style: format!("display: flex; align-items: center; gap: 6px; font-size: 13px; color: {}; cursor: pointer;", COLOR_DARK_GREY),
+                                                    input {
+                                                        r#type: "checkbox",
+                                                        checked: show_benchmark(),
+                                                        onchange: move |e| show_benchmark.set(e.checked()),
+                                                    }
+                                                    "Compare with holding {benchmark_asset}"
+                                                }
+                                                div { style: "display: flex; gap: 8px;",
+                                                    for range in ["24h", "7d", "30d", "all"] {
+                                                        button {
+                                                            key: "{range}",
+                                                            onclick: move |_| equity_range.set(range.to_string()),
+                                                            style: if equity_range() == range {
+                                                                "padding: 8px 16px; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-weight: bold;"
+                                                            } else {
+                                                                "padding: 8px 16px; background: var(--color-surface); color: var(--color-dark-grey); border: 1px solid var(--color-border); border-radius: 4px; cursor: pointer; font-size: 13px;"
+                                                            },
+                                                            "{range.to_uppercase()}"
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        EquityChart {
+                                            points: equity_history(),
+                                            range: equity_range(),
+                                            benchmark_asset: benchmark_asset(),
+                                            show_benchmark: show_benchmark()
+                                        }
+                                        if equity_history().len() >= 2 {
+                                            div { style: "margin-top: 20px;",
+                                                MetricGrid { cards: performance_cards(&equity_metrics()) }
+                                            }
+                                        }
+                                    }
+
+                                    // Lifetime Statistics
+                                    {
+                                        let lifetime_deposits: f64 = p.trade_history.iter()
+                                            .filter(|t| t.transaction_type == TransactionType::Deposit)
+                                            .map(|t| t.quantity)
+                                            .sum();
+                                        let lifetime_withdrawals: f64 = p.trade_history.iter()
+                                            .filter(|t| t.transaction_type == TransactionType::Withdrawal)
+                                            .map(|t| t.quantity)
+                                            .sum();
+                                        let lifetime_funding = 10000.0 + lifetime_deposits;
+                                        let total_trade_volume_usd: f64 = p.trade_history.iter()
+                                            .filter(|t| t.transaction_type == TransactionType::Trade)
+                                            .filter_map(|t| {
+                                                t.quote_usd_price.map(|q_usd| t.quantity * t.price * q_usd)
+                                            })
+                                            .sum();
+
+                                        rsx! {
+                                            div {
+                                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 30px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                                h2 {
+                                                    style: format!("margin: 0 0 20px 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
+                                                    "Lifetime Statistics"
+                                                }
+                                                div {
+                                                    style: "display: grid; grid-template-columns: repeat(auto-fit, minmax(200px, 1fr)); gap: 20px;",
+                                                    div {
+                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                                        p {
+                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                            "Total Funding"
+                                                        }
+                                                        p {
+                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_GREEN, FONT_HEADER),
+                                                            "{usd_amount(lifetime_funding)}"
+                                                        }
+                                                    }
+                                                    div {
+                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                                        p {
+                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                            "Total Deposits"
+                                                        }
+                                                        p {
+                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_HEADER),
+                                                            "{usd_amount(lifetime_deposits)}"
+                                                        }
+                                                    }
+                                                    div {
+                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                                        p {
+                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                            "Total Withdrawals"
+                                                        }
+                                                        p {
+                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_RED, FONT_HEADER),
+                                                            "{usd_amount(lifetime_withdrawals)}"
+                                                        }
+                                                    }
+                                                    div {
+                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                                        p {
+                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                            "Trade Volume (USD)"
+                                                        }
+                                                        p {
+                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_NAVY, FONT_HEADER),
+                                                            "{usd_amount(total_trade_volume_usd)}"
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    // Deposits and withdrawals live on the Funds page
+                                    div {
+                                        onclick: move |_| current_view.set(AppView::Funds),
+                                        style: format!("background: {}; border-radius: 8px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); margin-bottom: 20px; padding: 15px 20px; cursor: pointer; display: flex; justify-content: space-between; align-items: center; font-family: {}; font-weight: 600; color: {};", COLOR_CONTENT_BG, FONT_BODY, COLOR_DARK_GREY),
+                                        span { "Fund Account or Make Withdrawal" }
+                                        span { style: "font-size: 20px;", "→" }
+                                    }
+                                }
+                            }
+
+                            // Transaction History
+                            div {
+                                style: format!("background: {}; padding: 25px; border-radius: 8px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                h2 {
+                                    style: format!("margin: 0 0 20px 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
+                                    "Transaction History"
+                                }
+                                if p.trade_history.is_empty() {
+                                    p { style: format!("color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY), "No transactions yet" }
+                                } else {
+                                    div { style: "overflow-x: auto;",
+                                        table { style: format!("width: 100%; border-collapse: collapse; font-family: {};", FONT_BODY),
+                                            thead {
+                                                tr { style: format!("border-bottom: 2px solid {}; background: {};", COLOR_PAGE_BG, COLOR_PAGE_BG),
+                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Type" }
+                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Asset" }
+                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Action" }
+                                                    th { style: format!("padding: 12px 10px; text-align: right; font-weight: 600; color: {};", COLOR_DARK_GREY), "Quantity" }
+                                                    th { style: format!("padding: 12px 10px; text-align: right; font-weight: 600; color: {};", COLOR_DARK_GREY), "Price" }
+                                                    th { style: format!("padding: 12px 10px; text-align: right; font-weight: 600; color: {};", COLOR_DARK_GREY), "Total" }
+                                                    th { style: format!("padding: 12px 10px; text-align: center; font-weight: 600; color: {};", COLOR_DARK_GREY), "Source" }
+                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Time" }
+                                                }
+                                            }
+                                            tbody {
+                                                for trade in p.trade_history.iter().rev().take(10) {
+                                                    tr { style: "border-bottom: 1px solid var(--color-divider);",
+                                                    // Transaction Type
+                                                    td {
+                                                        style: "padding: 10px;",
+                                                        {
+                                                            match trade.transaction_type {
+                                                                TransactionType::Deposit => "💰 Deposit",
+                                                                TransactionType::Withdrawal => "💸 Withdraw",
+                                                                TransactionType::Trade => "📈 Trade",
+                                                            }
+                                                        }
+                                                    }
+                                                    // Asset
+                                                    td {
+                                                        style: "padding: 10px;",
+                                                        {
+                                                            match trade.transaction_type {
+                                                                TransactionType::Trade => format!("{}/{}", trade.base_asset, trade.quote_asset),
+                                                                _ => trade.asset().to_string(),
+                                                            }
+                                                        }
+                                                    }
+                                                    // Action
+                                                    td {
+                                                        style: if matches!(trade.side, TradeSide::Buy) { "padding: 10px; color: var(--color-green); font-weight: bold;" } else { "padding: 10px; color: var(--color-red); font-weight: bold;" },
+                                                        {
+                                                            match trade.transaction_type {
+                                                                TransactionType::Deposit => "+".to_string(),
+                                                                TransactionType::Withdrawal => "-".to_string(),
+                                                                TransactionType::Trade => format!("{:?}", trade.side),
+                                                            }
+                                                        }
+                                                    }
+                                                    td { style: "padding: 10px; text-align: right;", "{format_amount(trade.quantity, &trade.base_asset)}" }
+                                                    // Price column - show in quote asset terms
+                                                    td {
+                                                        style: "padding: 10px; text-align: right;",
+                                                        {
+                                                            quote_amount_label(trade.price, &trade.quote_asset)
+                                                        }
+                                                    }
+                                                    // Total column - show in quote asset terms
+                                                    td {
+                                                        style: "padding: 10px; text-align: right;",
+                                                        {
+                                                            quote_amount_label(trade.price * trade.quantity, &trade.quote_asset)
+                                                        }
+                                                    }
+                                                    // Source column - show bot icon if executed by bot
+                                                    td {
+                                                        style: "padding: 10px; text-align: center;",
+                                                        {
+                                                            trade.executed_by_bot
+                                                                .as_deref()
+                                                                .unwrap_or("Manual")
+                                                        }
+                                                    }
+                                                    td { style: "padding: 10px;", "{format_timestamp(&trade.timestamp)}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                if p.trade_history.len() > 10 {
+                                    p { style: "margin-top: 10px; color: var(--color-light-grey); font-size: 14px;",
+                                        "Showing last 10 of {p.trade_history.len()} transactions"
+                                    }
+                                }
+                            }
+                        }
+                    } else {
+                        p { style: format!("color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY), "Loading portfolio..." }
+                    }
+                }
+            },
+                AppView::Markets => rsx! {
+                    div {
+                        style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; font-family: {};", FONT_BODY),
+
+                        h1 {
+                            style: format!("margin: 0 0 10px 0; font-family: {}; color: {}; font-size: 32px;", FONT_HEADER, COLOR_DARK_GREY),
+                            "Markets"
+                        }
+                        p {
+                            style: format!("color: {}; margin: 0 0 30px 0; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                            "Click on a market to start trading"
+                        }
+
+                        div { style: "display: grid; grid-template-columns: repeat(auto-fit, minmax(350px, 1fr)); gap: 25px;",
+                            for (i, market) in market_list(&assets()).into_iter().enumerate() {
+                                {
+                                    let (base, quote) = split_market(&market);
+                                    let color = MARKET_COLORS[i % MARKET_COLORS.len()];
+                                    let current_prices = prices();
+                                    let current_histories = histories();
+                                    let price = market_price(&current_prices, &base, &quote);
+                                    let history = market_history(&current_histories, &base, &quote);
+                                    let title = format!("{}/{}", base, quote);
+                                    let subtitle = if quote == "USD" {
+                                        asset_name(&base)
+                                    } else {
+                                        format!("{} per {}", asset_name(&base), asset_name(&quote))
+                                    };
+                                    let icon = ASSET_INFO.read().get(&base).map(|info| (info.icon.clone(), info.symbol.clone()));
+
+                                    rsx! {
+                                        div {
+                                            key: "{market}",
+                                            onclick: move |_| current_view.set(AppView::Trading(market.clone())),
+                                            style: format!("background: {}; padding: 25px; border-radius: 8px; border: 2px solid var(--color-divider); cursor: pointer; transition: all 0.2s; box-shadow: 0 2px 4px rgba(0,0,0,0.05);", COLOR_CONTENT_BG),
+                                            div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 15px;",
+                                                h3 {
+                                                    style: format!("margin: 0; font-size: 24px; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY),
+                                                    if let Some((icon, symbol)) = icon {
+                                                        img { src: "{icon}", alt: "{symbol}", style: "width: 24px; height: 24px; vertical-align: middle; margin-right: 8px;" }
+                                                    }
+                                                    "{title}"
+                                                }
+                                                p {
+                                                    style: format!("margin: 0; font-size: 28px; font-weight: bold; color: {}; font-family: {};", color, FONT_HEADER),
+                                                    if price <= 0.0 {
+                                                        "--"
+                                                    } else {
+                                                        "{quote_amount_label(price, &quote)}"
+                                                    }
+                                                }
+                                            }
+                                            p {
+                                                style: format!("color: {}; font-size: 14px; margin-bottom: 15px; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                "{subtitle}"
+                                            }
+                                            Sparkline { prices: history, color: color.to_string() }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
                 },
+                AppView::Trading(asset) => rsx! {
+                    {
+                        let (base_asset, quote_asset) = split_market(&asset);
+                        let current_price = market_price(&prices(), &base_asset, &quote_asset);
+                        let current_history = market_history(&histories(), &base_asset, &quote_asset);
 
-                match current_view() {
-                    AppView::Auth => rsx! {
-                        // New landing page with navy background and two-column layout
-                        div {
-                            style: format!(
-                                "min-height: 100vh; background: {}; display: flex; align-items: center; justify-content: center; padding: 40px;",
-                                COLOR_NAVY
-                            ),
+                        rsx! {
                             div {
-                                style: "max-width: 1200px; width: 100%; display: flex; gap: 60px; align-items: center; flex-wrap: wrap;",
+                                style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; padding-bottom: 80px; font-family: {}; background: {};", FONT_BODY, COLOR_PAGE_BG),
 
-                                // Left column: App branding
+                                // Price display card - horizontal layout
                                 div {
-                                    style: format!("flex: 1; min-width: 300px; color: white; font-family: {};", FONT_HEADER),
-                                    h1 {
-                                        style: "font-size: 64px; font-weight: 700; margin: 0 0 10px 0; line-height: 1.2;",
-                                        "Trading"
+                                    style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1); display: flex; justify-content: space-between; align-items: center;", COLOR_CONTENT_BG),
+                                    div { style: "display: flex; gap: 15px; align-items: center;",
+                                        h1 {
+                                            style: format!("margin: 0; font-family: {}; color: {}; font-size: 28px;", FONT_HEADER, COLOR_DARK_GREY),
+                                            "{base_asset}/{quote_asset}"
+                                        }
+                                        // Market picker: switches the price panel, chart, trade form and history
+                                        select {
+                                            value: "{asset}",
+                                            onchange: move |e| current_view.set(AppView::Trading(e.value())),
+                                            style: format!("padding: 8px 12px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px; font-family: {};", FONT_BODY),
+                                            for market in market_list(&assets()) {
+                                                option {
+                                                    key: "{market}",
+                                                    value: "{market}",
+                                                    selected: market == asset,
+                                                    if market.contains('/') { "{market}" } else { "{market}/USD" }
+                                                }
+                                            }
+                                        }
                                     }
-                                    h1 {
-                                        style: "font-size: 64px; font-weight: 700; margin: 0 0 60px 0; line-height: 1.2;",
-                                        "Simulator"
+                                    div { style: "text-align: right;",
+                                        p {
+                                            style: format!("margin: 0; font-size: 36px; font-weight: bold; color: {}; font-family: {};", COLOR_NAVY, FONT_HEADER),
+                                            "{quote_amount_label(current_price, &quote_asset)}"
+                                        }
+                                        if let Some(stats) = ticker().filter(|t| t.asset == base_asset) {
+                                            {
+                                                let change_color = if stats.change >= 0.0 { COLOR_GREEN } else { COLOR_RED };
+                                                let sign = if stats.change >= 0.0 { "+" } else { "-" };
+                                                let change = stats.change.abs();
+                                                rsx! {
+                                                    p {
+                                                        style: format!("margin: 6px 0 0 0; font-size: 14px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                                        span { style: format!("color: {}; font-weight: bold;", change_color),
+                                                            "24h {sign}{stats.change_pct.abs():.2}% ({sign}{usd_amount(change)})"
+                                                        }
+                                                        "  ·  O {usd_amount(stats.open)}  H {usd_amount(stats.high)}  L {usd_amount(stats.low)}"
+                                                    }
+                                                }
+                                            }
+                                        }
                                     }
-                                    p {
-                                        style: "font-size: 16px; opacity: 0.8; margin: 0;",
-                                        "John Prominski"
+                                }
+
+                                // Price Chart (shows base asset price history) - Everything in one white div
+                                div {
+                                    style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                    div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 20px;",
+                                        h2 {
+                                            style: format!("margin: 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
+                                            "{base_asset} Price History"
+                                        }
+                                        div { style: "display: flex; gap: 15px; align-items: center;",
+                                        // Chart type toggle
+                                        div { style: "display: flex; gap: 4px; border: 1px solid var(--color-border); border-radius: 4px; overflow: hidden;",
+                                            button {
+                                                onclick: move |_| chart_type.set("line".to_string()),
+                                                style: if chart_type() == "line" {
+                                                    "padding: 6px 12px; background: #2196F3; color: white; border: none; cursor: pointer; font-size: 12px;"
+                                                } else {
+                                                    "padding: 6px 12px; background: var(--color-content-bg); color: var(--color-dark-grey); border: none; cursor: pointer; font-size: 12px;"
+                                                },
+                                                "Line"
+                                            }
+                                            button {
+                                                onclick: {
+                                                    let base = base_asset.clone();
+                                                    move |_| {
+                                                        chart_type.set("candlestick".to_string());
+                                                        // Trigger candle fetch
+                                                        fetch_candle_history(&base);
+                                                    }
+                                                },
+                                                style: if chart_type() == "candlestick" {
+                                                    "padding: 6px 12px; background: #2196F3; color: white; border: none; cursor: pointer; font-size: 12px;"
+                                                } else {
+                                                    "padding: 6px 12px; background: var(--color-content-bg); color: var(--color-dark-grey); border: none; cursor: pointer; font-size: 12px;"
+                                                },
+                                                "Candles"
+                                            }
+                                        }
+                                        // Timeframe selection
+                                        div { style: "display: flex; gap: 8px;",
+                                            button {
+                                                onclick: {
+                                                    let base = base_asset.clone();
+                                                    move |_| {
+                                                        selected_timeframe.set("1h".to_string());
+                                                        if chart_type() == "candlestick" {
+                                                            fetch_candle_history(&base);
+                                                        }
+                                                    }
+                                                },
+                                                style: if selected_timeframe() == "1h" {
+                                                    "padding: 8px 16px; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-weight: bold;"
+                                                } else {
+                                                    "padding: 8px 16px; background: var(--color-surface); color: var(--color-dark-grey); border: 1px solid var(--color-border); border-radius: 4px; cursor: pointer; font-size: 13px;"
+                                                },
+                                                "1H"
+                                            }
+                                            button {
+                                                onclick: {
+                                                    let base = base_asset.clone();
+                                                    move |_| {
+                                                        selected_timeframe.set("8h".to_string());
+                                                        if chart_type() == "candlestick" {
+                                                            fetch_candle_history(&base);
+                                                        }
+                                                    }
+                                                },
+                                                style: if selected_timeframe() == "8h" {
+                                                    "padding: 8px 16px; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-weight: bold;"
+                                                } else {
+                                                    "padding: 8px 16px; background: var(--color-surface); color: var(--color-dark-grey); border: 1px solid var(--color-border); border-radius: 4px; cursor: pointer; font-size: 13px;"
+                                                },
+                                                "8H"
+                                            }
+                                            button {
+                                                onclick: {
+                                                    let base = base_asset.clone();
+                                                    move |_| {
+                                                        selected_timeframe.set("24h".to_string());
+                                                        if chart_type() == "candlestick" {
+                                                            fetch_candle_history(&base);
+                                                        }
+                                                    }
+                                                },
+                                                style: if selected_timeframe() == "24h" {
+                                                    "padding: 8px 16px; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-weight: bold;"
+                                                } else {
+                                                    "padding: 8px 16px; background: var(--color-surface); color: var(--color-dark-grey); border: 1px solid var(--color-border); border-radius: 4px; cursor: pointer; font-size: 13px;"
+                                                },
+                                                "24H"
+                                            }
+                                        }
                                     }
-                                    p {
-                                        style: "font-size: 16px; opacity: 0.8; margin: 5px 0 0 0;",
-                                        "2025"
+                                }
+
+                                    // Render chart based on chart type
+                                    if chart_type() == "candlestick" {
+                                        if !candle_history().is_empty() {
+                                            CandlestickChart {
+                                                candles: candle_history(),
+                                                quote_asset: quote_asset.to_string(),
+                                                timeframe: selected_timeframe(),
+                                                indicator_data: indicator_data()
+                                            }
+                                        } else {
+                                            p { style: format!("color: {};", COLOR_LIGHT_GREY), "Loading candlestick data..." }
+                                        }
+                                    } else {
+                                        if !current_history.is_empty() {
+                                            PriceChart {
+                                                prices: current_history,
+                                                quote_asset: quote_asset.to_string(),
+                                                timeframe: selected_timeframe(),
+                                                indicator_data: indicator_data(),
+                                                overlays: overlay_indicators(&chart_indicators())
+                                            }
+                                        } else {
+                                            p { style: format!("color: {};", COLOR_LIGHT_GREY), "Loading price data..." }
+                                        }
                                     }
-                                    p {
-                                        style: "font-size: 16px; opacity: 0.8; margin: 5px 0 0 0;",
-                                        "Full-stack Rust Project"
+
+                                    // RSI Panels (only for 1h view), one per enabled RSI
+                                    if selected_timeframe() == "1h" {
+                                        if let Some(ref ind_data) = indicator_data() {
+                                            for indicator in chart_indicators().into_iter().filter(|i| i.enabled && !i.is_overlay()) {
+                                                if let Some(rsi_values) = ind_data.indicators.get(&indicator.key()) {
+                                                    RSIPanel {
+                                                        key: "{indicator.key()}",
+                                                        timestamps: ind_data.timestamps.clone(),
+                                                        rsi_values: rsi_values.clone(),
+                                                        label: indicator.label()
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    // Indicator toggles and settings (only for 1h linechart view) - Below chart
+                                    if selected_timeframe() == "1h" && chart_type() != "candlestick" {
+                                        div { style: format!("margin-top: 15px; padding: 10px; background: {}; border-radius: 4px", COLOR_CONTENT_BG),
+                                            div { style: "display: flex; flex-wrap: wrap; gap: 10px; align-items: center;",
+                                                span { style: format!("font-size: 13px; color: {}; font-weight: bold;", COLOR_DARK_GREY), "Indicators:" }
+                                                for (index, indicator) in chart_indicators().into_iter().enumerate() {
+                                                    label { key: "{index}",
+                                                        style: "display: flex; align-items: center; gap: 5px; cursor: pointer; font-size: 13px;",
+                                                        input {
+                                                            r#type: "checkbox",
+                                                            checked: indicator.enabled,
+                                                            onchange: move |_| {
+                                                                let mut indicators = chart_indicators();
+                                                                indicators[index].enabled = !indicators[index].enabled;
+                                                                set_chart_indicators(indicators);
+                                                            }
+                                                        }
+                                                        if let Some((_, color)) = overlay_indicators(&chart_indicators()).into_iter().find(|(key, _)| indicator.enabled && *key == indicator.key()) {
+                                                            span { style: format!("display: inline-block; width: 12px; height: 3px; background: {};", color) }
+                                                        }
+                                                        "{indicator.label()}"
+                                                    }
+                                                }
+                                                button {
+                                                    onclick: move |_| show_indicator_settings.set(!show_indicator_settings()),
+                                                    style: "margin-left: auto; padding: 4px 10px; background: var(--color-surface); color: var(--color-dark-grey); border: 1px solid var(--color-border); border-radius: 4px; cursor: pointer; font-size: 12px;",
+                                                    if show_indicator_settings() { "Done" } else { "⚙ Configure" }
+                                                }
+                                            }
+
+                                            // Chart settings: change periods, remove and add indicators
+                                            if show_indicator_settings() {
+                                                div { style: "margin-top: 10px; padding-top: 10px; border-top: 1px solid var(--color-divider); display: flex; flex-direction: column; gap: 8px;",
+                                                    for (index, indicator) in chart_indicators().into_iter().enumerate() {
+                                                        div { key: "{index}-{indicator.key()}",
+                                                            style: "display: flex; align-items: center; gap: 8px; font-size: 13px;",
+                                                            select {
+                                                                value: "{indicator.kind}",
+                                                                onchange: move |evt: Event<FormData>| {
+                                                                    let mut indicators = chart_indicators();
+                                                                    indicators[index].kind = evt.value();
+                                                                    set_chart_indicators(indicators);
+                                                                },
+                                                                style: "padding: 4px; border: 1px solid var(--color-border); border-radius: 4px;",
+                                                                for kind in INDICATOR_KINDS {
+                                                                    option { value: "{kind}", selected: kind == indicator.kind, "{kind.to_uppercase()}" }
+                                                                }
+                                                            }
+                                                            input {
+                                                                r#type: "number",
+                                                                min: "{INDICATOR_PERIODS.start()}",
+                                                                max: "{INDICATOR_PERIODS.end()}",
+                                                                value: "{indicator.period}",
+                                                                onchange: move |evt: Event<FormData>| {
+                                                                    let Ok(period) = evt.value().trim().parse::<u32>() else {
+                                                                        return;
+                                                                    };
+                                                                    let mut indicators = chart_indicators();
+                                                                    indicators[index].period = period.clamp(*INDICATOR_PERIODS.start(), *INDICATOR_PERIODS.end());
+                                                                    set_chart_indicators(indicators);
+                                                                },
+                                                                style: "width: 70px; padding: 4px; border: 1px solid var(--color-border); border-radius: 4px;"
+                                                            }
+                                                            button {
+                                                                onclick: move |_| {
+                                                                    let mut indicators = chart_indicators();
+                                                                    indicators.remove(index);
+                                                                    set_chart_indicators(indicators);
+                                                                },
+                                                                style: format!("background: none; border: none; color: {}; cursor: pointer; font-size: 13px;", COLOR_RED),
+                                                                "Remove"
+                                                            }
+                                                        }
+                                                    }
+                                                    if chart_indicators().len() < MAX_CHART_INDICATORS {
+                                                        div { style: "display: flex; align-items: center; gap: 8px; font-size: 13px;",
+                                                            select {
+                                                                value: "{new_indicator_kind}",
+                                                                onchange: move |evt: Event<FormData>| new_indicator_kind.set(evt.value()),
+                                                                style: "padding: 4px; border: 1px solid var(--color-border); border-radius: 4px;",
+                                                                for kind in INDICATOR_KINDS {
+                                                                    option { value: "{kind}", selected: kind == new_indicator_kind(), "{kind.to_uppercase()}" }
+                                                                }
+                                                            }
+                                                            input {
+                                                                r#type: "number",
+                                                                min: "{INDICATOR_PERIODS.start()}",
+                                                                max: "{INDICATOR_PERIODS.end()}",
+                                                                value: "{new_indicator_period}",
+                                                                oninput: move |evt: Event<FormData>| new_indicator_period.set(evt.value()),
+                                                                style: "width: 70px; padding: 4px; border: 1px solid var(--color-border); border-radius: 4px;"
+                                                            }
+                                                            button {
+                                                                onclick: move |_| {
+                                                                    let Ok(period) = new_indicator_period().trim().parse::<u32>() else {
+                                                                        return;
+                                                                    };
+                                                                    if !INDICATOR_PERIODS.contains(&period) {
+                                                                        return;
+                                                                    }
+                                                                    let mut indicator = IndicatorSetting::new(&new_indicator_kind(), period);
+                                                                    indicator.enabled = true;
+                                                                    let mut indicators = chart_indicators();
+                                                                    indicators.push(indicator);
+                                                                    set_chart_indicators(indicators);
+                                                                },
+                                                                style: format!("padding: 4px 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 12px;", COLOR_NAVY),
+                                                                "Add"
+                                                            }
+                                                            span { style: format!("font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                                                "Periods {INDICATOR_PERIODS.start()}–{INDICATOR_PERIODS.end()}"
+                                                            }
+                                                        }
+                                                    }
+                                                    button {
+                                                        onclick: move |_| set_chart_indicators(default_chart_indicators()),
+                                                        style: format!("align-self: flex-start; background: none; border: none; color: {}; cursor: pointer; font-size: 12px; padding: 0;", COLOR_LIGHT_GREY),
+                                                        "Reset to defaults"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+
+                                    // Headlines over the same timeframe as the chart, newest first
+                                    if !headlines().is_empty() {
+                                        div { style: format!("margin-top: 15px; padding: 10px; background: {}; border-radius: 4px", COLOR_CONTENT_BG),
+                                            div { style: format!("font-size: 13px; color: {}; font-weight: bold; margin-bottom: 8px;", COLOR_DARK_GREY), "{base_asset} News" }
+                                            for headline in headlines() {
+                                                div { key: "{headline.url}",
+                                                    style: "display: flex; gap: 10px; font-size: 13px; padding: 4px 0; border-top: 1px solid var(--color-divider);",
+                                                    span { style: format!("color: {}; white-space: nowrap;", COLOR_LIGHT_GREY),
+                                                        {
+                                                            let dt = chrono::DateTime::from_timestamp(headline.timestamp, 0).unwrap_or_default();
+                                                            dt.format("%b %d %H:%M").to_string()
+                                                        }
+                                                    }
+                                                    a { href: "{headline.url}", target: "_blank", rel: "noopener noreferrer",
+                                                        style: format!("color: {}; text-decoration: none;", COLOR_NAVY),
+                                                        "{headline.title}"
+                                                    }
+                                                    if !headline.source.is_empty() {
+                                                        span { style: format!("margin-left: auto; color: {}; white-space: nowrap;", COLOR_LIGHT_GREY), "{headline.source}" }
+                                                    }
+                                                }
+                                            }
+                                        }
                                     }
                                 }
 
-                                // Right column: Login component
-                                div {
-                                    style: format!("flex: 1; min-width: 350px; background: {}; padding: 40px; border-radius: 12px; box-shadow: 0 8px 24px rgba(0,0,0,0.2);", COLOR_CONTENT_BG),
-                                    h2 {
-                                        style: format!("margin: 0 0 30px 0; font-family: {}; color: {}; font-size: 28px;", FONT_HEADER, COLOR_DARK_GREY),
-                                        "Welcome"
+                            // Trade Form and Portfolio - side by side
+                            div { style: "display: grid; grid-template-columns: 1fr 1fr; gap: 25px; margin-bottom: 25px;",
+
+                                // Trade Form
+                                div { class: "trade-form",
+                                    style: format!("background: {}; padding: 25px; border-radius: 8px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                    h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Trade {base_asset}/{quote_asset}" }
+
+                                    // Side
+                                    div { style: "display: flex; gap: 10px; margin-bottom: 15px;",
+                                        for (side, color) in [("Buy", COLOR_GREEN), ("Sell", COLOR_RED)] {
+                                            button {
+                                                key: "{side}",
+                                                onclick: move |_| trade_side.set(side.to_string()),
+                                                style: if trade_side() == side {
+                                                    format!("flex: 1; padding: 8px; background: {}; color: white; border: 1px solid {}; border-radius: 4px; cursor: pointer; font-weight: bold;", color, color)
+                                                } else {
+                                                    format!("flex: 1; padding: 8px; background: var(--color-content-bg); color: {}; border: 1px solid {}; border-radius: 4px; cursor: pointer; font-weight: bold;", color, color)
+                                                },
+                                                "{side}"
+                                            }
+                                        }
                                     }
 
-                                    div { style: "margin-bottom: 20px;",
-                                        input {
-                                            r#type: "text",
-                                            placeholder: "Username",
-                                            value: "{auth_username}",
-                                            oninput: move |e| auth_username.set(e.value()),
-                                            style: format!("width: 100%; padding: 12px; margin-bottom: 10px; border: 1px solid #ddd; border-radius: 4px; font-size: 16px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Order Type:" }
+                                    select {
+                                        value: "{order_type}",
+                                        onchange: move |e| order_type.set(e.value()),
+                                        style: "margin: 10px 0; padding: 10px; width: 95%; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                        option { value: "market", "Market" }
+                                        option { value: "limit", "Limit" }
+                                        option { value: "stop", "Stop" }
+                                    }
+
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Quantity ({base_asset}):" }
+                                    input {
+                                        r#type: "number",
+                                        step: "0.001",
+                                        value: "{quantity}",
+                                        oninput: move |e| quantity.set(e.value()),
+                                        style: "margin: 10px 0; padding: 10px; width: 90%; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+
+                                    if order_type() != "market" {
+                                        label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY),
+                                            if order_type() == "limit" { "Limit Price ({quote_asset}):" } else { "Stop Price ({quote_asset}):" }
                                         }
                                         input {
-                                            r#type: "password",
-                                            placeholder: "Password",
-                                            value: "{auth_password}",
-                                            oninput: move |e| auth_password.set(e.value()),
-                                            style: format!("width: 100%; padding: 12px; border: 1px solid #ddd; border-radius: 4px; font-size: 16px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                            r#type: "number",
+                                            step: "any",
+                                            value: "{order_price}",
+                                            placeholder: format_amount(current_price, &quote_asset),
+                                            oninput: move |e| order_price.set(e.value()),
+                                            style: "margin: 10px 0; padding: 10px; width: 90%; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                        }
+                                        p { style: format!("margin: 0 0 10px 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                            match (order_type().as_str(), trade_side().as_str()) {
+                                                ("limit", "Buy") => "Buys once the price falls to this level or below",
+                                                ("limit", _) => "Sells once the price rises to this level or above",
+                                                (_, "Buy") => "Buys once the price rises to this level or above",
+                                                _ => "Sells once the price falls to this level or below",
+                                            }
                                         }
                                     }
 
-                                    div { style: "display: flex; flex-direction: column; gap: 12px; margin-bottom: 20px;",
-                                        button {
-                                            onclick: move |_| handle_login(),
-                                            style: format!("padding: 14px; background: {}; color: white; border: none; border-radius: 6px; cursor: pointer; font-size: 16px; font-weight: 600; font-family: {};", COLOR_NAVY, FONT_BODY),
-                                            "Login"
+                                    // Estimate from the preview endpoint
+                                    if let Some(preview) = trade_preview() {
+                                        div { style: "margin: 10px 0; padding: 12px; background: var(--color-surface); border-radius: 4px; font-size: 14px;",
+                                            div { style: "display: flex; justify-content: space-between; margin-bottom: 4px;",
+                                                span { style: format!("color: {};", COLOR_LIGHT_GREY), "Price" }
+                                                span { "{format_amount(preview.price, &quote_asset)} {quote_asset}" }
+                                            }
+                                            div { style: "display: flex; justify-content: space-between; margin-bottom: 4px;",
+                                                span { style: format!("color: {};", COLOR_LIGHT_GREY), "Amount" }
+                                                span { "{format_amount(preview.quote_amount, &quote_asset)} {quote_asset}" }
+                                            }
+                                            div { style: "display: flex; justify-content: space-between; margin-bottom: 4px;",
+                                                span { style: format!("color: {};", COLOR_LIGHT_GREY), "Fee" }
+                                                span { "{format_amount(preview.fee, &quote_asset)} {quote_asset}" }
+                                            }
+                                            div { style: format!("display: flex; justify-content: space-between; font-weight: bold; color: {};", COLOR_DARK_GREY),
+                                                span { if trade_side() == "Buy" { "Est. cost" } else { "Est. proceeds" } }
+                                                span {
+                                                    "{format_amount(preview.total, &quote_asset)} {quote_asset}"
+                                                    if let Some(usd) = preview.total_usd.filter(|_| quote_asset != "USD") {
+                                                        " ({usd_amount(usd)})"
+                                                    }
+                                                }
+                                            }
+                                            if !preview.sufficient_balance {
+                                                p { style: format!("margin: 8px 0 0 0; color: {};", COLOR_RED),
+                                                    if trade_side() == "Buy" { "Insufficient {quote_asset} at current balances" } else { "Insufficient {base_asset} at current balances" }
+                                                }
+                                            }
                                         }
-                                        button {
-                                            onclick: move |_| handle_signup(),
-                                            style: format!("padding: 14px; background: {}; color: white; border: none; border-radius: 6px; cursor: pointer; font-size: 16px; font-weight: 600; font-family: {};", COLOR_GREEN, FONT_BODY),
-                                            "Sign Up"
+                                    }
+
+                                    button {
+                                        onclick: {
+                                            let base = base_asset.to_string();
+                                            let quote_opt = if quote_asset != "USD" {
+                                                Some(quote_asset.to_string())
+                                            } else {
+                                                None
+                                            };
+                                            move |_| {
+                                                if order_type() == "market" {
+                                                    execute_trade(&trade_side(), &base, quote_opt.clone())
+                                                } else {
+                                                    place_order(&base, quote_opt.clone())
+                                                }
+                                            }
+                                        },
+                                        style: format!("width: 100%; margin-top: 10px; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", if trade_side() == "Buy" { COLOR_GREEN } else { COLOR_RED }),
+                                        if order_type() == "market" {
+                                            "{trade_side} {base_asset}"
+                                        } else {
+                                            "Place {order_type} {trade_side}"
+                                        }
+                                    }
+
+                                    if !status().is_empty() {
+                                        p { style: format!("margin-top: 10px; color: {};", COLOR_LIGHT_GREY), "{status}" }
+                                    }
+                                }
+
+                                // Portfolio
+                                if let Some(p) = portfolio() {
+                                    div { class: "portfolio",
+                                        style: format!("background: {}; padding: 25px; border-radius: 8px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                        h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Portfolio" }
+                                        {
+                                            // Calculate total portfolio value in USD
+                                            let total_value_usd = portfolio_value_usd(&p.asset_balances, &prices());
+
+                                            let base_balance = p.asset_balances.get(&base_asset).copied().unwrap_or(0.0);
+                                            let quote_balance = p.asset_balances.get(&quote_asset).copied().unwrap_or(0.0);
+
+                                            rsx! {
+                                                p { style: format!("font-size: 18px; font-weight: bold; margin-bottom: 15px; color: {};", COLOR_DARK_GREY),
+                                                    "Estimated Total Value: {display_value(total_value_usd, &display_currency(), &prices())}"
+                                                }
+                                                {
+                                                    if quote_asset == "USD" {
+                                                        rsx! {
+                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "USD: {usd_amount(quote_balance)}" }
+                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "{base_asset}: {format_amount(base_balance, &base_asset)}" }
+                                                        }
+                                                    } else {
+                                                        rsx! {
+                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "{base_asset}: {format_amount(base_balance, &base_asset)}" }
+                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "{quote_asset}: {format_amount(quote_balance, &quote_asset)}" }
+                                                        }
+                                                    }
+                                                }
+                                            }
                                         }
                                     }
+                                }
+                            }
 
-                                    div { style: "border-top: 1px solid #ddd; padding-top: 20px; margin-top: 20px;",
-                                        button {
-                                            onclick: move |_| handle_guest(),
-                                            style: format!("width: 100%; padding: 14px; background: {}; color: white; border: none; border-radius: 6px; cursor: pointer; font-size: 16px; font-weight: 600; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                            "Continue as Guest"
+                            // Open Orders
+                            div { class: "open-orders",
+                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                h2 { style: format!("margin-top: 0; margin-bottom: 15px; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Open Orders" }
+                                if open_orders().is_empty() {
+                                    p { style: format!("color: {};", COLOR_LIGHT_GREY), "No open orders" }
+                                } else {
+                                    table { style: "width: 100%; border-collapse: collapse; font-size: 14px;",
+                                        thead {
+                                            tr { style: "border-bottom: 2px solid var(--color-border); text-align: left;",
+                                                th { style: "padding: 8px;", "Market" }
+                                                th { style: "padding: 8px;", "Type" }
+                                                th { style: "padding: 8px;", "Side" }
+                                                th { style: "padding: 8px;", "Quantity" }
+                                                th { style: "padding: 8px;", "Price" }
+                                                th { style: "padding: 8px;", "" }
+                                            }
                                         }
-                                        p { style: format!("margin-top: 10px; font-size: 14px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                            "Guest profile resets on app restart"
+                                        tbody {
+                                            for order in open_orders() {
+                                                tr { key: "{order.id}", style: "border-bottom: 1px solid var(--color-divider);",
+                                                    td { style: "padding: 8px;", "{order.base_asset}/{order.quote_asset}" }
+                                                    td { style: "padding: 8px; text-transform: capitalize;", "{order.order_type}" }
+                                                    td { style: format!("padding: 8px; color: {};", if order.side == TradeSide::Buy { COLOR_GREEN } else { COLOR_RED }),
+                                                        "{order.side:?}"
+                                                    }
+                                                    td { style: "padding: 8px;", "{format_amount(order.quantity, &order.base_asset)}" }
+                                                    td { style: "padding: 8px;", "{format_amount(order.trigger_price, &order.quote_asset)}" }
+                                                    td { style: "padding: 8px; text-align: right;",
+                                                        button {
+                                                            onclick: {
+                                                                let order_id = order.id.clone();
+                                                                move |_| cancel_order(order_id.clone())
+                                                            },
+                                                            style: format!("padding: 4px 10px; background: var(--color-content-bg); color: {}; border: 1px solid {}; border-radius: 4px; cursor: pointer;", COLOR_RED, COLOR_RED),
+                                                            "Cancel"
+                                                        }
+                                                    }
+                                                }
+                                            }
                                         }
                                     }
-
-                                    if !auth_error().is_empty() {
-                                        p { style: format!("margin-top: 15px; color: {}; font-weight: bold; font-family: {};", COLOR_RED, FONT_BODY), "{auth_error}" }
-                                    }
                                 }
                             }
-                        }
-                    },
-                AppView::Dashboard => rsx! {
-                    div {
-                        style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; font-family: {};", FONT_BODY),
 
-                        h1 {
-                            style: format!("margin: 0 0 30px 0; font-family: {}; color: {}; font-size: 32px;", FONT_HEADER, COLOR_DARK_GREY),
-                            "Dashboard"
-                        }
+                            // Bot Controls
+                            div { class: "bot-controls",
+                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                h2 { style: format!("margin-top: 0; margin-bottom: 15px; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Trading Bot" }
 
-                        if let Some(p) = portfolio() {
-                            // Calculate total portfolio value in USD
-                            {
-                                let mut total_value_usd = 0.0;
-                                let mut btc_bal = 0.0;
-                                let mut eth_bal = 0.0;
-                                let mut usd_bal = 0.0;
+                                // Running bots, each with its own controls
+                                if let Some(bots) = running_bots() {
+                                    for bot in bots.clone() {
+                                        div { style: format!("background: var(--color-success-bg); padding: 15px; border-radius: 6px; margin-bottom: 15px; border-left: 4px solid {};", COLOR_GREEN),
+                                            if bot.dry_run {
+                                                p { style: format!("margin: 0; font-weight: bold; color: {};", COLOR_GREEN), "🤖 Bot Active (dry run: decisions are logged, not traded)" }
+                                            } else {
+                                                p { style: format!("margin: 0; font-weight: bold; color: {};", COLOR_GREEN), "🤖 Bot Active" }
+                                            }
+                                            if let Some(paused) = &bot.paused {
+                                                p { style: format!("margin: 5px 0 0 0; font-size: 13px; font-style: italic; color: {};", COLOR_LIGHT_GREY), "⏸ {paused}" }
+                                            }
+                                            if bot.context_failures > 0 {
+                                                p { style: format!("margin: 5px 0 0 0; font-size: 13px; color: {};", COLOR_RED), "⚠ Price data unavailable, retrying ({bot.context_failures} failed in a row)" }
+                                            }
+                                            if let Some(bot_name) = &bot.bot_name {
+                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Bot: {bot_name}" }
+                                            }
+                                            if let Some(pair) = &bot.trading_pair {
+                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Pair: {pair}" }
+                                            }
+                                            if let Some(stoploss) = bot.stoploss_amount {
+                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Stoploss: {usd_amount(stoploss)}" }
+                                            }
+                                            if let Some(initial_value) = bot.initial_portfolio_value {
+                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Started at: {usd_amount(initial_value)}" }
+                                            }
+                                            div { style: "display: flex; gap: 10px; margin-top: 10px;",
+                                                button {
+                                                    onclick: {
+                                                        let id = bot.bot_instance_id.clone().unwrap_or_default();
+                                                        move |_| stop_bot(id.clone())
+                                                    },
+                                                    style: format!("flex: 1; padding: 10px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 14px; font-weight: bold;", COLOR_RED),
+                                                    "Stop Bot"
+                                                }
+                                                button {
+                                                    onclick: {
+                                                        let id = bot.bot_instance_id.clone().unwrap_or_default();
+                                                        let pause = bot.paused.as_deref() != Some(PAUSED_BY_USER);
+                                                        move |_| pause_bot(id.clone(), pause)
+                                                    },
+                                                    style: format!("flex: 1; padding: 10px; background: var(--color-content-bg); color: {}; border: 1px solid {}; border-radius: 4px; cursor: pointer; font-size: 14px;", COLOR_NAVY, COLOR_NAVY),
+                                                    if bot.paused.as_deref() == Some(PAUSED_BY_USER) { "Resume Bot" } else { "Pause Bot" }
+                                                }
+                                                button {
+                                                    onclick: {
+                                                        let id = bot.bot_instance_id.clone().unwrap_or_default();
+                                                        move |_| export_strategy(id.clone())
+                                                    },
+                                                    style: format!("flex: 1; padding: 10px; background: var(--color-content-bg); color: {}; border: 1px solid {}; border-radius: 4px; cursor: pointer; font-size: 14px;", COLOR_NAVY, COLOR_NAVY),
+                                                    "Export Configuration"
+                                                }
+                                            }
+                                        }
+                                    }
 
-                                for (asset, balance) in p.asset_balances.iter() {
-                                    if asset == "USD" {
-                                        usd_bal = *balance;
-                                        total_value_usd += balance;
-                                    } else if asset == "BTC" {
-                                        btc_bal = *balance;
-                                        total_value_usd += balance * btc_price();
-                                    } else if asset == "ETH" {
-                                        eth_bal = *balance;
-                                        total_value_usd += balance * eth_price();
+                                    if bots.is_empty() {
+                                        div { style: format!("background: {}; padding: 15px; border-radius: 6px; margin-bottom: 15px; border-left: 4px solid {};", COLOR_PAGE_BG, COLOR_LIGHT_GREY),
+                                            p { style: format!("margin: 0; font-weight: bold; color: {};", COLOR_DARK_GREY), "⏸️ No Bot Running" }
+                                            p { style: format!("margin: 5px 0 0 0; font-size: 13px; color: {};", COLOR_LIGHT_GREY), "Configure and start a bot to trade automatically" }
+                                        }
+                                    } else {
+                                        h3 { style: format!("margin: 20px 0 10px 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Start Another Bot" }
                                     }
-                                }
 
-                                rsx! {
-                                    // 3-Column Portfolio Section
-                                    div {
-                                        style: format!("background: {}; padding: 30px; border-radius: 8px; margin-bottom: 30px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                        h2 {
-                                            style: format!("margin: 0 0 25px 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
-                                            "Portfolio"
+                                        div { style: "margin-bottom: 15px;",
+                                            label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Bot Strategy:" }
+                                            select {
+                                                value: "{selected_bot}",
+                                                onchange: move |e| selected_bot.set(e.value()),
+                                                style: "width: 100%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                                if available_bots().is_empty() {
+                                                    option { value: "naive_momentum", "Naive Momentum (Buy on 3↑, Sell on 3↓)" }
+                                                }
+                                                for bot in available_bots() {
+                                                    option { value: "{bot.strategy}", title: "{bot.description}", "{bot.name}" }
+                                                }
+                                            }
                                         }
 
-                                        div {
-                                            style: "display: grid; grid-template-columns: repeat(auto-fit, minmax(300px, 1fr)); gap: 25px;",
+                                        if selected_bot() == "script" {
+                                            div { style: "margin-bottom: 15px;",
+                                                label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Script (Rhai):" }
+                                                textarea {
+                                                    rows: "12",
+                                                    value: "{bot_script}",
+                                                    oninput: move |e| bot_script.set(e.value()),
+                                                    style: "width: 95%; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-family: monospace; font-size: 12px;",
+                                                }
+                                                p { style: format!("margin: 5px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                                    "Define on_tick(ctx) and return buy(amount), sell(amount) or hold(). ctx has price, prices, balances and stoploss; sma, std_dev, pct_change and slope work on price arrays, and this keeps values between ticks."
+                                                }
+                                            }
+                                        }
 
-                                            // Column 1: Total Value & Available Cash
-                                            div {
-                                                style: format!("background: {}; padding: 20px; border-radius: 6px; border: 1px solid #e0e0e0;", COLOR_PAGE_BG),
-                                                h3 {
-                                                    style: format!("margin: 0 0 15px 0; font-family: {}; color: {}; font-size: 16px; font-weight: 600;", FONT_BODY, COLOR_DARK_GREY),
-                                                    "Value Summary"
+                                        div { style: "margin-bottom: 15px;",
+                                            label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Stoploss ({quote_asset}):" }
+                                            input {
+                                                r#type: "number",
+                                                step: "100",
+                                                value: "{bot_stoploss}",
+                                                oninput: move |e| bot_stoploss.set(e.value()),
+                                                style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                            }
+                                            p { style: format!("margin: 5px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY), "Maximum loss before bot stops (step size will be 1% of this)" }
+                                        }
+
+                                        div { style: "margin-bottom: 15px;",
+                                            label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Trade Limits (optional):" }
+                                            div { style: "display: flex; gap: 8px;",
+                                                input {
+                                                    r#type: "number",
+                                                    placeholder: "Max position ({quote_asset})",
+                                                    value: "{bot_max_position}",
+                                                    oninput: move |e| bot_max_position.set(e.value()),
+                                                    style: "flex: 1; min-width: 0; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 13px;",
                                                 }
-                                                div {
-                                                    style: "margin-bottom: 15px;",
-                                                    p {
-                                                        style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                        "Estimated Total Value"
-                                                    }
-                                                    p {
-                                                        style: format!("margin: 5px 0 0 0; font-size: 28px; font-weight: bold; color: {}; font-family: {};", COLOR_GREEN, FONT_HEADER),
-                                                        "${total_value_usd:.2}"
-                                                    }
+                                                input {
+                                                    r#type: "number",
+                                                    placeholder: "Max trade ({quote_asset})",
+                                                    value: "{bot_max_trade}",
+                                                    oninput: move |e| bot_max_trade.set(e.value()),
+                                                    style: "flex: 1; min-width: 0; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 13px;",
                                                 }
-                                                div {
-                                                    p {
-                                                        style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                        "Available Cash"
-                                                    }
-                                                    p {
-                                                        style: format!("margin: 5px 0 0 0; font-size: 20px; font-weight: 600; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                                        "${usd_bal:.2}"
-                                                    }
+                                                input {
+                                                    r#type: "number",
+                                                    placeholder: "Max {base_asset} held",
+                                                    value: "{bot_max_base_position}",
+                                                    oninput: move |e| bot_max_base_position.set(e.value()),
+                                                    style: "flex: 1; min-width: 0; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 13px;",
+                                                }
+                                                input {
+                                                    r#type: "number",
+                                                    step: "1",
+                                                    placeholder: "Max trades/hour",
+                                                    value: "{bot_max_trades_per_hour}",
+                                                    oninput: move |e| bot_max_trades_per_hour.set(e.value()),
+                                                    style: "flex: 1; min-width: 0; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 13px;",
                                                 }
                                             }
+                                            p { style: format!("margin: 5px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY), "Oversized trades are cut down to fit; trades past the hourly count, and buys once the bot holds its maximum, are skipped" }
+                                        }
 
-                                            // Column 2: Asset Balances List
-                                            div {
-                                                style: format!("background: {}; padding: 20px; border-radius: 6px; border: 1px solid #e0e0e0;", COLOR_PAGE_BG),
-                                                h3 {
-                                                    style: format!("margin: 0 0 15px 0; font-family: {}; color: {}; font-size: 16px; font-weight: 600;", FONT_BODY, COLOR_DARK_GREY),
-                                                    "Asset Balances"
+                                        div { style: "margin-bottom: 15px;",
+                                            label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Active Hours, UTC (optional):" }
+                                            div { style: "display: flex; gap: 8px; align-items: center;",
+                                                input {
+                                                    r#type: "time",
+                                                    value: "{bot_active_from}",
+                                                    oninput: move |e| bot_active_from.set(e.value()),
+                                                    style: "flex: 1; min-width: 0; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 13px;",
                                                 }
-                                                div {
-                                                    style: "display: flex; flex-direction: column; gap: 10px;",
-                                                    div {
-                                                        style: "display: flex; justify-content: space-between; align-items: center; padding: 8px 0; border-bottom: 1px solid #e0e0e0;",
-                                                        span {
-                                                            style: format!("font-weight: 600; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                                            "USD"
-                                                        }
-                                                        span {
-                                                            style: format!("color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                                            "${usd_bal:.2}"
-                                                        }
-                                                    }
-                                                    div {
-                                                        style: "display: flex; justify-content: space-between; align-items: center; padding: 8px 0; border-bottom: 1px solid #e0e0e0;",
-                                                        span {
-                                                            style: format!("font-weight: 600; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                                            "BTC"
-                                                        }
-                                                        span {
-                                                            style: format!("color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                                            "{btc_bal:.8}"
-                                                        }
+                                                span { style: format!("font-size: 13px; color: {};", COLOR_DARK_GREY), "to" }
+                                                input {
+                                                    r#type: "time",
+                                                    value: "{bot_active_to}",
+                                                    oninput: move |e| bot_active_to.set(e.value()),
+                                                    style: "flex: 1; min-width: 0; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 13px;",
+                                                }
+                                                label { style: format!("display: flex; align-items: center; gap: 4px; font-size: 13px; color: {}; cursor: pointer;", COLOR_DARK_GREY),
+                                                    input {
+                                                        r#type: "checkbox",
+                                                        checked: bot_weekdays_only(),
+                                                        onchange: move |e| bot_weekdays_only.set(e.checked()),
                                                     }
-                                                    div {
-                                                        style: "display: flex; justify-content: space-between; align-items: center; padding: 8px 0;",
-                                                        span {
-                                                            style: format!("font-weight: 600; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                                            "ETH"
+                                                    "Weekdays only"
+                                                }
+                                            }
+                                            p { style: format!("margin: 5px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY), "Outside these hours the bot keeps watching prices but doesn't trade" }
+                                        }
+
+                                        label { style: format!("display: flex; align-items: center; gap: 6px; margin-bottom: 15px; font-size: 14px; color: {}; cursor: pointer;", COLOR_DARK_GREY),
+                                            input {
+                                                r#type: "checkbox",
+                                                checked: bot_dry_run(),
+                                                onchange: move |e| bot_dry_run.set(e.checked()),
+                                            }
+                                            "Dry run: log decisions against live prices without trading"
+                                        }
+
+                                        button {
+                                            onclick: {
+                                                let base = base_asset.to_string();
+                                                let quote = quote_asset.to_string();
+                                                move |_| start_bot(base.clone(), quote.clone())
+                                            },
+                                            style: format!("width: 100%; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_NAVY),
+                                            "Start Bot"
+                                        }
+
+                                        div { style: "margin-top: 20px;",
+                                            label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Or start from a configuration:" }
+                                            textarea {
+                                                rows: "6",
+                                                placeholder: "Paste an exported strategy configuration, or export one from a running bot to share it",
+                                                value: "{strategy_config}",
+                                                oninput: move |e| strategy_config.set(e.value()),
+                                                style: "width: 95%; padding: 8px; border: 1px solid var(--color-border); border-radius: 4px; font-family: monospace; font-size: 12px;",
+                                            }
+                                            button {
+                                                onclick: move |_| import_strategy(),
+                                                disabled: strategy_config().trim().is_empty(),
+                                                style: format!("width: 100%; margin-top: 10px; padding: 8px; background: var(--color-content-bg); color: {}; border: 1px solid {}; border-radius: 4px; cursor: pointer; font-size: 14px;", COLOR_NAVY, COLOR_NAVY),
+                                                "Start from Configuration"
+                                            }
+                                        }
+                                } else {
+                                    p { style: format!("color: {};", COLOR_LIGHT_GREY), "Loading bot status..." }
+                                }
+                            }
+
+                            // Trade History filtered by base_asset
+                            if let Some(p) = portfolio() {
+                                div { class: "trade-history",
+                                    style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                    h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "{base_asset} Trade History" }
+                                    {
+                                        let filtered_trades: Vec<_> = p.trade_history.iter()
+                                            .filter(|t| t.asset() == base_asset)
+                                            .collect();
+
+                                        if filtered_trades.is_empty() {
+                                            rsx! {
+                                                p { style: format!("color: {};", COLOR_LIGHT_GREY), "No {base_asset} trades yet" }
+                                            }
+                                        } else {
+                                            rsx! {
+                                                div { style: "overflow-x: auto;",
+                                                    table { style: "width: 100%; border-collapse: collapse;",
+                                                        thead {
+                                                            tr { style: "border-bottom: 2px solid var(--color-border);",
+                                                                th { style: format!("padding: 10px; text-align: left; color: {};", COLOR_DARK_GREY), "Side" }
+                                                                th { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "Quantity" }
+                                                                th { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "Price" }
+                                                                th { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "Total" }
+                                                                th { style: format!("padding: 10px; text-align: center; color: {};", COLOR_DARK_GREY), "Source" }
+                                                                th { style: format!("padding: 10px; text-align: left; color: {};", COLOR_DARK_GREY), "Time" }
+                                                            }
                                                         }
-                                                        span {
-                                                            style: format!("color: {}; font-family: {};", COLOR_DARK_GREY, FONT_BODY),
-                                                            "{eth_bal:.8}"
+                                                        tbody {
+                                                            for trade in filtered_trades.iter().rev().take(10) {
+                                                                tr { style: "border-bottom: 1px solid var(--color-divider);",
+                                                                    td {
+                                                                        style: if matches!(trade.side, TradeSide::Buy) {
+                                                                            format!("padding: 10px; color: {}; font-weight: bold;", COLOR_GREEN)
+                                                                        } else {
+                                                                            format!("padding: 10px; color: {}; font-weight: bold;", COLOR_RED)
+                                                                        },
+                                                                        "{trade.side:?}"
+                                                                    }
+                                                                    td { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "{format_amount(trade.quantity, &trade.base_asset)}" }
+                                                                    // Price column - show in quote asset terms
+                                                                    td {
+                                                                        style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY),
+                                                                        {
+                                                                            quote_amount_label(trade.price, &trade.quote_asset)
+                                                                        }
+                                                                    }
+                                                                    // Total column - show in quote asset terms
+                                                                    td {
+                                                                        style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY),
+                                                                        {
+                                                                            quote_amount_label(trade.price * trade.quantity, &trade.quote_asset)
+                                                                        }
+                                                                    }
+                                                                    // Source column - show bot icon if executed by bot
+                                                                    td {
+                                                                        style: format!("padding: 10px; text-align: center; color: {};", COLOR_DARK_GREY),
+                                                                        {
+                                                                            if let Some(bot_name) = &trade.executed_by_bot {
+                                                                                format!("🤖 {}", bot_name)
+                                                                            } else {
+                                                                                "Manual".to_string()
+                                                                            }
+                                                                        }
+                                                                    }
+                                                                    td { style: format!("padding: 10px; color: {};", COLOR_DARK_GREY), "{format_timestamp(&trade.timestamp)}" }
+                                                                }
+                                                            }
                                                         }
                                                     }
                                                 }
-                                            }
-
-                                            // Column 3: Pie Chart
-                                            div {
-                                                style: format!("background: {}; padding: 20px; border-radius: 6px; border: 1px solid #e0e0e0; display: flex; flex-direction: column; align-items: center; justify-content: center;", COLOR_PAGE_BG),
-                                                h3 {
-                                                    style: format!("margin: 0 0 15px 0; font-family: {}; color: {}; font-size: 16px; font-weight: 600; width: 100%; text-align: center;", FONT_BODY, COLOR_DARK_GREY),
-                                                    "Composition"
-                                                }
-                                                PortfolioPieChart {
-                                                    btc_balance: btc_bal,
-                                                    eth_balance: eth_bal,
-                                                    cash_balance: usd_bal,
-                                                    btc_price: btc_price(),
-                                                    eth_price: eth_price()
+                                                if filtered_trades.len() > 10 {
+                                                    p { style: format!("margin-top: 10px; color: {}; font-size: 14px;", COLOR_LIGHT_GREY),
+                                                        "Showing last 10 of {filtered_trades.len()} {base_asset} trades"
+                                                    }
                                                 }
                                             }
                                         }
                                     }
+                                }
+                            }
+                        }
+                    }
+                    }
+                },
+                AppView::Backtest => rsx! {
+                    div {
+                        style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; padding-bottom: 80px; font-family: {}; background: {};", FONT_BODY, COLOR_PAGE_BG),
 
-                                    // Lifetime Statistics
-                                    {
-                                        let lifetime_deposits: f64 = p.trade_history.iter()
-                                            .filter(|t| t.transaction_type == TransactionType::Deposit)
-                                            .map(|t| t.quantity)
-                                            .sum();
-                                        let lifetime_withdrawals: f64 = p.trade_history.iter()
-                                            .filter(|t| t.transaction_type == TransactionType::Withdrawal)
-                                            .map(|t| t.quantity)
-                                            .sum();
-                                        let lifetime_funding = 10000.0 + lifetime_deposits;
-                                        let total_trade_volume_usd: f64 = p.trade_history.iter()
-                                            .filter(|t| t.transaction_type == TransactionType::Trade)
-                                            .filter_map(|t| {
-                                                t.quote_usd_price.map(|q_usd| t.quantity * t.price * q_usd)
-                                            })
-                                            .sum();
+                        // Backtest settings
+                        div {
+                            style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                            h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Backtest a Strategy" }
+                            p { style: format!("margin-top: 0; font-size: 14px; color: {};", COLOR_LIGHT_GREY),
+                                "Replays a bot over recorded prices (the last 30 days, or further back where history has been imported) without touching your portfolio."
+                            }
 
-                                        rsx! {
-                                            div {
-                                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 30px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                                h2 {
-                                                    style: format!("margin: 0 0 20px 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
-                                                    "Lifetime Statistics"
-                                                }
-                                                div {
-                                                    style: "display: grid; grid-template-columns: repeat(auto-fit, minmax(200px, 1fr)); gap: 20px;",
-                                                    div {
-                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
-                                                        p {
-                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                            "Total Funding"
-                                                        }
-                                                        p {
-                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_GREEN, FONT_HEADER),
-                                                            "${lifetime_funding:.2}"
-                                                        }
-                                                    }
-                                                    div {
-                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
-                                                        p {
-                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                            "Total Deposits"
-                                                        }
-                                                        p {
-                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_HEADER),
-                                                            "${lifetime_deposits:.2}"
-                                                        }
-                                                    }
-                                                    div {
-                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
-                                                        p {
-                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                            "Total Withdrawals"
-                                                        }
-                                                        p {
-                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_RED, FONT_HEADER),
-                                                            "${lifetime_withdrawals:.2}"
-                                                        }
-                                                    }
-                                                    div {
-                                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
-                                                        p {
-                                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                            "Trade Volume (USD)"
-                                                        }
-                                                        p {
-                                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_NAVY, FONT_HEADER),
-                                                            "${total_trade_volume_usd:.2}"
-                                                        }
-                                                    }
-                                                }
+                            div { style: "display: grid; grid-template-columns: repeat(3, 1fr); gap: 15px 25px;",
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Strategy:" }
+                                    select {
+                                        value: "{backtest_strategy}",
+                                        onchange: move |e| backtest_strategy.set(e.value()),
+                                        style: "width: 100%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                        if available_bots().is_empty() {
+                                            option { value: "naive_momentum", "Naive Momentum (Buy on rises, Sell on falls)" }
+                                        }
+                                        for bot in available_bots() {
+                                            option { value: "{bot.strategy}", title: "{bot.description}", "{bot.name}" }
+                                        }
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Market:" }
+                                    select {
+                                        value: "{backtest_market}",
+                                        onchange: move |e| backtest_market.set(e.value()),
+                                        style: "width: 100%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                        for market in market_list(&assets()) {
+                                            option {
+                                                key: "{market}",
+                                                value: "{market}",
+                                                if market.contains('/') { "{market}" } else { "{market}/USD" }
                                             }
                                         }
                                     }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Stoploss (quote):" }
+                                    input {
+                                        r#type: "number",
+                                        value: "{backtest_stoploss}",
+                                        oninput: move |e| backtest_stoploss.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "From:" }
+                                    input {
+                                        r#type: "date",
+                                        value: "{backtest_start}",
+                                        oninput: move |e| backtest_start.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "To:" }
+                                    input {
+                                        r#type: "date",
+                                        value: "{backtest_end}",
+                                        oninput: move |e| backtest_end.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Starting balance (quote):" }
+                                    input {
+                                        r#type: "number",
+                                        value: "{backtest_balance}",
+                                        oninput: move |e| backtest_balance.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Trend length (ticks):" }
+                                    input {
+                                        r#type: "number",
+                                        min: "2",
+                                        max: "20",
+                                        value: "{backtest_trend_ticks}",
+                                        oninput: move |e| backtest_trend_ticks.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Trade size (% of stoploss):" }
+                                    input {
+                                        r#type: "number",
+                                        step: "0.1",
+                                        value: "{backtest_step_pct}",
+                                        oninput: move |e| backtest_step_pct.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Cooldown after a trade (ticks):" }
+                                    input {
+                                        r#type: "number",
+                                        min: "0",
+                                        value: "{backtest_cooldown}",
+                                        oninput: move |e| backtest_cooldown.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                            }
 
-                                    // Expandable Deposit/Withdraw Section
-                                    ExpandableSection {
-                                        title: "Fund Account or Make Withdrawal".to_string(),
-                                        children: rsx! {
-                                            div {
-                                                style: "display: grid; grid-template-columns: 1fr 1fr; gap: 20px;",
+                            button {
+                                onclick: move |_| run_backtest(),
+                                disabled: backtest_run().is_some_and(|run| run.status == "running"),
+                                style: format!("margin-top: 20px; padding: 12px 30px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_NAVY),
+                                if backtest_run().is_some_and(|run| run.status == "running") { "Running..." } else { "Run Backtest" }
+                            }
+                            if !backtest_error().is_empty() {
+                                p { style: format!("margin-top: 10px; color: {};", COLOR_RED), "{backtest_error}" }
+                            }
+                        }
 
-                                                // Deposit form
-                                                div {
-                                                    style: format!("padding: 20px; background: {}; border-radius: 6px; border: 1px solid #e0e0e0;", COLOR_PAGE_BG),
-                                                    h3 {
-                                                        style: format!("margin: 0 0 10px 0; color: {}; font-family: {};", COLOR_GREEN, FONT_HEADER),
-                                                        "Deposit"
-                                                    }
-                                                    p {
-                                                        style: format!("font-size: 12px; color: {}; margin: 5px 0 15px 0; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                        "Min: $10 | Max: $100,000"
-                                                    }
-                                                    input {
-                                                        r#type: "number",
-                                                        value: "{deposit_amount}",
-                                                        oninput: move |e| deposit_amount.set(e.value().clone()),
-                                                        style: format!("width: 100%; padding: 12px; margin-bottom: 10px; font-size: 16px; border: 1px solid #ddd; border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
-                                                        placeholder: "Amount"
-                                                    }
-                                                    button {
-                                                        onclick: move |_| execute_deposit(),
-                                                        style: format!("width: 100%; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; font-size: 16px; font-weight: 600; cursor: pointer; font-family: {};", COLOR_GREEN, FONT_BODY),
-                                                        "Deposit Funds"
-                                                    }
-                                                }
+                        // Results
+                        if let Some(result) = backtest_run().and_then(|run| run.result) {
+                            {
+                                let (base_asset, quote_asset) = split_market(&backtest_market());
+                                let metrics = result.metrics.clone();
+                                let span = match (result.equity.first(), result.equity.last()) {
+                                    (Some(first), Some(last)) => last.timestamp - first.timestamp,
+                                    _ => 0,
+                                };
+                                let points: Vec<EquityPoint> = result
+                                    .equity
+                                    .iter()
+                                    .map(|p| EquityPoint { timestamp: p.timestamp, value_usd: p.value, benchmark_usd: p.hold_value })
+                                    .collect();
+                                let mut metric_cards = vec![
+                                    ("Final Value".to_string(), format!("{} {}", format_amount(metrics.final_value, &quote_asset), quote_asset), None),
+                                    ("Buy & Hold".to_string(), format!("{:+.2}%", metrics.buy_and_hold_return_pct), Some(metrics.buy_and_hold_return_pct >= 0.0)),
+                                    ("Trades".to_string(), metrics.trade_count.to_string(), None),
+                                ];
+                                metric_cards.extend(performance_cards(&metrics.performance));
+                                if let Some((_, score)) = backtest_risk() {
+                                    metric_cards.extend(risk_cards(&score));
+                                }
 
-                                                // Withdrawal form
-                                                div {
-                                                    style: format!("padding: 20px; background: {}; border-radius: 6px; border: 1px solid #e0e0e0;", COLOR_PAGE_BG),
-                                                    h3 {
-                                                        style: format!("margin: 0 0 10px 0; color: {}; font-family: {};", COLOR_RED, FONT_HEADER),
-                                                        "Withdraw"
-                                                    }
-                                                    p {
-                                                        style: format!("font-size: 12px; color: {}; margin: 5px 0 15px 0; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                                        "Available: ${usd_bal:.2}"
-                                                    }
-                                                    input {
-                                                        r#type: "number",
-                                                        value: "{withdrawal_amount}",
-                                                        oninput: move |e| withdrawal_amount.set(e.value().clone()),
-                                                        style: format!("width: 100%; padding: 12px; margin-bottom: 10px; font-size: 16px; border: 1px solid #ddd; border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
-                                                        placeholder: "Amount"
+                                rsx! {
+                                    div {
+                                        style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                        h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Results" }
+                                        MetricGrid { cards: metric_cards }
+                                        if let Some(reason) = result.stopped_reason.clone() {
+                                            p { style: format!("color: {};", COLOR_RED), "Bot stopped early: {reason}" }
+                                        }
+                                        p { style: format!("font-size: 13px; color: {};", COLOR_LIGHT_GREY), "{result.ticks} ticks replayed" }
+                                        EquityChart {
+                                            points,
+                                            range: if span <= 86_400 { "24h".to_string() } else { "7d".to_string() },
+                                            benchmark_asset: base_asset.clone(),
+                                            show_benchmark: true,
+                                        }
+                                    }
+
+                                    div {
+                                        style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                        h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Trades" }
+                                        if result.trades.is_empty() {
+                                            p { style: format!("color: {};", COLOR_LIGHT_GREY), "The strategy made no trades in this range" }
+                                        } else {
+                                            div { style: "max-height: 400px; overflow-y: auto;",
+                                                table { style: "width: 100%; border-collapse: collapse; font-size: 14px;",
+                                                    thead {
+                                                        tr { style: "border-bottom: 2px solid var(--color-border); text-align: left;",
+                                                            th { style: "padding: 8px;", "Time" }
+                                                            th { style: "padding: 8px;", "Side" }
+                                                            th { style: "padding: 8px;", "Quantity ({base_asset})" }
+                                                            th { style: "padding: 8px;", "Price ({quote_asset})" }
+                                                        }
                                                     }
-                                                    button {
-                                                        onclick: move |_| execute_withdrawal(),
-                                                        style: format!("width: 100%; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; font-size: 16px; font-weight: 600; cursor: pointer; font-family: {};", COLOR_RED, FONT_BODY),
-                                                        "Withdraw Funds"
+                                                    tbody {
+                                                        for (i, trade) in result.trades.iter().enumerate() {
+                                                            tr { key: "{i}", style: "border-bottom: 1px solid var(--color-divider);",
+                                                                td { style: "padding: 8px;", "{format_timestamp(&trade.timestamp)}" }
+                                                                td { style: format!("padding: 8px; color: {};", if trade.side == TradeSide::Buy { COLOR_GREEN } else { COLOR_RED }),
+                                                                    "{trade.side:?}"
+                                                                }
+                                                                td { style: "padding: 8px;", "{format_amount(trade.quantity, &base_asset)}" }
+                                                                td { style: "padding: 8px;", "{format_amount(trade.price, &quote_asset)}" }
+                                                            }
+                                                        }
                                                     }
                                                 }
                                             }
@@ -2275,343 +5803,359 @@ fn App() -> Element {
                                     }
                                 }
                             }
+                        }
 
-                            // Transaction History
-                            div {
-                                style: format!("background: {}; padding: 25px; border-radius: 8px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                h2 {
-                                    style: format!("margin: 0 0 20px 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
-                                    "Transaction History"
+                        // Grid search over the strategy's parameters
+                        div {
+                            style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                            h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Optimize Parameters" }
+                            p { style: format!("color: {}; font-size: 14px;", COLOR_LIGHT_GREY),
+                                "Backtests every combination of these values (comma-separated, up to 200 combinations) over the range above and ranks them."
+                            }
+                            div { style: "display: grid; grid-template-columns: repeat(4, 1fr); gap: 15px;",
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Trend lengths:" }
+                                    input {
+                                        value: "{optimize_trend_ticks}",
+                                        oninput: move |e| optimize_trend_ticks.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
                                 }
-                                if p.trade_history.is_empty() {
-                                    p { style: format!("color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY), "No transactions yet" }
-                                } else {
-                                    div { style: "overflow-x: auto;",
-                                        table { style: format!("width: 100%; border-collapse: collapse; font-family: {};", FONT_BODY),
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Trade sizes (%):" }
+                                    input {
+                                        value: "{optimize_step_pct}",
+                                        oninput: move |e| optimize_step_pct.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Cooldowns:" }
+                                    input {
+                                        value: "{optimize_cooldown}",
+                                        oninput: move |e| optimize_cooldown.set(e.value()),
+                                        style: "width: 90%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                    }
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Rank by:" }
+                                    select {
+                                        value: "{optimize_rank_by}",
+                                        onchange: move |e| optimize_rank_by.set(e.value()),
+                                        style: "width: 100%; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
+                                        for (value, label) in RANK_BY_OPTIONS {
+                                            option { key: "{value}", value: "{value}", selected: optimize_rank_by() == value, "{label}" }
+                                        }
+                                    }
+                                }
+                            }
+                            button {
+                                onclick: move |_| run_optimize(),
+                                disabled: optimize_running(),
+                                style: format!("margin-top: 20px; padding: 12px 30px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_NAVY),
+                                if optimize_running() { "Optimizing..." } else { "Optimize" }
+                            }
+                            if !optimize_error().is_empty() {
+                                p { style: format!("margin-top: 10px; color: {};", COLOR_RED), "{optimize_error}" }
+                            }
+
+                            if let Some(result) = optimize_result() {
+                                {
+                                    let rank_label = RANK_BY_OPTIONS.iter().find(|(value, _)| *value == result.rank_by).map_or("Score", |(_, label)| *label);
+                                    let lower_is_better = result.rank_by == "max_drawdown";
+                                    rsx! {
+                                        p { style: format!("margin-top: 20px; font-size: 13px; color: {};", COLOR_LIGHT_GREY),
+                                            "{result.combinations} combinations, {result.ticks} ticks each. Showing the best 20."
+                                        }
+                                        table { style: "width: 100%; border-collapse: collapse; font-size: 14px;",
                                             thead {
-                                                tr { style: format!("border-bottom: 2px solid {}; background: {};", COLOR_PAGE_BG, COLOR_PAGE_BG),
-                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Type" }
-                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Asset" }
-                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Action" }
-                                                    th { style: format!("padding: 12px 10px; text-align: right; font-weight: 600; color: {};", COLOR_DARK_GREY), "Quantity" }
-                                                    th { style: format!("padding: 12px 10px; text-align: right; font-weight: 600; color: {};", COLOR_DARK_GREY), "Price" }
-                                                    th { style: format!("padding: 12px 10px; text-align: right; font-weight: 600; color: {};", COLOR_DARK_GREY), "Total" }
-                                                    th { style: format!("padding: 12px 10px; text-align: center; font-weight: 600; color: {};", COLOR_DARK_GREY), "Source" }
-                                                    th { style: format!("padding: 12px 10px; text-align: left; font-weight: 600; color: {};", COLOR_DARK_GREY), "Time" }
+                                                tr { style: format!("text-align: left; color: {};", COLOR_DARK_GREY),
+                                                    th { style: "padding: 8px;", "#" }
+                                                    th { style: "padding: 8px;", "Parameters" }
+                                                    th { style: "padding: 8px;", "{rank_label}" }
+                                                    th { style: "padding: 8px;", "Return" }
+                                                    th { style: "padding: 8px;", "Max Drawdown" }
+                                                    th { style: "padding: 8px;", "Trades" }
+                                                    th { style: "padding: 8px;", "" }
                                                 }
                                             }
                                             tbody {
-                                                for trade in p.trade_history.iter().rev().take(10) {
-                                                    tr { style: "border-bottom: 1px solid #e0e0e0;",
-                                                    // Transaction Type
-                                                    td {
-                                                        style: "padding: 10px;",
-                                                        {
-                                                            match trade.transaction_type {
-                                                                TransactionType::Deposit => "💰 Deposit",
-                                                                TransactionType::Withdrawal => "💸 Withdraw",
-                                                                TransactionType::Trade => "📈 Trade",
-                                                            }
-                                                        }
-                                                    }
-                                                    // Asset
-                                                    td {
-                                                        style: "padding: 10px;",
-                                                        {
-                                                            match trade.transaction_type {
-                                                                TransactionType::Trade => format!("{}/{}", trade.base_asset, trade.quote_asset),
-                                                                _ => trade.asset().to_string(),
-                                                            }
-                                                        }
-                                                    }
-                                                    // Action
-                                                    td {
-                                                        style: if matches!(trade.side, TradeSide::Buy) { "padding: 10px; color: #4caf50; font-weight: bold;" } else { "padding: 10px; color: #f44336; font-weight: bold;" },
-                                                        {
-                                                            match trade.transaction_type {
-                                                                TransactionType::Deposit => "+".to_string(),
-                                                                TransactionType::Withdrawal => "-".to_string(),
-                                                                TransactionType::Trade => format!("{:?}", trade.side),
-                                                            }
-                                                        }
-                                                    }
-                                                    td { style: "padding: 10px; text-align: right;", "{trade.quantity:.8}" }
-                                                    // Price column - show in quote asset terms
-                                                    td {
-                                                        style: "padding: 10px; text-align: right;",
-                                                        {
-                                                            if trade.quote_asset == "USD" {
-                                                                format!("${:.2}", trade.price)
-                                                            } else {
-                                                                format!("{:.4} {}", trade.price, trade.quote_asset)
-                                                            }
-                                                        }
-                                                    }
-                                                    // Total column - show in quote asset terms
-                                                    td {
-                                                        style: "padding: 10px; text-align: right;",
-                                                        {
-                                                            let total = trade.price * trade.quantity;
-                                                            if trade.quote_asset == "USD" {
-                                                                format!("${:.2}", total)
-                                                            } else {
-                                                                format!("{:.4} {}", total, trade.quote_asset)
+                                                for row in result.results.iter().take(20).cloned() {
+                                                    {
+                                                        let parameters = row.parameters.clone();
+                                                        let performance = row.metrics.performance.clone();
+                                                        let score = row.score.map_or("—".to_string(), |score| format!("{:.2}", score));
+                                                        rsx! {
+                                                            tr { key: "{row.rank}", style: "border-bottom: 1px solid var(--color-divider);",
+                                                                td { style: "padding: 8px;", "{row.rank}" }
+                                                                td { style: "padding: 8px; font-family: monospace; font-size: 12px;", "{row.parameters}" }
+                                                                td { style: "padding: 8px; font-weight: bold;", "{score}" }
+                                                                td { style: format!("padding: 8px; color: {};", if performance.total_return_pct >= 0.0 { COLOR_GREEN } else { COLOR_RED }),
+                                                                    "{performance.total_return_pct:+.2}%"
+                                                                }
+                                                                td { style: "padding: 8px;", "{performance.max_drawdown_pct:.2}%" }
+                                                                td { style: "padding: 8px;", "{row.metrics.trade_count}" }
+                                                                td { style: "padding: 8px;",
+                                                                    button {
+                                                                        onclick: move |_| apply_parameters(parameters.clone()),
+                                                                        style: "padding: 4px 10px; background: var(--color-surface); color: var(--color-dark-grey); border: 1px solid var(--color-border); border-radius: 4px; cursor: pointer; font-size: 12px;",
+                                                                        "Use"
+                                                                    }
+                                                                }
                                                             }
                                                         }
                                                     }
-                                                    // Source column - show bot icon if executed by bot
-                                                    td {
-                                                        style: "padding: 10px; text-align: center;",
-                                                        {
-                                                            trade.executed_by_bot
-                                                                .as_deref()
-                                                                .unwrap_or("Manual")
+                                                }
+                                            }
+                                        }
+                                        for map in result.heatmaps.iter().cloned() {
+                                            {
+                                                let scores: Vec<f64> = map.scores.iter().flatten().flatten().copied().collect();
+                                                let low = scores.iter().copied().fold(f64::INFINITY, f64::min);
+                                                let high = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                                                let (worst, best) = if lower_is_better { (high, low) } else { (low, high) };
+                                                rsx! {
+                                                    div { key: "{map.x_parameter}-{map.y_parameter}", style: "margin-top: 25px;",
+                                                        h3 { style: format!("margin: 0 0 10px 0; font-size: 15px; color: {};", COLOR_DARK_GREY),
+                                                            "Best {rank_label}: {map.y_parameter} (rows) × {map.x_parameter} (columns)"
+                                                        }
+                                                        table { style: "border-collapse: collapse; font-size: 13px;",
+                                                            tr {
+                                                                th { style: "padding: 6px;", "" }
+                                                                for (xi, x) in map.x_values.iter().enumerate() {
+                                                                    th { key: "{xi}", style: "padding: 6px; min-width: 60px;", "{x}" }
+                                                                }
+                                                            }
+                                                            for (yi, (y, row)) in map.y_values.iter().zip(map.scores.iter()).enumerate() {
+                                                                tr { key: "{yi}",
+                                                                    th { style: "padding: 6px; text-align: right;", "{y}" }
+                                                                    for (xi, score) in row.iter().enumerate() {
+                                                                        td {
+                                                                            key: "{xi}",
+                                                                            style: format!("padding: 6px; text-align: center; color: white; background: {};", heat_color(*score, worst, best)),
+                                                                            {score.map_or("—".to_string(), |score| format!("{:.2}", score))}
+                                                                        }
+                                                                    }
+                                                                }
+                                                            }
                                                         }
                                                     }
-                                                    td { style: "padding: 10px;", "{format_timestamp(&trade.timestamp)}" }
                                                 }
                                             }
                                         }
                                     }
                                 }
-                                if p.trade_history.len() > 10 {
-                                    p { style: "margin-top: 10px; color: #666; font-size: 14px;",
-                                        "Showing last 10 of {p.trade_history.len()} transactions"
-                                    }
-                                }
                             }
                         }
-                    } else {
-                        p { style: format!("color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY), "Loading portfolio..." }
-                    }
-                }
-            },
-                AppView::Markets => rsx! {
-                    div {
-                        style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; font-family: {};", FONT_BODY),
-
-                        h1 {
-                            style: format!("margin: 0 0 10px 0; font-family: {}; color: {}; font-size: 32px;", FONT_HEADER, COLOR_DARK_GREY),
-                            "Markets"
-                        }
-                        p {
-                            style: format!("color: {}; margin: 0 0 30px 0; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                            "Click on a market to start trading"
-                        }
 
-                        div { style: "display: grid; grid-template-columns: repeat(auto-fit, minmax(350px, 1fr)); gap: 25px;",
-                            // BTC/USD Market
-                            div {
-                                onclick: move |_| current_view.set(AppView::Trading("BTC".to_string())),
-                                style: format!("background: {}; padding: 25px; border-radius: 8px; border: 2px solid #e0e0e0; cursor: pointer; transition: all 0.2s; box-shadow: 0 2px 4px rgba(0,0,0,0.05);", COLOR_CONTENT_BG),
-                                div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 15px;",
-                                    h3 {
-                                        style: format!("margin: 0; font-size: 24px; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY),
-                                        "BTC/USD"
+                        // The optimizer over rolling windows, judged only on prices it didn't see
+                        div {
+                            style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                            h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Walk-Forward Analysis" }
+                            p { style: format!("color: {}; font-size: 14px;", COLOR_LIGHT_GREY),
+                                "Optimizes the values above over each in-sample window, then tests the winner on the out-of-sample window after it, stepping through the range. The chained out-of-sample results show how the strategy holds up on prices it wasn't tuned to."
+                            }
+                            div { style: "display: flex; gap: 15px; align-items: flex-end;",
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "In-sample (days):" }
+                                    input {
+                                        r#type: "number",
+                                        min: "1",
+                                        value: "{walk_forward_in_sample}",
+                                        oninput: move |e| walk_forward_in_sample.set(e.value()),
+                                        style: "width: 120px; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
                                     }
-                                    p {
-                                        style: format!("margin: 0; font-size: 28px; font-weight: bold; color: {}; font-family: {};", COLOR_NAVY, FONT_HEADER),
-                                        "${btc_price():.2}"
+                                }
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Out-of-sample (days):" }
+                                    input {
+                                        r#type: "number",
+                                        min: "1",
+                                        value: "{walk_forward_out_of_sample}",
+                                        oninput: move |e| walk_forward_out_of_sample.set(e.value()),
+                                        style: "width: 120px; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
                                     }
                                 }
-                                p {
-                                    style: format!("color: {}; font-size: 14px; margin-bottom: 15px; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                    "Bitcoin"
-                                }
-                                if !btc_history().is_empty() {
-                                    div { style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center;", COLOR_PAGE_BG),
-                                    svg {
-                                        width: "100%",
-                                        height: "100",
-                                        view_box: "0 0 300 100",
-                                        {
-                                            let prices = btc_history();
-                                            let min = prices.iter().map(|p| p.price).fold(f64::INFINITY, f64::min);
-                                            let max = prices.iter().map(|p| p.price).fold(f64::NEG_INFINITY, f64::max);
-                                            let range = if (max - min).abs() < 0.01 { 1.0 } else { max - min };
-
-                                            let mut path = String::from("M ");
-                                            for (i, point) in prices.iter().enumerate() {
-                                                let x = (i as f64 / (prices.len() - 1) as f64) * 300.0;
-                                                let y = 100.0 - ((point.price - min) / range) * 100.0;
-                                                if i == 0 {
-                                                    path.push_str(&format!("{} {} ", x, y));
-                                                } else {
-                                                    path.push_str(&format!("L {} {} ", x, y));
+                                button {
+                                    onclick: move |_| run_walk_forward(),
+                                    disabled: walk_forward_running(),
+                                    style: format!("padding: 12px 30px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_NAVY),
+                                    if walk_forward_running() { "Running..." } else { "Walk Forward" }
+                                }
+                            }
+                            if !walk_forward_error().is_empty() {
+                                p { style: format!("margin-top: 10px; color: {};", COLOR_RED), "{walk_forward_error}" }
+                            }
+                            if let Some(result) = walk_forward_result() {
+                                {
+                                    let (base_asset, _) = split_market(&backtest_market());
+                                    let mut metric_cards = vec![(
+                                        "Efficiency".to_string(),
+                                        result.efficiency_pct.map_or("—".to_string(), |efficiency| format!("{:.0}%", efficiency)),
+                                        result.efficiency_pct.map(|efficiency| efficiency >= 50.0),
+                                    )];
+                                    metric_cards.extend(performance_cards(&result.out_of_sample));
+                                    let points: Vec<EquityPoint> = result
+                                        .out_of_sample_equity
+                                        .iter()
+                                        .map(|p| EquityPoint { timestamp: p.timestamp, value_usd: p.value, benchmark_usd: p.hold_value })
+                                        .collect();
+                                    let span = match (points.first(), points.last()) {
+                                        (Some(first), Some(last)) => last.timestamp - first.timestamp,
+                                        _ => 0,
+                                    };
+                                    rsx! {
+                                        p { style: format!("margin-top: 15px; font-size: 14px; color: {};", COLOR_DARK_GREY),
+                                            {format!(
+                                                "{} windows of {} combinations{}. Out-of-sample, chained:",
+                                                result.windows.len(),
+                                                result.combinations,
+                                                if result.skipped_windows > 0 { format!(" ({} skipped without prices)", result.skipped_windows) } else { String::new() }
+                                            )}
+                                        }
+                                        MetricGrid { cards: metric_cards }
+                                        EquityChart {
+                                            points,
+                                            range: if span <= 86_400 { "24h".to_string() } else { "7d".to_string() },
+                                            benchmark_asset: base_asset,
+                                            show_benchmark: false,
+                                        }
+                                        table { style: "width: 100%; border-collapse: collapse; font-size: 14px; margin-top: 15px;",
+                                            thead {
+                                                tr { style: "border-bottom: 2px solid var(--color-border); text-align: left;",
+                                                    th { style: "padding: 8px;", "Out-of-sample" }
+                                                    th { style: "padding: 8px;", "Chosen parameters" }
+                                                    th { style: "padding: 8px;", "In-sample return" }
+                                                    th { style: "padding: 8px;", "Out-of-sample return" }
+                                                    th { style: "padding: 8px;", "Trades" }
                                                 }
                                             }
-
-                                                rsx! {
-                                                    path {
-                                                        d: "{path}",
-                                                        fill: "none",
-                                                        stroke: "#2196F3",
-                                                        stroke_width: "2"
+                                            tbody {
+                                                for (i, window) in result.windows.iter().enumerate() {
+                                                    tr { key: "{i}", style: "border-bottom: 1px solid var(--color-divider);",
+                                                        td { style: "padding: 8px;", "{format_timestamp(&window.out_of_sample_start)} – {format_timestamp(&window.out_of_sample_end)}" }
+                                                        td { style: "padding: 8px; font-family: monospace;", "{window.parameters}" }
+                                                        td { style: "padding: 8px;", {format!("{:+.2}%", window.in_sample.performance.total_return_pct)} }
+                                                        td {
+                                                            style: format!("padding: 8px; color: {};", if window.out_of_sample.performance.total_return_pct >= 0.0 { COLOR_GREEN } else { COLOR_RED }),
+                                                            {format!("{:+.2}%", window.out_of_sample.performance.total_return_pct)}
+                                                            if window.stopped_reason.is_some() { " (stopped)" }
+                                                        }
+                                                        td { style: "padding: 8px;", "{window.out_of_sample.trade_count}" }
                                                     }
                                                 }
                                             }
                                         }
                                     }
-                                } else {
-                                    div {
-                                        style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center; color: {}; font-family: {};", COLOR_PAGE_BG, COLOR_LIGHT_GREY, FONT_BODY),
-                                        "Loading chart..."
-                                    }
                                 }
                             }
+                        }
 
-                            // ETH/USD Market
-                            div {
-                                onclick: move |_| current_view.set(AppView::Trading("ETH".to_string())),
-                                style: format!("background: {}; padding: 25px; border-radius: 8px; border: 2px solid #e0e0e0; cursor: pointer; transition: all 0.2s; box-shadow: 0 2px 4px rgba(0,0,0,0.05);", COLOR_CONTENT_BG),
-                                div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 15px;",
-                                    h3 {
-                                        style: format!("margin: 0; font-size: 24px; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY),
-                                        "ETH/USD"
-                                    }
-                                    p {
-                                        style: format!("margin: 0; font-size: 28px; font-weight: bold; color: #9c27b0; font-family: {};", FONT_HEADER),
-                                        "${eth_price():.2}"
+                        // The same strategy through the live bot pipeline, sped up
+                        div {
+                            style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                            h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Live Replay" }
+                            p { style: format!("color: {}; font-size: 14px;", COLOR_LIGHT_GREY),
+                                "Runs the bot as it would run live (ticks, order checks, execution and stoploss) against the range above, played back 10 to 1000 times faster than real time. A replay may take up to an hour."
+                            }
+                            div { style: "display: flex; gap: 15px; align-items: flex-end;",
+                                div {
+                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Speed (×):" }
+                                    input {
+                                        r#type: "number",
+                                        min: "10",
+                                        max: "1000",
+                                        value: "{replay_speed}",
+                                        oninput: move |e| replay_speed.set(e.value()),
+                                        style: "width: 120px; padding: 10px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px;",
                                     }
                                 }
-                                p {
-                                    style: format!("color: {}; font-size: 14px; margin-bottom: 15px; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                    "Ethereum"
-                                }
-                                if !eth_history().is_empty() {
-                                    div { style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center;", COLOR_PAGE_BG),
-                                    svg {
-                                        width: "100%",
-                                        height: "100",
-                                        view_box: "0 0 300 100",
-                                        {
-                                            let prices = eth_history();
-                                            let min = prices.iter().map(|p| p.price).fold(f64::INFINITY, f64::min);
-                                            let max = prices.iter().map(|p| p.price).fold(f64::NEG_INFINITY, f64::max);
-                                            let range = if (max - min).abs() < 0.01 { 1.0 } else { max - min };
-
-                                            let mut path = String::from("M ");
-                                            for (i, point) in prices.iter().enumerate() {
-                                                let x = (i as f64 / (prices.len() - 1) as f64) * 300.0;
-                                                let y = 100.0 - ((point.price - min) / range) * 100.0;
-                                                if i == 0 {
-                                                    path.push_str(&format!("{} {} ", x, y));
-                                                } else {
-                                                    path.push_str(&format!("L {} {} ", x, y));
-                                                }
-                                            }
-
-                                            rsx! {
-                                                path {
-                                                    d: "{path}",
-                                                    fill: "none",
-                                                    stroke: "#9c27b0",
-                                                    stroke_width: "2"
-                                                }
-                                            }
-                                        }
+                                if let Some(run) = replay_run().filter(|run| run.status == "running") {
+                                    button {
+                                        onclick: move |_| cancel_replay(run.id.clone()),
+                                        style: format!("padding: 12px 30px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_RED),
+                                        "Cancel Replay"
                                     }
-                                }
                                 } else {
-                                    div {
-                                        style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center; color: {}; font-family: {};", COLOR_PAGE_BG, COLOR_LIGHT_GREY, FONT_BODY),
-                                        "Loading chart..."
+                                    button {
+                                        onclick: move |_| run_replay(),
+                                        style: format!("padding: 12px 30px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_NAVY),
+                                        "Start Replay"
                                     }
                                 }
                             }
-
-                            // BTC/ETH Market (cross-pair)
-                            div {
-                                onclick: move |_| current_view.set(AppView::Trading("BTC/ETH".to_string())),
-                                style: format!("background: {}; padding: 25px; border-radius: 8px; border: 2px solid #e0e0e0; cursor: pointer; transition: all 0.2s; box-shadow: 0 2px 4px rgba(0,0,0,0.05);", COLOR_CONTENT_BG),
-                                div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 15px;",
-                                    h3 {
-                                        style: format!("margin: 0; font-size: 24px; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY),
-                                        "BTC/ETH"
-                                    }
-                                {
-                                    let btc = btc_price();
-                                    let eth = eth_price();
-                                    let cross_price = if btc > 0.0 && eth > 0.0 {
-                                        btc / eth
-                                    } else {
-                                        0.0
-                                    };
-
-                                        rsx! {
-                                            p {
-                                                style: format!("margin: 0; font-size: 28px; font-weight: bold; color: #ff9800; font-family: {};", FONT_HEADER),
-                                                if cross_price > 0.0 {
-                                                    "{cross_price:.4} ETH"
-                                                } else {
-                                                    "--"
-                                                }
-                                            }
-                                        }
-                                    }
+                            if !replay_error().is_empty() {
+                                p { style: format!("margin-top: 10px; color: {};", COLOR_RED), "{replay_error}" }
+                            }
+                            if let Some(run) = replay_run() {
+                                p { style: format!("margin-top: 15px; font-size: 14px; color: {};", COLOR_DARK_GREY),
+                                    {format!(
+                                        "{} at {}× — replayed to {}, {} bot ticks",
+                                        run.status,
+                                        run.speed,
+                                        run.replayed_to.as_deref().map(format_timestamp).unwrap_or_else(|| "the start".to_string()),
+                                        run.ticks
+                                    )}
                                 }
-                                p {
-                                    style: format!("color: {}; font-size: 14px; margin-bottom: 15px; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
-                                    "Bitcoin per Ethereum"
+                                if let Some(reason) = run.stopped_reason.clone() {
+                                    p { style: format!("color: {};", COLOR_RED), "Bot stopped early: {reason}" }
                                 }
-                            {
-                                // Calculate BTC/ETH historical data
-                                let btc_hist = btc_history();
-                                let eth_hist = eth_history();
-                                let mut cross_history = Vec::new();
-
-                                for btc_point in btc_hist.iter() {
-                                    if let Some(eth_point) = eth_hist.iter().find(|e| e.timestamp == btc_point.timestamp) {
-                                        if eth_point.price > 0.0 {
-                                            cross_history.push(PricePoint {
-                                                timestamp: btc_point.timestamp,
-                                                price: btc_point.price / eth_point.price,
-                                            });
-                                        }
+                                if let (Some(final_value), Some(metrics)) = (run.final_value, run.metrics.clone()) {
+                                    {
+                                        let (_, quote_asset) = split_market(&backtest_market());
+                                        let mut metric_cards = vec![
+                                            ("Final Value".to_string(), format!("{} {}", format_amount(final_value, &quote_asset), quote_asset), None),
+                                            ("Trades".to_string(), run.trades.len().to_string(), None),
+                                        ];
+                                        metric_cards.extend(performance_cards(&metrics));
+                                        rsx! { MetricGrid { cards: metric_cards } }
                                     }
                                 }
+                            }
+                        }
 
-                                    if !cross_history.is_empty() {
-                                        rsx! {
-                                            div { style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center;", COLOR_PAGE_BG),
-                                            svg {
-                                                width: "100%",
-                                                height: "100",
-                                                view_box: "0 0 300 100",
-                                                {
-                                                    let prices = &cross_history;
-                                                    let min = prices.iter().map(|p| p.price).fold(f64::INFINITY, f64::min);
-                                                    let max = prices.iter().map(|p| p.price).fold(f64::NEG_INFINITY, f64::max);
-                                                    let range = if (max - min).abs() < 0.01 { 1.0 } else { max - min };
-
-                                                    let mut path = String::from("M ");
-                                                    for (i, point) in prices.iter().enumerate() {
-                                                        let x = (i as f64 / (prices.len() - 1) as f64) * 300.0;
-                                                        let y = 100.0 - ((point.price - min) / range) * 100.0;
-                                                        if i == 0 {
-                                                            path.push_str(&format!("{} {} ", x, y));
-                                                        } else {
-                                                            path.push_str(&format!("L {} {} ", x, y));
-                                                        }
-                                                    }
-
-                                                    rsx! {
-                                                        path {
-                                                            d: "{path}",
-                                                            fill: "none",
-                                                            stroke: "#ff9800",
-                                                            stroke_width: "2"
-                                                        }
-                                                    }
-                                                }
-                                            }
+                        // Earlier runs, kept on the server for an hour
+                        if !backtest_history().is_empty() {
+                            div {
+                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Recent Runs" }
+                                table { style: "width: 100%; border-collapse: collapse; font-size: 14px;",
+                                    thead {
+                                        tr { style: format!("text-align: left; color: {};", COLOR_DARK_GREY),
+                                            th { style: "padding: 8px;", "Started" }
+                                            th { style: "padding: 8px;", "Strategy" }
+                                            th { style: "padding: 8px;", "Market" }
+                                            th { style: "padding: 8px;", "Range" }
+                                            th { style: "padding: 8px;", "Return" }
+                                            th { style: "padding: 8px;", "Status" }
                                         }
                                     }
-                                    } else {
-                                        rsx! {
-                                            div {
-                                                style: format!("height: 120px; background: {}; border-radius: 4px; display: flex; align-items: center; justify-content: center; color: {}; font-family: {};", COLOR_PAGE_BG, COLOR_LIGHT_GREY, FONT_BODY),
-                                                "Loading chart..."
+                                    tbody {
+                                        for summary in backtest_history() {
+                                            {
+                                                let id = summary.id.clone();
+                                                let selected = backtest_run().is_some_and(|run| run.id == summary.id);
+                                                let range = format!("{} – {}", &summary.params.start[..10.min(summary.params.start.len())], &summary.params.end[..10.min(summary.params.end.len())]);
+                                                let (return_text, return_color) = match &summary.metrics {
+                                                    Some(metrics) => (
+                                                        format!("{:+.2}%", metrics.performance.total_return_pct),
+                                                        if metrics.performance.total_return_pct >= 0.0 { COLOR_GREEN } else { COLOR_RED },
+                                                    ),
+                                                    None => ("—".to_string(), COLOR_DARK_GREY),
+                                                };
+                                                rsx! {
+                                                    tr {
+                                                        key: "{summary.id}",
+                                                        onclick: move |_| load_backtest(id.clone()),
+                                                        style: format!("border-bottom: 1px solid var(--color-divider); cursor: pointer; {}", if selected { "font-weight: bold;" } else { "" }),
+                                                        td { style: "padding: 8px;", "{format_timestamp(&summary.created_at)}" }
+                                                        td { style: "padding: 8px;", "{summary.params.strategy}" }
+                                                        td { style: "padding: 8px;", "{summary.params.base_asset}/{summary.params.quote_asset}" }
+                                                        td { style: "padding: 8px;", "{range}" }
+                                                        td { style: format!("padding: 8px; color: {};", return_color), "{return_text}" }
+                                                        td { style: "padding: 8px;", "{summary.status}" }
+                                                    }
+                                                }
                                             }
                                         }
                                     }
@@ -2620,482 +6164,410 @@ fn App() -> Element {
                         }
                     }
                 },
-                AppView::Trading(asset) => rsx! {
-                    {
-                        let (base_asset, quote_asset, current_price, current_history) = if asset == "BTC/ETH" {
-                            // Cross-pair: BTC priced in ETH
-                            let btc = btc_price();
-                            let eth = eth_price();
-                            let cross_price = if btc > 0.0 && eth > 0.0 { btc / eth } else { 0.0 };
-
-                            // Calculate BTC/ETH historical data from BTC-USD and ETH-USD
-                            let btc_hist = btc_history();
-                            let eth_hist = eth_history();
-                            let mut cross_history = Vec::new();
+                AppView::Funds => {
+                    let usd_balance = funds().map(|summary| summary.usd_balance).unwrap_or(0.0);
+                    rsx! {
+                    div {
+                        style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; padding-bottom: 80px; font-family: {}; background: {};", FONT_BODY, COLOR_PAGE_BG),
 
-                            // Match timestamps and calculate cross-pair price
-                            for btc_point in btc_hist.iter() {
-                                if let Some(eth_point) = eth_hist.iter().find(|e| e.timestamp == btc_point.timestamp) {
-                                    if eth_point.price > 0.0 {
-                                        cross_history.push(PricePoint {
-                                            timestamp: btc_point.timestamp,
-                                            price: btc_point.price / eth_point.price,
-                                        });
-                                    }
-                                }
+                        // Lifetime totals
+                        div {
+                            style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                            h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Funds" }
+                            p { style: format!("margin-top: 0; font-size: 14px; color: {};", COLOR_LIGHT_GREY),
+                                "Simulated cash: deposits and withdrawals only change your USD balance. Lifetime funding includes the $10,000 every account starts with."
                             }
-
-                            ("BTC", "ETH", cross_price, cross_history)
-                        } else if asset == "BTC" {
-                            ("BTC", "USD", btc_price(), btc_history())
-                        } else {
-                            ("ETH", "USD", eth_price(), eth_history())
-                        };
-
-                        rsx! {
-                            div {
-                                style: format!("max-width: 1400px; margin: 0 auto; padding: 30px 20px; padding-bottom: 80px; font-family: {}; background: {};", FONT_BODY, COLOR_PAGE_BG),
-
-                                // Price display card - horizontal layout
+                            if let Some(summary) = funds() {
                                 div {
-                                    style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1); display: flex; justify-content: space-between; align-items: center;", COLOR_CONTENT_BG),
-                                    h1 {
-                                        style: format!("margin: 0; font-family: {}; color: {}; font-size: 28px;", FONT_HEADER, COLOR_DARK_GREY),
-                                        "{base_asset}/{quote_asset}"
+                                    style: "display: grid; grid-template-columns: repeat(auto-fit, minmax(180px, 1fr)); gap: 20px;",
+                                    div {
+                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                        p {
+                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                            "Cash Balance"
+                                        }
+                                        p {
+                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_NAVY, FONT_HEADER),
+                                            "{usd_amount(summary.usd_balance)}"
+                                        }
                                     }
-                                    p {
-                                        style: format!("margin: 0; font-size: 36px; font-weight: bold; color: {}; font-family: {};", COLOR_NAVY, FONT_HEADER),
-                                        if quote_asset == "USD" {
-                                            "${current_price:.2}"
-                                        } else {
-                                            "{current_price:.4} {quote_asset}"
+                                    div {
+                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                        p {
+                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                            "Lifetime Funding"
+                                        }
+                                        p {
+                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_GREEN, FONT_HEADER),
+                                            "{usd_amount(summary.lifetime_funding)}"
                                         }
                                     }
-                                }
-
-                                // Price Chart (shows base asset price history) - Everything in one white div
-                                div {
-                                    style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                    div { style: "display: flex; justify-content: space-between; align-items: center; margin-bottom: 20px;",
-                                        h2 {
-                                            style: format!("margin: 0; font-family: {}; color: {}; font-size: 24px;", FONT_HEADER, COLOR_DARK_GREY),
-                                            "{base_asset} Price History"
+                                    div {
+                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                        p {
+                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                            "Total Deposits"
                                         }
-                                        div { style: "display: flex; gap: 15px; align-items: center;",
-                                        // Chart type toggle
-                                        div { style: "display: flex; gap: 4px; border: 1px solid #ddd; border-radius: 4px; overflow: hidden;",
-                                            button {
-                                                onclick: move |_| chart_type.set("line".to_string()),
-                                                style: if chart_type() == "line" {
-                                                    "padding: 6px 12px; background: #2196F3; color: white; border: none; cursor: pointer; font-size: 12px;"
-                                                } else {
-                                                    "padding: 6px 12px; background: white; color: #333; border: none; cursor: pointer; font-size: 12px;"
-                                                },
-                                                "Line"
-                                            }
-                                            button {
-                                                onclick: move |_| {
-                                                    chart_type.set("candlestick".to_string());
-                                                    // Trigger candle fetch
-                                                    fetch_candle_history(&base_asset);
-                                                },
-                                                style: if chart_type() == "candlestick" {
-                                                    "padding: 6px 12px; background: #2196F3; color: white; border: none; cursor: pointer; font-size: 12px;"
-                                                } else {
-                                                    "padding: 6px 12px; background: white; color: #333; border: none; cursor: pointer; font-size: 12px;"
-                                                },
-                                                "Candles"
-                                            }
+                                        p {
+                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_HEADER),
+                                            "{usd_amount(summary.lifetime_deposits)}"
                                         }
-                                        // Timeframe selection
-                                        div { style: "display: flex; gap: 8px;",
-                                            button {
-                                                onclick: move |_| {
-                                                    selected_timeframe.set("1h".to_string());
-                                                    if chart_type() == "candlestick" {
-                                                        fetch_candle_history(&base_asset);
-                                                    }
-                                                },
-                                                style: if selected_timeframe() == "1h" {
-                                                    "padding: 8px 16px; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-weight: bold;"
-                                                } else {
-                                                    "padding: 8px 16px; background: #f5f5f5; color: #333; border: 1px solid #ddd; border-radius: 4px; cursor: pointer; font-size: 13px;"
-                                                },
-                                                "1H"
-                                            }
-                                            button {
-                                                onclick: move |_| {
-                                                    selected_timeframe.set("8h".to_string());
-                                                    if chart_type() == "candlestick" {
-                                                        fetch_candle_history(&base_asset);
-                                                    }
-                                                },
-                                                style: if selected_timeframe() == "8h" {
-                                                    "padding: 8px 16px; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-weight: bold;"
-                                                } else {
-                                                    "padding: 8px 16px; background: #f5f5f5; color: #333; border: 1px solid #ddd; border-radius: 4px; cursor: pointer; font-size: 13px;"
-                                                },
-                                                "8H"
-                                            }
-                                            button {
-                                                onclick: move |_| {
-                                                    selected_timeframe.set("24h".to_string());
-                                                    if chart_type() == "candlestick" {
-                                                        fetch_candle_history(&base_asset);
-                                                    }
-                                                },
-                                                style: if selected_timeframe() == "24h" {
-                                                    "padding: 8px 16px; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-weight: bold;"
-                                                } else {
-                                                    "padding: 8px 16px; background: #f5f5f5; color: #333; border: 1px solid #ddd; border-radius: 4px; cursor: pointer; font-size: 13px;"
-                                                },
-                                                "24H"
-                                            }
+                                    }
+                                    div {
+                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                        p {
+                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                            "Total Withdrawals"
+                                        }
+                                        p {
+                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_RED, FONT_HEADER),
+                                            "{usd_amount(summary.lifetime_withdrawals)}"
                                         }
                                     }
-                                }
-
-                                    // Render chart based on chart type
-                                    if chart_type() == "candlestick" {
-                                        if !candle_history().is_empty() {
-                                            CandlestickChart {
-                                                candles: candle_history(),
-                                                quote_asset: quote_asset.to_string(),
-                                                timeframe: selected_timeframe(),
-                                                indicator_data: indicator_data()
-                                            }
-                                        } else {
-                                            p { style: format!("color: {};", COLOR_LIGHT_GREY), "Loading candlestick data..." }
+                                    div {
+                                        style: format!("text-align: center; padding: 15px; background: {}; border-radius: 6px;", COLOR_PAGE_BG),
+                                        p {
+                                            style: format!("margin: 0; font-size: 12px; color: {}; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                            "Net Funding"
                                         }
-                                    } else {
-                                        if !current_history.is_empty() {
-                                            PriceChart {
-                                                prices: current_history,
-                                                quote_asset: quote_asset.to_string(),
-                                                timeframe: selected_timeframe(),
-                                                indicator_data: indicator_data()
-                                            }
-                                        } else {
-                                            p { style: format!("color: {};", COLOR_LIGHT_GREY), "Loading price data..." }
+                                        p {
+                                            style: format!("margin: 8px 0 0 0; font-size: 24px; font-weight: bold; color: {}; font-family: {};", COLOR_DARK_GREY, FONT_HEADER),
+                                            "{usd_amount(summary.net_funding)}"
                                         }
                                     }
+                                }
+                            } else {
+                                p { style: format!("color: {};", COLOR_LIGHT_GREY), "Loading..." }
+                            }
+                        }
+
+                        // Deposit and withdrawal forms
+                        div {
+                            style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                        div {
+                            style: "display: grid; grid-template-columns: 1fr 1fr; gap: 20px;",
+
+                            // Deposit form
+                            div {
+                                style: format!("padding: 20px; background: {}; border-radius: 6px; border: 1px solid var(--color-divider);", COLOR_PAGE_BG),
+                                h3 {
+                                    style: format!("margin: 0 0 10px 0; color: {}; font-family: {};", COLOR_GREEN, FONT_HEADER),
+                                    "Deposit"
+                                }
+                                p {
+                                    style: format!("font-size: 12px; color: {}; margin: 5px 0 15px 0; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                    "Min: $10 | Max: $100,000"
+                                }
+                                input {
+                                    r#type: "number",
+                                    value: "{deposit_amount}",
+                                    oninput: move |e| deposit_amount.set(e.value().clone()),
+                                    style: format!("width: 100%; padding: 12px; margin-bottom: 10px; font-size: 16px; border: 1px solid var(--color-border); border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                    placeholder: "Amount"
+                                }
+                                button {
+                                    onclick: move |_| execute_deposit(),
+                                    style: format!("width: 100%; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; font-size: 16px; font-weight: 600; cursor: pointer; font-family: {};", COLOR_GREEN, FONT_BODY),
+                                    "Deposit Funds"
+                                }
+                            }
+
+                            // Withdrawal form
+                            div {
+                                style: format!("padding: 20px; background: {}; border-radius: 6px; border: 1px solid var(--color-divider);", COLOR_PAGE_BG),
+                                h3 {
+                                    style: format!("margin: 0 0 10px 0; color: {}; font-family: {};", COLOR_RED, FONT_HEADER),
+                                    "Withdraw"
+                                }
+                                p {
+                                    style: format!("font-size: 12px; color: {}; margin: 5px 0 15px 0; font-family: {};", COLOR_LIGHT_GREY, FONT_BODY),
+                                    "Available: {usd_amount(usd_balance)}"
+                                }
+                                input {
+                                    r#type: "number",
+                                    value: "{withdrawal_amount}",
+                                    oninput: move |e| withdrawal_amount.set(e.value().clone()),
+                                    style: format!("width: 100%; padding: 12px; margin-bottom: 10px; font-size: 16px; border: 1px solid var(--color-border); border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                    placeholder: "Amount"
+                                }
+                                button {
+                                    onclick: move |_| execute_withdrawal(),
+                                    style: format!("width: 100%; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; font-size: 16px; font-weight: 600; cursor: pointer; font-family: {};", COLOR_RED, FONT_BODY),
+                                    "Withdraw Funds"
+                                }
+                            }
+                        }
+                        }
 
-                                    // RSI Panel (only for 1h view and when RSI is enabled)
-                                    if selected_timeframe() == "1h" && show_rsi_14() {
-                                        if let Some(ref ind_data) = indicator_data() {
-                                            if let Some(rsi_values) = ind_data.indicators.get("rsi_14") {
-                                                RSIPanel {
-                                                    timestamps: ind_data.timestamps.clone(),
-                                                    rsi_values: rsi_values.clone()
-                                                }
+                        ExpandableSection {
+                            title: "Deposit & Withdrawal History".to_string(),
+                            children: rsx! {
+                                if funds().map(|summary| summary.transfers.is_empty()).unwrap_or(true) {
+                                    p { style: format!("margin: 0; color: {};", COLOR_LIGHT_GREY), "No deposits or withdrawals yet" }
+                                } else {
+                                    table { style: format!("width: 100%; border-collapse: collapse; font-family: {};", FONT_BODY),
+                                        thead {
+                                            tr { style: "border-bottom: 2px solid var(--color-divider);",
+                                                th { style: format!("padding: 10px; text-align: left; color: {};", COLOR_DARK_GREY), "Type" }
+                                                th { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "Amount" }
+                                                th { style: format!("padding: 10px; text-align: left; color: {};", COLOR_DARK_GREY), "Time" }
                                             }
                                         }
-                                    }
-
-                                    // Indicator toggles (only for 1h linechart view) - Below chart
-                                    if selected_timeframe() == "1h" && chart_type() != "candlestick" {
-                                        // div { style: format!("display: flex; gap: 10px; align-items: center; margin-top: 15px; padding: 10px; background: {}; border-radius: 4px; border-top: 1px solid #e0e0e0;", COLOR_CONTENT_BG),
-                                        div { style: format!("display: flex; gap: 10px; align-items: center; margin-top: 15px; padding: 10px; background: {}; border-radius: 4px", COLOR_CONTENT_BG),
-                                            span { style: format!("font-size: 13px; color: {}; font-weight: bold;", COLOR_DARK_GREY), "Indicators:" }
-                                            label { style: "display: flex; align-items: center; gap: 5px; cursor: pointer; font-size: 13px;",
-                                                input {
-                                                    r#type: "checkbox",
-                                                    checked: show_sma_20(),
-                                                    onchange: move |_| show_sma_20.set(!show_sma_20())
-                                                }
-                                                "SMA(20)"
-                                            }
-                                            label { style: "display: flex; align-items: center; gap: 5px; cursor: pointer; font-size: 13px;",
-                                                input {
-                                                    r#type: "checkbox",
-                                                    checked: show_sma_50(),
-                                                    onchange: move |_| show_sma_50.set(!show_sma_50())
-                                                }
-                                                "SMA(50)"
-                                            }
-                                            label { style: "display: flex; align-items: center; gap: 5px; cursor: pointer; font-size: 13px;",
-                                                input {
-                                                    r#type: "checkbox",
-                                                    checked: show_ema_12(),
-                                                    onchange: move |_| show_ema_12.set(!show_ema_12())
-                                                }
-                                                "EMA(12)"
-                                            }
-                                            label { style: "display: flex; align-items: center; gap: 5px; cursor: pointer; font-size: 13px;",
-                                                input {
-                                                    r#type: "checkbox",
-                                                    checked: show_ema_26(),
-                                                    onchange: move |_| show_ema_26.set(!show_ema_26())
-                                                }
-                                                "EMA(26)"
-                                            }
-                                            label { style: "display: flex; align-items: center; gap: 5px; cursor: pointer; font-size: 13px;",
-                                                input {
-                                                    r#type: "checkbox",
-                                                    checked: show_rsi_14(),
-                                                    onchange: move |_| show_rsi_14.set(!show_rsi_14())
+                                        tbody {
+                                            for (i, transfer) in funds().map(|summary| summary.transfers).unwrap_or_default().into_iter().enumerate() {
+                                                tr { key: "{i}", style: "border-bottom: 1px solid var(--color-divider);",
+                                                    if transfer.transaction_type == TransactionType::Deposit {
+                                                        td { style: format!("padding: 10px; color: {}; font-weight: bold;", COLOR_GREEN), "Deposit" }
+                                                        td { style: format!("padding: 10px; text-align: right; color: {};", COLOR_GREEN), "+{usd_amount(transfer.quantity)}" }
+                                                    } else {
+                                                        td { style: format!("padding: 10px; color: {}; font-weight: bold;", COLOR_RED), "Withdrawal" }
+                                                        td { style: format!("padding: 10px; text-align: right; color: {};", COLOR_RED), "-{usd_amount(transfer.quantity)}" }
+                                                    }
+                                                    td { style: format!("padding: 10px; color: {};", COLOR_LIGHT_GREY), "{format_timestamp(&transfer.timestamp)}" }
                                                 }
-                                                "RSI(14)"
                                             }
                                         }
                                     }
                                 }
+                            }
+                        }
+                    }
+                    }
+                },
+                AppView::Settings => {
+                    let is_guest = user_id() == "demo_user";
+                    rsx! {
+                    div {
+                        style: format!("max-width: 900px; margin: 0 auto; padding: 30px 20px; padding-bottom: 80px; font-family: {}; background: {};", FONT_BODY, COLOR_PAGE_BG),
 
-                            // Trade Form and Portfolio - side by side
-                            div { style: "display: grid; grid-template-columns: 1fr 1fr; gap: 25px; margin-bottom: 25px;",
+                        h1 {
+                            style: format!("margin: 0 0 30px 0; font-family: {}; color: {}; font-size: 32px;", FONT_HEADER, COLOR_DARK_GREY),
+                            "Settings"
+                        }
+                        if is_guest {
+                            p { style: format!("margin-top: 0; font-size: 14px; color: {};", COLOR_LIGHT_GREY),
+                                "Guests share one demo account: preferences last for this visit only, and password, sessions and account deletion need your own account."
+                            }
+                        }
 
-                                // Trade Form
-                                div { class: "trade-form",
-                                    style: format!("background: {}; padding: 25px; border-radius: 8px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                    h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Trade {base_asset}/{quote_asset}" }
+                        // Display currency and which notifications pop up as toasts
+                        div {
+                            style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                            h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Preferences" }
+                            label {
+                                style: format!("display: block; margin-bottom: 6px; font-size: 14px; font-weight: 600; color: {};", COLOR_DARK_GREY),
+                                "Display currency"
+                            }
+                            select {
+                                value: "{display_currency}",
+                                onchange: move |evt: Event<FormData>| {
+                                    let currency = evt.value();
+                                    display_currency.set(currency.clone());
+                                    save_setting("display_currency", serde_json::json!(currency));
+                                },
+                                style: format!("padding: 8px 12px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px; font-family: {};", FONT_BODY),
+                                for currency in std::iter::once("USD".to_string()).chain(assets()) {
+                                    option {
+                                        key: "{currency}",
+                                        value: "{currency}",
+                                        selected: currency == display_currency(),
+                                        "{currency}"
+                                    }
+                                }
+                            }
+                            p { style: format!("margin: 6px 0 20px 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                "Portfolio totals are converted at live prices; trades and balances stay in their own assets."
+                            }
 
-                                    label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Quantity ({base_asset}):" }
+                            label {
+                                style: format!("display: block; margin-bottom: 6px; font-size: 14px; font-weight: 600; color: {};", COLOR_DARK_GREY),
+                                "Pop-up notifications"
+                            }
+                            for (category, label) in NOTIFICATION_CATEGORIES.iter().copied() {
+                                label { key: "{category}",
+                                    style: format!("display: flex; align-items: center; gap: 6px; margin-bottom: 6px; font-size: 14px; color: {}; cursor: pointer;", COLOR_DARK_GREY),
                                     input {
-                                        r#type: "number",
-                                        step: "0.001",
-                                        value: "{quantity}",
-                                        oninput: move |e| quantity.set(e.value()),
-                                        style: "margin: 10px 0; padding: 10px; width: 90%; border: 1px solid #ddd; border-radius: 4px; font-size: 14px;",
+                                        r#type: "checkbox",
+                                        checked: notification_preferences().get(category).copied().unwrap_or(true),
+                                        onchange: move |e| {
+                                            notification_preferences.write().insert(category.to_string(), e.checked());
+                                            save_setting("notification_preferences", serde_json::json!(notification_preferences()));
+                                        },
                                     }
+                                    "{label}"
+                                }
+                            }
+                            p { style: format!("margin: 6px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                "Muted notifications still appear in the notification center."
+                            }
 
-                                    div { style: "display: flex; gap: 10px; margin-top: 10px;",
-                                        button {
-                                            onclick: {
-                                                let base = base_asset.to_string();
-                                                let quote_opt = if quote_asset != "USD" {
-                                                    Some(quote_asset.to_string())
-                                                } else {
-                                                    None
-                                                };
-                                                move |_| execute_trade("Buy", &base, quote_opt.clone())
-                                            },
-                                            style: format!("flex: 1; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_GREEN),
-                                            "Buy {base_asset}"
-                                        }
-                                        button {
-                                            onclick: {
-                                                let base = base_asset.to_string();
-                                                let quote_opt = if quote_asset != "USD" {
-                                                    Some(quote_asset.to_string())
-                                                } else {
-                                                    None
-                                                };
-                                                move |_| execute_trade("Sell", &base, quote_opt.clone())
-                                            },
-                                            style: format!("flex: 1; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_RED),
-                                            "Sell {base_asset}"
-                                        }
+                            if !is_guest {
+                                label {
+                                    style: format!("display: block; margin: 20px 0 6px 0; font-size: 14px; font-weight: 600; color: {};", COLOR_DARK_GREY),
+                                    "Time zone"
+                                }
+                                div { style: "display: flex; align-items: center; gap: 10px;",
+                                    span { style: format!("font-size: 14px; color: {};", COLOR_DARK_GREY),
+                                        "{utc_offset_label(utc_offset_minutes().unwrap_or(0))}"
                                     }
-
-                                    if !status().is_empty() {
-                                        p { style: format!("margin-top: 10px; color: {};", COLOR_LIGHT_GREY), "{status}" }
+                                    button {
+                                        onclick: move |_| {
+                                            // The browser's current offset; saved again after a daylight saving change
+                                            let minutes = chrono::Local::now().offset().local_minus_utc() / 60;
+                                            utc_offset_minutes.set(Some(minutes));
+                                            save_setting("utc_offset_minutes", serde_json::json!(minutes));
+                                        },
+                                        style: format!("padding: 6px 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 13px; font-family: {};", COLOR_NAVY, FONT_BODY),
+                                        "Use this browser's time zone"
                                     }
                                 }
+                                p { style: format!("margin: 6px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                    "Statements and daily summaries start at local midnight in this time zone."
+                                }
 
-                                // Portfolio
-                                if let Some(p) = portfolio() {
-                                    div { class: "portfolio",
-                                        style: format!("background: {}; padding: 25px; border-radius: 8px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                        h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Portfolio" }
-                                        {
-                                            // Calculate total portfolio value in USD
-                                            let mut total_value_usd = 0.0;
-                                            for (asset, balance) in p.asset_balances.iter() {
-                                                if asset == "USD" {
-                                                    total_value_usd += balance;
-                                                } else if asset == "BTC" {
-                                                    total_value_usd += balance * btc_price();
-                                                } else if asset == "ETH" {
-                                                    total_value_usd += balance * eth_price();
-                                                }
-                                            }
-
-                                            let base_balance = p.asset_balances.get(base_asset).copied().unwrap_or(0.0);
-                                            let quote_balance = p.asset_balances.get(quote_asset).copied().unwrap_or(0.0);
-
-                                            rsx! {
-                                                p { style: format!("font-size: 18px; font-weight: bold; margin-bottom: 15px; color: {};", COLOR_DARK_GREY),
-                                                    "Estimated Total Value: ${total_value_usd:.2}"
-                                                }
-                                                {
-                                                    if quote_asset == "USD" {
-                                                        rsx! {
-                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "USD: ${quote_balance:.2}" }
-                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "{base_asset}: {base_balance:.8}" }
-                                                        }
-                                                    } else {
-                                                        rsx! {
-                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "{base_asset}: {base_balance:.8}" }
-                                                            p { style: format!("font-size: 16px; margin: 5px 0; color: {};", COLOR_DARK_GREY), "{quote_asset}: {quote_balance:.8}" }
-                                                        }
-                                                    }
-                                                }
-                                            }
+                                label {
+                                    style: format!("display: block; margin: 20px 0 6px 0; font-size: 14px; font-weight: 600; color: {};", COLOR_DARK_GREY),
+                                    "Daily summary"
+                                }
+                                input {
+                                    r#type: "time",
+                                    value: "{daily_summary_time}",
+                                    onchange: move |evt| {
+                                        let time = evt.value();
+                                        daily_summary_time.set(time.clone());
+                                        if time.is_empty() {
+                                            save_setting("daily_summary", serde_json::Value::Null);
+                                        } else {
+                                            save_setting("daily_summary", serde_json::json!({ "time": time }));
                                         }
-                                    }
+                                    },
+                                    style: format!("padding: 8px 12px; border: 1px solid var(--color-border); border-radius: 4px; font-size: 14px; font-family: {};", FONT_BODY),
+                                }
+                                p { style: format!("margin: 6px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                    "P&L, trades, bot activity and the biggest movers of the last 24 hours, sent at this local time to your notification endpoints. Clear it to stop."
                                 }
                             }
+                        }
 
-                            // Bot Controls
-                            div { class: "bot-controls",
+                        if !is_guest {
+                            // Change password
+                            div {
                                 style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                h2 { style: format!("margin-top: 0; margin-bottom: 15px; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Trading Bot" }
+                                h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Change Password" }
+                                input {
+                                    r#type: "password",
+                                    value: "{current_password}",
+                                    oninput: move |e| current_password.set(e.value()),
+                                    style: format!("width: 100%; max-width: 360px; display: block; padding: 10px; margin-bottom: 10px; font-size: 14px; border: 1px solid var(--color-border); border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                    placeholder: "Current password"
+                                }
+                                input {
+                                    r#type: "password",
+                                    value: "{new_password}",
+                                    oninput: move |e| new_password.set(e.value()),
+                                    style: format!("width: 100%; max-width: 360px; display: block; padding: 10px; margin-bottom: 10px; font-size: 14px; border: 1px solid var(--color-border); border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                    placeholder: "New password"
+                                }
+                                input {
+                                    r#type: "password",
+                                    value: "{confirm_password}",
+                                    oninput: move |e| confirm_password.set(e.value()),
+                                    style: format!("width: 100%; max-width: 360px; display: block; padding: 10px; margin-bottom: 10px; font-size: 14px; border: 1px solid var(--color-border); border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                    placeholder: "Confirm new password"
+                                }
+                                button {
+                                    onclick: move |_| change_password(),
+                                    style: format!("padding: 10px 20px; background: {}; color: white; border: none; border-radius: 4px; font-size: 14px; font-weight: 600; cursor: pointer; font-family: {};", COLOR_NAVY, FONT_BODY),
+                                    "Change Password"
+                                }
+                                if !password_status().is_empty() {
+                                    p { style: format!("margin: 10px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "{password_status}" }
+                                }
+                                p { style: format!("margin: 10px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                    "Changing your password signs out every other session. Accounts created with GitHub or Google have no password to change."
+                                }
+                            }
 
-                                // Bot Status Display
-                                if let Some(status) = bot_status() {
-                                    if status.is_active {
-                                        div { style: format!("background: #e8f5e9; padding: 15px; border-radius: 6px; margin-bottom: 15px; border-left: 4px solid {};", COLOR_GREEN),
-                                            p { style: format!("margin: 0; font-weight: bold; color: {};", COLOR_GREEN), "🤖 Bot Active" }
-                                            if let Some(bot_name) = &status.bot_name {
-                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Bot: {bot_name}" }
-                                            }
-                                            if let Some(pair) = &status.trading_pair {
-                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Pair: {pair}" }
-                                            }
-                                            if let Some(stoploss) = status.stoploss_amount {
-                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Stoploss: ${stoploss:.2}" }
+                            // Active sessions
+                            div {
+                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Active Sessions" }
+                                if sessions().is_empty() {
+                                    p { style: format!("color: {};", COLOR_LIGHT_GREY), "No active sessions" }
+                                }
+                                for session in sessions() {
+                                    div { key: "{session.id}",
+                                        style: "display: flex; justify-content: space-between; align-items: center; gap: 15px; padding: 10px 0; border-bottom: 1px solid var(--color-divider);",
+                                        div {
+                                            p { style: format!("margin: 0; font-size: 14px; color: {};", COLOR_DARK_GREY),
+                                                "{session.user_agent.clone().unwrap_or_else(|| \"Unknown device\".to_string())}"
                                             }
-                                            if let Some(initial_value) = status.initial_portfolio_value {
-                                                p { style: format!("margin: 5px 0 0 0; font-size: 14px; color: {};", COLOR_DARK_GREY), "Started at: ${initial_value:.2}" }
+                                            p { style: format!("margin: 4px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                                "{session.ip_address.clone().unwrap_or_default()} · signed in {format_timestamp(&session.created_at)}"
                                             }
-                                        }
-
-                                        button {
-                                            onclick: move |_| stop_bot(),
-                                            style: format!("width: 100%; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_RED),
-                                            "Stop Bot"
-                                        }
-                                    } else {
-                                        div { style: format!("background: {}; padding: 15px; border-radius: 6px; margin-bottom: 15px; border-left: 4px solid {};", COLOR_PAGE_BG, COLOR_LIGHT_GREY),
-                                            p { style: format!("margin: 0; font-weight: bold; color: {};", COLOR_DARK_GREY), "⏸️ No Bot Running" }
-                                            p { style: format!("margin: 5px 0 0 0; font-size: 13px; color: {};", COLOR_LIGHT_GREY), "Configure and start a bot to trade automatically" }
-                                        }
-
-                                        div { style: "margin-bottom: 15px;",
-                                            label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Bot Strategy:" }
-                                            select {
-                                                value: "{selected_bot}",
-                                                onchange: move |e| selected_bot.set(e.value()),
-                                                style: "width: 100%; padding: 10px; border: 1px solid #ddd; border-radius: 4px; font-size: 14px;",
-                                                option { value: "naive_momentum", "Naive Momentum (Buy on 3↑, Sell on 3↓)" }
+                                            if let Some(last_used_at) = &session.last_used_at {
+                                                p { style: format!("margin: 2px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY),
+                                                    "Last used {format_timestamp(last_used_at)}"
+                                                }
                                             }
                                         }
-
-                                        div { style: "margin-bottom: 15px;",
-                                            label { style: format!("display: block; margin-bottom: 5px; font-weight: bold; color: {};", COLOR_DARK_GREY), "Stoploss ({quote_asset}):" }
-                                            input {
-                                                r#type: "number",
-                                                step: "100",
-                                                value: "{bot_stoploss}",
-                                                oninput: move |e| bot_stoploss.set(e.value()),
-                                                style: "width: 90%; padding: 10px; border: 1px solid #ddd; border-radius: 4px; font-size: 14px;",
+                                        if session.current {
+                                            span { style: format!("font-size: 12px; font-weight: 600; color: {};", COLOR_GREEN), "This browser" }
+                                        } else {
+                                            button {
+                                                onclick: {
+                                                    let id = session.id.clone();
+                                                    move |_| revoke_session(id.clone())
+                                                },
+                                                style: format!("padding: 6px 12px; background: transparent; color: {}; border: 1px solid {}; border-radius: 4px; font-size: 13px; cursor: pointer; font-family: {};", COLOR_RED, COLOR_RED, FONT_BODY),
+                                                "Revoke"
                                             }
-                                            p { style: format!("margin: 5px 0 0 0; font-size: 12px; color: {};", COLOR_LIGHT_GREY), "Maximum loss before bot stops (step size will be 1% of this)" }
-                                        }
-
-                                        button {
-                                            onclick: {
-                                                let base = base_asset.to_string();
-                                                let quote = quote_asset.to_string();
-                                                move |_| start_bot(base.clone(), quote.clone())
-                                            },
-                                            style: format!("width: 100%; padding: 12px; background: {}; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 16px; font-weight: bold;", COLOR_NAVY),
-                                            "Start Bot"
                                         }
                                     }
-                                } else {
-                                    p { style: format!("color: {};", COLOR_LIGHT_GREY), "Loading bot status..." }
+                                }
+                                button {
+                                    onclick: move |_| logout_everywhere(),
+                                    style: format!("margin-top: 15px; padding: 10px 20px; background: {}; color: white; border: none; border-radius: 4px; font-size: 14px; font-weight: 600; cursor: pointer; font-family: {};", COLOR_NAVY, FONT_BODY),
+                                    "Log Out Everywhere"
                                 }
                             }
 
-                            // Trade History filtered by base_asset
-                            if let Some(p) = portfolio() {
-                                div { class: "trade-history",
-                                    style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
-                                    h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "{base_asset} Trade History" }
-                                    {
-                                        let filtered_trades: Vec<_> = p.trade_history.iter()
-                                            .filter(|t| t.asset() == base_asset)
-                                            .collect();
+                            // Export account data
+                            div {
+                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1);", COLOR_CONTENT_BG),
+                                h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_DARK_GREY), "Your Data" }
+                                p { style: format!("margin-top: 0; font-size: 14px; color: {};", COLOR_DARK_GREY),
+                                    "A zip archive of your profile, balances, trades, orders, bot history, daily summaries and settings, as JSON with CSV files for spreadsheets."
+                                }
+                                a {
+                                    href: "{API_BASE}/account/export?user_id={user_id}&token={session_token}",
+                                    download: "",
+                                    style: format!("display: inline-block; padding: 10px 20px; background: {}; color: white; border-radius: 4px; font-size: 14px; font-weight: 600; text-decoration: none; font-family: {};", COLOR_NAVY, FONT_BODY),
+                                    "Download My Data"
+                                }
+                            }
 
-                                        if filtered_trades.is_empty() {
-                                            rsx! {
-                                                p { style: format!("color: {};", COLOR_LIGHT_GREY), "No {base_asset} trades yet" }
-                                            }
-                                        } else {
-                                            rsx! {
-                                                div { style: "overflow-x: auto;",
-                                                    table { style: "width: 100%; border-collapse: collapse;",
-                                                        thead {
-                                                            tr { style: "border-bottom: 2px solid #ddd;",
-                                                                th { style: format!("padding: 10px; text-align: left; color: {};", COLOR_DARK_GREY), "Side" }
-                                                                th { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "Quantity" }
-                                                                th { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "Price" }
-                                                                th { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "Total" }
-                                                                th { style: format!("padding: 10px; text-align: center; color: {};", COLOR_DARK_GREY), "Source" }
-                                                                th { style: format!("padding: 10px; text-align: left; color: {};", COLOR_DARK_GREY), "Time" }
-                                                            }
-                                                        }
-                                                        tbody {
-                                                            for trade in filtered_trades.iter().rev().take(10) {
-                                                                tr { style: "border-bottom: 1px solid #eee;",
-                                                                    td {
-                                                                        style: if matches!(trade.side, TradeSide::Buy) {
-                                                                            format!("padding: 10px; color: {}; font-weight: bold;", COLOR_GREEN)
-                                                                        } else {
-                                                                            format!("padding: 10px; color: {}; font-weight: bold;", COLOR_RED)
-                                                                        },
-                                                                        "{trade.side:?}"
-                                                                    }
-                                                                    td { style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY), "{trade.quantity:.8}" }
-                                                                    // Price column - show in quote asset terms
-                                                                    td {
-                                                                        style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY),
-                                                                        {
-                                                                            if trade.quote_asset == "USD" {
-                                                                                format!("${:.2}", trade.price)
-                                                                            } else {
-                                                                                format!("{:.4} {}", trade.price, trade.quote_asset)
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                    // Total column - show in quote asset terms
-                                                                    td {
-                                                                        style: format!("padding: 10px; text-align: right; color: {};", COLOR_DARK_GREY),
-                                                                        {
-                                                                            let total = trade.price * trade.quantity;
-                                                                            if trade.quote_asset == "USD" {
-                                                                                format!("${:.2}", total)
-                                                                            } else {
-                                                                                format!("{:.4} {}", total, trade.quote_asset)
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                    // Source column - show bot icon if executed by bot
-                                                                    td {
-                                                                        style: format!("padding: 10px; text-align: center; color: {};", COLOR_DARK_GREY),
-                                                                        {
-                                                                            if let Some(bot_name) = &trade.executed_by_bot {
-                                                                                format!("🤖 {}", bot_name)
-                                                                            } else {
-                                                                                "Manual".to_string()
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                    td { style: format!("padding: 10px; color: {};", COLOR_DARK_GREY), "{format_timestamp(&trade.timestamp)}" }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                                if filtered_trades.len() > 10 {
-                                                    p { style: format!("margin-top: 10px; color: {}; font-size: 14px;", COLOR_LIGHT_GREY),
-                                                        "Showing last 10 of {filtered_trades.len()} {base_asset} trades"
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
+                            // Delete account
+                            div {
+                                style: format!("background: {}; padding: 25px; border-radius: 8px; margin-bottom: 25px; box-shadow: 0 2px 8px rgba(0,0,0,0.1); border: 1px solid {};", COLOR_CONTENT_BG, COLOR_RED),
+                                h2 { style: format!("margin-top: 0; font-family: {}; color: {};", FONT_HEADER, COLOR_RED), "Delete Account" }
+                                p { style: format!("margin-top: 0; font-size: 14px; color: {};", COLOR_DARK_GREY),
+                                    "Stops your bot and permanently deletes your balances, trades, orders, alerts and settings. This cannot be undone."
+                                }
+                                input {
+                                    r#type: "password",
+                                    value: "{delete_password}",
+                                    oninput: move |e| delete_password.set(e.value()),
+                                    style: format!("width: 100%; max-width: 360px; display: block; padding: 10px; margin-bottom: 10px; font-size: 14px; border: 1px solid var(--color-border); border-radius: 4px; font-family: {}; box-sizing: border-box;", FONT_BODY),
+                                    placeholder: "Password (leave empty if you sign in with GitHub or Google)"
+                                }
+                                button {
+                                    onclick: move |_| delete_account(),
+                                    style: format!("padding: 10px 20px; background: {}; color: white; border: none; border-radius: 4px; font-size: 14px; font-weight: 600; cursor: pointer; font-family: {};", COLOR_RED, FONT_BODY),
+                                    "Delete My Account"
+                                }
+                                if !delete_status().is_empty() {
+                                    p { style: format!("margin: 10px 0 0 0; font-size: 14px; color: {};", COLOR_RED), "{delete_status}" }
                                 }
                             }
                         }
@@ -3159,7 +6631,7 @@ fn App() -> Element {
             if !matches!(current_view(), AppView::Auth) {
                 StatusBar {
                     username: username(),
-                    bot_status: bot_status()
+                    running_bots: running_bots()
                 }
             }
         }